@@ -28,7 +28,8 @@ use async_trait::async_trait;
 use bitcrypto::{keccak256, ripemd160, sha256};
 use common::custom_futures::repeatable::{Ready, Retry, RetryOnError};
 use common::custom_futures::timeout::FutureTimerExt;
-use common::executor::{abortable_queue::AbortableQueue, AbortableSystem, AbortedError, Timer};
+use common::executor::{abortable_queue::AbortableQueue, AbortSettings, AbortableSystem, AbortedError, SpawnAbortable,
+                       Timer};
 use common::log::{debug, error, info, warn};
 use common::number_type_casting::SafeTypeCastingNumbers;
 use common::{get_utc_timestamp, now_sec, small_rng, DEX_FEE_ADDR_RAW_PUBKEY};
@@ -39,10 +40,10 @@ use crypto::{CryptoCtx, CryptoCtxError, GlobalHDAccountArc, KeyPairPolicy};
 use derive_more::Display;
 use enum_from::EnumFromStringify;
 use ethabi::{Contract, Function, Token};
-pub use ethcore_transaction::SignedTransaction as SignedEthTx;
+pub use ethcore_transaction::SignedTransaction as SignedLegacyEthTx;
 use ethcore_transaction::{Action, Transaction as UnSignedEthTx, UnverifiedTransaction};
 use ethereum_types::{Address, H160, H256, U256};
-use ethkey::{public_to_address, KeyPair, Public, Signature};
+use ethkey::{public_to_address, KeyPair, Public, Secret, Signature};
 use ethkey::{sign, verify_address};
 use futures::compat::Future01CompatExt;
 use futures::future::{join_all, select_ok, try_join_all, Either, FutureExt, TryFutureExt};
@@ -63,15 +64,17 @@ use sha3::{Digest, Keccak256};
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::ops::Deref;
-#[cfg(not(target_arch = "wasm32"))] use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::{Arc, Mutex};
 use url::Url;
-use web3::types::{Action as TraceAction, BlockId, BlockNumber, Bytes, CallRequest, FilterBuilder, Log, Trace,
+use web3::types::{Action as TraceAction, BlockId, BlockNumber, Bytes, CallRequest, FilterBuilder, Log,
                   TraceFilterBuilder, Transaction as Web3Transaction, TransactionId, U64};
 use web3::{self, Web3};
-use web3_transport::{http_transport::HttpTransportNode, EthFeeHistoryNamespace, Web3Transport};
+use web3_transport::node_health::DEFAULT_MAX_BLOCK_LAG;
+pub use web3_transport::node_health::{QuarantineReason, Web3NodeStatus};
+use web3_transport::{http_transport::{nodes_health_check_loop, HttpTransportNode},
+                     EthFeeHistoryNamespace, Web3Transport};
 
 cfg_wasm32! {
     use crypto::MetamaskArc;
@@ -82,24 +85,24 @@ cfg_wasm32! {
 
 use super::watcher_common::{validate_watcher_reward, REWARD_GAS_AMOUNT};
 use super::{coin_conf, lp_coinfind_or_err, AsyncMutex, BalanceError, BalanceFut, CheckIfMyPaymentSentArgs,
-            CoinBalance, CoinFutSpawner, CoinProtocol, CoinTransportMetrics, CoinsContext, ConfirmPaymentInput,
-            EthValidateFeeArgs, FeeApproxStage, FoundSwapTxSpend, HistorySyncState, IguanaPrivKey, MakerSwapTakerCoin,
-            MarketCoinOps, MmCoin, MmCoinEnum, MyAddressError, MyWalletAddress, NegotiateSwapContractAddrErr,
-            NumConversError, NumConversResult, PaymentInstructionArgs, PaymentInstructions, PaymentInstructionsErr,
-            PrivKeyBuildPolicy, PrivKeyPolicyNotAllowed, RawTransactionError, RawTransactionFut,
-            RawTransactionRequest, RawTransactionRes, RawTransactionResult, RefundError, RefundPaymentArgs,
-            RefundResult, RewardTarget, RpcClientType, RpcTransportEventHandler, RpcTransportEventHandlerShared,
-            SearchForSwapTxSpendInput, SendMakerPaymentSpendPreimageInput, SendPaymentArgs, SignatureError,
-            SignatureResult, SpendPaymentArgs, SwapOps, TakerSwapMakerCoin, TradeFee, TradePreimageError,
-            TradePreimageFut, TradePreimageResult, TradePreimageValue, Transaction, TransactionDetails,
-            TransactionEnum, TransactionErr, TransactionFut, TransactionType, TxMarshalingErr,
+            CoinBalance, CoinFutSpawner, CoinProtocol, CoinTransportMetrics, ConfirmPaymentInput, DerivationMethod,
+            EthValidateFeeArgs, FeeApproxStage, FoundSwapTxSpend, GetWithdrawSenderAddress, HistorySyncState,
+            IguanaPrivKey, MakerSwapTakerCoin, MarketCoinOps, MmCoin, MmCoinEnum, MyAddressError, MyWalletAddress,
+            NegotiateSwapContractAddrErr, NumConversError, NumConversResult, PaymentInstructionArgs,
+            PaymentInstructions, PaymentInstructionsErr, PrivKeyBuildPolicy, PrivKeyPolicyNotAllowed,
+            RawTransactionError, RawTransactionFut, RawTransactionRequest, RawTransactionRes, RawTransactionResult,
+            RefundError, RefundPaymentArgs, RefundResult, RewardTarget, RpcClientType, RpcTransportEventHandler,
+            RpcTransportEventHandlerShared, SearchForSwapTxSpendInput, SendMakerPaymentSpendPreimageInput,
+            SendPaymentArgs, SignatureError, SignatureResult, SpendPaymentArgs, SwapOps, TakerSwapMakerCoin, TradeFee,
+            TradePreimageError, TradePreimageFut, TradePreimageResult, TradePreimageValue, Transaction,
+            TransactionDetails, TransactionEnum, TransactionErr, TransactionFut, TransactionType, TxMarshalingErr,
             UnexpectedDerivationMethod, ValidateAddressResult, ValidateFeeArgs, ValidateInstructionsErr,
             ValidateOtherPubKeyErr, ValidatePaymentError, ValidatePaymentFut, ValidatePaymentInput, VerificationError,
             VerificationResult, WaitForHTLCTxSpendArgs, WatcherOps, WatcherReward, WatcherRewardError,
             WatcherSearchForSwapTxSpendInput, WatcherValidatePaymentInput, WatcherValidateTakerFeeInput,
-            WithdrawError, WithdrawFee, WithdrawFut, WithdrawRequest, WithdrawResult, EARLY_CONFIRMATION_ERR_LOG,
-            INVALID_CONTRACT_ADDRESS_ERR_LOG, INVALID_PAYMENT_STATE_ERR_LOG, INVALID_RECEIVER_ERR_LOG,
-            INVALID_SENDER_ERR_LOG, INVALID_SWAP_ID_ERR_LOG};
+            WithdrawError, WithdrawFee, WithdrawFut, WithdrawRequest, WithdrawResult, WithdrawSenderAddress,
+            EARLY_CONFIRMATION_ERR_LOG, INVALID_CONTRACT_ADDRESS_ERR_LOG, INVALID_PAYMENT_STATE_ERR_LOG,
+            INVALID_RECEIVER_ERR_LOG, INVALID_SENDER_ERR_LOG, INVALID_SWAP_ID_ERR_LOG};
 pub use rlp;
 
 #[cfg(test)] mod eth_tests;
@@ -113,6 +116,23 @@ use v2_activation::{build_address_and_priv_key_policy, EthActivationV2Error};
 mod nonce;
 use nonce::ParityNonce;
 
+mod eip1559;
+use eip1559::{Eip1559Transaction, FeePerGasEstimated, EIP1559_TX_TYPE, FEE_HISTORY_BLOCKS, PRIORITY_FEE_PERCENTILE};
+pub use eip1559::{SignedEip1559Transaction, VerifiedEip1559Transaction};
+
+pub mod contract_call;
+
+pub mod erc20_permit;
+use erc20_permit::PERMIT_GAS;
+
+pub mod eth_hd_wallet;
+use eth_hd_wallet::{ChecksumAddress, EthDerivationMethod};
+
+pub mod eth_tx_history_v2;
+pub mod name_resolution;
+pub mod pending_tx;
+pub mod typed_data;
+
 /// https://github.com/artemii235/etomic-swap/blob/master/contracts/EtomicSwap.sol
 /// Dev chain (195.201.137.5:8565) contract address: 0x83965C539899cC0F918552e5A26915de40ee8852
 /// Ropsten: https://ropsten.etherscan.io/address/0x7bc1bbdd6a0a722fc9bffc49c921b685ecb84b94
@@ -120,6 +140,11 @@ use nonce::ParityNonce;
 const SWAP_CONTRACT_ABI: &str = include_str!("eth/swap_contract_abi.json");
 /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-20.md
 const ERC20_ABI: &str = include_str!("eth/erc20_abi.json");
+/// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-2612.md
+const ERC20_PERMIT_ABI: &str = include_str!("eth/erc20_permit_abi.json");
+/// The `resolver` function of the ENS registry and the `addr` function of the resolver.
+/// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-137.md
+const ENS_ABI: &str = include_str!("eth/ens_abi.json");
 /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-721.md
 const ERC721_ABI: &str = include_str!("eth/erc721_abi.json");
 /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1155.md
@@ -164,6 +189,8 @@ const GUI_AUTH_SIGNED_MESSAGE_LIFETIME_SEC: i64 = 90;
 lazy_static! {
     pub static ref SWAP_CONTRACT: Contract = Contract::load(SWAP_CONTRACT_ABI.as_bytes()).unwrap();
     pub static ref ERC20_CONTRACT: Contract = Contract::load(ERC20_ABI.as_bytes()).unwrap();
+    pub static ref ERC20_PERMIT_CONTRACT: Contract = Contract::load(ERC20_PERMIT_ABI.as_bytes()).unwrap();
+    pub static ref ENS_CONTRACT: Contract = Contract::load(ENS_ABI.as_bytes()).unwrap();
     pub static ref ERC721_CONTRACT: Contract = Contract::load(ERC721_ABI.as_bytes()).unwrap();
     pub static ref ERC1155_CONTRACT: Contract = Contract::load(ERC1155_ABI.as_bytes()).unwrap();
 }
@@ -171,7 +198,62 @@ lazy_static! {
 pub type Web3RpcFut<T> = Box<dyn Future<Item = T, Error = MmError<Web3RpcError>> + Send>;
 pub type Web3RpcResult<T> = Result<T, MmError<Web3RpcError>>;
 pub type GasStationResult = Result<GasStationData, MmError<GasStationReqErr>>;
-type GasDetails = (U256, U256);
+type GasDetails = (U256, PayForGasOption);
+
+/// How a transaction pays for the gas: the legacy gas price or the EIP-1559 fees per gas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PayForGasOption {
+    Legacy {
+        gas_price: U256,
+    },
+    Eip1559 {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+}
+
+impl PayForGasOption {
+    /// The highest price per gas the transaction may pay.
+    fn max_fee_per_gas(&self) -> U256 {
+        match self {
+            PayForGasOption::Legacy { gas_price } => *gas_price,
+            PayForGasOption::Eip1559 { max_fee_per_gas, .. } => *max_fee_per_gas,
+        }
+    }
+
+    /// Sets the fees per gas to the `eth_estimateGas` request.
+    fn set_to_call_request(&self, req: &mut CallRequest) {
+        match *self {
+            // gas price must be supplied because some smart contracts base their
+            // logic on gas price, e.g. TUSD: https://github.com/KomodoPlatform/atomicDEX-API/issues/643
+            PayForGasOption::Legacy { gas_price } => req.gas_price = Some(gas_price),
+            PayForGasOption::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                req.transaction_type = Some(U64::from(EIP1559_TX_TYPE));
+                req.max_fee_per_gas = Some(max_fee_per_gas);
+                req.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            },
+        }
+    }
+
+    /// Increases the fees per gas to cover their growth till the transaction is sent.
+    fn increase_by_stage(self, stage: &FeeApproxStage) -> PayForGasOption {
+        match self {
+            PayForGasOption::Legacy { gas_price } => PayForGasOption::Legacy {
+                gas_price: increase_gas_price_by_stage(gas_price, stage),
+            },
+            PayForGasOption::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => PayForGasOption::Eip1559 {
+                max_fee_per_gas: increase_gas_price_by_stage(max_fee_per_gas, stage),
+                max_priority_fee_per_gas,
+            },
+        }
+    }
+}
 
 #[derive(Debug, Display)]
 pub enum GasStationReqErr {
@@ -343,26 +425,6 @@ impl From<Web3RpcError> for BalanceError {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct SavedTraces {
-    /// ETH traces for my_address
-    traces: Vec<Trace>,
-    /// Earliest processed block
-    earliest_block: U64,
-    /// Latest processed block
-    latest_block: U64,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-struct SavedErc20Events {
-    /// ERC20 events for my_address
-    events: Vec<Log>,
-    /// Earliest processed block
-    earliest_block: U64,
-    /// Latest processed block
-    latest_block: U64,
-}
-
 #[derive(Debug, PartialEq, Eq)]
 pub enum EthCoinType {
     /// Ethereum itself or it's forks: ETC/others
@@ -406,6 +468,7 @@ impl TryFrom<PrivKeyBuildPolicy> for EthPrivKeyBuildPolicy {
             PrivKeyBuildPolicy::IguanaPrivKey(iguana) => Ok(EthPrivKeyBuildPolicy::IguanaPrivKey(iguana)),
             PrivKeyBuildPolicy::GlobalHDAccount(global_hd) => Ok(EthPrivKeyBuildPolicy::GlobalHDAccount(global_hd)),
             PrivKeyBuildPolicy::Trezor => Err(PrivKeyPolicyNotAllowed::HardwareWalletNotSupported),
+            PrivKeyBuildPolicy::WatchOnly(_) => Err(PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported),
         }
     }
 }
@@ -445,10 +508,16 @@ pub struct EthCoinImpl {
     pub coin_type: EthCoinType,
     priv_key_policy: EthPrivKeyPolicy,
     my_address: Address,
+    /// [`DerivationMethod::HDWallet`] if the coin is activated with a BIP39 seed phrase,
+    /// otherwise [`DerivationMethod::SingleAddress`] with [`EthCoinImpl::my_address`].
+    derivation_method: Arc<EthDerivationMethod>,
     sign_message_prefix: Option<String>,
     swap_contract_address: Address,
     fallback_swap_contract: Option<Address>,
     contract_supports_watchers: bool,
+    /// Whether the swap contract has the `erc20PaymentWithPermit` entry point,
+    /// so the ERC20 payment is sent with an ERC-2612 permit instead of a separate `approve` transaction.
+    contract_supports_permit: bool,
     web3: Web3<Web3Transport>,
     /// The separate web3 instances kept to get nonce, will replace the web3 completely soon
     web3_instances: Vec<Web3Instance>,
@@ -462,9 +531,17 @@ pub struct EthCoinImpl {
     /// Using a weak reference by default in order to avoid circular references and leaks.
     pub ctx: MmWeak,
     chain_id: Option<u64>,
+    /// The ENS (or ENS-compatible) registry used to resolve names passed as the withdraw destination.
+    name_registry: Option<Address>,
     /// the block range used for eth_getLogs
     logs_block_range: u64,
     nonce_lock: Arc<AsyncMutex<()>>,
+    /// Whether an ERC20 swap payment approves the swap contract to spend the payment amount only
+    /// instead of an unlimited allowance. Set by the `exact_swap_approvals` coin config option.
+    exact_swap_approvals: bool,
+    /// Whether the swap transactions are sent as EIP-1559 ones. Set by the `swap_txs_eip1559` coin config option.
+    /// It's disabled by default, since the peers running the older versions can't decode EIP-1559 swap transactions.
+    swap_txs_eip1559: bool,
     erc20_tokens_infos: Arc<Mutex<HashMap<String, Erc20TokenInfo>>>,
     /// This spawner is used to spawn coin's related futures that should be aborted on coin deactivation
     /// and on [`MmArc::stop`].
@@ -543,122 +620,6 @@ impl EthCoinImpl {
         )
     }
 
-    /// Gets ETH traces from ETH node between addresses in `from_block` and `to_block`
-    fn eth_traces(
-        &self,
-        from_addr: Vec<Address>,
-        to_addr: Vec<Address>,
-        from_block: BlockNumber,
-        to_block: BlockNumber,
-        limit: Option<usize>,
-    ) -> Box<dyn Future<Item = Vec<Trace>, Error = String> + Send> {
-        let mut filter = TraceFilterBuilder::default()
-            .from_address(from_addr)
-            .to_address(to_addr)
-            .from_block(from_block)
-            .to_block(to_block);
-
-        if let Some(l) = limit {
-            filter = filter.count(l);
-        }
-
-        Box::new(
-            self.web3
-                .trace()
-                .filter(filter.build())
-                .compat()
-                .map_err(|e| ERRL!("{}", e)),
-        )
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    fn eth_traces_path(&self, ctx: &MmArc) -> PathBuf {
-        ctx.dbdir()
-            .join("TRANSACTIONS")
-            .join(format!("{}_{:#02x}_trace.json", self.ticker, self.my_address))
-    }
-
-    /// Load saved ETH traces from local DB
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load_saved_traces(&self, ctx: &MmArc) -> Option<SavedTraces> {
-        let content = gstuff::slurp(&self.eth_traces_path(ctx));
-        if content.is_empty() {
-            None
-        } else {
-            match json::from_slice(&content) {
-                Ok(t) => Some(t),
-                Err(_) => None,
-            }
-        }
-    }
-
-    /// Load saved ETH traces from local DB
-    #[cfg(target_arch = "wasm32")]
-    fn load_saved_traces(&self, _ctx: &MmArc) -> Option<SavedTraces> {
-        common::panic_w("'load_saved_traces' is not implemented in WASM");
-        unreachable!()
-    }
-
-    /// Store ETH traces to local DB
-    #[cfg(not(target_arch = "wasm32"))]
-    fn store_eth_traces(&self, ctx: &MmArc, traces: &SavedTraces) {
-        let content = json::to_vec(traces).unwrap();
-        let tmp_file = format!("{}.tmp", self.eth_traces_path(ctx).display());
-        std::fs::write(&tmp_file, content).unwrap();
-        std::fs::rename(tmp_file, self.eth_traces_path(ctx)).unwrap();
-    }
-
-    /// Store ETH traces to local DB
-    #[cfg(target_arch = "wasm32")]
-    fn store_eth_traces(&self, _ctx: &MmArc, _traces: &SavedTraces) {
-        common::panic_w("'store_eth_traces' is not implemented in WASM");
-        unreachable!()
-    }
-
-    #[cfg(not(target_arch = "wasm32"))]
-    fn erc20_events_path(&self, ctx: &MmArc) -> PathBuf {
-        ctx.dbdir()
-            .join("TRANSACTIONS")
-            .join(format!("{}_{:#02x}_events.json", self.ticker, self.my_address))
-    }
-
-    /// Store ERC20 events to local DB
-    #[cfg(not(target_arch = "wasm32"))]
-    fn store_erc20_events(&self, ctx: &MmArc, events: &SavedErc20Events) {
-        let content = json::to_vec(events).unwrap();
-        let tmp_file = format!("{}.tmp", self.erc20_events_path(ctx).display());
-        std::fs::write(&tmp_file, content).unwrap();
-        std::fs::rename(tmp_file, self.erc20_events_path(ctx)).unwrap();
-    }
-
-    /// Store ERC20 events to local DB
-    #[cfg(target_arch = "wasm32")]
-    fn store_erc20_events(&self, _ctx: &MmArc, _events: &SavedErc20Events) {
-        common::panic_w("'store_erc20_events' is not implemented in WASM");
-        unreachable!()
-    }
-
-    /// Load saved ERC20 events from local DB
-    #[cfg(not(target_arch = "wasm32"))]
-    fn load_saved_erc20_events(&self, ctx: &MmArc) -> Option<SavedErc20Events> {
-        let content = gstuff::slurp(&self.erc20_events_path(ctx));
-        if content.is_empty() {
-            None
-        } else {
-            match json::from_slice(&content) {
-                Ok(t) => Some(t),
-                Err(_) => None,
-            }
-        }
-    }
-
-    /// Load saved ERC20 events from local DB
-    #[cfg(target_arch = "wasm32")]
-    fn load_saved_erc20_events(&self, _ctx: &MmArc) -> Option<SavedErc20Events> {
-        common::panic_w("'load_saved_erc20_events' is not implemented in WASM");
-        unreachable!()
-    }
-
     /// The id used to differentiate payments on Etomic swap smart contract
     fn etomic_swap_id(&self, time_lock: u32, secret_hash: &[u8]) -> Vec<u8> {
         let mut input = vec![];
@@ -690,6 +651,12 @@ impl EthCoinImpl {
         Ok(try_s!(valid_addr_from_str(address)))
     }
 
+    /// Returns the health of the coin's web3 nodes, or `None` if the coin is connected via MetaMask.
+    /// Checks the nodes first if `refresh` is set.
+    pub async fn nodes_status(&self, refresh: bool) -> Option<Vec<Web3NodeStatus>> {
+        self.web3.transport().nodes_status(refresh).await
+    }
+
     pub fn erc20_token_address(&self) -> Option<Address> {
         match self.coin_type {
             EthCoinType::Erc20 { token_addr, .. } => Some(token_addr),
@@ -726,17 +693,87 @@ async fn get_tx_hex_by_hash_impl(coin: EthCoin, tx_hash: H256) -> RawTransaction
         .transaction(TransactionId::Hash(tx_hash))
         .await?
         .or_mm_err(|| RawTransactionError::HashNotExist(tx_hash.to_string()))?;
-    let raw = signed_tx_from_web3_tx(web3_tx).map_to_mm(RawTransactionError::InternalError)?;
+    let raw = signed_tx_from_web3_tx(web3_tx, coin.chain_id).map_to_mm(RawTransactionError::InternalError)?;
     Ok(RawTransactionRes {
-        tx_hex: BytesJson(rlp::encode(&raw).to_vec()),
+        tx_hex: BytesJson(raw.tx_hex()),
     })
 }
 
+/// Signs a transaction as an EIP-1559 one or as a legacy one depending on `pay_for_gas_option`.
+#[allow(clippy::too_many_arguments)]
+fn sign_eth_transaction(
+    coin: &EthCoin,
+    secret: &Secret,
+    nonce: U256,
+    gas: U256,
+    pay_for_gas_option: PayForGasOption,
+    action: Action,
+    value: U256,
+    data: Vec<u8>,
+) -> Result<SignedEthTx, String> {
+    match pay_for_gas_option {
+        PayForGasOption::Legacy { gas_price } => {
+            let tx = UnSignedEthTx {
+                nonce,
+                value,
+                action,
+                data,
+                gas,
+                gas_price,
+            };
+            Ok(SignedEthTx::Legacy(tx.sign(secret, coin.chain_id)))
+        },
+        PayForGasOption::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            let chain_id = coin
+                .chain_id
+                .ok_or_else(|| ERRL!("'chain_id' is required to sign an EIP-1559 transaction"))?;
+            let tx = Eip1559Transaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas,
+                action,
+                value,
+                data,
+                access_list: Vec::new(),
+            };
+            let signed = try_s!(tx.sign(secret));
+            Ok(SignedEthTx::Eip1559(try_s!(VerifiedEip1559Transaction::new(signed))))
+        },
+    }
+}
+
+/// Signs a transaction calling `call_addr` as an EIP-1559 one or as a legacy one depending on `pay_for_gas_option`.
+/// Returns the transaction hash and the signed transaction bytes.
+#[allow(clippy::too_many_arguments)]
+fn sign_transaction_with_pay_for_gas(
+    coin: &EthCoin,
+    secret: &Secret,
+    nonce: U256,
+    gas: U256,
+    pay_for_gas_option: PayForGasOption,
+    call_addr: Address,
+    value: U256,
+    data: Vec<u8>,
+) -> MmResult<(H256, BytesJson), WithdrawError> {
+    let action = Action::Call(call_addr);
+    let signed = sign_eth_transaction(coin, secret, nonce, gas, pay_for_gas_option, action, value, data)
+        .map_to_mm(WithdrawError::InternalError)?;
+    Ok((signed.hash(), BytesJson::from(signed.tx_hex())))
+}
+
 async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
-    let to_addr = coin
-        .address_from_str(&req.to)
-        .map_to_mm(WithdrawError::InvalidAddress)?;
-    let my_balance = coin.my_balance().compat().await?;
+    let (to_addr, to_name) = coin.resolve_withdraw_to(&req.to).await?;
+    let WithdrawSenderAddress {
+        address: ChecksumAddress(my_address),
+        derivation_path,
+        ..
+    } = coin.get_withdraw_sender_address(&req).await?;
+    let my_balance = coin.address_balance(my_address).compat().await?;
     let my_balance_dec = u256_to_big_decimal(my_balance, coin.decimals)?;
 
     let (mut wei_amount, dec_amount) = if req.max {
@@ -762,9 +799,17 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
     };
     let eth_value_dec = u256_to_big_decimal(eth_value, coin.decimals)?;
 
-    let (gas, gas_price) =
-        get_eth_gas_details(&coin, req.fee, eth_value, data.clone().into(), call_addr, req.max).await?;
-    let total_fee = gas * gas_price;
+    let (gas, pay_for_gas_option) = get_eth_gas_details(
+        &coin,
+        my_address,
+        req.fee,
+        eth_value,
+        data.clone().into(),
+        call_addr,
+        req.max,
+    )
+    .await?;
+    let total_fee = gas * pay_for_gas_option.max_fee_per_gas();
     let total_fee_dec = u256_to_big_decimal(total_fee, coin.decimals)?;
 
     if req.max && coin.coin_type == EthCoinType::Eth {
@@ -779,27 +824,32 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
     };
 
     let (tx_hash, tx_hex) = match coin.priv_key_policy {
-        EthPrivKeyPolicy::KeyPair(ref key_pair) => {
+        EthPrivKeyPolicy::KeyPair(ref activated_key_pair) => {
+            let key_pair = match derivation_path {
+                Some(ref derivation_path) => coin
+                    .derivation_method
+                    .hd_wallet_or_err()?
+                    .derive_key_pair(derivation_path)?,
+                None => activated_key_pair.clone(),
+            };
+
             let _nonce_lock = coin.nonce_lock.lock().await;
-            let (nonce, _) = get_addr_nonce(coin.my_address, coin.web3_instances.clone())
+            let (nonce, _) = get_addr_nonce(my_address, coin.web3_instances.clone())
                 .compat()
                 .timeout_secs(30.)
                 .await?
                 .map_to_mm(WithdrawError::Transport)?;
 
-            let tx = UnSignedEthTx {
+            sign_transaction_with_pay_for_gas(
+                &coin,
+                key_pair.secret(),
                 nonce,
-                value: eth_value,
-                action: Action::Call(call_addr),
-                data,
                 gas,
-                gas_price,
-            };
-
-            let signed = tx.sign(key_pair.secret(), coin.chain_id);
-            let bytes = rlp::encode(&signed);
-
-            (signed.hash, BytesJson::from(bytes.to_vec()))
+                pay_for_gas_option,
+                call_addr,
+                eth_value,
+                data,
+            )?
         },
         #[cfg(target_arch = "wasm32")]
         EthPrivKeyPolicy::Metamask(_) => {
@@ -808,16 +858,26 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
                 return MmError::err(WithdrawError::BroadcastExpected(error));
             }
 
-            let tx_to_send = TransactionRequest {
-                from: coin.my_address,
+            let mut tx_to_send = TransactionRequest {
+                from: my_address,
                 to: Some(to_addr),
                 gas: Some(gas),
-                gas_price: Some(gas_price),
                 value: Some(eth_value),
                 data: Some(data.clone().into()),
                 nonce: None,
                 ..TransactionRequest::default()
             };
+            match pay_for_gas_option {
+                PayForGasOption::Legacy { gas_price } => tx_to_send.gas_price = Some(gas_price),
+                PayForGasOption::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                } => {
+                    tx_to_send.transaction_type = Some(U64::from(EIP1559_TX_TYPE));
+                    tx_to_send.max_fee_per_gas = Some(max_fee_per_gas);
+                    tx_to_send.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+                },
+            }
 
             // Wait for 10 seconds for the transaction to appear on the RPC node.
             let wait_rpc_timeout = 10_000;
@@ -831,7 +891,7 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
                 .wait_for_tx_appears_on_rpc(tx_hash, wait_rpc_timeout, check_every)
                 .await?;
             let tx_hex = signed_tx
-                .map(|tx| BytesJson::from(rlp::encode(&tx).to_vec()))
+                .map(|tx| BytesJson::from(tx.tx_hex()))
                 // Return an empty `tx_hex` if the transaction is still not appeared on the RPC node.
                 .unwrap_or_default();
             (tx_hash, tx_hex)
@@ -843,19 +903,18 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
 
     let amount_decimal = u256_to_big_decimal(wei_amount, coin.decimals)?;
     let mut spent_by_me = amount_decimal.clone();
-    let received_by_me = if to_addr == coin.my_address {
+    let received_by_me = if to_addr == my_address {
         amount_decimal.clone()
     } else {
         0.into()
     };
-    let fee_details = EthTxFeeDetails::new(gas, gas_price, fee_coin)?;
+    let fee_details = EthTxFeeDetails::new(gas, pay_for_gas_option, fee_coin)?;
     if coin.coin_type == EthCoinType::Eth {
         spent_by_me += &fee_details.total_fee;
     }
-    let my_address = coin.my_address()?;
     Ok(TransactionDetails {
         to: vec![checksum_address(&format!("{:#02x}", to_addr))],
-        from: vec![my_address],
+        from: vec![ChecksumAddress(my_address).to_string()],
         total_amount: amount_decimal,
         my_balance_change: &received_by_me - &spent_by_me,
         spent_by_me,
@@ -870,6 +929,8 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name,
     })
 }
 
@@ -877,8 +938,8 @@ async fn withdraw_impl(coin: EthCoin, req: WithdrawRequest) -> WithdrawResult {
 /// which should be sent to`send_raw_transaction` RPC to broadcast the transaction.
 pub async fn withdraw_erc1155(ctx: MmArc, withdraw_type: WithdrawErc1155, url: Url) -> WithdrawNftResult {
     let coin = lp_coinfind_or_err(&ctx, &withdraw_type.chain.to_ticker()).await?;
-    let (to_addr, token_addr, eth_coin) =
-        get_valid_nft_add_to_withdraw(coin, &withdraw_type.to, &withdraw_type.token_address)?;
+    let (token_addr, eth_coin) = get_valid_nft_add_to_withdraw(coin, &withdraw_type.token_address)?;
+    let (to_addr, to_name) = eth_coin.resolve_withdraw_to(&withdraw_type.to).await?;
     let my_address = eth_coin.my_address()?;
 
     // todo check amount in nft cache, instead of sending new moralis req
@@ -934,8 +995,9 @@ pub async fn withdraw_erc1155(ctx: MmArc, withdraw_type: WithdrawErc1155, url: U
             ))
         },
     };
-    let (gas, gas_price) = get_eth_gas_details(
+    let (gas, pay_for_gas_option) = get_eth_gas_details(
         &eth_coin,
+        eth_coin.my_address,
         withdraw_type.fee,
         eth_value,
         data.clone().into(),
@@ -950,25 +1012,25 @@ pub async fn withdraw_erc1155(ctx: MmArc, withdraw_type: WithdrawErc1155, url: U
         .await?
         .map_to_mm(WithdrawError::Transport)?;
 
-    let tx = UnSignedEthTx {
+    let secret = eth_coin.priv_key_policy.key_pair_or_err()?.secret();
+    let (tx_hash, tx_hex) = sign_transaction_with_pay_for_gas(
+        &eth_coin,
+        secret,
         nonce,
-        value: eth_value,
-        action: Action::Call(call_addr),
-        data,
         gas,
-        gas_price,
-    };
-
-    let secret = eth_coin.priv_key_policy.key_pair_or_err()?.secret();
-    let signed = tx.sign(secret, eth_coin.chain_id);
-    let signed_bytes = rlp::encode(&signed);
-    let fee_details = EthTxFeeDetails::new(gas, gas_price, fee_coin)?;
+        pay_for_gas_option,
+        call_addr,
+        eth_value,
+        data,
+    )?;
+    let fee_details = EthTxFeeDetails::new(gas, pay_for_gas_option, fee_coin)?;
 
     Ok(TransactionNftDetails {
-        tx_hex: BytesJson::from(signed_bytes.to_vec()),
-        tx_hash: format!("{:02x}", signed.tx_hash()),
+        tx_hex,
+        tx_hash: format!("{:02x}", BytesJson::from(tx_hash.0.to_vec())),
         from: vec![my_address],
-        to: vec![withdraw_type.to],
+        to: vec![checksum_address(&format!("{:#02x}", to_addr))],
+        to_name,
         contract_type: ContractType::Erc1155,
         token_address: withdraw_type.token_address,
         token_id: withdraw_type.token_id,
@@ -986,8 +1048,8 @@ pub async fn withdraw_erc1155(ctx: MmArc, withdraw_type: WithdrawErc1155, url: U
 /// which should be sent to`send_raw_transaction` RPC to broadcast the transaction.
 pub async fn withdraw_erc721(ctx: MmArc, withdraw_type: WithdrawErc721) -> WithdrawNftResult {
     let coin = lp_coinfind_or_err(&ctx, &withdraw_type.chain.to_ticker()).await?;
-    let (to_addr, token_addr, eth_coin) =
-        get_valid_nft_add_to_withdraw(coin, &withdraw_type.to, &withdraw_type.token_address)?;
+    let (token_addr, eth_coin) = get_valid_nft_add_to_withdraw(coin, &withdraw_type.token_address)?;
+    let (to_addr, to_name) = eth_coin.resolve_withdraw_to(&withdraw_type.to).await?;
     let my_address = eth_coin.my_address()?;
 
     let (eth_value, data, call_addr, fee_coin) = match eth_coin.coin_type {
@@ -1009,8 +1071,9 @@ pub async fn withdraw_erc721(ctx: MmArc, withdraw_type: WithdrawErc721) -> Withd
             ))
         },
     };
-    let (gas, gas_price) = get_eth_gas_details(
+    let (gas, pay_for_gas_option) = get_eth_gas_details(
         &eth_coin,
+        eth_coin.my_address,
         withdraw_type.fee,
         eth_value,
         data.clone().into(),
@@ -1025,25 +1088,25 @@ pub async fn withdraw_erc721(ctx: MmArc, withdraw_type: WithdrawErc721) -> Withd
         .await?
         .map_to_mm(WithdrawError::Transport)?;
 
-    let tx = UnSignedEthTx {
+    let secret = eth_coin.priv_key_policy.key_pair_or_err()?.secret();
+    let (tx_hash, tx_hex) = sign_transaction_with_pay_for_gas(
+        &eth_coin,
+        secret,
         nonce,
-        value: eth_value,
-        action: Action::Call(call_addr),
-        data,
         gas,
-        gas_price,
-    };
-
-    let secret = eth_coin.priv_key_policy.key_pair_or_err()?.secret();
-    let signed = tx.sign(secret, eth_coin.chain_id);
-    let signed_bytes = rlp::encode(&signed);
-    let fee_details = EthTxFeeDetails::new(gas, gas_price, fee_coin)?;
+        pay_for_gas_option,
+        call_addr,
+        eth_value,
+        data,
+    )?;
+    let fee_details = EthTxFeeDetails::new(gas, pay_for_gas_option, fee_coin)?;
 
     Ok(TransactionNftDetails {
-        tx_hex: BytesJson::from(signed_bytes.to_vec()),
-        tx_hash: format!("{:02x}", signed.tx_hash()),
+        tx_hex,
+        tx_hash: format!("{:02x}", BytesJson::from(tx_hash.0.to_vec())),
         from: vec![my_address],
-        to: vec![withdraw_type.to],
+        to: vec![checksum_address(&format!("{:#02x}", to_addr))],
+        to_name,
         contract_type: ContractType::Erc721,
         token_address: withdraw_type.token_address,
         token_id: withdraw_type.token_id,
@@ -1123,7 +1186,7 @@ impl SwapOps for EthCoin {
             _ => panic!(),
         };
         validate_fee_impl(self.clone(), EthValidateFeeArgs {
-            fee_tx_hash: &tx.hash,
+            fee_tx_hash: &tx.hash(),
             expected_sender: validate_fee_args.expected_sender,
             fee_addr: validate_fee_args.fee_addr,
             amount: validate_fee_args.amount,
@@ -1191,7 +1254,7 @@ impl SwapOps for EthCoin {
                                 .await
                         );
                         match transaction {
-                            Some(t) => break Ok(Some(try_s!(signed_tx_from_web3_tx(t)).into())),
+                            Some(t) => break Ok(Some(try_s!(signed_tx_from_web3_tx(t, selfi.chain_id)).into())),
                             None => break Ok(None),
                         }
                     },
@@ -1247,14 +1310,14 @@ impl SwapOps for EthCoin {
         spend_tx: &[u8],
         watcher_reward: bool,
     ) -> Result<Vec<u8>, String> {
-        let unverified: UnverifiedTransaction = try_s!(rlp::decode(spend_tx));
+        let spend_tx = try_s!(signed_eth_tx_from_bytes(spend_tx));
         let function_name = get_function_name("receiverSpend", watcher_reward);
         let function = try_s!(SWAP_CONTRACT.function(&function_name));
 
         // Validate contract call; expected to be receiverSpend.
         // https://www.4byte.directory/signatures/?bytes4_signature=02ed292b.
         let expected_signature = function.short_signature();
-        let actual_signature = &unverified.data[0..4];
+        let actual_signature = &spend_tx.data()[0..4];
         if actual_signature != expected_signature {
             return ERR!(
                 "Expected 'receiverSpend' contract call signature: {:?}, found {:?}",
@@ -1263,7 +1326,7 @@ impl SwapOps for EthCoin {
             );
         };
 
-        let tokens = try_s!(decode_contract_call(function, &unverified.data));
+        let tokens = try_s!(decode_contract_call(function, spend_tx.data()));
         if tokens.len() < 3 {
             return ERR!("Invalid arguments in 'receiverSpend' call: {:?}", tokens);
         }
@@ -1426,8 +1489,7 @@ impl WatcherOps for EthCoin {
         _secret_hash: &[u8],
         _swap_unique_data: &[u8],
     ) -> TransactionFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(maker_payment_tx));
-        let signed = try_tx_fus!(SignedEthTx::new(tx));
+        let signed = try_tx_fus!(signed_eth_tx_from_bytes(maker_payment_tx));
         let fut = async move { Ok(TransactionEnum::from(signed)) };
 
         Box::new(fut.boxed().compat())
@@ -1442,8 +1504,7 @@ impl WatcherOps for EthCoin {
         _swap_contract_address: &Option<BytesJson>,
         _swap_unique_data: &[u8],
     ) -> TransactionFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(taker_payment_tx));
-        let signed = try_tx_fus!(SignedEthTx::new(tx));
+        let signed = try_tx_fus!(signed_eth_tx_from_bytes(taker_payment_tx));
         let fut = async move { Ok(TransactionEnum::from(signed)) };
 
         Box::new(fut.boxed().compat())
@@ -1471,10 +1532,8 @@ impl WatcherOps for EthCoin {
     }
 
     fn watcher_validate_taker_payment(&self, input: WatcherValidatePaymentInput) -> ValidatePaymentFut<()> {
-        let unsigned: UnverifiedTransaction = try_f!(rlp::decode(&input.payment_tx));
         let tx =
-            try_f!(SignedEthTx::new(unsigned)
-                .map_to_mm(|err| ValidatePaymentError::TxDeserializationError(err.to_string())));
+            try_f!(signed_eth_tx_from_bytes(&input.payment_tx).map_to_mm(ValidatePaymentError::TxDeserializationError));
         let sender = try_f!(addr_from_raw_pubkey(&input.taker_pub).map_to_mm(ValidatePaymentError::InvalidParameter));
         let receiver = try_f!(addr_from_raw_pubkey(&input.maker_pub).map_to_mm(ValidatePaymentError::InvalidParameter));
 
@@ -1490,7 +1549,7 @@ impl WatcherOps for EthCoin {
         let decimals = self.decimals;
 
         let fut = async move {
-            let tx_from_rpc = selfi.web3.eth().transaction(TransactionId::Hash(tx.hash)).await?;
+            let tx_from_rpc = selfi.web3.eth().transaction(TransactionId::Hash(tx.hash())).await?;
 
             let tx_from_rpc = tx_from_rpc.as_ref().ok_or_else(|| {
                 ValidatePaymentError::TxDoesNotExist(format!("Didn't find provided tx {:?} on ETH node", tx))
@@ -1711,9 +1770,8 @@ impl WatcherOps for EthCoin {
         &self,
         input: WatcherSearchForSwapTxSpendInput<'_>,
     ) -> Result<Option<FoundSwapTxSpend>, String> {
-        let unverified: UnverifiedTransaction = try_s!(rlp::decode(input.tx));
-        let tx = try_s!(SignedEthTx::new(unverified));
-        let swap_contract_address = match tx.action {
+        let tx = try_s!(signed_eth_tx_from_bytes(input.tx));
+        let swap_contract_address = match *tx.action() {
             Call(address) => address,
             Create => return Err(ERRL!("Invalid payment action: the payment action cannot be create")),
         };
@@ -1929,8 +1987,7 @@ impl MarketCoinOps for EthCoin {
         status.status(&[&self.ticker], "Waiting for confirmations…");
         status.deadline(input.wait_until * 1000);
 
-        let unsigned: UnverifiedTransaction = try_fus!(rlp::decode(&input.payment_tx));
-        let tx = try_fus!(SignedEthTx::new(unsigned));
+        let tx = try_fus!(signed_eth_tx_from_bytes(&input.payment_tx));
         let tx_hash = tx.hash();
 
         let required_confirms = U64::from(input.confirmations);
@@ -2002,12 +2059,11 @@ impl MarketCoinOps for EthCoin {
     }
 
     fn wait_for_htlc_tx_spend(&self, args: WaitForHTLCTxSpendArgs<'_>) -> TransactionFut {
-        let unverified: UnverifiedTransaction = try_tx_fus!(rlp::decode(args.tx_bytes));
-        let tx = try_tx_fus!(SignedEthTx::new(unverified));
+        let tx = try_tx_fus!(signed_eth_tx_from_bytes(args.tx_bytes));
 
         let swap_contract_address = match args.swap_contract_address {
             Some(addr) => try_tx_fus!(addr.try_to_address()),
-            None => match tx.action {
+            None => match *tx.action() {
                 Call(address) => address,
                 Create => {
                     return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
@@ -2023,7 +2079,7 @@ impl MarketCoinOps for EthCoin {
         };
 
         let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&func_name));
-        let decoded = try_tx_fus!(decode_contract_call(payment_func, &tx.data));
+        let decoded = try_tx_fus!(decode_payment_call(payment_func, tx.data()));
         let id = match decoded.first() {
             Some(Token::FixedBytes(bytes)) => bytes.clone(),
             invalid_token => {
@@ -2087,7 +2143,10 @@ impl MarketCoinOps for EthCoin {
                             },
                         };
 
-                        return Ok(TransactionEnum::from(try_tx_s!(signed_tx_from_web3_tx(transaction))));
+                        return Ok(TransactionEnum::from(try_tx_s!(signed_tx_from_web3_tx(
+                            transaction,
+                            selfi.chain_id
+                        ))));
                     }
                 }
 
@@ -2130,847 +2189,203 @@ impl MarketCoinOps for EthCoin {
     }
 }
 
-pub fn signed_eth_tx_from_bytes(bytes: &[u8]) -> Result<SignedEthTx, String> {
-    let tx: UnverifiedTransaction = try_s!(rlp::decode(bytes));
-    let signed = try_s!(SignedEthTx::new(tx));
-    Ok(signed)
+/// A signed legacy or EIP-1559 (type-2) transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignedEthTx {
+    Legacy(SignedLegacyEthTx),
+    Eip1559(VerifiedEip1559Transaction),
 }
 
-// We can use a nonce lock shared between tokens using the same platform coin and the platform itself.
-// For example, ETH/USDT-ERC20 should use the same lock, but it will be different for BNB/USDT-BEP20.
-lazy_static! {
-    static ref NONCE_LOCK: Mutex<HashMap<String, Arc<AsyncMutex<()>>>> = Mutex::new(HashMap::new());
+impl From<SignedLegacyEthTx> for SignedEthTx {
+    fn from(tx: SignedLegacyEthTx) -> Self { SignedEthTx::Legacy(tx) }
 }
 
-type EthTxFut = Box<dyn Future<Item = SignedEthTx, Error = TransactionErr> + Send + 'static>;
-
-async fn sign_and_send_transaction_with_keypair(
-    ctx: MmArc,
-    coin: &EthCoin,
-    key_pair: &KeyPair,
-    value: U256,
-    action: Action,
-    data: Vec<u8>,
-    gas: U256,
-) -> Result<SignedEthTx, TransactionErr> {
-    let mut status = ctx.log.status_handle();
-    macro_rules! tags {
-        () => {
-            &[&"sign-and-send"]
-        };
+impl SignedEthTx {
+    pub fn hash(&self) -> H256 {
+        match self {
+            SignedEthTx::Legacy(tx) => tx.hash,
+            SignedEthTx::Eip1559(tx) => tx.hash,
+        }
     }
-    let _nonce_lock = coin.nonce_lock.lock().await;
-    status.status(tags!(), "get_addr_nonce…");
-    let (nonce, web3_instances_with_latest_nonce) = try_tx_s!(
-        get_addr_nonce(coin.my_address, coin.web3_instances.clone())
-            .compat()
-            .await
-    );
-    status.status(tags!(), "get_gas_price…");
-    let gas_price = try_tx_s!(coin.get_gas_price().compat().await);
-
-    let tx = UnSignedEthTx {
-        nonce,
-        gas_price,
-        gas,
-        action,
-        value,
-        data,
-    };
-
-    let signed = tx.sign(key_pair.secret(), coin.chain_id);
-    let bytes = Bytes(rlp::encode(&signed).to_vec());
-    status.status(tags!(), "send_raw_transaction…");
-
-    let futures = web3_instances_with_latest_nonce
-        .into_iter()
-        .map(|web3_instance| web3_instance.web3.eth().send_raw_transaction(bytes.clone()));
-    try_tx_s!(select_ok(futures).await.map_err(|e| ERRL!("{}", e)), signed);
-
-    status.status(tags!(), "get_addr_nonce…");
-    coin.wait_for_addr_nonce_increase(coin.my_address, nonce).await;
-    Ok(signed)
-}
 
-#[cfg(target_arch = "wasm32")]
-async fn sign_and_send_transaction_with_metamask(
-    coin: EthCoin,
-    value: U256,
-    action: Action,
-    data: Vec<u8>,
-    gas: U256,
-) -> Result<SignedEthTx, TransactionErr> {
-    let to = match action {
-        Action::Create => None,
-        Action::Call(to) => Some(to),
-    };
-
-    let gas_price = try_tx_s!(coin.get_gas_price().compat().await);
-
-    let tx_to_send = TransactionRequest {
-        from: coin.my_address,
-        to,
-        gas: Some(gas),
-        gas_price: Some(gas_price),
-        value: Some(value),
-        data: Some(data.clone().into()),
-        nonce: None,
-        ..TransactionRequest::default()
-    };
-
-    // It's important to return the transaction hex for the swap,
-    // so wait up to 60 seconds for the transaction to appear on the RPC node.
-    let wait_rpc_timeout = 60_000;
-    let check_every = 1.;
-
-    // Please note that this method may take a long time
-    // due to `wallet_switchEthereumChain` and `eth_sendTransaction` requests.
-    let tx_hash = try_tx_s!(coin.web3.eth().send_transaction(tx_to_send).await);
-
-    let maybe_signed_tx = try_tx_s!(
-        coin.wait_for_tx_appears_on_rpc(tx_hash, wait_rpc_timeout, check_every)
-            .await
-    );
-    match maybe_signed_tx {
-        Some(signed_tx) => Ok(signed_tx),
-        None => TX_PLAIN_ERR!(
-            "Waited too long until the transaction {:?} appear on the RPC node",
-            tx_hash
-        ),
+    pub fn sender(&self) -> Address {
+        match self {
+            SignedEthTx::Legacy(tx) => tx.sender(),
+            SignedEthTx::Eip1559(tx) => tx.sender,
+        }
     }
-}
-
-impl EthCoin {
-    /// Downloads and saves ETH transaction history of my_address, relies on Parity trace_filter API
-    /// https://wiki.parity.io/JSONRPC-trace-module#trace_filter, this requires tracing to be enabled
-    /// in node config. Other ETH clients (Geth, etc.) are `not` supported (yet).
-    #[allow(clippy::cognitive_complexity)]
-    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
-    async fn process_eth_history(&self, ctx: &MmArc) {
-        // Artem Pikulin: by playing a bit with Parity mainnet node I've discovered that trace_filter API responds after reasonable time for 1000 blocks.
-        // I've tried to increase the amount to 10000, but request times out somewhere near 2500000 block.
-        // Also the Parity RPC server seem to get stuck while request in running (other requests performance is also lowered).
-        let delta = U64::from(1000);
-
-        let mut success_iteration = 0i32;
-        loop {
-            if ctx.is_stopping() {
-                break;
-            };
-            {
-                let coins_ctx = CoinsContext::from_ctx(ctx).unwrap();
-                let coins = coins_ctx.coins.lock().await;
-                if !coins.contains_key(&self.ticker) {
-                    ctx.log.log("", &[&"tx_history", &self.ticker], "Loop stopped");
-                    break;
-                };
-            }
-
-            let current_block = match self.web3.eth().block_number().await {
-                Ok(block) => block,
-                Err(e) => {
-                    ctx.log.log(
-                        "",
-                        &[&"tx_history", &self.ticker],
-                        &ERRL!("Error {} on eth_block_number, retrying", e),
-                    );
-                    Timer::sleep(10.).await;
-                    continue;
-                },
-            };
-
-            let mut saved_traces = match self.load_saved_traces(ctx) {
-                Some(traces) => traces,
-                None => SavedTraces {
-                    traces: vec![],
-                    earliest_block: current_block,
-                    latest_block: current_block,
-                },
-            };
-            *self.history_sync_state.lock().unwrap() = HistorySyncState::InProgress(json!({
-                "blocks_left": saved_traces.earliest_block.as_u64(),
-            }));
-
-            let mut existing_history = match self.load_history_from_file(ctx).compat().await {
-                Ok(history) => history,
-                Err(e) => {
-                    ctx.log.log(
-                        "",
-                        &[&"tx_history", &self.ticker],
-                        &ERRL!("Error {} on 'load_history_from_file', stop the history loop", e),
-                    );
-                    return;
-                },
-            };
-
-            // AP: AFAIK ETH RPC doesn't support conditional filters like `get this OR this` so we have
-            // to run several queries to get trace events including our address as sender `or` receiver
-            // TODO refactor this to batch requests instead of single request per query
-            if saved_traces.earliest_block > 0.into() {
-                let before_earliest = if saved_traces.earliest_block >= delta {
-                    saved_traces.earliest_block - delta
-                } else {
-                    0.into()
-                };
-
-                let from_traces_before_earliest = match self
-                    .eth_traces(
-                        vec![self.my_address],
-                        vec![],
-                        BlockNumber::Number(before_earliest),
-                        BlockNumber::Number(saved_traces.earliest_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(traces) => traces,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on eth_traces, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let to_traces_before_earliest = match self
-                    .eth_traces(
-                        vec![],
-                        vec![self.my_address],
-                        BlockNumber::Number(before_earliest),
-                        BlockNumber::Number(saved_traces.earliest_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(traces) => traces,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on eth_traces, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let total_length = from_traces_before_earliest.len() + to_traces_before_earliest.len();
-                mm_counter!(ctx.metrics, "tx.history.response.total_length", total_length as u64,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "eth_traces");
 
-                saved_traces.traces.extend(from_traces_before_earliest);
-                saved_traces.traces.extend(to_traces_before_earliest);
-                saved_traces.earliest_block = if before_earliest > 0.into() {
-                    // need to exclude the before earliest block from next iteration
-                    before_earliest - 1
-                } else {
-                    0.into()
-                };
-                self.store_eth_traces(ctx, &saved_traces);
-            }
-
-            if current_block > saved_traces.latest_block {
-                let from_traces_after_latest = match self
-                    .eth_traces(
-                        vec![self.my_address],
-                        vec![],
-                        BlockNumber::Number(saved_traces.latest_block + 1),
-                        BlockNumber::Number(current_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(traces) => traces,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on eth_traces, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let to_traces_after_latest = match self
-                    .eth_traces(
-                        vec![],
-                        vec![self.my_address],
-                        BlockNumber::Number(saved_traces.latest_block + 1),
-                        BlockNumber::Number(current_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(traces) => traces,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on eth_traces, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let total_length = from_traces_after_latest.len() + to_traces_after_latest.len();
-                mm_counter!(ctx.metrics, "tx.history.response.total_length", total_length as u64,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "eth_traces");
-
-                saved_traces.traces.extend(from_traces_after_latest);
-                saved_traces.traces.extend(to_traces_after_latest);
-                saved_traces.latest_block = current_block;
-
-                self.store_eth_traces(ctx, &saved_traces);
-            }
-            saved_traces.traces.sort_by(|a, b| b.block_number.cmp(&a.block_number));
-            for trace in saved_traces.traces {
-                let hash = sha256(&json::to_vec(&trace).unwrap());
-                let internal_id = BytesJson::from(hash.to_vec());
-                let processed = existing_history.iter().find(|tx| tx.internal_id == internal_id);
-                if processed.is_some() {
-                    continue;
-                }
-
-                // TODO Only standard Call traces are supported, contract creations, suicides and block rewards will be supported later
-                let call_data = match trace.action {
-                    TraceAction::Call(d) => d,
-                    _ => continue,
-                };
-
-                mm_counter!(ctx.metrics, "tx.history.request.count", 1, "coin" => self.ticker.clone(), "method" => "tx_detail_by_hash");
-
-                let web3_tx = match self
-                    .web3
-                    .eth()
-                    .transaction(TransactionId::Hash(trace.transaction_hash.unwrap()))
-                    .await
-                {
-                    Ok(tx) => tx,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!(
-                                "Error {} on getting transaction {:?}",
-                                e,
-                                trace.transaction_hash.unwrap()
-                            ),
-                        );
-                        continue;
-                    },
-                };
-                let web3_tx = match web3_tx {
-                    Some(t) => t,
-                    None => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("No such transaction {:?}", trace.transaction_hash.unwrap()),
-                        );
-                        continue;
-                    },
-                };
-
-                mm_counter!(ctx.metrics, "tx.history.response.count", 1, "coin" => self.ticker.clone(), "method" => "tx_detail_by_hash");
-
-                let receipt = match self
-                    .web3
-                    .eth()
-                    .transaction_receipt(trace.transaction_hash.unwrap())
-                    .await
-                {
-                    Ok(r) => r,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!(
-                                "Error {} on getting transaction {:?} receipt",
-                                e,
-                                trace.transaction_hash.unwrap()
-                            ),
-                        );
-                        continue;
-                    },
-                };
-                let fee_coin = match &self.coin_type {
-                    EthCoinType::Eth => self.ticker(),
-                    EthCoinType::Erc20 { platform, .. } => platform.as_str(),
-                };
-                let fee_details: Option<EthTxFeeDetails> = match receipt {
-                    Some(r) => {
-                        let gas_used = r.gas_used.unwrap_or_default();
-                        let gas_price = web3_tx.gas_price.unwrap_or_default();
-                        // It's relatively safe to unwrap `EthTxFeeDetails::new` as it may fail
-                        // due to `u256_to_big_decimal` only.
-                        // Also TX history is not used by any GUI and has significant disadvantages.
-                        Some(EthTxFeeDetails::new(gas_used, gas_price, fee_coin).unwrap())
-                    },
-                    None => None,
-                };
-
-                let total_amount: BigDecimal = u256_to_big_decimal(call_data.value, ETH_DECIMALS).unwrap();
-                let mut received_by_me = 0.into();
-                let mut spent_by_me = 0.into();
-
-                if call_data.from == self.my_address {
-                    // ETH transfer is actually happening only if no error occurred
-                    if trace.error.is_none() {
-                        spent_by_me = total_amount.clone();
-                    }
-                    if let Some(ref fee) = fee_details {
-                        spent_by_me += &fee.total_fee;
-                    }
-                }
-
-                if call_data.to == self.my_address {
-                    // ETH transfer is actually happening only if no error occurred
-                    if trace.error.is_none() {
-                        received_by_me = total_amount.clone();
-                    }
-                }
-
-                let raw = signed_tx_from_web3_tx(web3_tx).unwrap();
-                let block = match self
-                    .web3
-                    .eth()
-                    .block(BlockId::Number(BlockNumber::Number(trace.block_number.into())))
-                    .await
-                {
-                    Ok(b) => b.unwrap(),
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on getting block {} data", e, trace.block_number),
-                        );
-                        continue;
-                    },
-                };
-
-                let details = TransactionDetails {
-                    my_balance_change: &received_by_me - &spent_by_me,
-                    spent_by_me,
-                    received_by_me,
-                    total_amount,
-                    to: vec![checksum_address(&format!("{:#02x}", call_data.to))],
-                    from: vec![checksum_address(&format!("{:#02x}", call_data.from))],
-                    coin: self.ticker.clone(),
-                    fee_details: fee_details.map(|d| d.into()),
-                    block_height: trace.block_number,
-                    tx_hash: format!("{:02x}", BytesJson(raw.hash.as_bytes().to_vec())),
-                    tx_hex: BytesJson(rlp::encode(&raw).to_vec()),
-                    internal_id,
-                    timestamp: block.timestamp.into_or_max(),
-                    kmd_rewards: None,
-                    transaction_type: Default::default(),
-                    memo: None,
-                };
-
-                existing_history.push(details);
-
-                if let Err(e) = self.save_history_to_file(ctx, existing_history.clone()).compat().await {
-                    ctx.log.log(
-                        "",
-                        &[&"tx_history", &self.ticker],
-                        &ERRL!("Error {} on 'save_history_to_file', stop the history loop", e),
-                    );
-                    return;
-                }
-            }
-            if saved_traces.earliest_block == 0.into() {
-                if success_iteration == 0 {
-                    ctx.log.log(
-                        "😅",
-                        &[&"tx_history", &("coin", self.ticker.clone().as_str())],
-                        "history has been loaded successfully",
-                    );
-                }
-
-                success_iteration += 1;
-                *self.history_sync_state.lock().unwrap() = HistorySyncState::Finished;
-                Timer::sleep(15.).await;
-            } else {
-                Timer::sleep(2.).await;
-            }
+    pub fn public(&self) -> Option<Public> {
+        match self {
+            SignedEthTx::Legacy(tx) => tx.public,
+            SignedEthTx::Eip1559(tx) => Some(tx.public),
         }
     }
 
-    /// Downloads and saves ERC20 transaction history of my_address
-    #[allow(clippy::cognitive_complexity)]
-    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
-    async fn process_erc20_history(&self, token_addr: H160, ctx: &MmArc) {
-        let delta = U64::from(10000);
-
-        let mut success_iteration = 0i32;
-        loop {
-            if ctx.is_stopping() {
-                break;
-            };
-            {
-                let coins_ctx = CoinsContext::from_ctx(ctx).unwrap();
-                let coins = coins_ctx.coins.lock().await;
-                if !coins.contains_key(&self.ticker) {
-                    ctx.log.log("", &[&"tx_history", &self.ticker], "Loop stopped");
-                    break;
-                };
-            }
-
-            let current_block = match self.web3.eth().block_number().await {
-                Ok(block) => block,
-                Err(e) => {
-                    ctx.log.log(
-                        "",
-                        &[&"tx_history", &self.ticker],
-                        &ERRL!("Error {} on eth_block_number, retrying", e),
-                    );
-                    Timer::sleep(10.).await;
-                    continue;
-                },
-            };
-
-            let mut saved_events = match self.load_saved_erc20_events(ctx) {
-                Some(events) => events,
-                None => SavedErc20Events {
-                    events: vec![],
-                    earliest_block: current_block,
-                    latest_block: current_block,
-                },
-            };
-            *self.history_sync_state.lock().unwrap() = HistorySyncState::InProgress(json!({
-                "blocks_left": saved_events.earliest_block,
-            }));
-
-            // AP: AFAIK ETH RPC doesn't support conditional filters like `get this OR this` so we have
-            // to run several queries to get transfer events including our address as sender `or` receiver
-            // TODO refactor this to batch requests instead of single request per query
-            if saved_events.earliest_block > 0.into() {
-                let before_earliest = if saved_events.earliest_block >= delta {
-                    saved_events.earliest_block - delta
-                } else {
-                    0.into()
-                };
-
-                let from_events_before_earliest = match self
-                    .erc20_transfer_events(
-                        token_addr,
-                        Some(self.my_address),
-                        None,
-                        BlockNumber::Number(before_earliest),
-                        BlockNumber::Number(saved_events.earliest_block - 1),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(events) => events,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on erc20_transfer_events, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let to_events_before_earliest = match self
-                    .erc20_transfer_events(
-                        token_addr,
-                        None,
-                        Some(self.my_address),
-                        BlockNumber::Number(before_earliest),
-                        BlockNumber::Number(saved_events.earliest_block - 1),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(events) => events,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on erc20_transfer_events, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let total_length = from_events_before_earliest.len() + to_events_before_earliest.len();
-                mm_counter!(ctx.metrics, "tx.history.response.total_length", total_length as u64,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "erc20_transfer_events");
-
-                saved_events.events.extend(from_events_before_earliest);
-                saved_events.events.extend(to_events_before_earliest);
-                saved_events.earliest_block = if before_earliest > 0.into() {
-                    before_earliest - 1
-                } else {
-                    0.into()
-                };
-                self.store_erc20_events(ctx, &saved_events);
-            }
-
-            if current_block > saved_events.latest_block {
-                let from_events_after_latest = match self
-                    .erc20_transfer_events(
-                        token_addr,
-                        Some(self.my_address),
-                        None,
-                        BlockNumber::Number(saved_events.latest_block + 1),
-                        BlockNumber::Number(current_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(events) => events,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on erc20_transfer_events, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
-
-                let to_events_after_latest = match self
-                    .erc20_transfer_events(
-                        token_addr,
-                        None,
-                        Some(self.my_address),
-                        BlockNumber::Number(saved_events.latest_block + 1),
-                        BlockNumber::Number(current_block),
-                        None,
-                    )
-                    .compat()
-                    .await
-                {
-                    Ok(events) => events,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on erc20_transfer_events, retrying", e),
-                        );
-                        Timer::sleep(10.).await;
-                        continue;
-                    },
-                };
+    pub fn nonce(&self) -> U256 {
+        match self {
+            SignedEthTx::Legacy(tx) => tx.nonce,
+            SignedEthTx::Eip1559(tx) => tx.transaction.unsigned.nonce,
+        }
+    }
 
-                let total_length = from_events_after_latest.len() + to_events_after_latest.len();
-                mm_counter!(ctx.metrics, "tx.history.response.total_length", total_length as u64,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "erc20_transfer_events");
+    pub fn action(&self) -> &Action {
+        match self {
+            SignedEthTx::Legacy(tx) => &tx.action,
+            SignedEthTx::Eip1559(tx) => &tx.transaction.unsigned.action,
+        }
+    }
 
-                saved_events.events.extend(from_events_after_latest);
-                saved_events.events.extend(to_events_after_latest);
-                saved_events.latest_block = current_block;
-                self.store_erc20_events(ctx, &saved_events);
-            }
+    pub fn value(&self) -> U256 {
+        match self {
+            SignedEthTx::Legacy(tx) => tx.value,
+            SignedEthTx::Eip1559(tx) => tx.transaction.unsigned.value,
+        }
+    }
 
-            let all_events: HashMap<_, _> = saved_events
-                .events
-                .iter()
-                .filter(|e| e.block_number.is_some() && e.transaction_hash.is_some() && !e.is_removed())
-                .map(|e| (e.transaction_hash.unwrap(), e))
-                .collect();
-            let mut all_events: Vec<_> = all_events.into_values().collect();
-            all_events.sort_by(|a, b| b.block_number.unwrap().cmp(&a.block_number.unwrap()));
+    pub fn data(&self) -> &[u8] {
+        match self {
+            SignedEthTx::Legacy(tx) => &tx.data,
+            SignedEthTx::Eip1559(tx) => &tx.transaction.unsigned.data,
+        }
+    }
+}
 
-            for event in all_events {
-                let mut existing_history = match self.load_history_from_file(ctx).compat().await {
-                    Ok(history) => history,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on 'load_history_from_file', stop the history loop", e),
-                        );
-                        return;
-                    },
-                };
-                let internal_id = BytesJson::from(sha256(&json::to_vec(&event).unwrap()).to_vec());
-                if existing_history.iter().any(|item| item.internal_id == internal_id) {
-                    // the transaction already imported
-                    continue;
-                };
+/// Decodes a legacy RLP-encoded transaction or an EIP-2718 typed one.
+/// Only the EIP-1559 transactions are supported of the typed ones.
+pub fn signed_eth_tx_from_bytes(bytes: &[u8]) -> Result<SignedEthTx, String> {
+    match bytes.first() {
+        Some(&EIP1559_TX_TYPE) => {
+            let tx = try_s!(SignedEip1559Transaction::decode(bytes));
+            Ok(SignedEthTx::Eip1559(try_s!(VerifiedEip1559Transaction::new(tx))))
+        },
+        // The typed transactions start with a type byte, and the legacy ones start with an RLP list prefix.
+        // https://eips.ethereum.org/EIPS/eip-2718#transactiontype-only-goes-up-to-0x7f
+        Some(tx_type) if *tx_type <= 0x7f => ERR!("Unsupported transaction type {}", tx_type),
+        _ => {
+            let tx: UnverifiedTransaction = try_s!(rlp::decode(bytes));
+            Ok(SignedEthTx::Legacy(try_s!(SignedLegacyEthTx::new(tx))))
+        },
+    }
+}
 
-                let amount = U256::from(event.data.0.as_slice());
-                let total_amount = u256_to_big_decimal(amount, self.decimals).unwrap();
-                let mut received_by_me = 0.into();
-                let mut spent_by_me = 0.into();
+// We can use a nonce lock shared between tokens using the same platform coin and the platform itself.
+// For example, ETH/USDT-ERC20 should use the same lock, but it will be different for BNB/USDT-BEP20.
+lazy_static! {
+    static ref NONCE_LOCK: Mutex<HashMap<String, Arc<AsyncMutex<()>>>> = Mutex::new(HashMap::new());
+}
 
-                let from_addr = H160::from(event.topics[1]);
-                let to_addr = H160::from(event.topics[2]);
+// The swaps of the same ERC20 token read and update the swap contract allowance under this lock,
+// so they don't overwrite each other's approvals.
+lazy_static! {
+    static ref ERC20_ALLOWANCE_LOCK: Mutex<HashMap<String, Arc<AsyncMutex<()>>>> = Mutex::new(HashMap::new());
+}
 
-                if from_addr == self.my_address {
-                    spent_by_me = total_amount.clone();
-                }
+type EthTxFut = Box<dyn Future<Item = SignedEthTx, Error = TransactionErr> + Send + 'static>;
 
-                if to_addr == self.my_address {
-                    received_by_me = total_amount.clone();
-                }
+async fn sign_and_send_transaction_with_keypair(
+    ctx: MmArc,
+    coin: &EthCoin,
+    key_pair: &KeyPair,
+    value: U256,
+    action: Action,
+    data: Vec<u8>,
+    gas: U256,
+) -> Result<SignedEthTx, TransactionErr> {
+    let mut status = ctx.log.status_handle();
+    macro_rules! tags {
+        () => {
+            &[&"sign-and-send"]
+        };
+    }
+    let _nonce_lock = coin.nonce_lock.lock().await;
+    status.status(tags!(), "get_addr_nonce…");
+    let (nonce, web3_instances_with_latest_nonce) = try_tx_s!(
+        get_addr_nonce(coin.my_address, coin.web3_instances.clone())
+            .compat()
+            .await
+    );
+    status.status(tags!(), "get_swap_pay_for_gas_option…");
+    let pay_for_gas_option = try_tx_s!(coin.get_swap_pay_for_gas_option().await);
 
-                mm_counter!(ctx.metrics, "tx.history.request.count", 1,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "tx_detail_by_hash");
+    let signed = try_tx_s!(sign_eth_transaction(
+        coin,
+        key_pair.secret(),
+        nonce,
+        gas,
+        pay_for_gas_option,
+        action,
+        value,
+        data
+    ));
+    let bytes = Bytes(signed.tx_hex());
+    status.status(tags!(), "send_raw_transaction…");
 
-                let web3_tx = match self
-                    .web3
-                    .eth()
-                    .transaction(TransactionId::Hash(event.transaction_hash.unwrap()))
-                    .await
-                {
-                    Ok(tx) => tx,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!(
-                                "Error {} on getting transaction {:?}",
-                                e,
-                                event.transaction_hash.unwrap()
-                            ),
-                        );
-                        continue;
-                    },
-                };
+    let futures = web3_instances_with_latest_nonce
+        .into_iter()
+        .map(|web3_instance| web3_instance.web3.eth().send_raw_transaction(bytes.clone()));
+    try_tx_s!(select_ok(futures).await.map_err(|e| ERRL!("{}", e)), signed);
 
-                mm_counter!(ctx.metrics, "tx.history.response.count", 1,
-                    "coin" => self.ticker.clone(), "client" => "ethereum", "method" => "tx_detail_by_hash");
+    status.status(tags!(), "get_addr_nonce…");
+    coin.wait_for_addr_nonce_increase(coin.my_address, nonce).await;
+    Ok(signed)
+}
 
-                let web3_tx = match web3_tx {
-                    Some(t) => t,
-                    None => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("No such transaction {:?}", event.transaction_hash.unwrap()),
-                        );
-                        continue;
-                    },
-                };
+#[cfg(target_arch = "wasm32")]
+async fn sign_and_send_transaction_with_metamask(
+    coin: EthCoin,
+    value: U256,
+    action: Action,
+    data: Vec<u8>,
+    gas: U256,
+) -> Result<SignedEthTx, TransactionErr> {
+    let to = match action {
+        Action::Create => None,
+        Action::Call(to) => Some(to),
+    };
 
-                let receipt = match self
-                    .web3
-                    .eth()
-                    .transaction_receipt(event.transaction_hash.unwrap())
-                    .await
-                {
-                    Ok(r) => r,
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!(
-                                "Error {} on getting transaction {:?} receipt",
-                                e,
-                                event.transaction_hash.unwrap()
-                            ),
-                        );
-                        continue;
-                    },
-                };
-                let fee_coin = match &self.coin_type {
-                    EthCoinType::Eth => self.ticker(),
-                    EthCoinType::Erc20 { platform, .. } => platform.as_str(),
-                };
-                let fee_details = match receipt {
-                    Some(r) => {
-                        let gas_used = r.gas_used.unwrap_or_default();
-                        let gas_price = web3_tx.gas_price.unwrap_or_default();
-                        // It's relatively safe to unwrap `EthTxFeeDetails::new` as it may fail
-                        // due to `u256_to_big_decimal` only.
-                        // Also TX history is not used by any GUI and has significant disadvantages.
-                        Some(EthTxFeeDetails::new(gas_used, gas_price, fee_coin).unwrap())
-                    },
-                    None => None,
-                };
-                let block_number = event.block_number.unwrap();
-                let block = match self
-                    .web3
-                    .eth()
-                    .block(BlockId::Number(BlockNumber::Number(block_number)))
-                    .await
-                {
-                    Ok(Some(b)) => b,
-                    Ok(None) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Block {} is None", block_number),
-                        );
-                        continue;
-                    },
-                    Err(e) => {
-                        ctx.log.log(
-                            "",
-                            &[&"tx_history", &self.ticker],
-                            &ERRL!("Error {} on getting block {} data", e, block_number),
-                        );
-                        continue;
-                    },
-                };
+    let pay_for_gas_option = try_tx_s!(coin.get_swap_pay_for_gas_option().await);
 
-                let raw = signed_tx_from_web3_tx(web3_tx).unwrap();
-                let details = TransactionDetails {
-                    my_balance_change: &received_by_me - &spent_by_me,
-                    spent_by_me,
-                    received_by_me,
-                    total_amount,
-                    to: vec![checksum_address(&format!("{:#02x}", to_addr))],
-                    from: vec![checksum_address(&format!("{:#02x}", from_addr))],
-                    coin: self.ticker.clone(),
-                    fee_details: fee_details.map(|d| d.into()),
-                    block_height: block_number.as_u64(),
-                    tx_hash: format!("{:02x}", BytesJson(raw.hash.as_bytes().to_vec())),
-                    tx_hex: BytesJson(rlp::encode(&raw).to_vec()),
-                    internal_id: BytesJson(internal_id.to_vec()),
-                    timestamp: block.timestamp.into_or_max(),
-                    kmd_rewards: None,
-                    transaction_type: Default::default(),
-                    memo: None,
-                };
+    let mut tx_to_send = TransactionRequest {
+        from: coin.my_address,
+        to,
+        gas: Some(gas),
+        value: Some(value),
+        data: Some(data.clone().into()),
+        nonce: None,
+        ..TransactionRequest::default()
+    };
+    match pay_for_gas_option {
+        PayForGasOption::Legacy { gas_price } => tx_to_send.gas_price = Some(gas_price),
+        PayForGasOption::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            tx_to_send.transaction_type = Some(U64::from(EIP1559_TX_TYPE));
+            tx_to_send.max_fee_per_gas = Some(max_fee_per_gas);
+            tx_to_send.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        },
+    }
 
-                existing_history.push(details);
+    // It's important to return the transaction hex for the swap,
+    // so wait up to 60 seconds for the transaction to appear on the RPC node.
+    let wait_rpc_timeout = 60_000;
+    let check_every = 1.;
 
-                if let Err(e) = self.save_history_to_file(ctx, existing_history).compat().await {
-                    ctx.log.log(
-                        "",
-                        &[&"tx_history", &self.ticker],
-                        &ERRL!("Error {} on 'save_history_to_file', stop the history loop", e),
-                    );
-                    return;
-                }
-            }
-            if saved_events.earliest_block == 0.into() {
-                if success_iteration == 0 {
-                    ctx.log.log(
-                        "😅",
-                        &[&"tx_history", &("coin", self.ticker.clone().as_str())],
-                        "history has been loaded successfully",
-                    );
-                }
+    // Please note that this method may take a long time
+    // due to `wallet_switchEthereumChain` and `eth_sendTransaction` requests.
+    let tx_hash = try_tx_s!(coin.web3.eth().send_transaction(tx_to_send).await);
 
-                success_iteration += 1;
-                *self.history_sync_state.lock().unwrap() = HistorySyncState::Finished;
-                Timer::sleep(15.).await;
-            } else {
-                Timer::sleep(2.).await;
-            }
-        }
+    let maybe_signed_tx = try_tx_s!(
+        coin.wait_for_tx_appears_on_rpc(tx_hash, wait_rpc_timeout, check_every)
+            .await
+    );
+    match maybe_signed_tx {
+        Some(signed_tx) => Ok(signed_tx),
+        None => TX_PLAIN_ERR!(
+            "Waited too long until the transaction {:?} appear on the RPC node",
+            tx_hash
+        ),
     }
 }
 
@@ -3060,15 +2475,13 @@ impl EthCoin {
                 platform: _,
                 token_addr,
             } => {
-                let allowance_fut = self
-                    .allowance(swap_contract_address)
-                    .map_err(|e| TransactionErr::Plain(ERRL!("{}", e)));
-
                 let function_name = get_function_name("erc20Payment", args.watcher_reward.is_some());
                 let function = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
                 let mut value = U256::from(0);
                 let mut amount = trade_amount;
+                // The `erc20PaymentWithPermit` arguments preceding the permit signature.
+                let mut permit_payment_tokens = None;
 
                 let data = match args.watcher_reward {
                     Some(reward) => {
@@ -3093,72 +2506,115 @@ impl EthCoin {
                         ]))
                     },
                     None => {
-                        try_tx_fus!(function.encode_input(&[
+                        let payment_tokens = vec![
                             Token::FixedBytes(id),
                             Token::Uint(trade_amount),
                             Token::Address(*token_addr),
                             Token::Address(receiver_addr),
                             Token::FixedBytes(secret_hash),
-                            Token::Uint(time_lock)
-                        ]))
+                            Token::Uint(time_lock),
+                        ];
+                        let data = try_tx_fus!(function.encode_input(&payment_tokens));
+                        if self.contract_supports_permit {
+                            permit_payment_tokens = Some(payment_tokens);
+                        }
+                        data
                     },
                 };
 
                 let wait_for_required_allowance_until = args.wait_for_confirmation_until;
 
                 let arc = self.clone();
-                Box::new(allowance_fut.and_then(move |allowed| -> EthTxFut {
-                    if allowed < amount {
-                        Box::new(
-                            arc.approve(swap_contract_address, U256::max_value())
-                                .and_then(move |approved| {
-                                    // make sure the approve tx is confirmed by making sure that the allowed value has been updated
-                                    // this call is cheaper than waiting for confirmation calls
-                                    arc.wait_for_required_allowance(
-                                        swap_contract_address,
-                                        amount,
-                                        wait_for_required_allowance_until,
-                                    )
-                                    .map_err(move |e| {
-                                        TransactionErr::Plain(ERRL!(
-                                            "Allowed value was not updated in time after sending approve transaction {:02x}: {}",
-                                            approved.tx_hash(),
-                                            e
-                                        ))
-                                    })
-                                    .and_then(move |_| {
-                                        arc.sign_and_send_transaction(
-                                            value,
-                                            Action::Call(swap_contract_address),
-                                            data,
-                                            gas,
-                                        )
-                                    })
-                                }),
-                        )
-                    } else {
-                        Box::new(arc.sign_and_send_transaction(
-                            value,
-                            Action::Call(swap_contract_address),
-                            data,
-                            gas,
-                        ))
+                let token_addr = *token_addr;
+                let fut = async move {
+                    // The lock is held until the payment is sent, so the allowance is not spent by another swap meanwhile.
+                    let allowance_lock = arc.erc20_allowance_lock();
+                    let _allowance_lock = allowance_lock.lock().await;
+                    let allowed = try_tx_s!(arc.allowance(swap_contract_address).compat().await);
+                    if allowed >= amount {
+                        return arc
+                            .sign_and_send_transaction(value, Action::Call(swap_contract_address), data, gas)
+                            .compat()
+                            .await;
                     }
-                }))
+
+                    let payment_tokens = match permit_payment_tokens {
+                        Some(payment_tokens) => payment_tokens,
+                        None => {
+                            return arc
+                                .approve_and_send_erc20_payment(
+                                    swap_contract_address,
+                                    allowed,
+                                    amount,
+                                    wait_for_required_allowance_until,
+                                    value,
+                                    data,
+                                    gas,
+                                )
+                                .await
+                        },
+                    };
+                    // The permit is spent by the payment itself, so it's valid until the payment can be sent.
+                    let permit = match arc
+                        .sign_erc20_permit(token_addr, swap_contract_address, amount, time_lock)
+                        .await
+                    {
+                        Ok(Some(permit)) => permit,
+                        Ok(None) => {
+                            return arc
+                                .approve_and_send_erc20_payment(
+                                    swap_contract_address,
+                                    allowed,
+                                    amount,
+                                    wait_for_required_allowance_until,
+                                    value,
+                                    data,
+                                    gas,
+                                )
+                                .await
+                        },
+                        Err(e) => {
+                            warn!("Couldn't sign an ERC20 permit, falling back to 'approve': {}", e);
+                            return arc
+                                .approve_and_send_erc20_payment(
+                                    swap_contract_address,
+                                    allowed,
+                                    amount,
+                                    wait_for_required_allowance_until,
+                                    value,
+                                    data,
+                                    gas,
+                                )
+                                .await;
+                        },
+                    };
+
+                    let function = try_tx_s!(SWAP_CONTRACT.function("erc20PaymentWithPermit"));
+                    let tokens: Vec<Token> = payment_tokens.into_iter().chain(permit.into_tokens()).collect();
+                    let data = try_tx_s!(function.encode_input(&tokens));
+                    arc.sign_and_send_transaction(
+                        value,
+                        Action::Call(swap_contract_address),
+                        data,
+                        gas + U256::from(PERMIT_GAS),
+                    )
+                    .compat()
+                    .await
+                };
+                Box::new(fut.boxed().compat())
             },
         }
     }
 
     fn watcher_spends_hash_time_locked_payment(&self, input: SendMakerPaymentSpendPreimageInput) -> EthTxFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(input.preimage));
-        let payment = try_tx_fus!(SignedEthTx::new(tx));
+        let payment = try_tx_fus!(signed_eth_tx_from_bytes(input.preimage));
 
         let function_name = get_function_name("receiverSpend", input.watcher_reward);
         let spend_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
         let clone = self.clone();
         let secret_vec = input.secret.to_vec();
         let taker_addr = addr_from_raw_pubkey(input.taker_pub).unwrap();
-        let swap_contract_address = match payment.action {
+        let swap_contract_address = match *payment.action() {
             Call(address) => address,
             Create => {
                 return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
@@ -3172,7 +2628,7 @@ impl EthCoin {
             EthCoinType::Eth => {
                 let function_name = get_function_name("ethPayment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_contract_call(payment_func, payment.data()));
                 let swap_id_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 0));
 
                 let state_f = self.payment_status(swap_contract_address, swap_id_input.clone());
@@ -3188,7 +2644,7 @@ impl EthCoin {
                                 ))));
                             }
 
-                            let value = payment.value;
+                            let value = payment.value();
                             let reward_target = try_tx_fus!(get_function_input_data(&decoded, payment_func, 4));
                             let sends_contract_reward = try_tx_fus!(get_function_input_data(&decoded, payment_func, 5));
                             let watcher_reward_amount = try_tx_fus!(get_function_input_data(&decoded, payment_func, 6));
@@ -3221,7 +2677,7 @@ impl EthCoin {
                 let function_name = get_function_name("erc20Payment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_payment_call(payment_func, payment.data()));
                 let swap_id_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 0));
                 let amount_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 1));
 
@@ -3266,15 +2722,14 @@ impl EthCoin {
     }
 
     fn watcher_refunds_hash_time_locked_payment(&self, args: RefundPaymentArgs) -> EthTxFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(args.payment_tx));
-        let payment = try_tx_fus!(SignedEthTx::new(tx));
+        let payment = try_tx_fus!(signed_eth_tx_from_bytes(args.payment_tx));
 
         let function_name = get_function_name("senderRefund", true);
         let refund_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
         let clone = self.clone();
         let taker_addr = addr_from_raw_pubkey(args.other_pubkey).unwrap();
-        let swap_contract_address = match payment.action {
+        let swap_contract_address = match *payment.action() {
             Call(address) => address,
             Create => {
                 return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
@@ -3287,7 +2742,7 @@ impl EthCoin {
             EthCoinType::Eth => {
                 let function_name = get_function_name("ethPayment", true);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_contract_call(payment_func, payment.data()));
                 let swap_id_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 0));
                 let receiver_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 1));
                 let hash_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 2));
@@ -3305,7 +2760,7 @@ impl EthCoin {
                                 ))));
                             }
 
-                            let value = payment.value;
+                            let value = payment.value();
                             let reward_target = try_tx_fus!(get_function_input_data(&decoded, payment_func, 4));
                             let sends_contract_reward = try_tx_fus!(get_function_input_data(&decoded, payment_func, 5));
                             let reward_amount = try_tx_fus!(get_function_input_data(&decoded, payment_func, 6));
@@ -3338,7 +2793,7 @@ impl EthCoin {
                 let function_name = get_function_name("erc20Payment", true);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_contract_call(payment_func, payment.data()));
                 let swap_id_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 0));
                 let amount_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 1));
                 let receiver_input = try_tx_fus!(get_function_input_data(&decoded, payment_func, 3));
@@ -3386,8 +2841,7 @@ impl EthCoin {
     }
 
     fn spend_hash_time_locked_payment(&self, args: SpendPaymentArgs) -> EthTxFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(args.other_payment_tx));
-        let payment = try_tx_fus!(SignedEthTx::new(tx));
+        let payment = try_tx_fus!(signed_eth_tx_from_bytes(args.other_payment_tx));
         let swap_contract_address = try_tx_fus!(args.swap_contract_address.try_to_address());
 
         let function_name = get_function_name("receiverSpend", args.watcher_reward);
@@ -3401,7 +2855,7 @@ impl EthCoin {
             EthCoinType::Eth => {
                 let function_name = get_function_name("ethPayment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_contract_call(payment_func, payment.data()));
 
                 let state_f = self.payment_status(swap_contract_address, decoded[0].clone());
                 Box::new(
@@ -3419,7 +2873,7 @@ impl EthCoin {
                             let data = if watcher_reward {
                                 try_tx_fus!(spend_func.encode_input(&[
                                     decoded[0].clone(),
-                                    Token::Uint(payment.value),
+                                    Token::Uint(payment.value()),
                                     Token::FixedBytes(secret_vec),
                                     Token::Address(Address::default()),
                                     Token::Address(payment.sender()),
@@ -3431,7 +2885,7 @@ impl EthCoin {
                             } else {
                                 try_tx_fus!(spend_func.encode_input(&[
                                     decoded[0].clone(),
-                                    Token::Uint(payment.value),
+                                    Token::Uint(payment.value()),
                                     Token::FixedBytes(secret_vec),
                                     Token::Address(Address::default()),
                                     Token::Address(payment.sender()),
@@ -3454,7 +2908,7 @@ impl EthCoin {
                 let function_name = get_function_name("erc20Payment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_payment_call(payment_func, payment.data()));
                 let state_f = self.payment_status(swap_contract_address, decoded[0].clone());
 
                 Box::new(
@@ -3503,8 +2957,7 @@ impl EthCoin {
     }
 
     fn refund_hash_time_locked_payment(&self, args: RefundPaymentArgs) -> EthTxFut {
-        let tx: UnverifiedTransaction = try_tx_fus!(rlp::decode(args.payment_tx));
-        let payment = try_tx_fus!(SignedEthTx::new(tx));
+        let payment = try_tx_fus!(signed_eth_tx_from_bytes(args.payment_tx));
         let swap_contract_address = try_tx_fus!(args.swap_contract_address.try_to_address());
 
         let function_name = get_function_name("senderRefund", args.watcher_reward);
@@ -3518,7 +2971,7 @@ impl EthCoin {
                 let function_name = get_function_name("ethPayment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_contract_call(payment_func, payment.data()));
 
                 let state_f = self.payment_status(swap_contract_address, decoded[0].clone());
                 Box::new(
@@ -3533,7 +2986,7 @@ impl EthCoin {
                                 ))));
                             }
 
-                            let value = payment.value;
+                            let value = payment.value();
                             let data = if watcher_reward {
                                 try_tx_fus!(refund_func.encode_input(&[
                                     decoded[0].clone(),
@@ -3572,7 +3025,7 @@ impl EthCoin {
                 let function_name = get_function_name("erc20Payment", watcher_reward);
                 let payment_func = try_tx_fus!(SWAP_CONTRACT.function(&function_name));
 
-                let decoded = try_tx_fus!(decode_contract_call(payment_func, &payment.data));
+                let decoded = try_tx_fus!(decode_payment_call(payment_func, payment.data()));
                 let state_f = self.payment_status(swap_contract_address, decoded[0].clone());
                 Box::new(
                     state_f
@@ -3620,18 +3073,18 @@ impl EthCoin {
         }
     }
 
-    fn my_balance(&self) -> BalanceFut<U256> {
+    fn my_balance(&self) -> BalanceFut<U256> { self.address_balance(self.my_address) }
+
+    /// Requests the balance of the given `address`.
+    /// It's the ETH balance for the platform coin and the token balance for an ERC20 token.
+    fn address_balance(&self, address: Address) -> BalanceFut<U256> {
         let coin = self.clone();
         let fut = async move {
             match coin.coin_type {
-                EthCoinType::Eth => Ok(coin
-                    .web3
-                    .eth()
-                    .balance(coin.my_address, Some(BlockNumber::Latest))
-                    .await?),
+                EthCoinType::Eth => Ok(coin.web3.eth().balance(address, Some(BlockNumber::Latest)).await?),
                 EthCoinType::Erc20 { ref token_addr, .. } => {
                     let function = ERC20_CONTRACT.function("balanceOf")?;
-                    let data = function.encode_input(&[Token::Address(coin.my_address)])?;
+                    let data = function.encode_input(&[Token::Address(address)])?;
 
                     let res = coin.call_request(*token_addr, None, Some(data.into())).await?;
                     let decoded = function.decode_output(&res.0)?;
@@ -3649,11 +3102,19 @@ impl EthCoin {
     }
 
     pub async fn get_tokens_balance_list(&self) -> Result<HashMap<String, CoinBalance>, MmError<BalanceError>> {
+        self.get_tokens_balance_list_for_address(self.my_address).await
+    }
+
+    /// Requests balances of all activated ERC20 tokens for the given `address`.
+    pub async fn get_tokens_balance_list_for_address(
+        &self,
+        address: Address,
+    ) -> Result<HashMap<String, CoinBalance>, MmError<BalanceError>> {
         let coin = || self;
         let mut requests = Vec::new();
         for (token_ticker, info) in self.get_erc_tokens_infos() {
             let fut = async move {
-                let balance_as_u256 = coin().get_token_balance_by_address(info.token_address).await?;
+                let balance_as_u256 = coin().get_token_balance_by_address(info.token_address, address).await?;
                 let balance_as_big_decimal = u256_to_big_decimal(balance_as_u256, info.decimals)?;
                 let balance = CoinBalance::new(balance_as_big_decimal);
                 Ok((token_ticker, balance))
@@ -3664,10 +3125,14 @@ impl EthCoin {
         try_join_all(requests).await.map(|res| res.into_iter().collect())
     }
 
-    async fn get_token_balance_by_address(&self, token_address: Address) -> Result<U256, MmError<BalanceError>> {
+    async fn get_token_balance_by_address(
+        &self,
+        token_address: Address,
+        address: Address,
+    ) -> Result<U256, MmError<BalanceError>> {
         let coin = self.clone();
         let function = ERC20_CONTRACT.function("balanceOf")?;
-        let data = function.encode_input(&[Token::Address(coin.my_address)])?;
+        let data = function.encode_input(&[Token::Address(address)])?;
         let res = coin.call_request(token_address, None, Some(data.into())).await?;
         let decoded = function.decode_output(&res.0)?;
 
@@ -3697,21 +3162,24 @@ impl EthCoin {
     /// because [`CallRequest::from`] is set to [`EthCoinImpl::my_address`].
     fn estimate_gas_for_contract_call(&self, contract_addr: Address, call_data: Bytes) -> Web3RpcFut<U256> {
         let coin = self.clone();
-        Box::new(coin.get_gas_price().and_then(move |gas_price| {
+        let fut = async move {
+            let pay_for_gas_option = coin.get_swap_pay_for_gas_option().await?;
             let eth_value = U256::zero();
-            let estimate_gas_req = CallRequest {
+            let mut estimate_gas_req = CallRequest {
                 value: Some(eth_value),
                 data: Some(call_data),
                 from: Some(coin.my_address),
                 to: Some(contract_addr),
                 gas: None,
-                // gas price must be supplied because some smart contracts base their
-                // logic on gas price, e.g. TUSD: https://github.com/KomodoPlatform/atomicDEX-API/issues/643
-                gas_price: Some(gas_price),
                 ..CallRequest::default()
             };
-            coin.estimate_gas(estimate_gas_req).map_to_mm_fut(Web3RpcError::from)
-        }))
+            pay_for_gas_option.set_to_call_request(&mut estimate_gas_req);
+            coin.estimate_gas(estimate_gas_req)
+                .compat()
+                .await
+                .map_to_mm(Web3RpcError::from)
+        };
+        Box::new(fut.boxed().compat())
     }
 
     fn eth_balance(&self) -> BalanceFut<U256> {
@@ -3741,7 +3209,25 @@ impl EthCoin {
             .await
     }
 
-    fn allowance(&self, spender: Address) -> Web3RpcFut<U256> {
+    /// Calls the view `function` of the token contract and decodes its output.
+    fn call_token_function(
+        &self,
+        token_addr: Address,
+        function: &Function,
+        args: &[Token],
+    ) -> Box<dyn Future<Item = Vec<Token>, Error = String> + Send> {
+        let data = try_fus!(function.encode_input(args));
+        let function = function.clone();
+        let coin = self.clone();
+        let fut = async move {
+            let output = try_s!(coin.call_request(token_addr, None, Some(data.into())).await);
+            Ok(try_s!(function.decode_output(&output.0)))
+        };
+        Box::new(fut.boxed().compat())
+    }
+
+    /// Requests the amount of the ERC20 token that `spender` is allowed to transfer from my address.
+    pub fn allowance(&self, spender: Address) -> Web3RpcFut<U256> {
         let coin = self.clone();
         let fut = async move {
             match coin.coin_type {
@@ -3802,7 +3288,56 @@ impl EthCoin {
         Box::new(fut.boxed().compat())
     }
 
-    fn approve(&self, spender: Address, amount: U256) -> EthTxFut {
+    /// Approves the swap contract to spend the payment `amount` and sends the payment once the allowance is updated.
+    /// Must be called under [`EthCoin::erc20_allowance_lock`].
+    #[allow(clippy::too_many_arguments)]
+    async fn approve_and_send_erc20_payment(
+        &self,
+        swap_contract_address: Address,
+        allowed: U256,
+        amount: U256,
+        wait_for_required_allowance_until: u64,
+        value: U256,
+        data: Vec<u8>,
+        gas: U256,
+    ) -> Result<SignedEthTx, TransactionErr> {
+        let approve_amount = if self.exact_swap_approvals {
+            // `approve` replaces the allowance, so the remaining one is kept for the payments that are sent but not mined yet.
+            allowed.saturating_add(amount)
+        } else {
+            U256::max_value()
+        };
+        let approved = self.approve(swap_contract_address, approve_amount).compat().await?;
+
+        // make sure the approve tx is confirmed by making sure that the allowed value has been updated
+        // this call is cheaper than waiting for confirmation calls
+        if let Err(e) = self
+            .wait_for_required_allowance(swap_contract_address, amount, wait_for_required_allowance_until)
+            .compat()
+            .await
+        {
+            return TX_PLAIN_ERR!(
+                "Allowed value was not updated in time after sending approve transaction {:02x}: {}",
+                approved.tx_hash(),
+                e
+            );
+        }
+        self.sign_and_send_transaction(value, Action::Call(swap_contract_address), data, gas)
+            .compat()
+            .await
+    }
+
+    /// Returns the lock of the allowances of this ERC20 token.
+    pub(crate) fn erc20_allowance_lock(&self) -> Arc<AsyncMutex<()>> {
+        let mut map = ERC20_ALLOWANCE_LOCK.lock().unwrap();
+        map.entry(self.ticker.clone())
+            .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+            .clone()
+    }
+
+    /// Sends an ERC20 `approve` transaction that allows `spender` to transfer up to `amount` from my address.
+    /// Replaces the previous allowance, so `amount = 0` revokes it.
+    pub fn approve(&self, spender: Address, amount: U256) -> EthTxFut {
         let coin = self.clone();
         let fut = async move {
             let token_addr = match coin.coin_type {
@@ -3867,10 +3402,8 @@ impl EthCoin {
             .try_to_address()
             .map_to_mm(ValidatePaymentError::InvalidParameter));
 
-        let unsigned: UnverifiedTransaction = try_f!(rlp::decode(&input.payment_tx));
         let tx =
-            try_f!(SignedEthTx::new(unsigned)
-                .map_to_mm(|err| ValidatePaymentError::TxDeserializationError(err.to_string())));
+            try_f!(signed_eth_tx_from_bytes(&input.payment_tx).map_to_mm(ValidatePaymentError::TxDeserializationError));
         let sender = try_f!(addr_from_raw_pubkey(&input.other_pub).map_to_mm(ValidatePaymentError::InvalidParameter));
 
         let selfi = self.clone();
@@ -3895,9 +3428,9 @@ impl EthCoin {
                 )));
             }
 
-            let tx_from_rpc = selfi.web3.eth().transaction(TransactionId::Hash(tx.hash)).await?;
+            let tx_from_rpc = selfi.web3.eth().transaction(TransactionId::Hash(tx.hash())).await?;
             let tx_from_rpc = tx_from_rpc.as_ref().ok_or_else(|| {
-                ValidatePaymentError::TxDoesNotExist(format!("Didn't find provided tx {:?} on ETH node", tx.hash))
+                ValidatePaymentError::TxDoesNotExist(format!("Didn't find provided tx {:?} on ETH node", tx.hash()))
             })?;
 
             if tx_from_rpc.from != Some(sender) {
@@ -4015,7 +3548,7 @@ impl EthCoin {
                     let function = SWAP_CONTRACT
                         .function(&function_name)
                         .map_to_mm(|err| ValidatePaymentError::InternalError(err.to_string()))?;
-                    let decoded = decode_contract_call(function, &tx_from_rpc.input.0)
+                    let decoded = decode_payment_call(function, &tx_from_rpc.input.0)
                         .map_to_mm(|err| ValidatePaymentError::TxDeserializationError(err.to_string()))?;
 
                     if decoded[0] != Token::FixedBytes(swap_id.clone()) {
@@ -4150,8 +3683,7 @@ impl EthCoin {
         search_from_block: u64,
         watcher_reward: bool,
     ) -> Result<Option<FoundSwapTxSpend>, String> {
-        let unverified: UnverifiedTransaction = try_s!(rlp::decode(tx));
-        let tx = try_s!(SignedEthTx::new(unverified));
+        let tx = try_s!(signed_eth_tx_from_bytes(tx));
 
         let func_name = match self.coin_type {
             EthCoinType::Eth => get_function_name("ethPayment", watcher_reward),
@@ -4159,7 +3691,7 @@ impl EthCoin {
         };
 
         let payment_func = try_s!(SWAP_CONTRACT.function(&func_name));
-        let decoded = try_s!(decode_contract_call(payment_func, &tx.data));
+        let decoded = try_s!(decode_payment_call(payment_func, tx.data()));
         let id = match decoded.first() {
             Some(Token::FixedBytes(bytes)) => bytes.clone(),
             invalid_token => return ERR!("Expected Token::FixedBytes, got {:?}", invalid_token),
@@ -4194,7 +3726,7 @@ impl EthCoin {
                         };
 
                         return Ok(Some(FoundSwapTxSpend::Spent(TransactionEnum::from(try_s!(
-                            signed_tx_from_web3_tx(transaction)
+                            signed_tx_from_web3_tx(transaction, self.chain_id)
                         )))));
                     },
                     None => return ERR!("Found ReceiverSpent event, but it doesn't have tx_hash"),
@@ -4220,7 +3752,7 @@ impl EthCoin {
                         };
 
                         return Ok(Some(FoundSwapTxSpend::Refunded(TransactionEnum::from(try_s!(
-                            signed_tx_from_web3_tx(transaction)
+                            signed_tx_from_web3_tx(transaction, self.chain_id)
                         )))));
                     },
                     None => return ERR!("Found SenderRefunded event, but it doesn't have tx_hash"),
@@ -4278,20 +3810,11 @@ impl EthCoin {
                 },
             };
 
-            let fee_history_namespace: EthFeeHistoryNamespace<_> = coin.web3.api();
-            let eth_fee_history_price = match fee_history_namespace
-                .eth_fee_history(U256::from(1u64), BlockNumber::Latest, &[])
-                .await
-            {
-                Ok(res) => res
-                    .base_fee_per_gas
-                    .first()
-                    .map(|val| increase_by_percent_one_gwei(*val, BASE_BLOCK_FEE_DIFF_PCT)),
-                Err(e) => {
-                    debug!("Error {} on eth_feeHistory request", e);
-                    None
-                },
-            };
+            // Legacy transactions pay the whole gas price, so it has to cover both the base fee and the priority fee.
+            let eth_fee_history_price = coin.get_eip1559_fee_per_gas().await.map(|fee_per_gas| {
+                increase_by_percent_one_gwei(fee_per_gas.base_fee_per_gas, BASE_BLOCK_FEE_DIFF_PCT)
+                    + fee_per_gas.max_priority_fee_per_gas
+            });
 
             // on editions < 2021 the compiler will resolve array.into_iter() as (&array).into_iter()
             // https://doc.rust-lang.org/edition-guide/rust-2021/IntoIterator-for-arrays.html#details
@@ -4303,6 +3826,53 @@ impl EthCoin {
         Box::new(fut.boxed().compat())
     }
 
+    /// Estimates the EIP-1559 fees per gas using `eth_feeHistory`.
+    /// Returns `None` if the chain doesn't support EIP-1559 or the request failed.
+    async fn get_eip1559_fee_per_gas(&self) -> Option<FeePerGasEstimated> {
+        let fee_history_namespace: EthFeeHistoryNamespace<_> = self.web3.api();
+        match fee_history_namespace
+            .eth_fee_history(U256::from(FEE_HISTORY_BLOCKS), BlockNumber::Latest, &[
+                PRIORITY_FEE_PERCENTILE,
+            ])
+            .await
+        {
+            Ok(res) => FeePerGasEstimated::from_fee_history(&res),
+            Err(e) => {
+                debug!("Error {} on eth_feeHistory request", e);
+                None
+            },
+        }
+    }
+
+    /// Returns the EIP-1559 fees per gas if the chain supports them, otherwise the legacy gas price.
+    /// EIP-1559 withdrawals are used by default, since they don't overpay the base fee.
+    async fn get_pay_for_gas_option(&self) -> Web3RpcResult<PayForGasOption> {
+        let fee_per_gas = match self.chain_id {
+            Some(_) => self.get_eip1559_fee_per_gas().await,
+            None => None,
+        };
+        match fee_per_gas {
+            Some(fee_per_gas) => Ok(PayForGasOption::Eip1559 {
+                max_fee_per_gas: fee_per_gas.max_fee_per_gas,
+                max_priority_fee_per_gas: fee_per_gas.max_priority_fee_per_gas,
+            }),
+            None => Ok(PayForGasOption::Legacy {
+                gas_price: self.get_gas_price().compat().await?,
+            }),
+        }
+    }
+
+    /// Returns the gas price policy of the swap transactions:
+    /// the legacy gas price unless [`EthCoinImpl::swap_txs_eip1559`] is set.
+    async fn get_swap_pay_for_gas_option(&self) -> Web3RpcResult<PayForGasOption> {
+        if self.swap_txs_eip1559 {
+            return self.get_pay_for_gas_option().await;
+        }
+        Ok(PayForGasOption::Legacy {
+            gas_price: self.get_gas_price().compat().await?,
+        })
+    }
+
     /// Checks every second till at least one ETH node recognizes that nonce is increased.
     /// Parity has reliable "nextNonce" method that always returns correct nonce for address.
     /// But we can't expect that all nodes will always be Parity.
@@ -4342,7 +3912,7 @@ impl EthCoin {
         while now_ms() < wait_until {
             let maybe_tx = self.web3.eth().transaction(TransactionId::Hash(tx_hash)).await?;
             if let Some(tx) = maybe_tx {
-                let signed_tx = signed_tx_from_web3_tx(tx).map_to_mm(Web3RpcError::InvalidResponse)?;
+                let signed_tx = signed_tx_from_web3_tx(tx, self.chain_id).map_to_mm(Web3RpcError::InvalidResponse)?;
                 return Ok(Some(signed_tx));
             }
 
@@ -4443,17 +4013,36 @@ impl EthCoin {
 pub struct EthTxFeeDetails {
     pub coin: String,
     pub gas: u64,
-    /// WEI units per 1 gas
+    /// WEI units per 1 gas.
+    /// The max fee per gas in the case of an EIP-1559 transaction.
     pub gas_price: BigDecimal,
+    /// WEI units per 1 gas, set for EIP-1559 transactions only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<BigDecimal>,
+    /// WEI units per 1 gas, set for EIP-1559 transactions only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<BigDecimal>,
+    /// The upper bound of the fee in the case of an EIP-1559 transaction.
     pub total_fee: BigDecimal,
 }
 
 impl EthTxFeeDetails {
-    fn new(gas: U256, gas_price: U256, coin: &str) -> NumConversResult<EthTxFeeDetails> {
+    fn new(gas: U256, pay_for_gas_option: PayForGasOption, coin: &str) -> NumConversResult<EthTxFeeDetails> {
+        let gas_price = pay_for_gas_option.max_fee_per_gas();
         let total_fee = gas * gas_price;
         // Fees are always paid in ETH, can use 18 decimals by default
         let total_fee = u256_to_big_decimal(total_fee, ETH_DECIMALS)?;
         let gas_price = u256_to_big_decimal(gas_price, ETH_DECIMALS)?;
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match pay_for_gas_option {
+            PayForGasOption::Legacy { .. } => (None, None),
+            PayForGasOption::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => (
+                Some(u256_to_big_decimal(max_fee_per_gas, ETH_DECIMALS)?),
+                Some(u256_to_big_decimal(max_priority_fee_per_gas, ETH_DECIMALS)?),
+            ),
+        };
 
         let gas_u64 = u64::try_from(gas).map_to_mm(|e| NumConversError::new(e.to_string()))?;
 
@@ -4461,6 +4050,8 @@ impl EthTxFeeDetails {
             coin: coin.to_owned(),
             gas: gas_u64,
             gas_price,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             total_fee,
         })
     }
@@ -4518,26 +4109,9 @@ impl MmCoin for EthCoin {
         }
     }
 
-    fn process_history_loop(&self, ctx: MmArc) -> Box<dyn Future<Item = (), Error = ()> + Send> {
-        cfg_wasm32! {
-            ctx.log.log(
-                "🤔",
-                &[&"tx_history", &self.ticker],
-                &ERRL!("Transaction history is not supported for ETH/ERC20 coins"),
-            );
-            Box::new(futures01::future::ok(()))
-        }
-        cfg_native! {
-            let coin = self.clone();
-            let fut = async move {
-                match coin.coin_type {
-                    EthCoinType::Eth => coin.process_eth_history(&ctx).await,
-                    EthCoinType::Erc20 { ref token_addr, .. } => coin.process_erc20_history(*token_addr, &ctx).await,
-                }
-                Ok(())
-            };
-            Box::new(fut.boxed().compat())
-        }
+    fn process_history_loop(&self, _ctx: MmArc) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+        warn!("'process_history_loop' is not implemented for EthCoin! Consider using 'my_tx_history_v2'");
+        Box::new(futures01::future::err(()))
     }
 
     fn history_sync_status(&self) -> HistorySyncState { self.history_sync_state.lock().unwrap().clone() }
@@ -4567,8 +4141,10 @@ impl MmCoin for EthCoin {
         value: TradePreimageValue,
         stage: FeeApproxStage,
     ) -> TradePreimageResult<TradeFee> {
-        let gas_price = self.get_gas_price().compat().await?;
-        let gas_price = increase_gas_price_by_stage(gas_price, &stage);
+        // Swap transactions may be sent as EIP-1559 ones if `swap_txs_eip1559` is set,
+        // so the fee is estimated by `max_fee_per_gas` that the transactions may pay at most.
+        let pay_for_gas_option = self.get_swap_pay_for_gas_option().await?.increase_by_stage(&stage);
+        let gas_price = pay_for_gas_option.max_fee_per_gas();
         let gas_limit = match self.coin_type {
             EthCoinType::Eth => {
                 // this gas_limit includes gas for `ethPayment` and `senderRefund` contract calls
@@ -4581,7 +4157,14 @@ impl MmCoin for EthCoin {
                     },
                 };
                 let allowed = self.allowance(self.swap_contract_address).compat().await?;
-                if allowed < value {
+                // The permit support is checked only if the allowance is not enough, since it requires extra RPC calls.
+                // A permit is not signed here, since anyone could submit it if it leaked.
+                let pays_with_permit =
+                    allowed < value && self.contract_supports_permit && self.erc20_may_support_permit(token_addr).await;
+                if pays_with_permit {
+                    // this gas_limit includes gas for `erc20PaymentWithPermit` and `senderRefund` contract calls
+                    U256::from(300_000 + PERMIT_GAS)
+                } else if allowed < value {
                     // estimate gas for the `approve` contract call
 
                     // Pass a dummy spender. Let's use `my_address`.
@@ -4618,9 +4201,8 @@ impl MmCoin for EthCoin {
     fn get_receiver_trade_fee(&self, stage: FeeApproxStage) -> TradePreimageFut<TradeFee> {
         let coin = self.clone();
         let fut = async move {
-            let gas_price = coin.get_gas_price().compat().await?;
-            let gas_price = increase_gas_price_by_stage(gas_price, &stage);
-            let total_fee = gas_price * U256::from(ETH_GAS);
+            let pay_for_gas_option = coin.get_swap_pay_for_gas_option().await?.increase_by_stage(&stage);
+            let total_fee = pay_for_gas_option.max_fee_per_gas() * U256::from(ETH_GAS);
             let amount = u256_to_big_decimal(total_fee, ETH_DECIMALS)?;
             let fee_coin = match &coin.coin_type {
                 EthCoinType::Eth => &coin.ticker,
@@ -4654,24 +4236,21 @@ impl MmCoin for EthCoin {
             },
         };
 
-        let gas_price = self.get_gas_price().compat().await?;
-        let gas_price = increase_gas_price_by_stage(gas_price, &stage);
-        let estimate_gas_req = CallRequest {
+        let pay_for_gas_option = self.get_swap_pay_for_gas_option().await?.increase_by_stage(&stage);
+        let mut estimate_gas_req = CallRequest {
             value: Some(eth_value),
             data: Some(data.clone().into()),
             from: Some(self.my_address),
             to: Some(*call_addr),
             gas: None,
-            // gas price must be supplied because some smart contracts base their
-            // logic on gas price, e.g. TUSD: https://github.com/KomodoPlatform/atomicDEX-API/issues/643
-            gas_price: Some(gas_price),
             ..CallRequest::default()
         };
+        pay_for_gas_option.set_to_call_request(&mut estimate_gas_req);
 
         // Please note if the wallet's balance is insufficient to withdraw, then `estimate_gas` may fail with the `Exception` error.
         // Ideally we should determine the case when we have the insufficient balance and return `TradePreimageError::NotSufficientBalance` error.
         let gas_limit = self.estimate_gas(estimate_gas_req).compat().await?;
-        let total_fee = gas_limit * gas_price;
+        let total_fee = gas_limit * pay_for_gas_option.max_fee_per_gas();
         let amount = u256_to_big_decimal(total_fee, ETH_DECIMALS)?;
         Ok(TradeFee {
             coin: fee_coin.into(),
@@ -4964,18 +4543,79 @@ pub fn wei_from_big_decimal(amount: &BigDecimal, decimals: u8) -> NumConversResu
 }
 
 impl Transaction for SignedEthTx {
-    fn tx_hex(&self) -> Vec<u8> { rlp::encode(self).to_vec() }
+    fn tx_hex(&self) -> Vec<u8> {
+        match self {
+            SignedEthTx::Legacy(tx) => rlp::encode(tx).to_vec(),
+            SignedEthTx::Eip1559(tx) => tx.transaction.raw(),
+        }
+    }
 
-    fn tx_hash(&self) -> BytesJson { self.hash.0.to_vec().into() }
+    fn tx_hash(&self) -> BytesJson { self.hash().0.to_vec().into() }
 }
 
-fn signed_tx_from_web3_tx(transaction: Web3Transaction) -> Result<SignedEthTx, String> {
+/// Converts the transaction returned by the RPC node.
+/// `chain_id` is required to restore an EIP-1559 transaction, since it's not returned by the node.
+fn signed_tx_from_web3_tx(transaction: Web3Transaction, chain_id: Option<u64>) -> Result<SignedEthTx, String> {
     let r = transaction.r.ok_or_else(|| ERRL!("'Transaction::r' is not set"))?;
     let s = transaction.s.ok_or_else(|| ERRL!("'Transaction::s' is not set"))?;
     let v = transaction
         .v
         .ok_or_else(|| ERRL!("'Transaction::v' is not set"))?
         .as_u64();
+    let action = match transaction.to {
+        Some(addr) => Action::Call(addr),
+        None => Action::Create,
+    };
+
+    match transaction.transaction_type.map(|tx_type| tx_type.as_u64()) {
+        None | Some(0) => (),
+        Some(tx_type) if tx_type == EIP1559_TX_TYPE as u64 => {
+            let chain_id =
+                chain_id.ok_or_else(|| ERRL!("'chain_id' is required to restore an EIP-1559 transaction"))?;
+            let max_fee_per_gas = transaction
+                .max_fee_per_gas
+                .ok_or_else(|| ERRL!("'Transaction::max_fee_per_gas' is not set"))?;
+            let max_priority_fee_per_gas = transaction
+                .max_priority_fee_per_gas
+                .ok_or_else(|| ERRL!("'Transaction::max_priority_fee_per_gas' is not set"))?;
+            let unsigned = Eip1559Transaction {
+                chain_id,
+                nonce: transaction.nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas: transaction.gas,
+                action,
+                value: transaction.value,
+                data: transaction.input.0,
+                access_list: transaction.access_list.unwrap_or_default(),
+            };
+            if v > 1 {
+                return ERR!("Invalid y_parity {} of the EIP-1559 transaction", v);
+            }
+            let mut r_bytes = [0u8; 32];
+            r.to_big_endian(&mut r_bytes);
+            let mut s_bytes = [0u8; 32];
+            s.to_big_endian(&mut s_bytes);
+            let signed = SignedEip1559Transaction {
+                unsigned,
+                y_parity: v as u8,
+                r: H256::from(r_bytes),
+                s: H256::from(s_bytes),
+            };
+            let verified = try_s!(VerifiedEip1559Transaction::new(signed));
+            // The hash doesn't match if the transaction fields are restored incorrectly, e.g. `chain_id`.
+            if verified.hash != transaction.hash {
+                return ERR!(
+                    "Restored transaction hash {:02x} doesn't match the expected {:02x}",
+                    verified.hash,
+                    transaction.hash
+                );
+            }
+            return Ok(SignedEthTx::Eip1559(verified));
+        },
+        Some(tx_type) => return ERR!("Unsupported transaction type {}", tx_type),
+    }
+
     let gas_price = transaction
         .gas_price
         .ok_or_else(|| ERRL!("'Transaction::gas_price' is not set"))?;
@@ -4991,14 +4631,11 @@ fn signed_tx_from_web3_tx(transaction: Web3Transaction) -> Result<SignedEthTx, S
             gas: transaction.gas,
             value: transaction.value,
             nonce: transaction.nonce,
-            action: match transaction.to {
-                Some(addr) => Action::Call(addr),
-                None => Action::Create,
-            },
+            action,
         },
     };
 
-    Ok(try_s!(SignedEthTx::new(unverified)))
+    Ok(SignedEthTx::Legacy(try_s!(SignedLegacyEthTx::new(unverified))))
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -5115,6 +4752,33 @@ pub fn decode_contract_call(function: &Function, contract_call_bytes: &[u8]) ->
     function.decode_input(&contract_call_bytes[4..])
 }
 
+/// Decodes the swap payment call. If `payment_func` is `erc20Payment`, the `erc20PaymentWithPermit` call is accepted too.
+/// The permit variant starts with the same arguments, so the decoded tokens are indexed the same way.
+fn decode_payment_call(payment_func: &Function, contract_call_bytes: &[u8]) -> Result<Vec<Token>, ethabi::Error> {
+    let permit_func = SWAP_CONTRACT.function("erc20PaymentWithPermit")?;
+    if payment_func.name == "erc20Payment" && contract_call_bytes.starts_with(&permit_func.short_signature()) {
+        return decode_contract_call(permit_func, contract_call_bytes);
+    }
+    decode_contract_call(payment_func, contract_call_bytes)
+}
+
+/// Spawns the periodic health checks of the coin's nodes, so the requests are routed to the healthy ones.
+/// The nodes behind the others by more than the `max_block_lag` coin config are quarantined.
+/// Tokens sharing the platform coin's transport don't need their own checks.
+fn spawn_nodes_health_check_loop(coin: &EthCoinImpl, conf: &Json) {
+    match coin.web3.transport() {
+        Web3Transport::Http(http) => {
+            http.set_max_block_lag(conf["max_block_lag"].as_u64().unwrap_or(DEFAULT_MAX_BLOCK_LAG));
+            let settings = AbortSettings::info_on_abort(format!("nodes_health_check_loop stopped for {}", coin.ticker));
+            coin.abortable_system
+                .weak_spawner()
+                .spawn_with_settings(nodes_health_check_loop(http.clone()), settings);
+        },
+        #[cfg(target_arch = "wasm32")]
+        Web3Transport::Metamask(_) => (),
+    }
+}
+
 fn rpc_event_handlers_for_eth_transport(ctx: &MmArc, ticker: String) -> Vec<RpcTransportEventHandlerShared> {
     let metrics = ctx.metrics.weak();
     vec![CoinTransportMetrics::new(metrics, ticker, RpcClientType::Ethereum).into_shared()]
@@ -5161,6 +4825,7 @@ pub async fn eth_coin_from_conf_and_request(
         }
     }
     let contract_supports_watchers = req["contract_supports_watchers"].as_bool().unwrap_or_default();
+    let contract_supports_permit = req["contract_supports_permit"].as_bool().unwrap_or_default();
 
     let (my_address, key_pair) = try_s!(build_address_and_priv_key_policy(conf, priv_key_policy).await);
 
@@ -5247,11 +4912,13 @@ pub async fn eth_coin_from_conf_and_request(
     let coin = EthCoinImpl {
         priv_key_policy: key_pair,
         my_address,
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(my_address))),
         coin_type,
         sign_message_prefix,
         swap_contract_address,
         fallback_swap_contract,
         contract_supports_watchers,
+        contract_supports_permit,
         decimals,
         ticker: ticker.into(),
         gas_station_url: try_s!(json::from_value(req["gas_station_url"].clone())),
@@ -5263,17 +4930,21 @@ pub async fn eth_coin_from_conf_and_request(
         ctx: ctx.weak(),
         required_confirmations,
         chain_id: conf["chain_id"].as_u64(),
+        name_registry: try_s!(name_resolution::name_registry_from_conf(conf)),
         logs_block_range: conf["logs_block_range"].as_u64().unwrap_or(DEFAULT_LOGS_BLOCK_RANGE),
         nonce_lock,
+        exact_swap_approvals: conf["exact_swap_approvals"].as_bool().unwrap_or_default(),
+        swap_txs_eip1559: conf["swap_txs_eip1559"].as_bool().unwrap_or_default(),
         erc20_tokens_infos: Default::default(),
         abortable_system,
     };
+    spawn_nodes_health_check_loop(&coin, conf);
     Ok(EthCoin(Arc::new(coin)))
 }
 
 /// Displays the address in mixed-case checksum form
 /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-55.md
-fn checksum_address(addr: &str) -> String {
+pub fn checksum_address(addr: &str) -> String {
     let mut addr = addr.to_lowercase();
     if addr.starts_with("0x") {
         addr.replace_range(..2, "");
@@ -5369,6 +5040,52 @@ fn get_addr_nonce(
     Box::new(Box::pin(fut).compat())
 }
 
+/// The transaction counts of an address.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AddrNonces {
+    /// The number of the mined transactions, i.e. the nonce of the first transaction that is not mined yet.
+    pub latest: U256,
+    /// The number of the mined and the mempool transactions, i.e. the nonce of the next transaction.
+    pub pending: U256,
+}
+
+/// Requests the pending transaction count using [`get_addr_nonce`],
+/// then requests the latest transaction count from the nodes that returned the highest pending one.
+#[cfg_attr(test, mockable)]
+fn get_addr_nonces(addr: Address, web3s: Vec<Web3Instance>) -> Web3RpcFut<AddrNonces> {
+    let fut = async move {
+        let (pending, web3s) = get_addr_nonce(addr, web3s)
+            .compat()
+            .await
+            .map_to_mm(Web3RpcError::Transport)?;
+
+        let futures = web3s
+            .iter()
+            .map(|web3| web3.web3.eth().transaction_count(addr, Some(BlockNumber::Latest)));
+        let mut latest = None;
+        let mut last_error = None;
+        for latest_res in join_all(futures).await {
+            match latest_res {
+                Ok(nonce) => latest = latest.max(Some(nonce)),
+                Err(e) => {
+                    error!("Error getting the latest nonce for addr {:?}: {}", addr, e);
+                    last_error = Some(e);
+                },
+            }
+        }
+        match (latest, last_error) {
+            // The pending count can't be less than the latest one, but the nodes may be inconsistent with each other.
+            (Some(latest), _) => Ok(AddrNonces {
+                latest,
+                pending: pending.max(latest),
+            }),
+            (None, Some(e)) => MmError::err(Web3RpcError::from(e)),
+            (None, None) => MmError::err(Web3RpcError::Internal("No web3 instances available".to_owned())),
+        }
+    };
+    Box::new(fut.boxed().compat())
+}
+
 fn increase_by_percent_one_gwei(num: U256, percent: u64) -> U256 {
     let one_gwei = U256::from(10u64.pow(9));
     let percent = (num / U256::from(100)) * U256::from(percent);
@@ -5447,9 +5164,8 @@ pub enum GetValidEthWithdrawAddError {
 
 fn get_valid_nft_add_to_withdraw(
     coin_enum: MmCoinEnum,
-    to: &str,
     token_add: &str,
-) -> MmResult<(Address, Address, EthCoin), GetValidEthWithdrawAddError> {
+) -> MmResult<(Address, EthCoin), GetValidEthWithdrawAddError> {
     let eth_coin = match coin_enum {
         MmCoinEnum::EthCoin(eth_coin) => eth_coin,
         _ => {
@@ -5458,9 +5174,8 @@ fn get_valid_nft_add_to_withdraw(
             })
         },
     };
-    let to_addr = valid_addr_from_str(to).map_err(GetValidEthWithdrawAddError::InvalidAddress)?;
     let token_addr = addr_from_str(token_add).map_err(GetValidEthWithdrawAddError::InvalidAddress)?;
-    Ok((to_addr, token_addr, eth_coin))
+    Ok((token_addr, eth_coin))
 }
 
 #[derive(Clone, Debug, Deserialize, Display, EnumFromStringify, PartialEq, Serialize)]
@@ -5487,8 +5202,10 @@ impl From<Web3RpcError> for EthGasDetailsErr {
     }
 }
 
+/// Returns the gas limit and the gas price policy of a transaction sent from `my_address`.
 async fn get_eth_gas_details(
     eth_coin: &EthCoin,
+    my_address: Address,
     fee: Option<WithdrawFee>,
     eth_value: U256,
     data: Bytes,
@@ -5498,35 +5215,57 @@ async fn get_eth_gas_details(
     match fee {
         Some(WithdrawFee::EthGas { gas_price, gas }) => {
             let gas_price = wei_from_big_decimal(&gas_price, 9)?;
-            Ok((gas.into(), gas_price))
+            Ok((gas.into(), PayForGasOption::Legacy { gas_price }))
+        },
+        Some(WithdrawFee::EthGasEip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            gas,
+        }) => {
+            if eth_coin.chain_id.is_none() {
+                let error = format!(
+                    "'chain_id' is not set for {}, EIP-1559 fees are not supported",
+                    eth_coin.ticker
+                );
+                return MmError::err(EthGasDetailsErr::InvalidFeePolicy(error));
+            }
+            let max_fee_per_gas = wei_from_big_decimal(&max_fee_per_gas, 9)?;
+            let max_priority_fee_per_gas = wei_from_big_decimal(&max_priority_fee_per_gas, 9)?;
+            if max_priority_fee_per_gas > max_fee_per_gas {
+                let error = "'max_priority_fee_per_gas' must not exceed 'max_fee_per_gas'".to_owned();
+                return MmError::err(EthGasDetailsErr::InvalidFeePolicy(error));
+            }
+            let pay_for_gas_option = PayForGasOption::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            };
+            Ok((gas.into(), pay_for_gas_option))
         },
         Some(fee_policy) => {
-            let error = format!("Expected 'EthGas' fee type, found {:?}", fee_policy);
+            let error = format!("Expected 'EthGas' or 'EthGasEip1559' fee type, found {:?}", fee_policy);
             MmError::err(EthGasDetailsErr::InvalidFeePolicy(error))
         },
         None => {
-            let gas_price = eth_coin.get_gas_price().compat().await?;
+            let pay_for_gas_option = eth_coin.get_pay_for_gas_option().await?;
             // covering edge case by deducting the standard transfer fee when we want to max withdraw ETH
             let eth_value_for_estimate = if fungible_max && eth_coin.coin_type == EthCoinType::Eth {
-                eth_value - gas_price * U256::from(21000)
+                eth_value - pay_for_gas_option.max_fee_per_gas() * U256::from(21000)
             } else {
                 eth_value
             };
-            let estimate_gas_req = CallRequest {
+            let mut estimate_gas_req = CallRequest {
                 value: Some(eth_value_for_estimate),
                 data: Some(data),
-                from: Some(eth_coin.my_address),
+                from: Some(my_address),
                 to: Some(call_addr),
                 gas: None,
-                // gas price must be supplied because some smart contracts base their
-                // logic on gas price, e.g. TUSD: https://github.com/KomodoPlatform/atomicDEX-API/issues/643
-                gas_price: Some(gas_price),
                 ..CallRequest::default()
             };
+            pay_for_gas_option.set_to_call_request(&mut estimate_gas_req);
             // TODO Note if the wallet's balance is insufficient to withdraw, then `estimate_gas` may fail with the `Exception` error.
             // TODO Ideally we should determine the case when we have the insufficient balance and return `WithdrawError::NotSufficientBalance`.
             let gas_limit = eth_coin.estimate_gas(estimate_gas_req).compat().await?;
-            Ok((gas_limit, gas_price))
+            Ok((gas_limit, pay_for_gas_option))
         },
     }
 }
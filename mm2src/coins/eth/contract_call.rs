@@ -0,0 +1,191 @@
+//! Calling arbitrary contract functions described by an ABI fragment.
+
+use super::*;
+use crate::rpc_command::eth_contract_call::{EthContractCallError, EthContractCallResult};
+use ethabi::token::{LenientTokenizer, Tokenizer};
+use ethabi::ParamType;
+
+impl EthCoin {
+    /// Executes `function` as a read-only `eth_call` on the latest block and decodes its outputs.
+    pub async fn call_contract_function(
+        &self,
+        contract: Address,
+        function: &Function,
+        args: &[Json],
+    ) -> EthContractCallResult<Vec<Json>> {
+        let data = encode_contract_call(function, args)?;
+        let output = self.call_request(contract, None, Some(data.into())).await?;
+        let tokens = function
+            .decode_output(&output.0)
+            .map_to_mm(|e| EthContractCallError::InvalidResponse(e.to_string()))?;
+        Ok(tokens.into_iter().map(token_to_json).collect())
+    }
+
+    /// Signs a transaction calling `function` of the `contract` with the platform coin `amount` attached.
+    /// The gas is estimated if `fee` is not set.
+    ///
+    /// The transaction is broadcasted if `broadcast` is true.
+    pub async fn sign_contract_call(
+        &self,
+        contract: Address,
+        function: &Function,
+        args: &[Json],
+        amount: &BigDecimal,
+        fee: Option<WithdrawFee>,
+        broadcast: bool,
+    ) -> EthContractCallResult<TransactionDetails> {
+        let data = encode_contract_call(function, args)?;
+        let value = wei_from_big_decimal(amount, ETH_DECIMALS)?;
+        let (gas, pay_for_gas_option) =
+            get_eth_gas_details(self, self.my_address, fee, value, data.clone().into(), contract, false).await?;
+
+        let key_pair = self.priv_key_policy.key_pair_or_err()?;
+        // The lock is held until the transaction is broadcasted, so the nonce can't be taken by another one.
+        let _nonce_lock = self.nonce_lock.lock().await;
+        let (nonce, _) = get_addr_nonce(self.my_address, self.web3_instances.clone())
+            .compat()
+            .timeout_secs(30.)
+            .await
+            .map_to_mm(|e| EthContractCallError::Transport(e.to_string()))?
+            .map_to_mm(EthContractCallError::Transport)?;
+        let (tx_hash, tx_hex) = sign_transaction_with_pay_for_gas(
+            self,
+            key_pair.secret(),
+            nonce,
+            gas,
+            pay_for_gas_option,
+            contract,
+            value,
+            data,
+        )
+        .mm_err(|e| EthContractCallError::InternalError(e.to_string()))?;
+
+        if broadcast {
+            self.send_raw_tx_bytes(&tx_hex.0)
+                .compat()
+                .await
+                .map_to_mm(EthContractCallError::Transport)?;
+            self.wait_for_addr_nonce_increase(self.my_address, nonce).await;
+        }
+
+        let fee_details = EthTxFeeDetails::new(gas, pay_for_gas_option, self.platform_ticker())?;
+        let spent_by_me = amount + &fee_details.total_fee;
+        Ok(TransactionDetails {
+            to: vec![checksum_address(&format!("{:#02x}", contract))],
+            from: vec![self.my_address()?],
+            total_amount: amount.clone(),
+            my_balance_change: -spent_by_me.clone(),
+            spent_by_me,
+            received_by_me: 0.into(),
+            tx_hex,
+            tx_hash: format!("{:02x}", BytesJson::from(tx_hash.0.to_vec())),
+            block_height: 0,
+            fee_details: Some(fee_details.into()),
+            coin: self.platform_ticker().to_owned(),
+            internal_id: vec![].into(),
+            timestamp: now_sec(),
+            kmd_rewards: None,
+            transaction_type: Default::default(),
+            memo: None,
+            psbt: None,
+            to_name: None,
+        })
+    }
+}
+
+fn encode_contract_call(function: &Function, args: &[Json]) -> EthContractCallResult<Vec<u8>> {
+    if args.len() != function.inputs.len() {
+        return MmError::err(EthContractCallError::InvalidArguments(format!(
+            "'{}' expects {} arguments, found {}",
+            function.name,
+            function.inputs.len(),
+            args.len()
+        )));
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            tokenize_arg(&param.kind, arg)
+                .map_to_mm(|e| EthContractCallError::InvalidArguments(format!("'{}': {}", param.name, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    function
+        .encode_input(&tokens)
+        .map_to_mm(|e| EthContractCallError::InvalidArguments(e.to_string()))
+}
+
+/// Converts a JSON argument to the token of the given type.
+/// Arrays and tuples are expected as JSON arrays, numbers either as JSON numbers or as decimal strings,
+/// addresses and bytes as hex strings with or without the `0x` prefix.
+pub(super) fn tokenize_arg(kind: &ParamType, arg: &Json) -> Result<Token, String> {
+    match (kind, arg) {
+        (ParamType::Array(item_kind), Json::Array(items)) => items
+            .iter()
+            .map(|item| tokenize_arg(item_kind, item))
+            .collect::<Result<_, _>>()
+            .map(Token::Array),
+        (ParamType::FixedArray(item_kind, len), Json::Array(items)) => {
+            if items.len() != *len {
+                return ERR!("Expected {} items, found {}", len, items.len());
+            }
+            items
+                .iter()
+                .map(|item| tokenize_arg(item_kind, item))
+                .collect::<Result<_, _>>()
+                .map(Token::FixedArray)
+        },
+        (ParamType::Tuple(kinds), Json::Array(items)) => {
+            if items.len() != kinds.len() {
+                return ERR!("Expected {} tuple items, found {}", kinds.len(), items.len());
+            }
+            kinds
+                .iter()
+                .zip(items)
+                .map(|(kind, item)| tokenize_arg(kind, item))
+                .collect::<Result<_, _>>()
+                .map(Token::Tuple)
+        },
+        (ParamType::Array(_) | ParamType::FixedArray(..) | ParamType::Tuple(_), _) => {
+            ERR!("Expected a JSON array, found {}", arg)
+        },
+        (_, Json::String(value)) => {
+            let value = match kind {
+                ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+                    value.strip_prefix("0x").unwrap_or(value)
+                },
+                _ => value.as_str(),
+            };
+            LenientTokenizer::tokenize(kind, value).map_err(|e| ERRL!("{}", e))
+        },
+        (_, Json::Number(_) | Json::Bool(_)) => {
+            LenientTokenizer::tokenize(kind, &arg.to_string()).map_err(|e| ERRL!("{}", e))
+        },
+        _ => ERR!("Unexpected argument {}", arg),
+    }
+}
+
+/// Converts a decoded output to JSON.
+/// Integers are represented as decimal strings since they may exceed the JSON number range.
+pub(super) fn token_to_json(token: Token) -> Json {
+    match token {
+        Token::Address(address) => Json::String(checksum_address(&format!("{:#02x}", address))),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => Json::String(format!("0x{}", hex::encode(bytes))),
+        Token::Uint(number) => Json::String(number.to_string()),
+        Token::Int(number) => {
+            // The signed integers are encoded in two's complement.
+            if number.bit(255) {
+                let (abs, _) = (!number).overflowing_add(U256::one());
+                Json::String(format!("-{}", abs))
+            } else {
+                Json::String(number.to_string())
+            }
+        },
+        Token::Bool(value) => Json::Bool(value),
+        Token::String(value) => Json::String(value),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            Json::Array(tokens.into_iter().map(token_to_json).collect())
+        },
+    }
+}
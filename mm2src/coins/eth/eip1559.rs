@@ -0,0 +1,264 @@
+//! EIP-1559 (type-2) transactions: fee estimation based on `eth_feeHistory` and transaction signing.
+//!
+//! https://eips.ethereum.org/EIPS/eip-1559
+
+use super::web3_transport::FeeHistoryResult;
+use bitcrypto::keccak256;
+use ethcore_transaction::Action;
+use ethereum_types::{Address, H256, U256};
+use ethkey::{public_to_address, recover, sign, Public, Secret, Signature};
+use rlp::{DecoderError, Rlp, RlpStream};
+use web3::types::{AccessList, AccessListItem};
+
+/// The EIP-2718 transaction type of EIP-1559 transactions.
+pub(crate) const EIP1559_TX_TYPE: u8 = 0x02;
+/// The number of the latest blocks whose fees are taken into account.
+pub(crate) const FEE_HISTORY_BLOCKS: u64 = 5;
+/// The percentile of the priority fees paid within a block.
+pub(crate) const PRIORITY_FEE_PERCENTILE: f64 = 50.;
+/// `max_fee_per_gas` covers the base fee growing by 12.5% during 6 consecutive full blocks.
+const BASE_FEE_MULTIPLIER: u64 = 2;
+
+/// Fees per gas estimated for a type-2 transaction to be included into one of the next blocks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeePerGasEstimated {
+    /// The base fee of the next block.
+    pub base_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl FeePerGasEstimated {
+    /// Estimates the fees from the `eth_feeHistory` result requested with [`PRIORITY_FEE_PERCENTILE`].
+    /// Returns `None` if the chain doesn't support EIP-1559, i.e. there is no base fee.
+    pub fn from_fee_history(history: &FeeHistoryResult) -> Option<FeePerGasEstimated> {
+        // `baseFeePerGas` includes the base fee of the block next to the newest one.
+        let base_fee_per_gas = *history.base_fee_per_gas.last()?;
+        if base_fee_per_gas.is_zero() {
+            return None;
+        }
+
+        let mut priority_fees: Vec<U256> = history
+            .reward
+            .iter()
+            .flatten()
+            .filter_map(|block_rewards| block_rewards.first().copied())
+            .collect();
+        priority_fees.sort();
+        let max_priority_fee_per_gas = priority_fees.get(priority_fees.len() / 2).copied().unwrap_or_default();
+
+        let max_fee_per_gas = base_fee_per_gas
+            .saturating_mul(BASE_FEE_MULTIPLIER.into())
+            .saturating_add(max_priority_fee_per_gas);
+        Some(FeePerGasEstimated {
+            base_fee_per_gas,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        })
+    }
+}
+
+/// An unsigned EIP-1559 transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas: U256,
+    pub action: Action,
+    pub value: U256,
+    pub data: Vec<u8>,
+    /// The transactions signed by us have an empty access list,
+    /// but the ones sent by other wallets may have it.
+    pub access_list: AccessList,
+}
+
+impl Eip1559Transaction {
+    fn rlp_append_unsigned(&self, stream: &mut RlpStream) {
+        stream.append(&self.chain_id);
+        append_u256(stream, self.nonce);
+        append_u256(stream, self.max_priority_fee_per_gas);
+        append_u256(stream, self.max_fee_per_gas);
+        append_u256(stream, self.gas);
+        match self.action {
+            Action::Call(address) => stream.append(&address.as_bytes().to_vec()),
+            Action::Create => stream.append_empty_data(),
+        };
+        append_u256(stream, self.value);
+        stream.append(&self.data);
+        stream.begin_list(self.access_list.len());
+        for item in self.access_list.iter() {
+            stream.begin_list(2);
+            stream.append(&item.address.as_bytes().to_vec());
+            stream.begin_list(item.storage_keys.len());
+            for key in item.storage_keys.iter() {
+                stream.append(&key.as_bytes().to_vec());
+            }
+        }
+    }
+
+    fn rlp_decode_unsigned(rlp: &Rlp) -> Result<Eip1559Transaction, DecoderError> {
+        let to: Vec<u8> = rlp.val_at(5)?;
+        let action = match to.len() {
+            0 => Action::Create,
+            20 => Action::Call(Address::from_slice(&to)),
+            _ => return Err(DecoderError::RlpInvalidLength),
+        };
+        let mut access_list = Vec::new();
+        for item in rlp.at(8)?.iter() {
+            if item.item_count()? != 2 {
+                return Err(DecoderError::RlpIncorrectListLen);
+            }
+            let address: Vec<u8> = item.val_at(0)?;
+            if address.len() != 20 {
+                return Err(DecoderError::RlpInvalidLength);
+            }
+            let storage_keys = item
+                .at(1)?
+                .iter()
+                .map(|key| decode_h256(&key))
+                .collect::<Result<_, _>>()?;
+            access_list.push(AccessListItem {
+                address: Address::from_slice(&address),
+                storage_keys,
+            });
+        }
+        Ok(Eip1559Transaction {
+            chain_id: rlp.val_at(0)?,
+            nonce: decode_u256(&rlp.at(1)?)?,
+            max_priority_fee_per_gas: decode_u256(&rlp.at(2)?)?,
+            max_fee_per_gas: decode_u256(&rlp.at(3)?)?,
+            gas: decode_u256(&rlp.at(4)?)?,
+            action,
+            value: decode_u256(&rlp.at(6)?)?,
+            data: rlp.val_at(7)?,
+            access_list,
+        })
+    }
+
+    /// keccak256(0x02 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas, to, value, data, access_list]))
+    pub fn signature_hash(&self) -> H256 {
+        let mut stream = RlpStream::new_list(9);
+        self.rlp_append_unsigned(&mut stream);
+        H256::from(keccak256(&typed_payload(&stream.out())).take())
+    }
+
+    pub fn sign(self, secret: &Secret) -> Result<SignedEip1559Transaction, ethkey::Error> {
+        let signature = sign(secret, &self.signature_hash())?;
+        Ok(SignedEip1559Transaction {
+            y_parity: signature.v(),
+            r: H256::from_slice(signature.r()),
+            s: H256::from_slice(signature.s()),
+            unsigned: self,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignedEip1559Transaction {
+    pub unsigned: Eip1559Transaction,
+    pub y_parity: u8,
+    pub r: H256,
+    pub s: H256,
+}
+
+impl SignedEip1559Transaction {
+    /// 0x02 || rlp([chain_id, nonce, max_priority_fee_per_gas, max_fee_per_gas, gas, to, value, data, access_list, y_parity, r, s])
+    pub fn raw(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(12);
+        self.unsigned.rlp_append_unsigned(&mut stream);
+        stream.append(&self.y_parity);
+        append_u256(&mut stream, U256::from_big_endian(self.r.as_bytes()));
+        append_u256(&mut stream, U256::from_big_endian(self.s.as_bytes()));
+        typed_payload(&stream.out())
+    }
+
+    pub fn hash(&self) -> H256 { H256::from(keccak256(&self.raw()).take()) }
+
+    /// Decodes the transaction from the [`SignedEip1559Transaction::raw`] bytes.
+    pub fn decode(raw: &[u8]) -> Result<SignedEip1559Transaction, DecoderError> {
+        match raw.first() {
+            Some(&EIP1559_TX_TYPE) => (),
+            _ => return Err(DecoderError::Custom("Expected an EIP-1559 transaction type")),
+        }
+        let rlp = Rlp::new(&raw[1..]);
+        if rlp.item_count()? != 12 {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        let y_parity: u8 = rlp.val_at(9)?;
+        if y_parity > 1 {
+            return Err(DecoderError::Custom("Invalid y_parity"));
+        }
+        Ok(SignedEip1559Transaction {
+            unsigned: Eip1559Transaction::rlp_decode_unsigned(&rlp)?,
+            y_parity,
+            r: H256::from(u256_to_be_bytes(decode_u256(&rlp.at(10)?)?)),
+            s: H256::from(u256_to_be_bytes(decode_u256(&rlp.at(11)?)?)),
+        })
+    }
+
+    /// Recovers the public key that signed the transaction.
+    pub fn recover_public(&self) -> Result<Public, ethkey::Error> {
+        let signature = Signature::from_rsv(&self.r, &self.s, self.y_parity);
+        recover(&signature, &self.unsigned.signature_hash())
+    }
+}
+
+/// A signed EIP-1559 transaction along with its recovered sender.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedEip1559Transaction {
+    pub transaction: SignedEip1559Transaction,
+    pub hash: H256,
+    pub sender: Address,
+    pub public: Public,
+}
+
+impl VerifiedEip1559Transaction {
+    pub fn new(transaction: SignedEip1559Transaction) -> Result<VerifiedEip1559Transaction, ethkey::Error> {
+        let public = transaction.recover_public()?;
+        Ok(VerifiedEip1559Transaction {
+            hash: transaction.hash(),
+            sender: public_to_address(&public),
+            public,
+            transaction,
+        })
+    }
+}
+
+fn typed_payload(rlp: &[u8]) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(rlp.len() + 1);
+    payload.push(EIP1559_TX_TYPE);
+    payload.extend_from_slice(rlp);
+    payload
+}
+
+/// RLP integers are big-endian byte strings without leading zeros.
+fn append_u256(stream: &mut RlpStream, value: U256) {
+    let bytes = u256_to_be_bytes(value);
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    stream.append(&bytes[leading_zeros..].to_vec());
+}
+
+fn decode_u256(rlp: &Rlp) -> Result<U256, DecoderError> {
+    let bytes: Vec<u8> = rlp.as_val()?;
+    match bytes.first() {
+        _ if bytes.len() > 32 => Err(DecoderError::RlpIsTooBig),
+        Some(0) => Err(DecoderError::RlpInvalidIndirection),
+        _ => Ok(U256::from_big_endian(&bytes)),
+    }
+}
+
+fn decode_h256(rlp: &Rlp) -> Result<H256, DecoderError> {
+    let bytes: Vec<u8> = rlp.as_val()?;
+    if bytes.len() != 32 {
+        return Err(DecoderError::RlpInvalidLength);
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+fn u256_to_be_bytes(value: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
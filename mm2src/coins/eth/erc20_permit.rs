@@ -0,0 +1,199 @@
+//! ERC-2612 permits let the swap contract spend an ERC20 payment without a separate `approve` transaction.
+//! https://eips.ethereum.org/EIPS/eip-2612
+
+use super::*;
+use typed_data::TypedData;
+
+/// The extra gas spent by `erc20PaymentWithPermit` to verify the permit signature and update the allowance.
+pub(super) const PERMIT_GAS: u64 = 60_000;
+/// EIP-2612 doesn't specify the version of the EIP-712 domain. Most of the tokens use "1", but USDC uses "2".
+const PERMIT_DOMAIN_VERSIONS: [&str; 2] = ["1", "2"];
+const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// The permit signature along with its `deadline`.
+#[derive(Clone)]
+pub(super) struct Erc20Permit {
+    pub(super) deadline: U256,
+    pub(super) v: u8,
+    pub(super) r: Vec<u8>,
+    pub(super) s: Vec<u8>,
+}
+
+impl Erc20Permit {
+    /// The `erc20PaymentWithPermit` arguments following the `erc20Payment` ones.
+    pub(super) fn into_tokens(self) -> Vec<Token> {
+        vec![
+            Token::Uint(self.deadline),
+            Token::Uint(self.v.into()),
+            Token::FixedBytes(self.r),
+            Token::FixedBytes(self.s),
+        ]
+    }
+}
+
+impl EthCoin {
+    /// Returns the EIP-712 domain `name` and `version` of the token if it implements ERC-2612,
+    /// i.e. if its `DOMAIN_SEPARATOR` is reproduced with one of the known domain versions.
+    pub(super) async fn erc20_permit_domain(&self, token_addr: Address) -> Option<(String, String)> {
+        let chain_id = self.chain_id?;
+        let domain_separator_func = ERC20_PERMIT_CONTRACT.function("DOMAIN_SEPARATOR").ok()?;
+        let domain_separator = match self
+            .call_token_function(token_addr, domain_separator_func, &[])
+            .compat()
+            .await
+            .ok()?
+            .pop()
+        {
+            Some(Token::FixedBytes(domain_separator)) => domain_separator,
+            _ => return None,
+        };
+        let name_func = ERC20_CONTRACT.function("name").ok()?;
+        let name = match self
+            .call_token_function(token_addr, name_func, &[])
+            .compat()
+            .await
+            .ok()?
+            .pop()
+        {
+            Some(Token::String(name)) => name,
+            _ => return None,
+        };
+
+        let version = PERMIT_DOMAIN_VERSIONS
+            .iter()
+            .find(|version| eip712_domain_separator(&name, version, chain_id, token_addr) == domain_separator);
+        if version.is_none() {
+            debug!("Couldn't reproduce the DOMAIN_SEPARATOR of the {:?} token", token_addr);
+        }
+        version.map(|version| (name, version.to_string()))
+    }
+
+    /// Checks if the token looks like an ERC-2612 one without signing a permit.
+    /// Unlike [`EthCoin::sign_erc20_permit`], it doesn't tell apart a token with a non-standard `permit`, e.g. DAI,
+    /// so it's used where a signed permit could leak, e.g. to the RPC node on the fee estimation.
+    pub(super) async fn erc20_may_support_permit(&self, token_addr: Address) -> bool {
+        self.erc20_permit_domain(token_addr).await.is_some() && self.erc20_permit_nonce(token_addr).await.is_ok()
+    }
+
+    /// Signs a permit allowing `spender` to transfer `value` of the token from my address until `deadline`.
+    /// Returns `None` if the token doesn't support ERC-2612 permits or doesn't accept the signed one.
+    pub(super) async fn sign_erc20_permit(
+        &self,
+        token_addr: Address,
+        spender: Address,
+        value: U256,
+        deadline: U256,
+    ) -> Result<Option<Erc20Permit>, String> {
+        let (name, version) = match self.erc20_permit_domain(token_addr).await {
+            Some(domain) => domain,
+            None => return Ok(None),
+        };
+        let nonce = try_s!(self.erc20_permit_nonce(token_addr).await);
+
+        let typed_data: TypedData = try_s!(json::from_value(json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Permit": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ]
+            },
+            "domain": {
+                "name": name,
+                "version": version,
+                "chainId": self.chain_id,
+                "verifyingContract": format!("{:#02x}", token_addr)
+            },
+            "primaryType": "Permit",
+            "message": {
+                "owner": format!("{:#02x}", self.my_address),
+                "spender": format!("{:#02x}", spender),
+                "value": value.to_string(),
+                "nonce": nonce.to_string(),
+                "deadline": deadline.to_string()
+            }
+        })));
+        let (_hash, signature) = try_s!(self.sign_typed_data(typed_data));
+
+        let permit = Erc20Permit {
+            deadline,
+            v: signature[64],
+            r: signature[..32].to_vec(),
+            s: signature[32..64].to_vec(),
+        };
+        if let Err(e) = self.simulate_erc20_permit(token_addr, spender, value, &permit).await {
+            debug!("The {:?} token doesn't accept the ERC-2612 permit: {}", token_addr, e);
+            return Ok(None);
+        }
+        Ok(Some(permit))
+    }
+
+    /// Requests the current permit nonce of my address.
+    async fn erc20_permit_nonce(&self, token_addr: Address) -> Result<U256, String> {
+        let nonces_func = try_s!(ERC20_PERMIT_CONTRACT.function("nonces"));
+        match try_s!(
+            self.call_token_function(token_addr, nonces_func, &[Token::Address(self.my_address)])
+                .compat()
+                .await
+        )
+        .pop()
+        {
+            Some(Token::Uint(nonce)) => Ok(nonce),
+            token => ERR!("Expected Token::Uint, got {:?}", token),
+        }
+    }
+
+    /// Checks if the token accepts the `permit` by estimating the gas of the `permit` call.
+    /// Some tokens implement a `permit` that is not compatible with ERC-2612, e.g. DAI,
+    /// although their `DOMAIN_SEPARATOR` is reproduced with the known domain versions.
+    async fn simulate_erc20_permit(
+        &self,
+        token_addr: Address,
+        spender: Address,
+        value: U256,
+        permit: &Erc20Permit,
+    ) -> Result<(), String> {
+        let permit_func = try_s!(ERC20_PERMIT_CONTRACT.function("permit"));
+        let args: Vec<Token> = vec![
+            Token::Address(self.my_address),
+            Token::Address(spender),
+            Token::Uint(value),
+        ]
+        .into_iter()
+        .chain(permit.clone().into_tokens())
+        .collect();
+        let data = try_s!(permit_func.encode_input(&args));
+        let request = CallRequest {
+            from: Some(self.my_address),
+            to: Some(token_addr),
+            data: Some(data.into()),
+            ..CallRequest::default()
+        };
+        try_s!(self.estimate_gas(request).compat().await);
+        Ok(())
+    }
+}
+
+pub(super) fn eip712_domain_separator(
+    name: &str,
+    version: &str,
+    chain_id: u64,
+    verifying_contract: Address,
+) -> Vec<u8> {
+    let encoded = ethabi::encode(&[
+        Token::FixedBytes(keccak256(EIP712_DOMAIN_TYPE.as_bytes()).take().to_vec()),
+        Token::FixedBytes(keccak256(name.as_bytes()).take().to_vec()),
+        Token::FixedBytes(keccak256(version.as_bytes()).take().to_vec()),
+        Token::Uint(chain_id.into()),
+        Token::Address(verifying_contract),
+    ]);
+    keccak256(&encoded).take().to_vec()
+}
@@ -0,0 +1,582 @@
+//! HD wallet of ETH and ERC20 coins activated with a BIP39 seed phrase.
+//!
+//! Addresses are derived at `m/44'/60'/account'/0/address_index`.
+//! ETH doesn't have change addresses, so only [`Bip44Chain::External`] chain is supported.
+
+use super::*;
+use crate::coin_balance::{self, EnableCoinBalanceError, EnableCoinScanPolicy, EnabledCoinBalanceParams,
+                          HDAccountBalance, HDAddressBalance, HDAddressBalanceScanner, HDWalletBalance,
+                          HDWalletBalanceOps};
+use crate::hd_confirm_address::HDConfirmAddress;
+use crate::hd_pubkey::HDXPubExtractor;
+use crate::hd_wallet::{AccountUpdatingError, AddressDerivingError, AddressDerivingResult, AsyncMutexGuard,
+                       HDAccountMut, HDAccountOps, HDAccountsMap, HDAccountsMutex, HDAddress, HDAddressId,
+                       HDWalletCoinOps, HDWalletOps, InvalidBip44ChainError, NewAccountCreatingError,
+                       NewAddressDeriveConfirmError, NewAddressDerivingError};
+use crate::hd_wallet_storage::{HDAccountStorageItem, HDWalletCoinStorage, HDWalletCoinWithStorageOps,
+                               HDWalletStorageResult};
+use crate::rpc_command::account_balance::{self, AccountBalanceParams, AccountBalanceRpcOps, HDAccountBalanceResponse};
+use crate::rpc_command::get_new_address::{self, GetNewAddressParams, GetNewAddressResponse, GetNewAddressRpcError,
+                                          GetNewAddressRpcOps};
+use crate::rpc_command::hd_account_balance_rpc_error::HDAccountBalanceRpcError;
+use crate::rpc_command::init_account_balance::{self, InitAccountBalanceParams, InitAccountBalanceRpcOps};
+use crate::rpc_command::init_create_account::{self, CreateAccountRpcError, CreateAccountState, CreateNewAccountParams,
+                                              InitCreateAccountRpcOps};
+use crate::rpc_command::init_scan_for_new_addresses::{self, InitScanAddressesRpcOps, ScanAddressesParams,
+                                                      ScanAddressesResponse};
+use crate::utxo::utxo_common;
+use crate::{BalanceResult, CoinWithDerivationMethod, DerivationMethod, GetWithdrawSenderAddress, WithdrawFrom,
+            WithdrawSenderAddress};
+use crypto::{Bip32DerPathOps, Bip44Chain, ChildNumber, DerivationPath, Secp256k1ExtendedPublicKey, StandardHDPath,
+             StandardHDPathError, StandardHDPathToAccount, StandardHDPathToCoin};
+use std::fmt;
+
+pub type EthDerivationMethod = DerivationMethod<ChecksumAddress, EthHDWallet>;
+pub type EthHDAddress = HDAddress<ChecksumAddress, Public>;
+
+/// The default number of empty addresses in a row that are checked when scanning for new addresses.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// An ETH address that is displayed in the mixed-case checksum encoding
+/// as it's done by [`MarketCoinOps::my_address`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ChecksumAddress(pub Address);
+
+impl fmt::Display for ChecksumAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&checksum_address(&format!("{:#02x}", self.0)))
+    }
+}
+
+impl From<Address> for ChecksumAddress {
+    fn from(address: Address) -> Self { ChecksumAddress(address) }
+}
+
+pub struct EthHDWallet {
+    pub hd_wallet_storage: HDWalletCoinStorage,
+    /// Derivation path of the coin.
+    /// This derivation path consists of `purpose` and `coin_type` only
+    /// where the full `BIP44` address has the following structure:
+    /// `m/purpose'/coin_type'/account'/change/address_index`.
+    pub derivation_path: StandardHDPathToCoin,
+    /// User accounts.
+    pub accounts: HDAccountsMutex<EthHDAccount>,
+    // The max number of empty addresses in a row.
+    // If transactions were sent to an address outside the `gap_limit`, they will not be identified.
+    pub gap_limit: u32,
+    /// The seed context is used to derive account extended public keys and to sign transactions
+    /// of any HD wallet address.
+    pub global_hd_ctx: GlobalHDAccountArc,
+}
+
+impl HDWalletOps for EthHDWallet {
+    type HDAccount = EthHDAccount;
+
+    fn coin_type(&self) -> u32 { self.derivation_path.coin_type() }
+
+    fn gap_limit(&self) -> u32 { self.gap_limit }
+
+    fn get_accounts_mutex(&self) -> &HDAccountsMutex<Self::HDAccount> { &self.accounts }
+}
+
+impl EthHDWallet {
+    /// Derives a key pair of the HD wallet address located at the given `derivation_path`.
+    pub fn derive_key_pair(&self, derivation_path: &DerivationPath) -> AddressDerivingResult<KeyPair> {
+        let derivation_path = StandardHDPath::try_from(derivation_path.clone())
+            .map_to_mm(|e| AddressDerivingError::Internal(StandardHDPathError::from(e).to_string()))?;
+        let secret = self.global_hd_ctx.derive_secp256k1_secret_at(&derivation_path)?;
+        KeyPair::from_secret_slice(secret.as_slice()).map_to_mm(|e| AddressDerivingError::Internal(e.to_string()))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EthHDAccount {
+    pub account_id: u32,
+    /// [Extended public key](https://learnmeabitcoin.com/technical/extended-keys) that corresponds to the derivation path:
+    /// `m/purpose'/coin_type'/account'`.
+    pub extended_pubkey: Secp256k1ExtendedPublicKey,
+    /// [`EthHDWallet::derivation_path`] derived by [`EthHDAccount::account_id`].
+    pub account_derivation_path: StandardHDPathToAccount,
+    /// The number of addresses that we know have been used by the user.
+    pub external_addresses_number: u32,
+}
+
+impl HDAccountOps for EthHDAccount {
+    fn known_addresses_number(&self, chain: Bip44Chain) -> MmResult<u32, InvalidBip44ChainError> {
+        match chain {
+            Bip44Chain::External => Ok(self.external_addresses_number),
+            Bip44Chain::Internal => MmError::err(InvalidBip44ChainError { chain }),
+        }
+    }
+
+    fn account_derivation_path(&self) -> DerivationPath { self.account_derivation_path.to_derivation_path() }
+
+    fn account_id(&self) -> u32 { self.account_id }
+}
+
+impl EthHDAccount {
+    pub fn try_from_storage_item(
+        wallet_der_path: &StandardHDPathToCoin,
+        account_info: &HDAccountStorageItem,
+    ) -> HDWalletStorageResult<EthHDAccount> {
+        const ACCOUNT_CHILD_HARDENED: bool = true;
+
+        let account_child = ChildNumber::new(account_info.account_id, ACCOUNT_CHILD_HARDENED)?;
+        let account_derivation_path = wallet_der_path
+            .derive(account_child)
+            .map_to_mm(StandardHDPathError::from)?;
+        let extended_pubkey = Secp256k1ExtendedPublicKey::from_str(&account_info.account_xpub)?;
+        Ok(EthHDAccount {
+            account_id: account_info.account_id,
+            extended_pubkey,
+            account_derivation_path,
+            external_addresses_number: account_info.external_addresses_number,
+        })
+    }
+
+    pub fn to_storage_item(&self) -> HDAccountStorageItem {
+        HDAccountStorageItem {
+            account_id: self.account_id,
+            account_xpub: self.extended_pubkey.to_string(bip32::Prefix::XPUB),
+            external_addresses_number: self.external_addresses_number,
+            internal_addresses_number: 0,
+        }
+    }
+
+    /// Derives an address of this account at the given `chain` and `address_id`.
+    pub fn derive_address(&self, chain: Bip44Chain, address_id: u32) -> AddressDerivingResult<EthHDAddress> {
+        if let Bip44Chain::Internal = chain {
+            return MmError::err(AddressDerivingError::InvalidBip44Chain { chain });
+        }
+        let change_child = chain.to_child_number();
+        let address_id_child = ChildNumber::from(address_id);
+
+        let derived_pubkey = self
+            .extended_pubkey
+            .derive_child(change_child)?
+            .derive_child(address_id_child)?;
+        // Skip the `0x04` prefix of the uncompressed public key.
+        let pubkey = Public::from_slice(&derived_pubkey.public_key().serialize_uncompressed()[1..]);
+
+        let mut derivation_path = self.account_derivation_path.to_derivation_path();
+        derivation_path.push(change_child);
+        derivation_path.push(address_id_child);
+        Ok(HDAddress {
+            address: ChecksumAddress(public_to_address(&pubkey)),
+            pubkey,
+            derivation_path,
+        })
+    }
+}
+
+pub(crate) async fn load_hd_accounts_from_storage(
+    hd_wallet_storage: &HDWalletCoinStorage,
+    derivation_path: &StandardHDPathToCoin,
+) -> HDWalletStorageResult<HDAccountsMap<EthHDAccount>> {
+    let accounts = hd_wallet_storage.load_all_accounts().await?;
+    let res: HDWalletStorageResult<HDAccountsMap<EthHDAccount>> = accounts
+        .iter()
+        .map(|account_info| {
+            let account = EthHDAccount::try_from_storage_item(derivation_path, account_info)?;
+            Ok((account.account_id, account))
+        })
+        .collect();
+    match res {
+        Ok(accounts) => Ok(accounts),
+        Err(e) if e.get_inner().is_deserializing_err() => {
+            warn!("Error loading HD accounts from the storage: '{}'. Clear accounts", e);
+            hd_wallet_storage.clear_accounts().await?;
+            Ok(HDAccountsMap::new())
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Creates a new HD account.
+/// Unlike UTXO, the account extended public key is derived from the seed directly,
+/// so there is no need to extract it with an [`HDXPubExtractor`].
+async fn create_new_account<'a>(
+    coin: &EthCoin,
+    hd_wallet: &'a EthHDWallet,
+) -> MmResult<HDAccountMut<'a, EthHDAccount>, NewAccountCreatingError> {
+    const INIT_ACCOUNT_ID: u32 = 0;
+    let new_account_id = hd_wallet
+        .accounts
+        .lock()
+        .await
+        .iter()
+        // The last element of the BTreeMap has the max account index.
+        .last()
+        .map(|(account_id, _account)| *account_id + 1)
+        .unwrap_or(INIT_ACCOUNT_ID);
+    let max_accounts_number = hd_wallet.account_limit();
+    if new_account_id >= max_accounts_number {
+        return MmError::err(NewAccountCreatingError::AccountLimitReached { max_accounts_number });
+    }
+
+    let account_child_hardened = true;
+    let account_child = ChildNumber::new(new_account_id, account_child_hardened)
+        .map_to_mm(|e| NewAccountCreatingError::Internal(e.to_string()))?;
+
+    let account_derivation_path: StandardHDPathToAccount = hd_wallet.derivation_path.derive(account_child)?;
+    let account_pubkey = hd_wallet
+        .global_hd_ctx
+        .derive_secp256k1_extended_pubkey(account_derivation_path.to_derivation_path())
+        .mm_err(|e| NewAccountCreatingError::Internal(e.to_string()))?;
+
+    let new_account = EthHDAccount {
+        account_id: new_account_id,
+        extended_pubkey: account_pubkey,
+        account_derivation_path,
+        // We don't know how many addresses are used by the user at this moment.
+        external_addresses_number: 0,
+    };
+
+    let accounts = hd_wallet.accounts.lock().await;
+    if accounts.contains_key(&new_account_id) {
+        let error = format!(
+            "Account '{}' has been activated while we proceed the 'create_new_account' function",
+            new_account_id
+        );
+        return MmError::err(NewAccountCreatingError::Internal(error));
+    }
+
+    coin.upload_new_account(hd_wallet, new_account.to_storage_item())
+        .await?;
+
+    Ok(AsyncMutexGuard::map(accounts, |accounts| {
+        accounts
+            .entry(new_account_id)
+            // the `entry` method should return [`Entry::Vacant`] due to the checks above
+            .or_insert(new_account)
+    }))
+}
+
+impl EthCoin {
+    /// Enables the HD wallet on the coin activation.
+    /// Creates the default HD account if the HD wallet hasn't been enabled before,
+    /// scans for new addresses if it's prescribed by [`EnabledCoinBalanceParams::scan_policy`],
+    /// and returns known addresses of every HD account.
+    ///
+    /// Unlike [`HDWalletBalanceOps::enable_hd_wallet`], it doesn't request balances,
+    /// since they are requested for the platform coin and every activated token separately.
+    pub async fn enable_hd_wallet_addresses(
+        &self,
+        hd_wallet: &EthHDWallet,
+        params: &EnabledCoinBalanceParams,
+    ) -> MmResult<Vec<EthHDAddress>, EnableCoinBalanceError> {
+        let mut accounts = hd_wallet.get_accounts_mut().await;
+        let scan_new_addresses = if accounts.is_empty() {
+            drop(accounts);
+            info!(
+                "{} HD wallet hasn't been enabled before. Create default HD account",
+                self.ticker()
+            );
+            create_new_account(self, hd_wallet).await?;
+            accounts = hd_wallet.get_accounts_mut().await;
+            matches!(
+                params.scan_policy,
+                EnableCoinScanPolicy::ScanIfNewWallet | EnableCoinScanPolicy::Scan
+            )
+        } else {
+            matches!(params.scan_policy, EnableCoinScanPolicy::Scan)
+        };
+
+        let mut addresses = Vec::new();
+        for hd_account in accounts.values_mut() {
+            if scan_new_addresses {
+                self.scan_for_new_addresses(hd_wallet, hd_account, self, hd_wallet.gap_limit())
+                    .await?;
+            }
+            if let Some(min_addresses_number) = params.min_addresses_number {
+                while hd_account.external_addresses_number < min_addresses_number {
+                    self.generate_new_address(hd_wallet, hd_account, Bip44Chain::External)
+                        .await?;
+                }
+            }
+            let account_addresses = self
+                .derive_known_addresses(hd_account, Bip44Chain::External)
+                .await
+                .mm_err(NewAddressDerivingError::from)?;
+            addresses.extend(account_addresses);
+        }
+        Ok(addresses)
+    }
+}
+
+#[async_trait]
+impl HDWalletCoinOps for EthCoin {
+    type Address = ChecksumAddress;
+    type Pubkey = Public;
+    type HDWallet = EthHDWallet;
+    type HDAccount = EthHDAccount;
+
+    async fn derive_addresses<Ids>(
+        &self,
+        hd_account: &Self::HDAccount,
+        address_ids: Ids,
+    ) -> AddressDerivingResult<Vec<HDAddress<Self::Address, Self::Pubkey>>>
+    where
+        Ids: Iterator<Item = HDAddressId> + Send,
+    {
+        address_ids
+            .map(|HDAddressId { chain, address_id }| hd_account.derive_address(chain, address_id))
+            .collect()
+    }
+
+    /// There is no hardware wallet to confirm the address on, so the address is just generated.
+    async fn generate_and_confirm_new_address<ConfirmAddress>(
+        &self,
+        hd_wallet: &Self::HDWallet,
+        hd_account: &mut Self::HDAccount,
+        chain: Bip44Chain,
+        _confirm_address: &ConfirmAddress,
+    ) -> MmResult<HDAddress<Self::Address, Self::Pubkey>, NewAddressDeriveConfirmError>
+    where
+        ConfirmAddress: HDConfirmAddress,
+    {
+        Ok(self.generate_new_address(hd_wallet, hd_account, chain).await?)
+    }
+
+    async fn create_new_account<'a, XPubExtractor>(
+        &self,
+        hd_wallet: &'a Self::HDWallet,
+        _xpub_extractor: &XPubExtractor,
+    ) -> MmResult<HDAccountMut<'a, Self::HDAccount>, NewAccountCreatingError>
+    where
+        XPubExtractor: HDXPubExtractor,
+    {
+        create_new_account(self, hd_wallet).await
+    }
+
+    async fn set_known_addresses_number(
+        &self,
+        hd_wallet: &Self::HDWallet,
+        hd_account: &mut Self::HDAccount,
+        chain: Bip44Chain,
+        new_known_addresses_number: u32,
+    ) -> MmResult<(), AccountUpdatingError> {
+        let max_addresses_number = hd_wallet.address_limit();
+        if new_known_addresses_number >= max_addresses_number {
+            return MmError::err(AccountUpdatingError::AddressLimitReached { max_addresses_number });
+        }
+        match chain {
+            Bip44Chain::External => {
+                self.update_external_addresses_number(hd_wallet, hd_account.account_id, new_known_addresses_number)
+                    .await?;
+                hd_account.external_addresses_number = new_known_addresses_number;
+            },
+            Bip44Chain::Internal => return MmError::err(AccountUpdatingError::from(InvalidBip44ChainError { chain })),
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HDAddressBalanceScanner for EthCoin {
+    type Address = ChecksumAddress;
+
+    /// An address is considered used if it has sent at least one transaction or has a non-zero balance.
+    async fn is_address_used(&self, address: &Self::Address) -> BalanceResult<bool> {
+        let nonce = self
+            .web3
+            .eth()
+            .transaction_count(address.0, Some(BlockNumber::Latest))
+            .await?;
+        if !nonce.is_zero() {
+            return Ok(true);
+        }
+        let balance = self.address_balance(address.0).compat().await?;
+        Ok(!balance.is_zero())
+    }
+}
+
+#[async_trait]
+impl HDWalletBalanceOps for EthCoin {
+    type HDAddressScanner = EthCoin;
+
+    async fn produce_hd_address_scanner(&self) -> BalanceResult<Self::HDAddressScanner> { Ok(self.clone()) }
+
+    async fn enable_hd_wallet<XPubExtractor>(
+        &self,
+        hd_wallet: &Self::HDWallet,
+        xpub_extractor: &XPubExtractor,
+        params: EnabledCoinBalanceParams,
+    ) -> MmResult<HDWalletBalance, EnableCoinBalanceError>
+    where
+        XPubExtractor: HDXPubExtractor,
+    {
+        coin_balance::common_impl::enable_hd_wallet(self, hd_wallet, xpub_extractor, params).await
+    }
+
+    async fn scan_for_new_addresses(
+        &self,
+        hd_wallet: &Self::HDWallet,
+        hd_account: &mut Self::HDAccount,
+        address_scanner: &Self::HDAddressScanner,
+        gap_limit: u32,
+    ) -> BalanceResult<Vec<HDAddressBalance>> {
+        utxo_common::scan_for_new_addresses_impl(
+            self,
+            hd_wallet,
+            hd_account,
+            address_scanner,
+            Bip44Chain::External,
+            gap_limit,
+        )
+        .await
+    }
+
+    async fn all_known_addresses_balances(&self, hd_account: &Self::HDAccount) -> BalanceResult<Vec<HDAddressBalance>> {
+        let external_addresses = hd_account
+            .known_addresses_number(Bip44Chain::External)
+            .mm_err(|e| BalanceError::Internal(e.to_string()))?;
+        self.known_addresses_balances_with_ids(hd_account, Bip44Chain::External, 0..external_addresses)
+            .await
+    }
+
+    async fn known_address_balance(&self, address: &Self::Address) -> BalanceResult<CoinBalance> {
+        let balance = self.address_balance(address.0).compat().await?;
+        Ok(CoinBalance::new(u256_to_big_decimal(balance, self.decimals)?))
+    }
+
+    async fn known_addresses_balances(
+        &self,
+        addresses: Vec<Self::Address>,
+    ) -> BalanceResult<Vec<(Self::Address, CoinBalance)>> {
+        let requests = addresses.into_iter().map(|address| async move {
+            let balance = self.known_address_balance(&address).await?;
+            Ok::<_, MmError<BalanceError>>((address, balance))
+        });
+        try_join_all(requests).await
+    }
+}
+
+impl HDWalletCoinWithStorageOps for EthCoin {
+    fn hd_wallet_storage<'a>(&self, hd_wallet: &'a Self::HDWallet) -> &'a HDWalletCoinStorage {
+        &hd_wallet.hd_wallet_storage
+    }
+}
+
+impl CoinWithDerivationMethod for EthCoin {
+    type Address = ChecksumAddress;
+    type HDWallet = EthHDWallet;
+
+    fn derivation_method(&self) -> &DerivationMethod<Self::Address, Self::HDWallet> { &self.derivation_method }
+}
+
+#[async_trait]
+impl GetWithdrawSenderAddress for EthCoin {
+    type Address = ChecksumAddress;
+    type Pubkey = Public;
+
+    async fn get_withdraw_sender_address(
+        &self,
+        req: &WithdrawRequest,
+    ) -> MmResult<WithdrawSenderAddress<Self::Address, Self::Pubkey>, WithdrawError> {
+        self.get_sender_address(req.from.as_ref()).await
+    }
+}
+
+impl EthCoin {
+    /// Returns the HD wallet address that `from` refers to, or my address if the coin is initialized with an Iguana private key.
+    pub(crate) async fn get_sender_address(
+        &self,
+        from: Option<&WithdrawFrom>,
+    ) -> MmResult<WithdrawSenderAddress<ChecksumAddress, Public>, WithdrawError> {
+        match *self.derivation_method {
+            DerivationMethod::SingleAddress(my_address) => {
+                if from.is_some() {
+                    let error = "'from' is not supported if the coin is initialized with an Iguana private key";
+                    return MmError::err(WithdrawError::UnexpectedFromAddress(error.to_owned()));
+                }
+                let pubkey = match self.priv_key_policy {
+                    EthPrivKeyPolicy::KeyPair(ref key_pair) => *key_pair.public(),
+                    #[cfg(target_arch = "wasm32")]
+                    EthPrivKeyPolicy::Metamask(ref metamask_policy) => {
+                        // Skip the `0x04` prefix of the uncompressed public key.
+                        Public::from_slice(&metamask_policy.public_key_uncompressed[1..])
+                    },
+                };
+                Ok(WithdrawSenderAddress {
+                    address: my_address,
+                    pubkey,
+                    derivation_path: None,
+                })
+            },
+            DerivationMethod::HDWallet(ref hd_wallet) => {
+                utxo_common::get_withdraw_hd_sender(self, from, hd_wallet).await
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl GetNewAddressRpcOps for EthCoin {
+    async fn get_new_address_rpc_without_conf(
+        &self,
+        params: GetNewAddressParams,
+    ) -> MmResult<GetNewAddressResponse, GetNewAddressRpcError> {
+        get_new_address::common_impl::get_new_address_rpc_without_conf(self, params).await
+    }
+
+    async fn get_new_address_rpc<ConfirmAddress>(
+        &self,
+        params: GetNewAddressParams,
+        confirm_address: &ConfirmAddress,
+    ) -> MmResult<GetNewAddressResponse, GetNewAddressRpcError>
+    where
+        ConfirmAddress: HDConfirmAddress,
+    {
+        get_new_address::common_impl::get_new_address_rpc(self, params, confirm_address).await
+    }
+}
+
+#[async_trait]
+impl AccountBalanceRpcOps for EthCoin {
+    async fn account_balance_rpc(
+        &self,
+        params: AccountBalanceParams,
+    ) -> MmResult<HDAccountBalanceResponse, HDAccountBalanceRpcError> {
+        account_balance::common_impl::account_balance_rpc(self, params).await
+    }
+}
+
+#[async_trait]
+impl InitAccountBalanceRpcOps for EthCoin {
+    async fn init_account_balance_rpc(
+        &self,
+        params: InitAccountBalanceParams,
+    ) -> MmResult<HDAccountBalance, HDAccountBalanceRpcError> {
+        init_account_balance::common_impl::init_account_balance_rpc(self, params).await
+    }
+}
+
+#[async_trait]
+impl InitScanAddressesRpcOps for EthCoin {
+    async fn init_scan_for_new_addresses_rpc(
+        &self,
+        params: ScanAddressesParams,
+    ) -> MmResult<ScanAddressesResponse, HDAccountBalanceRpcError> {
+        init_scan_for_new_addresses::common_impl::scan_for_new_addresses_rpc(self, params).await
+    }
+}
+
+#[async_trait]
+impl InitCreateAccountRpcOps for EthCoin {
+    async fn init_create_account_rpc<XPubExtractor>(
+        &self,
+        params: CreateNewAccountParams,
+        state: CreateAccountState,
+        xpub_extractor: &XPubExtractor,
+    ) -> MmResult<HDAccountBalance, CreateAccountRpcError>
+    where
+        XPubExtractor: HDXPubExtractor,
+    {
+        init_create_account::common_impl::init_create_new_account_rpc(self, params, state, xpub_extractor).await
+    }
+
+    async fn revert_creating_account(&self, account_id: u32) {
+        init_create_account::common_impl::revert_creating_account(self, account_id).await
+    }
+}
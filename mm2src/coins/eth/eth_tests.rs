@@ -1,10 +1,13 @@
+use super::web3_transport::FeeHistoryResult;
 use super::*;
-use crate::IguanaPrivKey;
+use crate::rpc_command::eth_pending_tx::PendingEthTxError;
+use crate::{IguanaPrivKey, WithdrawFrom};
 use common::{block_on, now_sec_u32};
 use mm2_core::mm_ctx::{MmArc, MmCtxBuilder};
 use mm2_test_helpers::for_tests::{eth_jst_testnet_conf, eth_testnet_conf, ETH_DEV_NODE, ETH_DEV_SWAP_CONTRACT,
                                   ETH_DEV_TOKEN_CONTRACT, ETH_MAINNET_NODE, ETH_MAINNET_SWAP_CONTRACT};
 use mocktopus::mocking::*;
+use web3::types::AccessListItem;
 
 /// The gas price for the tests
 const GAS_PRICE: u64 = 50_000_000_000;
@@ -22,7 +25,7 @@ fn check_sum(addr: &str, expected: &str) {
     assert_eq!(expected, actual);
 }
 
-fn eth_coin_for_test(
+pub(super) fn eth_coin_for_test(
     coin_type: EthCoinType,
     urls: &[&str],
     fallback_swap_contract: Option<Address>,
@@ -63,11 +66,13 @@ fn eth_coin_for_test(
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         gas_station_policy: GasStationPricePolicy::MeanAverageFast,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         ticker,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
@@ -77,8 +82,11 @@ fn eth_coin_for_test(
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -229,11 +237,13 @@ fn send_and_refund_erc20_payment() {
             token_addr: Address::from_str(ETH_DEV_TOKEN_CONTRACT).unwrap(),
         },
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
             is_parity: false,
@@ -247,8 +257,11 @@ fn send_and_refund_erc20_payment() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -322,11 +335,13 @@ fn send_and_refund_eth_payment() {
         ticker: "ETH".into(),
         coin_type: EthCoinType::Eth,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
             is_parity: false,
@@ -340,8 +355,11 @@ fn send_and_refund_eth_payment() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -425,11 +443,13 @@ fn test_nonce_several_urls() {
         ticker: "ETH".into(),
         coin_type: EthCoinType::Eth,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![
             Web3Instance {
                 web3: web3_devnet.clone(),
@@ -453,8 +473,11 @@ fn test_nonce_several_urls() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -491,11 +514,13 @@ fn test_wait_for_payment_spend_timeout() {
         gas_station_policy: GasStationPricePolicy::MeanAverageFast,
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         ticker: "ETH".into(),
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
@@ -505,8 +530,11 @@ fn test_wait_for_payment_spend_timeout() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     };
@@ -561,11 +589,13 @@ fn test_search_for_swap_tx_spend_was_spent() {
         gas_station_policy: GasStationPricePolicy::MeanAverageFast,
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address,
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         ticker: "ETH".into(),
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
@@ -575,8 +605,11 @@ fn test_search_for_swap_tx_spend_was_spent() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -672,11 +705,13 @@ fn test_search_for_swap_tx_spend_was_refunded() {
         gas_station_policy: GasStationPricePolicy::MeanAverageFast,
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address,
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         ticker: "BAT".into(),
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
@@ -686,8 +721,11 @@ fn test_search_for_swap_tx_spend_was_refunded() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -735,7 +773,7 @@ fn test_search_for_swap_tx_spend_was_refunded() {
 fn test_withdraw_impl_manual_fee() {
     let (_ctx, coin) = eth_coin_for_test(EthCoinType::Eth, &["http://dummy.dummy"], None);
 
-    EthCoin::my_balance.mock_safe(|_| {
+    EthCoin::address_balance.mock_safe(|_, _| {
         let balance = wei_from_big_decimal(&1000000000.into(), 18).unwrap();
         MockResult::Return(Box::new(futures01::future::ok(balance)))
     });
@@ -747,6 +785,10 @@ fn test_withdraw_impl_manual_fee() {
         to: "0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94".to_string(),
         coin: "ETH".to_string(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::EthGas {
             gas: ETH_GAS,
             gas_price: 1.into(),
@@ -761,6 +803,8 @@ fn test_withdraw_impl_manual_fee() {
             coin: "ETH".into(),
             gas_price: "0.000000001".parse().unwrap(),
             gas: ETH_GAS,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             total_fee: "0.00015".parse().unwrap(),
         }
         .into(),
@@ -779,7 +823,7 @@ fn test_withdraw_impl_fee_details() {
         None,
     );
 
-    EthCoin::my_balance.mock_safe(|_| {
+    EthCoin::address_balance.mock_safe(|_, _| {
         let balance = wei_from_big_decimal(&1000000000.into(), 18).unwrap();
         MockResult::Return(Box::new(futures01::future::ok(balance)))
     });
@@ -791,6 +835,10 @@ fn test_withdraw_impl_fee_details() {
         to: "0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94".to_string(),
         coin: "JST".to_string(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::EthGas {
             gas: ETH_GAS,
             gas_price: 1.into(),
@@ -805,6 +853,8 @@ fn test_withdraw_impl_fee_details() {
             coin: "ETH".into(),
             gas_price: "0.000000001".parse().unwrap(),
             gas: ETH_GAS,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             total_fee: "0.00015".parse().unwrap(),
         }
         .into(),
@@ -812,6 +862,209 @@ fn test_withdraw_impl_fee_details() {
     assert_eq!(expected, tx_details.fee_details);
 }
 
+#[test]
+fn test_eip1559_fee_per_gas_from_fee_history() {
+    let history = FeeHistoryResult {
+        oldest_block: 100.into(),
+        base_fee_per_gas: vec![10.into(), 12.into(), 11.into(), 13.into()],
+        reward: Some(vec![vec![3.into()], vec![1.into()], vec![2.into()]]),
+    };
+    let expected = FeePerGasEstimated {
+        base_fee_per_gas: 13.into(),
+        max_fee_per_gas: 28.into(),
+        max_priority_fee_per_gas: 2.into(),
+    };
+    assert_eq!(FeePerGasEstimated::from_fee_history(&history), Some(expected));
+
+    // The chain doesn't support EIP-1559.
+    let history = FeeHistoryResult {
+        oldest_block: 100.into(),
+        base_fee_per_gas: vec![0.into(), 0.into()],
+        reward: None,
+    };
+    assert_eq!(FeePerGasEstimated::from_fee_history(&history), None);
+}
+
+#[test]
+fn test_eip1559_transaction_sign() {
+    let key_pair = KeyPair::from_secret_slice(
+        &hex::decode("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f").unwrap(),
+    )
+    .unwrap();
+    let tx = Eip1559Transaction {
+        chain_id: 1,
+        nonce: 0.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+        max_fee_per_gas: 30_000_000_000u64.into(),
+        gas: ETH_GAS.into(),
+        action: Action::Call(Address::from_str("0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94").unwrap()),
+        value: 1_000_000_000_000_000u64.into(),
+        data: vec![],
+        access_list: vec![],
+    };
+    let signature_hash = tx.signature_hash();
+    let signed = tx.sign(key_pair.secret()).unwrap();
+
+    let raw = signed.raw();
+    assert_eq!(raw[0], EIP1559_TX_TYPE);
+    let rlp = rlp::Rlp::new(&raw[1..]);
+    assert_eq!(rlp.item_count().unwrap(), 12);
+    assert_eq!(signed.hash(), H256::from(keccak256(&raw).take()));
+
+    let signature = Signature::from_rsv(&signed.r, &signed.s, signed.y_parity);
+    let public = ethkey::recover(&signature, &signature_hash).unwrap();
+    assert_eq!(&public, key_pair.public());
+}
+
+#[test]
+fn test_eip1559_transaction_decode() {
+    let key_pair = KeyPair::from_secret_slice(
+        &hex::decode("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f").unwrap(),
+    )
+    .unwrap();
+    let to = Address::from_str("0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94").unwrap();
+    let tx = Eip1559Transaction {
+        chain_id: 1,
+        nonce: 7.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+        max_fee_per_gas: 30_000_000_000u64.into(),
+        gas: 150_000.into(),
+        action: Action::Call(to),
+        value: 0.into(),
+        data: vec![0xde, 0xad, 0xbe, 0xef],
+        access_list: vec![AccessListItem {
+            address: to,
+            storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+        }],
+    };
+    let signed = tx.sign(key_pair.secret()).unwrap();
+    let raw = signed.raw();
+    assert_eq!(SignedEip1559Transaction::decode(&raw).unwrap(), signed);
+
+    let decoded = signed_eth_tx_from_bytes(&raw).unwrap();
+    assert_eq!(decoded.hash(), signed.hash());
+    assert_eq!(decoded.sender(), key_pair.address());
+    assert_eq!(decoded.public(), Some(*key_pair.public()));
+    assert_eq!(decoded.nonce(), 7.into());
+    assert_eq!(decoded.data(), &[0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(decoded.tx_hex(), raw);
+
+    // EIP-2930 transactions aren't supported.
+    let mut eip2930 = raw.clone();
+    eip2930[0] = 0x01;
+    assert!(signed_eth_tx_from_bytes(&eip2930).is_err());
+}
+
+#[test]
+fn test_eip1559_transaction_from_web3_tx() {
+    let key_pair = KeyPair::from_secret_slice(
+        &hex::decode("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f").unwrap(),
+    )
+    .unwrap();
+    let to = Address::from_str("0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94").unwrap();
+    let tx = Eip1559Transaction {
+        chain_id: 1,
+        nonce: 0.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+        max_fee_per_gas: 30_000_000_000u64.into(),
+        gas: ETH_GAS.into(),
+        action: Action::Call(to),
+        value: 1_000_000_000_000_000u64.into(),
+        data: vec![],
+        access_list: vec![],
+    };
+    let signed = tx.sign(key_pair.secret()).unwrap();
+
+    // The transaction as it's returned by `eth_getTransactionByHash`.
+    let web3_tx: Web3Transaction = serde_json::from_value(json!({
+        "hash": signed.hash(),
+        "nonce": "0x0",
+        "blockHash": null,
+        "blockNumber": null,
+        "transactionIndex": null,
+        "from": key_pair.address(),
+        "to": to,
+        "value": "0x38d7ea4c68000",
+        "gasPrice": "0x6fc23ac00",
+        "gas": format!("{:#x}", ETH_GAS),
+        "input": "0x",
+        "type": "0x2",
+        "maxFeePerGas": "0x6fc23ac00",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "accessList": [],
+        "v": format!("{:#x}", signed.y_parity),
+        "r": U256::from_big_endian(signed.r.as_bytes()),
+        "s": U256::from_big_endian(signed.s.as_bytes()),
+    }))
+    .unwrap();
+
+    let restored = signed_tx_from_web3_tx(web3_tx.clone(), Some(1)).unwrap();
+    assert_eq!(restored.hash(), signed.hash());
+    assert_eq!(restored.sender(), key_pair.address());
+    assert_eq!(restored.tx_hex(), signed.raw());
+
+    // The restored transaction hash doesn't match if `chain_id` is wrong.
+    assert!(signed_tx_from_web3_tx(web3_tx.clone(), Some(56)).is_err());
+    assert!(signed_tx_from_web3_tx(web3_tx, None).is_err());
+}
+
+#[test]
+fn test_history_tx_data_of_mined_eip1559_transaction() {
+    use super::eth_tx_history_v2::HistoryTxData;
+
+    let key_pair = KeyPair::from_secret_slice(
+        &hex::decode("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f").unwrap(),
+    )
+    .unwrap();
+    let to = Address::from_str("0x7Bc1bBDD6A0a722fC9bffC49c921B685ECB84b94").unwrap();
+    let tx = Eip1559Transaction {
+        chain_id: 1,
+        nonce: 3.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+        max_fee_per_gas: 30_000_000_000u64.into(),
+        gas: ETH_GAS.into(),
+        action: Action::Call(to),
+        value: 1_000_000_000_000_000u64.into(),
+        data: vec![],
+        access_list: vec![],
+    };
+    let signed = tx.sign(key_pair.secret()).unwrap();
+
+    // The mined transaction as it's returned by `eth_getTransactionByHash`, `gasPrice` is the effective one.
+    let mut mined = json!({
+        "hash": signed.hash(),
+        "nonce": "0x3",
+        "blockHash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+        "blockNumber": "0xf4240",
+        "transactionIndex": "0x5",
+        "from": key_pair.address(),
+        "to": to,
+        "value": "0x38d7ea4c68000",
+        "gasPrice": "0x4a817c800",
+        "gas": format!("{:#x}", ETH_GAS),
+        "input": "0x",
+        "type": "0x2",
+        "maxFeePerGas": "0x6fc23ac00",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "accessList": [],
+        "v": format!("{:#x}", signed.y_parity),
+        "r": U256::from_big_endian(signed.r.as_bytes()),
+        "s": U256::from_big_endian(signed.s.as_bytes()),
+    });
+    let web3_tx: Web3Transaction = serde_json::from_value(mined.clone()).unwrap();
+
+    let tx_data = HistoryTxData::new(web3_tx, Some(1), None, 1_650_000_000);
+    assert_eq!(tx_data.tx_hash, hex::encode(signed.hash().as_bytes()));
+    assert_eq!(tx_data.tx_hex.0, signed.raw());
+
+    // A transaction of an unsupported type is still saved in the history without `tx_hex`.
+    mined["type"] = json!("0x1");
+    let web3_tx: Web3Transaction = serde_json::from_value(mined).unwrap();
+    let tx_data = HistoryTxData::new(web3_tx, Some(1), None, 1_650_000_000);
+    assert_eq!(tx_data.tx_hash, hex::encode(signed.hash().as_bytes()));
+    assert!(tx_data.tx_hex.0.is_empty());
+}
+
 #[test]
 #[cfg(not(target_arch = "wasm32"))]
 fn test_nonce_lock() {
@@ -861,6 +1114,549 @@ fn test_add_ten_pct_one_gwei() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn test_min_replacement_fee() {
+    let pending = PayForGasOption::Eip1559 {
+        max_fee_per_gas: 30_000_000_001u64.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+    };
+    let min = pending_tx::min_replacement_pay_for_gas(pending);
+    let expected = PayForGasOption::Eip1559 {
+        max_fee_per_gas: 33_000_000_002u64.into(),
+        max_priority_fee_per_gas: 1_100_000_000u64.into(),
+    };
+    assert_eq!(min, expected);
+    pending_tx::check_replacement_fee(min, min).unwrap();
+
+    // The priority fee isn't increased.
+    let new = PayForGasOption::Eip1559 {
+        max_fee_per_gas: 50_000_000_000u64.into(),
+        max_priority_fee_per_gas: 1_000_000_000u64.into(),
+    };
+    let err = pending_tx::check_replacement_fee(new, min).unwrap_err().into_inner();
+    match err {
+        PendingEthTxError::FeeNotIncreased { param, .. } => assert_eq!(param, "max_priority_fee_per_gas"),
+        e => panic!("Unexpected error: {}", e),
+    }
+
+    // A legacy transaction pays the whole gas price to the validator.
+    let new = PayForGasOption::Legacy {
+        gas_price: 33_000_000_002u64.into(),
+    };
+    pending_tx::check_replacement_fee(new, min).unwrap();
+}
+
+#[test]
+fn test_replace_pending_tx_from_iguana_address() {
+    let (_ctx, coin) = eth_coin_for_test(EthCoinType::Eth, &["http://dummy.dummy"], None);
+    let from = WithdrawFrom::DerivationPath {
+        derivation_path: "m/44'/60'/0'/0/1".to_owned(),
+    };
+    let tx_hash = rpc::v1::types::H256::default();
+
+    // 'from' is supported by HD wallets only.
+    let err = block_on(coin.cancel_pending_tx(tx_hash, None, Some(&from), true))
+        .unwrap_err()
+        .into_inner();
+    match err {
+        PendingEthTxError::UnexpectedFromAddress(_) => (),
+        e => panic!("Unexpected error: {}", e),
+    }
+}
+
+fn signed_tx_for_test(coin: &EthCoin) -> SignedEthTx {
+    let tx = UnSignedEthTx {
+        nonce: 0.into(),
+        gas_price: GAS_PRICE.into(),
+        gas: ETH_GAS.into(),
+        action: Action::Call(coin.swap_contract_address),
+        value: 0.into(),
+        data: vec![],
+    };
+    let key_pair = coin.priv_key_policy.key_pair_or_err().unwrap();
+    SignedEthTx::Legacy(tx.sign(key_pair.secret(), None))
+}
+
+fn jst_coin_for_test() -> (MmArc, EthCoin) {
+    eth_coin_for_test(
+        EthCoinType::Erc20 {
+            platform: "ETH".to_string(),
+            token_addr: Address::from_str(ETH_DEV_TOKEN_CONTRACT).unwrap(),
+        },
+        &["http://dummy.dummy"],
+        None,
+    )
+}
+
+#[test]
+fn test_approve_and_send_erc20_payment_exact_approvals() {
+    let approved = Arc::new(Mutex::new(Vec::new()));
+    let approved_clone = approved.clone();
+    let (_ctx, mut coin) = jst_coin_for_test();
+    let tx = signed_tx_for_test(&coin);
+
+    let approve_tx = tx.clone();
+    EthCoin::approve.mock_safe(move |_, _, amount| {
+        approved_clone.lock().unwrap().push(amount);
+        MockResult::Return(Box::new(futures01::future::ok(approve_tx.clone())))
+    });
+    EthCoin::wait_for_required_allowance
+        .mock_safe(|_, _, _, _| MockResult::Return(Box::new(futures01::future::ok(()))));
+    EthCoin::sign_and_send_transaction
+        .mock_safe(move |_, _, _, _, _| MockResult::Return(Box::new(futures01::future::ok(tx.clone()))));
+
+    let swap_contract = coin.swap_contract_address;
+    let allowed = U256::from(100);
+    let amount = U256::from(50);
+
+    block_on(coin.approve_and_send_erc20_payment(
+        swap_contract,
+        allowed,
+        amount,
+        now_sec(),
+        0.into(),
+        vec![],
+        0.into(),
+    ))
+    .unwrap();
+
+    Arc::get_mut(&mut coin.0).unwrap().exact_swap_approvals = true;
+    // The allowance of another swap that is not spent yet is kept.
+    block_on(coin.approve_and_send_erc20_payment(
+        swap_contract,
+        allowed,
+        amount,
+        now_sec(),
+        0.into(),
+        vec![],
+        0.into(),
+    ))
+    .unwrap();
+
+    assert_eq!(*approved.lock().unwrap(), vec![U256::max_value(), U256::from(150)]);
+}
+
+#[test]
+fn test_erc20_allowance_rpcs() {
+    use crate::rpc_command::erc20_allowance::{approve_erc20, get_erc20_allowance, revoke_erc20_approval,
+                                              Erc20AllowanceError};
+    use crate::{lp_register_coin, RegisterCoinParams};
+
+    let approved = Arc::new(Mutex::new(Vec::new()));
+    let approved_clone = approved.clone();
+    let (ctx, coin) = jst_coin_for_test();
+    let tx = signed_tx_for_test(&coin);
+    let spender = checksum_address(&format!("{:#02x}", coin.swap_contract_address));
+    block_on(lp_register_coin(
+        &ctx,
+        MmCoinEnum::EthCoin(coin.clone()),
+        RegisterCoinParams {
+            ticker: "JST".to_owned(),
+        },
+    ))
+    .unwrap();
+
+    EthCoin::allowance.mock_safe(|_, _| MockResult::Return(Box::new(futures01::future::ok(U256::max_value()))));
+    EthCoin::approve.mock_safe(move |_, _, amount| {
+        approved_clone.lock().unwrap().push(amount);
+        MockResult::Return(Box::new(futures01::future::ok(tx.clone())))
+    });
+
+    let req = serde_json::from_value(json!({"coin": "JST", "spender": spender})).unwrap();
+    let res = block_on(get_erc20_allowance(ctx.clone(), req)).unwrap();
+    let res = serde_json::to_value(res).unwrap();
+    assert_eq!(res["spender"], json!(spender));
+    assert_eq!(res["unlimited"], json!(true));
+
+    let req = serde_json::from_value(json!({"coin": "JST", "spender": spender, "amount": "1.5"})).unwrap();
+    block_on(approve_erc20(ctx.clone(), req)).unwrap();
+    let req = serde_json::from_value(json!({"coin": "JST", "spender": spender})).unwrap();
+    block_on(revoke_erc20_approval(ctx.clone(), req)).unwrap();
+    assert_eq!(*approved.lock().unwrap(), vec![
+        U256::from(1_500_000_000_000_000_000u64),
+        U256::zero()
+    ]);
+
+    let req = serde_json::from_value(json!({"coin": "JST", "spender": "invalid"})).unwrap();
+    let err = block_on(get_erc20_allowance(ctx, req)).unwrap_err().into_inner();
+    match err {
+        Erc20AllowanceError::InvalidSpender(_) => (),
+        e => panic!("Unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_contract_call_args_and_outputs() {
+    use ethabi::ParamType;
+
+    let kind = ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Bytes)),
+    ]);
+    let arg = json!(["0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359", "1000000000000000000", [
+        "0x01ff", "02"
+    ]]);
+    let token = contract_call::tokenize_arg(&kind, &arg).unwrap();
+    let expected = Token::Tuple(vec![
+        Token::Address(Address::from_str("fb6916095ca1df60bb79ce92ce3ea74c37c5d359").unwrap()),
+        Token::Uint(U256::exp10(18)),
+        Token::Array(vec![Token::Bytes(vec![1, 255]), Token::Bytes(vec![2])]),
+    ]);
+    assert_eq!(token, expected);
+
+    let expected_json = json!(["0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359", "1000000000000000000", [
+        "0x01ff", "0x02"
+    ]]);
+    assert_eq!(contract_call::token_to_json(token), expected_json);
+
+    // JSON numbers and booleans are accepted too.
+    let token = contract_call::tokenize_arg(&ParamType::Uint(8), &json!(42)).unwrap();
+    assert_eq!(token, Token::Uint(42.into()));
+    let token = contract_call::tokenize_arg(&ParamType::Bool, &json!(true)).unwrap();
+    assert_eq!(token, Token::Bool(true));
+
+    // Negative integers are decoded from two's complement.
+    let minus_one = Token::Int(U256::max_value());
+    assert_eq!(contract_call::token_to_json(minus_one), json!("-1"));
+
+    contract_call::tokenize_arg(&ParamType::FixedArray(Box::new(ParamType::Bool), 2), &json!([true])).unwrap_err();
+    contract_call::tokenize_arg(&ParamType::Array(Box::new(ParamType::Bool)), &json!("true")).unwrap_err();
+}
+
+#[test]
+fn test_sign_and_recover_typed_data() {
+    let typed_data = json!({
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "chainId", "type": "uint256" }
+            ],
+            "Permit": [
+                { "name": "owner", "type": "address" },
+                { "name": "value", "type": "uint256" },
+                { "name": "deadline", "type": "uint256" }
+            ]
+        },
+        "domain": { "name": "Token", "chainId": 1 },
+        "primaryType": "Permit",
+        "message": {
+            "owner": "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "value": "1000000000000000000",
+            "deadline": 1700000000
+        }
+    });
+    let (_ctx, coin) = eth_coin_for_test(EthCoinType::Eth, &["http://dummy.dummy"], None);
+
+    let (hash, signature) = coin
+        .sign_typed_data(json::from_value(typed_data.clone()).unwrap())
+        .unwrap();
+    assert!(signature[64] == 27 || signature[64] == 28);
+
+    let (recovered_hash, signer) = coin
+        .recover_typed_data_signer(json::from_value(typed_data).unwrap(), signature)
+        .unwrap();
+    assert_eq!(recovered_hash, hash);
+    assert_eq!(signer, coin.my_address);
+}
+
+#[test]
+fn test_nodes_health_failing_node_quarantine() {
+    use web3_transport::node_health::NodesHealth;
+
+    let now_ms = 1_000_000;
+    let mut health = NodesHealth::new(3);
+    health.on_success(0, 300);
+    health.on_success(1, 100);
+    health.on_success(2, 200);
+    assert_eq!(health.ordered_nodes(now_ms), vec![1, 2, 0]);
+
+    for _ in 0..3 {
+        health.on_error(1, "timeout".to_owned(), now_ms);
+    }
+    assert_eq!(health.ordered_nodes(now_ms), vec![2, 0, 1]);
+    let status = &health.statuses(vec!["a".into(), "b".into(), "c".into()], now_ms)[1];
+    assert!(status.is_quarantined);
+    assert_eq!(status.quarantine_reason, Some(QuarantineReason::Failing));
+    assert_eq!(status.quarantined_until, Some((now_ms + 30_000) / 1000));
+    assert_eq!(status.rank, 2);
+
+    // The node is quarantined for twice as long if it fails again after the quarantine ends.
+    let now_ms = now_ms + 30_000;
+    health.on_error(1, "timeout".to_owned(), now_ms);
+    let status = &health.statuses(vec!["a".into(), "b".into(), "c".into()], now_ms)[1];
+    assert_eq!(status.quarantined_until, Some((now_ms + 60_000) / 1000));
+
+    health.on_success(1, 100);
+    let status = &health.statuses(vec!["a".into(), "b".into(), "c".into()], now_ms)[1];
+    assert!(!status.is_quarantined);
+    assert_eq!(status.consecutive_errors, 0);
+}
+
+#[test]
+fn test_nodes_health_lagging_node_quarantine() {
+    use web3_transport::node_health::NodesHealth;
+
+    let now_ms = 1_000_000;
+    let mut health = NodesHealth::new(2);
+    health.on_success(0, 100);
+    health.on_success(1, 200);
+    health.update_block_lag(&[Some(100), Some(110)]);
+    assert_eq!(health.ordered_nodes(now_ms), vec![1, 0]);
+    let status = &health.statuses(vec!["a".into(), "b".into()], now_ms)[0];
+    assert_eq!(status.quarantine_reason, Some(QuarantineReason::Behind(10)));
+    assert_eq!(status.block_lag, Some(10));
+    assert_eq!(status.quarantined_until, None);
+
+    // The node is released once it catches up.
+    health.update_block_lag(&[Some(115), Some(118)]);
+    assert_eq!(health.ordered_nodes(now_ms), vec![0, 1]);
+
+    // The block of a node that didn't respond is not compared with the newer blocks of the others.
+    health.update_block_lag(&[Some(130), None]);
+    let statuses = health.statuses(vec!["a".into(), "b".into()], now_ms);
+    assert!(!statuses[1].is_quarantined);
+    assert_eq!(statuses[1].block_number, Some(118));
+    assert_eq!(statuses[1].block_lag, Some(3));
+    assert_eq!(statuses[0].block_lag, Some(0));
+}
+
+#[test]
+fn test_nodes_health_configured_max_block_lag() {
+    use web3_transport::node_health::NodesHealth;
+
+    let now_ms = 1_000_000;
+    let mut health = NodesHealth::new(2);
+    health.set_max_block_lag(20);
+    health.update_block_lag(&[Some(100), Some(110)]);
+    let statuses = health.statuses(vec!["a".into(), "b".into()], now_ms);
+    assert!(!statuses[0].is_quarantined);
+    assert_eq!(statuses[0].block_lag, Some(10));
+
+    health.update_block_lag(&[Some(100), Some(121)]);
+    let status = &health.statuses(vec!["a".into(), "b".into()], now_ms)[0];
+    assert_eq!(status.quarantine_reason, Some(QuarantineReason::Behind(21)));
+}
+
+#[test]
+fn test_nodes_health_unknown_latency_score() {
+    use web3_transport::node_health::NodesHealth;
+
+    let now_ms = 1_000_000;
+    let mut health = NodesHealth::new(3);
+    health.on_success(0, 300);
+    health.on_success(1, 100);
+    // The node with unknown latency is tried first to measure it.
+    assert_eq!(health.ordered_nodes(now_ms), vec![2, 1, 0]);
+
+    // The error rate of a node with unknown latency is penalized too.
+    health.on_error(2, "timeout".to_owned(), now_ms);
+    health.on_error(2, "timeout".to_owned(), now_ms);
+    assert_eq!(health.ordered_nodes(now_ms), vec![1, 0, 2]);
+}
+
+/// The test vectors from https://github.com/ethereum/EIPs/blob/master/EIPS/eip-137.md#namehash-algorithm
+#[test]
+fn test_namehash() {
+    assert_eq!(
+        hex::encode(name_resolution::namehash("eth")),
+        "93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae"
+    );
+    assert_eq!(
+        hex::encode(name_resolution::namehash("foo.eth")),
+        "de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f"
+    );
+}
+
+#[test]
+fn test_name_registry_from_conf() {
+    let ens_registry = Address::from_str("00000000000C2E074eC69A0dFb2997BA6C7d2e1e").unwrap();
+    let conf = json!({"coin": "ETH", "chain_id": 1});
+    assert_eq!(name_resolution::name_registry_from_conf(&conf), Ok(Some(ens_registry)));
+
+    let conf = json!({"coin": "MATIC", "chain_id": 137});
+    assert_eq!(name_resolution::name_registry_from_conf(&conf), Ok(None));
+
+    let conf = json!({
+        "coin": "RSK",
+        "chain_id": 30,
+        "name_registry": "0xcb868aeabd31e2b66f74e9a55cf064abb31a4ad5"
+    });
+    let expected = Address::from_str("cb868aeabd31e2b66f74e9a55cf064abb31a4ad5").unwrap();
+    assert_eq!(name_resolution::name_registry_from_conf(&conf), Ok(Some(expected)));
+
+    let conf = json!({"coin": "ETH", "chain_id": 1, "name_registry": "invalid"});
+    assert!(name_resolution::name_registry_from_conf(&conf).is_err());
+}
+
+/// Mocks the `registry` to return the `resolver` and the `resolver` to return the `address` for any name.
+fn mock_name_resolution(registry: Address, resolver: Address, address: Address) {
+    EthCoin::call_request.mock_safe(move |_, to, _, _| {
+        let output = if to == registry {
+            resolver
+        } else if to == resolver {
+            address
+        } else {
+            panic!("Unexpected call to {:?}", to)
+        };
+        let output = ethabi::encode(&[Token::Address(output)]);
+        MockResult::Return(Box::pin(futures::future::ok(output.into())))
+    });
+}
+
+#[test]
+fn test_resolve_withdraw_to() {
+    let registry = Address::from([1; 20]);
+    let resolver = Address::from([2; 20]);
+    let address = Address::from([3; 20]);
+    let (_ctx, mut coin) = eth_coin_for_test(EthCoinType::Eth, &[ETH_DEV_NODE], None);
+    Arc::get_mut(&mut coin.0).unwrap().name_registry = Some(registry);
+
+    mock_name_resolution(registry, resolver, address);
+    let (to_addr, to_name) = block_on(coin.resolve_withdraw_to("Vitalik.ETH")).unwrap();
+    assert_eq!(to_addr, address);
+    assert_eq!(to_name, Some("vitalik.eth".to_owned()));
+
+    mock_name_resolution(registry, Address::zero(), address);
+    let error = block_on(coin.resolve_withdraw_to("vitalik.eth"))
+        .unwrap_err()
+        .into_inner();
+    let expected = WithdrawError::InvalidAddress("'vitalik.eth' is not registered or has no resolver".to_owned());
+    assert_eq!(error, expected);
+
+    mock_name_resolution(registry, resolver, Address::zero());
+    let error = block_on(coin.resolve_withdraw_to("vitalik.eth"))
+        .unwrap_err()
+        .into_inner();
+    let expected = WithdrawError::InvalidAddress("'vitalik.eth' doesn't resolve to an address".to_owned());
+    assert_eq!(error, expected);
+
+    // A hex address is returned as is without requesting the registry.
+    EthCoin::call_request.mock_safe(|_, to, _, _| panic!("Unexpected call to {:?}", to));
+    let (to_addr, to_name) = block_on(coin.resolve_withdraw_to("0x0303030303030303030303030303030303030303")).unwrap();
+    assert_eq!(to_addr, address);
+    assert_eq!(to_name, None);
+}
+
+/// The `DOMAIN_SEPARATOR` values of the USDC and DAI tokens on the Ethereum mainnet.
+#[test]
+fn test_erc20_permit_domain_separator() {
+    let usdc = Address::from_str("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48").unwrap();
+    let actual = erc20_permit::eip712_domain_separator("USD Coin", "2", 1, usdc);
+    assert_eq!(
+        hex::encode(actual),
+        "06c37168a7db5138defc7866392bb87a741f9b3d104deb5094588ce041cae335"
+    );
+
+    let dai = Address::from_str("6B175474E89094C44Da98b954EedeAC495271d0F").unwrap();
+    let actual = erc20_permit::eip712_domain_separator("Dai Stablecoin", "1", 1, dai);
+    assert_eq!(
+        hex::encode(actual),
+        "dbb8cf42e1ecb028be3f3dbc922e1d878b963f411dc388ced501601c60f7c6f7"
+    );
+}
+
+/// DAI reproduces the known `DOMAIN_SEPARATOR`, but its `permit` is not compatible with ERC-2612,
+/// so the payment must be sent after `approve`.
+#[test]
+fn test_erc20_payment_falls_back_to_approve_if_permit_is_not_accepted() {
+    let approved = Arc::new(Mutex::new(Vec::new()));
+    let approved_clone = approved.clone();
+    let sent_data = Arc::new(Mutex::new(Vec::new()));
+    let sent_data_clone = sent_data.clone();
+    let (_ctx, mut coin) = jst_coin_for_test();
+    let tx = signed_tx_for_test(&coin);
+    {
+        let coin = Arc::get_mut(&mut coin.0).unwrap();
+        coin.contract_supports_permit = true;
+        coin.chain_id = Some(1);
+    }
+
+    EthCoin::call_token_function.mock_safe(|_, token_addr, function, _| {
+        let token = match function.name.as_str() {
+            "DOMAIN_SEPARATOR" => Token::FixedBytes(erc20_permit::eip712_domain_separator(
+                "Dai Stablecoin",
+                "1",
+                1,
+                token_addr,
+            )),
+            "name" => Token::String("Dai Stablecoin".to_owned()),
+            "nonces" => Token::Uint(0.into()),
+            name => panic!("Unexpected function: {}", name),
+        };
+        MockResult::Return(Box::new(futures01::future::ok(vec![token])))
+    });
+    // The ERC-2612 `permit` reverts.
+    EthCoin::estimate_gas.mock_safe(|_, _| {
+        MockResult::Return(Box::new(futures01::future::err(web3::Error::InvalidResponse(
+            "execution reverted".to_owned(),
+        ))))
+    });
+    EthCoin::allowance.mock_safe(|_, _| MockResult::Return(Box::new(futures01::future::ok(0.into()))));
+    let approve_tx = tx.clone();
+    EthCoin::approve.mock_safe(move |_, _, amount| {
+        approved_clone.lock().unwrap().push(amount);
+        MockResult::Return(Box::new(futures01::future::ok(approve_tx.clone())))
+    });
+    EthCoin::wait_for_required_allowance
+        .mock_safe(|_, _, _, _| MockResult::Return(Box::new(futures01::future::ok(()))));
+    EthCoin::sign_and_send_transaction.mock_safe(move |_, _, _, data, _| {
+        sent_data_clone.lock().unwrap().push(data);
+        MockResult::Return(Box::new(futures01::future::ok(tx.clone())))
+    });
+
+    let payment_args = SendPaymentArgs {
+        time_lock_duration: 0,
+        time_lock: now_sec_u32() + 1000,
+        other_pubkey: &DEX_FEE_ADDR_RAW_PUBKEY,
+        secret_hash: &[1; 20],
+        amount: "1".parse().unwrap(),
+        swap_contract_address: &coin.swap_contract_address(),
+        swap_unique_data: &[],
+        payment_instructions: &None,
+        watcher_reward: None,
+        wait_for_confirmation_until: 0,
+    };
+    coin.send_taker_payment(payment_args).wait().unwrap();
+
+    assert_eq!(*approved.lock().unwrap(), vec![U256::max_value()]);
+    let sent_data = sent_data.lock().unwrap();
+    assert_eq!(sent_data.len(), 1);
+    let erc20_payment = SWAP_CONTRACT.function("erc20Payment").unwrap();
+    assert!(sent_data[0].starts_with(&erc20_payment.short_signature()));
+}
+
+/// The fee estimation must not sign a permit, since it could leak to the RPC node on the `permit` simulation.
+#[test]
+fn test_erc20_sender_trade_fee_with_permit_does_not_sign_permit() {
+    let (_ctx, mut coin) = jst_coin_for_test();
+    {
+        let coin = Arc::get_mut(&mut coin.0).unwrap();
+        coin.contract_supports_permit = true;
+        coin.chain_id = Some(1);
+    }
+
+    EthCoin::call_token_function.mock_safe(|_, token_addr, function, _| {
+        let token = match function.name.as_str() {
+            "DOMAIN_SEPARATOR" => {
+                Token::FixedBytes(erc20_permit::eip712_domain_separator("Jst Token", "1", 1, token_addr))
+            },
+            "name" => Token::String("Jst Token".to_owned()),
+            "nonces" => Token::Uint(0.into()),
+            name => panic!("Unexpected function: {}", name),
+        };
+        MockResult::Return(Box::new(futures01::future::ok(vec![token])))
+    });
+    EthCoin::estimate_gas.mock_safe(|_, _| panic!("'permit' must not be simulated on the fee estimation"));
+    EthCoin::allowance.mock_safe(|_, _| MockResult::Return(Box::new(futures01::future::ok(0.into()))));
+    EthCoin::get_gas_price.mock_safe(|_| MockResult::Return(Box::new(futures01::future::ok(GAS_PRICE.into()))));
+
+    let value = TradePreimageValue::Exact(1.into());
+    let actual = block_on(coin.get_sender_trade_fee(value, FeeApproxStage::WithoutApprox)).unwrap();
+    let expected = u256_to_big_decimal(((300_000 + PERMIT_GAS) * GAS_PRICE).into(), 18).unwrap();
+    assert_eq!(actual.amount.to_decimal(), expected);
+}
+
 #[test]
 fn get_sender_trade_preimage() {
     /// Trade fee for the ETH coin is `2 * 150_000 * gas_price` always.
@@ -1083,7 +1879,7 @@ fn validate_dex_fee_invalid_sender_eth() {
     )))
     .unwrap()
     .unwrap();
-    let tx = signed_tx_from_web3_tx(tx).unwrap().into();
+    let tx = signed_tx_from_web3_tx(tx, coin.chain_id).unwrap().into();
     let amount: BigDecimal = "0.000526435076465".parse().unwrap();
     let validate_fee_args = ValidateFeeArgs {
         fee_tx: &tx,
@@ -1117,7 +1913,7 @@ fn validate_dex_fee_invalid_sender_erc() {
     )))
     .unwrap()
     .unwrap();
-    let tx = signed_tx_from_web3_tx(tx).unwrap().into();
+    let tx = signed_tx_from_web3_tx(tx, coin.chain_id).unwrap().into();
     let amount: BigDecimal = "5.548262548262548262".parse().unwrap();
     let validate_fee_args = ValidateFeeArgs {
         fee_tx: &tx,
@@ -1135,7 +1931,7 @@ fn validate_dex_fee_invalid_sender_erc() {
 }
 
 fn sender_compressed_pub(tx: &SignedEthTx) -> [u8; 33] {
-    let tx_pubkey = tx.public.unwrap();
+    let tx_pubkey = tx.public().unwrap();
     let mut raw_pubkey = [0; 65];
     raw_pubkey[0] = 0x04;
     raw_pubkey[1..].copy_from_slice(tx_pubkey.as_bytes());
@@ -1153,7 +1949,7 @@ fn validate_dex_fee_eth_confirmed_before_min_block() {
     )))
     .unwrap()
     .unwrap();
-    let tx = signed_tx_from_web3_tx(tx).unwrap();
+    let tx = signed_tx_from_web3_tx(tx, coin.chain_id).unwrap();
     let compressed_public = sender_compressed_pub(&tx);
     let tx = tx.into();
     let amount: BigDecimal = "0.000526435076465".parse().unwrap();
@@ -1190,7 +1986,7 @@ fn validate_dex_fee_erc_confirmed_before_min_block() {
     .unwrap()
     .unwrap();
 
-    let tx = signed_tx_from_web3_tx(tx).unwrap();
+    let tx = signed_tx_from_web3_tx(tx, coin.chain_id).unwrap();
     let compressed_public = sender_compressed_pub(&tx);
     let tx = tx.into();
     let amount: BigDecimal = "5.548262548262548262".parse().unwrap();
@@ -1341,11 +2137,13 @@ fn test_message_hash() {
         ticker: "ETH".into(),
         coin_type: EthCoinType::Eth,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
             is_parity: false,
@@ -1359,8 +2157,11 @@ fn test_message_hash() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -1386,11 +2187,13 @@ fn test_sign_verify_message() {
         ticker: "ETH".into(),
         coin_type: EthCoinType::Eth,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
             is_parity: false,
@@ -1404,8 +2207,11 @@ fn test_sign_verify_message() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -1442,11 +2248,13 @@ fn test_eth_extract_secret() {
         gas_station_policy: GasStationPricePolicy::MeanAverageFast,
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address,
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         ticker: "ETH".into(),
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
@@ -1456,8 +2264,11 @@ fn test_eth_extract_secret() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -1554,3 +2365,66 @@ fn test_eth_validate_valid_and_invalid_pubkey() {
     assert!(coin.validate_other_pubkey(&[1u8; 20]).is_err());
     assert!(coin.validate_other_pubkey(&[1u8; 8]).is_err());
 }
+
+fn global_hd_ctx_for_test(passphrase: &str) -> GlobalHDAccountArc {
+    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let crypto_ctx = CryptoCtx::init_with_global_hd_account(ctx, passphrase, 0).unwrap();
+    match crypto_ctx.key_pair_policy() {
+        KeyPairPolicy::GlobalHDAccount(global_hd_ctx) => global_hd_ctx.clone(),
+        KeyPairPolicy::Iguana => panic!("Expected 'KeyPairPolicy::GlobalHDAccount'"),
+    }
+}
+
+#[test]
+fn test_eth_hd_account_derive_address() {
+    use super::eth_hd_wallet::EthHDAccount;
+    use crate::hd_wallet::{HDAccountOps, HDAddress};
+    use crypto::{Bip32DerPathOps, Bip44Chain, ChildNumber, StandardHDPathToCoin};
+
+    const PASSPHRASE: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const EXPECTED_ADDRESS: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+
+    let global_hd_ctx = global_hd_ctx_for_test(PASSPHRASE);
+    let coin_derivation_path = StandardHDPathToCoin::from_str("m/44'/60'").unwrap();
+    let account_derivation_path = coin_derivation_path.derive(ChildNumber::new(0, true).unwrap()).unwrap();
+    let extended_pubkey = global_hd_ctx
+        .derive_secp256k1_extended_pubkey(account_derivation_path.to_derivation_path())
+        .unwrap();
+    let hd_account = EthHDAccount {
+        account_id: 0,
+        extended_pubkey,
+        account_derivation_path,
+        external_addresses_number: 1,
+    };
+
+    let HDAddress {
+        address,
+        pubkey,
+        derivation_path,
+    } = hd_account.derive_address(Bip44Chain::External, 0).unwrap();
+    assert_eq!(address.to_string(), EXPECTED_ADDRESS);
+    assert_eq!(public_to_address(&pubkey), address.0);
+    assert_eq!(derivation_path.to_string(), "m/44'/60'/0'/0/0");
+
+    // The first HD wallet address is the same as the address of the coin activated with the same seed.
+    let secret = global_hd_ctx.derive_secp256k1_secret(&coin_derivation_path).unwrap();
+    let key_pair = KeyPair::from_secret_slice(secret.as_slice()).unwrap();
+    assert_eq!(key_pair.address(), address.0);
+
+    // ETH doesn't have change addresses.
+    hd_account.derive_address(Bip44Chain::Internal, 0).unwrap_err();
+    hd_account.known_addresses_number(Bip44Chain::Internal).unwrap_err();
+
+    let storage_item = hd_account.to_storage_item();
+    assert_eq!(storage_item.internal_addresses_number, 0);
+    let restored = EthHDAccount::try_from_storage_item(&coin_derivation_path, &storage_item).unwrap();
+    assert_eq!(restored.account_id, hd_account.account_id);
+    assert_eq!(restored.external_addresses_number, hd_account.external_addresses_number);
+    assert_eq!(
+        restored.account_derivation_path.to_derivation_path(),
+        hd_account.account_derivation_path.to_derivation_path()
+    );
+    let restored_address = restored.derive_address(Bip44Chain::External, 0).unwrap();
+    assert_eq!(restored_address.address, address);
+}
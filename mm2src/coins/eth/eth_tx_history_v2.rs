@@ -0,0 +1,972 @@
+//! Fetching the ETH and ERC20 transaction history of my address and keeping it in [`TxHistoryStorage`].
+//!
+//! The history is indexed locally from the node without an explorer:
+//! ETH transfers are found by `trace_filter`, ERC20 transfers are found by the `Transfer` event logs.
+//! If the node doesn't support `trace_filter`, only the ERC20 history is fetched.
+//! The blocks are requested in `logs_block_range` chunks, and the range of the fetched blocks is saved
+//! as a [`BlockCursor`] per coin, so the fetching is continued from the saved blocks after restart.
+
+use super::*;
+use crate::my_tx_history_v2::{CoinWithTxHistoryV2, MyTxHistoryErrorV2, MyTxHistoryTarget, TxHistoryStorage};
+use crate::tx_history_storage::{token_id_from_tx_type, BlockCursor, GetTxHistoryFilters, WalletId};
+use crate::TxFeeDetails;
+use common::state_machine::prelude::*;
+use common::PagingOptionsEnum;
+use jsonrpc_core::ErrorCode;
+use std::num::NonZeroUsize;
+
+/// The blocks deeper than this are considered final, so a chain reorganization never reverts them.
+const REORG_ROLLBACK_DEPTH: u64 = 64;
+/// The number of the older chunks fetched per round before checking the new blocks again.
+const BACKFILL_CHUNKS_PER_ROUND: usize = 10;
+/// The number of the transactions requested from the storage at once on a chain reorganization.
+const REORG_REMOVE_BATCH: usize = 100;
+const NEW_BLOCKS_POLL_INTERVAL: f64 = 15.;
+const ON_IO_ERROR_COOLDOWN: f64 = 30.;
+/// The `Method not supported` error code defined by EIP-1474.
+const METHOD_NOT_SUPPORTED_CODE: i64 = -32004;
+
+#[async_trait]
+impl CoinWithTxHistoryV2 for EthCoin {
+    /// ERC20 tokens share the history of the platform coin, and their transactions are distinguished by `token_id`.
+    fn history_wallet_id(&self) -> WalletId { WalletId::new(self.platform_ticker().to_owned()) }
+
+    async fn get_tx_history_filters(
+        &self,
+        _target: MyTxHistoryTarget,
+    ) -> MmResult<GetTxHistoryFilters, MyTxHistoryErrorV2> {
+        let token_id = match &self.coin_type {
+            EthCoinType::Eth => String::new(),
+            EthCoinType::Erc20 { token_addr, .. } => erc20_token_id(token_addr),
+        };
+        self.get_tx_history_filters_for(&token_id)
+            .mm_err(|e| MyTxHistoryErrorV2::Internal(e.to_string()))
+    }
+}
+
+/// Returns the `token_id` of the ERC20 transfers saved in the history.
+fn erc20_token_id(token_addr: &Address) -> String { token_id_from_tx_type(&erc20_tx_type(token_addr)) }
+
+fn erc20_tx_type(token_addr: &Address) -> TransactionType {
+    TransactionType::TokenTransfer(token_addr.as_bytes().to_vec().into())
+}
+
+/// The coin which history is fetched: the platform coin or one of its ERC20 tokens.
+struct HistoryTarget {
+    ticker: String,
+    token: Option<Erc20TokenInfo>,
+}
+
+impl HistoryTarget {
+    /// Empty for the platform coin.
+    fn token_id(&self) -> String {
+        match &self.token {
+            Some(token) => erc20_token_id(&token.token_address),
+            None => String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum SyncError {
+    Storage(String),
+    Rpc(String),
+    /// The node doesn't support an RPC method which is required to fetch the history, so retrying is useless.
+    Unsupported(String),
+}
+
+type SyncResult<T> = Result<T, SyncError>;
+
+fn storage_err<E: std::fmt::Debug>(e: E) -> SyncError { SyncError::Storage(format!("{:?}", e)) }
+
+fn rpc_err<E: std::fmt::Display>(e: E) -> SyncError { SyncError::Rpc(e.to_string()) }
+
+struct EthTxHistoryCtx<Storage: TxHistoryStorage> {
+    coin: EthCoin,
+    storage: Storage,
+    /// The reason why the platform coin history can't be fetched from the node, e.g. `trace_filter` is not supported.
+    /// It's detected once, then only the ERC20 history is fetched.
+    eth_history_unsupported: Option<String>,
+}
+
+impl<Storage: TxHistoryStorage> EthTxHistoryCtx<Storage> {
+    /// The platform coin goes first, then the ERC20 tokens that are activated at the moment.
+    /// The platform coin is skipped if its history is not supported by the node.
+    fn history_targets(&self) -> Vec<HistoryTarget> {
+        let platform = HistoryTarget {
+            ticker: self.coin.ticker.clone(),
+            token: None,
+        };
+        let platform = Some(platform).filter(|_| self.eth_history_unsupported.is_none());
+        let tokens = self
+            .coin
+            .get_erc_tokens_infos()
+            .into_iter()
+            .map(|(ticker, token)| HistoryTarget {
+                ticker,
+                token: Some(token),
+            });
+        platform.into_iter().chain(tokens).collect()
+    }
+
+    /// Fetches the history of `target` up to `current_block` and at most [`BACKFILL_CHUNKS_PER_ROUND`] older chunks.
+    /// Returns the number of the older blocks that are not fetched yet.
+    async fn sync_target(&self, target: &HistoryTarget, current_block: u64) -> SyncResult<u64> {
+        let wallet_id = self.coin.history_wallet_id();
+        let token_id = target.token_id();
+        let range = self.coin.logs_block_range.max(1);
+
+        let mut cursor = match self
+            .storage
+            .get_block_cursor(&wallet_id, &token_id)
+            .await
+            .map_err(storage_err)?
+        {
+            Some(cursor) => self.rollback_on_reorg(target, cursor).await?,
+            None => {
+                let from_block = current_block.saturating_sub(range);
+                self.fetch_and_save(target, from_block, current_block).await?;
+                let cursor = BlockCursor {
+                    from_block,
+                    to_block: current_block,
+                    to_block_hash: self.coin.history_block_hash(current_block).await?,
+                };
+                self.save_cursor(&token_id, &cursor).await?;
+                cursor
+            },
+        };
+
+        while cursor.to_block < current_block {
+            let to_block = current_block.min(cursor.to_block + range);
+            self.fetch_and_save(target, cursor.to_block + 1, to_block).await?;
+            cursor.to_block = to_block;
+            cursor.to_block_hash = self.coin.history_block_hash(to_block).await?;
+            self.save_cursor(&token_id, &cursor).await?;
+        }
+
+        for _ in 0..BACKFILL_CHUNKS_PER_ROUND {
+            if cursor.from_block == 0 {
+                break;
+            }
+            let from_block = cursor.from_block.saturating_sub(range);
+            self.fetch_and_save(target, from_block, cursor.from_block - 1).await?;
+            cursor.from_block = from_block;
+            self.save_cursor(&token_id, &cursor).await?;
+        }
+        Ok(cursor.from_block)
+    }
+
+    /// Checks if the last fetched block is still in the chain.
+    /// Otherwise, removes the transactions of the last [`REORG_ROLLBACK_DEPTH`] blocks to fetch them again.
+    async fn rollback_on_reorg(&self, target: &HistoryTarget, cursor: BlockCursor) -> SyncResult<BlockCursor> {
+        if self.coin.history_block_hash(cursor.to_block).await? == cursor.to_block_hash {
+            return Ok(cursor);
+        }
+
+        let rollback_to = cursor
+            .to_block
+            .saturating_sub(REORG_ROLLBACK_DEPTH)
+            .max(cursor.from_block);
+        warn!(
+            "Chain reorganization detected at block {} for {}, rolling the history back to block {}",
+            cursor.to_block, target.ticker, rollback_to
+        );
+
+        let wallet_id = self.coin.history_wallet_id();
+        let filters = self
+            .coin
+            .get_tx_history_filters_for(&target.token_id())
+            .map_err(storage_err)?;
+        // The transactions are sorted by the block height in descending order,
+        // so the pages are requested until a transaction of a not reverted block is met or the history ends.
+        // The reverted transactions are removed after all of them are collected, not to shift the pages.
+        let mut reverted = Vec::new();
+        let mut page_number = NonZeroUsize::new(1).expect("1 is not zero");
+        loop {
+            let paging = PagingOptionsEnum::PageNumber(page_number);
+            let page = self
+                .storage
+                .get_history(&wallet_id, filters.clone(), paging, REORG_REMOVE_BATCH)
+                .await
+                .map_err(storage_err)?;
+            let page_len = page.transactions.len();
+            let reverted_before = reverted.len();
+            reverted.extend(
+                page.transactions
+                    .into_iter()
+                    .filter(|tx| tx.block_height > rollback_to)
+                    .map(|tx| tx.internal_id),
+            );
+            let is_last_page =
+                reverted.len() - reverted_before < page_len || page_number.get() * REORG_REMOVE_BATCH >= page.total;
+            if is_last_page {
+                break;
+            }
+            page_number = NonZeroUsize::new(page_number.get() + 1).expect("page number is not zero");
+        }
+        for internal_id in reverted {
+            self.storage
+                .remove_tx_from_history(&wallet_id, &internal_id)
+                .await
+                .map_err(storage_err)?;
+        }
+
+        let cursor = BlockCursor {
+            from_block: cursor.from_block,
+            to_block: rollback_to,
+            to_block_hash: self.coin.history_block_hash(rollback_to).await?,
+        };
+        self.save_cursor(&target.token_id(), &cursor).await?;
+        Ok(cursor)
+    }
+
+    async fn save_cursor(&self, token_id: &str, cursor: &BlockCursor) -> SyncResult<()> {
+        self.storage
+            .set_block_cursor(&self.coin.history_wallet_id(), token_id, cursor)
+            .await
+            .map_err(storage_err)
+    }
+
+    /// Fetches the transfers of `target` within the inclusive block range and adds the new ones to the history.
+    async fn fetch_and_save(&self, target: &HistoryTarget, from_block: u64, to_block: u64) -> SyncResult<()> {
+        let transfers = match &target.token {
+            Some(token) => self.coin.erc20_transfers(token, from_block, to_block).await?,
+            None => self.coin.eth_transfers(from_block, to_block).await?,
+        };
+
+        let wallet_id = self.coin.history_wallet_id();
+        let mut tx_data_cache = HashMap::new();
+        let mut txs = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            // The transaction could be saved already if the node was stopped before the cursor was saved.
+            let internal_id = transfer.internal_id();
+            if txs.iter().any(|tx: &TransactionDetails| tx.internal_id == internal_id)
+                || self
+                    .storage
+                    .get_tx_from_history(&wallet_id, &internal_id)
+                    .await
+                    .map_err(storage_err)?
+                    .is_some()
+            {
+                continue;
+            }
+
+            if !tx_data_cache.contains_key(&transfer.tx_hash) {
+                let tx_data = self
+                    .coin
+                    .history_tx_data(transfer.tx_hash, transfer.block_number)
+                    .await?;
+                tx_data_cache.insert(transfer.tx_hash, tx_data);
+            }
+            let tx_data = &tx_data_cache[&transfer.tx_hash];
+            txs.push(self.coin.transfer_details(target, transfer, internal_id, tx_data)?);
+        }
+
+        if !txs.is_empty() {
+            self.storage
+                .add_transactions_to_history(&wallet_id, txs)
+                .await
+                .map_err(storage_err)?;
+        }
+        Ok(())
+    }
+}
+
+/// A transfer of ETH or an ERC20 token from or to my address.
+struct Transfer {
+    tx_hash: H256,
+    block_number: u64,
+    from: Address,
+    to: Address,
+    amount: U256,
+    /// Distinguishes several transfers within one transaction:
+    /// the trace address for an ETH transfer, the token address and the log index for an ERC20 transfer.
+    position: Vec<u8>,
+    /// Whether the transfer has been reverted, e.g. by the failed internal call.
+    failed: bool,
+}
+
+impl Transfer {
+    fn internal_id(&self) -> BytesJson {
+        let mut preimage = self.tx_hash.as_bytes().to_vec();
+        preimage.extend_from_slice(&self.position);
+        sha256(&preimage).to_vec().into()
+    }
+}
+
+/// The data of a transaction which is common for all the transfers within it.
+pub(super) struct HistoryTxData {
+    pub(super) tx_hash: String,
+    pub(super) tx_hex: BytesJson,
+    sender: Option<Address>,
+    fee_details: Option<TxFeeDetails>,
+    timestamp: u64,
+}
+
+impl HistoryTxData {
+    /// `web3_tx` is the mined transaction as it's returned by `eth_getTransactionByHash`.
+    /// If the transaction can't be restored, e.g. its type is not supported, `tx_hex` is left empty,
+    /// so the transfers are still saved in the history.
+    pub(super) fn new(
+        web3_tx: Web3Transaction,
+        chain_id: Option<u64>,
+        fee_details: Option<TxFeeDetails>,
+        timestamp: u64,
+    ) -> HistoryTxData {
+        let tx_hash = format!("{:02x}", BytesJson::from(web3_tx.hash.as_bytes().to_vec()));
+        let sender = web3_tx.from;
+        let tx_hex = match signed_tx_from_web3_tx(web3_tx, chain_id) {
+            Ok(signed) => BytesJson::from(signed.tx_hex()),
+            Err(e) => {
+                warn!("Couldn't restore the transaction {}: {}", tx_hash, e);
+                BytesJson::default()
+            },
+        };
+        HistoryTxData {
+            tx_hash,
+            tx_hex,
+            sender,
+            fee_details,
+            timestamp,
+        }
+    }
+}
+
+/// Returns [`SyncError::Unsupported`] if the node doesn't provide the `trace` API.
+fn trace_filter_err(e: web3::Error) -> SyncError {
+    match e {
+        web3::Error::Rpc(ref rpc_err)
+            if rpc_err.code == ErrorCode::MethodNotFound
+                || rpc_err.code == ErrorCode::ServerError(METHOD_NOT_SUPPORTED_CODE) =>
+        {
+            SyncError::Unsupported(format!("'trace_filter' is not supported by the node: {}", e))
+        },
+        e => rpc_err(e),
+    }
+}
+
+#[cfg_attr(test, mockable)]
+impl EthCoin {
+    /// `token_id` is empty for the platform coin.
+    fn get_tx_history_filters_for(&self, token_id: &str) -> MmResult<GetTxHistoryFilters, MyAddressError> {
+        let filters = GetTxHistoryFilters::for_address(self.my_address()?);
+        if token_id.is_empty() {
+            Ok(filters)
+        } else {
+            Ok(filters.with_token_id(token_id.to_owned()))
+        }
+    }
+
+    async fn history_block_hash(&self, block_number: u64) -> SyncResult<String> {
+        let block = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+            .await
+            .map_err(rpc_err)?;
+        match block.and_then(|block| block.hash) {
+            Some(hash) => Ok(format!("{:02x}", BytesJson::from(hash.as_bytes().to_vec()))),
+            None => Err(SyncError::Rpc(format!("Block {} is not found", block_number))),
+        }
+    }
+
+    /// Requests ETH transfers from and to my address using `trace_filter`.
+    /// Please note the node has to support the `trace` API.
+    async fn eth_transfers(&self, from_block: u64, to_block: u64) -> SyncResult<Vec<Transfer>> {
+        let filter = |from_addr, to_addr| {
+            TraceFilterBuilder::default()
+                .from_address(from_addr)
+                .to_address(to_addr)
+                .from_block(BlockNumber::Number(from_block.into()))
+                .to_block(BlockNumber::Number(to_block.into()))
+                .build()
+        };
+        // The node doesn't support `from OR to` filters, so the traces are requested twice.
+        let from_me = self.web3.trace().filter(filter(vec![self.my_address], vec![]));
+        let to_me = self.web3.trace().filter(filter(vec![], vec![self.my_address]));
+        let (from_me, to_me) = futures::future::try_join(from_me, to_me)
+            .await
+            .map_err(trace_filter_err)?;
+
+        let transfers = from_me
+            .into_iter()
+            .chain(to_me)
+            .filter_map(|trace| {
+                // Only standard calls are supported, contract creations, suicides and block rewards are skipped.
+                let call = match trace.action {
+                    TraceAction::Call(call) => call,
+                    _ => return None,
+                };
+                let position = trace
+                    .trace_address
+                    .iter()
+                    .flat_map(|index| (*index as u64).to_be_bytes())
+                    .collect();
+                Some(Transfer {
+                    tx_hash: trace.transaction_hash?,
+                    block_number: trace.block_number,
+                    from: call.from,
+                    to: call.to,
+                    amount: call.value,
+                    position,
+                    failed: trace.error.is_some(),
+                })
+            })
+            .collect();
+        Ok(transfers)
+    }
+
+    /// Requests ERC20 transfers from and to my address using the `Transfer` event logs.
+    async fn erc20_transfers(
+        &self,
+        token: &Erc20TokenInfo,
+        from_block: u64,
+        to_block: u64,
+    ) -> SyncResult<Vec<Transfer>> {
+        let from_me = self.erc20_transfer_events(
+            token.token_address,
+            Some(self.my_address),
+            None,
+            BlockNumber::Number(from_block.into()),
+            BlockNumber::Number(to_block.into()),
+            None,
+        );
+        let to_me = self.erc20_transfer_events(
+            token.token_address,
+            None,
+            Some(self.my_address),
+            BlockNumber::Number(from_block.into()),
+            BlockNumber::Number(to_block.into()),
+            None,
+        );
+        let (from_me, to_me) = from_me.join(to_me).compat().await.map_err(SyncError::Rpc)?;
+
+        let transfers = from_me
+            .into_iter()
+            .chain(to_me)
+            .filter(|log| !log.is_removed() && log.topics.len() >= 3)
+            .filter_map(|log| {
+                let mut position = token.token_address.as_bytes().to_vec();
+                position.extend_from_slice(&log.log_index?.low_u64().to_be_bytes());
+                Some(Transfer {
+                    tx_hash: log.transaction_hash?,
+                    block_number: log.block_number?.as_u64(),
+                    from: H160::from(log.topics[1]),
+                    to: H160::from(log.topics[2]),
+                    amount: U256::from(log.data.0.as_slice()),
+                    position,
+                    failed: false,
+                })
+            })
+            .collect();
+        Ok(transfers)
+    }
+
+    async fn history_tx_data(&self, tx_hash: H256, block_number: u64) -> SyncResult<HistoryTxData> {
+        let web3_tx = self
+            .web3
+            .eth()
+            .transaction(TransactionId::Hash(tx_hash))
+            .await
+            .map_err(rpc_err)?
+            .ok_or_else(|| SyncError::Rpc(format!("Transaction {:?} is not found", tx_hash)))?;
+        let receipt = self.web3.eth().transaction_receipt(tx_hash).await.map_err(rpc_err)?;
+        let block = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+            .await
+            .map_err(rpc_err)?
+            .ok_or_else(|| SyncError::Rpc(format!("Block {} is not found", block_number)))?;
+
+        // The fee is always paid in the platform coin.
+        // The gas price of a mined transaction is the effective one, even for the EIP-1559 transactions.
+        let fee_details = match receipt {
+            Some(receipt) => {
+                let gas_used = receipt.gas_used.unwrap_or_default();
+                let gas_price = web3_tx.gas_price.unwrap_or_default();
+                let fee = EthTxFeeDetails::new(gas_used, PayForGasOption::Legacy { gas_price }, self.platform_ticker())
+                    .map_err(|e| SyncError::Rpc(e.to_string()))?;
+                Some(fee.into())
+            },
+            None => None,
+        };
+
+        // The typed transactions are signed over `chain_id`, so it's required to restore them.
+        let is_typed = web3_tx.transaction_type.map_or(false, |tx_type| !tx_type.is_zero());
+        let chain_id = match self.chain_id {
+            None if is_typed => Some(self.web3.eth().chain_id().await.map_err(rpc_err)?.as_u64()),
+            chain_id => chain_id,
+        };
+        Ok(HistoryTxData::new(
+            web3_tx,
+            chain_id,
+            fee_details,
+            block.timestamp.into_or_max(),
+        ))
+    }
+
+    fn transfer_details(
+        &self,
+        target: &HistoryTarget,
+        transfer: Transfer,
+        internal_id: BytesJson,
+        tx_data: &HistoryTxData,
+    ) -> SyncResult<TransactionDetails> {
+        let (decimals, transaction_type) = match &target.token {
+            Some(token) => (token.decimals, erc20_tx_type(&token.token_address)),
+            None => (ETH_DECIMALS, TransactionType::StandardTransfer),
+        };
+        let total_amount = u256_to_big_decimal(transfer.amount, decimals).map_err(|e| SyncError::Rpc(e.to_string()))?;
+
+        // The amount is transferred only if the call hasn't been reverted.
+        let transferred = if transfer.failed {
+            BigDecimal::from(0)
+        } else {
+            total_amount.clone()
+        };
+        let mut spent_by_me = BigDecimal::from(0);
+        let mut received_by_me = BigDecimal::from(0);
+        if transfer.from == self.my_address {
+            spent_by_me = transferred.clone();
+        }
+        if transfer.to == self.my_address {
+            received_by_me = transferred;
+        }
+        // The fee of the platform coin transaction is spent by me if I sent the transaction.
+        if target.token.is_none() && tx_data.sender == Some(self.my_address) && transfer.from == self.my_address {
+            if let Some(TxFeeDetails::Eth(fee)) = &tx_data.fee_details {
+                spent_by_me += &fee.total_fee;
+            }
+        }
+
+        Ok(TransactionDetails {
+            my_balance_change: &received_by_me - &spent_by_me,
+            spent_by_me,
+            received_by_me,
+            total_amount,
+            to: vec![checksum_address(&format!("{:#02x}", transfer.to))],
+            from: vec![checksum_address(&format!("{:#02x}", transfer.from))],
+            coin: target.ticker.clone(),
+            fee_details: tx_data.fee_details.clone(),
+            block_height: transfer.block_number,
+            tx_hash: tx_data.tx_hash.clone(),
+            tx_hex: tx_data.tx_hex.clone(),
+            internal_id,
+            timestamp: tx_data.timestamp,
+            kmd_rewards: None,
+            transaction_type,
+            memo: None,
+            psbt: None,
+            to_name: None,
+        })
+    }
+
+    fn set_history_sync_state(&self, state: HistorySyncState) { *self.history_sync_state.lock().unwrap() = state; }
+}
+
+struct EthHistoryInit<Storage> {
+    phantom: std::marker::PhantomData<Storage>,
+}
+
+impl<Storage> EthHistoryInit<Storage> {
+    fn new() -> Self {
+        EthHistoryInit {
+            phantom: Default::default(),
+        }
+    }
+}
+
+struct FetchingHistory<Storage> {
+    phantom: std::marker::PhantomData<Storage>,
+}
+
+impl<Storage> FetchingHistory<Storage> {
+    fn new() -> Self {
+        FetchingHistory {
+            phantom: Default::default(),
+        }
+    }
+}
+
+struct WaitForNewBlocks<Storage> {
+    last_fetched_block: u64,
+    phantom: std::marker::PhantomData<Storage>,
+}
+
+impl<Storage> WaitForNewBlocks<Storage> {
+    fn new(last_fetched_block: u64) -> Self {
+        WaitForNewBlocks {
+            last_fetched_block,
+            phantom: Default::default(),
+        }
+    }
+}
+
+struct OnIoErrorCooldown<Storage> {
+    phantom: std::marker::PhantomData<Storage>,
+}
+
+impl<Storage> OnIoErrorCooldown<Storage> {
+    fn new() -> Self {
+        OnIoErrorCooldown {
+            phantom: Default::default(),
+        }
+    }
+}
+
+struct Stopped<Storage> {
+    stop_reason: String,
+    phantom: std::marker::PhantomData<Storage>,
+}
+
+impl<Storage> Stopped<Storage> {
+    fn storage_error(e: String) -> Self {
+        Stopped {
+            stop_reason: format!("Storage error: {}", e),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<Storage> TransitionFrom<EthHistoryInit<Storage>> for FetchingHistory<Storage> {}
+impl<Storage> TransitionFrom<EthHistoryInit<Storage>> for Stopped<Storage> {}
+impl<Storage> TransitionFrom<FetchingHistory<Storage>> for FetchingHistory<Storage> {}
+impl<Storage> TransitionFrom<FetchingHistory<Storage>> for WaitForNewBlocks<Storage> {}
+impl<Storage> TransitionFrom<FetchingHistory<Storage>> for OnIoErrorCooldown<Storage> {}
+impl<Storage> TransitionFrom<FetchingHistory<Storage>> for Stopped<Storage> {}
+impl<Storage> TransitionFrom<WaitForNewBlocks<Storage>> for FetchingHistory<Storage> {}
+impl<Storage> TransitionFrom<WaitForNewBlocks<Storage>> for OnIoErrorCooldown<Storage> {}
+impl<Storage> TransitionFrom<OnIoErrorCooldown<Storage>> for FetchingHistory<Storage> {}
+
+#[async_trait]
+impl<Storage: TxHistoryStorage> State for EthHistoryInit<Storage> {
+    type Ctx = EthTxHistoryCtx<Storage>;
+    type Result = ();
+
+    async fn on_changed(self: Box<Self>, ctx: &mut Self::Ctx) -> StateResult<Self::Ctx, Self::Result> {
+        ctx.coin.set_history_sync_state(HistorySyncState::NotStarted);
+
+        let wallet_id = ctx.coin.history_wallet_id();
+        match ctx.storage.is_initialized_for(&wallet_id).await {
+            Ok(true) => (),
+            Ok(false) => {
+                if let Err(e) = ctx.storage.init(&wallet_id).await {
+                    return Self::change_state(Stopped::storage_error(format!("{:?}", e)));
+                }
+            },
+            Err(e) => return Self::change_state(Stopped::storage_error(format!("{:?}", e))),
+        }
+
+        Self::change_state(FetchingHistory::new())
+    }
+}
+
+#[async_trait]
+impl<Storage: TxHistoryStorage> State for FetchingHistory<Storage> {
+    type Ctx = EthTxHistoryCtx<Storage>;
+    type Result = ();
+
+    async fn on_changed(self: Box<Self>, ctx: &mut Self::Ctx) -> StateResult<Self::Ctx, Self::Result> {
+        let current_block = match ctx.coin.web3.eth().block_number().await {
+            Ok(block) => block.as_u64(),
+            Err(e) => {
+                error!("Error {} on eth_blockNumber for {}", e, ctx.coin.ticker);
+                return Self::change_state(OnIoErrorCooldown::new());
+            },
+        };
+
+        let mut blocks_left = 0;
+        let mut rpc_error = false;
+        for target in ctx.history_targets() {
+            // An error of one coin shouldn't prevent fetching the history of others,
+            // e.g. if the node doesn't support `trace_filter`, the ERC20 history can be fetched still.
+            match ctx.sync_target(&target, current_block).await {
+                Ok(left) => blocks_left += left,
+                Err(SyncError::Rpc(e)) => {
+                    error!("Error {} on fetching the {} history", e, target.ticker);
+                    rpc_error = true;
+                },
+                Err(SyncError::Unsupported(e)) => {
+                    warn!("The {} history can't be fetched: {}", target.ticker, e);
+                    ctx.eth_history_unsupported = Some(e);
+                },
+                Err(SyncError::Storage(e)) => return Self::change_state(Stopped::storage_error(e)),
+            }
+        }
+
+        if rpc_error {
+            return Self::change_state(OnIoErrorCooldown::new());
+        }
+        if let Some(reason) = &ctx.eth_history_unsupported {
+            ctx.coin.set_history_sync_state(HistorySyncState::Error(json!({
+                "message": reason,
+            })));
+        } else if blocks_left > 0 {
+            ctx.coin.set_history_sync_state(HistorySyncState::InProgress(json!({
+                "blocks_left": blocks_left,
+            })));
+        } else {
+            ctx.coin.set_history_sync_state(HistorySyncState::Finished);
+        }
+
+        if blocks_left > 0 {
+            return Self::change_state(FetchingHistory::new());
+        }
+        Self::change_state(WaitForNewBlocks::new(current_block))
+    }
+}
+
+#[async_trait]
+impl<Storage: TxHistoryStorage> State for WaitForNewBlocks<Storage> {
+    type Ctx = EthTxHistoryCtx<Storage>;
+    type Result = ();
+
+    async fn on_changed(self: Box<Self>, ctx: &mut Self::Ctx) -> StateResult<Self::Ctx, Self::Result> {
+        loop {
+            Timer::sleep(NEW_BLOCKS_POLL_INTERVAL).await;
+
+            match ctx.coin.web3.eth().block_number().await {
+                Ok(block) if block.as_u64() > self.last_fetched_block => {
+                    return Self::change_state(FetchingHistory::new())
+                },
+                Ok(_) => (),
+                Err(e) => {
+                    error!("Error {} on eth_blockNumber for {}", e, ctx.coin.ticker);
+                    return Self::change_state(OnIoErrorCooldown::new());
+                },
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<Storage: TxHistoryStorage> State for OnIoErrorCooldown<Storage> {
+    type Ctx = EthTxHistoryCtx<Storage>;
+    type Result = ();
+
+    async fn on_changed(self: Box<Self>, _ctx: &mut Self::Ctx) -> StateResult<Self::Ctx, Self::Result> {
+        Timer::sleep(ON_IO_ERROR_COOLDOWN).await;
+        // The fetching is continued from the saved cursors.
+        Self::change_state(FetchingHistory::new())
+    }
+}
+
+#[async_trait]
+impl<Storage: TxHistoryStorage> LastState for Stopped<Storage> {
+    type Ctx = EthTxHistoryCtx<Storage>;
+    type Result = ();
+
+    async fn on_changed(self: Box<Self>, ctx: &mut Self::Ctx) -> Self::Result {
+        info!(
+            "Stopping tx history fetching for {}. Reason: {}",
+            ctx.coin.ticker, self.stop_reason
+        );
+        ctx.coin.set_history_sync_state(HistorySyncState::Error(json!({
+            "message": self.stop_reason,
+        })));
+    }
+}
+
+/// Fetches the history of the platform `coin` and its ERC20 tokens, and keeps it up to date.
+pub async fn eth_history_loop(coin: EthCoin, storage: impl TxHistoryStorage) {
+    let ctx = EthTxHistoryCtx {
+        coin,
+        storage,
+        eth_history_unsupported: None,
+    };
+    let state_machine: StateMachine<_, ()> = StateMachine::from_ctx(ctx);
+    state_machine.run(EthHistoryInit::new()).await;
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::eth::eth_tests::eth_coin_for_test;
+    use crate::tx_history_storage::TxHistoryStorageBuilder;
+    use common::block_on;
+    use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
+    use mocktopus::mocking::*;
+
+    const LOGS_BLOCK_RANGE: u64 = 100;
+
+    fn history_ctx_for_test() -> EthTxHistoryCtx<impl TxHistoryStorage> {
+        let (_ctx, mut coin) = eth_coin_for_test(EthCoinType::Eth, &["http://dummy.dummy"], None);
+        Arc::get_mut(&mut coin.0).unwrap().logs_block_range = LOGS_BLOCK_RANGE;
+
+        let storage = TxHistoryStorageBuilder::new(&mm_ctx_with_custom_db()).build().unwrap();
+        block_on(storage.init(&coin.history_wallet_id())).unwrap();
+        EthTxHistoryCtx {
+            coin,
+            storage,
+            eth_history_unsupported: None,
+        }
+    }
+
+    fn eth_target() -> HistoryTarget {
+        HistoryTarget {
+            ticker: "ETH".to_owned(),
+            token: None,
+        }
+    }
+
+    fn mock_block_hashes() {
+        EthCoin::history_block_hash.mock_safe(|_, block_number| {
+            MockResult::Return(Box::pin(futures::future::ok(format!("hash_{}", block_number))))
+        });
+    }
+
+    /// Mocks `trace_filter` with no transfers found and returns the requested block ranges.
+    fn mock_eth_transfers() -> Arc<Mutex<Vec<(u64, u64)>>> {
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let requested_clone = requested.clone();
+        EthCoin::eth_transfers.mock_safe(move |_, from_block, to_block| {
+            requested_clone.lock().unwrap().push((from_block, to_block));
+            MockResult::Return(Box::pin(futures::future::ok(Vec::new())))
+        });
+        requested
+    }
+
+    fn history_tx(coin: &EthCoin, internal_id: u64, block_height: u64) -> TransactionDetails {
+        let my_address = coin.my_address().unwrap();
+        TransactionDetails {
+            tx_hex: BytesJson::default(),
+            tx_hash: format!("{:064x}", internal_id),
+            from: vec![my_address.clone()],
+            to: vec![my_address],
+            total_amount: 0.into(),
+            spent_by_me: 0.into(),
+            received_by_me: 0.into(),
+            my_balance_change: 0.into(),
+            block_height,
+            timestamp: 0,
+            fee_details: None,
+            coin: coin.ticker.clone(),
+            internal_id: internal_id.to_be_bytes().to_vec().into(),
+            kmd_rewards: None,
+            transaction_type: TransactionType::StandardTransfer,
+            memo: None,
+            psbt: None,
+            to_name: None,
+        }
+    }
+
+    fn saved_cursor<Storage: TxHistoryStorage>(ctx: &EthTxHistoryCtx<Storage>) -> Option<BlockCursor> {
+        block_on(ctx.storage.get_block_cursor(&ctx.coin.history_wallet_id(), "")).unwrap()
+    }
+
+    #[test]
+    fn test_sync_target_resumes_from_cursor() {
+        let ctx = history_ctx_for_test();
+        mock_block_hashes();
+        let requested = mock_eth_transfers();
+
+        let cursor = BlockCursor {
+            from_block: 150,
+            to_block: 950,
+            to_block_hash: "hash_950".to_owned(),
+        };
+        block_on(ctx.save_cursor("", &cursor)).unwrap();
+
+        let blocks_left = block_on(ctx.sync_target(&eth_target(), 1000)).unwrap();
+        assert_eq!(blocks_left, 0);
+        // The new blocks are fetched first, then the older ones down to the genesis block.
+        assert_eq!(*requested.lock().unwrap(), vec![(951, 1000), (50, 149), (0, 49)]);
+        let expected_cursor = BlockCursor {
+            from_block: 0,
+            to_block: 1000,
+            to_block_hash: "hash_1000".to_owned(),
+        };
+        assert_eq!(saved_cursor(&ctx), Some(expected_cursor));
+
+        // Nothing is requested again if there are no new blocks.
+        requested.lock().unwrap().clear();
+        let blocks_left = block_on(ctx.sync_target(&eth_target(), 1000)).unwrap();
+        assert_eq!(blocks_left, 0);
+        assert!(requested.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sync_target_backfill_terminates() {
+        let ctx = history_ctx_for_test();
+        mock_block_hashes();
+        let requested = mock_eth_transfers();
+
+        let cursor = BlockCursor {
+            from_block: 5000,
+            to_block: 6000,
+            to_block_hash: "hash_6000".to_owned(),
+        };
+        block_on(ctx.save_cursor("", &cursor)).unwrap();
+
+        // Only `BACKFILL_CHUNKS_PER_ROUND` older chunks are fetched per round.
+        let blocks_left = block_on(ctx.sync_target(&eth_target(), 6000)).unwrap();
+        assert_eq!(blocks_left, 4000);
+        assert_eq!(requested.lock().unwrap().len(), BACKFILL_CHUNKS_PER_ROUND);
+
+        let mut rounds = 1;
+        let mut blocks_left = blocks_left;
+        while blocks_left > 0 {
+            assert!(rounds < 5, "the backfill doesn't terminate");
+            blocks_left = block_on(ctx.sync_target(&eth_target(), 6000)).unwrap();
+            rounds += 1;
+        }
+        assert_eq!(rounds, 5);
+
+        // The older chunks are adjacent, and the backfill stops at the genesis block.
+        let requested = requested.lock().unwrap();
+        assert_eq!(requested.len(), 5 * BACKFILL_CHUNKS_PER_ROUND);
+        assert_eq!(requested.first(), Some(&(4900, 4999)));
+        assert_eq!(requested.last(), Some(&(0, 99)));
+        for chunks in requested.windows(2) {
+            assert_eq!(chunks[1].1 + 1, chunks[0].0);
+        }
+        assert_eq!(saved_cursor(&ctx).unwrap().from_block, 0);
+    }
+
+    #[test]
+    fn test_sync_target_rolls_back_on_reorg() {
+        let ctx = history_ctx_for_test();
+        mock_block_hashes();
+        let requested = mock_eth_transfers();
+        let wallet_id = ctx.coin.history_wallet_id();
+
+        // The last fetched block has been replaced by a chain reorganization.
+        let cursor = BlockCursor {
+            from_block: 0,
+            to_block: 1000,
+            to_block_hash: "hash_of_the_reverted_block".to_owned(),
+        };
+        block_on(ctx.save_cursor("", &cursor)).unwrap();
+
+        let rollback_to = 1000 - REORG_ROLLBACK_DEPTH;
+        // More transactions than `REORG_REMOVE_BATCH` are reverted, so they are requested by several pages.
+        let reverted = (0..150).map(|id| history_tx(&ctx.coin, id, rollback_to + 1 + id % REORG_ROLLBACK_DEPTH));
+        let kept: Vec<_> = (150..155)
+            .map(|id| history_tx(&ctx.coin, id, rollback_to - (id - 150)))
+            .collect();
+        block_on(
+            ctx.storage
+                .add_transactions_to_history(&wallet_id, reverted.chain(kept.clone())),
+        )
+        .unwrap();
+
+        let blocks_left = block_on(ctx.sync_target(&eth_target(), 1000)).unwrap();
+        assert_eq!(blocks_left, 0);
+        // The reverted blocks are fetched again.
+        assert_eq!(*requested.lock().unwrap(), vec![(rollback_to + 1, 1000)]);
+        let expected_cursor = BlockCursor {
+            from_block: 0,
+            to_block: 1000,
+            to_block_hash: "hash_1000".to_owned(),
+        };
+        assert_eq!(saved_cursor(&ctx), Some(expected_cursor));
+
+        let filters = ctx.coin.get_tx_history_filters_for("").unwrap();
+        let paging = PagingOptionsEnum::PageNumber(NonZeroUsize::new(1).unwrap());
+        let history = block_on(ctx.storage.get_history(&wallet_id, filters, paging, 1000)).unwrap();
+        let actual_ids: Vec<_> = history.transactions.into_iter().map(|tx| tx.internal_id).collect();
+        let expected_ids: Vec<_> = kept.into_iter().map(|tx| tx.internal_id).collect();
+        assert_eq!(actual_ids, expected_ids);
+    }
+}
@@ -1,5 +1,5 @@
 use super::*;
-use crate::lp_coininit;
+use crate::{lp_coininit, CoinsContext};
 use crypto::CryptoCtx;
 use mm2_core::mm_ctx::MmCtxBuilder;
 use mm2_test_helpers::for_tests::{ETH_DEV_NODE, ETH_DEV_SWAP_CONTRACT};
@@ -27,11 +27,13 @@ async fn test_send() {
         ticker: "ETH".into(),
         coin_type: EthCoinType::Eth,
         my_address: key_pair.address(),
+        derivation_method: Arc::new(DerivationMethod::SingleAddress(ChecksumAddress(key_pair.address()))),
         sign_message_prefix: Some(String::from("Ethereum Signed Message:\n")),
         priv_key_policy: key_pair.into(),
         swap_contract_address: Address::from_str(ETH_DEV_SWAP_CONTRACT).unwrap(),
         fallback_swap_contract: None,
         contract_supports_watchers: false,
+        contract_supports_permit: false,
         web3_instances: vec![Web3Instance {
             web3: web3.clone(),
             is_parity: false,
@@ -45,8 +47,11 @@ async fn test_send() {
         ctx: ctx.weak(),
         required_confirmations: 1.into(),
         chain_id: None,
+        name_registry: None,
         logs_block_range: DEFAULT_LOGS_BLOCK_RANGE,
         nonce_lock: new_nonce_lock(),
+        exact_swap_approvals: false,
+        swap_txs_eip1559: false,
         erc20_tokens_infos: Default::default(),
         abortable_system: AbortableQueue::default(),
     }));
@@ -0,0 +1,102 @@
+//! Resolving ENS (or ENS-compatible) names passed as the withdraw destination.
+//! https://docs.ens.domains/resolution
+
+use super::*;
+
+/// The ENS registry is deployed at the same address on the Ethereum mainnet and testnets.
+const ENS_REGISTRY: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+/// The chains where [`ENS_REGISTRY`] is used unless `name_registry` is set in the coin config:
+/// the Ethereum mainnet, Goerli and Sepolia.
+const ENS_CHAIN_IDS: [u64; 3] = [1, 5, 11155111];
+
+/// Returns the `name_registry` set in the coin config or the ENS registry if the coin is on an ENS chain.
+pub(super) fn name_registry_from_conf(conf: &Json) -> Result<Option<Address>, String> {
+    if let Some(registry) = conf["name_registry"].as_str() {
+        let registry = try_s!(addr_from_str(registry));
+        return Ok(Some(registry));
+    }
+    match conf["chain_id"].as_u64() {
+        Some(chain_id) if ENS_CHAIN_IDS.contains(&chain_id) => Ok(Some(try_s!(addr_from_str(ENS_REGISTRY)))),
+        _ => Ok(None),
+    }
+}
+
+impl EthCoin {
+    /// Returns the address of the withdraw destination `to`.
+    /// If `to` is a name rather than a hex address, it's resolved and returned along with the address.
+    pub(super) async fn resolve_withdraw_to(&self, to: &str) -> MmResult<(Address, Option<String>), WithdrawError> {
+        if !is_name(to) {
+            let to_addr = self.address_from_str(to).map_to_mm(WithdrawError::InvalidAddress)?;
+            return Ok((to_addr, None));
+        }
+
+        let name = normalize_name(to).map_to_mm(WithdrawError::InvalidAddress)?;
+        let to_addr = self.resolve_name(&name).await?;
+        Ok((to_addr, Some(name)))
+    }
+
+    /// Asks the registry for the resolver of the `name` and then the resolver for the address.
+    async fn resolve_name(&self, name: &str) -> MmResult<Address, WithdrawError> {
+        let registry = self.name_registry.or_mm_err(|| {
+            WithdrawError::InvalidAddress(format!(
+                "Can't resolve '{}': 'name_registry' is not set for {}",
+                name, self.ticker
+            ))
+        })?;
+        let node = namehash(name);
+
+        let resolver = self.call_name_function(registry, "resolver", node).await?;
+        if resolver.is_zero() {
+            let error = format!("'{}' is not registered or has no resolver", name);
+            return MmError::err(WithdrawError::InvalidAddress(error));
+        }
+        let address = self.call_name_function(resolver, "addr", node).await?;
+        if address.is_zero() {
+            let error = format!("'{}' doesn't resolve to an address", name);
+            return MmError::err(WithdrawError::InvalidAddress(error));
+        }
+        Ok(address)
+    }
+
+    async fn call_name_function(
+        &self,
+        contract: Address,
+        function_name: &str,
+        node: [u8; 32],
+    ) -> MmResult<Address, WithdrawError> {
+        let function = ENS_CONTRACT.function(function_name)?;
+        let data = function.encode_input(&[Token::FixedBytes(node.to_vec())])?;
+        let output = self.call_request(contract, None, Some(data.into())).await?;
+        match function.decode_output(&output.0)?.pop() {
+            Some(Token::Address(address)) => Ok(address),
+            token => MmError::err(WithdrawError::Transport(format!(
+                "Unexpected '{}' output: {:?}",
+                function_name, token
+            ))),
+        }
+    }
+}
+
+/// Hex addresses are prefixed with `0x` and can't contain dots, so anything else with a dot is considered as a name.
+fn is_name(to: &str) -> bool { !to.starts_with("0x") && to.contains('.') }
+
+/// Lowercases the name. Full UTS-46 normalization isn't supported, so only ASCII names are accepted.
+fn normalize_name(name: &str) -> Result<String, String> {
+    if !name.is_ascii() {
+        return ERR!("Only ASCII names are supported, got '{}'", name);
+    }
+    let name = name.to_ascii_lowercase();
+    if name.split('.').any(str::is_empty) {
+        return ERR!("Invalid name '{}': empty label", name);
+    }
+    Ok(name)
+}
+
+/// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-137.md#namehash-algorithm
+pub(super) fn namehash(name: &str) -> [u8; 32] {
+    name.rsplit('.').fold([0; 32], |node, label| {
+        let mut data = node.to_vec();
+        data.extend_from_slice(&keccak256(label.as_bytes()).take());
+        keccak256(&data).take()
+    })
+}
@@ -0,0 +1,300 @@
+//! Replacing the pending transactions that block the nonce of my address.
+
+use super::*;
+use crate::rpc_command::eth_pending_tx::{PendingEthTxError, PendingEthTxResult};
+use crate::{WithdrawFrom, WithdrawSenderAddress};
+use rpc::v1::types::H256 as H256Json;
+
+/// The nodes don't accept a transaction replacing a pending one unless it pays at least 10% more per gas.
+const REPLACEMENT_FEE_BUMP_PERCENT: u64 = 10;
+/// The gas used by a plain ETH transfer.
+const ETH_TRANSFER_GAS: u64 = 21_000;
+
+impl EthCoin {
+    /// Requests the latest and the pending transaction counts of my address or the HD wallet address that `from` refers to.
+    /// Returns the checksum address along with its nonces.
+    pub async fn addr_nonces(&self, from: Option<&WithdrawFrom>) -> PendingEthTxResult<(String, AddrNonces)> {
+        let ChecksumAddress(address) = self.get_sender_address(from).await?.address;
+        let nonces = get_addr_nonces(address, self.web3_instances.clone()).compat().await?;
+        Ok((checksum_address(&format!("{:#02x}", address)), nonces))
+    }
+
+    /// Signs a transaction with the same nonce, recipient, value and data as the pending `tx_hash`,
+    /// but with the higher fee.
+    pub async fn speed_up_pending_tx(
+        &self,
+        tx_hash: H256Json,
+        fee: Option<WithdrawFee>,
+        from: Option<&WithdrawFrom>,
+        broadcast: bool,
+    ) -> PendingEthTxResult<TransactionDetails> {
+        let sender = self.pending_tx_sender(from).await?;
+        let pending_tx = self.load_pending_tx(&sender, tx_hash).await?;
+        let call_addr = pending_tx
+            .to
+            .or_mm_err(|| PendingEthTxError::ContractDeployment(tx_hash))?;
+        let data = pending_tx.input.0.clone();
+        let (value, gas) = (pending_tx.value, pending_tx.gas);
+        self.sign_replacement(&sender, &pending_tx, fee, call_addr, value, data, gas, broadcast)
+            .await
+    }
+
+    /// Signs a zero-value transfer to the sender address with the nonce of the pending `tx_hash` and the higher fee.
+    pub async fn cancel_pending_tx(
+        &self,
+        tx_hash: H256Json,
+        fee: Option<WithdrawFee>,
+        from: Option<&WithdrawFrom>,
+        broadcast: bool,
+    ) -> PendingEthTxResult<TransactionDetails> {
+        let sender = self.pending_tx_sender(from).await?;
+        let pending_tx = self.load_pending_tx(&sender, tx_hash).await?;
+        let gas = U256::from(ETH_TRANSFER_GAS);
+        let to = sender.address;
+        self.sign_replacement(&sender, &pending_tx, fee, to, U256::zero(), Vec::new(), gas, broadcast)
+            .await
+    }
+
+    /// Returns my address or the HD wallet address that `from` refers to along with its key pair.
+    async fn pending_tx_sender(&self, from: Option<&WithdrawFrom>) -> PendingEthTxResult<PendingTxSender> {
+        let WithdrawSenderAddress {
+            address: ChecksumAddress(address),
+            derivation_path,
+            ..
+        } = self.get_sender_address(from).await?;
+        let key_pair = match derivation_path {
+            Some(ref derivation_path) => self
+                .derivation_method
+                .hd_wallet_or_err()?
+                .derive_key_pair(derivation_path)
+                .mm_err(|e| PendingEthTxError::InternalError(e.to_string()))?,
+            None => self.priv_key_policy.key_pair_or_err()?.clone(),
+        };
+        Ok(PendingTxSender { address, key_pair })
+    }
+
+    /// Loads the transaction and checks if it's sent from the `sender` address and its nonce is not used yet.
+    async fn load_pending_tx(
+        &self,
+        sender: &PendingTxSender,
+        tx_hash: H256Json,
+    ) -> PendingEthTxResult<Web3Transaction> {
+        let pending_tx = self
+            .web3
+            .eth()
+            .transaction(TransactionId::Hash(H256::from(tx_hash.0)))
+            .await?
+            .or_mm_err(|| PendingEthTxError::TxNotFound(tx_hash))?;
+        if pending_tx.from != Some(sender.address) {
+            return MmError::err(PendingEthTxError::ForeignTx {
+                tx_hash,
+                my_address: sender.checksum_address(),
+            });
+        }
+
+        // The nonce may be already used by another mined transaction, e.g. by the previous replacement.
+        let nonces = get_addr_nonces(sender.address, self.web3_instances.clone())
+            .compat()
+            .await?;
+        if pending_tx.block_number.is_some() || pending_tx.nonce < nonces.latest {
+            return MmError::err(PendingEthTxError::TxAlreadyConfirmed(tx_hash));
+        }
+        Ok(pending_tx)
+    }
+
+    /// Signs the replacement of the `pending_tx`.
+    /// The replacement is broadcasted if `broadcast` is true.
+    #[allow(clippy::too_many_arguments)]
+    async fn sign_replacement(
+        &self,
+        sender: &PendingTxSender,
+        pending_tx: &Web3Transaction,
+        fee: Option<WithdrawFee>,
+        call_addr: Address,
+        value: U256,
+        data: Vec<u8>,
+        default_gas: U256,
+        broadcast: bool,
+    ) -> PendingEthTxResult<TransactionDetails> {
+        let min_pay_for_gas = min_replacement_pay_for_gas(pay_for_gas_option_of_tx(pending_tx)?);
+        let (gas, pay_for_gas_option) = match fee {
+            Some(fee) => {
+                // The gas is not estimated if the fee is set explicitly.
+                let (gas, pay_for_gas_option) = get_eth_gas_details(
+                    self,
+                    sender.address,
+                    Some(fee),
+                    value,
+                    data.clone().into(),
+                    call_addr,
+                    false,
+                )
+                .await?;
+                check_replacement_fee(pay_for_gas_option, min_pay_for_gas)?;
+                (gas, pay_for_gas_option)
+            },
+            None => (default_gas, self.replacement_pay_for_gas(min_pay_for_gas).await?),
+        };
+
+        // The lock is held until the replacement is broadcasted, so it doesn't race with the transactions sent by swaps.
+        let _nonce_lock = self.nonce_lock.lock().await;
+        let (tx_hash, tx_hex) = sign_transaction_with_pay_for_gas(
+            self,
+            sender.key_pair.secret(),
+            pending_tx.nonce,
+            gas,
+            pay_for_gas_option,
+            call_addr,
+            value,
+            data,
+        )
+        .mm_err(|e| PendingEthTxError::InternalError(e.to_string()))?;
+
+        if broadcast {
+            self.send_raw_tx_bytes(&tx_hex.0)
+                .compat()
+                .await
+                .map_to_mm(PendingEthTxError::Transport)?;
+        }
+
+        // The replacement is always paid in the platform coin, even if the pending transaction is an ERC20 transfer.
+        let fee_details = EthTxFeeDetails::new(gas, pay_for_gas_option, self.platform_ticker())?;
+        let amount = u256_to_big_decimal(value, ETH_DECIMALS)?;
+        let spent_by_me = &amount + &fee_details.total_fee;
+        let received_by_me = if call_addr == sender.address {
+            amount.clone()
+        } else {
+            0.into()
+        };
+        Ok(TransactionDetails {
+            to: vec![checksum_address(&format!("{:#02x}", call_addr))],
+            from: vec![sender.checksum_address()],
+            total_amount: amount,
+            my_balance_change: &received_by_me - &spent_by_me,
+            spent_by_me,
+            received_by_me,
+            tx_hex,
+            tx_hash: format!("{:02x}", BytesJson::from(tx_hash.0.to_vec())),
+            block_height: 0,
+            fee_details: Some(fee_details.into()),
+            coin: self.platform_ticker().to_owned(),
+            internal_id: vec![].into(),
+            timestamp: now_sec(),
+            kmd_rewards: None,
+            transaction_type: Default::default(),
+            memo: None,
+            psbt: None,
+            to_name: None,
+        })
+    }
+
+    /// Returns the current network fee of the same type as `min_pay_for_gas`, but not less than it.
+    async fn replacement_pay_for_gas(&self, min_pay_for_gas: PayForGasOption) -> PendingEthTxResult<PayForGasOption> {
+        match min_pay_for_gas {
+            PayForGasOption::Legacy { gas_price } => {
+                let network_gas_price = self.get_gas_price().compat().await?;
+                Ok(PayForGasOption::Legacy {
+                    gas_price: network_gas_price.max(gas_price),
+                })
+            },
+            PayForGasOption::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let (max_fee_per_gas, max_priority_fee_per_gas) = match self.get_eip1559_fee_per_gas().await {
+                    Some(network_fee) => (
+                        network_fee.max_fee_per_gas.max(max_fee_per_gas),
+                        network_fee.max_priority_fee_per_gas.max(max_priority_fee_per_gas),
+                    ),
+                    None => (max_fee_per_gas, max_priority_fee_per_gas),
+                };
+                Ok(PayForGasOption::Eip1559 {
+                    max_fee_per_gas: max_fee_per_gas.max(max_priority_fee_per_gas),
+                    max_priority_fee_per_gas,
+                })
+            },
+        }
+    }
+}
+
+/// The address that sent the pending transaction along with its key pair.
+struct PendingTxSender {
+    address: Address,
+    key_pair: KeyPair,
+}
+
+impl PendingTxSender {
+    fn checksum_address(&self) -> String { checksum_address(&format!("{:#02x}", self.address)) }
+}
+
+fn pay_for_gas_option_of_tx(tx: &Web3Transaction) -> PendingEthTxResult<PayForGasOption> {
+    let not_set = |field: &str| PendingEthTxError::Transport(format!("'Transaction::{}' is not set", field));
+    if tx.transaction_type == Some(U64::from(EIP1559_TX_TYPE)) {
+        Ok(PayForGasOption::Eip1559 {
+            max_fee_per_gas: tx.max_fee_per_gas.or_mm_err(|| not_set("max_fee_per_gas"))?,
+            max_priority_fee_per_gas: tx
+                .max_priority_fee_per_gas
+                .or_mm_err(|| not_set("max_priority_fee_per_gas"))?,
+        })
+    } else {
+        Ok(PayForGasOption::Legacy {
+            gas_price: tx.gas_price.or_mm_err(|| not_set("gas_price"))?,
+        })
+    }
+}
+
+/// The price per gas paid to the validator. A legacy transaction pays the whole gas price.
+fn priority_fee_per_gas(pay_for_gas_option: PayForGasOption) -> U256 {
+    match pay_for_gas_option {
+        PayForGasOption::Legacy { gas_price } => gas_price,
+        PayForGasOption::Eip1559 {
+            max_priority_fee_per_gas,
+            ..
+        } => max_priority_fee_per_gas,
+    }
+}
+
+/// Returns the minimum fee that a replacement of the pending transaction paying `prev` has to pay.
+pub(super) fn min_replacement_pay_for_gas(prev: PayForGasOption) -> PayForGasOption {
+    let bump = |fee: U256| fee + (fee * REPLACEMENT_FEE_BUMP_PERCENT + 99) / 100;
+    match prev {
+        PayForGasOption::Legacy { gas_price } => PayForGasOption::Legacy {
+            gas_price: bump(gas_price),
+        },
+        PayForGasOption::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => PayForGasOption::Eip1559 {
+            max_fee_per_gas: bump(max_fee_per_gas),
+            max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+        },
+    }
+}
+
+/// Checks if both the max fee and the priority fee of `new` are not less than the ones of `min`.
+pub(super) fn check_replacement_fee(new: PayForGasOption, min: PayForGasOption) -> PendingEthTxResult<()> {
+    let checks = match new {
+        PayForGasOption::Legacy { gas_price } => vec![("gas_price", gas_price, min.max_fee_per_gas())],
+        PayForGasOption::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => vec![
+            ("max_fee_per_gas", max_fee_per_gas, min.max_fee_per_gas()),
+            (
+                "max_priority_fee_per_gas",
+                max_priority_fee_per_gas,
+                priority_fee_per_gas(min),
+            ),
+        ],
+    };
+    for (param, provided, required) in checks {
+        if provided < required {
+            return MmError::err(PendingEthTxError::FeeNotIncreased {
+                param: param.to_owned(),
+                required: u256_to_big_decimal(required, 9)?,
+                provided: u256_to_big_decimal(provided, 9)?,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Signing and verifying EIP-712 typed structured data with the key of my address.
+
+use super::*;
+use crate::rpc_command::eth_typed_data::{EthTypedDataError, EthTypedDataResult};
+use mm2_eth::address::address_from_pubkey_uncompressed;
+use mm2_eth::eip712::Eip712;
+use mm2_eth::eip712_encode::hash_typed_data;
+use mm2_eth::recovery::recover_pubkey;
+
+/// The EIP-712 payload as it's passed to `eth_signTypedData_v4`.
+pub type TypedData = Eip712<Json, Json>;
+
+/// `eth_signTypedData_v4` returns the recovery id increased by 27 as `ecrecover` expects it.
+const RECOVERY_ID_OFFSET: u8 = 27;
+
+impl EthCoin {
+    /// Signs the EIP-712 hash of `typed_data`.
+    /// Returns the hash and the signature in the `r || s || v` form.
+    pub fn sign_typed_data(&self, typed_data: TypedData) -> EthTypedDataResult<(H256, Signature)> {
+        self.check_typed_data_chain_id(&typed_data)?;
+        let hash = typed_data_hash(typed_data)?;
+
+        let key_pair = self.priv_key_policy.key_pair_or_err()?;
+        let mut signature = sign(key_pair.secret(), &hash)?;
+        signature[64] += RECOVERY_ID_OFFSET;
+        Ok((hash, signature))
+    }
+
+    /// Returns the address that signed the EIP-712 hash of `typed_data`.
+    /// Both `0/1` and `27/28` recovery ids are accepted.
+    pub fn recover_typed_data_signer(
+        &self,
+        typed_data: TypedData,
+        signature: Signature,
+    ) -> EthTypedDataResult<(H256, Address)> {
+        self.check_typed_data_chain_id(&typed_data)?;
+        let hash = typed_data_hash(typed_data)?;
+
+        let pubkey = recover_pubkey(hash, signature).mm_err(|e| EthTypedDataError::InvalidSignature(e.to_string()))?;
+        Ok((hash, address_from_pubkey_uncompressed(pubkey)))
+    }
+
+    /// The signature could be replayed on another chain if the domain's `chainId` doesn't match the coin's one.
+    fn check_typed_data_chain_id(&self, typed_data: &TypedData) -> EthTypedDataResult<()> {
+        let domain_chain_id = match &typed_data.domain["chainId"] {
+            Json::Null => return Ok(()),
+            Json::Number(number) => number.as_u64(),
+            Json::String(string) => match string.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => string.parse().ok(),
+            },
+            _ => None,
+        };
+        let domain_chain_id = domain_chain_id.or_mm_err(|| {
+            EthTypedDataError::InvalidTypedData(format!("Invalid 'domain.chainId': {}", typed_data.domain["chainId"]))
+        })?;
+
+        match self.chain_id {
+            Some(chain_id) if chain_id != domain_chain_id => MmError::err(EthTypedDataError::ChainIdMismatch {
+                coin_chain_id: chain_id,
+                domain_chain_id,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn typed_data_hash(typed_data: TypedData) -> EthTypedDataResult<H256> {
+    hash_typed_data(typed_data).map_to_mm(|e| EthTypedDataError::InvalidTypedData(e.to_string()))
+}
@@ -1,4 +1,8 @@
+use super::eth_hd_wallet::{load_hd_accounts_from_storage, EthHDWallet, DEFAULT_GAP_LIMIT};
 use super::*;
+use crate::coin_balance::{EnableCoinBalanceError, EnabledCoinBalanceParams};
+use crate::hd_wallet::HDAccountsMutex;
+use crate::hd_wallet_storage::HDWalletCoinStorage;
 use common::executor::AbortedError;
 use crypto::{CryptoCtxError, StandardHDPathToCoin};
 use enum_from::EnumFromTrait;
@@ -54,6 +58,22 @@ impl From<UnexpectedDerivationMethod> for EthActivationV2Error {
     fn from(e: UnexpectedDerivationMethod) -> Self { EthActivationV2Error::InternalError(e.to_string()) }
 }
 
+impl From<EnableCoinBalanceError> for EthActivationV2Error {
+    fn from(e: EnableCoinBalanceError) -> Self {
+        match e {
+            EnableCoinBalanceError::NewAddressDerivingError(addr) => {
+                EthActivationV2Error::InternalError(addr.to_string())
+            },
+            EnableCoinBalanceError::NewAccountCreatingError(acc) => {
+                EthActivationV2Error::InternalError(acc.to_string())
+            },
+            EnableCoinBalanceError::BalanceError(balance) => {
+                EthActivationV2Error::CouldNotFetchBalance(balance.to_string())
+            },
+        }
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 impl From<MetamaskError> for EthActivationV2Error {
     fn from(e: MetamaskError) -> Self { from_metamask_error(e) }
@@ -92,6 +112,9 @@ pub struct EthActivationV2Request {
     pub fallback_swap_contract: Option<Address>,
     #[serde(default)]
     pub contract_supports_watchers: bool,
+    /// Whether the swap contract accepts ERC20 payments with an ERC-2612 permit.
+    #[serde(default)]
+    pub contract_supports_permit: bool,
     pub gas_station_url: Option<String>,
     pub gas_station_decimals: Option<u8>,
     #[serde(default)]
@@ -100,6 +123,11 @@ pub struct EthActivationV2Request {
     pub required_confirmations: Option<u64>,
     #[serde(default)]
     pub priv_key_policy: EthPrivKeyActivationPolicy,
+    // The max number of empty addresses in a row.
+    // If transactions were sent to an address outside the `gap_limit`, they will not be identified.
+    pub gap_limit: Option<u32>,
+    #[serde(flatten)]
+    pub enable_params: EnabledCoinBalanceParams,
 }
 
 #[derive(Clone, Deserialize)]
@@ -192,6 +220,7 @@ impl EthCoin {
         let token = EthCoinImpl {
             priv_key_policy: self.priv_key_policy.clone(),
             my_address: self.my_address,
+            derivation_method: self.derivation_method.clone(),
             coin_type: EthCoinType::Erc20 {
                 platform: protocol.platform,
                 token_addr: protocol.token_addr,
@@ -200,6 +229,7 @@ impl EthCoin {
             swap_contract_address: self.swap_contract_address,
             fallback_swap_contract: self.fallback_swap_contract,
             contract_supports_watchers: self.contract_supports_watchers,
+            contract_supports_permit: self.contract_supports_permit,
             decimals,
             ticker,
             gas_station_url: self.gas_station_url.clone(),
@@ -211,8 +241,11 @@ impl EthCoin {
             ctx: self.ctx.clone(),
             required_confirmations,
             chain_id: self.chain_id,
+            name_registry: self.name_registry,
             logs_block_range: self.logs_block_range,
             nonce_lock: self.nonce_lock.clone(),
+            exact_swap_approvals: conf["exact_swap_approvals"].as_bool().unwrap_or_default(),
+            swap_txs_eip1559: conf["swap_txs_eip1559"].as_bool().unwrap_or_default(),
             erc20_tokens_infos: Default::default(),
             abortable_system,
         };
@@ -246,10 +279,27 @@ pub async fn eth_coin_from_conf_and_request_v2(
         }
     }
 
+    // The seed is required to derive HD wallet addresses.
+    let global_hd_ctx = match priv_key_policy {
+        EthPrivKeyBuildPolicy::GlobalHDAccount(ref global_hd_ctx) => Some(global_hd_ctx.clone()),
+        _ => None,
+    };
     let (my_address, priv_key_policy) = build_address_and_priv_key_policy(conf, priv_key_policy).await?;
     let my_address_str = checksum_address(&format!("{:02x}", my_address));
+    let derivation_method = match global_hd_ctx {
+        Some(global_hd_ctx) => {
+            let hd_wallet = build_hd_wallet(ctx, &ticker, conf, global_hd_ctx, req.gap_limit).await?;
+            DerivationMethod::HDWallet(hd_wallet)
+        },
+        None => DerivationMethod::SingleAddress(ChecksumAddress(my_address)),
+    };
 
     let chain_id = conf["chain_id"].as_u64();
+    let name_registry =
+        name_resolution::name_registry_from_conf(conf).map_to_mm(|error| EthActivationV2Error::ActivationFailed {
+            ticker: ticker.clone(),
+            error,
+        })?;
 
     let (web3, web3_instances) = match (req.rpc_mode, &priv_key_policy) {
         (EthRpcMode::Http, EthPrivKeyPolicy::KeyPair(key_pair)) => {
@@ -291,11 +341,13 @@ pub async fn eth_coin_from_conf_and_request_v2(
     let coin = EthCoinImpl {
         priv_key_policy,
         my_address,
+        derivation_method: Arc::new(derivation_method),
         coin_type: EthCoinType::Eth,
         sign_message_prefix,
         swap_contract_address: req.swap_contract_address,
         fallback_swap_contract: req.fallback_swap_contract,
         contract_supports_watchers: req.contract_supports_watchers,
+        contract_supports_permit: req.contract_supports_permit,
         decimals: ETH_DECIMALS,
         ticker,
         gas_station_url: req.gas_station_url,
@@ -307,11 +359,15 @@ pub async fn eth_coin_from_conf_and_request_v2(
         ctx: ctx.weak(),
         required_confirmations,
         chain_id,
+        name_registry,
         logs_block_range: conf["logs_block_range"].as_u64().unwrap_or(DEFAULT_LOGS_BLOCK_RANGE),
         nonce_lock,
+        exact_swap_approvals: conf["exact_swap_approvals"].as_bool().unwrap_or_default(),
+        swap_txs_eip1559: conf["swap_txs_eip1559"].as_bool().unwrap_or_default(),
         erc20_tokens_infos: Default::default(),
         abortable_system,
     };
+    spawn_nodes_health_check_loop(&coin, conf);
 
     Ok(EthCoin(Arc::new(coin)))
 }
@@ -326,10 +382,7 @@ pub(crate) async fn build_address_and_priv_key_policy(
     let raw_priv_key = match priv_key_policy {
         EthPrivKeyBuildPolicy::IguanaPrivKey(iguana) => iguana,
         EthPrivKeyBuildPolicy::GlobalHDAccount(global_hd_ctx) => {
-            // Consider storing `derivation_path` at `EthCoinImpl`.
-            let derivation_path: Option<StandardHDPathToCoin> = json::from_value(conf["derivation_path"].clone())
-                .map_to_mm(|e| EthActivationV2Error::ErrorDeserializingDerivationPath(e.to_string()))?;
-            let derivation_path = derivation_path.or_mm_err(|| EthActivationV2Error::DerivationPathIsNotSet)?;
+            let derivation_path = derivation_path_from_conf(conf)?;
             global_hd_ctx
                 .derive_secp256k1_secret(&derivation_path)
                 .mm_err(|e| EthActivationV2Error::InternalError(e.to_string()))?
@@ -355,6 +408,38 @@ pub(crate) async fn build_address_and_priv_key_policy(
     Ok((address, EthPrivKeyPolicy::KeyPair(key_pair)))
 }
 
+fn derivation_path_from_conf(conf: &Json) -> MmResult<StandardHDPathToCoin, EthActivationV2Error> {
+    let derivation_path: Option<StandardHDPathToCoin> = json::from_value(conf["derivation_path"].clone())
+        .map_to_mm(|e| EthActivationV2Error::ErrorDeserializingDerivationPath(e.to_string()))?;
+    derivation_path.or_mm_err(|| EthActivationV2Error::DerivationPathIsNotSet)
+}
+
+/// Builds the HD wallet of the coin activated with a BIP39 seed phrase.
+/// HD accounts that have been enabled before are loaded from the HD wallet storage.
+async fn build_hd_wallet(
+    ctx: &MmArc,
+    ticker: &str,
+    conf: &Json,
+    global_hd_ctx: GlobalHDAccountArc,
+    gap_limit: Option<u32>,
+) -> MmResult<EthHDWallet, EthActivationV2Error> {
+    let derivation_path = derivation_path_from_conf(conf)?;
+    let hd_wallet_rmd160 = global_hd_ctx.root_pubkey_rmd160();
+    let hd_wallet_storage = HDWalletCoinStorage::init_with_rmd160(ctx, ticker.to_owned(), hd_wallet_rmd160)
+        .await
+        .mm_err(|e| EthActivationV2Error::InternalError(e.to_string()))?;
+    let accounts = load_hd_accounts_from_storage(&hd_wallet_storage, &derivation_path)
+        .await
+        .mm_err(|e| EthActivationV2Error::InternalError(e.to_string()))?;
+    Ok(EthHDWallet {
+        hd_wallet_storage,
+        derivation_path,
+        accounts: HDAccountsMutex::new(accounts),
+        gap_limit: gap_limit.unwrap_or(DEFAULT_GAP_LIMIT),
+        global_hd_ctx,
+    })
+}
+
 async fn build_http_transport(
     ctx: &MmArc,
     coin_ticker: String,
@@ -1,18 +1,24 @@
+use crate::eth::web3_transport::node_health::{NodesHealth, Web3NodeStatus};
 use crate::eth::{web3_transport::Web3SendOut, EthCoin, GuiAuthMessages, RpcTransportEventHandler,
                  RpcTransportEventHandlerShared, Web3RpcError};
-use common::APPLICATION_JSON;
-use futures::lock::Mutex as AsyncMutex;
+use common::executor::Timer;
+use common::log::warn;
+use common::{now_ms, APPLICATION_JSON};
+use futures::future::join_all;
 use http::header::CONTENT_TYPE;
 use jsonrpc_core::{Call, Response};
 use mm2_net::transport::{GuiAuthValidation, GuiAuthValidationGenerator};
 use serde_json::Value as Json;
 #[cfg(not(target_arch = "wasm32"))] use std::ops::Deref;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use web3::error::{Error, TransportError};
 use web3::helpers::{build_request, to_result_from_output, to_string};
 use web3::{RequestId, Transport};
 
+/// The interval between the health checks of the nodes.
+const HEALTH_CHECK_INTERVAL_S: f64 = 30.;
+
 #[derive(Serialize, Clone)]
 pub struct AuthPayload<'a> {
     #[serde(flatten)]
@@ -34,11 +40,28 @@ fn single_response<T: Deref<Target = [u8]>>(response: T, rpc_url: &str) -> Resul
 }
 
 #[derive(Debug)]
-struct HttpTransportRpcClient(AsyncMutex<HttpTransportRpcClientImpl>);
-
-#[derive(Debug)]
-struct HttpTransportRpcClientImpl {
+struct HttpTransportRpcClient {
     nodes: Vec<HttpTransportNode>,
+    /// The health of `nodes` used to choose the node a request is sent to.
+    health: Mutex<NodesHealth>,
+}
+
+impl HttpTransportRpcClient {
+    fn new(nodes: Vec<HttpTransportNode>) -> HttpTransportRpcClient {
+        let health = Mutex::new(NodesHealth::new(nodes.len()));
+        HttpTransportRpcClient { nodes, health }
+    }
+
+    fn ordered_nodes(&self) -> Vec<usize> { self.health.lock().unwrap().ordered_nodes(now_ms()) }
+
+    fn on_node_success(&self, index: usize, started_ms: u64) {
+        let latency_ms = now_ms().saturating_sub(started_ms);
+        self.health.lock().unwrap().on_success(index, latency_ms);
+    }
+
+    fn on_node_error(&self, index: usize, error: &Web3RpcError) {
+        self.health.lock().unwrap().on_error(index, error.to_string(), now_ms());
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -59,10 +82,9 @@ impl HttpTransport {
     #[cfg(test)]
     #[inline]
     pub fn new(nodes: Vec<HttpTransportNode>) -> Self {
-        let client_impl = HttpTransportRpcClientImpl { nodes };
         HttpTransport {
             id: Arc::new(AtomicUsize::new(0)),
-            client: Arc::new(HttpTransportRpcClient(AsyncMutex::new(client_impl))),
+            client: Arc::new(HttpTransportRpcClient::new(nodes)),
             event_handlers: Default::default(),
             gui_auth_validation_generator: None,
         }
@@ -73,10 +95,9 @@ impl HttpTransport {
         nodes: Vec<HttpTransportNode>,
         event_handlers: Vec<RpcTransportEventHandlerShared>,
     ) -> Self {
-        let client_impl = HttpTransportRpcClientImpl { nodes };
         HttpTransport {
             id: Arc::new(AtomicUsize::new(0)),
-            client: Arc::new(HttpTransportRpcClient(AsyncMutex::new(client_impl))),
+            client: Arc::new(HttpTransportRpcClient::new(nodes)),
             event_handlers,
             gui_auth_validation_generator: None,
         }
@@ -88,15 +109,69 @@ impl HttpTransport {
             uri: url.parse().unwrap(),
             gui_auth,
         }];
-        let client_impl = HttpTransportRpcClientImpl { nodes };
-
         HttpTransport {
             id: Arc::new(AtomicUsize::new(0)),
-            client: Arc::new(HttpTransportRpcClient(AsyncMutex::new(client_impl))),
+            client: Arc::new(HttpTransportRpcClient::new(nodes)),
             event_handlers: Default::default(),
             gui_auth_validation_generator: None,
         }
     }
+
+    /// Sets the number of blocks a node can be behind the others before it's quarantined.
+    pub(crate) fn set_max_block_lag(&self, max_block_lag: u64) {
+        self.client.health.lock().unwrap().set_max_block_lag(max_block_lag);
+    }
+
+    /// Returns the health of the nodes in the order they're configured.
+    pub fn nodes_status(&self) -> Vec<Web3NodeStatus> {
+        let urls = self.client.nodes.iter().map(|node| node.uri.to_string()).collect();
+        self.client.health.lock().unwrap().statuses(urls, now_ms())
+    }
+
+    /// Requests the latest block from all the nodes to measure their latency and block lag,
+    /// including the quarantined nodes so they're released once they recover.
+    pub async fn check_nodes_health(&self) {
+        let (_id, request) = self.prepare("eth_blockNumber", vec![]);
+        let serialized_request = to_string(&request);
+
+        let futures = self.client.nodes.iter().enumerate().map(|(index, node)| {
+            let serialized_request = serialized_request.clone();
+            let request = &request;
+            async move {
+                let serialized_request =
+                    match handle_gui_auth_payload_if_activated(&self.gui_auth_validation_generator, node, request) {
+                        Ok(Some(r)) => r,
+                        Ok(None) => serialized_request,
+                        // It's not the node's fault, the error is returned by `send_request` anyway.
+                        Err(_) => return None,
+                    };
+
+                let started_ms = now_ms();
+                match send_request_once(serialized_request, &node.uri, &self.event_handlers).await {
+                    Ok(response) => {
+                        self.client.on_node_success(index, started_ms);
+                        block_number_from_response(&response)
+                    },
+                    Err(e) => {
+                        self.client.on_node_error(index, &Web3RpcError::from(e));
+                        None
+                    },
+                }
+            }
+        });
+        // The lag is measured only between the blocks requested at once.
+        let block_numbers = join_all(futures).await;
+        self.client.health.lock().unwrap().update_block_lag(&block_numbers);
+    }
+}
+
+/// Checks the health of the nodes every [`HEALTH_CHECK_INTERVAL_S`].
+/// Should be spawned on the coin's abortable system so it's stopped when the coin is disabled.
+pub(crate) async fn nodes_health_check_loop(transport: HttpTransport) {
+    loop {
+        transport.check_nodes_health().await;
+        Timer::sleep(HEALTH_CHECK_INTERVAL_S).await;
+    }
 }
 
 impl Transport for HttpTransport {
@@ -109,17 +184,6 @@ impl Transport for HttpTransport {
         (id, request)
     }
 
-    #[cfg(not(target_arch = "wasm32"))]
-    fn send(&self, _id: RequestId, request: Call) -> Self::Out {
-        Box::pin(send_request(
-            request,
-            self.client.clone(),
-            self.event_handlers.clone(),
-            self.gui_auth_validation_generator.clone(),
-        ))
-    }
-
-    #[cfg(target_arch = "wasm32")]
     fn send(&self, _id: RequestId, request: Call) -> Self::Out {
         Box::pin(send_request(
             request,
@@ -169,29 +233,19 @@ fn handle_gui_auth_payload_if_activated(
     Ok(Some(to_string(&auth_request)))
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+/// Sends the request to the nodes in the order of their health until one of them responds.
 async fn send_request(
     request: Call,
     client: Arc<HttpTransportRpcClient>,
     event_handlers: Vec<RpcTransportEventHandlerShared>,
     gui_auth_validation_generator: Option<GuiAuthValidationGenerator>,
 ) -> Result<Json, Error> {
-    use common::executor::Timer;
-    use common::log::warn;
-    use futures::future::{select, Either};
-    use gstuff::binprint;
-    use http::header::HeaderValue;
-    use mm2_net::transport::slurp_req;
-
-    const REQUEST_TIMEOUT_S: f64 = 60.;
-
-    let mut errors = Vec::new();
-
     let serialized_request = to_string(&request);
 
-    let mut client_impl = client.0.lock().await;
+    let mut errors = Vec::new();
 
-    for (i, node) in client_impl.nodes.clone().iter().enumerate() {
+    for index in client.ordered_nodes() {
+        let node = &client.nodes[index];
         let serialized_request =
             match handle_gui_auth_payload_if_activated(&gui_auth_validation_generator, node, &request) {
                 Ok(Some(r)) => r,
@@ -202,93 +256,67 @@ async fn send_request(
                 },
             };
 
-        event_handlers.on_outgoing_request(serialized_request.as_bytes());
-
-        let mut req = http::Request::new(serialized_request.clone().into_bytes());
-        *req.method_mut() = http::Method::POST;
-        *req.uri_mut() = node.uri.clone();
-        req.headers_mut()
-            .insert(CONTENT_TYPE, HeaderValue::from_static(APPLICATION_JSON));
-        let timeout = Timer::sleep(REQUEST_TIMEOUT_S);
-        let req = Box::pin(slurp_req(req));
-        let rc = select(req, timeout).await;
-        let res = match rc {
-            Either::Left((r, _t)) => r,
-            Either::Right((_t, _r)) => {
-                let error = format!(
-                    "Error requesting '{}': {}s timeout expired",
-                    node.uri, REQUEST_TIMEOUT_S
-                );
-                warn!("{}", error);
-                errors.push(Web3RpcError::Transport(error));
-                continue;
+        let started_ms = now_ms();
+        match send_request_once(serialized_request, &node.uri, &event_handlers).await {
+            Ok(response) => {
+                client.on_node_success(index, started_ms);
+                return Ok(response);
             },
-        };
-
-        let (status, _headers, body) = match res {
-            Ok(r) => r,
-            Err(err) => {
-                errors.push(Web3RpcError::Transport(err.to_string()));
-                continue;
+            // The node is up, but the request itself failed (e.g. the call reverted), so there is no reason to retry it.
+            Err(Error::Rpc(e)) => {
+                client.on_node_success(index, started_ms);
+                return Err(Error::Rpc(e));
+            },
+            Err(e) => {
+                let error = Web3RpcError::from(e);
+                warn!("Error requesting '{}': {}", node.uri, error);
+                client.on_node_error(index, &error);
+                errors.push(error);
             },
-        };
-
-        event_handlers.on_incoming_response(&body);
-
-        if !status.is_success() {
-            errors.push(Web3RpcError::Transport(format!(
-                "Server '{:?}' response !200: {}, {}",
-                node,
-                status,
-                binprint(&body, b'.')
-            )));
-            continue;
         }
-
-        client_impl.nodes.rotate_left(i);
-
-        return single_response(body, &node.uri.to_string());
     }
 
     Err(request_failed_error(&request, &errors))
 }
 
-#[cfg(target_arch = "wasm32")]
-async fn send_request(
-    request: Call,
-    client: Arc<HttpTransportRpcClient>,
-    event_handlers: Vec<RpcTransportEventHandlerShared>,
-    gui_auth_validation_generator: Option<GuiAuthValidationGenerator>,
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_request_once(
+    request_payload: String,
+    uri: &http::Uri,
+    event_handlers: &Vec<RpcTransportEventHandlerShared>,
 ) -> Result<Json, Error> {
-    let serialized_request = to_string(&request);
+    use futures::future::{select, Either};
+    use gstuff::binprint;
+    use http::header::HeaderValue;
+    use mm2_net::transport::slurp_req;
 
-    let mut transport_errors = Vec::new();
-    let mut client_impl = client.0.lock().await;
+    const REQUEST_TIMEOUT_S: f64 = 60.;
 
-    for (i, node) in client_impl.nodes.clone().iter().enumerate() {
-        let serialized_request =
-            match handle_gui_auth_payload_if_activated(&gui_auth_validation_generator, node, &request) {
-                Ok(Some(r)) => r,
-                Ok(None) => serialized_request.clone(),
-                Err(e) => {
-                    transport_errors.push(e);
-                    continue;
-                },
-            };
+    event_handlers.on_outgoing_request(request_payload.as_bytes());
 
-        match send_request_once(serialized_request.clone(), &node.uri, &event_handlers).await {
-            Ok(response_json) => {
-                client_impl.nodes.rotate_left(i);
-                return Ok(response_json);
-            },
-            Err(Error::Transport(e)) => {
-                transport_errors.push(Web3RpcError::Transport(e.to_string()));
-            },
-            Err(e) => return Err(e),
-        }
+    let mut req = http::Request::new(request_payload.into_bytes());
+    *req.method_mut() = http::Method::POST;
+    *req.uri_mut() = uri.clone();
+    req.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(APPLICATION_JSON));
+    let timeout = Timer::sleep(REQUEST_TIMEOUT_S);
+    let req = Box::pin(slurp_req(req));
+    let (status, _headers, body) = match select(req, timeout).await {
+        Either::Left((r, _t)) => r.map_err(|e| Error::Transport(TransportError::Message(e.to_string())))?,
+        Either::Right((_t, _r)) => {
+            let error = format!("Error requesting '{}': {}s timeout expired", uri, REQUEST_TIMEOUT_S);
+            return Err(Error::Transport(TransportError::Message(error)));
+        },
+    };
+
+    event_handlers.on_incoming_response(&body);
+
+    if !status.is_success() {
+        let error = format!("Server '{}' response !200: {}, {}", uri, status, binprint(&body, b'.'));
+        return Err(Error::Transport(TransportError::Message(error)));
     }
 
-    Err(request_failed_error(&request, &transport_errors))
+    single_response(body, &uri.to_string())
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -327,6 +355,12 @@ async fn send_request_once(
     }
 }
 
+/// Parses the `eth_blockNumber` result.
+fn block_number_from_response(response: &Json) -> Option<u64> {
+    let block_number = response.as_str()?;
+    u64::from_str_radix(block_number.trim_start_matches("0x"), 16).ok()
+}
+
 fn request_failed_error(request: &Call, errors: &[Web3RpcError]) -> Error {
     let errors: String = errors.iter().map(|e| format!("{:?}; ", e)).collect();
     let error = format!("request {:?} failed: {}", request, errors);
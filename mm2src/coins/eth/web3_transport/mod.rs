@@ -13,6 +13,7 @@ use web3::{Error, RequestId, Transport};
 
 pub(crate) mod http_transport;
 #[cfg(target_arch = "wasm32")] pub(crate) mod metamask_transport;
+pub(crate) mod node_health;
 
 type Web3SendOut = BoxFuture<'static, Result<Json, Error>>;
 
@@ -49,6 +50,21 @@ impl Web3Transport {
         http_transport::HttpTransport::single_node(url, gui_auth).into()
     }
 
+    /// Returns the health of the nodes, or `None` if the transport doesn't track it, i.e. for MetaMask.
+    /// Checks the nodes first if `refresh` is set.
+    pub(crate) async fn nodes_status(&self, refresh: bool) -> Option<Vec<node_health::Web3NodeStatus>> {
+        match self {
+            Web3Transport::Http(http) => {
+                if refresh {
+                    http.check_nodes_health().await;
+                }
+                Some(http.nodes_status())
+            },
+            #[cfg(target_arch = "wasm32")]
+            Web3Transport::Metamask(_) => None,
+        }
+    }
+
     pub fn gui_auth_validation_generator_as_mut(&mut self) -> Option<&mut GuiAuthValidationGenerator> {
         match self {
             Web3Transport::Http(http) => http.gui_auth_validation_generator.as_mut(),
@@ -111,6 +127,9 @@ pub struct FeeHistoryResult {
     pub oldest_block: U256,
     #[serde(rename = "baseFeePerGas")]
     pub base_fee_per_gas: Vec<U256>,
+    /// Effective priority fees per gas at the requested percentiles, one list per block.
+    /// Absent if no percentiles were requested.
+    pub reward: Option<Vec<Vec<U256>>>,
 }
 
 impl<T: Transport> EthFeeHistoryNamespace<T> {
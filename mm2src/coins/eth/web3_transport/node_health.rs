@@ -0,0 +1,221 @@
+//! Tracks the health of the web3 nodes so that requests are routed to the best one
+//! and the nodes that are failing or lagging behind are quarantined.
+
+/// The weight of the latest sample in the latency and error rate moving averages.
+const EMA_WEIGHT: f64 = 0.2;
+/// The score of a node is multiplied by `1 + error_rate * ERROR_RATE_PENALTY`,
+/// so a node failing half of the requests is considered 6 times slower.
+const ERROR_RATE_PENALTY: f64 = 10.;
+/// The number of consecutive errors after which the node is quarantined.
+const MAX_CONSECUTIVE_ERRORS: u32 = 3;
+/// The node is quarantined if it's behind the highest block of the same health check by more than this number of blocks.
+/// Can be overridden by the `max_block_lag` coin config, since the block time differs between the chains.
+pub(crate) const DEFAULT_MAX_BLOCK_LAG: u64 = 5;
+/// The duration of the first quarantine of a failing node. It's doubled for each subsequent quarantine.
+const QUARANTINE_MS: u64 = 30_000;
+const MAX_QUARANTINE_MS: u64 = 600_000;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum QuarantineReason {
+    /// The node failed the last requests.
+    Failing,
+    /// The node is behind the highest block of the same health check by the given number of blocks.
+    Behind(u64),
+}
+
+#[derive(Clone, Debug)]
+struct Quarantine {
+    reason: QuarantineReason,
+    /// The timestamp in milliseconds when a failing node is tried again.
+    /// A lagging node is released once it catches up.
+    until_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct NodeHealth {
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    consecutive_errors: u32,
+    /// The number of the quarantines in a row due to errors.
+    failing_quarantines: u32,
+    block_number: Option<u64>,
+    /// The number of blocks the node was behind the others in the last health check it responded in.
+    block_lag: Option<u64>,
+    last_error: Option<String>,
+    quarantine: Option<Quarantine>,
+}
+
+impl NodeHealth {
+    fn is_quarantined(&self, now_ms: u64) -> bool {
+        match self.quarantine {
+            Some(Quarantine {
+                until_ms: Some(until_ms),
+                ..
+            }) => now_ms < until_ms,
+            Some(Quarantine { until_ms: None, .. }) => true,
+            None => false,
+        }
+    }
+
+    /// The lower the better. `unknown_latency_ms` is used if the latency of the node hasn't been measured yet,
+    /// so the errors of such a node are penalized too.
+    fn score(&self, unknown_latency_ms: f64) -> f64 {
+        self.latency_ms.unwrap_or(unknown_latency_ms) * (1. + self.error_rate * ERROR_RATE_PENALTY)
+    }
+}
+
+/// The status of a web3 node returned by the `get_eth_nodes_status` RPC.
+#[derive(Clone, Debug, Serialize)]
+pub struct Web3NodeStatus {
+    pub url: String,
+    /// The position of the node in the order the requests are sent in. `0` is the node tried first.
+    pub rank: usize,
+    pub is_quarantined: bool,
+    pub quarantine_reason: Option<QuarantineReason>,
+    /// The timestamp in seconds when a failing node is tried again.
+    pub quarantined_until: Option<u64>,
+    /// The moving average of the response time.
+    pub latency_ms: Option<u64>,
+    /// The moving average of the share of the failed requests.
+    pub error_rate: f64,
+    pub consecutive_errors: u32,
+    pub block_number: Option<u64>,
+    /// The number of blocks the node was behind the highest block in the last health check it responded in.
+    pub block_lag: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct NodesHealth {
+    /// The health of the nodes in the order they're configured.
+    nodes: Vec<NodeHealth>,
+    max_block_lag: u64,
+}
+
+impl NodesHealth {
+    pub(crate) fn new(nodes_count: usize) -> NodesHealth {
+        NodesHealth {
+            nodes: vec![NodeHealth::default(); nodes_count],
+            max_block_lag: DEFAULT_MAX_BLOCK_LAG,
+        }
+    }
+
+    pub(crate) fn set_max_block_lag(&mut self, max_block_lag: u64) { self.max_block_lag = max_block_lag; }
+
+    /// Returns the indexes of the nodes in the order the request should be sent in:
+    /// the healthy nodes by their score and then the quarantined ones, so they're still tried if all others fail.
+    /// A node with unknown latency is scored as the fastest one and goes before it, so the latency is measured,
+    /// unless the node has failed the previous requests.
+    pub(crate) fn ordered_nodes(&self, now_ms: u64) -> Vec<usize> {
+        let unknown_latency_ms = self
+            .nodes
+            .iter()
+            .filter_map(|node| node.latency_ms)
+            .min_by(f64::total_cmp)
+            .unwrap_or_default();
+        let mut indexes: Vec<usize> = (0..self.nodes.len()).collect();
+        indexes.sort_by(|a, b| {
+            let (a, b) = (&self.nodes[*a], &self.nodes[*b]);
+            a.is_quarantined(now_ms)
+                .cmp(&b.is_quarantined(now_ms))
+                .then_with(|| a.score(unknown_latency_ms).total_cmp(&b.score(unknown_latency_ms)))
+                .then_with(|| a.latency_ms.is_some().cmp(&b.latency_ms.is_some()))
+        });
+        indexes
+    }
+
+    /// Records a successful response.
+    pub(crate) fn on_success(&mut self, index: usize, latency_ms: u64) {
+        let node = &mut self.nodes[index];
+        node.latency_ms = Some(match node.latency_ms {
+            Some(avg) => avg + (latency_ms as f64 - avg) * EMA_WEIGHT,
+            None => latency_ms as f64,
+        });
+        node.error_rate -= node.error_rate * EMA_WEIGHT;
+        node.consecutive_errors = 0;
+        if matches!(&node.quarantine, Some(q) if q.reason == QuarantineReason::Failing) {
+            node.quarantine = None;
+        }
+        node.failing_quarantines = 0;
+    }
+
+    /// Records a failed request. The node is quarantined if it keeps failing.
+    pub(crate) fn on_error(&mut self, index: usize, error: String, now_ms: u64) {
+        let node = &mut self.nodes[index];
+        node.error_rate += (1. - node.error_rate) * EMA_WEIGHT;
+        node.consecutive_errors += 1;
+        node.last_error = Some(error);
+
+        // A failing node is quarantined again on the first error after the quarantine ends.
+        if node.consecutive_errors >= MAX_CONSECUTIVE_ERRORS && !node.is_quarantined(now_ms) {
+            let quarantine_ms = QUARANTINE_MS
+                .saturating_mul(1 << node.failing_quarantines.min(16))
+                .min(MAX_QUARANTINE_MS);
+            node.failing_quarantines += 1;
+            node.quarantine = Some(Quarantine {
+                reason: QuarantineReason::Failing,
+                until_ms: Some(now_ms + quarantine_ms),
+            });
+        }
+    }
+
+    /// Records the latest blocks of the nodes requested at once by a health check,
+    /// quarantines the nodes that are too far behind the highest of them and releases the ones that caught up.
+    /// `block_numbers` are in the order the nodes are configured, `None` if the node didn't respond.
+    /// The blocks of different health checks are never compared, since the chain keeps growing in between.
+    pub(crate) fn update_block_lag(&mut self, block_numbers: &[Option<u64>]) {
+        let highest_block = match block_numbers.iter().flatten().max() {
+            Some(block) => *block,
+            None => return,
+        };
+        for (node, block_number) in self.nodes.iter_mut().zip(block_numbers) {
+            // The lag of a node that didn't respond is unknown, so it stays as is.
+            let block_number = match block_number {
+                Some(block) => *block,
+                None => continue,
+            };
+            let lag = highest_block.saturating_sub(block_number);
+            node.block_number = Some(block_number);
+            node.block_lag = Some(lag);
+
+            // A failing node stays quarantined until the quarantine ends or the node responds successfully.
+            if matches!(&node.quarantine, Some(q) if q.reason == QuarantineReason::Failing) {
+                continue;
+            }
+            node.quarantine = if lag > self.max_block_lag {
+                Some(Quarantine {
+                    reason: QuarantineReason::Behind(lag),
+                    until_ms: None,
+                })
+            } else {
+                None
+            };
+        }
+    }
+
+    pub(crate) fn statuses(&self, urls: Vec<String>, now_ms: u64) -> Vec<Web3NodeStatus> {
+        let ordered = self.ordered_nodes(now_ms);
+        urls.into_iter()
+            .zip(self.nodes.iter())
+            .enumerate()
+            .map(|(index, (url, node))| {
+                let is_quarantined = node.is_quarantined(now_ms);
+                let quarantine = node.quarantine.as_ref().filter(|_| is_quarantined);
+                Web3NodeStatus {
+                    url,
+                    rank: ordered.iter().position(|i| *i == index).unwrap_or(index),
+                    is_quarantined,
+                    quarantine_reason: quarantine.map(|q| q.reason.clone()),
+                    quarantined_until: quarantine.and_then(|q| q.until_ms).map(|until_ms| until_ms / 1000),
+                    latency_ms: node.latency_ms.map(|latency| latency.round() as u64),
+                    error_rate: node.error_rate,
+                    consecutive_errors: node.consecutive_errors,
+                    block_number: node.block_number,
+                    block_lag: node.block_lag,
+                    last_error: node.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+}
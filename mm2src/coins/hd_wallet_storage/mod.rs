@@ -222,7 +222,8 @@ impl HDWalletCoinStorage {
         })
     }
 
-    #[cfg(any(test, target_arch = "wasm32"))]
+    /// Initializes the storage of the HD wallet identified by `hd_wallet_rmd160` instead of a Hardware Wallet pubkey,
+    /// e.g. of a watch-only wallet.
     pub async fn init_with_rmd160(
         ctx: &MmArc,
         coin: String,
@@ -270,7 +271,7 @@ impl HDWalletCoinStorage {
             .await
     }
 
-    async fn upload_new_account(&self, account_info: HDAccountStorageItem) -> HDWalletStorageResult<()> {
+    pub(crate) async fn upload_new_account(&self, account_info: HDAccountStorageItem) -> HDWalletStorageResult<()> {
         let wallet_id = self.wallet_id();
         self.inner.upload_new_account(wallet_id, account_info).await
     }
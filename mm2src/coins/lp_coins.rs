@@ -85,6 +85,7 @@ use utxo_signer::with_key_pair::UtxoSignWithKeyPairError;
 cfg_native! {
     use crate::lightning::LightningCoin;
     use crate::lightning::ln_conf::PlatformCoinConfirmationTargets;
+    use crate::simulated_coin::{simulated_coin_from_conf, SimulatedCoin, SimulatedTx};
     use ::lightning::ln::PaymentHash as LightningPayment;
     use async_std::fs;
     use futures::AsyncWriteExt;
@@ -97,7 +98,8 @@ cfg_native! {
 
 cfg_wasm32! {
     use hd_wallet_storage::HDWalletDb;
-    use mm2_db::indexed_db::{ConstructibleDb, DbLocked, SharedDb};
+    use mm2_db::indexed_db::{ConstructibleDb, DbLocked, InitDbResult, SharedDb};
+    use utxo::frozen_outpoints_storage::FrozenOutpointsDb;
     use tx_history_storage::wasm::{clear_tx_history, load_tx_history, save_tx_history, TxHistoryDb};
     pub type TxHistoryDbLocked<'a> = DbLocked<'a, TxHistoryDb>;
 }
@@ -237,6 +239,8 @@ use rpc_command::{get_new_address::{GetNewAddressTaskManager, GetNewAddressTaskM
                   init_scan_for_new_addresses::{ScanAddressesTaskManager, ScanAddressesTaskManagerShared},
                   init_withdraw::{WithdrawTaskManager, WithdrawTaskManagerShared}};
 
+#[cfg(not(target_arch = "wasm32"))] pub mod simulated_coin;
+
 pub mod tendermint;
 use tendermint::{CosmosTransaction, CustomTendermintMsgType, TendermintCoin, TendermintFeeDetails,
                  TendermintProtocolInfo, TendermintToken, TendermintTokenProtocolInfo};
@@ -282,7 +286,7 @@ use utxo::slp::{slp_addr_from_pubkey_str, SlpFeeDetails};
 use utxo::utxo_common::big_decimal_from_sat_unsigned;
 use utxo::utxo_standard::{utxo_standard_coin_with_policy, UtxoStandardCoin};
 use utxo::UtxoActivationParams;
-use utxo::{BlockchainNetwork, GenerateTxError, UtxoFeeDetails, UtxoTx};
+use utxo::{BlockchainNetwork, GenerateTxError, UtxoFeeDetails, UtxoFeePriority, UtxoTx};
 
 pub mod nft;
 use nft::nft_errors::GetNftInfoError;
@@ -437,6 +441,8 @@ pub enum TxHistoryError {
 pub enum PrivKeyPolicyNotAllowed {
     #[display(fmt = "Hardware Wallet is not supported")]
     HardwareWalletNotSupported,
+    #[display(fmt = "Watch-only wallet is not supported")]
+    WatchOnlyWalletNotSupported,
 }
 
 impl Serialize for PrivKeyPolicyNotAllowed {
@@ -454,6 +460,8 @@ pub enum UnexpectedDerivationMethod {
     ExpectedSingleAddress,
     #[display(fmt = "Expected 'HDWallet' derivationMethod")]
     ExpectedHDWallet,
+    #[display(fmt = "Public key is unknown for a watch-only wallet")]
+    WatchOnlyPubkeyIsUnknown,
 }
 
 pub trait Transaction: fmt::Debug + 'static {
@@ -472,6 +480,8 @@ pub enum TransactionEnum {
     CosmosTransaction(CosmosTransaction),
     #[cfg(not(target_arch = "wasm32"))]
     LightningPayment(LightningPayment),
+    #[cfg(not(target_arch = "wasm32"))]
+    SimulatedTx(SimulatedTx),
 }
 
 ifrom!(TransactionEnum, UtxoTx);
@@ -480,6 +490,8 @@ ifrom!(TransactionEnum, SignedEthTx);
 ifrom!(TransactionEnum, ZTransaction);
 #[cfg(not(target_arch = "wasm32"))]
 ifrom!(TransactionEnum, LightningPayment);
+#[cfg(not(target_arch = "wasm32"))]
+ifrom!(TransactionEnum, SimulatedTx);
 
 impl TransactionEnum {
     #[cfg(not(target_arch = "wasm32"))]
@@ -501,6 +513,8 @@ impl Deref for TransactionEnum {
             TransactionEnum::CosmosTransaction(ref t) => t,
             #[cfg(not(target_arch = "wasm32"))]
             TransactionEnum::LightningPayment(ref p) => p,
+            #[cfg(not(target_arch = "wasm32"))]
+            TransactionEnum::SimulatedTx(ref t) => t,
         }
     }
 }
@@ -1062,11 +1076,27 @@ pub enum WithdrawFee {
     UtxoPerKbyte {
         amount: BigDecimal,
     },
+    /// The fee rate is estimated by the node for the confirmation within `blocks`.
+    UtxoTargetBlocks {
+        blocks: u32,
+    },
+    /// The fee rate is estimated by the node for the confirmation at the given priority.
+    UtxoPriority {
+        priority: UtxoFeePriority,
+    },
     EthGas {
         /// in gwei
         gas_price: BigDecimal,
         gas: u64,
     },
+    /// The fees of an EIP-1559 (type-2) transaction.
+    EthGasEip1559 {
+        /// in gwei
+        max_fee_per_gas: BigDecimal,
+        /// in gwei
+        max_priority_fee_per_gas: BigDecimal,
+        gas: u64,
+    },
     Qrc20Gas {
         /// in satoshi
         gas_limit: u64,
@@ -1115,15 +1145,49 @@ pub enum WithdrawFrom {
     },
 }
 
+/// One of the recipients of a batch withdraw.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WithdrawOutput {
+    pub to: String,
+    #[serde(default)]
+    pub amount: BigDecimal,
+    /// Whether this output absorbs the rest of the balance left after paying the other outputs and the fee.
+    #[serde(default)]
+    pub max: bool,
+}
+
+/// An unspent output explicitly selected to fund a withdraw.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WithdrawInput {
+    pub tx_hash: H256Json,
+    pub vout: u32,
+}
+
 #[derive(Clone, Deserialize)]
 pub struct WithdrawRequest {
     coin: String,
     from: Option<WithdrawFrom>,
+    #[serde(default)]
     to: String,
     #[serde(default)]
     amount: BigDecimal,
     #[serde(default)]
     max: bool,
+    /// The list of recipients paid within one transaction.
+    /// Mutually exclusive with `to`, `amount` and `max`. Supported by UTXO coins only.
+    #[serde(default)]
+    outputs: Vec<WithdrawOutput>,
+    /// The unspent outputs to fund the transaction with. All of them are spent.
+    /// If empty, the inputs are selected automatically. Supported by UTXO coins only.
+    #[serde(default)]
+    inputs: Vec<WithdrawInput>,
+    /// Whether to signal opt-in Replace-By-Fee (BIP-125) so that the fee can be bumped later using `bump_fee`.
+    #[serde(default)]
+    rbf: bool,
+    /// Whether to return an unsigned BIP-174 PSBT to be signed elsewhere instead of signing the transaction.
+    /// The signed PSBT can be broadcasted using `broadcast_psbt`. Supported by UTXO coins only.
+    #[serde(default)]
+    psbt: bool,
     fee: Option<WithdrawFee>,
     memo: Option<String>,
     /// Currently, this flag is used by ETH/ERC20 coins activated with MetaMask **only**.
@@ -1178,12 +1242,76 @@ impl WithdrawRequest {
             to,
             amount: 0.into(),
             max: true,
+            outputs: Vec::new(),
+            inputs: Vec::new(),
+            rbf: false,
+            psbt: false,
             fee: None,
             memo: None,
             #[cfg(target_arch = "wasm32")]
             broadcast: false,
         }
     }
+
+    pub fn is_batch(&self) -> bool { !self.outputs.is_empty() }
+
+    /// Checks if the `coin` supports the batch `outputs` and the explicit `inputs` if they are requested.
+    #[allow(clippy::result_large_err)]
+    fn check_supported_by(&self, coin: &MmCoinEnum) -> Result<(), MmError<WithdrawError>> {
+        if self.is_batch() && !coin.supports_batch_withdraw() {
+            return MmError::err(WithdrawError::CoinDoesntSupportBatchWithdraw {
+                coin: self.coin.clone(),
+            });
+        }
+        if !self.inputs.is_empty() && !coin.supports_coin_control() {
+            return MmError::err(WithdrawError::CoinDoesntSupportCoinControl {
+                coin: self.coin.clone(),
+            });
+        }
+        if self.rbf && !coin.supports_rbf() {
+            return MmError::err(WithdrawError::CoinDoesntSupportRbf {
+                coin: self.coin.clone(),
+            });
+        }
+        if self.psbt && !coin.supports_psbt() {
+            return MmError::err(WithdrawError::CoinDoesntSupportPsbt {
+                coin: self.coin.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the recipients of the withdraw: either the batch `outputs` or the single `to` recipient.
+    #[allow(clippy::result_large_err)]
+    pub fn withdraw_outputs(&self) -> Result<Vec<WithdrawOutput>, MmError<WithdrawError>> {
+        if !self.is_batch() {
+            return Ok(vec![WithdrawOutput {
+                to: self.to.clone(),
+                amount: self.amount.clone(),
+                max: self.max,
+            }]);
+        }
+
+        if !self.to.is_empty() || !self.amount.is_zero() || self.max {
+            let error = "'to', 'amount' and 'max' can't be used together with 'outputs'".to_owned();
+            return MmError::err(WithdrawError::InvalidOutputs(error));
+        }
+        if self.outputs.iter().filter(|output| output.max).count() > 1 {
+            let error = "Only one output can be marked as 'max'".to_owned();
+            return MmError::err(WithdrawError::InvalidOutputs(error));
+        }
+        for output in self.outputs.iter() {
+            if output.max && !output.amount.is_zero() {
+                let error = format!("'amount' can't be set for the 'max' output to {}", output.to);
+                return MmError::err(WithdrawError::InvalidOutputs(error));
+            }
+            if !output.max && output.amount <= BigDecimal::zero() {
+                let error = format!("'amount' of the output to {} must be positive", output.to);
+                return MmError::err(WithdrawError::InvalidOutputs(error));
+            }
+        }
+        Ok(self.outputs.clone())
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -1230,6 +1358,26 @@ pub enum TxFeeDetails {
     Solana(SolanaFeeDetails),
 }
 
+impl TxFeeDetails {
+    /// Returns the ticker of the coin the fee is paid with, if it's specified, and the total fee amount.
+    pub fn total_fee(&self) -> (Option<&str>, BigDecimal) {
+        match self {
+            TxFeeDetails::Utxo(utxo) => (utxo.coin.as_deref(), utxo.amount.clone()),
+            TxFeeDetails::Eth(eth) => (Some(&eth.coin), eth.total_fee.clone()),
+            TxFeeDetails::Qrc20(qrc20) => (Some(&qrc20.coin), &qrc20.miner_fee + &qrc20.total_gas_fee),
+            TxFeeDetails::Slp(slp) => (Some(&slp.coin), slp.amount.clone()),
+            TxFeeDetails::Tendermint(tendermint) => (Some(&tendermint.coin), tendermint.amount.clone()),
+            #[cfg(all(
+                feature = "enable-solana",
+                not(target_os = "ios"),
+                not(target_os = "android"),
+                not(target_arch = "wasm32")
+            ))]
+            TxFeeDetails::Solana(solana) => (None, solana.amount.clone()),
+        }
+    }
+}
+
 /// Deserialize the TxFeeDetails as an untagged enum.
 impl<'de> Deserialize<'de> for TxFeeDetails {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
@@ -1362,6 +1510,13 @@ pub struct TransactionDetails {
     #[serde(default)]
     transaction_type: TransactionType,
     memo: Option<String>,
+    /// The unsigned transaction as base64 encoded BIP-174 PSBT if it was requested by `WithdrawRequest::psbt`.
+    /// In this case, `tx_hex` and `tx_hash` correspond to the unsigned transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    psbt: Option<String>,
+    /// The name (e.g. ENS) the withdraw destination was resolved from. `to` contains the resolved address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    to_name: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -1371,6 +1526,16 @@ pub struct BlockHeightAndTime {
 }
 
 impl TransactionDetails {
+    pub fn tx_hash(&self) -> &str { &self.tx_hash }
+
+    pub fn coin(&self) -> &str { &self.coin }
+
+    pub fn timestamp(&self) -> u64 { self.timestamp }
+
+    pub fn my_balance_change(&self) -> &BigDecimal { &self.my_balance_change }
+
+    pub fn fee_details(&self) -> Option<&TxFeeDetails> { self.fee_details.as_ref() }
+
     /// Whether the transaction details block height should be updated (when tx is confirmed)
     pub fn should_update_block_height(&self) -> bool {
         // checking for std::u64::MAX because there was integer overflow
@@ -1869,6 +2034,26 @@ pub enum WithdrawError {
     InvalidFeePolicy(String),
     #[display(fmt = "Invalid memo field: {}", _0)]
     InvalidMemo(String),
+    #[display(fmt = "Invalid withdraw outputs: {}", _0)]
+    InvalidOutputs(String),
+    #[display(fmt = "'{}' coin doesn't support batch withdraw", coin)]
+    CoinDoesntSupportBatchWithdraw {
+        coin: String,
+    },
+    #[display(fmt = "Invalid withdraw inputs: {}", _0)]
+    InvalidInputs(String),
+    #[display(fmt = "'{}' coin doesn't support explicit inputs selection", coin)]
+    CoinDoesntSupportCoinControl {
+        coin: String,
+    },
+    #[display(fmt = "'{}' coin doesn't support Replace-By-Fee", coin)]
+    CoinDoesntSupportRbf {
+        coin: String,
+    },
+    #[display(fmt = "'{}' coin doesn't support PSBT", coin)]
+    CoinDoesntSupportPsbt {
+        coin: String,
+    },
     #[display(fmt = "No such coin {}", coin)]
     NoSuchCoin {
         coin: String,
@@ -1944,6 +2129,12 @@ impl HttpStatusCode for WithdrawError {
             | WithdrawError::InvalidAddress(_)
             | WithdrawError::InvalidFeePolicy(_)
             | WithdrawError::InvalidMemo(_)
+            | WithdrawError::InvalidOutputs(_)
+            | WithdrawError::CoinDoesntSupportBatchWithdraw { .. }
+            | WithdrawError::InvalidInputs(_)
+            | WithdrawError::CoinDoesntSupportCoinControl { .. }
+            | WithdrawError::CoinDoesntSupportRbf { .. }
+            | WithdrawError::CoinDoesntSupportPsbt { .. }
             | WithdrawError::FromAddressNotFound
             | WithdrawError::UnexpectedFromAddress(_)
             | WithdrawError::UnknownAccount { .. }
@@ -2167,6 +2358,9 @@ pub trait MmCoin:
         coin_conf["wallet_only"].as_bool().unwrap_or(false)
     }
 
+    /// The coin is activated without a private key, so it cannot sign transactions and participate in the swaps.
+    fn is_watch_only(&self) -> bool { false }
+
     /// Returns a spawner pinned to the coin.
     ///
     /// # Note
@@ -2363,6 +2557,8 @@ pub enum MmCoinEnum {
     SplToken(SplToken),
     #[cfg(not(target_arch = "wasm32"))]
     LightningCoin(LightningCoin),
+    #[cfg(not(target_arch = "wasm32"))]
+    SimulatedCoin(SimulatedCoin),
     Test(TestCoin),
 }
 
@@ -2432,6 +2628,11 @@ impl From<ZCoin> for MmCoinEnum {
     fn from(c: ZCoin) -> MmCoinEnum { MmCoinEnum::ZCoin(c) }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl From<SimulatedCoin> for MmCoinEnum {
+    fn from(c: SimulatedCoin) -> MmCoinEnum { MmCoinEnum::SimulatedCoin(c) }
+}
+
 // NB: When stable and groked by IDEs, `enum_dispatch` can be used instead of `Deref` to speed things up.
 impl Deref for MmCoinEnum {
     type Target = dyn MmCoin;
@@ -2449,6 +2650,8 @@ impl Deref for MmCoinEnum {
             MmCoinEnum::LightningCoin(ref c) => c,
             #[cfg(not(target_arch = "wasm32"))]
             MmCoinEnum::ZCoin(ref c) => c,
+            #[cfg(not(target_arch = "wasm32"))]
+            MmCoinEnum::SimulatedCoin(ref c) => c,
             MmCoinEnum::Test(ref c) => c,
             #[cfg(all(
                 feature = "enable-solana",
@@ -2484,6 +2687,31 @@ impl MmCoinEnum {
 
     pub fn is_eth(&self) -> bool { matches!(self, MmCoinEnum::EthCoin(_)) }
 
+    /// Whether the coin is able to pay several `WithdrawRequest::outputs` within one transaction.
+    pub fn supports_batch_withdraw(&self) -> bool {
+        matches!(
+            self,
+            MmCoinEnum::UtxoCoin(_) | MmCoinEnum::QtumCoin(_) | MmCoinEnum::Bch(_)
+        )
+    }
+
+    /// Whether the coin relays the transactions signaling opt-in Replace-By-Fee.
+    /// Note that Bitcoin Cash doesn't support RBF.
+    pub fn supports_rbf(&self) -> bool { matches!(self, MmCoinEnum::UtxoCoin(_) | MmCoinEnum::QtumCoin(_)) }
+
+    /// Whether the coin is able to export unsigned transactions as PSBT.
+    /// Bitcoin Cash is excluded since its signatures commit to the fork id that PSBT signers don't know about.
+    pub fn supports_psbt(&self) -> bool { matches!(self, MmCoinEnum::UtxoCoin(_) | MmCoinEnum::QtumCoin(_)) }
+
+    /// Whether the coin is able to spend the `WithdrawRequest::inputs` selected by the user
+    /// and to freeze its unspent outputs.
+    pub fn supports_coin_control(&self) -> bool {
+        matches!(
+            self,
+            MmCoinEnum::UtxoCoin(_) | MmCoinEnum::QtumCoin(_) | MmCoinEnum::Bch(_)
+        )
+    }
+
     fn is_platform_coin(&self) -> bool { self.ticker() == self.platform_ticker() }
 }
 
@@ -2548,6 +2776,8 @@ pub struct CoinsContext {
     tx_history_db: SharedDb<TxHistoryDb>,
     #[cfg(target_arch = "wasm32")]
     hd_wallet_db: SharedDb<HDWalletDb>,
+    #[cfg(target_arch = "wasm32")]
+    frozen_outpoints_db: SharedDb<FrozenOutpointsDb>,
 }
 
 #[derive(Debug)]
@@ -2572,6 +2802,8 @@ impl CoinsContext {
                 tx_history_db: ConstructibleDb::new(ctx).into_shared(),
                 #[cfg(target_arch = "wasm32")]
                 hd_wallet_db: ConstructibleDb::new_shared_db(ctx).into_shared(),
+                #[cfg(target_arch = "wasm32")]
+                frozen_outpoints_db: ConstructibleDb::new(ctx).into_shared(),
             })
         })))
     }
@@ -2696,13 +2928,32 @@ impl CoinsContext {
     async fn tx_history_db(&self) -> TxHistoryResult<TxHistoryDbLocked<'_>> {
         Ok(self.tx_history_db.get_or_initialize().await?)
     }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) async fn frozen_outpoints_db(&self) -> InitDbResult<DbLocked<'_, FrozenOutpointsDb>> {
+        self.frozen_outpoints_db.get_or_initialize().await
+    }
 }
 
 /// This enum is used in coin activation requests.
-#[derive(Copy, Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum PrivKeyActivationPolicy {
     ContextPrivKey,
     Trezor,
+    /// Activates the coin without any private key, so transactions can't be signed.
+    WatchOnly(WatchOnlyParams),
+}
+
+/// The public data a watch-only wallet is built from.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchOnlyParams {
+    /// The extended public key of the `m/purpose'/coin_type'/account'` account.
+    /// The wallet is activated in the HD mode, so addresses are scanned as for a hardware wallet.
+    /// SLIP-132 prefixes like `ypub` or `zpub` are accepted too.
+    AccountXpub(String),
+    /// A single P2PKH, P2WPKH or P2TR address.
+    Address(String),
 }
 
 impl Default for PrivKeyActivationPolicy {
@@ -2713,20 +2964,26 @@ impl Default for PrivKeyActivationPolicy {
 pub enum PrivKeyPolicy<T> {
     KeyPair(T),
     Trezor,
+    WatchOnly,
 }
 
 impl<T> PrivKeyPolicy<T> {
     pub fn key_pair(&self) -> Option<&T> {
         match self {
             PrivKeyPolicy::KeyPair(key_pair) => Some(key_pair),
-            PrivKeyPolicy::Trezor => None,
+            PrivKeyPolicy::Trezor | PrivKeyPolicy::WatchOnly => None,
         }
     }
 
     pub fn key_pair_or_err(&self) -> Result<&T, MmError<PrivKeyPolicyNotAllowed>> {
-        self.key_pair()
-            .or_mm_err(|| PrivKeyPolicyNotAllowed::HardwareWalletNotSupported)
+        match self {
+            PrivKeyPolicy::KeyPair(key_pair) => Ok(key_pair),
+            PrivKeyPolicy::Trezor => MmError::err(PrivKeyPolicyNotAllowed::HardwareWalletNotSupported),
+            PrivKeyPolicy::WatchOnly => MmError::err(PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported),
+        }
     }
+
+    pub fn is_watch_only(&self) -> bool { matches!(self, PrivKeyPolicy::WatchOnly) }
 }
 
 #[derive(Clone)]
@@ -2734,6 +2991,7 @@ pub enum PrivKeyBuildPolicy {
     IguanaPrivKey(IguanaPrivKey),
     GlobalHDAccount(GlobalHDAccountArc),
     Trezor,
+    WatchOnly(WatchOnlyParams),
 }
 
 impl PrivKeyBuildPolicy {
@@ -2842,6 +3100,9 @@ pub enum CoinProtocol {
     },
     #[cfg(not(target_arch = "wasm32"))]
     ZHTLC(ZcoinProtocolInfo),
+    /// The stand-in coin settled by the local ledger, see [`simulated_coin`].
+    #[cfg(not(target_arch = "wasm32"))]
+    SIMULATION,
 }
 
 pub type RpcTransportEventHandlerShared = Arc<dyn RpcTransportEventHandler + Send + Sync + 'static>;
@@ -3097,6 +3358,12 @@ pub async fn lp_coininit(ctx: &MmArc, ticker: &str, req: &Json) -> Result<MmCoin
         CoinProtocol::ZHTLC { .. } => return ERR!("ZHTLC protocol is not supported by lp_coininit"),
         #[cfg(not(target_arch = "wasm32"))]
         CoinProtocol::LIGHTNING { .. } => return ERR!("Lightning protocol is not supported by lp_coininit"),
+        #[cfg(not(target_arch = "wasm32"))]
+        CoinProtocol::SIMULATION => {
+            let crypto_ctx = try_s!(CryptoCtx::from_ctx(ctx));
+            let key_pair = *crypto_ctx.mm2_internal_key_pair();
+            try_s!(simulated_coin_from_conf(ctx, ticker, &coins_en, key_pair)).into()
+        },
         #[cfg(all(feature = "enable-solana", not(target_arch = "wasm32")))]
         CoinProtocol::SOLANA => {
             return ERR!("Solana protocol is not supported by lp_coininit - use enable_solana_with_tokens instead")
@@ -3285,6 +3552,7 @@ pub async fn validate_address(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>
 
 pub async fn withdraw(ctx: MmArc, req: WithdrawRequest) -> WithdrawResult {
     let coin = lp_coinfind_or_err(&ctx, &req.coin).await?;
+    req.check_supported_by(&coin)?;
     coin.withdraw(req).compat().await
 }
 
@@ -3688,6 +3956,9 @@ pub fn address_by_coin_conf_and_pubkey_str(
         },
         #[cfg(not(target_arch = "wasm32"))]
         CoinProtocol::ZHTLC { .. } => ERR!("address_by_coin_conf_and_pubkey_str is not supported for ZHTLC protocol!"),
+        // The simulated address is the hex-encoded public key itself.
+        #[cfg(not(target_arch = "wasm32"))]
+        CoinProtocol::SIMULATION => Ok(pubkey.to_owned()),
     }
 }
 
@@ -1,6 +1,6 @@
 use crate::hd_wallet::{AddressDerivingError, InvalidBip44ChainError};
 use crate::tendermint::{TENDERMINT_ASSET_PROTOCOL_TYPE, TENDERMINT_COIN_PROTOCOL_TYPE};
-use crate::tx_history_storage::{CreateTxHistoryStorageError, FilteringAddresses, GetTxHistoryFilters,
+use crate::tx_history_storage::{BlockCursor, CreateTxHistoryStorageError, FilteringAddresses, GetTxHistoryFilters,
                                 TxHistoryStorageBuilder, WalletId};
 use crate::utxo::utxo_common::big_decimal_from_sat_unsigned;
 use crate::{coin_conf, lp_coinfind_or_err, BlockHeightAndTime, CoinFindError, HDAccountAddressId, HistorySyncState,
@@ -18,6 +18,7 @@ use mm2_number::BigDecimal;
 use num_traits::ToPrimitive;
 use rpc::v1::types::{Bytes as BytesJson, ToTxHash};
 use std::collections::HashSet;
+use std::num::NonZeroUsize;
 
 #[derive(Debug)]
 pub enum RemoveTxResult {
@@ -121,6 +122,22 @@ pub trait TxHistoryStorage: Send + Sync + 'static {
         tx_hash: &str,
     ) -> Result<Option<BytesJson>, MmError<Self::Error>>;
 
+    /// Gets the range of blocks which history of the given `token_id` is fetched already.
+    /// `token_id` is an empty string for the platform coin.
+    async fn get_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+    ) -> Result<Option<BlockCursor>, MmError<Self::Error>>;
+
+    /// Saves the range of blocks which history of the given `token_id` is fetched already.
+    async fn set_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+        cursor: &BlockCursor,
+    ) -> Result<(), MmError<Self::Error>>;
+
     /// Gets transaction history for the selected wallet according to the specified `filters`.
     async fn get_history(
         &self,
@@ -254,6 +271,8 @@ impl<'a, Addr: Clone + DisplayAddress + Eq + std::hash::Hash, Tx: Transaction> T
             kmd_rewards: None,
             transaction_type: self.transaction_type,
             memo: None,
+            psbt: None,
+            to_name: None,
         }
     }
 }
@@ -393,12 +412,41 @@ pub async fn my_tx_history_v2_rpc(
         MmCoinEnum::SlpToken(slp_token) => my_tx_history_v2_impl(ctx, &slp_token, request).await,
         MmCoinEnum::UtxoCoin(utxo) => my_tx_history_v2_impl(ctx, &utxo, request).await,
         MmCoinEnum::QtumCoin(qtum) => my_tx_history_v2_impl(ctx, &qtum, request).await,
+        MmCoinEnum::EthCoin(eth) => my_tx_history_v2_impl(ctx, &eth, request).await,
         MmCoinEnum::Tendermint(tendermint) => my_tx_history_v2_impl(ctx, &tendermint, request).await,
         MmCoinEnum::TendermintToken(tendermint_token) => my_tx_history_v2_impl(ctx, &tendermint_token, request).await,
         other => MmError::err(MyTxHistoryErrorV2::NotSupportedFor(other.ticker().to_owned())),
     }
 }
 
+/// Loads the whole transaction history V2 of the given `coin` for the `target`, page by page.
+/// Returns [`MyTxHistoryErrorV2::NotSupportedFor`] if the coin doesn't support the transaction history V2.
+pub async fn load_all_tx_history_v2(
+    ctx: MmArc,
+    coin: &str,
+    target: MyTxHistoryTarget,
+) -> MmResult<Vec<TransactionDetails>, MyTxHistoryErrorV2> {
+    const PAGE_LIMIT: usize = 1000;
+
+    let mut transactions = Vec::new();
+    let mut page_number = NonZeroUsize::new(1).expect("1 > 0");
+    loop {
+        let request = MyTxHistoryRequestV2 {
+            coin: coin.to_owned(),
+            limit: PAGE_LIMIT,
+            paging_options: PagingOptionsEnum::PageNumber(page_number),
+            target: target.clone(),
+        };
+        let response = my_tx_history_v2_rpc(ctx.clone(), request).await?;
+        let page_len = response.transactions.len();
+        transactions.extend(response.transactions.into_iter().map(|tx| tx.details));
+        if page_len < PAGE_LIMIT || transactions.len() >= response.total {
+            return Ok(transactions);
+        }
+        page_number = NonZeroUsize::new(page_number.get() + 1).expect("page_number > 0");
+    }
+}
+
 pub(crate) async fn my_tx_history_v2_impl<Coin>(
     ctx: MmArc,
     coin: &Coin,
@@ -211,6 +211,9 @@ pub struct TransactionNftDetails {
     pub(crate) from: Vec<String>,
     /// NFTs are sent to these addresses
     pub(crate) to: Vec<String>,
+    /// The name (e.g. ENS) the withdraw destination was resolved from. `to` contains the resolved address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) to_name: Option<String>,
     pub(crate) contract_type: ContractType,
     pub(crate) token_address: String,
     pub(crate) token_id: BigDecimal,
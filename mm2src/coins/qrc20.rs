@@ -9,7 +9,7 @@ use crate::utxo::rpc_clients::{ElectrumClient, NativeClient, UnspentInfo, UtxoRp
 use crate::utxo::tx_cache::{UtxoVerboseCacheOps, UtxoVerboseCacheShared};
 use crate::utxo::utxo_builder::{UtxoCoinBuildError, UtxoCoinBuildResult, UtxoCoinBuilder, UtxoCoinBuilderCommonOps,
                                 UtxoFieldsWithGlobalHDBuilder, UtxoFieldsWithHardwareWalletBuilder,
-                                UtxoFieldsWithIguanaSecretBuilder};
+                                UtxoFieldsWithIguanaSecretBuilder, UtxoFieldsWithWatchOnlyBuilder};
 use crate::utxo::utxo_common::{self, big_decimal_from_sat, check_all_utxo_inputs_signed_by_pub, UtxoTxBuilder};
 use crate::utxo::{qtum, ActualTxFee, AdditionalTxData, AddrFromStrError, BroadcastTxErr, FeePolicy, GenerateTxError,
                   GetUtxoListOps, HistoryUtxoTx, HistoryUtxoTxMap, MatureUnspentList, RecentlySpentOutPointsGuard,
@@ -270,6 +270,10 @@ impl<'a> UtxoFieldsWithGlobalHDBuilder for Qrc20CoinBuilder<'a> {}
 /// `UtxoCoinBuilder` trait requires `UtxoFieldsWithHardwareWalletBuilder` to be implemented.
 impl<'a> UtxoFieldsWithHardwareWalletBuilder for Qrc20CoinBuilder<'a> {}
 
+/// Although, `Qrc20Coin` doesn't support [`PrivKeyBuildPolicy::WatchOnly`] yet,
+/// `UtxoCoinBuilder` trait requires `UtxoFieldsWithWatchOnlyBuilder` to be implemented.
+impl<'a> UtxoFieldsWithWatchOnlyBuilder for Qrc20CoinBuilder<'a> {}
+
 #[async_trait]
 impl<'a> UtxoCoinBuilder for Qrc20CoinBuilder<'a> {
     type ResultCoin = Qrc20Coin;
@@ -287,6 +291,10 @@ impl<'a> UtxoCoinBuilder for Qrc20CoinBuilder<'a> {
                 let priv_key_err = PrivKeyPolicyNotAllowed::HardwareWalletNotSupported;
                 return MmError::err(UtxoCoinBuildError::PrivKeyPolicyNotAllowed(priv_key_err));
             },
+            PrivKeyBuildPolicy::WatchOnly(_) => {
+                let priv_key_err = PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported;
+                return MmError::err(UtxoCoinBuildError::PrivKeyPolicyNotAllowed(priv_key_err));
+            },
         };
 
         let inner = Qrc20CoinFields {
@@ -1593,6 +1601,8 @@ async fn qrc20_withdraw(coin: Qrc20Coin, req: WithdrawRequest) -> WithdrawResult
         kmd_rewards: None,
         transaction_type: TransactionType::StandardTransfer,
         memo: None,
+        psbt: None,
+        to_name: None,
     })
 }
 
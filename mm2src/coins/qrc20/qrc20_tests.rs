@@ -5,8 +5,8 @@ use chain::OutPoint;
 use common::{block_on, wait_until_sec, DEX_FEE_ADDR_RAW_PUBKEY};
 use crypto::Secp256k1Secret;
 use itertools::Itertools;
-use mm2_core::mm_ctx::MmCtxBuilder;
 use mm2_number::bigdecimal::Zero;
+use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
 use mocktopus::mocking::{MockResult, Mockable};
 use rpc::v1::types::ToTxHash;
 use std::convert::TryFrom;
@@ -36,7 +36,7 @@ pub fn qrc20_coin_for_test(priv_key: [u8; 32], fallback_swap: Option<&str>) -> (
         "fallback_swap_contract": fallback_swap,
     });
     let contract_address = H160::from_str("0xd362e096e873eb7907e205fadc6175c6fec7bc44").unwrap();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = Qrc20ActivationParams::from_legacy_req(&req).unwrap();
 
     let coin = block_on(qrc20_coin_with_priv_key(
@@ -80,6 +80,10 @@ fn test_withdraw_to_p2sh_address_should_fail() {
         to: p2sh_address.to_string(),
         coin: "QRC20".into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
@@ -116,6 +120,10 @@ fn test_withdraw_impl_fee_details() {
         to: "qHmJ3KA6ZAjR9wGjpFASn4gtUSeFAqdZgs".into(),
         coin: "QRC20".into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::Qrc20Gas {
             gas_limit: 2_500_000,
             gas_price: 40,
@@ -629,6 +637,8 @@ fn test_transfer_details_by_hash() {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     };
     assert_eq!(actual, expected);
 
@@ -654,6 +664,8 @@ fn test_transfer_details_by_hash() {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     };
     assert_eq!(actual, expected);
 
@@ -679,6 +691,8 @@ fn test_transfer_details_by_hash() {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     };
     assert_eq!(actual, expected);
 
@@ -704,6 +718,8 @@ fn test_transfer_details_by_hash() {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     };
     assert_eq!(actual, expected);
 
@@ -729,6 +745,8 @@ fn test_transfer_details_by_hash() {
         kmd_rewards: None,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     };
     assert_eq!(actual, expected);
     assert!(it.next().is_none());
@@ -876,7 +894,7 @@ fn test_get_sender_trade_fee_preimage_for_correct_ticker() {
     });
 
     let contract_address = H160::from_str("0xd362e096e873eb7907e205fadc6175c6fec7bc44").unwrap();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = Qrc20ActivationParams::from_legacy_req(&req).unwrap();
 
     let coin = block_on(qrc20_coin_with_priv_key(
@@ -984,7 +1002,7 @@ fn test_coin_from_conf_without_decimals() {
     });
     // 0459c999c3edf05e73c83f3fbae9f0f020919f91 has 12 decimals instead of standard 8
     let contract_address = H160::from_str("0x0459c999c3edf05e73c83f3fbae9f0f020919f91").unwrap();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = Qrc20ActivationParams::from_legacy_req(&req).unwrap();
 
     let coin = block_on(qrc20_coin_with_priv_key(
@@ -0,0 +1,235 @@
+use crate::utxo::utxo_common::{address_from_pubkey, checked_address_from_str};
+use crate::utxo::{output_script, UtxoAddressFormat, UtxoCommonOps};
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum, PrivKeyPolicyNotAllowed};
+use common::{HttpStatusCode, StatusCode};
+use crypto::{CryptoCtx, KeyPairPolicy, StandardHDPath};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use keys::{KeyPair, Private, Type as ScriptType};
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use std::str::FromStr;
+use utxo_signer::bip322::{sign_simple, verify_simple, Bip322Error};
+
+pub type Bip322RpcResult<T> = Result<T, MmError<Bip322RpcError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum Bip322RpcError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support BIP-322 message signing", coin)]
+    CoinDoesntSupportBip322 { coin: String },
+    #[display(
+        fmt = "BIP-322 simple signatures are supported for segwit and taproot addresses only, got {}",
+        _0
+    )]
+    UnsupportedAddressFormat(UtxoAddressFormat),
+    #[display(fmt = "Invalid address: {}", _0)]
+    InvalidAddress(String),
+    #[display(fmt = "Invalid derivation path: {}", _0)]
+    InvalidDerivationPath(String),
+    #[display(fmt = "Signing by a derivation path requires the HD wallet mode")]
+    HDWalletIsNotEnabled,
+    #[display(fmt = "Signature decoding error: {}", _0)]
+    SignatureDecodingError(String),
+    #[from_stringify("PrivKeyPolicyNotAllowed", "keys::Error")]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for Bip322RpcError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Bip322RpcError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            Bip322RpcError::CoinDoesntSupportBip322 { .. }
+            | Bip322RpcError::UnsupportedAddressFormat(_)
+            | Bip322RpcError::InvalidAddress(_)
+            | Bip322RpcError::InvalidDerivationPath(_)
+            | Bip322RpcError::HDWalletIsNotEnabled
+            | Bip322RpcError::SignatureDecodingError(_) => StatusCode::BAD_REQUEST,
+            Bip322RpcError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for Bip322RpcError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => Bip322RpcError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl From<Bip322Error> for Bip322RpcError {
+    fn from(e: Bip322Error) -> Self {
+        match e {
+            Bip322Error::InvalidSignature(signature) => Bip322RpcError::SignatureDecodingError(signature),
+            Bip322Error::UnsupportedScript(_) => Bip322RpcError::InvalidAddress(e.to_string()),
+            Bip322Error::MismatchScript(_) | Bip322Error::ErrorSigning(_) => {
+                Bip322RpcError::InternalError(e.to_string())
+            },
+        }
+    }
+}
+
+impl From<base64::DecodeError> for Bip322RpcError {
+    fn from(e: base64::DecodeError) -> Self { Bip322RpcError::SignatureDecodingError(e.to_string()) }
+}
+
+#[derive(Deserialize)]
+pub struct SignMessageBip322Request {
+    coin: String,
+    message: String,
+    /// The full derivation path of the HD address to sign the message with, e.g. `m/84'/0'/0'/0/1`.
+    /// Requires the HD wallet mode. If not set, the message is signed with the enabled address of the coin.
+    derivation_path: Option<String>,
+    /// The format of the address to sign the message for, either `segwit` or `taproot`.
+    /// If not set, the address format of the coin is used.
+    address_format: Option<UtxoAddressFormat>,
+}
+
+#[derive(Serialize)]
+pub struct SignMessageBip322Response {
+    /// The address the message is signed for.
+    address: String,
+    /// The base64 encoded BIP-322 simple signature.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyMessageBip322Request {
+    coin: String,
+    message: String,
+    /// The base64 encoded BIP-322 simple signature.
+    signature: String,
+    address: String,
+}
+
+#[derive(Serialize)]
+pub struct VerifyMessageBip322Response {
+    is_valid: bool,
+}
+
+/// Signs the message according to [BIP-322](https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki)
+/// proving the ownership of a segwit or taproot address.
+/// Unlike `sign_message`, the signature doesn't depend on `sign_message_prefix`.
+pub async fn sign_message_bip322(
+    ctx: MmArc,
+    req: SignMessageBip322Request,
+) -> Bip322RpcResult<SignMessageBip322Response> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => sign_message_bip322_impl(&ctx, &utxo, req),
+        MmCoinEnum::QtumCoin(qtum) => sign_message_bip322_impl(&ctx, &qtum, req),
+        _ => MmError::err(Bip322RpcError::CoinDoesntSupportBip322 { coin: req.coin }),
+    }
+}
+
+pub async fn verify_message_bip322(
+    ctx: MmArc,
+    req: VerifyMessageBip322Request,
+) -> Bip322RpcResult<VerifyMessageBip322Response> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => verify_message_bip322_impl(&utxo, &req),
+        MmCoinEnum::QtumCoin(qtum) => verify_message_bip322_impl(&qtum, &req),
+        _ => MmError::err(Bip322RpcError::CoinDoesntSupportBip322 { coin: req.coin }),
+    }
+}
+
+fn sign_message_bip322_impl<T>(
+    ctx: &MmArc,
+    coin: &T,
+    req: SignMessageBip322Request,
+) -> Bip322RpcResult<SignMessageBip322Response>
+where
+    T: UtxoCommonOps,
+{
+    let conf = &coin.as_ref().conf;
+    let addr_format = req.address_format.unwrap_or_else(|| coin.addr_format().clone());
+    match addr_format {
+        UtxoAddressFormat::Segwit | UtxoAddressFormat::Taproot if conf.bech32_hrp.is_some() => (),
+        UtxoAddressFormat::Segwit | UtxoAddressFormat::Taproot => {
+            let error = format!("'{}' coin doesn't support segwit", conf.ticker);
+            return MmError::err(Bip322RpcError::InvalidAddress(error));
+        },
+        other => return MmError::err(Bip322RpcError::UnsupportedAddressFormat(other)),
+    }
+
+    let key_pair = match req.derivation_path {
+        Some(derivation_path) => hd_key_pair(ctx, coin, &derivation_path)?,
+        None => *coin.as_ref().priv_key_policy.key_pair_or_err()?,
+    };
+
+    let address = address_from_pubkey(
+        key_pair.public(),
+        conf.pub_addr_prefix,
+        conf.pub_t_addr_prefix,
+        conf.checksum_type,
+        conf.bech32_hrp.clone(),
+        addr_format,
+    );
+    let script_pubkey = output_script(&address, ScriptType::P2WPKH);
+    let signature = sign_simple(&key_pair, &script_pubkey, req.message.as_bytes())?;
+
+    Ok(SignMessageBip322Response {
+        address: address.display_address().map_to_mm(Bip322RpcError::InternalError)?,
+        signature: base64::encode(&*signature),
+    })
+}
+
+fn verify_message_bip322_impl<T>(
+    coin: &T,
+    req: &VerifyMessageBip322Request,
+) -> Bip322RpcResult<VerifyMessageBip322Response>
+where
+    T: UtxoCommonOps,
+{
+    let address =
+        checked_address_from_str(coin, &req.address).mm_err(|e| Bip322RpcError::InvalidAddress(e.to_string()))?;
+    match address.addr_format {
+        UtxoAddressFormat::Segwit | UtxoAddressFormat::Taproot => (),
+        other => return MmError::err(Bip322RpcError::UnsupportedAddressFormat(other)),
+    }
+
+    let signature = base64::decode(&req.signature)?;
+    let script_pubkey = output_script(&address, ScriptType::P2WPKH);
+    let is_valid = verify_simple(&script_pubkey, req.message.as_bytes(), &signature)?;
+    Ok(VerifyMessageBip322Response { is_valid })
+}
+
+/// Derives the key pair of the HD address at the given `derivation_path` from the global HD account.
+fn hd_key_pair<T>(ctx: &MmArc, coin: &T, derivation_path: &str) -> Bip322RpcResult<KeyPair>
+where
+    T: UtxoCommonOps,
+{
+    let derivation_path = StandardHDPath::from_str(derivation_path)
+        .map_to_mm(|e| Bip322RpcError::InvalidDerivationPath(e.to_string()))?;
+    let conf = &coin.as_ref().conf;
+    if let Some(ref coin_derivation_path) = conf.derivation_path {
+        let expected_coin_type = coin_derivation_path.coin_type();
+        if derivation_path.coin_type() != expected_coin_type {
+            let error = format!(
+                "Derivation path '{}' must have '{}' coin type",
+                derivation_path, expected_coin_type
+            );
+            return MmError::err(Bip322RpcError::InvalidDerivationPath(error));
+        }
+    }
+
+    let crypto_ctx = CryptoCtx::from_ctx(ctx).mm_err(|e| Bip322RpcError::InternalError(e.to_string()))?;
+    let global_hd = match crypto_ctx.key_pair_policy() {
+        KeyPairPolicy::GlobalHDAccount(global_hd) => global_hd.clone(),
+        KeyPairPolicy::Iguana => return MmError::err(Bip322RpcError::HDWalletIsNotEnabled),
+    };
+    let secret = global_hd
+        .derive_secp256k1_secret_at(&derivation_path)
+        .mm_err(|e| Bip322RpcError::InvalidDerivationPath(e.to_string()))?;
+
+    let private = Private {
+        prefix: conf.wif_prefix,
+        secret,
+        compressed: true,
+        checksum_type: conf.checksum_type,
+    };
+    Ok(KeyPair::from_private(private)?)
+}
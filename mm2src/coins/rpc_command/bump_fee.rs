@@ -0,0 +1,491 @@
+use crate::utxo::rpc_clients::UtxoRpcClientOps;
+use crate::utxo::utxo_common::{big_decimal_from_sat_unsigned, calc_tx_fee, get_prev_tx, tx_size_in_v_bytes,
+                               SEQUENCE_RBF};
+use crate::utxo::{output_script, prev_script_for_signing, sat_from_big_decimal, ActualTxFee, GetUtxoListOps,
+                  UtxoAddressFormat, UtxoCommonOps, UtxoFeeDetails, UtxoTx};
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum, NumConversError, PrivKeyPolicyNotAllowed,
+            TransactionDetails, UnexpectedDerivationMethod, WithdrawFee};
+use bitcrypto::dhash160;
+use chain::constants::SEQUENCE_FINAL;
+use chain::TransactionOutput;
+use common::{now_sec, HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use futures::compat::Future01CompatExt;
+use keys::{Public, Type as ScriptType};
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use rpc::v1::types::{ToTxHash, H256 as H256Json};
+use script::{Builder, Script, SignatureVersion, TransactionInputSigner, UnsignedTransactionInput};
+use serialization::{deserialize, serialize, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use std::cmp::max;
+use utxo_signer::with_key_pair::{self, UtxoSignWithKeyPairError};
+
+pub type BumpFeeResult<T> = Result<T, MmError<BumpFeeError>>;
+
+/// The default incremental relay fee rate (`-incrementalrelayfee`) of Bitcoin Core in satoshis per kilobyte.
+const INCREMENTAL_RELAY_FEE_PER_KB: u64 = 1000;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum BumpFeeError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support this way of fee bumping", coin)]
+    CoinDoesntSupportFeeBumping { coin: String },
+    #[display(fmt = "Invalid fee policy: {}", _0)]
+    InvalidFeePolicy(String),
+    #[display(fmt = "Transaction {} is already confirmed", _0)]
+    TxAlreadyConfirmed(H256Json),
+    #[display(fmt = "Transaction {} doesn't signal Replace-By-Fee", _0)]
+    TxNotReplaceable(H256Json),
+    #[display(fmt = "Transaction {} spends outputs that don't belong to my address", _0)]
+    ForeignInputs(H256Json),
+    #[display(
+        fmt = "Transaction {} doesn't have an unspent change output to take the fee from",
+        _0
+    )]
+    NoChangeOutput(H256Json),
+    #[display(fmt = "The new fee {} doesn't exceed the current fee {}", new_fee, old_fee)]
+    FeeNotIncreased { old_fee: BigDecimal, new_fee: BigDecimal },
+    #[display(
+        fmt = "The replacement fee {} is less than the minimum {} required by the incremental relay fee",
+        new_fee,
+        min_fee
+    )]
+    InsufficientReplacementFee { new_fee: BigDecimal, min_fee: BigDecimal },
+    #[display(
+        fmt = "Not enough change to pay the fee: available {}, required at least {}",
+        available,
+        required
+    )]
+    NotSufficientChange {
+        available: BigDecimal,
+        required: BigDecimal,
+    },
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify(
+        "NumConversError",
+        "UnexpectedDerivationMethod",
+        "PrivKeyPolicyNotAllowed",
+        "UtxoSignWithKeyPairError"
+    )]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for BumpFeeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BumpFeeError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            BumpFeeError::CoinDoesntSupportFeeBumping { .. }
+            | BumpFeeError::InvalidFeePolicy(_)
+            | BumpFeeError::TxAlreadyConfirmed(_)
+            | BumpFeeError::TxNotReplaceable(_)
+            | BumpFeeError::ForeignInputs(_)
+            | BumpFeeError::NoChangeOutput(_)
+            | BumpFeeError::FeeNotIncreased { .. }
+            | BumpFeeError::InsufficientReplacementFee { .. }
+            | BumpFeeError::NotSufficientChange { .. } => StatusCode::BAD_REQUEST,
+            BumpFeeError::Transport(_) | BumpFeeError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for BumpFeeError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => BumpFeeError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_utxo_rpc_error!(BumpFeeError);
+
+#[derive(Deserialize)]
+pub struct BumpFeeRequest {
+    coin: String,
+    /// The hash of the pending transaction.
+    tx_hash: H256Json,
+    /// The new fee rate. Either `UtxoFixed` or `UtxoPerKbyte`.
+    fee: WithdrawFee,
+}
+
+/// Rebuilds the pending transaction `tx_hash` that signals Replace-By-Fee at the higher fee rate.
+/// The fee difference is taken from the change output.
+/// Besides the transactions spending my address, the swap spends and refunds of the coins configured
+/// with `swap_txs_rbf` can be replaced, since the counterparty looks them up by the spent HTLC output.
+///
+/// Returns the signed replacement that should be broadcasted using `send_raw_transaction`.
+pub async fn bump_fee(ctx: MmArc, req: BumpFeeRequest) -> BumpFeeResult<TransactionDetails> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => bump_fee_rbf_impl(&utxo, &req).await,
+        MmCoinEnum::QtumCoin(qtum) => bump_fee_rbf_impl(&qtum, &req).await,
+        _ => MmError::err(BumpFeeError::CoinDoesntSupportFeeBumping { coin: req.coin }),
+    }
+}
+
+/// Generates a Child-Pays-For-Parent transaction that spends the unspent change output of the pending `tx_hash`
+/// back to my address at the fee high enough to pay the requested fee rate for both of the transactions.
+///
+/// Swap payments can't be replaced since their hashes are already sent to the counterparty,
+/// but CPFP keeps the parent hash unchanged, so a stuck swap payment (or spend/refund) can be accelerated this way.
+///
+/// Returns the signed child transaction that should be broadcasted using `send_raw_transaction`.
+pub async fn bump_fee_cpfp(ctx: MmArc, req: BumpFeeRequest) -> BumpFeeResult<TransactionDetails> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => bump_fee_cpfp_impl(&utxo, &req).await,
+        MmCoinEnum::QtumCoin(qtum) => bump_fee_cpfp_impl(&qtum, &req).await,
+        MmCoinEnum::Bch(bch) => bump_fee_cpfp_impl(&bch, &req).await,
+        _ => MmError::err(BumpFeeError::CoinDoesntSupportFeeBumping { coin: req.coin }),
+    }
+}
+
+pub(crate) async fn bump_fee_rbf_impl<T>(coin: &T, req: &BumpFeeRequest) -> BumpFeeResult<TransactionDetails>
+where
+    T: UtxoCommonOps,
+{
+    let decimals = coin.as_ref().decimals;
+    let actual_tx_fee = actual_tx_fee(&req.fee, decimals)?;
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err()?;
+    let my_script_pubkey = output_script(my_address, ScriptType::P2PKH).to_bytes();
+    let key_pair = coin.as_ref().priv_key_policy.key_pair_or_err()?;
+
+    let tx = load_pending_tx(coin, &req.tx_hash).await?;
+    if !tx.inputs.iter().any(|input| input.sequence <= SEQUENCE_RBF) {
+        return MmError::err(BumpFeeError::TxNotReplaceable(req.tx_hash));
+    }
+
+    let prev_outputs = load_prev_outputs(coin, &tx).await?;
+    let replaced_inputs = if prev_outputs
+        .iter()
+        .all(|prev_output| prev_output.script_pubkey == my_script_pubkey)
+    {
+        ReplacedInputs::MyAddress
+    } else {
+        htlc_unlocking_data(&tx, &prev_outputs, key_pair.public())
+            .or_mm_err(|| BumpFeeError::ForeignInputs(req.tx_hash))?
+    };
+    let spent_by_me: u64 = prev_outputs.iter().map(|prev_output| prev_output.value).sum();
+    let old_fee = tx_fee(&tx, &prev_outputs)?;
+    let change_index = tx
+        .outputs
+        .iter()
+        .position(|output| output.script_pubkey == my_script_pubkey)
+        .or_mm_err(|| BumpFeeError::NoChangeOutput(req.tx_hash))?;
+
+    // Keep the inputs, the outputs and the lock time of the replaced transaction.
+    let mut unsigned = coin.as_ref().transaction_preimage();
+    unsigned.lock_time = tx.lock_time;
+    unsigned.inputs = tx
+        .inputs
+        .iter()
+        .zip(prev_outputs.iter())
+        .map(|(input, prev_output)| UnsignedTransactionInput {
+            previous_output: input.previous_output,
+            sequence: input.sequence,
+            amount: prev_output.value,
+            witness: Vec::new(),
+        })
+        .collect();
+    unsigned.outputs = tx.outputs.clone();
+
+    // The replacement is signed by the same key, so it has the same size as the replaced transaction.
+    let v_size = signed_tx_v_size(&tx);
+    let new_fee = calc_tx_fee(&actual_tx_fee, v_size);
+    // BIP-125 rule 4: the replacement must pay for its own bandwidth at the incremental relay fee rate.
+    let min_fee = old_fee + calc_tx_fee(&ActualTxFee::Dynamic(INCREMENTAL_RELAY_FEE_PER_KB), v_size);
+    if new_fee < min_fee {
+        return MmError::err(BumpFeeError::InsufficientReplacementFee {
+            new_fee: big_decimal_from_sat_unsigned(new_fee, decimals),
+            min_fee: big_decimal_from_sat_unsigned(min_fee, decimals),
+        });
+    }
+
+    let fee_delta = new_fee - old_fee;
+    let change = &mut unsigned.outputs[change_index];
+    let required = fee_delta + coin.as_ref().dust_amount;
+    if change.value < required {
+        return MmError::err(BumpFeeError::NotSufficientChange {
+            available: big_decimal_from_sat_unsigned(change.value, decimals),
+            required: big_decimal_from_sat_unsigned(required, decimals),
+        });
+    }
+    change.value -= fee_delta;
+
+    let signed = match replaced_inputs {
+        ReplacedInputs::MyAddress => sign_with_my_key_pair(coin, unsigned)?,
+        ReplacedInputs::Htlc {
+            redeem_script,
+            script_data,
+        } => {
+            let signed_input = with_key_pair::p2sh_spend(
+                &unsigned,
+                0,
+                key_pair,
+                script_data,
+                redeem_script,
+                coin.as_ref().conf.signature_version,
+                coin.as_ref().conf.fork_id,
+            )?;
+            UtxoTx {
+                inputs: vec![signed_input],
+                ..UtxoTx::from(unsigned)
+            }
+        },
+    };
+    tx_details(coin, &signed, spent_by_me, new_fee)
+}
+
+/// How the inputs of the replaced transaction are unlocked.
+enum ReplacedInputs {
+    /// Every input spends an output of my address.
+    MyAddress,
+    /// The only input spends a swap HTLC output, i.e. the replaced transaction is a swap spend or refund.
+    /// `script_data` is the data pushed between the signature and the `redeem_script`, e.g. the secret.
+    Htlc { redeem_script: Script, script_data: Script },
+}
+
+/// Extracts the data unlocking the HTLC spent by the swap spend or refund `tx`
+/// if the HTLC can be unlocked by `my_public`.
+fn htlc_unlocking_data(tx: &UtxoTx, prev_outputs: &[TransactionOutput], my_public: &Public) -> Option<ReplacedInputs> {
+    let (input, prev_output) = match (tx.inputs.as_slice(), prev_outputs) {
+        ([input], [prev_output]) => (input, prev_output),
+        _ => return None,
+    };
+    let script_sig: Script = input.script_sig.clone().into();
+    let instructions = script_sig.iter().collect::<Result<Vec<_>, _>>().ok()?;
+    // The signature, at least one unlocking data item and the redeem script.
+    if instructions.len() < 3 {
+        return None;
+    }
+    let signature = instructions.first()?;
+    let redeem_script_push = instructions.last()?;
+
+    let redeem_script: Script = redeem_script_push.data?.to_vec().into();
+    let expected_script_pubkey = Builder::build_p2sh(&dhash160(&redeem_script).into()).to_bytes();
+    if prev_output.script_pubkey != expected_script_pubkey {
+        return None;
+    }
+    let my_public = my_public.to_vec();
+    if !redeem_script
+        .iter()
+        .any(|instruction| matches!(instruction, Ok(instruction) if instruction.data == Some(my_public.as_slice())))
+    {
+        return None;
+    }
+
+    let script_data = script_sig[signature.step..script_sig.len() - redeem_script_push.step].to_vec();
+    Some(ReplacedInputs::Htlc {
+        redeem_script,
+        script_data: script_data.into(),
+    })
+}
+
+pub(crate) async fn bump_fee_cpfp_impl<T>(coin: &T, req: &BumpFeeRequest) -> BumpFeeResult<TransactionDetails>
+where
+    T: UtxoCommonOps + GetUtxoListOps,
+{
+    let decimals = coin.as_ref().decimals;
+    let actual_tx_fee = actual_tx_fee(&req.fee, decimals)?;
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err()?;
+    let my_script_pubkey = output_script(my_address, ScriptType::P2PKH).to_bytes();
+
+    let parent = load_pending_tx(coin, &req.tx_hash).await?;
+    let parent_prev_outputs = load_prev_outputs(coin, &parent).await?;
+    let parent_fee = tx_fee(&parent, &parent_prev_outputs)?;
+    let parent_hash = parent.hash();
+
+    // The frozen unspents are excluded, so take the biggest of the rest parent outputs paying to my address.
+    let (unspents, _recently_spent) = coin.get_all_unspent_ordered_list(my_address).await?;
+    let change = unspents
+        .into_iter()
+        .filter(|unspent| unspent.outpoint.hash == parent_hash)
+        .max_by_key(|unspent| unspent.value)
+        .or_mm_err(|| BumpFeeError::NoChangeOutput(req.tx_hash))?;
+
+    let mut unsigned = coin.as_ref().transaction_preimage();
+    unsigned.inputs.push(UnsignedTransactionInput {
+        previous_output: change.outpoint,
+        sequence: SEQUENCE_FINAL,
+        amount: change.value,
+        witness: Vec::new(),
+    });
+    unsigned.outputs.push(TransactionOutput {
+        value: change.value,
+        script_pubkey: my_script_pubkey,
+    });
+
+    let child_v_size = tx_size_in_v_bytes(&my_address.addr_format, &UtxoTx::from(unsigned.clone())) as u64;
+    let package_fee = calc_tx_fee(&actual_tx_fee, signed_tx_v_size(&parent) + child_v_size);
+    if package_fee <= parent_fee {
+        return MmError::err(BumpFeeError::FeeNotIncreased {
+            old_fee: big_decimal_from_sat_unsigned(parent_fee, decimals),
+            new_fee: big_decimal_from_sat_unsigned(package_fee, decimals),
+        });
+    }
+    // The child has to pay at least the requested fee rate for itself.
+    let child_fee = max(package_fee - parent_fee, calc_tx_fee(&actual_tx_fee, child_v_size));
+
+    let required = child_fee + coin.as_ref().dust_amount;
+    if change.value < required {
+        return MmError::err(BumpFeeError::NotSufficientChange {
+            available: big_decimal_from_sat_unsigned(change.value, decimals),
+            required: big_decimal_from_sat_unsigned(required, decimals),
+        });
+    }
+    unsigned.outputs[0].value -= child_fee;
+
+    let signed = sign_with_my_key_pair(coin, unsigned)?;
+    tx_details(coin, &signed, change.value, child_fee)
+}
+
+fn actual_tx_fee(fee: &WithdrawFee, decimals: u8) -> BumpFeeResult<ActualTxFee> {
+    match fee {
+        WithdrawFee::UtxoFixed { amount } => Ok(ActualTxFee::FixedPerKb(sat_from_big_decimal(amount, decimals)?)),
+        WithdrawFee::UtxoPerKbyte { amount } => Ok(ActualTxFee::Dynamic(sat_from_big_decimal(amount, decimals)?)),
+        fee_policy => {
+            let error = format!(
+                "Expected 'UtxoFixed' or 'UtxoPerKbyte' fee types, found {:?}",
+                fee_policy
+            );
+            MmError::err(BumpFeeError::InvalidFeePolicy(error))
+        },
+    }
+}
+
+/// Loads the transaction and checks if it's not confirmed yet.
+async fn load_pending_tx<T: UtxoCommonOps>(coin: &T, tx_hash: &H256Json) -> BumpFeeResult<UtxoTx> {
+    let verbose = coin
+        .as_ref()
+        .rpc_client
+        .get_verbose_transaction(tx_hash)
+        .compat()
+        .await?;
+    if verbose.confirmations > 0 || verbose.height.unwrap_or_default() > 0 {
+        return MmError::err(BumpFeeError::TxAlreadyConfirmed(*tx_hash));
+    }
+
+    let mut tx: UtxoTx =
+        deserialize(verbose.hex.as_slice()).map_to_mm(|e| BumpFeeError::InternalError(e.to_string()))?;
+    tx.tx_hash_algo = coin.as_ref().tx_hash_algo;
+    Ok(tx)
+}
+
+/// Loads the outputs spent by the inputs of the given `tx`.
+async fn load_prev_outputs<T: UtxoCommonOps>(coin: &T, tx: &UtxoTx) -> BumpFeeResult<Vec<TransactionOutput>> {
+    let mut prev_outputs = Vec::with_capacity(tx.inputs.len());
+    for input in tx.inputs.iter() {
+        let prev_tx = get_prev_tx(coin, &input.previous_output.hash).await?;
+        let prev_output = prev_tx
+            .outputs
+            .get(input.previous_output.index as usize)
+            .cloned()
+            .or_mm_err(|| {
+                let prev_hash: H256Json = input.previous_output.hash.reversed().into();
+                let error = format!("{}:{} output not found", prev_hash, input.previous_output.index);
+                BumpFeeError::InternalError(error)
+            })?;
+        prev_outputs.push(prev_output);
+    }
+    Ok(prev_outputs)
+}
+
+fn tx_fee(tx: &UtxoTx, prev_outputs: &[TransactionOutput]) -> BumpFeeResult<u64> {
+    let sum_inputs: u64 = prev_outputs.iter().map(|prev_output| prev_output.value).sum();
+    let sum_outputs: u64 = tx.outputs.iter().map(|output| output.value).sum();
+    sum_inputs.checked_sub(sum_outputs).or_mm_err(|| {
+        let error = format!("Transaction outputs {} exceed its inputs {}", sum_outputs, sum_inputs);
+        BumpFeeError::InternalError(error)
+    })
+}
+
+/// Calculates the virtual size of the signed transaction according to BIP-141.
+fn signed_tx_v_size(tx: &UtxoTx) -> u64 {
+    let base_size = serialize(tx).len();
+    let total_size = serialize_with_flags(tx, SERIALIZE_TRANSACTION_WITNESS).len();
+    ((base_size * 3 + total_size + 3) / 4) as u64
+}
+
+fn sign_with_my_key_pair<T: UtxoCommonOps>(coin: &T, unsigned: TransactionInputSigner) -> BumpFeeResult<UtxoTx> {
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err()?;
+    let key_pair = coin.as_ref().priv_key_policy.key_pair_or_err()?;
+    let signature_version = match my_address.addr_format {
+        UtxoAddressFormat::Segwit => SignatureVersion::WitnessV0,
+        UtxoAddressFormat::Taproot => SignatureVersion::Taproot,
+        _ => coin.as_ref().conf.signature_version,
+    };
+    Ok(with_key_pair::sign_tx(
+        unsigned,
+        key_pair,
+        prev_script_for_signing(my_address),
+        signature_version,
+        coin.as_ref().conf.fork_id,
+    )?)
+}
+
+fn tx_details<T: UtxoCommonOps>(
+    coin: &T,
+    signed: &UtxoTx,
+    spent_by_me: u64,
+    fee: u64,
+) -> BumpFeeResult<TransactionDetails> {
+    let ticker = coin.as_ref().conf.ticker.clone();
+    let decimals = coin.as_ref().decimals;
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err()?;
+    let my_address_string = my_address.display_address().map_to_mm(BumpFeeError::InternalError)?;
+    let my_script_pubkey = output_script(my_address, ScriptType::P2PKH).to_bytes();
+
+    let mut received_by_me = 0;
+    let mut to = Vec::new();
+    for output in signed.outputs.iter() {
+        if output.script_pubkey == my_script_pubkey {
+            received_by_me += output.value;
+            continue;
+        }
+        let addresses = coin
+            .addresses_from_script(&output.script_pubkey.clone().into())
+            .map_to_mm(BumpFeeError::InternalError)?;
+        for address in addresses {
+            to.push(address.display_address().map_to_mm(BumpFeeError::InternalError)?);
+        }
+    }
+    if to.is_empty() {
+        to.push(my_address_string.clone());
+    }
+
+    let tx_hex = if my_address.addr_format.is_witness() {
+        serialize_with_flags(signed, SERIALIZE_TRANSACTION_WITNESS).into()
+    } else {
+        serialize(signed).into()
+    };
+    let fee_details = UtxoFeeDetails {
+        coin: Some(ticker.clone()),
+        amount: big_decimal_from_sat_unsigned(fee, decimals),
+        fee_rate: None,
+        fee_rate_source: None,
+    };
+    Ok(TransactionDetails {
+        from: vec![my_address_string],
+        to,
+        total_amount: big_decimal_from_sat_unsigned(spent_by_me, decimals),
+        spent_by_me: big_decimal_from_sat_unsigned(spent_by_me, decimals),
+        received_by_me: big_decimal_from_sat_unsigned(received_by_me, decimals),
+        my_balance_change: big_decimal_from_sat_unsigned(received_by_me, decimals)
+            - big_decimal_from_sat_unsigned(spent_by_me, decimals),
+        tx_hash: signed.hash().reversed().to_vec().to_tx_hash(),
+        tx_hex,
+        fee_details: Some(fee_details.into()),
+        block_height: 0,
+        coin: ticker,
+        internal_id: vec![].into(),
+        timestamp: now_sec(),
+        kmd_rewards: None,
+        transaction_type: Default::default(),
+        memo: None,
+        psbt: None,
+        to_name: None,
+    })
+}
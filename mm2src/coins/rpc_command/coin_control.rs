@@ -0,0 +1,220 @@
+use crate::utxo::frozen_outpoints_storage::{FrozenOutpointsStorage, FrozenOutpointsStorageError};
+use crate::utxo::rpc_clients::UtxoRpcClientOps;
+use crate::utxo::utxo_common::{big_decimal_from_sat_unsigned, get_all_unspent_ordered_list_with_frozen};
+use crate::utxo::{UtxoCoinFields, UtxoCommonOps};
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum, UnexpectedDerivationMethod};
+use chain::OutPoint;
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use futures::compat::Future01CompatExt;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use rpc::v1::types::H256 as H256Json;
+
+pub type CoinControlResult<T> = Result<T, MmError<CoinControlError>>;
+
+#[derive(Debug, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum CoinControlError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support coin control", coin)]
+    CoinDoesntSupportCoinControl { coin: String },
+    #[display(fmt = "Invalid address: {}", _0)]
+    InvalidAddress(String),
+    #[display(fmt = "'address' is required for the coin activated with an HD wallet")]
+    AddressRequired,
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for CoinControlError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CoinControlError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            CoinControlError::CoinDoesntSupportCoinControl { .. }
+            | CoinControlError::InvalidAddress(_)
+            | CoinControlError::AddressRequired => StatusCode::BAD_REQUEST,
+            CoinControlError::Transport(_) | CoinControlError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for CoinControlError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => CoinControlError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_utxo_rpc_error!(CoinControlError);
+
+impl From<UnexpectedDerivationMethod> for CoinControlError {
+    fn from(_: UnexpectedDerivationMethod) -> Self { CoinControlError::AddressRequired }
+}
+
+impl From<FrozenOutpointsStorageError> for CoinControlError {
+    fn from(e: FrozenOutpointsStorageError) -> Self { CoinControlError::InternalError(e.to_string()) }
+}
+
+/// The outpoint of an unspent transaction output.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UnspentOutPoint {
+    pub tx_hash: H256Json,
+    pub vout: u32,
+}
+
+impl From<UnspentOutPoint> for OutPoint {
+    fn from(outpoint: UnspentOutPoint) -> Self {
+        OutPoint {
+            hash: outpoint.tx_hash.reversed().into(),
+            index: outpoint.vout,
+        }
+    }
+}
+
+impl From<OutPoint> for UnspentOutPoint {
+    fn from(outpoint: OutPoint) -> Self {
+        UnspentOutPoint {
+            tx_hash: outpoint.hash.reversed().into(),
+            vout: outpoint.index,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ListUnspentsRequest {
+    coin: String,
+    /// The address to list the unspent outputs of.
+    /// Defaults to the coin address if the coin is activated with a single address.
+    address: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UnspentOutputDetails {
+    #[serde(flatten)]
+    outpoint: UnspentOutPoint,
+    value: BigDecimal,
+    /// The block height the transaction is mined in. `None` if the transaction is not mined yet.
+    height: Option<u64>,
+    confirmations: u64,
+    /// Whether the output is frozen and so is never spent by withdraws or swaps.
+    frozen: bool,
+}
+
+#[derive(Serialize)]
+pub struct ListUnspentsResponse {
+    coin: String,
+    address: String,
+    unspents: Vec<UnspentOutputDetails>,
+}
+
+#[derive(Deserialize)]
+pub struct FreezeUnspentsRequest {
+    coin: String,
+    outpoints: Vec<UnspentOutPoint>,
+}
+
+#[derive(Serialize)]
+pub struct FreezeUnspentsResponse {
+    /// All of the outpoints frozen after the request is handled.
+    frozen: Vec<UnspentOutPoint>,
+}
+
+pub async fn list_unspents(ctx: MmArc, req: ListUnspentsRequest) -> CoinControlResult<ListUnspentsResponse> {
+    let coin = lp_coinfind_or_err(&ctx, &req.coin).await?;
+    let (address, unspents) = match coin {
+        MmCoinEnum::UtxoCoin(utxo) => list_unspents_impl(&utxo, req.address).await?,
+        MmCoinEnum::QtumCoin(qtum) => list_unspents_impl(&qtum, req.address).await?,
+        MmCoinEnum::Bch(bch) => list_unspents_impl(&bch, req.address).await?,
+        _ => return MmError::err(CoinControlError::CoinDoesntSupportCoinControl { coin: req.coin }),
+    };
+    Ok(ListUnspentsResponse {
+        coin: req.coin,
+        address,
+        unspents,
+    })
+}
+
+/// Marks the given outpoints as frozen so that withdraws and swaps never spend them until they're unfrozen.
+pub async fn freeze_unspents(ctx: MmArc, req: FreezeUnspentsRequest) -> CoinControlResult<FreezeUnspentsResponse> {
+    set_unspents_frozen(ctx, req, true).await
+}
+
+pub async fn unfreeze_unspents(ctx: MmArc, req: FreezeUnspentsRequest) -> CoinControlResult<FreezeUnspentsResponse> {
+    set_unspents_frozen(ctx, req, false).await
+}
+
+async fn set_unspents_frozen(
+    ctx: MmArc,
+    req: FreezeUnspentsRequest,
+    frozen: bool,
+) -> CoinControlResult<FreezeUnspentsResponse> {
+    let coin = lp_coinfind_or_err(&ctx, &req.coin).await?;
+    let fields: &UtxoCoinFields = match coin {
+        MmCoinEnum::UtxoCoin(ref utxo) => utxo.as_ref(),
+        MmCoinEnum::QtumCoin(ref qtum) => qtum.as_ref(),
+        MmCoinEnum::Bch(ref bch) => bch.as_ref(),
+        _ => return MmError::err(CoinControlError::CoinDoesntSupportCoinControl { coin: req.coin }),
+    };
+
+    let outpoints: Vec<OutPoint> = req.outpoints.into_iter().map(OutPoint::from).collect();
+    FrozenOutpointsStorage::new(ctx.clone(), req.coin)
+        .set_frozen(&outpoints, frozen)
+        .await?;
+
+    let mut frozen_outpoints = fields.frozen_outpoints.lock().unwrap();
+    for outpoint in outpoints {
+        if frozen {
+            frozen_outpoints.insert(outpoint);
+        } else {
+            frozen_outpoints.remove(&outpoint);
+        }
+    }
+    Ok(FreezeUnspentsResponse {
+        frozen: frozen_outpoints.iter().copied().map(UnspentOutPoint::from).collect(),
+    })
+}
+
+async fn list_unspents_impl<T>(
+    coin: &T,
+    address: Option<String>,
+) -> CoinControlResult<(String, Vec<UnspentOutputDetails>)>
+where
+    T: UtxoCommonOps,
+{
+    let address = match address {
+        Some(address) => coin
+            .address_from_str(&address)
+            .mm_err(|e| CoinControlError::InvalidAddress(e.to_string()))?,
+        None => coin.as_ref().derivation_method.single_addr_or_err()?.clone(),
+    };
+    let address_string = address.display_address().map_to_mm(CoinControlError::InternalError)?;
+
+    let block_count = coin.as_ref().rpc_client.get_block_count().compat().await?;
+    let (unspents, _recently_spent) = get_all_unspent_ordered_list_with_frozen(coin, &address).await?;
+
+    let decimals = coin.as_ref().decimals;
+    let frozen_outpoints = coin.as_ref().frozen_outpoints.lock().unwrap();
+    let unspents = unspents
+        .into_iter()
+        .map(|unspent| {
+            let confirmations = match unspent.height {
+                Some(height) if height > 0 && block_count >= height => block_count - height + 1,
+                _ => 0,
+            };
+            UnspentOutputDetails {
+                outpoint: unspent.outpoint.into(),
+                value: big_decimal_from_sat_unsigned(unspent.value, decimals),
+                height: unspent.height,
+                confirmations,
+                frozen: frozen_outpoints.contains(&unspent.outpoint),
+            }
+        })
+        .collect();
+    Ok((address_string, unspents))
+}
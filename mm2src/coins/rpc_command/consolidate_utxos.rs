@@ -0,0 +1,396 @@
+use crate::utxo::rpc_clients::UnspentInfo;
+use crate::utxo::utxo_common::{big_decimal_from_sat_unsigned, get_tx_fee_for_target, UtxoTxBuilder};
+use crate::utxo::{output_script, prev_script_for_signing, sat_from_big_decimal, ActualTxFee, BroadcastTxErr,
+                  FeePolicy, GenerateTxError, GetUtxoListOps, UtxoAddressFormat, UtxoCommonOps, UtxoTxBroadcastOps,
+                  UtxoTxGenerationOps};
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum, NumConversError, PrivKeyPolicyNotAllowed,
+            UnexpectedDerivationMethod};
+use chain::TransactionOutput;
+use common::executor::abortable_queue::AbortableQueue;
+use common::executor::{AbortSettings, AbortableSystem, AbortedError, SpawnAbortable, Timer};
+use common::log::{error, info};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use keys::Type as ScriptType;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use rpc::v1::types::{Bytes as BytesJson, H256 as H256Json};
+use script::SignatureVersion;
+use serialization::{serialize, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use utxo_signer::with_key_pair::{sign_tx, UtxoSignWithKeyPairError};
+
+/// The number of the unspents merged by one transaction if not specified otherwise.
+const DEFAULT_MAX_MERGED_UTXOS: usize = 100;
+/// How often the background consolidation checks the fee rate if not specified otherwise, in seconds.
+const DEFAULT_CHECK_EVERY: f64 = 600.;
+/// The background consolidation shouldn't spam the node with the fee rate and the unspents requests, in seconds.
+const MIN_CHECK_EVERY: f64 = 30.;
+
+pub type ConsolidateUtxosResult<T> = Result<T, MmError<ConsolidateUtxosError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum ConsolidateUtxosError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support UTXO consolidation", coin)]
+    CoinDoesntSupportConsolidation { coin: String },
+    #[display(fmt = "Invalid request: {}", _0)]
+    InvalidRequest(String),
+    #[display(
+        fmt = "There are only {} unspents matching the request, nothing to consolidate",
+        matching
+    )]
+    NothingToConsolidate { matching: usize },
+    #[display(fmt = "The current fee rate {} exceeds the maximum {}", current, max)]
+    FeeRateTooHigh { current: BigDecimal, max: BigDecimal },
+    #[display(fmt = "Error generating transaction: {}", _0)]
+    CannotGenerateTx(String),
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify(
+        "NumConversError",
+        "UnexpectedDerivationMethod",
+        "PrivKeyPolicyNotAllowed",
+        "UtxoSignWithKeyPairError",
+        "AbortedError"
+    )]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for ConsolidateUtxosError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConsolidateUtxosError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            ConsolidateUtxosError::CoinDoesntSupportConsolidation { .. }
+            | ConsolidateUtxosError::InvalidRequest(_)
+            | ConsolidateUtxosError::NothingToConsolidate { .. }
+            | ConsolidateUtxosError::FeeRateTooHigh { .. }
+            | ConsolidateUtxosError::CannotGenerateTx(_) => StatusCode::BAD_REQUEST,
+            ConsolidateUtxosError::Transport(_) | ConsolidateUtxosError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        }
+    }
+}
+
+impl From<CoinFindError> for ConsolidateUtxosError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => ConsolidateUtxosError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_utxo_rpc_error!(ConsolidateUtxosError);
+
+impl From<GenerateTxError> for ConsolidateUtxosError {
+    fn from(e: GenerateTxError) -> Self {
+        match e {
+            GenerateTxError::Transport(transport) => ConsolidateUtxosError::Transport(transport),
+            GenerateTxError::Internal(internal) => ConsolidateUtxosError::InternalError(internal),
+            other => ConsolidateUtxosError::CannotGenerateTx(other.to_string()),
+        }
+    }
+}
+
+impl From<BroadcastTxErr> for ConsolidateUtxosError {
+    fn from(e: BroadcastTxErr) -> Self {
+        match e {
+            BroadcastTxErr::Rpc(rpc) => ConsolidateUtxosError::from(rpc),
+            BroadcastTxErr::Other(other) => ConsolidateUtxosError::Transport(other),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ConsolidateUtxosRequest {
+    coin: String,
+    /// Only the unspents of a lower value are merged. All of the unspents are merged if not set.
+    merge_below: Option<BigDecimal>,
+    /// The maximum number of the unspents merged by one transaction.
+    #[serde(default = "default_max_merged_utxos")]
+    max_merged_utxos: usize,
+    /// The unspents are merged only if the estimated fee rate (coins per kbyte) doesn't exceed this value.
+    /// Required in the background mode.
+    max_fee_rate: Option<BigDecimal>,
+    /// The number of blocks the fee is estimated for.
+    /// Consolidation isn't urgent, so it makes sense to set it higher than the coin's `estimate_fee_blocks`.
+    fee_target_blocks: Option<u32>,
+    /// Only generate the transaction to show the fee and the resulting number of unspents, don't broadcast it.
+    #[serde(default)]
+    dry_run: bool,
+    /// If set, the consolidation is repeated in the background until it's stopped using `stop_utxo_consolidation`
+    /// or the coin is disabled.
+    background: Option<BackgroundConsolidationParams>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct BackgroundConsolidationParams {
+    /// How often the fee rate and the unspents are checked, in seconds.
+    #[serde(default = "default_check_every")]
+    check_every: f64,
+}
+
+#[derive(Serialize)]
+pub struct ConsolidationDetails {
+    tx_hash: H256Json,
+    tx_hex: BytesJson,
+    /// Whether the transaction is broadcasted. It's false for dry runs.
+    broadcasted: bool,
+    merged_utxos: usize,
+    /// The value of the resulting unspent with the fee already deducted.
+    consolidated_amount: BigDecimal,
+    fee: BigDecimal,
+    /// The fee rate per kbyte the transaction is generated with.
+    fee_rate: BigDecimal,
+    utxos_before: usize,
+    utxos_after: usize,
+}
+
+#[derive(Serialize)]
+pub struct ConsolidateUtxosResponse {
+    /// The result of the immediate consolidation attempt.
+    /// In the background mode it's `None` if the fee rate is too high or there is nothing to consolidate yet.
+    consolidation: Option<ConsolidationDetails>,
+    /// Whether the background consolidation is started.
+    background: bool,
+}
+
+#[derive(Deserialize)]
+pub struct StopUtxoConsolidationRequest {
+    coin: String,
+}
+
+#[derive(Serialize)]
+pub struct StopUtxoConsolidationResponse {
+    /// Whether there was a background consolidation to stop.
+    stopped: bool,
+}
+
+fn default_max_merged_utxos() -> usize { DEFAULT_MAX_MERGED_UTXOS }
+
+fn default_check_every() -> f64 { DEFAULT_CHECK_EVERY }
+
+/// Merges the unspents of my address into one output if the fee rate is low enough.
+/// Swap payments and withdraws spending many small unspents are bigger and so more expensive,
+/// so it's better to merge them in advance while the mempool is cheap.
+pub async fn consolidate_utxos(
+    ctx: MmArc,
+    req: ConsolidateUtxosRequest,
+) -> ConsolidateUtxosResult<ConsolidateUtxosResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => consolidate_utxos_impl(utxo, req).await,
+        MmCoinEnum::QtumCoin(qtum) => consolidate_utxos_impl(qtum, req).await,
+        _ => MmError::err(ConsolidateUtxosError::CoinDoesntSupportConsolidation { coin: req.coin }),
+    }
+}
+
+/// Stops the background consolidation started by `consolidate_utxos`.
+pub async fn stop_utxo_consolidation(
+    ctx: MmArc,
+    req: StopUtxoConsolidationRequest,
+) -> ConsolidateUtxosResult<StopUtxoConsolidationResponse> {
+    let coin = lp_coinfind_or_err(&ctx, &req.coin).await?;
+    let utxo_fields = match coin {
+        MmCoinEnum::UtxoCoin(ref utxo) => utxo.as_ref(),
+        MmCoinEnum::QtumCoin(ref qtum) => qtum.as_ref(),
+        _ => return MmError::err(ConsolidateUtxosError::CoinDoesntSupportConsolidation { coin: req.coin }),
+    };
+    // Dropping the abortable system aborts the spawned loop.
+    let consolidation_loop = utxo_fields.consolidation_loop.lock().unwrap().take();
+    Ok(StopUtxoConsolidationResponse {
+        stopped: consolidation_loop.is_some(),
+    })
+}
+
+pub(crate) async fn consolidate_utxos_impl<T>(
+    coin: T,
+    req: ConsolidateUtxosRequest,
+) -> ConsolidateUtxosResult<ConsolidateUtxosResponse>
+where
+    T: UtxoCommonOps + GetUtxoListOps + UtxoTxBroadcastOps + UtxoTxGenerationOps + Clone + Send + Sync + 'static,
+{
+    if req.max_merged_utxos < 2 {
+        let error = "'max_merged_utxos' must be at least 2".to_owned();
+        return MmError::err(ConsolidateUtxosError::InvalidRequest(error));
+    }
+
+    let check_every = match req.background {
+        Some(ref background) => background.check_every,
+        None => {
+            let consolidation = consolidate_once(&coin, &req).await?;
+            return Ok(ConsolidateUtxosResponse {
+                consolidation: Some(consolidation),
+                background: false,
+            });
+        },
+    };
+    if req.dry_run {
+        let error = "Dry run can't be done in the background".to_owned();
+        return MmError::err(ConsolidateUtxosError::InvalidRequest(error));
+    }
+    // Otherwise, the background loop would merge the unspents at any fee rate as soon as there are enough of them.
+    if req.max_fee_rate.is_none() {
+        let error = "'max_fee_rate' is required in the background mode".to_owned();
+        return MmError::err(ConsolidateUtxosError::InvalidRequest(error));
+    }
+    if !check_every.is_finite() || check_every < MIN_CHECK_EVERY {
+        let error = format!("'check_every' must be at least {} seconds", MIN_CHECK_EVERY);
+        return MmError::err(ConsolidateUtxosError::InvalidRequest(error));
+    }
+
+    let consolidation = match consolidate_once(&coin, &req).await {
+        Ok(consolidation) => Some(consolidation),
+        Err(e) if is_retriable(e.get_inner()) => None,
+        Err(e) => return Err(e),
+    };
+
+    let ticker = coin.as_ref().conf.ticker.clone();
+    let consolidation_loop: AbortableQueue = coin.as_ref().abortable_system.create_subsystem()?;
+    let settings = AbortSettings::info_on_abort(format!("UTXO consolidation stopped for {}", ticker));
+    consolidation_loop
+        .weak_spawner()
+        .spawn_with_settings(consolidation_loop_fut(coin.clone(), req, check_every), settings);
+    // Replacing the previous abortable system stops the previous consolidation loop if any.
+    *coin.as_ref().consolidation_loop.lock().unwrap() = Some(consolidation_loop);
+
+    Ok(ConsolidateUtxosResponse {
+        consolidation,
+        background: true,
+    })
+}
+
+async fn consolidation_loop_fut<T>(coin: T, req: ConsolidateUtxosRequest, check_every: f64)
+where
+    T: UtxoCommonOps + GetUtxoListOps + UtxoTxBroadcastOps + UtxoTxGenerationOps,
+{
+    let ticker = &coin.as_ref().conf.ticker;
+    loop {
+        Timer::sleep(check_every).await;
+
+        match consolidate_once(&coin, &req).await {
+            Ok(consolidation) => info!(
+                "Merged {} UTXOs of coin {}, tx_hash {:?}",
+                consolidation.merged_utxos, ticker, consolidation.tx_hash
+            ),
+            Err(e) if is_retriable(e.get_inner()) => (),
+            Err(e) => error!("Error {} on UTXO consolidation attempt for coin {}", e, ticker),
+        }
+    }
+}
+
+/// Whether the consolidation should be just postponed in the background mode.
+fn is_retriable(e: &ConsolidateUtxosError) -> bool {
+    matches!(
+        e,
+        ConsolidateUtxosError::NothingToConsolidate { .. } | ConsolidateUtxosError::FeeRateTooHigh { .. }
+    )
+}
+
+pub(crate) async fn consolidate_once<T>(
+    coin: &T,
+    req: &ConsolidateUtxosRequest,
+) -> ConsolidateUtxosResult<ConsolidationDetails>
+where
+    T: UtxoCommonOps + GetUtxoListOps + UtxoTxBroadcastOps + UtxoTxGenerationOps,
+{
+    let decimals = coin.as_ref().decimals;
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err()?;
+    let key_pair = coin.as_ref().priv_key_policy.key_pair_or_err()?;
+
+    let fee_rate = current_fee_rate(coin, req.fee_target_blocks).await?;
+    let fee_rate_sat = match fee_rate {
+        ActualTxFee::Dynamic(sat) | ActualTxFee::FixedPerKb(sat) => sat,
+    };
+    if let Some(ref max_fee_rate) = req.max_fee_rate {
+        if fee_rate_sat > sat_from_big_decimal(max_fee_rate, decimals)? {
+            return MmError::err(ConsolidateUtxosError::FeeRateTooHigh {
+                current: big_decimal_from_sat_unsigned(fee_rate_sat, decimals),
+                max: max_fee_rate.clone(),
+            });
+        }
+    }
+    let merge_below = match req.merge_below {
+        Some(ref merge_below) => Some(sat_from_big_decimal(merge_below, decimals)?),
+        None => None,
+    };
+
+    let (unspents, mut recently_spent) = coin.get_unspent_ordered_list(my_address).await?;
+    let utxos_before = unspents.len();
+    // The unspents are ordered ascending, so the smallest ones are merged first.
+    let to_merge: Vec<UnspentInfo> = unspents
+        .into_iter()
+        .filter(|unspent| merge_below.map_or(true, |merge_below| unspent.value < merge_below))
+        .take(req.max_merged_utxos)
+        .collect();
+    if to_merge.len() < 2 {
+        return MmError::err(ConsolidateUtxosError::NothingToConsolidate {
+            matching: to_merge.len(),
+        });
+    }
+
+    let merged_value: u64 = to_merge.iter().map(|unspent| unspent.value).sum();
+    let output = TransactionOutput {
+        value: merged_value,
+        script_pubkey: output_script(my_address, ScriptType::P2PKH).to_bytes(),
+    };
+    let (unsigned, data) = UtxoTxBuilder::new(coin)
+        .add_available_inputs(to_merge.clone())
+        .spend_all_available_inputs()
+        .add_outputs(vec![output])
+        .with_fee_policy(FeePolicy::DeductFromOutput(0))
+        .with_fee(fee_rate)
+        .build()
+        .await?;
+
+    let signature_version = match my_address.addr_format {
+        UtxoAddressFormat::Segwit => SignatureVersion::WitnessV0,
+        UtxoAddressFormat::Taproot => SignatureVersion::Taproot,
+        _ => coin.as_ref().conf.signature_version,
+    };
+    let signed = sign_tx(
+        unsigned,
+        key_pair,
+        prev_script_for_signing(my_address),
+        signature_version,
+        coin.as_ref().conf.fork_id,
+    )?;
+
+    let broadcasted = !req.dry_run;
+    if broadcasted {
+        coin.broadcast_tx(&signed).await?;
+        recently_spent.add_spent(to_merge.clone(), signed.hash(), signed.outputs.clone());
+    }
+
+    let tx_hex = if my_address.addr_format.is_witness() {
+        serialize_with_flags(&signed, SERIALIZE_TRANSACTION_WITNESS)
+    } else {
+        serialize(&signed)
+    };
+    Ok(ConsolidationDetails {
+        tx_hash: signed.hash().reversed().into(),
+        tx_hex: tx_hex.into(),
+        broadcasted,
+        merged_utxos: to_merge.len(),
+        consolidated_amount: big_decimal_from_sat_unsigned(signed.outputs[0].value, decimals),
+        fee: big_decimal_from_sat_unsigned(data.fee_amount, decimals),
+        fee_rate: big_decimal_from_sat_unsigned(fee_rate_sat, decimals),
+        utxos_before,
+        utxos_after: utxos_before - to_merge.len() + 1,
+    })
+}
+
+/// Estimates the fee rate for the given number of blocks if the coin is configured to use the dynamic fee.
+async fn current_fee_rate<T>(coin: &T, fee_target_blocks: Option<u32>) -> ConsolidateUtxosResult<ActualTxFee>
+where
+    T: UtxoCommonOps + UtxoTxGenerationOps,
+{
+    match fee_target_blocks {
+        Some(target_blocks) => Ok(get_tx_fee_for_target(coin.as_ref(), target_blocks).await?),
+        None => Ok(coin.get_tx_fee().await?),
+    }
+}
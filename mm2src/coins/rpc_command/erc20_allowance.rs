@@ -0,0 +1,139 @@
+use crate::eth::{u256_to_big_decimal, wei_from_big_decimal, EthCoin};
+use crate::{CoinFindError, MmCoin, NumConversError, Transaction};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use ethereum_types::{Address, U256};
+use futures::compat::Future01CompatExt;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+
+pub type Erc20AllowanceResult<T> = Result<T, MmError<Erc20AllowanceError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum Erc20AllowanceError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' is not an ERC20 token", coin)]
+    CoinIsNotErc20 { coin: String },
+    #[display(fmt = "Invalid spender address: {}", _0)]
+    InvalidSpender(String),
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify("NumConversError")]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for Erc20AllowanceError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Erc20AllowanceError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            Erc20AllowanceError::CoinIsNotErc20 { .. } | Erc20AllowanceError::InvalidSpender(_) => {
+                StatusCode::BAD_REQUEST
+            },
+            Erc20AllowanceError::Transport(_) | Erc20AllowanceError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        }
+    }
+}
+
+impl From<CoinFindError> for Erc20AllowanceError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => Erc20AllowanceError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_web3_rpc_error!(Erc20AllowanceError);
+
+#[derive(Deserialize)]
+pub struct GetAllowanceRequest {
+    coin: String,
+    /// The address allowed to transfer the tokens from my address, e.g. the swap contract.
+    spender: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetAllowanceResponse {
+    spender: String,
+    /// The amount of the token that `spender` is allowed to transfer.
+    allowance: BigDecimal,
+    /// Whether the allowance is the maximum possible value, i.e. unlimited.
+    unlimited: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ApproveRequest {
+    coin: String,
+    spender: String,
+    /// The exact amount that `spender` is allowed to transfer. Replaces the current allowance.
+    amount: BigDecimal,
+}
+
+#[derive(Deserialize)]
+pub struct RevokeApprovalRequest {
+    coin: String,
+    spender: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApproveResponse {
+    /// The hash of the broadcasted `approve` transaction.
+    tx_hash: String,
+}
+
+/// Requests the amount of the ERC20 token that `spender` is allowed to transfer from my address.
+pub async fn get_erc20_allowance(ctx: MmArc, req: GetAllowanceRequest) -> Erc20AllowanceResult<GetAllowanceResponse> {
+    let (coin, spender) = find_erc20_and_spender(&ctx, &req.coin, &req.spender).await?;
+    let allowance = coin.allowance(spender).compat().await?;
+    Ok(GetAllowanceResponse {
+        spender: req.spender,
+        allowance: u256_to_big_decimal(allowance, coin.decimals())?,
+        unlimited: allowance == U256::max_value(),
+    })
+}
+
+/// Broadcasts an `approve` transaction that allows `spender` to transfer exactly `amount` of the token.
+pub async fn approve_erc20(ctx: MmArc, req: ApproveRequest) -> Erc20AllowanceResult<ApproveResponse> {
+    let (coin, spender) = find_erc20_and_spender(&ctx, &req.coin, &req.spender).await?;
+    let amount = wei_from_big_decimal(&req.amount, coin.decimals())?;
+    send_approve(&coin, spender, amount).await
+}
+
+/// Broadcasts an `approve` transaction that sets the allowance of `spender` to zero.
+pub async fn revoke_erc20_approval(ctx: MmArc, req: RevokeApprovalRequest) -> Erc20AllowanceResult<ApproveResponse> {
+    let (coin, spender) = find_erc20_and_spender(&ctx, &req.coin, &req.spender).await?;
+    send_approve(&coin, spender, U256::zero()).await
+}
+
+async fn find_erc20_and_spender(ctx: &MmArc, ticker: &str, spender: &str) -> Erc20AllowanceResult<(EthCoin, Address)> {
+    let coin = super::find_eth_coin(ctx, ticker)
+        .await?
+        .filter(|eth| eth.erc20_token_address().is_some())
+        .or_mm_err(|| Erc20AllowanceError::CoinIsNotErc20 {
+            coin: ticker.to_owned(),
+        })?;
+    let spender = coin
+        .address_from_str(spender)
+        .map_to_mm(Erc20AllowanceError::InvalidSpender)?;
+    Ok((coin, spender))
+}
+
+async fn send_approve(coin: &EthCoin, spender: Address, amount: U256) -> Erc20AllowanceResult<ApproveResponse> {
+    // Don't replace the allowance while a swap is approving and sending its payment.
+    let allowance_lock = coin.erc20_allowance_lock();
+    let _allowance_lock = allowance_lock.lock().await;
+    let signed = coin
+        .approve(spender, amount)
+        .compat()
+        .await
+        .map_to_mm(|e| Erc20AllowanceError::Transport(e.get_plain_text_format()))?;
+    Ok(ApproveResponse {
+        tx_hash: format!("{:02x}", signed.tx_hash()),
+    })
+}
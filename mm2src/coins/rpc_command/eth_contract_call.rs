@@ -0,0 +1,154 @@
+use crate::coin_errors::MyAddressError;
+use crate::eth::{EthCoin, EthGasDetailsErr, Web3RpcError};
+use crate::{CoinFindError, NumConversError, PrivKeyPolicyNotAllowed, TransactionDetails, WithdrawFee};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use ethabi::Function;
+use ethereum_types::Address;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use serde_json::{self as json, Value as Json};
+
+pub type EthContractCallResult<T> = Result<T, MmError<EthContractCallError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum EthContractCallError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' is not an ETH/EVM platform coin", coin)]
+    CoinIsNotEthPlatform { coin: String },
+    #[display(fmt = "Invalid contract address: {}", _0)]
+    InvalidContractAddress(String),
+    #[display(fmt = "Invalid ABI function fragment: {}", _0)]
+    InvalidAbi(String),
+    #[display(fmt = "Invalid function arguments: {}", _0)]
+    InvalidArguments(String),
+    #[display(fmt = "Invalid fee policy: {}", _0)]
+    InvalidFeePolicy(String),
+    #[display(fmt = "Couldn't decode the function outputs: {}", _0)]
+    InvalidResponse(String),
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify("NumConversError", "MyAddressError", "PrivKeyPolicyNotAllowed")]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for EthContractCallError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EthContractCallError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            EthContractCallError::CoinIsNotEthPlatform { .. }
+            | EthContractCallError::InvalidContractAddress(_)
+            | EthContractCallError::InvalidAbi(_)
+            | EthContractCallError::InvalidArguments(_)
+            | EthContractCallError::InvalidFeePolicy(_) => StatusCode::BAD_REQUEST,
+            EthContractCallError::InvalidResponse(_)
+            | EthContractCallError::Transport(_)
+            | EthContractCallError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for EthContractCallError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => EthContractCallError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_web3_rpc_error!(EthContractCallError);
+
+impl From<web3::Error> for EthContractCallError {
+    fn from(e: web3::Error) -> Self { EthContractCallError::from(Web3RpcError::from(e)) }
+}
+
+impl From<EthGasDetailsErr> for EthContractCallError {
+    fn from(e: EthGasDetailsErr) -> Self {
+        match e {
+            EthGasDetailsErr::InvalidFeePolicy(e) => EthContractCallError::InvalidFeePolicy(e),
+            EthGasDetailsErr::Transport(e) => EthContractCallError::Transport(e),
+            EthGasDetailsErr::Internal(e) => EthContractCallError::InternalError(e),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EthContractCallRequest {
+    /// The platform coin, e.g. `ETH` or `MATIC`.
+    coin: String,
+    contract_address: String,
+    /// The JSON ABI fragment of the called function, e.g.
+    /// `{"name": "balanceOf", "inputs": [{"name": "owner", "type": "address"}], "outputs": [{"name": "", "type": "uint256"}]}`.
+    abi: Json,
+    /// The function arguments in the order of the ABI inputs.
+    #[serde(default)]
+    args: Vec<Json>,
+}
+
+#[derive(Deserialize)]
+pub struct SignEthContractCallRequest {
+    coin: String,
+    contract_address: String,
+    abi: Json,
+    #[serde(default)]
+    args: Vec<Json>,
+    /// The amount of the platform coin sent to the contract, e.g. to wrap ETH into WETH.
+    #[serde(default)]
+    value: BigDecimal,
+    /// Either `EthGas` or `EthGasEip1559`. If not set, the gas is estimated and the current network fee is used.
+    fee: Option<WithdrawFee>,
+    /// Whether to broadcast the signed transaction, otherwise it should be sent using `send_raw_transaction`.
+    #[serde(default)]
+    broadcast: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EthContractCallResponse {
+    /// The decoded function outputs. Integers are returned as decimal strings.
+    outputs: Vec<Json>,
+}
+
+/// Calls a view function of the contract on the latest block without sending a transaction.
+pub async fn call_eth_contract(
+    ctx: MmArc,
+    req: EthContractCallRequest,
+) -> EthContractCallResult<EthContractCallResponse> {
+    let (coin, contract, function) = parse_contract_call(&ctx, &req.coin, &req.contract_address, &req.abi).await?;
+    let outputs = coin.call_contract_function(contract, &function, &req.args).await?;
+    Ok(EthContractCallResponse { outputs })
+}
+
+/// Signs a transaction calling the contract function from my address.
+pub async fn sign_eth_contract_call(
+    ctx: MmArc,
+    req: SignEthContractCallRequest,
+) -> EthContractCallResult<TransactionDetails> {
+    let (coin, contract, function) = parse_contract_call(&ctx, &req.coin, &req.contract_address, &req.abi).await?;
+    coin.sign_contract_call(contract, &function, &req.args, &req.value, req.fee, req.broadcast)
+        .await
+}
+
+async fn parse_contract_call(
+    ctx: &MmArc,
+    ticker: &str,
+    contract_address: &str,
+    abi: &Json,
+) -> EthContractCallResult<(EthCoin, Address, Function)> {
+    let coin = super::find_eth_coin(ctx, ticker)
+        .await?
+        .filter(|eth| eth.erc20_token_address().is_none())
+        .or_mm_err(|| EthContractCallError::CoinIsNotEthPlatform {
+            coin: ticker.to_owned(),
+        })?;
+    let contract = coin
+        .address_from_str(contract_address)
+        .map_to_mm(EthContractCallError::InvalidContractAddress)?;
+    let function: Function =
+        json::from_value(abi.clone()).map_to_mm(|e| EthContractCallError::InvalidAbi(e.to_string()))?;
+    Ok((coin, contract, function))
+}
@@ -0,0 +1,65 @@
+use crate::eth::Web3NodeStatus;
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+
+pub type EthNodesStatusResult<T> = Result<T, MmError<EthNodesStatusError>>;
+
+#[derive(Debug, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum EthNodesStatusError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't track the health of its nodes", coin)]
+    CoinDoesntSupportNodesStatus { coin: String },
+}
+
+impl HttpStatusCode for EthNodesStatusError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EthNodesStatusError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            EthNodesStatusError::CoinDoesntSupportNodesStatus { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl From<CoinFindError> for EthNodesStatusError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => EthNodesStatusError::NoSuchCoin { coin },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct EthNodesStatusRequest {
+    coin: String,
+    /// Whether to check the nodes before returning their status instead of returning the last known one.
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EthNodesStatusResponse {
+    /// The nodes in the order they're configured.
+    nodes: Vec<Web3NodeStatus>,
+}
+
+/// Returns the latency, error rate and block lag of the coin's nodes and whether they're quarantined.
+/// The requests are sent to the nodes in the order of their `rank`.
+pub async fn get_eth_nodes_status(
+    ctx: MmArc,
+    req: EthNodesStatusRequest,
+) -> EthNodesStatusResult<EthNodesStatusResponse> {
+    let coin = match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::EthCoin(eth) => eth,
+        _ => return MmError::err(EthNodesStatusError::CoinDoesntSupportNodesStatus { coin: req.coin }),
+    };
+    let nodes = coin
+        .nodes_status(req.refresh)
+        .await
+        .or_mm_err(|| EthNodesStatusError::CoinDoesntSupportNodesStatus { coin: req.coin })?;
+    Ok(EthNodesStatusResponse { nodes })
+}
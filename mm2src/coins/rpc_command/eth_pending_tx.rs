@@ -0,0 +1,190 @@
+use crate::coin_errors::MyAddressError;
+use crate::eth::{EthCoin, EthGasDetailsErr, Web3RpcError};
+use crate::{CoinFindError, NumConversError, PrivKeyPolicyNotAllowed, TransactionDetails, UnexpectedDerivationMethod,
+            WithdrawError, WithdrawFee, WithdrawFrom};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use rpc::v1::types::H256 as H256Json;
+
+pub type PendingEthTxResult<T> = Result<T, MmError<PendingEthTxError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum PendingEthTxError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support replacing pending transactions", coin)]
+    CoinDoesntSupportTxReplacement { coin: String },
+    #[display(fmt = "Invalid fee policy: {}", _0)]
+    InvalidFeePolicy(String),
+    #[display(fmt = "Transaction {} is not found", _0)]
+    TxNotFound(H256Json),
+    #[display(fmt = "Transaction {} or another one with the same nonce is already confirmed", _0)]
+    TxAlreadyConfirmed(H256Json),
+    #[display(fmt = "Transaction {} isn't sent from my address {}", tx_hash, my_address)]
+    ForeignTx { tx_hash: H256Json, my_address: String },
+    #[display(fmt = "Unexpected 'from' address: {}", _0)]
+    UnexpectedFromAddress(String),
+    #[display(fmt = "Transaction {} deploys a contract and can't be sped up", _0)]
+    ContractDeployment(H256Json),
+    #[display(
+        fmt = "'{}' must be at least {} Gwei to replace the pending transaction, found {} Gwei",
+        param,
+        required,
+        provided
+    )]
+    FeeNotIncreased {
+        param: String,
+        required: BigDecimal,
+        provided: BigDecimal,
+    },
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify(
+        "NumConversError",
+        "MyAddressError",
+        "PrivKeyPolicyNotAllowed",
+        "UnexpectedDerivationMethod"
+    )]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for PendingEthTxError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PendingEthTxError::NoSuchCoin { .. } | PendingEthTxError::TxNotFound(_) => StatusCode::NOT_FOUND,
+            PendingEthTxError::CoinDoesntSupportTxReplacement { .. }
+            | PendingEthTxError::InvalidFeePolicy(_)
+            | PendingEthTxError::TxAlreadyConfirmed(_)
+            | PendingEthTxError::ForeignTx { .. }
+            | PendingEthTxError::UnexpectedFromAddress(_)
+            | PendingEthTxError::ContractDeployment(_)
+            | PendingEthTxError::FeeNotIncreased { .. } => StatusCode::BAD_REQUEST,
+            PendingEthTxError::Transport(_) | PendingEthTxError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for PendingEthTxError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => PendingEthTxError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_web3_rpc_error!(PendingEthTxError);
+
+impl From<web3::Error> for PendingEthTxError {
+    fn from(e: web3::Error) -> Self { PendingEthTxError::from(Web3RpcError::from(e)) }
+}
+
+impl From<WithdrawError> for PendingEthTxError {
+    fn from(e: WithdrawError) -> Self {
+        match e {
+            WithdrawError::FromAddressNotFound | WithdrawError::UnknownAccount { .. } => {
+                PendingEthTxError::UnexpectedFromAddress(e.to_string())
+            },
+            WithdrawError::UnexpectedFromAddress(e) => PendingEthTxError::UnexpectedFromAddress(e),
+            WithdrawError::Transport(e) => PendingEthTxError::Transport(e),
+            e => PendingEthTxError::InternalError(e.to_string()),
+        }
+    }
+}
+
+impl From<EthGasDetailsErr> for PendingEthTxError {
+    fn from(e: EthGasDetailsErr) -> Self {
+        match e {
+            EthGasDetailsErr::InvalidFeePolicy(e) => PendingEthTxError::InvalidFeePolicy(e),
+            EthGasDetailsErr::Transport(e) => PendingEthTxError::Transport(e),
+            EthGasDetailsErr::Internal(e) => PendingEthTxError::InternalError(e),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReplacePendingEthTxRequest {
+    coin: String,
+    /// The hash of the pending transaction sent from my address.
+    tx_hash: H256Json,
+    /// The fee of the replacement. Either `EthGas` or `EthGasEip1559`.
+    /// If not set, the current network fee is used, but not less than the minimum required to replace `tx_hash`.
+    fee: Option<WithdrawFee>,
+    /// The HD wallet address that sent `tx_hash`. Required if the coin is activated with an HD wallet.
+    from: Option<WithdrawFrom>,
+    /// Whether to broadcast the signed replacement.
+    /// Otherwise, it's only returned to be broadcasted later using `send_raw_transaction`.
+    #[serde(default)]
+    broadcast: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PendingNoncesRequest {
+    coin: String,
+    /// The HD wallet address which nonces are requested. My address is used if not set.
+    from: Option<WithdrawFrom>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingNoncesResponse {
+    address: String,
+    /// The nonce of the first transaction that is not mined yet.
+    latest_nonce: u64,
+    /// The nonce of the next transaction.
+    pending_nonce: u64,
+    /// The nonces of the broadcasted transactions that are not mined yet.
+    /// Every new transaction is queued behind them, so they have to be sped up or cancelled.
+    stuck_nonces: Vec<u64>,
+}
+
+/// Signs a replacement of the pending `tx_hash` that pays more for gas.
+/// The replacement has the same nonce, recipient, value and data, so it's mined instead of the pending one.
+pub async fn speed_up_eth_transaction(
+    ctx: MmArc,
+    req: ReplacePendingEthTxRequest,
+) -> PendingEthTxResult<TransactionDetails> {
+    let coin = find_eth_coin(&ctx, &req.coin).await?;
+    coin.speed_up_pending_tx(req.tx_hash, req.fee, req.from.as_ref(), req.broadcast)
+        .await
+}
+
+/// Signs a zero-value transfer to my address with the nonce of the pending `tx_hash`.
+/// Once the transfer is mined, the pending transaction becomes invalid and is dropped from the mempool.
+pub async fn cancel_eth_transaction(
+    ctx: MmArc,
+    req: ReplacePendingEthTxRequest,
+) -> PendingEthTxResult<TransactionDetails> {
+    let coin = find_eth_coin(&ctx, &req.coin).await?;
+    coin.cancel_pending_tx(req.tx_hash, req.fee, req.from.as_ref(), req.broadcast)
+        .await
+}
+
+/// Compares the pending and the latest transaction counts of the address to detect the stuck nonces.
+pub async fn get_eth_pending_nonces(
+    ctx: MmArc,
+    req: PendingNoncesRequest,
+) -> PendingEthTxResult<PendingNoncesResponse> {
+    let coin = find_eth_coin(&ctx, &req.coin).await?;
+    let (address, nonces) = coin.addr_nonces(req.from.as_ref()).await?;
+    let latest_nonce = nonces.latest.low_u64();
+    let pending_nonce = nonces.pending.low_u64();
+    Ok(PendingNoncesResponse {
+        address,
+        latest_nonce,
+        pending_nonce,
+        stuck_nonces: (latest_nonce..pending_nonce).collect(),
+    })
+}
+
+async fn find_eth_coin(ctx: &MmArc, ticker: &str) -> PendingEthTxResult<EthCoin> {
+    super::find_eth_coin(ctx, ticker)
+        .await?
+        .or_mm_err(|| PendingEthTxError::CoinDoesntSupportTxReplacement {
+            coin: ticker.to_owned(),
+        })
+}
@@ -0,0 +1,131 @@
+use crate::coin_errors::MyAddressError;
+use crate::eth::typed_data::TypedData;
+use crate::eth::{checksum_address, EthCoin};
+use crate::{CoinFindError, MarketCoinOps, PrivKeyPolicyNotAllowed};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use ethkey::Signature;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use std::str::FromStr;
+
+pub type EthTypedDataResult<T> = Result<T, MmError<EthTypedDataError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum EthTypedDataError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support EIP-712 typed data", coin)]
+    CoinDoesntSupportTypedData { coin: String },
+    #[display(fmt = "Invalid typed data: {}", _0)]
+    InvalidTypedData(String),
+    #[display(
+        fmt = "The typed data is intended for chain {}, but the coin is on chain {}",
+        domain_chain_id,
+        coin_chain_id
+    )]
+    ChainIdMismatch { coin_chain_id: u64, domain_chain_id: u64 },
+    #[display(fmt = "Invalid signature: {}", _0)]
+    InvalidSignature(String),
+    #[display(fmt = "Invalid address: {}", _0)]
+    InvalidAddress(String),
+    #[from_stringify("MyAddressError", "PrivKeyPolicyNotAllowed", "ethkey::Error")]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for EthTypedDataError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            EthTypedDataError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            EthTypedDataError::CoinDoesntSupportTypedData { .. }
+            | EthTypedDataError::InvalidTypedData(_)
+            | EthTypedDataError::ChainIdMismatch { .. }
+            | EthTypedDataError::InvalidSignature(_)
+            | EthTypedDataError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            EthTypedDataError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for EthTypedDataError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => EthTypedDataError::NoSuchCoin { coin },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SignTypedDataRequest {
+    coin: String,
+    /// The `types`, `domain`, `primaryType` and `message` object as it's passed to `eth_signTypedData_v4`.
+    typed_data: TypedData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignTypedDataResponse {
+    /// The address whose key signed the data.
+    address: String,
+    /// The EIP-712 hash of the typed data.
+    hash: String,
+    /// The 65 bytes `r || s || v` signature with `v` being 27 or 28.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyTypedDataRequest {
+    coin: String,
+    typed_data: TypedData,
+    signature: String,
+    /// The expected signer.
+    address: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyTypedDataResponse {
+    /// Whether the data is signed by `address`.
+    is_valid: bool,
+    /// The address recovered from the signature.
+    signer: String,
+    hash: String,
+}
+
+/// Signs an EIP-712 typed structured data with the key of the coin's address,
+/// the same way as `eth_signTypedData_v4` does.
+pub async fn sign_typed_data(ctx: MmArc, req: SignTypedDataRequest) -> EthTypedDataResult<SignTypedDataResponse> {
+    let coin = find_eth_coin(&ctx, &req.coin).await?;
+    let (hash, signature) = coin.sign_typed_data(req.typed_data)?;
+    Ok(SignTypedDataResponse {
+        address: coin.my_address()?,
+        hash: format!("0x{:02x}", hash),
+        signature: format!("0x{}", signature),
+    })
+}
+
+/// Recovers the signer of an EIP-712 typed structured data and compares it with the expected address.
+pub async fn verify_typed_data(ctx: MmArc, req: VerifyTypedDataRequest) -> EthTypedDataResult<VerifyTypedDataResponse> {
+    let coin = find_eth_coin(&ctx, &req.coin).await?;
+    let address = coin
+        .address_from_str(&req.address)
+        .map_to_mm(EthTypedDataError::InvalidAddress)?;
+    let signature = Signature::from_str(req.signature.strip_prefix("0x").unwrap_or(&req.signature))
+        .map_to_mm(|e| EthTypedDataError::InvalidSignature(e.to_string()))?;
+
+    let (hash, signer) = coin.recover_typed_data_signer(req.typed_data, signature)?;
+    Ok(VerifyTypedDataResponse {
+        is_valid: signer == address,
+        signer: checksum_address(&format!("{:#02x}", signer)),
+        hash: format!("0x{:02x}", hash),
+    })
+}
+
+async fn find_eth_coin(ctx: &MmArc, ticker: &str) -> EthTypedDataResult<EthCoin> {
+    super::find_eth_coin(ctx, ticker)
+        .await?
+        .or_mm_err(|| EthTypedDataError::CoinDoesntSupportTypedData {
+            coin: ticker.to_owned(),
+        })
+}
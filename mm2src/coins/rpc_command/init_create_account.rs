@@ -236,6 +236,7 @@ impl RpcTask for InitCreateAccountTask {
             match self.coin {
                 MmCoinEnum::UtxoCoin(utxo) => utxo.revert_creating_account(account_id).await,
                 MmCoinEnum::QtumCoin(qtum) => qtum.revert_creating_account(account_id).await,
+                MmCoinEnum::EthCoin(eth) => eth.revert_creating_account(account_id).await,
                 _ => (),
             }
         };
@@ -261,7 +262,9 @@ impl RpcTask for InitCreateAccountTask {
                 on_passphrase_request: CreateAccountAwaitingStatus::EnterTrezorPassphrase,
                 on_ready: CreateAccountInProgressStatus::RequestingAccountBalance,
             };
-            let xpub_extractor = CreateAccountXPubExtractor::new(ctx, task_handle, hw_statuses)?;
+            // Don't require the hardware wallet context here since ETH accounts are derived from the seed phrase.
+            // The error is returned on the first attempt to extract an xpub from the device.
+            let xpub_extractor = CreateAccountXPubExtractor::new_unchecked(ctx, task_handle, hw_statuses);
             coin.init_create_account_rpc(params, state, &xpub_extractor).await
         }
 
@@ -286,6 +289,16 @@ impl RpcTask for InitCreateAccountTask {
                 )
                 .await
             },
+            MmCoinEnum::EthCoin(ref eth) => {
+                create_new_account_helper(
+                    &self.ctx,
+                    eth,
+                    self.req.params.clone(),
+                    self.task_state.clone(),
+                    task_handle,
+                )
+                .await
+            },
             _ => MmError::err(CreateAccountRpcError::CoinIsActivatedNotWithHDWallet),
         }
     }
@@ -34,6 +34,7 @@ pub trait CoinWithdrawInit {
 
 pub async fn init_withdraw(ctx: MmArc, request: WithdrawRequest) -> WithdrawInitResult<InitWithdrawResponse> {
     let coin = lp_coinfind_or_err(&ctx, &request.coin).await?;
+    request.check_supported_by(&coin)?;
     let spawner = coin.spawner();
     let task = WithdrawTask {
         ctx: ctx.clone(),
@@ -1,4 +1,52 @@
+/// Implements `From<UtxoRpcError>` for the RPC errors that have the `Transport(String)` and `InternalError(String)` variants.
+macro_rules! impl_from_utxo_rpc_error {
+    ($($error: ident),+) => {
+        $(
+            impl From<$crate::utxo::rpc_clients::UtxoRpcError> for $error {
+                fn from(e: $crate::utxo::rpc_clients::UtxoRpcError) -> Self {
+                    use $crate::utxo::rpc_clients::UtxoRpcError;
+                    match e {
+                        UtxoRpcError::Transport(transport) | UtxoRpcError::ResponseParseError(transport) => {
+                            $error::Transport(transport.to_string())
+                        },
+                        UtxoRpcError::InvalidResponse(resp) => $error::Transport(resp),
+                        UtxoRpcError::Internal(internal) => $error::InternalError(internal),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+/// Implements `From<Web3RpcError>` for the RPC errors that have the `Transport(String)` and `InternalError(String)` variants.
+macro_rules! impl_from_web3_rpc_error {
+    ($($error: ident),+) => {
+        $(
+            impl From<$crate::eth::Web3RpcError> for $error {
+                fn from(e: $crate::eth::Web3RpcError) -> Self {
+                    use $crate::eth::Web3RpcError;
+                    match e {
+                        Web3RpcError::Transport(tr) | Web3RpcError::InvalidResponse(tr) | Web3RpcError::Timeout(tr) => {
+                            $error::Transport(tr)
+                        },
+                        Web3RpcError::Internal(internal) => $error::InternalError(internal),
+                    }
+                }
+            }
+        )+
+    };
+}
+
 pub mod account_balance;
+pub mod bip322;
+pub mod bump_fee;
+pub mod coin_control;
+pub mod consolidate_utxos;
+pub mod erc20_allowance;
+pub mod eth_contract_call;
+pub mod eth_nodes_status;
+pub mod eth_pending_tx;
+pub mod eth_typed_data;
 pub mod get_current_mtp;
 pub mod get_enabled_coins;
 pub mod get_new_address;
@@ -8,4 +56,17 @@ pub mod init_create_account;
 pub mod init_scan_for_new_addresses;
 pub mod init_withdraw;
 #[cfg(not(target_arch = "wasm32"))] pub mod lightning;
+pub mod multisig;
+pub mod psbt;
 pub mod tendermint;
+
+/// Finds the activated ETH or ERC20 coin. Returns `None` if `ticker` is another coin.
+async fn find_eth_coin(
+    ctx: &mm2_core::mm_ctx::MmArc,
+    ticker: &str,
+) -> mm2_err_handle::prelude::MmResult<Option<crate::eth::EthCoin>, crate::CoinFindError> {
+    match crate::lp_coinfind_or_err(ctx, ticker).await? {
+        crate::MmCoinEnum::EthCoin(eth) => Ok(Some(eth)),
+        _ => Ok(None),
+    }
+}
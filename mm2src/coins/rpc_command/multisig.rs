@@ -0,0 +1,678 @@
+use crate::utxo::rpc_clients::{electrum_script_hash, NativeClient, UnspentInfo, UtxoRpcClientEnum, UtxoRpcClientOps,
+                               UtxoRpcError};
+use crate::utxo::utxo_common::{big_decimal_from_sat_unsigned, get_prev_tx, InputUnlockingSize, UtxoTxBuilder};
+use crate::utxo::{output_script, sat_from_big_decimal, ActualTxFee, Address, FeePolicy, GenerateTxError,
+                  UtxoAddressFormat, UtxoCommonOps};
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum, NumConversError, PrivKeyPolicy, WithdrawFee};
+use bitcrypto::{dhash160, sha256};
+use chain::TransactionOutput;
+use common::{HttpStatusCode, StatusCode};
+use crypto::{Bip44Chain, ChildNumber, Secp256k1ExtendedPublicKey};
+use derive_more::Display;
+use enum_from::EnumFromStringify;
+use futures::compat::Future01CompatExt;
+use keys::{AddressHashEnum, Public, Type as ScriptType};
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::BigDecimal;
+use primitives::hash::H264;
+use rpc::v1::types::{Bytes as BytesJson, H256 as H256Json};
+use script::{Builder, Script, SignatureVersion};
+use std::collections::HashSet;
+use std::str::FromStr;
+use utxo_signer::psbt::{multisig_script_pubkey, Psbt, PsbtError};
+
+/// The redeem script of a P2SH output can't exceed 520 bytes, so there can't be more than 15 compressed public keys.
+const MAX_MULTISIG_KEYS: usize = 15;
+/// The number of the transactions requested from a native node at once.
+const LIST_TRANSACTIONS_STEP: u64 = 1000;
+
+pub type MultisigResult<T> = Result<T, MmError<MultisigError>>;
+
+#[derive(Debug, Display, EnumFromStringify, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum MultisigError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support multisig wallets", coin)]
+    CoinDoesntSupportMultisig { coin: String },
+    #[display(fmt = "Invalid multisig wallet: {}", _0)]
+    InvalidMultisigWallet(String),
+    #[display(fmt = "Invalid co-signer key '{}': {}", key, reason)]
+    InvalidKey { key: String, reason: String },
+    #[display(fmt = "Invalid address: {}", _0)]
+    InvalidAddress(String),
+    #[display(fmt = "Invalid fee policy: {}", _0)]
+    InvalidFeePolicy(String),
+    #[display(fmt = "Error generating transaction: {}", _0)]
+    CannotGenerateTx(String),
+    #[display(fmt = "Invalid PSBT: {}", _0)]
+    InvalidPsbt(String),
+    #[display(
+        fmt = "The multisig address '{}' is not imported to the native node, call 'multisig_address' first",
+        address
+    )]
+    AddressNotImported { address: String },
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[from_stringify("NumConversError")]
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for MultisigError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MultisigError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            MultisigError::CoinDoesntSupportMultisig { .. }
+            | MultisigError::InvalidMultisigWallet(_)
+            | MultisigError::InvalidKey { .. }
+            | MultisigError::InvalidAddress(_)
+            | MultisigError::InvalidFeePolicy(_)
+            | MultisigError::CannotGenerateTx(_)
+            | MultisigError::InvalidPsbt(_)
+            | MultisigError::AddressNotImported { .. } => StatusCode::BAD_REQUEST,
+            MultisigError::Transport(_) | MultisigError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for MultisigError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => MultisigError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_utxo_rpc_error!(MultisigError);
+
+impl From<GenerateTxError> for MultisigError {
+    fn from(e: GenerateTxError) -> Self {
+        match e {
+            GenerateTxError::Transport(transport) => MultisigError::Transport(transport),
+            GenerateTxError::Internal(internal) => MultisigError::InternalError(internal),
+            other => MultisigError::CannotGenerateTx(other.to_string()),
+        }
+    }
+}
+
+impl From<PsbtError> for MultisigError {
+    fn from(e: PsbtError) -> Self {
+        match e {
+            PsbtError::ErrorSigning(signing) => MultisigError::InternalError(signing.to_string()),
+            other => MultisigError::InvalidPsbt(other.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultisigScriptType {
+    P2sh,
+    P2wsh,
+}
+
+impl MultisigScriptType {
+    fn signature_version(&self) -> SignatureVersion {
+        match self {
+            MultisigScriptType::P2sh => SignatureVersion::Base,
+            MultisigScriptType::P2wsh => SignatureVersion::WitnessV0,
+        }
+    }
+}
+
+/// The m-of-n multisig wallet description. It's not stored anywhere, so it's passed to every multisig RPC.
+#[derive(Deserialize)]
+pub struct MultisigWalletParams {
+    /// The number of the signatures required to spend the wallet funds (m).
+    required: usize,
+    /// The keys of the co-signers (n). Every key is either a hex encoded compressed public key
+    /// or an account extended public key (xpub) the key of the `address_id` external address is derived from.
+    keys: Vec<String>,
+    /// The index of the external address the keys are derived from the extended public keys at.
+    #[serde(default)]
+    address_id: u32,
+    script_type: MultisigScriptType,
+    /// Whether to sort the public keys as BIP-67 suggests, so that the wallet doesn't depend on the order of the keys.
+    #[serde(default = "common::true_f")]
+    sort_keys: bool,
+}
+
+struct MultisigWallet {
+    required: usize,
+    pubkeys: Vec<Public>,
+    script_type: MultisigScriptType,
+    /// The redeem script of P2SH or the witness script of P2WSH.
+    script: Script,
+    script_pubkey: Script,
+    address: Address,
+}
+
+impl MultisigWallet {
+    fn input_unlocking_size(&self) -> InputUnlockingSize {
+        let is_witness = self.script_type == MultisigScriptType::P2wsh;
+        InputUnlockingSize::multisig(self.required, self.script.len(), is_witness)
+    }
+
+    fn address_string(&self) -> MultisigResult<String> {
+        self.address.display_address().map_to_mm(MultisigError::InternalError)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MultisigAddressRequest {
+    coin: String,
+    wallet: MultisigWalletParams,
+    /// Whether the native node should rescan the blockchain for the transactions of the just imported address.
+    /// Ignored if the coin is activated with Electrum.
+    #[serde(default)]
+    rescan: bool,
+}
+
+#[derive(Serialize)]
+pub struct MultisigAddressResponse {
+    address: String,
+    script_type: MultisigScriptType,
+    required: usize,
+    /// The public keys of the co-signers in the order they're pushed to the script.
+    pubkeys: Vec<String>,
+    /// The redeem script of P2SH or the witness script of P2WSH.
+    script: BytesJson,
+}
+
+#[derive(Deserialize)]
+pub struct MultisigWalletRequest {
+    coin: String,
+    wallet: MultisigWalletParams,
+}
+
+#[derive(Serialize)]
+pub struct MultisigBalanceResponse {
+    address: String,
+    balance: BigDecimal,
+    unspents_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct MultisigHistoryItem {
+    tx_hash: H256Json,
+    /// The block height the transaction is mined in. `None` if the transaction is not mined yet.
+    height: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct MultisigHistoryResponse {
+    address: String,
+    transactions: Vec<MultisigHistoryItem>,
+}
+
+#[derive(Deserialize)]
+pub struct MultisigWithdrawRequest {
+    coin: String,
+    wallet: MultisigWalletParams,
+    to: String,
+    #[serde(default)]
+    amount: BigDecimal,
+    #[serde(default)]
+    max: bool,
+    fee: Option<WithdrawFee>,
+}
+
+#[derive(Serialize)]
+pub struct MultisigWithdrawResponse {
+    /// The base64 encoded PSBT to be signed by the rest of the co-signers.
+    psbt: String,
+    /// The number of the inputs signed by my key. It's zero if my key is not one of the co-signer keys.
+    signed_inputs: usize,
+    /// Whether the PSBT has enough signatures to be broadcasted using `broadcast_psbt`.
+    complete: bool,
+    fee: BigDecimal,
+    spent_by_wallet: BigDecimal,
+    received_by_wallet: BigDecimal,
+}
+
+#[derive(Deserialize)]
+pub struct SignMultisigPsbtRequest {
+    coin: String,
+    /// The base64 encoded PSBT generated by `multisig_withdraw`.
+    psbt: String,
+}
+
+#[derive(Deserialize)]
+pub struct CombinePsbtRequest {
+    coin: String,
+    /// The base64 encoded PSBTs of the same transaction signed by the different co-signers.
+    psbts: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MultisigPsbtResponse {
+    psbt: String,
+    /// The number of the inputs signed by my key while handling the request.
+    signed_inputs: usize,
+    /// Whether the PSBT has enough signatures to be broadcasted using `broadcast_psbt`.
+    complete: bool,
+}
+
+/// Returns the address of the m-of-n multisig wallet.
+/// The address is also imported to the native node as watch-only so that its balance and history can be tracked.
+pub async fn multisig_address(ctx: MmArc, req: MultisigAddressRequest) -> MultisigResult<MultisigAddressResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => multisig_address_impl(&utxo, &req).await,
+        MmCoinEnum::QtumCoin(qtum) => multisig_address_impl(&qtum, &req).await,
+        _ => MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+}
+
+pub async fn multisig_balance(ctx: MmArc, req: MultisigWalletRequest) -> MultisigResult<MultisigBalanceResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => multisig_balance_impl(&utxo, &req.wallet).await,
+        MmCoinEnum::QtumCoin(qtum) => multisig_balance_impl(&qtum, &req.wallet).await,
+        _ => MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+}
+
+/// Returns the hashes of the transactions sending to or spending from the multisig wallet.
+pub async fn multisig_history(ctx: MmArc, req: MultisigWalletRequest) -> MultisigResult<MultisigHistoryResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => multisig_history_impl(&utxo, &req.wallet).await,
+        MmCoinEnum::QtumCoin(qtum) => multisig_history_impl(&qtum, &req.wallet).await,
+        _ => MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+}
+
+/// Generates a PSBT spending the multisig wallet funds and signs it if my key is one of the co-signer keys.
+/// The PSBT is expected to be signed by the rest of the co-signers using `sign_multisig_psbt`,
+/// combined using `combine_psbt` and broadcasted using `broadcast_psbt`.
+pub async fn multisig_withdraw(ctx: MmArc, req: MultisigWithdrawRequest) -> MultisigResult<MultisigWithdrawResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => multisig_withdraw_impl(&utxo, &req).await,
+        MmCoinEnum::QtumCoin(qtum) => multisig_withdraw_impl(&qtum, &req).await,
+        _ => MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+}
+
+/// Signs the inputs of the PSBT spending the outputs of the multisig wallets my key is a co-signer of.
+pub async fn sign_multisig_psbt(ctx: MmArc, req: SignMultisigPsbtRequest) -> MultisigResult<MultisigPsbtResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => sign_multisig_psbt_impl(&utxo, &req.psbt),
+        MmCoinEnum::QtumCoin(qtum) => sign_multisig_psbt_impl(&qtum, &req.psbt),
+        _ => MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+}
+
+/// Combines the signatures of the PSBTs signed by the different co-signers into one PSBT.
+pub async fn combine_psbt(ctx: MmArc, req: CombinePsbtRequest) -> MultisigResult<MultisigPsbtResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(_) | MmCoinEnum::QtumCoin(_) => (),
+        _ => return MmError::err(MultisigError::CoinDoesntSupportMultisig { coin: req.coin }),
+    }
+
+    let mut psbts = req.psbts.iter().map(|psbt| decode_psbt(psbt));
+    let mut combined = match psbts.next() {
+        Some(psbt) => psbt?,
+        None => return MmError::err(MultisigError::InvalidPsbt("No PSBT to combine".to_owned())),
+    };
+    for psbt in psbts {
+        combined.combine(psbt?)?;
+    }
+    Ok(psbt_response(&combined, 0))
+}
+
+async fn multisig_address_impl<T>(coin: &T, req: &MultisigAddressRequest) -> MultisigResult<MultisigAddressResponse>
+where
+    T: UtxoCommonOps,
+{
+    let wallet = multisig_wallet(coin, &req.wallet)?;
+    let address = wallet.address_string()?;
+    if let UtxoRpcClientEnum::Native(ref native) = coin.as_ref().rpc_client {
+        native
+            .import_address(&address, &address, req.rescan)
+            .compat()
+            .await
+            .map_to_mm(UtxoRpcError::from)?;
+    }
+
+    Ok(MultisigAddressResponse {
+        address,
+        script_type: wallet.script_type,
+        required: wallet.required,
+        pubkeys: wallet.pubkeys.iter().map(|pubkey| pubkey.to_string()).collect(),
+        script: wallet.script.to_bytes().into(),
+    })
+}
+
+pub(crate) async fn multisig_balance_impl<T>(
+    coin: &T,
+    params: &MultisigWalletParams,
+) -> MultisigResult<MultisigBalanceResponse>
+where
+    T: UtxoCommonOps,
+{
+    let wallet = multisig_wallet(coin, params)?;
+    let unspents = multisig_unspents(coin, &wallet).await?;
+    let balance = unspents.iter().fold(0, |sum, unspent| sum + unspent.value);
+    Ok(MultisigBalanceResponse {
+        address: wallet.address_string()?,
+        balance: big_decimal_from_sat_unsigned(balance, coin.as_ref().decimals),
+        unspents_count: unspents.len(),
+    })
+}
+
+async fn multisig_history_impl<T>(coin: &T, params: &MultisigWalletParams) -> MultisigResult<MultisigHistoryResponse>
+where
+    T: UtxoCommonOps,
+{
+    let wallet = multisig_wallet(coin, params)?;
+    let address = wallet.address_string()?;
+
+    let transactions = match coin.as_ref().rpc_client {
+        UtxoRpcClientEnum::Electrum(ref electrum) => {
+            let script_hash = hex::encode(electrum_script_hash(&wallet.script_pubkey));
+            let history = electrum
+                .scripthash_get_history(&script_hash)
+                .compat()
+                .await
+                .map_to_mm(UtxoRpcError::from)?;
+            history
+                .into_iter()
+                .map(|item| MultisigHistoryItem {
+                    tx_hash: item.tx_hash,
+                    // Electrum returns 0 or -1 height for the mempool transactions.
+                    height: if item.height > 0 {
+                        Some(item.height as u64)
+                    } else {
+                        None
+                    },
+                })
+                .collect()
+        },
+        UtxoRpcClientEnum::Native(ref native) => {
+            ensure_address_imported(native, &address).await?;
+            let block_count = native.get_block_count().compat().await?;
+            let transactions = native
+                .list_all_transactions(LIST_TRANSACTIONS_STEP)
+                .compat()
+                .await
+                .map_to_mm(UtxoRpcError::from)?;
+            // A transaction is listed once per every output or input of the address.
+            let mut known = HashSet::new();
+            transactions
+                .into_iter()
+                .filter(|item| item.address == address && !item.is_conflicting() && known.insert(item.txid))
+                .map(|item| MultisigHistoryItem {
+                    tx_hash: item.txid,
+                    height: if item.confirmations > 0 {
+                        Some((block_count + 1).saturating_sub(item.confirmations as u64))
+                    } else {
+                        None
+                    },
+                })
+                .collect()
+        },
+    };
+    Ok(MultisigHistoryResponse { address, transactions })
+}
+
+async fn multisig_withdraw_impl<T>(coin: &T, req: &MultisigWithdrawRequest) -> MultisigResult<MultisigWithdrawResponse>
+where
+    T: UtxoCommonOps,
+{
+    let decimals = coin.as_ref().decimals;
+    let wallet = multisig_wallet(coin, &req.wallet)?;
+
+    let to = coin
+        .address_from_str(&req.to)
+        .mm_err(|e| MultisigError::InvalidAddress(e.to_string()))?;
+    let conf = &coin.as_ref().conf;
+    let is_p2sh = to.prefix == conf.p2sh_addr_prefix && to.t_addr_prefix == conf.p2sh_t_addr_prefix;
+    let to_script = output_script(&to, if is_p2sh { ScriptType::P2SH } else { ScriptType::P2PKH });
+
+    // `UTXO_LOCK` isn't taken: the PSBT is broadcasted later by `broadcast_psbt` after the cosigners sign it,
+    // and the multisig wallet unspents are never spent by the coin itself.
+    let unspents = multisig_unspents(coin, &wallet).await?;
+    let (value, fee_policy) = if req.max {
+        let available = unspents.iter().fold(0, |sum, unspent| sum + unspent.value);
+        (available, FeePolicy::DeductFromOutput(0))
+    } else {
+        (sat_from_big_decimal(&req.amount, decimals)?, FeePolicy::SendExact)
+    };
+
+    let mut tx_builder = UtxoTxBuilder::new(coin)
+        .with_from_address(wallet.address.clone())
+        .with_change_script(wallet.script_pubkey.clone())
+        .with_input_unlocking_size(wallet.input_unlocking_size())
+        .add_available_inputs(unspents)
+        .add_outputs(vec![TransactionOutput {
+            value,
+            script_pubkey: to_script.to_bytes(),
+        }])
+        .with_fee_policy(fee_policy);
+    match req.fee {
+        Some(WithdrawFee::UtxoFixed { ref amount }) => {
+            let fixed = sat_from_big_decimal(amount, decimals)?;
+            tx_builder = tx_builder.with_fee(ActualTxFee::FixedPerKb(fixed));
+        },
+        Some(WithdrawFee::UtxoPerKbyte { ref amount }) => {
+            let dynamic = sat_from_big_decimal(amount, decimals)?;
+            tx_builder = tx_builder.with_fee(ActualTxFee::Dynamic(dynamic));
+        },
+        Some(ref fee_policy) => {
+            let error = format!(
+                "Expected 'UtxoFixed' or 'UtxoPerKbyte' fee types, found {:?}",
+                fee_policy
+            );
+            return MmError::err(MultisigError::InvalidFeePolicy(error));
+        },
+        None => (),
+    }
+    let (unsigned, data) = tx_builder.build().await?;
+
+    let mut psbt = Psbt::from_unsigned(&unsigned)?;
+    for (psbt_input, input) in psbt.inputs.iter_mut().zip(unsigned.inputs.iter()) {
+        match wallet.script_type {
+            MultisigScriptType::P2sh => {
+                psbt_input.non_witness_utxo = Some(get_prev_tx(coin, &input.previous_output.hash).await?);
+                psbt_input.redeem_script = Some(wallet.script.to_bytes());
+            },
+            MultisigScriptType::P2wsh => {
+                psbt_input.witness_utxo = Some(TransactionOutput {
+                    value: input.amount,
+                    script_pubkey: wallet.script_pubkey.to_bytes(),
+                });
+                psbt_input.witness_script = Some(wallet.script.to_bytes());
+            },
+        }
+    }
+    let signed_inputs = sign_multisig_inputs(coin, &mut psbt)?;
+    let response = psbt_response(&psbt, signed_inputs);
+
+    let fee_amount = data.fee_amount + data.unused_change.unwrap_or_default();
+    Ok(MultisigWithdrawResponse {
+        psbt: response.psbt,
+        signed_inputs,
+        complete: response.complete,
+        fee: big_decimal_from_sat_unsigned(fee_amount, decimals),
+        spent_by_wallet: big_decimal_from_sat_unsigned(data.spent_by_me, decimals),
+        received_by_wallet: big_decimal_from_sat_unsigned(data.received_by_me, decimals),
+    })
+}
+
+fn sign_multisig_psbt_impl<T>(coin: &T, psbt: &str) -> MultisigResult<MultisigPsbtResponse>
+where
+    T: UtxoCommonOps,
+{
+    let mut psbt = decode_psbt(psbt)?;
+    let signed_inputs = sign_multisig_inputs(coin, &mut psbt)?;
+    Ok(psbt_response(&psbt, signed_inputs))
+}
+
+/// Signs the multisig inputs of the `psbt` if the coin is activated with a private key.
+fn sign_multisig_inputs<T>(coin: &T, psbt: &mut Psbt) -> MultisigResult<usize>
+where
+    T: UtxoCommonOps,
+{
+    let conf = &coin.as_ref().conf;
+    match coin.as_ref().priv_key_policy {
+        PrivKeyPolicy::KeyPair(ref key_pair) => {
+            Ok(psbt.sign_multisig_inputs(key_pair, conf.signature_version, conf.fork_id)?)
+        },
+        PrivKeyPolicy::Trezor | PrivKeyPolicy::WatchOnly => Ok(0),
+    }
+}
+
+fn multisig_wallet<T>(coin: &T, params: &MultisigWalletParams) -> MultisigResult<MultisigWallet>
+where
+    T: UtxoCommonOps,
+{
+    let conf = &coin.as_ref().conf;
+    if params.keys.len() > MAX_MULTISIG_KEYS {
+        let error = format!(
+            "Expected at most {} keys, found {}",
+            MAX_MULTISIG_KEYS,
+            params.keys.len()
+        );
+        return MmError::err(MultisigError::InvalidMultisigWallet(error));
+    }
+    if params.required == 0 || params.required > params.keys.len() {
+        let error = format!(
+            "The number of the required signatures {} must be in the range [1; {}]",
+            params.required,
+            params.keys.len()
+        );
+        return MmError::err(MultisigError::InvalidMultisigWallet(error));
+    }
+
+    let mut pubkeys = params
+        .keys
+        .iter()
+        .map(|key| cosigner_pubkey(key, params.address_id))
+        .collect::<MultisigResult<Vec<_>>>()?;
+    if params.sort_keys {
+        pubkeys.sort_by(|a, b| a[..].cmp(&b[..]));
+    }
+    let unique: HashSet<_> = pubkeys.iter().map(|pubkey| pubkey.to_vec()).collect();
+    if unique.len() != pubkeys.len() {
+        let error = "The keys must be unique".to_owned();
+        return MmError::err(MultisigError::InvalidMultisigWallet(error));
+    }
+
+    let script = Builder::build_multisig(params.required, &pubkeys)
+        .map_to_mm(|e| MultisigError::InvalidMultisigWallet(e.to_string()))?;
+    let address = match params.script_type {
+        MultisigScriptType::P2sh => Address {
+            prefix: conf.p2sh_addr_prefix,
+            t_addr_prefix: conf.p2sh_t_addr_prefix,
+            hash: AddressHashEnum::AddressHash(dhash160(&script)),
+            checksum_type: conf.checksum_type,
+            hrp: conf.bech32_hrp.clone(),
+            addr_format: coin.addr_format_for_standard_scripts(),
+        },
+        MultisigScriptType::P2wsh => {
+            if !conf.segwit || conf.bech32_hrp.is_none() {
+                let error = format!("'{}' coin doesn't support segwit", conf.ticker);
+                return MmError::err(MultisigError::InvalidMultisigWallet(error));
+            }
+            Address {
+                prefix: conf.pub_addr_prefix,
+                t_addr_prefix: conf.pub_t_addr_prefix,
+                hash: AddressHashEnum::WitnessScriptHash(sha256(&script)),
+                checksum_type: conf.checksum_type,
+                hrp: conf.bech32_hrp.clone(),
+                addr_format: UtxoAddressFormat::Segwit,
+            }
+        },
+    };
+
+    Ok(MultisigWallet {
+        required: params.required,
+        pubkeys,
+        script_type: params.script_type,
+        script_pubkey: multisig_script_pubkey(&script, params.script_type.signature_version()),
+        script,
+        address,
+    })
+}
+
+/// Parses either a hex encoded public key or an account extended public key.
+/// The key of the `address_id` external address is derived from the latter.
+fn cosigner_pubkey(key: &str, address_id: u32) -> MultisigResult<Public> {
+    let invalid_key = |reason: String| MultisigError::InvalidKey {
+        key: key.to_owned(),
+        reason,
+    };
+
+    let pubkey = match hex::decode(key) {
+        Ok(bytes) => Public::from_slice(&bytes).map_to_mm(|e| invalid_key(e.to_string()))?,
+        Err(_) => {
+            let derived = Secp256k1ExtendedPublicKey::from_str(key)
+                .and_then(|xpub| xpub.derive_child(Bip44Chain::External.to_child_number()))
+                .and_then(|xpub| xpub.derive_child(ChildNumber::from(address_id)))
+                .map_to_mm(|e| invalid_key(e.to_string()))?;
+            Public::Compressed(H264::from(derived.public_key().serialize()))
+        },
+    };
+    if !matches!(pubkey, Public::Compressed(_)) {
+        return MmError::err(invalid_key("Only compressed public keys are supported".to_owned()));
+    }
+    Ok(pubkey)
+}
+
+/// Returns the unspent outputs of the multisig wallet sorted by the value in ascending order.
+async fn multisig_unspents<T>(coin: &T, wallet: &MultisigWallet) -> MultisigResult<Vec<UnspentInfo>>
+where
+    T: UtxoCommonOps,
+{
+    let mut unspents: Vec<UnspentInfo> = match coin.as_ref().rpc_client {
+        // The script of a P2SH address can't be derived by `list_unspent`, so the script hash is requested directly.
+        UtxoRpcClientEnum::Electrum(ref electrum) => {
+            let script_hash = hex::encode(electrum_script_hash(&wallet.script_pubkey));
+            electrum
+                .scripthash_list_unspent(&script_hash)
+                .compat()
+                .await
+                .map_to_mm(UtxoRpcError::from)?
+                .into_iter()
+                .map(UnspentInfo::from)
+                .collect()
+        },
+        UtxoRpcClientEnum::Native(ref native) => {
+            ensure_address_imported(native, &wallet.address_string()?).await?;
+            native
+                .list_unspent(&wallet.address, coin.as_ref().decimals)
+                .compat()
+                .await?
+        },
+    };
+    unspents.sort_by(|a, b| a.value.cmp(&b.value));
+    Ok(unspents)
+}
+
+/// A native node knows nothing about the multisig address until it's imported by `multisig_address`,
+/// so the address would look empty otherwise.
+async fn ensure_address_imported(native: &NativeClient, address: &str) -> MultisigResult<()> {
+    let imported = native
+        .is_address_imported(address)
+        .await
+        .map_to_mm(MultisigError::Transport)?;
+    if !imported {
+        return MmError::err(MultisigError::AddressNotImported {
+            address: address.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn decode_psbt(psbt: &str) -> MultisigResult<Psbt> {
+    let psbt_bytes = base64::decode(psbt).map_to_mm(|e| MultisigError::InvalidPsbt(e.to_string()))?;
+    Ok(Psbt::deserialize(&psbt_bytes)?)
+}
+
+fn psbt_response(psbt: &Psbt, signed_inputs: usize) -> MultisigPsbtResponse {
+    MultisigPsbtResponse {
+        psbt: base64::encode(&psbt.serialize()),
+        signed_inputs,
+        complete: psbt.clone().finalize().is_ok(),
+    }
+}
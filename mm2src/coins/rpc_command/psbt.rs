@@ -0,0 +1,110 @@
+use crate::utxo::rpc_clients::UtxoRpcClientOps;
+use crate::utxo::UtxoCommonOps;
+use crate::{lp_coinfind_or_err, CoinFindError, MmCoinEnum};
+use common::{HttpStatusCode, StatusCode};
+use derive_more::Display;
+use futures::compat::Future01CompatExt;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use rpc::v1::types::{Bytes as BytesJson, H256 as H256Json};
+use serialization::{serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use utxo_signer::psbt::{Psbt, PsbtError};
+
+pub type PsbtRpcResult<T> = Result<T, MmError<PsbtRpcError>>;
+
+#[derive(Debug, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum PsbtRpcError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "'{}' coin doesn't support PSBT", coin)]
+    CoinDoesntSupportPsbt { coin: String },
+    #[display(fmt = "Invalid PSBT: {}", _0)]
+    InvalidPsbt(String),
+    #[display(fmt = "PSBT can't be finalized: {}", _0)]
+    NotFinalized(String),
+    #[display(fmt = "Transport error: {}", _0)]
+    Transport(String),
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for PsbtRpcError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PsbtRpcError::NoSuchCoin { .. } => StatusCode::NOT_FOUND,
+            PsbtRpcError::CoinDoesntSupportPsbt { .. }
+            | PsbtRpcError::InvalidPsbt(_)
+            | PsbtRpcError::NotFinalized(_) => StatusCode::BAD_REQUEST,
+            PsbtRpcError::Transport(_) | PsbtRpcError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<CoinFindError> for PsbtRpcError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => PsbtRpcError::NoSuchCoin { coin },
+        }
+    }
+}
+
+impl_from_utxo_rpc_error!(PsbtRpcError);
+
+impl From<PsbtError> for PsbtRpcError {
+    fn from(e: PsbtError) -> Self {
+        let error = e.to_string();
+        match e {
+            PsbtError::UnsupportedTxFormat | PsbtError::InvalidPsbt(_) => PsbtRpcError::InvalidPsbt(error),
+            PsbtError::UnknownSpentOutput { .. }
+            | PsbtError::UnsupportedScript { .. }
+            | PsbtError::InputNotSigned { .. }
+            | PsbtError::NotEnoughSignatures { .. }
+            | PsbtError::InputNotFinalized { .. } => PsbtRpcError::NotFinalized(error),
+            PsbtError::ErrorSigning(_) => PsbtRpcError::InternalError(error),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BroadcastPsbtRequest {
+    coin: String,
+    /// The base64 encoded PSBT signed by an external signer.
+    psbt: String,
+}
+
+#[derive(Serialize)]
+pub struct BroadcastPsbtResponse {
+    tx_hash: H256Json,
+    tx_hex: BytesJson,
+}
+
+/// Finalizes the PSBT signed elsewhere (e.g. by an offline signer), extracts the transaction and broadcasts it.
+/// The PSBT is usually generated by `withdraw` with the `psbt` flag or by `multisig_withdraw`.
+pub async fn broadcast_psbt(ctx: MmArc, req: BroadcastPsbtRequest) -> PsbtRpcResult<BroadcastPsbtResponse> {
+    match lp_coinfind_or_err(&ctx, &req.coin).await? {
+        MmCoinEnum::UtxoCoin(utxo) => broadcast_psbt_impl(&utxo, &req.psbt).await,
+        MmCoinEnum::QtumCoin(qtum) => broadcast_psbt_impl(&qtum, &req.psbt).await,
+        _ => MmError::err(PsbtRpcError::CoinDoesntSupportPsbt { coin: req.coin }),
+    }
+}
+
+async fn broadcast_psbt_impl<T>(coin: &T, psbt_base64: &str) -> PsbtRpcResult<BroadcastPsbtResponse>
+where
+    T: UtxoCommonOps,
+{
+    let psbt_bytes = base64::decode(psbt_base64).map_to_mm(|e| PsbtRpcError::InvalidPsbt(e.to_string()))?;
+    let mut psbt = Psbt::deserialize(&psbt_bytes)?;
+    psbt.finalize()?;
+    let mut tx = psbt.extract_tx()?;
+    tx.tx_hash_algo = coin.as_ref().tx_hash_algo;
+
+    let tx_hex: BytesJson = serialize_with_flags(&tx, SERIALIZE_TRANSACTION_WITNESS).into();
+    let tx_hash = coin
+        .as_ref()
+        .rpc_client
+        .send_raw_transaction(tx_hex.clone())
+        .compat()
+        .await?;
+    Ok(BroadcastPsbtResponse { tx_hash, tx_hex })
+}
@@ -0,0 +1,1136 @@
+//! A stand-in coin used to run swaps in the dry-run (simulation) mode.
+//!
+//! The coin doesn't talk to any blockchain. Every transaction is settled instantly by writing it into
+//! [`SimulatedLedger`], a directory shared by all local nodes that have activated the coin with the same `ledger_path`
+//! (a directory in the system temp folder by default). It allows to run the full `run_maker_swap`/`run_taker_swap`
+//! state machines between two local nodes without docker chains or real funds, e.g.
+//!
+//! ```json
+//! {"coin":"SIMA","protocol":{"type":"SIMULATION"},"simulated_balance":"1000","simulated_tx_fee":"0.00001"}
+//! ```
+
+use crate::coin_errors::{MyAddressError, ValidatePaymentError};
+use crate::{BalanceError, BalanceFut, CanRefundHtlc, CheckIfMyPaymentSentArgs, CoinBalance, CoinFutSpawner,
+            ConfirmPaymentInput, FeeApproxStage, FoundSwapTxSpend, HistorySyncState, MakerSwapTakerCoin,
+            MarketCoinOps, MmCoin, MmCoinEnum, NegotiateSwapContractAddrErr, PaymentInstructionArgs,
+            PaymentInstructions, PaymentInstructionsErr, RawTransactionError, RawTransactionFut,
+            RawTransactionRequest, RawTransactionRes, RefundPaymentArgs, RefundResult, SearchForSwapTxSpendInput,
+            SendMakerPaymentSpendPreimageInput, SendPaymentArgs, SignatureError, SignatureResult, SpendPaymentArgs,
+            SwapOps, TakerSwapMakerCoin, TradeFee, TradePreimageFut, TradePreimageResult, TradePreimageValue,
+            Transaction, TransactionDetails, TransactionEnum, TransactionErr, TransactionFut, TransactionType,
+            TxFeeDetails, TxMarshalingErr, UnexpectedDerivationMethod, UtxoFeeDetails, ValidateAddressResult,
+            ValidateFeeArgs, ValidateInstructionsErr, ValidateOtherPubKeyErr, ValidatePaymentFut,
+            ValidatePaymentInput, VerificationError, VerificationResult, WaitForHTLCTxSpendArgs, WatcherOps,
+            WatcherReward, WatcherRewardError, WatcherSearchForSwapTxSpendInput, WatcherValidatePaymentInput,
+            WatcherValidateTakerFeeInput, WithdrawError, WithdrawFut, WithdrawRequest};
+use async_trait::async_trait;
+use bitcrypto::{dhash160, sha256};
+use common::executor::{abortable_queue::AbortableQueue, AbortableSystem, AbortedError, Timer};
+use common::log::warn;
+use common::now_sec;
+use futures::{FutureExt, TryFutureExt};
+use futures01::Future;
+use keys::KeyPair;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::{BigDecimal, MmNumber};
+use rpc::v1::types::{Bytes as BytesJson, H256 as H256Json};
+use serde_json::{self as json, Value as Json};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+const DEFAULT_SIMULATED_BALANCE: &str = "1000";
+const DEFAULT_SIMULATED_TX_FEE: &str = "0.00001";
+const SIMULATED_COIN_DECIMALS: u8 = 8;
+const LEDGER_TXS_DIR: &str = "txs";
+const LEDGER_SPENDS_DIR: &str = "spends";
+
+/// The HTLC the payment transaction locks the coins in.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SimulatedHtlc {
+    pub time_lock: u32,
+    pub secret_hash: BytesJson,
+    pub sender_pubkey: BytesJson,
+    pub receiver_pubkey: BytesJson,
+}
+
+/// The reference to the HTLC payment spent by the transaction.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SimulatedHtlcSpend {
+    pub payment_tx_hash: H256Json,
+    /// The secret revealed by the receiver, `None` if the payment is refunded by the sender.
+    pub secret: Option<BytesJson>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SimulatedTx {
+    pub coin: String,
+    /// The sender address, `None` if the coins are released from the HTLC.
+    pub from: Option<String>,
+    /// The receiver address, `None` if the coins are locked in the HTLC.
+    pub to: Option<String>,
+    pub amount: BigDecimal,
+    pub fee: BigDecimal,
+    /// The address that pays the fee.
+    pub fee_payer: String,
+    pub htlc: Option<SimulatedHtlc>,
+    pub htlc_spend: Option<SimulatedHtlcSpend>,
+    pub memo: Option<BytesJson>,
+    pub created_at: u64,
+    /// Makes the hashes of the identical transactions different.
+    pub nonce: u64,
+}
+
+impl SimulatedTx {
+    fn from_bytes(bytes: &[u8]) -> Result<SimulatedTx, String> {
+        json::from_slice(bytes).map_err(|e| ERRL!("Error deserializing simulated tx: {}", e))
+    }
+
+    fn hash(&self) -> H256Json { sha256(&self.tx_hex()).take().into() }
+}
+
+impl Transaction for SimulatedTx {
+    fn tx_hex(&self) -> Vec<u8> { json::to_vec(self).expect("Serialization should not fail") }
+
+    fn tx_hash(&self) -> BytesJson { self.hash().0.to_vec().into() }
+}
+
+/// The instant settlement layer shared by the local nodes.
+/// Every transaction is stored as `txs/<tx_hash>` file,
+/// every spent HTLC payment is marked by `spends/<payment_tx_hash>` file containing the spending tx hash.
+#[derive(Debug)]
+pub struct SimulatedLedger {
+    path: PathBuf,
+}
+
+impl SimulatedLedger {
+    fn new(path: PathBuf) -> Result<SimulatedLedger, String> {
+        try_s!(fs::create_dir_all(path.join(LEDGER_TXS_DIR)));
+        try_s!(fs::create_dir_all(path.join(LEDGER_SPENDS_DIR)));
+        Ok(SimulatedLedger { path })
+    }
+
+    fn tx_path(&self, tx_hash: &H256Json) -> PathBuf { self.path.join(LEDGER_TXS_DIR).join(format!("{:02x}", tx_hash)) }
+
+    fn spend_path(&self, payment_tx_hash: &H256Json) -> PathBuf {
+        self.path
+            .join(LEDGER_SPENDS_DIR)
+            .join(format!("{:02x}", payment_tx_hash))
+    }
+
+    /// Settles the transaction. Fails if the transaction spends an HTLC payment that is spent already.
+    fn submit(&self, tx: &SimulatedTx) -> Result<H256Json, String> {
+        let tx_hash = tx.hash();
+        let tx_path = self.tx_path(&tx_hash);
+        if let Some(ref spend) = tx.htlc_spend {
+            if self.get_tx(&spend.payment_tx_hash)?.is_none() {
+                return ERR!("HTLC payment {:02x} is not found", spend.payment_tx_hash);
+            }
+        }
+        // Write to a temporary file first so other nodes never read a partially written tx.
+        let tmp_path = tx_path.with_extension("tmp");
+        try_s!(fs::write(&tmp_path, tx.tx_hex()));
+        if let Some(ref spend) = tx.htlc_spend {
+            // `create_new` guarantees that only one node can spend the payment
+            let spend_marker = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(self.spend_path(&spend.payment_tx_hash));
+            let mut file = match spend_marker {
+                Ok(file) => file,
+                Err(e) => {
+                    fs::remove_file(&tmp_path).ok();
+                    if e.kind() == ErrorKind::AlreadyExists {
+                        return ERR!("HTLC payment {:02x} is spent already", spend.payment_tx_hash);
+                    }
+                    return ERR!("{}", e);
+                },
+            };
+            try_s!(fs::rename(&tmp_path, &tx_path));
+            try_s!(file.write_all(format!("{:02x}", tx_hash).as_bytes()));
+        } else {
+            try_s!(fs::rename(&tmp_path, &tx_path));
+        }
+        Ok(tx_hash)
+    }
+
+    fn get_tx(&self, tx_hash: &H256Json) -> Result<Option<SimulatedTx>, String> {
+        match fs::read(self.tx_path(tx_hash)) {
+            Ok(bytes) => SimulatedTx::from_bytes(&bytes).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => ERR!("{}", e),
+        }
+    }
+
+    fn find_spend(&self, payment_tx_hash: &H256Json) -> Result<Option<SimulatedTx>, String> {
+        let spend_tx_hash = match fs::read_to_string(self.spend_path(payment_tx_hash)) {
+            // the marker is created before the spending tx hash is written into it
+            Ok(hash) if hash.is_empty() => return Ok(None),
+            Ok(hash) => try_s!(H256Json::from_str(&hash)),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return ERR!("{}", e),
+        };
+        self.get_tx(&spend_tx_hash)
+    }
+
+    /// Returns the paths of the settled transactions skipping the ones being written at the moment.
+    fn tx_paths(&self) -> Result<Vec<PathBuf>, String> {
+        let mut paths = Vec::new();
+        for entry in try_s!(fs::read_dir(self.path.join(LEDGER_TXS_DIR))) {
+            let path = try_s!(entry).path();
+            if path.extension().is_none() {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn all_txs(&self, coin: &str) -> Result<Vec<SimulatedTx>, String> {
+        let mut txs = Vec::new();
+        for path in self.tx_paths()? {
+            let tx = SimulatedTx::from_bytes(&try_s!(fs::read(path)))?;
+            if tx.coin == coin {
+                txs.push(tx);
+            }
+        }
+        Ok(txs)
+    }
+
+    /// The number of settled transactions is used as the block height since every transaction is mined instantly.
+    fn height(&self) -> Result<u64, String> { Ok(self.tx_paths()?.len() as u64) }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulatedCoin(Arc<SimulatedCoinImpl>);
+
+impl Deref for SimulatedCoin {
+    type Target = SimulatedCoinImpl;
+
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+#[derive(Debug)]
+pub struct SimulatedCoinImpl {
+    ticker: String,
+    key_pair: KeyPair,
+    my_address: String,
+    initial_balance: BigDecimal,
+    tx_fee: BigDecimal,
+    required_confirmations: AtomicU64,
+    requires_notarization: AtomicBool,
+    ledger: SimulatedLedger,
+    nonce: AtomicU64,
+    abortable_system: AbortableQueue,
+}
+
+/// The simulated address is the hex-encoded compressed public key.
+fn address_from_pubkey(pubkey: &[u8]) -> String { hex::encode(pubkey) }
+
+fn hash_secret(secret: &[u8], secret_hash_len: usize) -> Vec<u8> {
+    match secret_hash_len {
+        20 => dhash160(secret).take().to_vec(),
+        _ => sha256(secret).take().to_vec(),
+    }
+}
+
+pub fn simulated_coin_from_conf(
+    ctx: &MmArc,
+    ticker: &str,
+    conf: &Json,
+    key_pair: KeyPair,
+) -> Result<SimulatedCoin, String> {
+    let parse_amount = |field: &str, default: &str| -> Result<BigDecimal, String> {
+        let amount = conf[field].as_str().unwrap_or(default);
+        BigDecimal::from_str(amount).map_err(|e| ERRL!("Invalid '{}': {}", field, e))
+    };
+    let initial_balance = parse_amount("simulated_balance", DEFAULT_SIMULATED_BALANCE)?;
+    let tx_fee = parse_amount("simulated_tx_fee", DEFAULT_SIMULATED_TX_FEE)?;
+    let ledger_path = match conf["ledger_path"].as_str() {
+        Some(path) => PathBuf::from(path),
+        None => std::env::temp_dir().join("mm2_simulation"),
+    };
+    warn!(
+        "{} is a simulated coin, its transactions are settled in {}",
+        ticker,
+        ledger_path.display()
+    );
+
+    let my_address = address_from_pubkey(&**key_pair.public());
+    let abortable_system: AbortableQueue = try_s!(ctx.abortable_system.create_subsystem());
+    Ok(SimulatedCoin(Arc::new(SimulatedCoinImpl {
+        ticker: ticker.to_owned(),
+        key_pair,
+        my_address,
+        initial_balance,
+        tx_fee,
+        required_confirmations: AtomicU64::new(conf["required_confirmations"].as_u64().unwrap_or(1)),
+        requires_notarization: AtomicBool::new(false),
+        ledger: try_s!(SimulatedLedger::new(ledger_path)),
+        nonce: AtomicU64::new(0),
+        abortable_system,
+    })))
+}
+
+impl SimulatedCoin {
+    fn my_pubkey(&self) -> Vec<u8> { self.key_pair.public().to_vec() }
+
+    fn watchers_not_supported(&self) -> String { format!("Watchers are not supported by {}", self.ticker) }
+
+    fn new_tx(&self, from: Option<String>, to: Option<String>, amount: BigDecimal) -> SimulatedTx {
+        SimulatedTx {
+            coin: self.ticker.clone(),
+            from,
+            to,
+            amount,
+            fee: self.tx_fee.clone(),
+            fee_payer: self.my_address.clone(),
+            htlc: None,
+            htlc_spend: None,
+            memo: None,
+            created_at: now_sec(),
+            nonce: self.nonce.fetch_add(1, AtomicOrdering::Relaxed),
+        }
+    }
+
+    fn balance(&self) -> Result<BigDecimal, String> {
+        let mut balance = self.initial_balance.clone();
+        for tx in self.ledger.all_txs(&self.ticker)? {
+            if tx.from.as_ref() == Some(&self.my_address) {
+                balance -= &tx.amount;
+            }
+            if tx.to.as_ref() == Some(&self.my_address) {
+                balance += &tx.amount;
+            }
+            if tx.fee_payer == self.my_address {
+                balance -= &tx.fee;
+            }
+        }
+        Ok(balance)
+    }
+
+    fn submit_tx(&self, tx: SimulatedTx) -> TransactionFut {
+        let required = &tx.amount + &tx.fee;
+        if tx.from.as_ref() == Some(&self.my_address) {
+            let balance = try_tx_fus!(self.balance());
+            if balance < required {
+                return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+                    "Not sufficient balance {}, required {}",
+                    balance,
+                    required
+                ))));
+            }
+        }
+        try_tx_fus!(self.ledger.submit(&tx));
+        Box::new(futures01::future::ok(TransactionEnum::SimulatedTx(tx)))
+    }
+
+    fn send_htlc_payment(&self, args: SendPaymentArgs) -> TransactionFut {
+        let mut tx = self.new_tx(Some(self.my_address.clone()), None, args.amount);
+        tx.htlc = Some(SimulatedHtlc {
+            time_lock: args.time_lock,
+            secret_hash: args.secret_hash.into(),
+            sender_pubkey: self.my_pubkey().into(),
+            receiver_pubkey: args.other_pubkey.into(),
+        });
+        self.submit_tx(tx)
+    }
+
+    fn spend_htlc_payment(&self, args: SpendPaymentArgs) -> TransactionFut {
+        let payment = try_tx_fus!(SimulatedTx::from_bytes(args.other_payment_tx));
+        let htlc = try_tx_fus!(payment.htlc.as_ref().ok_or("The tx is not an HTLC payment"));
+        if htlc.receiver_pubkey.0 != self.my_pubkey() {
+            return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+                "HTLC payment can't be spent by {}",
+                self.my_address
+            ))));
+        }
+        if hash_secret(args.secret, htlc.secret_hash.len()) != htlc.secret_hash.0 {
+            return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+                "Invalid secret for the HTLC payment"
+            ))));
+        }
+        let mut tx = self.new_tx(None, Some(self.my_address.clone()), payment.amount.clone());
+        tx.htlc_spend = Some(SimulatedHtlcSpend {
+            payment_tx_hash: payment.hash(),
+            secret: Some(args.secret.into()),
+        });
+        self.submit_tx(tx)
+    }
+
+    fn refund_htlc_payment(&self, args: RefundPaymentArgs) -> TransactionFut {
+        let payment = try_tx_fus!(SimulatedTx::from_bytes(args.payment_tx));
+        let htlc = try_tx_fus!(payment.htlc.as_ref().ok_or("The tx is not an HTLC payment"));
+        if htlc.sender_pubkey.0 != self.my_pubkey() {
+            return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+                "HTLC payment can't be refunded by {}",
+                self.my_address
+            ))));
+        }
+        let now = now_sec();
+        if now < htlc.time_lock as u64 {
+            return Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+                "HTLC payment can't be refunded before {}, now {}",
+                htlc.time_lock,
+                now
+            ))));
+        }
+        let mut tx = self.new_tx(None, Some(self.my_address.clone()), payment.amount.clone());
+        tx.htlc_spend = Some(SimulatedHtlcSpend {
+            payment_tx_hash: payment.hash(),
+            secret: None,
+        });
+        self.submit_tx(tx)
+    }
+
+    fn validate_htlc_payment(&self, input: ValidatePaymentInput) -> ValidatePaymentFut<()> {
+        let validate = || -> MmResult<(), ValidatePaymentError> {
+            let payment =
+                SimulatedTx::from_bytes(&input.payment_tx).map_to_mm(ValidatePaymentError::TxDeserializationError)?;
+            let settled = self
+                .ledger
+                .get_tx(&payment.hash())
+                .map_to_mm(ValidatePaymentError::InternalError)?;
+            if settled.is_none() {
+                return MmError::err(ValidatePaymentError::TxDoesNotExist(format!("{:02x}", payment.hash())));
+            }
+            let htlc = payment
+                .htlc
+                .as_ref()
+                .or_mm_err(|| ValidatePaymentError::WrongPaymentTx("The tx is not an HTLC payment".to_owned()))?;
+            let expected = SimulatedHtlc {
+                time_lock: input.time_lock,
+                secret_hash: input.secret_hash.clone().into(),
+                sender_pubkey: input.other_pub.clone().into(),
+                receiver_pubkey: self.my_pubkey().into(),
+            };
+            if *htlc != expected {
+                return MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
+                    "Invalid HTLC {:?}, expected {:?}",
+                    htlc, expected
+                )));
+            }
+            if payment.amount != input.amount {
+                return MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
+                    "Invalid payment amount {}, expected {}",
+                    payment.amount, input.amount
+                )));
+            }
+            Ok(())
+        };
+        Box::new(futures01::future::result(validate()))
+    }
+
+    fn search_for_htlc_spend(&self, payment_tx: &[u8]) -> Result<Option<FoundSwapTxSpend>, String> {
+        let payment = SimulatedTx::from_bytes(payment_tx)?;
+        let spend = match self.ledger.find_spend(&payment.hash())? {
+            Some(spend) => spend,
+            None => return Ok(None),
+        };
+        let is_refund = spend.htlc_spend.as_ref().map_or(false, |spend| spend.secret.is_none());
+        let spend = TransactionEnum::SimulatedTx(spend);
+        if is_refund {
+            Ok(Some(FoundSwapTxSpend::Refunded(spend)))
+        } else {
+            Ok(Some(FoundSwapTxSpend::Spent(spend)))
+        }
+    }
+}
+
+#[async_trait]
+impl MarketCoinOps for SimulatedCoin {
+    fn ticker(&self) -> &str { &self.ticker }
+
+    fn my_address(&self) -> MmResult<String, MyAddressError> { Ok(self.my_address.clone()) }
+
+    fn get_public_key(&self) -> Result<String, MmError<UnexpectedDerivationMethod>> {
+        Ok(hex::encode(self.my_pubkey()))
+    }
+
+    fn sign_message_hash(&self, _message: &str) -> Option<[u8; 32]> { None }
+
+    fn sign_message(&self, _message: &str) -> SignatureResult<String> {
+        MmError::err(SignatureError::InvalidRequest(
+            "Message signing is not supported by simulated coins".to_owned(),
+        ))
+    }
+
+    fn verify_message(&self, _signature: &str, _message: &str, _address: &str) -> VerificationResult<bool> {
+        MmError::err(VerificationError::InvalidRequest(
+            "Message verification is not supported by simulated coins".to_owned(),
+        ))
+    }
+
+    fn my_balance(&self) -> BalanceFut<CoinBalance> {
+        let balance = self.balance().map(|spendable| CoinBalance {
+            spendable,
+            unspendable: BigDecimal::from(0),
+        });
+        Box::new(futures01::future::result(balance.map_to_mm(BalanceError::Internal)))
+    }
+
+    fn base_coin_balance(&self) -> BalanceFut<BigDecimal> {
+        Box::new(self.my_balance().map(|CoinBalance { spendable, .. }| spendable))
+    }
+
+    fn platform_ticker(&self) -> &str { &self.ticker }
+
+    fn send_raw_tx(&self, tx: &str) -> Box<dyn Future<Item = String, Error = String> + Send> {
+        let bytes = try_fus!(hex::decode(tx));
+        self.send_raw_tx_bytes(&bytes)
+    }
+
+    fn send_raw_tx_bytes(&self, tx: &[u8]) -> Box<dyn Future<Item = String, Error = String> + Send> {
+        let tx = try_fus!(SimulatedTx::from_bytes(tx));
+        let tx_hash = try_fus!(self.ledger.submit(&tx));
+        Box::new(futures01::future::ok(format!("{:02x}", tx_hash)))
+    }
+
+    fn wait_for_confirmations(&self, input: ConfirmPaymentInput) -> Box<dyn Future<Item = (), Error = String> + Send> {
+        let tx = try_fus!(SimulatedTx::from_bytes(&input.payment_tx));
+        // transactions are confirmed as soon as they are settled in the ledger
+        match try_fus!(self.ledger.get_tx(&tx.hash())) {
+            Some(_) => Box::new(futures01::future::ok(())),
+            None => Box::new(futures01::future::err(ERRL!(
+                "Transaction {:02x} is not found in the ledger",
+                tx.hash()
+            ))),
+        }
+    }
+
+    fn wait_for_htlc_tx_spend(&self, args: WaitForHTLCTxSpendArgs<'_>) -> TransactionFut {
+        let payment = try_tx_fus!(SimulatedTx::from_bytes(args.tx_bytes));
+        let payment_tx_hash = payment.hash();
+        let wait_until = args.wait_until;
+        let check_every = args.check_every;
+        let coin = self.clone();
+        let fut = async move {
+            loop {
+                if let Some(spend) = try_tx_s!(coin.ledger.find_spend(&payment_tx_hash)) {
+                    return Ok(TransactionEnum::SimulatedTx(spend));
+                }
+                if now_sec() > wait_until {
+                    return Err(TransactionErr::Plain(ERRL!(
+                        "Waited too long until {} for {:02x} to be spent",
+                        wait_until,
+                        payment_tx_hash
+                    )));
+                }
+                Timer::sleep(check_every).await;
+            }
+        };
+        Box::new(fut.boxed().compat())
+    }
+
+    fn tx_enum_from_bytes(&self, bytes: &[u8]) -> Result<TransactionEnum, MmError<TxMarshalingErr>> {
+        SimulatedTx::from_bytes(bytes)
+            .map(TransactionEnum::SimulatedTx)
+            .map_to_mm(TxMarshalingErr::InvalidInput)
+    }
+
+    fn current_block(&self) -> Box<dyn Future<Item = u64, Error = String> + Send> {
+        Box::new(futures01::future::result(self.ledger.height()))
+    }
+
+    fn display_priv_key(&self) -> Result<String, String> { Ok(hex::encode(&*self.key_pair.private().secret)) }
+
+    fn min_tx_amount(&self) -> BigDecimal { BigDecimal::from_str("0.00001").expect("Valid decimal") }
+
+    fn min_trading_vol(&self) -> MmNumber { MmNumber::from("0.0001") }
+}
+
+#[async_trait]
+impl SwapOps for SimulatedCoin {
+    fn send_taker_fee(&self, fee_addr: &[u8], amount: BigDecimal, uuid: &[u8]) -> TransactionFut {
+        let mut tx = self.new_tx(
+            Some(self.my_address.clone()),
+            Some(address_from_pubkey(fee_addr)),
+            amount,
+        );
+        tx.memo = Some(uuid.into());
+        self.submit_tx(tx)
+    }
+
+    fn send_maker_payment(&self, maker_payment_args: SendPaymentArgs) -> TransactionFut {
+        self.send_htlc_payment(maker_payment_args)
+    }
+
+    fn send_taker_payment(&self, taker_payment_args: SendPaymentArgs) -> TransactionFut {
+        self.send_htlc_payment(taker_payment_args)
+    }
+
+    fn send_maker_spends_taker_payment(&self, maker_spends_payment_args: SpendPaymentArgs) -> TransactionFut {
+        self.spend_htlc_payment(maker_spends_payment_args)
+    }
+
+    fn send_taker_spends_maker_payment(&self, taker_spends_payment_args: SpendPaymentArgs) -> TransactionFut {
+        self.spend_htlc_payment(taker_spends_payment_args)
+    }
+
+    fn send_taker_refunds_payment(&self, taker_refunds_payment_args: RefundPaymentArgs) -> TransactionFut {
+        self.refund_htlc_payment(taker_refunds_payment_args)
+    }
+
+    fn send_maker_refunds_payment(&self, maker_refunds_payment_args: RefundPaymentArgs) -> TransactionFut {
+        self.refund_htlc_payment(maker_refunds_payment_args)
+    }
+
+    fn validate_fee(&self, validate_fee_args: ValidateFeeArgs) -> ValidatePaymentFut<()> {
+        let tx = match validate_fee_args.fee_tx {
+            TransactionEnum::SimulatedTx(tx) => tx,
+            _ => {
+                return Box::new(futures01::future::err(
+                    ValidatePaymentError::WrongPaymentTx("The tx is not a simulated tx".to_owned()).into(),
+                ))
+            },
+        };
+        let expected_from = address_from_pubkey(validate_fee_args.expected_sender);
+        let expected_to = address_from_pubkey(validate_fee_args.fee_addr);
+        let expected_memo = BytesJson::from(validate_fee_args.uuid);
+        let result = if tx.from.as_ref() != Some(&expected_from) || tx.to.as_ref() != Some(&expected_to) {
+            MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
+                "Invalid dex fee sender {:?} or receiver {:?}",
+                tx.from, tx.to
+            )))
+        } else if &tx.amount < validate_fee_args.amount {
+            MmError::err(ValidatePaymentError::WrongPaymentTx(format!(
+                "Invalid dex fee amount {}, expected at least {}",
+                tx.amount, validate_fee_args.amount
+            )))
+        } else if tx.memo.as_ref() != Some(&expected_memo) {
+            MmError::err(ValidatePaymentError::WrongPaymentTx(
+                "Dex fee is sent for another swap".to_owned(),
+            ))
+        } else {
+            Ok(())
+        };
+        Box::new(futures01::future::result(result))
+    }
+
+    fn validate_maker_payment(&self, input: ValidatePaymentInput) -> ValidatePaymentFut<()> {
+        self.validate_htlc_payment(input)
+    }
+
+    fn validate_taker_payment(&self, input: ValidatePaymentInput) -> ValidatePaymentFut<()> {
+        self.validate_htlc_payment(input)
+    }
+
+    fn check_if_my_payment_sent(
+        &self,
+        if_my_payment_sent_args: CheckIfMyPaymentSentArgs,
+    ) -> Box<dyn Future<Item = Option<TransactionEnum>, Error = String> + Send> {
+        let expected = SimulatedHtlc {
+            time_lock: if_my_payment_sent_args.time_lock,
+            secret_hash: if_my_payment_sent_args.secret_hash.into(),
+            sender_pubkey: self.my_pubkey().into(),
+            receiver_pubkey: if_my_payment_sent_args.other_pub.into(),
+        };
+        let txs = try_fus!(self.ledger.all_txs(&self.ticker));
+        let payment = txs
+            .into_iter()
+            .find(|tx| tx.htlc.as_ref() == Some(&expected))
+            .map(TransactionEnum::SimulatedTx);
+        Box::new(futures01::future::ok(payment))
+    }
+
+    async fn search_for_swap_tx_spend_my(
+        &self,
+        input: SearchForSwapTxSpendInput<'_>,
+    ) -> Result<Option<FoundSwapTxSpend>, String> {
+        self.search_for_htlc_spend(input.tx)
+    }
+
+    async fn search_for_swap_tx_spend_other(
+        &self,
+        input: SearchForSwapTxSpendInput<'_>,
+    ) -> Result<Option<FoundSwapTxSpend>, String> {
+        self.search_for_htlc_spend(input.tx)
+    }
+
+    fn check_tx_signed_by_pub(&self, tx: &[u8], expected_pub: &[u8]) -> Result<bool, MmError<ValidatePaymentError>> {
+        let tx = SimulatedTx::from_bytes(tx).map_to_mm(ValidatePaymentError::TxDeserializationError)?;
+        Ok(tx.fee_payer == address_from_pubkey(expected_pub))
+    }
+
+    async fn extract_secret(
+        &self,
+        secret_hash: &[u8],
+        spend_tx: &[u8],
+        _watcher_reward: bool,
+    ) -> Result<Vec<u8>, String> {
+        let spend_tx = SimulatedTx::from_bytes(spend_tx)?;
+        let secret = match spend_tx.htlc_spend.and_then(|spend| spend.secret) {
+            Some(secret) => secret.0,
+            None => return ERR!("The tx doesn't reveal the secret"),
+        };
+        if hash_secret(&secret, secret_hash.len()) != secret_hash {
+            return ERR!("The revealed secret doesn't match the secret hash");
+        }
+        Ok(secret)
+    }
+
+    fn is_auto_refundable(&self) -> bool { false }
+
+    async fn wait_for_htlc_refund(&self, _tx: &[u8], _locktime: u64) -> RefundResult<()> {
+        MmError::err(crate::RefundError::Internal(
+            "wait_for_htlc_refund is not supported for simulated coins".to_owned(),
+        ))
+    }
+
+    fn negotiate_swap_contract_addr(
+        &self,
+        _other_side_address: Option<&[u8]>,
+    ) -> Result<Option<BytesJson>, MmError<NegotiateSwapContractAddrErr>> {
+        Ok(None)
+    }
+
+    fn derive_htlc_key_pair(&self, _swap_unique_data: &[u8]) -> KeyPair { self.key_pair }
+
+    fn derive_htlc_pubkey(&self, _swap_unique_data: &[u8]) -> Vec<u8> { self.my_pubkey() }
+
+    fn can_refund_htlc(&self, locktime: u64) -> Box<dyn Future<Item = CanRefundHtlc, Error = String> + Send + '_> {
+        let now = now_sec();
+        let can_refund = if now >= locktime {
+            CanRefundHtlc::CanRefundNow
+        } else {
+            CanRefundHtlc::HaveToWait(locktime - now)
+        };
+        Box::new(futures01::future::ok(can_refund))
+    }
+
+    fn validate_other_pubkey(&self, raw_pubkey: &[u8]) -> MmResult<(), ValidateOtherPubKeyErr> {
+        if let Err(err) = keys::Public::from_slice(raw_pubkey) {
+            return MmError::err(ValidateOtherPubKeyErr::InvalidPubKey(err.to_string()));
+        };
+        Ok(())
+    }
+
+    async fn maker_payment_instructions(
+        &self,
+        _args: PaymentInstructionArgs<'_>,
+    ) -> Result<Option<Vec<u8>>, MmError<PaymentInstructionsErr>> {
+        Ok(None)
+    }
+
+    async fn taker_payment_instructions(
+        &self,
+        _args: PaymentInstructionArgs<'_>,
+    ) -> Result<Option<Vec<u8>>, MmError<PaymentInstructionsErr>> {
+        Ok(None)
+    }
+
+    fn validate_maker_payment_instructions(
+        &self,
+        _instructions: &[u8],
+        _args: PaymentInstructionArgs,
+    ) -> Result<PaymentInstructions, MmError<ValidateInstructionsErr>> {
+        MmError::err(ValidateInstructionsErr::UnsupportedCoin(self.ticker().to_string()))
+    }
+
+    fn validate_taker_payment_instructions(
+        &self,
+        _instructions: &[u8],
+        _args: PaymentInstructionArgs,
+    ) -> Result<PaymentInstructions, MmError<ValidateInstructionsErr>> {
+        MmError::err(ValidateInstructionsErr::UnsupportedCoin(self.ticker().to_string()))
+    }
+}
+
+#[async_trait]
+impl TakerSwapMakerCoin for SimulatedCoin {
+    async fn on_taker_payment_refund_start(&self, _maker_payment: &[u8]) -> RefundResult<()> { Ok(()) }
+
+    async fn on_taker_payment_refund_success(&self, _maker_payment: &[u8]) -> RefundResult<()> { Ok(()) }
+}
+
+#[async_trait]
+impl MakerSwapTakerCoin for SimulatedCoin {
+    async fn on_maker_payment_refund_start(&self, _taker_payment: &[u8]) -> RefundResult<()> { Ok(()) }
+
+    async fn on_maker_payment_refund_success(&self, _taker_payment: &[u8]) -> RefundResult<()> { Ok(()) }
+}
+
+#[async_trait]
+impl WatcherOps for SimulatedCoin {
+    fn create_maker_payment_spend_preimage(
+        &self,
+        _maker_payment_tx: &[u8],
+        _time_lock: u32,
+        _maker_pub: &[u8],
+        _secret_hash: &[u8],
+        _swap_unique_data: &[u8],
+    ) -> TransactionFut {
+        Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+            "{}",
+            self.watchers_not_supported()
+        ))))
+    }
+
+    fn send_maker_payment_spend_preimage(&self, _input: SendMakerPaymentSpendPreimageInput) -> TransactionFut {
+        Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+            "{}",
+            self.watchers_not_supported()
+        ))))
+    }
+
+    fn create_taker_payment_refund_preimage(
+        &self,
+        _taker_payment_tx: &[u8],
+        _time_lock: u32,
+        _maker_pub: &[u8],
+        _secret_hash: &[u8],
+        _swap_contract_address: &Option<BytesJson>,
+        _swap_unique_data: &[u8],
+    ) -> TransactionFut {
+        Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+            "{}",
+            self.watchers_not_supported()
+        ))))
+    }
+
+    fn send_taker_payment_refund_preimage(&self, _watcher_refunds_payment_args: RefundPaymentArgs) -> TransactionFut {
+        Box::new(futures01::future::err(TransactionErr::Plain(ERRL!(
+            "{}",
+            self.watchers_not_supported()
+        ))))
+    }
+
+    fn watcher_validate_taker_fee(&self, _input: WatcherValidateTakerFeeInput) -> ValidatePaymentFut<()> {
+        Box::new(futures01::future::err(MmError::new(
+            ValidatePaymentError::InternalError(self.watchers_not_supported()),
+        )))
+    }
+
+    fn watcher_validate_taker_payment(&self, _input: WatcherValidatePaymentInput) -> ValidatePaymentFut<()> {
+        Box::new(futures01::future::err(MmError::new(
+            ValidatePaymentError::InternalError(self.watchers_not_supported()),
+        )))
+    }
+
+    async fn watcher_search_for_swap_tx_spend(
+        &self,
+        _input: WatcherSearchForSwapTxSpendInput<'_>,
+    ) -> Result<Option<FoundSwapTxSpend>, String> {
+        ERR!("{}", self.watchers_not_supported())
+    }
+
+    async fn get_taker_watcher_reward(
+        &self,
+        _other_coin: &MmCoinEnum,
+        _coin_amount: Option<BigDecimal>,
+        _other_coin_amount: Option<BigDecimal>,
+        _reward_amount: Option<BigDecimal>,
+        _wait_until: u64,
+    ) -> Result<WatcherReward, MmError<WatcherRewardError>> {
+        MmError::err(WatcherRewardError::InvalidCoinType(self.watchers_not_supported()))
+    }
+
+    async fn get_maker_watcher_reward(
+        &self,
+        _other_coin: &MmCoinEnum,
+        _reward_amount: Option<BigDecimal>,
+        _wait_until: u64,
+    ) -> Result<Option<WatcherReward>, MmError<WatcherRewardError>> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl MmCoin for SimulatedCoin {
+    fn is_asset_chain(&self) -> bool { false }
+
+    fn spawner(&self) -> CoinFutSpawner { CoinFutSpawner::new(&self.abortable_system) }
+
+    fn get_raw_transaction(&self, req: RawTransactionRequest) -> RawTransactionFut {
+        let tx_hash =
+            try_f!(H256Json::from_str(&req.tx_hash)
+                .map_to_mm(|e| RawTransactionError::InvalidHashError(format!("{:?}", e))));
+        self.get_tx_hex_by_hash(tx_hash.0.to_vec())
+    }
+
+    fn get_tx_hex_by_hash(&self, tx_hash: Vec<u8>) -> RawTransactionFut {
+        if tx_hash.len() != 32 {
+            return Box::new(futures01::future::err(MmError::new(
+                RawTransactionError::InvalidHashError(hex::encode(tx_hash)),
+            )));
+        }
+        let tx_hash = H256Json::from(tx_hash.as_slice());
+        let res = match self.ledger.get_tx(&tx_hash) {
+            Ok(Some(tx)) => Ok(RawTransactionRes {
+                tx_hex: tx.tx_hex().into(),
+            }),
+            Ok(None) => MmError::err(RawTransactionError::HashNotExist(format!("{:02x}", tx_hash))),
+            Err(e) => MmError::err(RawTransactionError::InternalError(e)),
+        };
+        Box::new(futures01::future::result(res))
+    }
+
+    fn withdraw(&self, req: WithdrawRequest) -> WithdrawFut {
+        let coin = self.clone();
+        let fut = async move {
+            if let ValidateAddressResult {
+                is_valid: false,
+                reason: Some(reason),
+            } = coin.validate_address(&req.to)
+            {
+                return MmError::err(WithdrawError::InvalidAddress(reason));
+            }
+            let balance = coin.balance().map_to_mm(WithdrawError::InternalError)?;
+            let amount = if req.max {
+                if balance <= coin.tx_fee {
+                    return MmError::err(WithdrawError::ZeroBalanceToWithdrawMax);
+                }
+                &balance - &coin.tx_fee
+            } else {
+                req.amount.clone()
+            };
+            let min_amount = coin.min_tx_amount();
+            if amount < min_amount {
+                return MmError::err(WithdrawError::AmountTooLow {
+                    amount,
+                    threshold: min_amount,
+                });
+            }
+            let required = &amount + &coin.tx_fee;
+            if balance < required {
+                return MmError::err(WithdrawError::NotSufficientBalance {
+                    coin: coin.ticker.clone(),
+                    available: balance,
+                    required,
+                });
+            }
+            let tx = coin.new_tx(Some(coin.my_address.clone()), Some(req.to.clone()), amount.clone());
+            let tx_hash = tx.hash();
+            let to_me = req.to == coin.my_address;
+            let received_by_me = if to_me { amount.clone() } else { BigDecimal::from(0) };
+            Ok(TransactionDetails {
+                tx_hex: tx.tx_hex().into(),
+                tx_hash: format!("{:02x}", tx_hash),
+                from: vec![coin.my_address.clone()],
+                to: vec![req.to],
+                total_amount: amount.clone(),
+                spent_by_me: &amount + &coin.tx_fee,
+                my_balance_change: &received_by_me - &required,
+                received_by_me,
+                block_height: 0,
+                timestamp: now_sec(),
+                fee_details: Some(TxFeeDetails::Utxo(UtxoFeeDetails {
+                    coin: Some(coin.ticker.clone()),
+                    amount: coin.tx_fee.clone(),
+                    fee_rate: None,
+                    fee_rate_source: None,
+                })),
+                coin: coin.ticker.clone(),
+                internal_id: tx_hash.0.to_vec().into(),
+                kmd_rewards: None,
+                transaction_type: TransactionType::StandardTransfer,
+                memo: req.memo,
+                psbt: None,
+                to_name: None,
+            })
+        };
+        Box::new(fut.boxed().compat())
+    }
+
+    fn decimals(&self) -> u8 { SIMULATED_COIN_DECIMALS }
+
+    fn convert_to_address(&self, _from: &str, _to_address_format: Json) -> Result<String, String> {
+        ERR!("Address conversion is not supported by simulated coins")
+    }
+
+    fn validate_address(&self, address: &str) -> ValidateAddressResult {
+        let is_valid = hex::decode(address)
+            .ok()
+            .map_or(false, |pubkey| keys::Public::from_slice(&pubkey).is_ok());
+        ValidateAddressResult {
+            is_valid,
+            reason: (!is_valid).then(|| "The address must be a hex-encoded public key".to_owned()),
+        }
+    }
+
+    fn process_history_loop(&self, _ctx: MmArc) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+        Box::new(futures01::future::ok(()))
+    }
+
+    fn history_sync_status(&self) -> HistorySyncState { HistorySyncState::NotEnabled }
+
+    fn get_trade_fee(&self) -> Box<dyn Future<Item = TradeFee, Error = String> + Send> {
+        Box::new(futures01::future::ok(TradeFee {
+            coin: self.ticker.clone(),
+            amount: self.tx_fee.clone().into(),
+            paid_from_trading_vol: false,
+        }))
+    }
+
+    async fn get_sender_trade_fee(
+        &self,
+        _value: TradePreimageValue,
+        _stage: FeeApproxStage,
+    ) -> TradePreimageResult<TradeFee> {
+        Ok(TradeFee {
+            coin: self.ticker.clone(),
+            amount: self.tx_fee.clone().into(),
+            paid_from_trading_vol: false,
+        })
+    }
+
+    fn get_receiver_trade_fee(&self, _stage: FeeApproxStage) -> TradePreimageFut<TradeFee> {
+        Box::new(futures01::future::ok(TradeFee {
+            coin: self.ticker.clone(),
+            amount: self.tx_fee.clone().into(),
+            paid_from_trading_vol: false,
+        }))
+    }
+
+    async fn get_fee_to_send_taker_fee(
+        &self,
+        _dex_fee_amount: BigDecimal,
+        _stage: FeeApproxStage,
+    ) -> TradePreimageResult<TradeFee> {
+        Ok(TradeFee {
+            coin: self.ticker.clone(),
+            amount: self.tx_fee.clone().into(),
+            paid_from_trading_vol: false,
+        })
+    }
+
+    fn required_confirmations(&self) -> u64 { self.required_confirmations.load(AtomicOrdering::Relaxed) }
+
+    fn requires_notarization(&self) -> bool { self.requires_notarization.load(AtomicOrdering::Relaxed) }
+
+    fn set_required_confirmations(&self, confirmations: u64) {
+        self.required_confirmations
+            .store(confirmations, AtomicOrdering::Relaxed);
+    }
+
+    fn set_requires_notarization(&self, requires_nota: bool) {
+        self.requires_notarization.store(requires_nota, AtomicOrdering::Relaxed);
+    }
+
+    fn swap_contract_address(&self) -> Option<BytesJson> { None }
+
+    fn fallback_swap_contract(&self) -> Option<BytesJson> { None }
+
+    fn mature_confirmations(&self) -> Option<u32> { None }
+
+    fn coin_protocol_info(&self, _amount_to_receive: Option<MmNumber>) -> Vec<u8> { Vec::new() }
+
+    fn is_coin_protocol_supported(
+        &self,
+        _info: &Option<Vec<u8>>,
+        _amount_to_send: Option<MmNumber>,
+        _locktime: u64,
+        _is_maker: bool,
+    ) -> bool {
+        true
+    }
+
+    fn on_disabled(&self) -> Result<(), AbortedError> { AbortableSystem::abort_all(&self.abortable_system) }
+
+    fn on_token_deactivated(&self, _ticker: &str) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use crypto::privkey::key_pair_from_seed;
+    use mm2_core::mm_ctx::MmCtxBuilder;
+
+    fn simulated_coin(ctx: &MmArc, ticker: &str, ledger_path: &std::path::Path, seed: &str) -> SimulatedCoin {
+        let conf = json!({
+            "coin": ticker,
+            "protocol": {"type": "SIMULATION"},
+            "ledger_path": ledger_path.display().to_string(),
+        });
+        let key_pair = key_pair_from_seed(seed).unwrap();
+        simulated_coin_from_conf(ctx, ticker, &conf, key_pair).unwrap()
+    }
+
+    #[test]
+    fn test_simulated_htlc_spend_and_double_spend() {
+        let ctx = MmCtxBuilder::default().into_mm_arc();
+        let ledger_path = std::env::temp_dir().join(format!("mm2_simulation_test_{}", now_sec()));
+        let maker = simulated_coin(&ctx, "SIMA", &ledger_path, "maker seed");
+        let taker = simulated_coin(&ctx, "SIMA", &ledger_path, "taker seed");
+
+        let secret = [1; 32];
+        let secret_hash = dhash160(&secret).take();
+        let time_lock = (now_sec() + 1000) as u32;
+        let payment = maker
+            .send_maker_payment(SendPaymentArgs {
+                time_lock_duration: 1000,
+                time_lock,
+                other_pubkey: &taker.my_pubkey(),
+                secret_hash: &secret_hash,
+                amount: 1.into(),
+                swap_contract_address: &None,
+                swap_unique_data: &[],
+                payment_instructions: &None,
+                watcher_reward: None,
+                wait_for_confirmation_until: 0,
+            })
+            .wait()
+            .unwrap();
+
+        let spend_args = SpendPaymentArgs {
+            other_payment_tx: &payment.tx_hex(),
+            time_lock,
+            other_pubkey: &maker.my_pubkey(),
+            secret: &secret,
+            secret_hash: &secret_hash,
+            swap_contract_address: &None,
+            swap_unique_data: &[],
+            watcher_reward: false,
+        };
+        let spend = taker
+            .send_taker_spends_maker_payment(spend_args.clone())
+            .wait()
+            .unwrap();
+        let extracted = block_on(maker.extract_secret(&secret_hash, &spend.tx_hex(), false)).unwrap();
+        assert_eq!(extracted, secret.to_vec());
+
+        // the same HTLC can't be spent twice
+        taker.send_taker_spends_maker_payment(spend_args).wait().unwrap_err();
+
+        let expected_taker_balance = BigDecimal::from_str("1000.99999").unwrap();
+        assert_eq!(taker.balance().unwrap(), expected_taker_balance);
+        let expected_maker_balance = BigDecimal::from_str("998.99999").unwrap();
+        assert_eq!(maker.balance().unwrap(), expected_maker_balance);
+
+        fs::remove_dir_all(ledger_path).unwrap();
+    }
+
+    #[test]
+    fn test_simulated_watcher_ops_not_supported() {
+        let ctx = MmCtxBuilder::default().into_mm_arc();
+        let ledger_path = std::env::temp_dir().join(format!("mm2_simulation_watcher_test_{}", now_sec()));
+        let coin = simulated_coin(&ctx, "SIMA", &ledger_path, "watcher seed");
+
+        let error = coin
+            .create_maker_payment_spend_preimage(&[], 0, &[], &[], &[])
+            .wait()
+            .unwrap_err();
+        assert!(error
+            .get_plain_text_format()
+            .contains("Watchers are not supported by SIMA"));
+
+        let input = WatcherSearchForSwapTxSpendInput {
+            time_lock: 0,
+            taker_pub: &[],
+            maker_pub: &[],
+            secret_hash: &[],
+            tx: &[],
+            search_from_block: 0,
+            watcher_reward: false,
+        };
+        let error = block_on(coin.watcher_search_for_swap_tx_spend(input)).unwrap_err();
+        assert!(error.contains("Watchers are not supported by SIMA"));
+
+        let _ = fs::remove_dir_all(ledger_path);
+    }
+}
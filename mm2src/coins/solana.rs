@@ -190,6 +190,7 @@ pub async fn solana_coin_with_policy(
             try_s!(global_hd.derive_secp256k1_secret(&derivation_path))
         },
         PrivKeyBuildPolicy::Trezor => return ERR!("{}", PrivKeyPolicyNotAllowed::HardwareWalletNotSupported),
+        PrivKeyBuildPolicy::WatchOnly(_) => return ERR!("{}", PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported),
     };
 
     let key_pair = try_s!(generate_keypair_from_slice(priv_key.as_slice()));
@@ -290,6 +291,8 @@ async fn withdraw_base_coin_impl(coin: SolanaCoin, req: WithdrawRequest) -> With
         kmd_rewards: None,
         transaction_type: TransactionType::StandardTransfer,
         memo: None,
+        psbt: None,
+        to_name: None,
     })
 }
 
@@ -70,6 +70,8 @@ impl SolanaConfirmedTransaction {
                     kmd_rewards: None,
                     transaction_type: TransactionType::StandardTransfer,
                     memo: None,
+                    psbt: None,
+                    to_name: None,
                 };
                 transactions.push(tx);
             }
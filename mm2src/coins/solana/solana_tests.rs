@@ -163,6 +163,10 @@ fn solana_transaction_simulations() {
                 to: sol_coin.my_address.clone(),
                 amount: request_amount.clone(),
                 max: false,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
@@ -191,6 +195,10 @@ fn solana_transaction_zero_balance() {
                 to: sol_coin.my_address.clone(),
                 amount: BigDecimal::from_str("0.000001").unwrap(),
                 max: false,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
@@ -220,6 +228,10 @@ fn solana_transaction_simulations_not_enough_for_fees() {
                 to: sol_coin.my_address.clone(),
                 amount: BigDecimal::from(1),
                 max: false,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
@@ -254,6 +266,10 @@ fn solana_transaction_simulations_max() {
                 to: sol_coin.my_address.clone(),
                 amount: BigDecimal::from(0),
                 max: true,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
@@ -283,6 +299,10 @@ fn solana_test_transactions() {
                 to: sol_coin.my_address.clone(),
                 amount: BigDecimal::try_from(0.0001).unwrap(),
                 max: false,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
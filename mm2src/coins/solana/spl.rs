@@ -169,6 +169,8 @@ async fn withdraw_spl_token_impl(coin: SplToken, req: WithdrawRequest) -> Withdr
         kmd_rewards: None,
         transaction_type: TransactionType::StandardTransfer,
         memo: None,
+        psbt: None,
+        to_name: None,
     })
 }
 
@@ -111,6 +111,10 @@ fn test_spl_transactions() {
                 to: "AYJmtzc9D4KU6xsDzhKShFyYKUNXY622j9QoQEo4LfpX".to_string(),
                 amount: withdraw_amount.clone(),
                 max: false,
+                outputs: Vec::new(),
+                inputs: Vec::new(),
+                rbf: false,
+                psbt: false,
                 fee: None,
                 memo: None,
             })
@@ -661,6 +661,8 @@ impl TendermintCoin {
                 kmd_rewards: None,
                 transaction_type: TransactionType::default(),
                 memo: Some(memo),
+                psbt: None,
+                to_name: None,
             })
         };
         Box::new(fut.boxed().compat())
@@ -1936,6 +1938,8 @@ impl MmCoin for TendermintCoin {
                 kmd_rewards: None,
                 transaction_type: TransactionType::default(),
                 memo: Some(memo),
+                psbt: None,
+                to_name: None,
             })
         };
         Box::new(fut.boxed().compat())
@@ -2655,6 +2659,14 @@ pub(crate) fn secret_from_priv_key_policy(
                 kind,
             })
         },
+        PrivKeyBuildPolicy::WatchOnly(_) => {
+            let kind =
+                TendermintInitErrorKind::PrivKeyPolicyNotAllowed(PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported);
+            MmError::err(TendermintInitError {
+                ticker: ticker.to_string(),
+                kind,
+            })
+        },
     }
 }
 
@@ -228,6 +228,8 @@ impl TendermintToken {
                 kmd_rewards: None,
                 transaction_type: TransactionType::default(),
                 memo: Some(memo),
+                psbt: None,
+                to_name: None,
             })
         };
         Box::new(fut.boxed().compat())
@@ -702,6 +704,8 @@ impl MmCoin for TendermintToken {
                 kmd_rewards: None,
                 transaction_type: TransactionType::default(),
                 memo: Some(memo),
+                psbt: None,
+                to_name: None,
             })
         };
         Box::new(fut.boxed().compat())
@@ -710,6 +710,8 @@ where
                             kmd_rewards: None,
                             transaction_type,
                             memo: Some(deserialized_tx.body.memo.clone()),
+                            psbt: None,
+                            to_name: None,
                         };
                         tx_details.push(details.clone());
 
@@ -105,6 +105,18 @@ impl<'de> Deserialize<'de> for ConfirmationStatus {
     }
 }
 
+/// The range of blocks which history is fetched already.
+/// It allows to continue fetching the history after restart instead of rescanning the whole chain.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BlockCursor {
+    /// The lowest fetched block.
+    pub from_block: u64,
+    /// The highest fetched block.
+    pub to_block: u64,
+    /// The hash of the highest fetched block is used to detect chain reorganizations.
+    pub to_block_hash: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct WalletId {
     ticker: String,
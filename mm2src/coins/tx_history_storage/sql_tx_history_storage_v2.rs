@@ -1,5 +1,5 @@
 use crate::my_tx_history_v2::{GetHistoryResult, RemoveTxResult, TxHistoryStorage, TxHistoryStorageError};
-use crate::tx_history_storage::{token_id_from_tx_type, ConfirmationStatus, CreateTxHistoryStorageError,
+use crate::tx_history_storage::{token_id_from_tx_type, BlockCursor, ConfirmationStatus, CreateTxHistoryStorageError,
                                 FilteringAddresses, GetTxHistoryFilters, WalletId};
 use crate::TransactionDetails;
 use async_trait::async_trait;
@@ -19,6 +19,8 @@ fn tx_history_table(wallet_id: &WalletId) -> String { wallet_id.to_sql_table_nam
 
 fn tx_address_table(wallet_id: &WalletId) -> String { wallet_id.to_sql_table_name() + "_tx_address" }
 
+fn block_cursor_table(wallet_id: &WalletId) -> String { wallet_id.to_sql_table_name() + "_block_cursor" }
+
 /// Please note TX cache table name doesn't depend on [`WalletId::hd_wallet_rmd160`].
 fn tx_cache_table(wallet_id: &WalletId) -> String { format!("{}_tx_cache", wallet_id.ticker) }
 
@@ -73,6 +75,23 @@ fn create_tx_cache_table_sql(wallet_id: &WalletId) -> Result<String, MmError<Sql
     Ok(sql)
 }
 
+fn create_block_cursor_table_sql(wallet_id: &WalletId) -> Result<String, MmError<SqlError>> {
+    let table_name = block_cursor_table(wallet_id);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "CREATE TABLE IF NOT EXISTS {} (
+            token_id VARCHAR(255) NOT NULL PRIMARY KEY,
+            from_block INTEGER NOT NULL,
+            to_block INTEGER NOT NULL,
+            to_block_hash VARCHAR(255) NOT NULL
+        );",
+        table_name
+    );
+
+    Ok(sql)
+}
+
 fn create_internal_id_index_sql<F>(wallet_id: &WalletId, table_name_creator: F) -> Result<String, MmError<SqlError>>
 where
     F: FnOnce(&WalletId) -> String,
@@ -202,6 +221,30 @@ fn get_tx_hex_from_cache_sql(wallet_id: &WalletId) -> Result<String, MmError<Sql
     Ok(sql)
 }
 
+fn select_block_cursor_sql(wallet_id: &WalletId) -> Result<String, MmError<SqlError>> {
+    let table_name = block_cursor_table(wallet_id);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "SELECT from_block, to_block, to_block_hash FROM {} WHERE token_id = ?1;",
+        table_name
+    );
+
+    Ok(sql)
+}
+
+fn upsert_block_cursor_sql(wallet_id: &WalletId) -> Result<String, MmError<SqlError>> {
+    let table_name = block_cursor_table(wallet_id);
+    validate_table_name(&table_name)?;
+
+    let sql = format!(
+        "INSERT OR REPLACE INTO {} (token_id, from_block, to_block, to_block_hash) VALUES (?1, ?2, ?3, ?4);",
+        table_name
+    );
+
+    Ok(sql)
+}
+
 /// Creates `SqlQuery` builder to query transactions from `tx_history` table
 /// joining `tx_addresses` table and specifying from/to `for_addresses` addresses.
 fn tx_history_with_addresses_builder_preimage<'a>(
@@ -354,6 +397,16 @@ fn tx_details_from_row(row: &Row<'_>) -> Result<TransactionDetails, SqlError> {
 
 fn block_height_from_row(row: &Row<'_>) -> Result<u32, SqlError> { row.get(0) }
 
+fn block_cursor_from_row(row: &Row<'_>) -> Result<BlockCursor, SqlError> {
+    let from_block: i64 = row.get(0)?;
+    let to_block: i64 = row.get(1)?;
+    Ok(BlockCursor {
+        from_block: from_block as u64,
+        to_block: to_block as u64,
+        to_block_hash: row.get(2)?,
+    })
+}
+
 impl TxHistoryStorageError for SqlError {}
 
 impl ConfirmationStatus {
@@ -395,6 +448,7 @@ impl TxHistoryStorage for SqliteTxHistoryStorage {
         let sql_history = create_tx_history_table_sql(wallet_id)?;
         let sql_cache = create_tx_cache_table_sql(wallet_id)?;
         let sql_addr = create_tx_address_table_sql(wallet_id)?;
+        let sql_block_cursor = create_block_cursor_table_sql(wallet_id)?;
 
         let sql_history_index = create_internal_id_index_sql(wallet_id, tx_history_table)?;
         let sql_addr_index = create_internal_id_index_sql(wallet_id, tx_address_table)?;
@@ -405,6 +459,7 @@ impl TxHistoryStorage for SqliteTxHistoryStorage {
             conn.execute(&sql_history, NO_PARAMS).map(|_| ())?;
             conn.execute(&sql_addr, NO_PARAMS).map(|_| ())?;
             conn.execute(&sql_cache, NO_PARAMS).map(|_| ())?;
+            conn.execute(&sql_block_cursor, NO_PARAMS).map(|_| ())?;
 
             conn.execute(&sql_history_index, NO_PARAMS).map(|_| ())?;
             conn.execute(&sql_addr_index, NO_PARAMS).map(|_| ())?;
@@ -420,13 +475,19 @@ impl TxHistoryStorage for SqliteTxHistoryStorage {
         let tx_cache_table = tx_cache_table(wallet_id);
         validate_table_name(&tx_cache_table)?;
 
+        // The table has been added later, so it should be created for the wallets initialized before.
+        let block_cursor_table = block_cursor_table(wallet_id);
+        validate_table_name(&block_cursor_table)?;
+
         let selfi = self.clone();
         async_blocking(move || {
             let conn = selfi.0.lock().unwrap();
             let history_initialized =
                 query_single_row(&conn, CHECK_TABLE_EXISTS_SQL, [tx_history_table], string_from_row)?;
             let cache_initialized = query_single_row(&conn, CHECK_TABLE_EXISTS_SQL, [tx_cache_table], string_from_row)?;
-            Ok(history_initialized.is_some() && cache_initialized.is_some())
+            let block_cursor_initialized =
+                query_single_row(&conn, CHECK_TABLE_EXISTS_SQL, [block_cursor_table], string_from_row)?;
+            Ok(history_initialized.is_some() && cache_initialized.is_some() && block_cursor_initialized.is_some())
         })
         .await
     }
@@ -674,6 +735,44 @@ impl TxHistoryStorage for SqliteTxHistoryStorage {
         .await
     }
 
+    async fn get_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+    ) -> Result<Option<BlockCursor>, MmError<Self::Error>> {
+        let sql = select_block_cursor_sql(wallet_id)?;
+        let params = [token_id.to_owned()];
+        let selfi = self.clone();
+
+        async_blocking(move || {
+            let conn = selfi.0.lock().unwrap();
+            query_single_row(&conn, &sql, params, block_cursor_from_row).map_to_mm(SqlError::from)
+        })
+        .await
+    }
+
+    async fn set_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+        cursor: &BlockCursor,
+    ) -> Result<(), MmError<Self::Error>> {
+        let sql = upsert_block_cursor_sql(wallet_id)?;
+        let params = [
+            token_id.to_owned(),
+            cursor.from_block.to_string(),
+            cursor.to_block.to_string(),
+            cursor.to_block_hash.clone(),
+        ];
+        let selfi = self.clone();
+
+        async_blocking(move || {
+            let conn = selfi.0.lock().unwrap();
+            conn.execute(&sql, params).map(|_| ()).map_err(MmError::new)
+        })
+        .await
+    }
+
     async fn get_history(
         &self,
         wallet_id: &WalletId,
@@ -2,7 +2,8 @@
 //! to print all transactions from `../for_tests/tBCH_tx_history_fixtures.json` ordered.
 
 use crate::my_tx_history_v2::{GetHistoryResult, TxHistoryStorage};
-use crate::tx_history_storage::{FilteringAddresses, GetTxHistoryFilters, TxHistoryStorageBuilder, WalletId};
+use crate::tx_history_storage::{BlockCursor, FilteringAddresses, GetTxHistoryFilters, TxHistoryStorageBuilder,
+                                WalletId};
 use crate::{BytesJson, TransactionDetails};
 use common::PagingOptionsEnum;
 use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
@@ -578,6 +579,57 @@ async fn test_get_history_for_addresses_impl() {
     assert_get_history_result(result, Vec::new(), 4, 4);
 }
 
+async fn test_block_cursor_impl() {
+    let wallet_id = wallet_id_for_test("TEST_BLOCK_CURSOR");
+    let token_id = "dac17f958d2ee523a2206206994597c13d831ec7";
+
+    let ctx = mm_ctx_with_custom_db();
+    let storage = TxHistoryStorageBuilder::new(&ctx).build().unwrap();
+
+    storage.init(&wallet_id).await.unwrap();
+
+    assert_eq!(storage.get_block_cursor(&wallet_id, "").await.unwrap(), None);
+
+    let platform_cursor = BlockCursor {
+        from_block: 100,
+        to_block: 200,
+        to_block_hash: "01".to_owned(),
+    };
+    storage
+        .set_block_cursor(&wallet_id, "", &platform_cursor)
+        .await
+        .unwrap();
+
+    let token_cursor = BlockCursor {
+        from_block: 150,
+        to_block: 300,
+        to_block_hash: "02".to_owned(),
+    };
+    storage
+        .set_block_cursor(&wallet_id, token_id, &token_cursor)
+        .await
+        .unwrap();
+
+    // The cursors of the platform coin and the token must not affect each other.
+    let actual = storage.get_block_cursor(&wallet_id, "").await.unwrap();
+    assert_eq!(actual, Some(platform_cursor));
+    let actual = storage.get_block_cursor(&wallet_id, token_id).await.unwrap();
+    assert_eq!(actual, Some(token_cursor.clone()));
+
+    // The cursor must be replaced.
+    let updated_cursor = BlockCursor {
+        to_block: 400,
+        to_block_hash: "03".to_owned(),
+        ..token_cursor
+    };
+    storage
+        .set_block_cursor(&wallet_id, token_id, &updated_cursor)
+        .await
+        .unwrap();
+    let actual = storage.get_block_cursor(&wallet_id, token_id).await.unwrap();
+    assert_eq!(actual, Some(updated_cursor));
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod native_tests {
     use super::wallet_id_for_test;
@@ -643,6 +695,9 @@ mod native_tests {
 
     #[test]
     fn test_get_history_for_addresses() { block_on(super::test_get_history_for_addresses_impl()); }
+
+    #[test]
+    fn test_block_cursor() { block_on(super::test_block_cursor_impl()); }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -713,4 +768,7 @@ mod wasm_tests {
 
     #[wasm_bindgen_test]
     async fn test_get_history_for_addresses() { super::test_get_history_for_addresses_impl().await; }
+
+    #[wasm_bindgen_test]
+    async fn test_block_cursor() { super::test_block_cursor_impl().await; }
 }
@@ -1,10 +1,10 @@
 use crate::tx_history_storage::wasm::tx_history_storage_v1::TxHistoryTableV1;
-use crate::tx_history_storage::wasm::tx_history_storage_v2::{TxCacheTableV2, TxHistoryTableV2};
+use crate::tx_history_storage::wasm::tx_history_storage_v2::{BlockCursorTableV2, TxCacheTableV2, TxHistoryTableV2};
 use async_trait::async_trait;
 use mm2_db::indexed_db::{DbIdentifier, DbInstance, DbLocked, IndexedDb, IndexedDbBuilder, InitDbResult};
 
 const DB_NAME: &str = "tx_history";
-const DB_VERSION: u32 = 1;
+const DB_VERSION: u32 = 2;
 
 pub type TxHistoryDbLocked<'a> = DbLocked<'a, TxHistoryDb>;
 
@@ -22,6 +22,7 @@ impl DbInstance for TxHistoryDb {
             .with_table::<TxHistoryTableV1>()
             .with_table::<TxHistoryTableV2>()
             .with_table::<TxCacheTableV2>()
+            .with_table::<BlockCursorTableV2>()
             .build()
             .await?;
         Ok(TxHistoryDb { inner })
@@ -75,8 +75,8 @@ pub(crate) struct TxHistoryTableV1 {
 impl TableSignature for TxHistoryTableV1 {
     fn table_name() -> &'static str { "tx_history" }
 
-    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
-        if let (0, 1) = (old_version, new_version) {
+    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, _new_version: u32) -> OnUpgradeResult<()> {
+        if old_version == 0 {
             let table = upgrader.create_table(Self::table_name())?;
             table.create_index("history_id", true)?;
         }
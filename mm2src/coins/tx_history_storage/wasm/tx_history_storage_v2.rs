@@ -1,7 +1,7 @@
 use crate::my_tx_history_v2::{GetHistoryResult, RemoveTxResult, TxHistoryStorage};
 use crate::tx_history_storage::wasm::tx_history_db::{TxHistoryDb, TxHistoryDbLocked};
 use crate::tx_history_storage::wasm::{WasmTxHistoryError, WasmTxHistoryResult};
-use crate::tx_history_storage::{token_id_from_tx_type, ConfirmationStatus, CreateTxHistoryStorageError,
+use crate::tx_history_storage::{token_id_from_tx_type, BlockCursor, ConfirmationStatus, CreateTxHistoryStorageError,
                                 FilteringAddresses, GetTxHistoryFilters, WalletId};
 use crate::{compare_transaction_details, CoinsContext, TransactionDetails};
 use async_trait::async_trait;
@@ -252,6 +252,49 @@ impl TxHistoryStorage for IndexedDbTxHistoryStorage {
         }
     }
 
+    async fn get_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+    ) -> MmResult<Option<BlockCursor>, Self::Error> {
+        let locked_db = self.lock_db().await?;
+        let db_transaction = locked_db.get_inner().transaction().await?;
+        let table = db_transaction.table::<BlockCursorTableV2>().await?;
+
+        let index_keys = MultiIndex::new(BlockCursorTableV2::WALLET_ID_TOKEN_ID_INDEX)
+            .with_value(&wallet_id.ticker)?
+            .with_value(wallet_id.hd_wallet_rmd160_or_exclude())?
+            .with_value(token_id)?;
+        Ok(table
+            .get_item_by_unique_multi_index(index_keys)
+            .await?
+            .map(|(_item_id, item)| item.cursor))
+    }
+
+    async fn set_block_cursor(
+        &self,
+        wallet_id: &WalletId,
+        token_id: &str,
+        cursor: &BlockCursor,
+    ) -> MmResult<(), Self::Error> {
+        let locked_db = self.lock_db().await?;
+        let db_transaction = locked_db.get_inner().transaction().await?;
+        let table = db_transaction.table::<BlockCursorTableV2>().await?;
+
+        let index_keys = MultiIndex::new(BlockCursorTableV2::WALLET_ID_TOKEN_ID_INDEX)
+            .with_value(&wallet_id.ticker)?
+            .with_value(wallet_id.hd_wallet_rmd160_or_exclude())?
+            .with_value(token_id)?;
+        let item = BlockCursorTableV2 {
+            coin: wallet_id.ticker.clone(),
+            hd_wallet_rmd160: wallet_id.hd_wallet_rmd160_or_exclude(),
+            token_id: token_id.to_owned(),
+            cursor: cursor.clone(),
+        };
+        table.replace_item_by_unique_multi_index(index_keys, &item).await?;
+        Ok(())
+    }
+
     /// This is totally inefficient due to we query all items from the storage
     /// and then checks whether it were sent from/to one of the specified `for_addresses`.
     ///
@@ -416,8 +459,8 @@ impl TxHistoryTableV2 {
 impl TableSignature for TxHistoryTableV2 {
     fn table_name() -> &'static str { "tx_history_v2" }
 
-    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
-        if let (0, 1) = (old_version, new_version) {
+    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, _new_version: u32) -> OnUpgradeResult<()> {
+        if old_version == 0 {
             let table = upgrader.create_table(Self::table_name())?;
             table.create_multi_index(TxHistoryTableV2::WALLET_ID_INDEX, &["coin", "hd_wallet_rmd160"], false)?;
             table.create_multi_index(
@@ -470,8 +513,8 @@ impl TxCacheTableV2 {
 impl TableSignature for TxCacheTableV2 {
     fn table_name() -> &'static str { "tx_cache_v2" }
 
-    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
-        if let (0, 1) = (old_version, new_version) {
+    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, _new_version: u32) -> OnUpgradeResult<()> {
+        if old_version == 0 {
             let table = upgrader.create_table(Self::table_name())?;
             table.create_multi_index(TxCacheTableV2::COIN_TX_HASH_INDEX, &["coin", "tx_hash"], true)?;
         }
@@ -479,6 +522,39 @@ impl TableSignature for TxCacheTableV2 {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct BlockCursorTableV2 {
+    coin: String,
+    hd_wallet_rmd160: String,
+    token_id: String,
+    cursor: BlockCursor,
+}
+
+impl BlockCursorTableV2 {
+    /// A **unique** index that consists of the following properties:
+    /// * coin - coin ticker
+    /// * hd_wallet_rmd160 - HD wallet RIPEMD160 (can be an empty string)
+    /// * token_id - token ID (can be an empty string)
+    const WALLET_ID_TOKEN_ID_INDEX: &'static str = "wallet_id_token_id";
+}
+
+impl TableSignature for BlockCursorTableV2 {
+    fn table_name() -> &'static str { "block_cursor_v2" }
+
+    fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
+        // The table has been added in the second version of the database.
+        if old_version < 2 && new_version >= 2 {
+            let table = upgrader.create_table(Self::table_name())?;
+            table.create_multi_index(
+                BlockCursorTableV2::WALLET_ID_TOKEN_ID_INDEX,
+                &["coin", "hd_wallet_rmd160", "token_id"],
+                true,
+            )?;
+        }
+        Ok(())
+    }
+}
+
 fn tx_details_from_item(item: TxHistoryTableV2) -> WasmTxHistoryResult<TransactionDetails> {
     json::from_value(item.details_json).map_to_mm(|e| WasmTxHistoryError::ErrorDeserializing(e.to_string()))
 }
@@ -27,6 +27,7 @@ pub(crate) mod bchd_grpc;
 #[rustfmt::skip]
 #[path = "utxo/pb.rs"]
 mod bchd_pb;
+pub mod frozen_outpoints_storage;
 pub mod qtum;
 pub mod rpc_clients;
 pub mod slp;
@@ -119,6 +120,8 @@ pub mod utxo_common_tests;
 const KILO_BYTE: u64 = 1000;
 /// https://bitcoin.stackexchange.com/a/77192
 const MAX_DER_SIGNATURE_LEN: usize = 72;
+/// https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#default-signing
+const SCHNORR_SIGNATURE_LEN: usize = 64;
 const COMPRESSED_PUBKEY_LEN: usize = 33;
 const P2PKH_OUTPUT_LEN: u64 = 34;
 const MATURE_CONFIRMATIONS_DEFAULT: u32 = 100;
@@ -278,6 +281,42 @@ pub enum ActualTxFee {
     FixedPerKb(u64),
 }
 
+/// The confirmation target in blocks the fee rate is estimated for at the given priority.
+const HIGH_PRIORITY_TARGET_BLOCKS: u32 = 2;
+const MEDIUM_PRIORITY_TARGET_BLOCKS: u32 = 6;
+const LOW_PRIORITY_TARGET_BLOCKS: u32 = 24;
+
+/// The named priority of a transaction, resolved to a confirmation target in blocks.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UtxoFeePriority {
+    Low,
+    Medium,
+    High,
+}
+
+impl UtxoFeePriority {
+    pub fn target_blocks(&self) -> u32 {
+        match self {
+            UtxoFeePriority::Low => LOW_PRIORITY_TARGET_BLOCKS,
+            UtxoFeePriority::Medium => MEDIUM_PRIORITY_TARGET_BLOCKS,
+            UtxoFeePriority::High => HIGH_PRIORITY_TARGET_BLOCKS,
+        }
+    }
+}
+
+/// Where the fee rate of a generated transaction comes from.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum UtxoFeeRateSource {
+    /// The fee rate is set explicitly by the request.
+    Manual,
+    /// The fixed fee rate from the coin config.
+    CoinConfig,
+    /// The fee rate is estimated by the node for the confirmation within `target_blocks`.
+    Estimated { target_blocks: u32 },
+}
+
 /// Fee policy applied on transaction creation
 pub enum FeePolicy {
     /// Send the exact amount specified in output(s), fee is added to spent input amount
@@ -553,6 +592,13 @@ pub struct UtxoCoinConf {
     pub mature_confirmations: u32,
     /// The number of blocks used for estimate_fee/estimate_smart_fee RPC calls
     pub estimate_fee_blocks: u32,
+    /// The priority the fee rate of swap transactions is estimated for.
+    /// If not set, the fee rate is estimated for `estimate_fee_blocks`.
+    pub swap_fee_priority: Option<UtxoFeePriority>,
+    /// Whether the swap spends and refunds signal Replace-By-Fee (BIP-125), so they can be bumped by `bump_fee`.
+    /// They're looked up by the spent HTLC output, so they can be safely replaced.
+    /// The swap payments never signal RBF, since the counterparty may act on an unconfirmed payment.
+    pub swap_txs_rbf: bool,
     /// The name of the coin with which Trezor wallet associates this asset.
     pub trezor_coin: Option<String>,
     /// Whether to verify swaps and lightning transactions using spv or not. When enabled, block headers will be retrieved, verified according
@@ -592,6 +638,12 @@ pub struct UtxoCoinFields {
     /// The daemon needs some time to update the listunspent list for address which makes it return already spent UTXOs
     /// This cache helps to prevent UTXO reuse in such cases
     pub recently_spent_outpoints: AsyncMutex<RecentlySpentOutPoints>,
+    /// The outpoints frozen by the user. They are never selected to fund withdraws or swap payments.
+    /// Persisted in the [`frozen_outpoints_storage::FrozenOutpointsStorage`] and loaded on the coin activation.
+    pub frozen_outpoints: Mutex<HashSet<OutPoint>>,
+    /// The abortable system of the background UTXO consolidation started by the `consolidate_utxos` RPC.
+    /// The consolidation is stopped once it's dropped or replaced.
+    pub consolidation_loop: Mutex<Option<AbortableQueue>>,
     pub tx_hash_algo: TxHashAlgo,
     /// The flag determines whether to use mature unspent outputs *only* to generate transactions.
     /// https://github.com/KomodoPlatform/atomicDEX-API/issues/1181
@@ -1248,6 +1300,11 @@ pub fn compressed_pub_key_from_priv_raw(raw_priv: &[u8], sum_type: ChecksumType)
 pub struct UtxoFeeDetails {
     pub coin: Option<String>,
     pub amount: BigDecimal,
+    /// The fee rate per kbyte the transaction is generated with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<BigDecimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_rate_source: Option<UtxoFeeRateSource>,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1813,10 +1870,11 @@ where
 
     let signature_version = match &my_address.addr_format {
         UtxoAddressFormat::Segwit => SignatureVersion::WitnessV0,
+        UtxoAddressFormat::Taproot => SignatureVersion::Taproot,
         _ => coin.as_ref().conf.signature_version,
     };
 
-    let prev_script = Builder::build_p2pkh(&my_address.hash);
+    let prev_script = prev_script_for_signing(&my_address);
     let signed = try_tx_s!(sign_tx(
         unsigned,
         key_pair,
@@ -1835,6 +1893,7 @@ where
 pub fn output_script(address: &Address, script_type: ScriptType) -> Script {
     match address.addr_format {
         UtxoAddressFormat::Segwit => Builder::build_witness_script(&address.hash),
+        UtxoAddressFormat::Taproot => Builder::build_p2tr(&address.hash),
         _ => match script_type {
             ScriptType::P2PKH => Builder::build_p2pkh(&address.hash),
             ScriptType::P2SH => Builder::build_p2sh(&address.hash),
@@ -1844,6 +1903,15 @@ pub fn output_script(address: &Address, script_type: ScriptType) -> Script {
     }
 }
 
+/// Returns the script that `sign_tx` expects the inputs spending the outputs of the `address` to be signed with:
+/// the P2TR script pubkey for taproot addresses, the P2PKH script otherwise (it's also the script code of P2WPKH inputs).
+pub fn prev_script_for_signing(address: &Address) -> Script {
+    match address.addr_format {
+        UtxoAddressFormat::Taproot => Builder::build_p2tr(&address.hash),
+        _ => Builder::build_p2pkh(&address.hash),
+    }
+}
+
 pub fn address_by_conf_and_pubkey_str(
     coin: &str,
     conf: &Json,
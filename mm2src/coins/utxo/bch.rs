@@ -459,6 +459,8 @@ impl BchCoin {
         let fee = Some(TxFeeDetails::Utxo(UtxoFeeDetails {
             coin: Some(self.ticker().into()),
             amount: big_decimal_from_sat_unsigned(total_input - total_output, self.decimals()),
+            fee_rate: None,
+            fee_rate_source: None,
         }));
         tx_builder.set_tx_fee(fee);
         Ok(tx_builder.build())
@@ -1202,6 +1204,8 @@ impl MarketCoinOps for BchCoin {
 impl MmCoin for BchCoin {
     fn is_asset_chain(&self) -> bool { utxo_common::is_asset_chain(&self.utxo_arc) }
 
+    fn is_watch_only(&self) -> bool { utxo_common::is_watch_only(&self.utxo_arc) }
+
     fn spawner(&self) -> CoinFutSpawner { CoinFutSpawner::new(&self.as_ref().abortable_system) }
 
     fn get_raw_transaction(&self, req: RawTransactionRequest) -> RawTransactionFut {
@@ -1390,10 +1394,9 @@ impl UtxoTxHistoryOps for BchCoin {
 pub fn tbch_coin_for_test() -> (MmArc, BchCoin) {
     use common::block_on;
     use crypto::privkey::key_pair_from_seed;
-    use mm2_core::mm_ctx::MmCtxBuilder;
-    use mm2_test_helpers::for_tests::BCHD_TESTNET_URLS;
+    use mm2_test_helpers::for_tests::{mm_ctx_with_custom_db, BCHD_TESTNET_URLS};
 
-    let ctx = MmCtxBuilder::default().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let keypair = key_pair_from_seed("BCH SLP test").unwrap();
 
     let conf = json!({"coin":"BCH","pubtype":0,"p2shtype":5,"mm2":1,"fork_id":"0x40","protocol":{"type":"UTXO"}, "sign_message_prefix": "Bitcoin Signed Message:\n",
@@ -1424,9 +1427,9 @@ pub fn tbch_coin_for_test() -> (MmArc, BchCoin) {
 pub fn bch_coin_for_test() -> BchCoin {
     use common::block_on;
     use crypto::privkey::key_pair_from_seed;
-    use mm2_core::mm_ctx::MmCtxBuilder;
+    use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
 
-    let ctx = MmCtxBuilder::default().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let keypair = key_pair_from_seed("BCH SLP test").unwrap();
 
     let conf = json!({"coin":"BCH","pubtype":0,"p2shtype":5,"mm2":1,"fork_id":"0x40","protocol":{"type":"UTXO"},
@@ -1506,6 +1509,8 @@ mod bch_tests {
         let expected_fee = Some(TxFeeDetails::Utxo(UtxoFeeDetails {
             coin: Some("BCH".into()),
             amount: "0.00001481".parse().unwrap(),
+            fee_rate: None,
+            fee_rate_source: None,
         }));
         assert_eq!(expected_fee, details.fee_details);
 
@@ -0,0 +1,277 @@
+//! Persists the outpoints frozen by the user, so they stay frozen after the coin is re-enabled.
+
+use chain::OutPoint;
+use derive_more::Display;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use primitives::hash::H256;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+pub type FrozenOutpointsResult<T> = MmResult<T, FrozenOutpointsStorageError>;
+
+#[derive(Debug, Display)]
+pub enum FrozenOutpointsStorageError {
+    #[display(fmt = "Error loading frozen outpoints: {}", _0)]
+    ErrorLoading(String),
+    #[display(fmt = "Error saving frozen outpoints: {}", _0)]
+    ErrorSaving(String),
+    #[display(fmt = "Internal error: {}", _0)]
+    Internal(String),
+}
+
+/// The transaction hash is stored in the RPC (reversed) byte order.
+fn outpoint_tx_hash(outpoint: &OutPoint) -> String { outpoint.hash.reversed().to_string() }
+
+fn outpoint_from_stored(tx_hash: &str, index: u32) -> FrozenOutpointsResult<OutPoint> {
+    let hash = H256::from_str(tx_hash)
+        .map_to_mm(|e| FrozenOutpointsStorageError::ErrorLoading(format!("Invalid tx hash '{}': {:?}", tx_hash, e)))?;
+    Ok(OutPoint {
+        hash: hash.reversed(),
+        index,
+    })
+}
+
+pub struct FrozenOutpointsStorage {
+    ctx: MmArc,
+    ticker: String,
+}
+
+impl FrozenOutpointsStorage {
+    pub fn new(ctx: MmArc, ticker: String) -> FrozenOutpointsStorage { FrozenOutpointsStorage { ctx, ticker } }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native_impl {
+    use super::*;
+    use db_common::sqlite::rusqlite::{Connection, Error as SqlError, NO_PARAMS};
+    use std::sync::{Arc, Mutex};
+
+    const CREATE_FROZEN_OUTPOINT_TABLE: &str = "CREATE TABLE IF NOT EXISTS frozen_outpoint (
+        coin VARCHAR(255) NOT NULL,
+        tx_hash VARCHAR(255) NOT NULL,
+        vout INTEGER NOT NULL,
+        PRIMARY KEY (coin, tx_hash, vout)
+    );";
+
+    const INSERT_FROZEN_OUTPOINT: &str =
+        "INSERT OR IGNORE INTO frozen_outpoint (coin, tx_hash, vout) VALUES (?1, ?2, ?3);";
+
+    const DELETE_FROZEN_OUTPOINT: &str = "DELETE FROM frozen_outpoint WHERE coin = ?1 AND tx_hash = ?2 AND vout = ?3;";
+
+    const SELECT_FROZEN_OUTPOINTS: &str = "SELECT tx_hash, vout FROM frozen_outpoint WHERE coin = ?1;";
+
+    impl From<SqlError> for FrozenOutpointsStorageError {
+        fn from(e: SqlError) -> Self { FrozenOutpointsStorageError::Internal(e.to_string()) }
+    }
+
+    impl FrozenOutpointsStorage {
+        fn conn(&self) -> FrozenOutpointsResult<Arc<Mutex<Connection>>> {
+            let conn = self.ctx.sqlite_connection.ok_or(FrozenOutpointsStorageError::Internal(
+                "sqlite_connection is not initialized".to_owned(),
+            ))?;
+            Ok(conn.clone())
+        }
+
+        /// Creates the `frozen_outpoint` table if it doesn't exist yet.
+        /// Should be called once on the coin activation.
+        pub async fn init(&self) -> FrozenOutpointsResult<()> {
+            let conn = self.conn()?;
+            let conn = conn.lock().unwrap();
+            conn.execute(CREATE_FROZEN_OUTPOINT_TABLE, NO_PARAMS)?;
+            Ok(())
+        }
+
+        pub async fn load(&self) -> FrozenOutpointsResult<HashSet<OutPoint>> {
+            let conn = self.conn()?;
+            let conn = conn.lock().unwrap();
+
+            let mut stmt = conn.prepare(SELECT_FROZEN_OUTPOINTS)?;
+            let rows = stmt
+                .query_map(&[self.ticker.as_str()], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()
+                .map_to_mm(|e| FrozenOutpointsStorageError::ErrorLoading(e.to_string()))?;
+            rows.iter()
+                .map(|(tx_hash, index)| outpoint_from_stored(tx_hash, *index))
+                .collect()
+        }
+
+        /// Freezes or unfreezes the given `outpoints` in one transaction.
+        pub async fn set_frozen(&self, outpoints: &[OutPoint], frozen: bool) -> FrozenOutpointsResult<()> {
+            let conn = self.conn()?;
+            let mut conn = conn.lock().unwrap();
+
+            let sql = if frozen {
+                INSERT_FROZEN_OUTPOINT
+            } else {
+                DELETE_FROZEN_OUTPOINT
+            };
+            let transaction = conn.transaction()?;
+            for outpoint in outpoints {
+                let params = [
+                    self.ticker.clone(),
+                    outpoint_tx_hash(outpoint),
+                    outpoint.index.to_string(),
+                ];
+                transaction
+                    .execute(sql, &params)
+                    .map_to_mm(|e| FrozenOutpointsStorageError::ErrorSaving(e.to_string()))?;
+            }
+            transaction
+                .commit()
+                .map_to_mm(|e| FrozenOutpointsStorageError::ErrorSaving(e.to_string()))
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm_impl {
+    use super::*;
+    use crate::CoinsContext;
+    use async_trait::async_trait;
+    use mm2_db::indexed_db::{DbIdentifier, DbInstance, DbTransactionError, DbUpgrader, IndexedDb, IndexedDbBuilder,
+                             InitDbError, InitDbResult, MultiIndex, OnUpgradeResult, TableSignature};
+
+    const DB_NAME: &str = "frozen_outpoints";
+    const DB_VERSION: u32 = 1;
+    const COIN_OUTPOINT_INDEX: &str = "coin_outpoint";
+
+    pub struct FrozenOutpointsDb {
+        inner: IndexedDb,
+    }
+
+    #[async_trait]
+    impl DbInstance for FrozenOutpointsDb {
+        fn db_name() -> &'static str { DB_NAME }
+
+        async fn init(db_id: DbIdentifier) -> InitDbResult<Self> {
+            let inner = IndexedDbBuilder::new(db_id)
+                .with_version(DB_VERSION)
+                .with_table::<FrozenOutpointTable>()
+                .build()
+                .await?;
+            Ok(FrozenOutpointsDb { inner })
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct FrozenOutpointTable {
+        coin: String,
+        tx_hash: String,
+        vout: u32,
+    }
+
+    impl TableSignature for FrozenOutpointTable {
+        fn table_name() -> &'static str { "frozen_outpoint" }
+
+        fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, _new_version: u32) -> OnUpgradeResult<()> {
+            if old_version == 0 {
+                let table = upgrader.create_table(Self::table_name())?;
+                table.create_index("coin", false)?;
+                table.create_multi_index(COIN_OUTPOINT_INDEX, &["coin", "tx_hash", "vout"], true)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl From<DbTransactionError> for FrozenOutpointsStorageError {
+        fn from(e: DbTransactionError) -> Self { FrozenOutpointsStorageError::Internal(e.to_string()) }
+    }
+
+    impl From<InitDbError> for FrozenOutpointsStorageError {
+        fn from(e: InitDbError) -> Self { FrozenOutpointsStorageError::Internal(e.to_string()) }
+    }
+
+    impl FrozenOutpointsStorage {
+        /// The `frozen_outpoint` table is created on the database upgrade.
+        pub async fn init(&self) -> FrozenOutpointsResult<()> { Ok(()) }
+
+        pub async fn load(&self) -> FrozenOutpointsResult<HashSet<OutPoint>> {
+            let coins_ctx = CoinsContext::from_ctx(&self.ctx).map_to_mm(FrozenOutpointsStorageError::Internal)?;
+            let db = coins_ctx.frozen_outpoints_db().await?;
+            let transaction = db.inner.transaction().await?;
+            let table = transaction.table::<FrozenOutpointTable>().await?;
+
+            table
+                .get_items("coin", &self.ticker)
+                .await?
+                .iter()
+                .map(|(_item_id, item)| outpoint_from_stored(&item.tx_hash, item.vout))
+                .collect()
+        }
+
+        /// Freezes or unfreezes the given `outpoints` in one transaction.
+        pub async fn set_frozen(&self, outpoints: &[OutPoint], frozen: bool) -> FrozenOutpointsResult<()> {
+            let coins_ctx = CoinsContext::from_ctx(&self.ctx).map_to_mm(FrozenOutpointsStorageError::Internal)?;
+            let db = coins_ctx.frozen_outpoints_db().await?;
+            let transaction = db.inner.transaction().await?;
+            let table = transaction.table::<FrozenOutpointTable>().await?;
+
+            for outpoint in outpoints {
+                let item = FrozenOutpointTable {
+                    coin: self.ticker.clone(),
+                    tx_hash: outpoint_tx_hash(outpoint),
+                    vout: outpoint.index,
+                };
+                let index_keys = MultiIndex::new(COIN_OUTPOINT_INDEX)
+                    .with_value(&item.coin)?
+                    .with_value(&item.tx_hash)?
+                    .with_value(item.vout)?;
+                if frozen {
+                    table
+                        .add_item_or_ignore_by_unique_multi_index(index_keys, &item)
+                        .await?;
+                } else {
+                    table.delete_item_by_unique_multi_index(index_keys).await?;
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm_impl::FrozenOutpointsDb;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
+
+    #[test]
+    fn test_frozen_outpoints_storage() {
+        let ctx = mm_ctx_with_custom_db();
+
+        let rick = FrozenOutpointsStorage::new(ctx.clone(), "RICK".to_owned());
+        let morty = FrozenOutpointsStorage::new(ctx, "MORTY".to_owned());
+        block_on(rick.init()).unwrap();
+        // The table is created once, but `init` may be called on every activation.
+        block_on(morty.init()).unwrap();
+        let outpoints = [
+            OutPoint {
+                hash: H256::from([1; 32]),
+                index: 0,
+            },
+            OutPoint {
+                hash: H256::from([2; 32]),
+                index: 3,
+            },
+        ];
+
+        block_on(rick.set_frozen(&outpoints, true)).unwrap();
+        // Freezing the same outpoint twice is not an error.
+        block_on(rick.set_frozen(&outpoints[..1], true)).unwrap();
+        block_on(morty.set_frozen(&outpoints[1..], true)).unwrap();
+        assert_eq!(block_on(rick.load()).unwrap(), outpoints.iter().copied().collect());
+
+        block_on(rick.set_frozen(&outpoints[..1], false)).unwrap();
+        assert_eq!(block_on(rick.load()).unwrap(), outpoints[1..].iter().copied().collect());
+        assert_eq!(
+            block_on(morty.load()).unwrap(),
+            outpoints[1..].iter().copied().collect()
+        );
+    }
+}
@@ -116,7 +116,7 @@ impl QtumDelegationOps for QtumCoin {
 
 impl QtumCoin {
     async fn remove_delegation_impl(&self) -> DelegationResult {
-        if self.addr_format().is_segwit() {
+        if self.addr_format().is_witness() {
             return MmError::err(DelegationError::DelegationOpsNotSupported {
                 reason: "Qtum doesn't support delegation for segwit".to_string(),
             });
@@ -219,7 +219,7 @@ impl QtumCoin {
                 amount,
                 staker,
                 am_i_staking,
-                is_staking_supported: !my_address.addr_format.is_segwit(),
+                is_staking_supported: !my_address.addr_format.is_witness(),
             }
             .into(),
         };
@@ -227,7 +227,7 @@ impl QtumCoin {
     }
 
     async fn add_delegation_impl(&self, request: QtumDelegationRequest) -> DelegationResult {
-        if self.addr_format().is_segwit() {
+        if self.addr_format().is_witness() {
             return MmError::err(DelegationError::DelegationOpsNotSupported {
                 reason: "Qtum doesn't support delegation for segwit".to_string(),
             });
@@ -338,6 +338,8 @@ impl QtumCoin {
             kmd_rewards: None,
             transaction_type,
             memo: None,
+            psbt: None,
+            to_name: None,
         })
     }
 
@@ -1581,6 +1581,8 @@ impl From<SlpFeeDetails> for TxFeeDetails {
 impl MmCoin for SlpToken {
     fn is_asset_chain(&self) -> bool { false }
 
+    fn is_watch_only(&self) -> bool { self.platform_coin.is_watch_only() }
+
     fn spawner(&self) -> CoinFutSpawner { CoinFutSpawner::new(&self.conf.abortable_system) }
 
     fn get_raw_transaction(&self, req: RawTransactionRequest) -> RawTransactionFut {
@@ -1709,6 +1711,8 @@ impl MmCoin for SlpToken {
                 kmd_rewards: None,
                 transaction_type: Default::default(),
                 memo: None,
+                psbt: None,
+                to_name: None,
             };
             Ok(details)
         };
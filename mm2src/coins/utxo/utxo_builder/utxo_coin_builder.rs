@@ -1,5 +1,6 @@
 use crate::hd_wallet::{HDAccountsMap, HDAccountsMutex};
-use crate::hd_wallet_storage::{HDWalletCoinStorage, HDWalletStorageError};
+use crate::hd_wallet_storage::{HDAccountStorageItem, HDWalletCoinStorage, HDWalletStorageError};
+use crate::utxo::frozen_outpoints_storage::{FrozenOutpointsStorage, FrozenOutpointsStorageError};
 use crate::utxo::rpc_clients::{ElectrumClient, ElectrumClientImpl, ElectrumRpcRequest, EstimateFeeMethod,
                                UtxoRpcClientEnum};
 use crate::utxo::tx_cache::{UtxoVerboseCacheOps, UtxoVerboseCacheShared};
@@ -9,16 +10,19 @@ use crate::utxo::{output_script, utxo_common, ElectrumBuilderArgs, ElectrumProto
                   RecentlySpentOutPoints, TxFee, UtxoCoinConf, UtxoCoinFields, UtxoHDAccount, UtxoHDWallet,
                   UtxoRpcMode, UtxoSyncStatus, UtxoSyncStatusLoopHandle, DEFAULT_GAP_LIMIT, UTXO_DUST_AMOUNT};
 use crate::{BlockchainNetwork, CoinTransportMetrics, DerivationMethod, HistorySyncState, IguanaPrivKey,
-            PrivKeyBuildPolicy, PrivKeyPolicy, PrivKeyPolicyNotAllowed, RpcClientType, UtxoActivationParams};
+            PrivKeyBuildPolicy, PrivKeyPolicy, PrivKeyPolicyNotAllowed, RpcClientType, UtxoActivationParams,
+            WatchOnlyParams};
 use async_trait::async_trait;
-use chain::TxHashAlgo;
+use bitcrypto::dhash160;
+use chain::{OutPoint, TxHashAlgo};
 use common::custom_futures::repeatable::{Ready, Retry};
 use common::executor::{abortable_queue::AbortableQueue, AbortSettings, AbortableSystem, AbortedError, SpawnAbortable,
                        Timer};
 use common::log::{error, info, LogOnError};
 use common::small_rng;
-use crypto::{Bip32DerPathError, CryptoCtx, CryptoCtxError, GlobalHDAccountArc, HwWalletType, Secp256k1Secret,
-             StandardHDPathError, StandardHDPathToCoin};
+use crypto::{Bip32DerPathError, CryptoCtx, CryptoCtxError, GlobalHDAccountArc, HwWalletType,
+             Secp256k1ExtendedPublicKey, Secp256k1Secret, StandardHDPathError, StandardHDPathToCoin, XPub,
+             XPubConverter};
 use derive_more::Display;
 use futures::channel::mpsc::{channel, unbounded, Receiver as AsyncReceiver, UnboundedReceiver};
 use futures::compat::Future01CompatExt;
@@ -35,6 +39,8 @@ use serde_json::{self as json, Value as Json};
 use spv_validation::conf::SPVConf;
 use spv_validation::helpers_validation::SPVError;
 use spv_validation::storage::{BlockHeaderStorageError, BlockHeaderStorageOps};
+use std::collections::HashSet;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex, Weak};
 
 cfg_native! {
@@ -78,6 +84,10 @@ pub enum UtxoCoinBuildError {
         fmt = "Coin doesn't support Trezor hardware wallet. Please consider adding the 'trezor_coin' field to the coins config"
     )]
     CoinDoesntSupportTrezor,
+    #[display(fmt = "Invalid watch-only account xpub: {}", _0)]
+    InvalidWatchOnlyXpub(String),
+    #[display(fmt = "Invalid watch-only address: {}", _0)]
+    InvalidWatchOnlyAddress(String),
     BlockHeaderStorageError(BlockHeaderStorageError),
     #[display(fmt = "Error {} on getting the height of the latest block from rpc!", _0)]
     CantGetBlockCount(String),
@@ -112,9 +122,16 @@ impl From<AbortedError> for UtxoCoinBuildError {
     fn from(e: AbortedError) -> Self { UtxoCoinBuildError::Internal(e.to_string()) }
 }
 
+impl From<FrozenOutpointsStorageError> for UtxoCoinBuildError {
+    fn from(e: FrozenOutpointsStorageError) -> Self { UtxoCoinBuildError::Internal(e.to_string()) }
+}
+
 #[async_trait]
 pub trait UtxoCoinBuilder:
-    UtxoFieldsWithIguanaSecretBuilder + UtxoFieldsWithGlobalHDBuilder + UtxoFieldsWithHardwareWalletBuilder
+    UtxoFieldsWithIguanaSecretBuilder
+    + UtxoFieldsWithGlobalHDBuilder
+    + UtxoFieldsWithHardwareWalletBuilder
+    + UtxoFieldsWithWatchOnlyBuilder
 {
     type ResultCoin;
     type Error: NotMmError;
@@ -130,6 +147,7 @@ pub trait UtxoCoinBuilder:
                 self.build_utxo_fields_with_global_hd(global_hd_ctx).await
             },
             PrivKeyBuildPolicy::Trezor => self.build_utxo_fields_with_trezor().await,
+            PrivKeyBuildPolicy::WatchOnly(params) => self.build_utxo_fields_watch_only(params).await,
         }
     }
 }
@@ -180,10 +198,12 @@ where
     };
     let key_pair = KeyPair::from_private(private).map_to_mm(|e| UtxoCoinBuildError::Internal(e.to_string()))?;
     let addr_format = builder.address_format()?;
+    let hash = utxo_common::address_hash_from_pubkey(key_pair.public(), &addr_format)
+        .map_to_mm(|e| UtxoCoinBuildError::Internal(e.to_string()))?;
     let my_address = Address {
         prefix: conf.pub_addr_prefix,
         t_addr_prefix: conf.pub_t_addr_prefix,
-        hash: AddressHashEnum::AddressHash(key_pair.public().address_hash()),
+        hash,
         checksum_type: conf.checksum_type,
         hrp: conf.bech32_hrp.clone(),
         addr_format,
@@ -208,6 +228,7 @@ where
     let tx_cache = builder.tx_cache();
     let (block_headers_status_notifier, block_headers_status_watcher) =
         builder.block_header_status_channel(&conf.spv_conf);
+    let frozen_outpoints = builder.frozen_outpoints().await?;
 
     let coin = UtxoCoinFields {
         conf,
@@ -219,6 +240,8 @@ where
         history_sync_state: Mutex::new(initial_history_state),
         tx_cache,
         recently_spent_outpoints: AsyncMutex::new(RecentlySpentOutPoints::new(my_script_pubkey)),
+        frozen_outpoints: Mutex::new(frozen_outpoints),
+        consolidation_loop: Mutex::new(None),
         tx_fee,
         tx_hash_algo,
         check_utxo_maturity,
@@ -281,6 +304,7 @@ pub trait UtxoFieldsWithHardwareWalletBuilder: UtxoCoinBuilderCommonOps {
         let tx_cache = self.tx_cache();
         let (block_headers_status_notifier, block_headers_status_watcher) =
             self.block_header_status_channel(&conf.spv_conf);
+        let frozen_outpoints = self.frozen_outpoints().await?;
 
         let coin = UtxoCoinFields {
             conf,
@@ -292,6 +316,8 @@ pub trait UtxoFieldsWithHardwareWalletBuilder: UtxoCoinBuilderCommonOps {
             history_sync_state: Mutex::new(initial_history_state),
             tx_cache,
             recently_spent_outpoints,
+            frozen_outpoints: Mutex::new(frozen_outpoints),
+            consolidation_loop: Mutex::new(None),
             tx_fee,
             tx_hash_algo,
             check_utxo_maturity,
@@ -337,6 +363,138 @@ pub trait UtxoFieldsWithHardwareWalletBuilder: UtxoCoinBuilderCommonOps {
     }
 }
 
+#[async_trait]
+pub trait UtxoFieldsWithWatchOnlyBuilder: UtxoCoinBuilderCommonOps {
+    async fn build_utxo_fields_watch_only(&self, params: WatchOnlyParams) -> UtxoCoinBuildResult<UtxoCoinFields> {
+        let conf = UtxoConfBuilder::new(self.conf(), self.activation_params(), self.ticker()).build()?;
+
+        let (derivation_method, my_script_pubkey) = match params {
+            WatchOnlyParams::AccountXpub(xpub) => {
+                let hd_wallet = self.watch_only_hd_wallet(&conf, xpub).await?;
+                // As for Trezor, use a default script pubkey.
+                (DerivationMethod::HDWallet(hd_wallet), Bytes::new())
+            },
+            WatchOnlyParams::Address(address) => {
+                let my_address = self.watch_only_address(&conf, &address)?;
+                let my_script_pubkey = output_script(&my_address, ScriptType::P2PKH).to_bytes();
+                (DerivationMethod::SingleAddress(my_address), my_script_pubkey)
+            },
+        };
+
+        // Create an abortable system linked to the `MmCtx` so if the context is stopped via `MmArc::stop`,
+        // all spawned futures related to this `UTXO` coin will be aborted as well.
+        let abortable_system: AbortableQueue = self.ctx().abortable_system.create_subsystem()?;
+
+        let rpc_client = self.rpc_client(abortable_system.create_subsystem()?).await?;
+        let tx_fee = self.tx_fee(&rpc_client).await?;
+        let decimals = self.decimals(&rpc_client).await?;
+        let dust_amount = self.dust_amount();
+
+        let initial_history_state = self.initial_history_state();
+        let tx_hash_algo = self.tx_hash_algo();
+        let check_utxo_maturity = self.check_utxo_maturity();
+        let tx_cache = self.tx_cache();
+        let (block_headers_status_notifier, block_headers_status_watcher) =
+            self.block_header_status_channel(&conf.spv_conf);
+        let frozen_outpoints = self.frozen_outpoints().await?;
+
+        let coin = UtxoCoinFields {
+            conf,
+            decimals,
+            dust_amount,
+            rpc_client,
+            priv_key_policy: PrivKeyPolicy::WatchOnly,
+            derivation_method,
+            history_sync_state: Mutex::new(initial_history_state),
+            tx_cache,
+            recently_spent_outpoints: AsyncMutex::new(RecentlySpentOutPoints::new(my_script_pubkey)),
+            frozen_outpoints: Mutex::new(frozen_outpoints),
+            consolidation_loop: Mutex::new(None),
+            tx_fee,
+            tx_hash_algo,
+            check_utxo_maturity,
+            block_headers_status_notifier,
+            block_headers_status_watcher,
+            abortable_system,
+        };
+        Ok(coin)
+    }
+
+    /// Builds an HD wallet of the account the given `xpub` corresponds to.
+    /// The account is stored under the RIPEMD160(SHA256) of the account pubkey,
+    /// so the known addresses numbers are kept across activations.
+    async fn watch_only_hd_wallet(&self, conf: &UtxoCoinConf, xpub: XPub) -> UtxoCoinBuildResult<UtxoHDWallet> {
+        const ACCOUNT_DEPTH: u8 = 3;
+
+        let xpub = XPubConverter::replace_magic_prefix(xpub)
+            .mm_err(|e| UtxoCoinBuildError::InvalidWatchOnlyXpub(e.to_string()))?;
+        let extended_pubkey = Secp256k1ExtendedPublicKey::from_str(&xpub)
+            .map_to_mm(|e| UtxoCoinBuildError::InvalidWatchOnlyXpub(e.to_string()))?;
+        let attrs = extended_pubkey.attrs();
+        if attrs.depth != ACCOUNT_DEPTH || !attrs.child_number.is_hardened() {
+            let error = "Expected the xpub of the m/purpose'/coin_type'/account' derivation path".to_owned();
+            return MmError::err(UtxoCoinBuildError::InvalidWatchOnlyXpub(error));
+        }
+        let account_id = attrs.child_number.index();
+
+        let address_format = self.address_format()?;
+        let derivation_path = conf
+            .derivation_path
+            .clone()
+            .or_mm_err(|| UtxoConfError::DerivationPathIsNotSet)?;
+
+        let hd_wallet_rmd160 = dhash160(&extended_pubkey.public_key().serialize());
+        let hd_wallet_storage =
+            HDWalletCoinStorage::init_with_rmd160(self.ctx(), self.ticker().to_owned(), hd_wallet_rmd160).await?;
+
+        let mut accounts = utxo_common::load_hd_accounts_from_storage(&hd_wallet_storage, &derivation_path).await?;
+        if !accounts.contains_key(&account_id) {
+            let account_info = HDAccountStorageItem {
+                account_id,
+                account_xpub: xpub,
+                external_addresses_number: 0,
+                internal_addresses_number: 0,
+            };
+            let account = UtxoHDAccount::try_from_storage_item(&derivation_path, &account_info)?;
+            hd_wallet_storage.upload_new_account(account_info).await?;
+            accounts.insert(account_id, account);
+        }
+
+        Ok(UtxoHDWallet {
+            hd_wallet_rmd160,
+            hd_wallet_storage,
+            address_format,
+            derivation_path,
+            accounts: HDAccountsMutex::new(accounts),
+            gap_limit: self.activation_params().gap_limit.unwrap_or(DEFAULT_GAP_LIMIT),
+        })
+    }
+
+    /// Parses the watch-only `address` and checks if it's a P2PKH, P2WPKH or P2TR address of this coin.
+    fn watch_only_address(&self, conf: &UtxoCoinConf, address: &str) -> UtxoCoinBuildResult<Address> {
+        let my_address = utxo_common::address_from_str_with_conf(conf, address)
+            .mm_err(|e| UtxoCoinBuildError::InvalidWatchOnlyAddress(e.to_string()))?;
+        let is_supported = match my_address.addr_format {
+            UtxoAddressFormat::Standard | UtxoAddressFormat::CashAddress { .. } => {
+                my_address.prefix == conf.pub_addr_prefix && my_address.t_addr_prefix == conf.pub_t_addr_prefix
+            },
+            // P2WSH addresses are parsed as `Segwit` too, but have 32 bytes hashes.
+            UtxoAddressFormat::Segwit => {
+                conf.segwit && my_address.hrp == conf.bech32_hrp && my_address.hash.is_address_hash()
+            },
+            UtxoAddressFormat::Taproot => conf.segwit && my_address.hrp == conf.bech32_hrp,
+        };
+        if !is_supported {
+            let error = format!(
+                "'{}' is not a P2PKH, P2WPKH or P2TR address of {}",
+                address, conf.ticker
+            );
+            return MmError::err(UtxoCoinBuildError::InvalidWatchOnlyAddress(error));
+        }
+        Ok(my_address)
+    }
+}
+
 #[async_trait]
 pub trait UtxoCoinBuilderCommonOps {
     fn ctx(&self) -> &MmArc;
@@ -355,9 +513,9 @@ pub trait UtxoCoinBuilderCommonOps {
 
         let mut address_format = match format_from_req {
             Some(from_req) => {
-                if from_req.is_segwit() != format_from_conf.is_segwit() {
+                if from_req.is_witness() != format_from_conf.is_witness() {
                     let error = format!(
-                        "Both conf {:?} and request {:?} must be either Segwit or Standard/CashAddress",
+                        "Both conf {:?} and request {:?} must be either Segwit/Taproot or Standard/CashAddress",
                         format_from_conf, from_req
                     );
                     return MmError::err(UtxoCoinBuildError::from(UtxoConfError::InvalidAddressFormat(error)));
@@ -379,9 +537,11 @@ pub trait UtxoCoinBuilderCommonOps {
         }
 
         let is_segwit_in_conf = self.conf()["segwit"].as_bool().unwrap_or(false);
-        if address_format.is_segwit() && (!is_segwit_in_conf || self.conf()["bech32_hrp"].is_null()) {
-            let error =
-                "Cannot use Segwit address format for coin without segwit support or bech32_hrp in config".to_owned();
+        if address_format.is_witness() && (!is_segwit_in_conf || self.conf()["bech32_hrp"].is_null()) {
+            let error = format!(
+                "Cannot use {} address format for coin without segwit support or bech32_hrp in config",
+                address_format
+            );
             return MmError::err(UtxoCoinBuildError::from(UtxoConfError::InvalidAddressFormat(error)));
         }
         Ok(address_format)
@@ -442,6 +602,13 @@ pub trait UtxoCoinBuilderCommonOps {
         }
     }
 
+    /// Loads the outpoints frozen by the user before the coin was re-enabled.
+    async fn frozen_outpoints(&self) -> UtxoCoinBuildResult<HashSet<OutPoint>> {
+        let storage = FrozenOutpointsStorage::new(self.ctx().clone(), self.ticker().to_owned());
+        storage.init().await?;
+        Ok(storage.load().await?)
+    }
+
     async fn rpc_client(&self, abortable_system: AbortableQueue) -> UtxoCoinBuildResult<UtxoRpcClientEnum> {
         match self.activation_params().mode.clone() {
             UtxoRpcMode::Native => {
@@ -1,6 +1,6 @@
 use crate::utxo::rpc_clients::EstimateFeeMode;
-use crate::utxo::{parse_hex_encoded_u32, UtxoCoinConf, DEFAULT_DYNAMIC_FEE_VOLATILITY_PERCENT, KMD_MTP_BLOCK_COUNT,
-                  MATURE_CONFIRMATIONS_DEFAULT};
+use crate::utxo::{parse_hex_encoded_u32, UtxoCoinConf, UtxoFeePriority, DEFAULT_DYNAMIC_FEE_VOLATILITY_PERCENT,
+                  KMD_MTP_BLOCK_COUNT, MATURE_CONFIRMATIONS_DEFAULT};
 use crate::UtxoActivationParams;
 use bitcrypto::ChecksumType;
 use crypto::{Bip32Error, StandardHDPathToCoin};
@@ -32,6 +32,7 @@ pub enum UtxoConfError {
     InvalidVersionGroupId(String),
     InvalidAddressFormat(String),
     InvalidDecimals(String),
+    InvalidSignatureVersion(String),
 }
 
 impl From<Bip32Error> for UtxoConfError {
@@ -70,7 +71,7 @@ impl<'a> UtxoConfBuilder<'a> {
         let tx_fee_volatility_percent = self.tx_fee_volatility_percent();
         let version_group_id = self.version_group_id(tx_version, overwintered)?;
         let consensus_branch_id = self.consensus_branch_id(tx_version)?;
-        let signature_version = self.signature_version();
+        let signature_version = self.signature_version()?;
         let fork_id = self.fork_id();
 
         // should be sufficient to detect zcash by overwintered flag
@@ -88,6 +89,8 @@ impl<'a> UtxoConfBuilder<'a> {
         let mtp_block_count = self.mtp_block_count();
         let estimate_fee_mode = self.estimate_fee_mode();
         let estimate_fee_blocks = self.estimate_fee_blocks();
+        let swap_fee_priority = self.swap_fee_priority();
+        let swap_txs_rbf = self.conf["swap_txs_rbf"].as_bool().unwrap_or(false);
         let trezor_coin = self.trezor_coin();
         let derivation_path = self.derivation_path()?;
         let avg_blocktime = self.avg_blocktime();
@@ -123,6 +126,8 @@ impl<'a> UtxoConfBuilder<'a> {
             estimate_fee_mode,
             mature_confirmations,
             estimate_fee_blocks,
+            swap_fee_priority,
+            swap_txs_rbf,
             trezor_coin,
             spv_conf,
             derivation_path,
@@ -226,13 +231,20 @@ impl<'a> UtxoConfBuilder<'a> {
         Ok(consensus_branch_id)
     }
 
-    fn signature_version(&self) -> SignatureVersion {
+    /// The taproot signature version is derived from the P2TR address format only,
+    /// since the swap and other generic signing paths can't compute the taproot sighash.
+    fn signature_version(&self) -> UtxoConfResult<SignatureVersion> {
         let default_signature_version = if self.ticker == "BCH" || self.fork_id() != 0 {
             SignatureVersion::ForkId
         } else {
             SignatureVersion::Base
         };
-        json::from_value(self.conf["signature_version"].clone()).unwrap_or(default_signature_version)
+        match json::from_value(self.conf["signature_version"].clone()).unwrap_or(default_signature_version) {
+            SignatureVersion::Taproot => MmError::err(UtxoConfError::InvalidSignatureVersion(
+                "'taproot' signature_version is not supported, use the 'taproot' address format instead".to_owned(),
+            )),
+            signature_version => Ok(signature_version),
+        }
     }
 
     fn fork_id(&self) -> u32 {
@@ -282,6 +294,10 @@ impl<'a> UtxoConfBuilder<'a> {
 
     fn estimate_fee_blocks(&self) -> u32 { json::from_value(self.conf["estimate_fee_blocks"].clone()).unwrap_or(1) }
 
+    fn swap_fee_priority(&self) -> Option<UtxoFeePriority> {
+        json::from_value(self.conf["swap_fee_priority"].clone()).unwrap_or(None)
+    }
+
     fn trezor_coin(&self) -> Option<String> { self.conf["trezor_coin"].as_str().map(|coin| coin.to_string()) }
 
     fn spv_conf(&self) -> UtxoConfResult<Option<SPVConf>> {
@@ -67,6 +67,8 @@ pub const DEFAULT_FEE_VOUT: usize = 0;
 pub const DEFAULT_SWAP_TX_SPEND_SIZE: u64 = 305;
 pub const DEFAULT_SWAP_VOUT: usize = 0;
 pub const DEFAULT_SWAP_VIN: usize = 0;
+/// The maximum input sequence number that signals opt-in Replace-By-Fee according to BIP-125.
+pub const SEQUENCE_RBF: u32 = SEQUENCE_FINAL - 2;
 const MIN_BTC_TRADING_VOL: &str = "0.00777";
 
 macro_rules! true_or {
@@ -95,13 +97,26 @@ pub struct UtxoMergeParams {
     max_merge_at_once: usize,
 }
 
+/// Returns the fee rate of the swap transactions and the rest transactions generated by the coin itself.
+/// It's estimated for the `swap_fee_priority` if configured.
 pub async fn get_tx_fee(coin: &UtxoCoinFields) -> UtxoRpcResult<ActualTxFee> {
+    let conf = &coin.conf;
+    let target_blocks = match conf.swap_fee_priority {
+        Some(priority) => priority.target_blocks(),
+        None => conf.estimate_fee_blocks,
+    };
+    get_tx_fee_for_target(coin, target_blocks).await
+}
+
+/// Estimates the fee rate for the confirmation within `target_blocks` if the coin is configured to use the dynamic fee.
+/// Returns the fixed fee rate otherwise.
+pub async fn get_tx_fee_for_target(coin: &UtxoCoinFields, target_blocks: u32) -> UtxoRpcResult<ActualTxFee> {
     let conf = &coin.conf;
     match &coin.tx_fee {
         TxFee::Dynamic(method) => {
             let fee = coin
                 .rpc_client
-                .estimate_fee_sat(coin.decimals, method, &conf.estimate_fee_mode, conf.estimate_fee_blocks)
+                .estimate_fee_sat(coin.decimals, method, &conf.estimate_fee_mode, target_blocks)
                 .compat()
                 .await?;
             Ok(ActualTxFee::Dynamic(fee))
@@ -644,6 +659,7 @@ pub fn addresses_from_script<T: UtxoCommonOps>(coin: &T, script: &Script) -> Res
                 ),
                 ScriptType::P2WPKH => (conf.pub_addr_prefix, conf.pub_t_addr_prefix, UtxoAddressFormat::Segwit),
                 ScriptType::P2WSH => (conf.pub_addr_prefix, conf.pub_t_addr_prefix, UtxoAddressFormat::Segwit),
+                ScriptType::P2TR => (conf.pub_addr_prefix, conf.pub_t_addr_prefix, UtxoAddressFormat::Taproot),
             };
 
             Address {
@@ -672,6 +688,11 @@ where
 }
 
 pub fn address_from_str_unchecked(coin: &UtxoCoinFields, address: &str) -> MmResult<Address, AddrFromStrError> {
+    address_from_str_with_conf(&coin.conf, address)
+}
+
+/// Parses the `address` in any of the formats supported by the coin `conf` without checking its prefixes.
+pub fn address_from_str_with_conf(conf: &UtxoCoinConf, address: &str) -> MmResult<Address, AddrFromStrError> {
     let mut errors = Vec::with_capacity(3);
 
     match Address::from_str(address) {
@@ -681,9 +702,9 @@ pub fn address_from_str_unchecked(coin: &UtxoCoinFields, address: &str) -> MmRes
 
     match Address::from_segwitaddress(
         address,
-        coin.conf.checksum_type,
-        coin.conf.pub_addr_prefix,
-        coin.conf.pub_t_addr_prefix,
+        conf.checksum_type,
+        conf.pub_addr_prefix,
+        conf.pub_t_addr_prefix,
     ) {
         Ok(segwit) => return Ok(segwit),
         Err(e) => errors.push(e),
@@ -691,10 +712,10 @@ pub fn address_from_str_unchecked(coin: &UtxoCoinFields, address: &str) -> MmRes
 
     match Address::from_cashaddress(
         address,
-        coin.conf.checksum_type,
-        coin.conf.pub_addr_prefix,
-        coin.conf.p2sh_addr_prefix,
-        coin.conf.pub_t_addr_prefix,
+        conf.checksum_type,
+        conf.pub_addr_prefix,
+        conf.p2sh_addr_prefix,
+        conf.pub_t_addr_prefix,
     ) {
         Ok(cashaddress) => return Ok(cashaddress),
         Err(e) => errors.push(e),
@@ -708,6 +729,8 @@ pub fn my_public_key(coin: &UtxoCoinFields) -> Result<&Public, MmError<Unexpecte
         PrivKeyPolicy::KeyPair(ref key_pair) => Ok(key_pair.public()),
         // Hardware Wallets requires BIP39/BIP44 derivation path to extract a public key.
         PrivKeyPolicy::Trezor => MmError::err(UnexpectedDerivationMethod::ExpectedSingleAddress),
+        // A watch-only wallet is built from an address or an account xpub that don't reveal the public key.
+        PrivKeyPolicy::WatchOnly => MmError::err(UnexpectedDerivationMethod::WatchOnlyPubkeyIsUnknown),
     }
 }
 
@@ -733,6 +756,65 @@ where
     Box::new(fut.boxed().compat().map(|tx| tx.into()))
 }
 
+/// Returns the sequence of the swap spend or refund input.
+/// `SEQUENCE_RBF` still enables the lock time required by the refunds.
+fn swap_spend_sequence(coin: &UtxoCoinFields, default_sequence: u32) -> u32 {
+    if coin.conf.swap_txs_rbf {
+        SEQUENCE_RBF
+    } else {
+        default_sequence
+    }
+}
+
+/// The estimated size of the data unlocking every input of the transaction.
+/// It's used to calculate the fee of the transactions spending the outputs of non-standard scripts,
+/// e.g. multisig ones, when it can't be derived from the address format.
+#[derive(Clone, Copy, Debug)]
+pub enum InputUnlockingSize {
+    /// The size of the `script_sig` including its length prefix.
+    ScriptSig(usize),
+    /// The size of the serialized `script_witness` including the number of the witness items.
+    Witness(usize),
+}
+
+impl InputUnlockingSize {
+    /// The unlocking data of an input spending a P2SH or (if `is_witness`) P2WSH multisig output:
+    /// the dummy element consumed by `OP_CHECKMULTISIG`, the `required` signatures and the multisig script itself.
+    pub fn multisig(required: usize, script_len: usize, is_witness: bool) -> InputUnlockingSize {
+        let compact_size_len = |len: usize| if len < 253 { 1 } else { 3 };
+        // Every signature is pushed along with its sighash type.
+        let signatures_len = required * (2 + MAX_DER_SIGNATURE_LEN);
+        if is_witness {
+            let witness_len = 1 + 1 + signatures_len + compact_size_len(script_len) + script_len;
+            InputUnlockingSize::Witness(witness_len)
+        } else {
+            let push_script_len = match script_len {
+                len if len < Opcode::OP_PUSHDATA1 as usize => 1,
+                len if len < 0x100 => 2,
+                _ => 3,
+            };
+            let script_sig_len = 1 + signatures_len + push_script_len + script_len;
+            InputUnlockingSize::ScriptSig(compact_size_len(script_sig_len) + script_sig_len)
+        }
+    }
+
+    /// Returns the virtual size of the `tx` that has all of the inputs unlocked.
+    /// The `tx` is expected to have the empty `script_sig` and `script_witness` of every input.
+    pub fn tx_size_in_v_bytes(&self, tx: &UtxoTx) -> usize {
+        let transaction_bytes = serialize(tx);
+        match self {
+            // 1 byte of the empty `script_sig` length is already counted.
+            InputUnlockingSize::ScriptSig(size) => transaction_bytes.len() + tx.inputs().len() * (size - 1),
+            InputUnlockingSize::Witness(size) => {
+                let base_size = transaction_bytes.len();
+                // 2 additional bytes for the marker and the flag.
+                let total_size = transaction_bytes.len() + 2 + tx.inputs().len() * size;
+                ((0.75 * base_size as f64) + (0.25 * total_size as f64)) as usize
+            },
+        }
+    }
+}
+
 pub fn tx_size_in_v_bytes(from_addr_format: &UtxoAddressFormat, tx: &UtxoTx) -> usize {
     let transaction_bytes = serialize(tx);
     // 2 bytes are used to indicate the length of signature and pubkey
@@ -747,15 +829,45 @@ pub fn tx_size_in_v_bytes(from_addr_format: &UtxoAddressFormat, tx: &UtxoTx) ->
             let total_size = transaction_bytes.len() + 4 + tx.inputs().len() * (additional_len + 1);
             ((0.75 * base_size as f64) + (0.25 * total_size as f64)) as usize
         },
+        UtxoAddressFormat::Taproot => {
+            let base_size = transaction_bytes.len();
+            // 2 additional bytes for the marker and the flag, and 2 additional bytes for every input in the witness
+            // to indicate the number of witness items and the length of the Schnorr signature
+            let total_size = transaction_bytes.len() + 2 + tx.inputs().len() * (2 + SCHNORR_SIGNATURE_LEN);
+            ((0.75 * base_size as f64) + (0.25 * total_size as f64)) as usize
+        },
         _ => transaction_bytes.len() + tx.inputs().len() * additional_len,
     }
 }
 
+/// Calculates the fee of a transaction which virtual size is `v_size` bytes.
+pub fn calc_tx_fee(actual_tx_fee: &ActualTxFee, v_size: u64) -> u64 {
+    match actual_tx_fee {
+        ActualTxFee::Dynamic(f) => (f * v_size) / KILO_BYTE,
+        ActualTxFee::FixedPerKb(f) => {
+            let v_size_kb = if v_size % KILO_BYTE == 0 {
+                v_size / KILO_BYTE
+            } else {
+                v_size / KILO_BYTE + 1
+            };
+            f * v_size_kb
+        },
+    }
+}
+
 pub struct UtxoTxBuilder<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> {
     coin: &'a T,
     from: Option<Address>,
+    /// The script the change is sent to. Defaults to the script of the `from` address.
+    change_script: Option<Script>,
+    /// Overrides the input size derived from the `from` address format.
+    input_unlocking_size: Option<InputUnlockingSize>,
     /// The available inputs that *can* be included in the resulting tx
     available_inputs: Vec<UnspentInfo>,
+    /// Whether all of the available inputs *must* be included in the resulting tx.
+    spend_all_available_inputs: bool,
+    /// The sequence number of the inputs selected from the available ones.
+    sequence: u32,
     fee_policy: FeePolicy,
     fee: Option<ActualTxFee>,
     gas_fee: Option<u64>,
@@ -774,7 +886,11 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
             tx: coin.as_ref().transaction_preimage(),
             coin,
             from: coin.as_ref().derivation_method.single_addr().cloned(),
+            change_script: None,
+            input_unlocking_size: None,
             available_inputs: vec![],
+            spend_all_available_inputs: false,
+            sequence: SEQUENCE_FINAL,
             fee_policy: FeePolicy::SendExact,
             fee: None,
             gas_fee: None,
@@ -792,6 +908,20 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
         self
     }
 
+    /// Sends the change to the given script instead of the script of the `from` address,
+    /// e.g. to the P2SH script of a multisig wallet as it can't be derived from the address.
+    pub fn with_change_script(mut self, script: Script) -> Self {
+        self.change_script = Some(script);
+        self
+    }
+
+    /// Estimates the fee using the given size of the input unlocking data instead of the one derived from
+    /// the `from` address format. It's required to spend the outputs of non-standard scripts, e.g. multisig ones.
+    pub fn with_input_unlocking_size(mut self, size: InputUnlockingSize) -> Self {
+        self.input_unlocking_size = Some(size);
+        self
+    }
+
     pub fn with_dust(mut self, dust_amount: u64) -> Self {
         self.dust = Some(dust_amount);
         self
@@ -816,6 +946,18 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
         self
     }
 
+    /// Makes the builder spend all of the available inputs instead of stopping once the outputs are covered.
+    pub fn spend_all_available_inputs(mut self) -> Self {
+        self.spend_all_available_inputs = true;
+        self
+    }
+
+    /// Signals that the resulting tx can be replaced by a tx paying a higher fee (BIP-125).
+    pub fn signal_rbf(mut self) -> Self {
+        self.sequence = SEQUENCE_RBF;
+        self
+    }
+
     pub fn add_outputs(mut self, outputs: impl IntoIterator<Item = TransactionOutput>) -> Self {
         self.tx.outputs.extend(outputs);
         self
@@ -845,23 +987,12 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
         from_addr_format: &UtxoAddressFormat,
         actual_tx_fee: &ActualTxFee,
     ) -> bool {
-        self.tx_fee = match &actual_tx_fee {
-            ActualTxFee::Dynamic(f) => {
-                let transaction = UtxoTx::from(self.tx.clone());
-                let v_size = tx_size_in_v_bytes(from_addr_format, &transaction);
-                (f * v_size as u64) / KILO_BYTE
-            },
-            ActualTxFee::FixedPerKb(f) => {
-                let transaction = UtxoTx::from(self.tx.clone());
-                let v_size = tx_size_in_v_bytes(from_addr_format, &transaction) as u64;
-                let v_size_kb = if v_size % KILO_BYTE == 0 {
-                    v_size / KILO_BYTE
-                } else {
-                    v_size / KILO_BYTE + 1
-                };
-                f * v_size_kb
-            },
-        };
+        let transaction = UtxoTx::from(self.tx.clone());
+        let v_size = match self.input_unlocking_size {
+            Some(ref size) => size.tx_size_in_v_bytes(&transaction),
+            None => tx_size_in_v_bytes(from_addr_format, &transaction),
+        } as u64;
+        self.tx_fee = calc_tx_fee(actual_tx_fee, v_size);
 
         match self.fee_policy {
             FeePolicy::SendExact => {
@@ -925,7 +1056,10 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
             .from
             .clone()
             .or_mm_err(|| GenerateTxError::Internal("'from' address is not specified".to_owned()))?;
-        let change_script_pubkey = output_script(&from, ScriptType::P2PKH).to_bytes();
+        let change_script_pubkey = match self.change_script {
+            Some(ref script) => script.to_bytes(),
+            None => output_script(&from, ScriptType::P2PKH).to_bytes(),
+        };
 
         let actual_tx_fee = match self.fee {
             Some(fee) => fee,
@@ -971,13 +1105,14 @@ impl<'a, T: AsRef<UtxoCoinFields> + UtxoTxGenerationOps> UtxoTxBuilder<'a, T> {
         for utxo in self.available_inputs.clone() {
             self.tx.inputs.push(UnsignedTransactionInput {
                 previous_output: utxo.outpoint,
-                sequence: SEQUENCE_FINAL,
+                sequence: self.sequence,
                 amount: utxo.value,
                 witness: vec![],
             });
             self.sum_inputs += utxo.value;
 
-            if self.update_fee_and_check_completeness(&from.addr_format, &actual_tx_fee) {
+            let is_complete = self.update_fee_and_check_completeness(&from.addr_format, &actual_tx_fee);
+            if is_complete && !self.spend_all_available_inputs {
                 break;
             }
         }
@@ -1311,7 +1446,7 @@ pub fn send_maker_spends_taker_payment<T: UtxoCommonOps + SwapOps>(coin: T, args
             redeem_script,
             outputs: vec![output],
             script_data,
-            sequence: SEQUENCE_FINAL,
+            sequence: swap_spend_sequence(coin.as_ref(), SEQUENCE_FINAL),
             lock_time: time_lock,
             keypair: &key_pair,
         };
@@ -1538,7 +1673,7 @@ pub fn send_taker_spends_maker_payment<T: UtxoCommonOps + SwapOps>(coin: T, args
             redeem_script,
             outputs: vec![output],
             script_data,
-            sequence: SEQUENCE_FINAL,
+            sequence: swap_spend_sequence(coin.as_ref(), SEQUENCE_FINAL),
             lock_time: time_lock,
             keypair: &key_pair,
         };
@@ -1595,7 +1730,7 @@ pub fn send_taker_refunds_payment<T: UtxoCommonOps + SwapOps>(coin: T, args: Ref
             redeem_script,
             outputs: vec![output],
             script_data,
-            sequence: SEQUENCE_FINAL - 1,
+            sequence: swap_spend_sequence(coin.as_ref(), SEQUENCE_FINAL - 1),
             lock_time: time_lock,
             keypair: &key_pair,
         };
@@ -1669,7 +1804,7 @@ pub fn send_maker_refunds_payment<T: UtxoCommonOps + SwapOps>(coin: T, args: Ref
             redeem_script,
             outputs: vec![output],
             script_data,
-            sequence: SEQUENCE_FINAL - 1,
+            sequence: swap_spend_sequence(coin.as_ref(), SEQUENCE_FINAL - 1),
             lock_time: time_lock,
             keypair: &key_pair,
         };
@@ -2471,6 +2606,7 @@ pub fn display_priv_key(coin: &UtxoCoinFields) -> Result<String, String> {
     match coin.priv_key_policy {
         PrivKeyPolicy::KeyPair(ref key_pair) => Ok(key_pair.private().to_string()),
         PrivKeyPolicy::Trezor => ERR!("'display_priv_key' doesn't support Hardware Wallets"),
+        PrivKeyPolicy::WatchOnly => ERR!("'display_priv_key' doesn't support watch-only wallets"),
     }
 }
 
@@ -2488,6 +2624,8 @@ pub fn min_trading_vol(coin: &UtxoCoinFields) -> MmNumber {
 
 pub fn is_asset_chain(coin: &UtxoCoinFields) -> bool { coin.conf.asset_chain }
 
+pub fn is_watch_only(coin: &UtxoCoinFields) -> bool { coin.priv_key_policy.is_watch_only() }
+
 pub async fn get_raw_transaction(coin: &UtxoCoinFields, req: RawTransactionRequest) -> RawTransactionResult {
     let hash = H256Json::from_str(&req.tx_hash).map_to_mm(|e| RawTransactionError::InvalidHashError(e.to_string()))?;
     let hex = coin
@@ -2543,7 +2681,7 @@ where
 {
     match coin.derivation_method() {
         DerivationMethod::SingleAddress(my_address) => get_withdraw_iguana_sender(coin, req, my_address),
-        DerivationMethod::HDWallet(hd_wallet) => get_withdraw_hd_sender(coin, req, hd_wallet).await,
+        DerivationMethod::HDWallet(hd_wallet) => get_withdraw_hd_sender(coin, req.from.as_ref(), hd_wallet).await,
     }
 }
 
@@ -2567,19 +2705,22 @@ pub fn get_withdraw_iguana_sender<T: UtxoCommonOps>(
     })
 }
 
+/// Returns the HD wallet address that is specified by [`WithdrawRequest::from`].
+/// This function is not UTXO specific and is also used by ETH/ERC20 coins activated with an HD wallet.
 pub async fn get_withdraw_hd_sender<T>(
     coin: &T,
-    req: &WithdrawRequest,
+    from: Option<&WithdrawFrom>,
     hd_wallet: &T::HDWallet,
-) -> MmResult<WithdrawSenderAddress<Address, Public>, WithdrawError>
+) -> MmResult<WithdrawSenderAddress<T::Address, T::Pubkey>, WithdrawError>
 where
-    T: HDWalletCoinOps<Address = Address, Pubkey = Public> + Sync,
+    T: HDWalletCoinOps + Sync,
+    T::Address: std::fmt::Display,
 {
     let HDAccountAddressId {
         account_id,
         chain,
         address_id,
-    } = match req.from.clone().or_mm_err(|| WithdrawError::FromAddressNotFound)? {
+    } = match from.cloned().or_mm_err(|| WithdrawError::FromAddressNotFound)? {
         WithdrawFrom::AddressId(id) => id,
         WithdrawFrom::DerivationPath { derivation_path } => {
             let derivation_path = StandardHDPath::from_str(&derivation_path)
@@ -2634,6 +2775,9 @@ pub fn convert_to_address<T: UtxoCommonOps>(coin: &T, from: &str, to_address_for
                 None => ERR!("Cannot convert to a segwit address for a coin with no bech32_hrp in config"),
             }
         },
+        UtxoAddressFormat::Taproot => {
+            ERR!("Cannot convert to a taproot address as it's derived from the public key rather than its hash")
+        },
         UtxoAddressFormat::CashAddress { network, .. } => Ok(try_s!(from_address
             .to_cashaddress(
                 &network,
@@ -3198,6 +3342,8 @@ pub async fn tx_details_by_hash<T: UtxoCommonOps>(
     let fee_details = UtxoFeeDetails {
         coin: Some(coin.as_ref().conf.ticker.clone()),
         amount: fee,
+        fee_rate: None,
+        fee_rate_source: None,
     };
 
     Ok(TransactionDetails {
@@ -3217,6 +3363,8 @@ pub async fn tx_details_by_hash<T: UtxoCommonOps>(
         kmd_rewards,
         transaction_type: Default::default(),
         memo: None,
+        psbt: None,
+        to_name: None,
     })
 }
 
@@ -3248,6 +3396,24 @@ where
     Ok(tx)
 }
 
+/// Loads the transaction with the given `tx_hash`, e.g. the one an input spends an output of.
+pub async fn get_prev_tx<T>(coin: &T, tx_hash: &H256) -> UtxoRpcResult<UtxoTx>
+where
+    T: AsRef<UtxoCoinFields>,
+{
+    let tx_hash: H256Json = tx_hash.reversed().into();
+    let tx_bytes = coin
+        .as_ref()
+        .rpc_client
+        .get_transaction_bytes(&tx_hash)
+        .compat()
+        .await?;
+    let mut tx: UtxoTx = deserialize(tx_bytes.as_slice())
+        .map_to_mm(|e| UtxoRpcError::InvalidResponse(format!("Error deserializing {}: {}", tx_hash, e)))?;
+    tx.tx_hash_algo = coin.as_ref().tx_hash_algo;
+    Ok(tx)
+}
+
 /// This function is used when the transaction details were calculated without considering the KMD rewards.
 /// We know that [`TransactionDetails::fee`] was calculated by `fee = input_amount - output_amount`,
 /// where `output_amount = actual_output_amount + kmd_rewards` or `actual_output_amount = output_amount - kmd_rewards`.
@@ -3281,6 +3447,8 @@ where
         tx_details.fee_details = Some(TxFeeDetails::Utxo(UtxoFeeDetails {
             coin: Some(coin.as_ref().conf.ticker.clone()),
             amount: actual_fee_amount,
+            fee_rate: None,
+            fee_rate_source: None,
         }));
     }
 
@@ -3549,7 +3717,7 @@ pub fn coin_protocol_info<T: UtxoCommonOps>(coin: &T) -> Vec<u8> {
 pub fn is_coin_protocol_supported<T: UtxoCommonOps>(coin: &T, info: &Option<Vec<u8>>) -> bool {
     match info {
         Some(format) => rmp_serde::from_read_ref::<_, UtxoAddressFormat>(format).is_ok(),
-        None => !coin.addr_format().is_segwit(),
+        None => !coin.addr_format().is_witness(),
     }
 }
 
@@ -3772,16 +3940,21 @@ pub fn address_from_raw_pubkey(
     hrp: Option<String>,
     addr_format: UtxoAddressFormat,
 ) -> Result<Address, String> {
+    let hash = try_s!(address_hash_from_pubkey(
+        &try_s!(Public::from_slice(pub_key)),
+        &addr_format
+    ));
     Ok(Address {
         t_addr_prefix,
         prefix,
-        hash: try_s!(Public::from_slice(pub_key)).address_hash().into(),
+        hash,
         checksum_type,
         hrp,
         addr_format,
     })
 }
 
+/// Panics if the `addr_format` is [`UtxoAddressFormat::Taproot`] and `pub_key` is not a valid secp256k1 point.
 pub fn address_from_pubkey(
     pub_key: &Public,
     prefix: u8,
@@ -3790,16 +3963,29 @@ pub fn address_from_pubkey(
     hrp: Option<String>,
     addr_format: UtxoAddressFormat,
 ) -> Address {
+    let hash = address_hash_from_pubkey(pub_key, &addr_format).expect("Expected a valid public key");
     Address {
         t_addr_prefix,
         prefix,
-        hash: pub_key.address_hash().into(),
+        hash,
         checksum_type,
         hrp,
         addr_format,
     }
 }
 
+/// Returns the hash the address of the given format is built from:
+/// the taproot output key for P2TR addresses and `ripemd160(sha256(pub_key))` for the rest.
+pub fn address_hash_from_pubkey(
+    pub_key: &Public,
+    addr_format: &UtxoAddressFormat,
+) -> Result<AddressHashEnum, keys::Error> {
+    match addr_format {
+        UtxoAddressFormat::Taproot => Ok(AddressHashEnum::WitnessScriptHash(pub_key.taproot_output_key()?)),
+        _ => Ok(pub_key.address_hash().into()),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 #[cfg_attr(test, mockable)]
 pub fn validate_payment<T: UtxoCommonOps>(
@@ -4100,11 +4286,26 @@ where
 }
 
 /// [`GetUtxoListOps::get_all_unspent_ordered_list`] implementation.
-/// Returns available mature and immature unspents in ascending
+/// Returns available mature and immature unspents in ascending order excluding the frozen ones
 /// + `RecentlySpentOutPoints` MutexGuard for further interaction (e.g. to add new transaction to it).
 pub async fn get_all_unspent_ordered_list<'a, T: UtxoCommonOps>(
     coin: &'a T,
     address: &Address,
+) -> UtxoRpcResult<(Vec<UnspentInfo>, RecentlySpentOutPointsGuard<'a>)> {
+    let (unspents, recently_spent) = get_all_unspent_ordered_list_with_frozen(coin, address).await?;
+    Ok((exclude_frozen_unspents(coin, unspents), recently_spent))
+}
+
+/// Returns available mature and immature unspents in ascending order including the frozen ones
+/// + `RecentlySpentOutPoints` MutexGuard for further interaction (e.g. to add new transaction to it).
+///
+/// # Important
+///
+/// The frozen unspents must never be spent.
+/// Consider using [`GetUtxoListOps::get_all_unspent_ordered_list`] to generate transactions.
+pub async fn get_all_unspent_ordered_list_with_frozen<'a, T: UtxoCommonOps>(
+    coin: &'a T,
+    address: &Address,
 ) -> UtxoRpcResult<(Vec<UnspentInfo>, RecentlySpentOutPointsGuard<'a>)> {
     let decimals = coin.as_ref().decimals;
     let unspents = coin
@@ -4136,11 +4337,23 @@ pub async fn get_all_unspent_ordered_map<T: UtxoCommonOps>(
     let recently_spent = coin.as_ref().recently_spent_outpoints.lock().await;
     for (_address, unspents) in unspents_map.iter_mut() {
         let unordered_unspents = recently_spent.replace_spent_outputs_with_cache(unspents.iter().cloned().collect());
-        *unspents = sort_dedup_unspents(unordered_unspents);
+        *unspents = exclude_frozen_unspents(coin, sort_dedup_unspents(unordered_unspents));
     }
     Ok((unspents_map, recently_spent))
 }
 
+/// Filters out the unspents frozen by the user.
+pub fn exclude_frozen_unspents<T: AsRef<UtxoCoinFields>>(coin: &T, unspents: Vec<UnspentInfo>) -> Vec<UnspentInfo> {
+    let frozen = coin.as_ref().frozen_outpoints.lock().unwrap();
+    if frozen.is_empty() {
+        return unspents;
+    }
+    unspents
+        .into_iter()
+        .filter(|unspent| !frozen.contains(&unspent.outpoint))
+        .collect()
+}
+
 /// Increase the given `dynamic_fee` according to the fee approximation `stage` using the [`UtxoCoinFields::tx_fee_volatility_percent`].
 pub fn increase_dynamic_fee_by_stage<T>(coin: &T, dynamic_fee: u64, stage: &FeeApproxStage) -> u64
 where
@@ -4213,7 +4426,7 @@ pub fn addr_format(coin: &dyn AsRef<UtxoCoinFields>) -> &UtxoAddressFormat {
 
 pub fn addr_format_for_standard_scripts(coin: &dyn AsRef<UtxoCoinFields>) -> UtxoAddressFormat {
     match &coin.as_ref().conf.default_address_format {
-        UtxoAddressFormat::Segwit => UtxoAddressFormat::Standard,
+        UtxoAddressFormat::Segwit | UtxoAddressFormat::Taproot => UtxoAddressFormat::Standard,
         format @ (UtxoAddressFormat::Standard | UtxoAddressFormat::CashAddress { .. }) => format.clone(),
     }
 }
@@ -4236,7 +4449,7 @@ where
                 Ok(())
             }
         },
-        UtxoAddressFormat::Segwit => {
+        UtxoAddressFormat::Segwit | UtxoAddressFormat::Taproot => {
             if !conf.segwit {
                 return MmError::err(UnsupportedAddr::SegwitNotActivated(conf.ticker.clone()));
             }
@@ -4281,6 +4494,10 @@ pub fn derive_htlc_key_pair(coin: &UtxoCoinFields, _swap_unique_data: &[u8]) ->
     match coin.priv_key_policy {
         PrivKeyPolicy::KeyPair(k) => k,
         PrivKeyPolicy::Trezor => todo!(),
+        // Watch-only coins are rejected by the `MmCoin::is_watch_only` checks on the order creation, order matching,
+        // `check_balance_for_maker_swap`/`check_balance_for_taker_swap` and the swap kick-start/recovery,
+        // so the swap never gets to the HTLC key derivation.
+        PrivKeyPolicy::WatchOnly => unreachable!("HTLC key pair can't be derived for a watch-only coin"),
     }
 }
 
@@ -238,6 +238,8 @@ where
     let fee_details = UtxoFeeDetails {
         coin: Some(ticker.to_string()),
         amount: fee,
+        fee_rate: None,
+        fee_rate_source: None,
     };
 
     tx_builder.set_tx_fee(Some(TxFeeDetails::from(fee_details)));
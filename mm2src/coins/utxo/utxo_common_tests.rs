@@ -120,6 +120,8 @@ pub(super) fn utxo_coin_fields_for_test(
             estimate_fee_mode: None,
             mature_confirmations: MATURE_CONFIRMATIONS_DEFAULT,
             estimate_fee_blocks: 1,
+            swap_fee_priority: None,
+            swap_txs_rbf: false,
             trezor_coin: None,
             spv_conf: None,
             derivation_path: None,
@@ -134,6 +136,8 @@ pub(super) fn utxo_coin_fields_for_test(
         history_sync_state: Mutex::new(HistorySyncState::NotEnabled),
         tx_cache: DummyVerboseCache::default().into_shared(),
         recently_spent_outpoints: AsyncMutex::new(RecentlySpentOutPoints::new(my_script_pubkey)),
+        frozen_outpoints: Mutex::new(HashSet::new()),
+        consolidation_loop: Mutex::new(None),
         tx_hash_algo: TxHashAlgo::DSHA256,
         check_utxo_maturity: false,
         block_headers_status_notifier: None,
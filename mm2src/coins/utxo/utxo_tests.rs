@@ -8,9 +8,16 @@ use crate::hd_wallet_storage::{HDWalletMockStorage, HDWalletStorageInternalOps};
 use crate::my_tx_history_v2::for_tests::init_storage_for;
 use crate::my_tx_history_v2::CoinWithTxHistoryV2;
 use crate::rpc_command::account_balance::{AccountBalanceParams, AccountBalanceRpcOps, HDAccountBalanceResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::rpc_command::bump_fee::{bump_fee_cpfp_impl, bump_fee_rbf_impl, BumpFeeError, BumpFeeRequest};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::rpc_command::consolidate_utxos::{consolidate_once, consolidate_utxos_impl, ConsolidateUtxosError,
+                                            ConsolidateUtxosRequest};
 use crate::rpc_command::get_new_address::{GetNewAddressParams, GetNewAddressRpcError, GetNewAddressRpcOps};
 use crate::rpc_command::init_scan_for_new_addresses::{InitScanAddressesRpcOps, ScanAddressesParams,
                                                       ScanAddressesResponse};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::rpc_command::multisig::{multisig_balance_impl, MultisigError, MultisigWalletParams};
 use crate::utxo::qtum::{qtum_coin_with_priv_key, QtumCoin, QtumDelegationOps, QtumDelegationRequest};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::utxo::rpc_clients::{BlockHashOrHeight, NativeUnspent};
@@ -20,21 +27,26 @@ use crate::utxo::rpc_clients::{ElectrumBalance, ElectrumClient, ElectrumClientIm
 use crate::utxo::spv::SimplePaymentVerification;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::utxo::utxo_block_header_storage::{BlockHeaderStorage, SqliteBlockHeadersStorage};
-use crate::utxo::utxo_builder::{UtxoArcBuilder, UtxoCoinBuilder, UtxoCoinBuilderCommonOps};
+use crate::utxo::utxo_builder::{UtxoArcBuilder, UtxoCoinBuildError, UtxoCoinBuilder, UtxoCoinBuilderCommonOps,
+                                UtxoConfBuilder, UtxoConfError, UtxoFieldsWithWatchOnlyBuilder};
 use crate::utxo::utxo_common::UtxoTxBuilder;
 #[cfg(not(target_arch = "wasm32"))]
+use crate::utxo::utxo_common::{calc_tx_fee, tx_size_in_v_bytes};
+#[cfg(not(target_arch = "wasm32"))]
 use crate::utxo::utxo_common_tests::TEST_COIN_DECIMALS;
 use crate::utxo::utxo_common_tests::{self, utxo_coin_fields_for_test, utxo_coin_from_fields, TEST_COIN_NAME};
 use crate::utxo::utxo_standard::{utxo_standard_coin_with_priv_key, UtxoStandardCoin};
 use crate::utxo::utxo_tx_history_v2::{UtxoTxDetailsParams, UtxoTxHistoryOps};
-#[cfg(not(target_arch = "wasm32"))] use crate::WithdrawFee;
 use crate::{BlockHeightAndTime, CoinBalance, ConfirmPaymentInput, IguanaPrivKey, PrivKeyBuildPolicy,
             SearchForSwapTxSpendInput, SpendPaymentArgs, StakingInfosDetails, SwapOps, TradePreimageValue,
-            TxFeeDetails, TxMarshalingErr, ValidateFeeArgs, WaitForHTLCTxSpendArgs, INVALID_SENDER_ERR_LOG};
-use chain::{BlockHeader, BlockHeaderBits, OutPoint};
+            TxFeeDetails, TxMarshalingErr, ValidateFeeArgs, WaitForHTLCTxSpendArgs, WatchOnlyParams, WithdrawError,
+            INVALID_SENDER_ERR_LOG};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{WithdrawFee, WithdrawInput, WithdrawOutput};
+use chain::{BlockHeader, BlockHeaderBits, OutPoint, TransactionInput};
 use common::executor::Timer;
 use common::{block_on, wait_until_sec, OrdRange, PagingOptionsEnum, DEX_FEE_ADDR_RAW_PUBKEY};
-use crypto::{privkey::key_pair_from_seed, Bip44Chain, RpcDerivationPath, Secp256k1Secret};
+use crypto::{privkey::key_pair_from_seed, Bip44Chain, ChildNumber, RpcDerivationPath, Secp256k1Secret};
 #[cfg(not(target_arch = "wasm32"))]
 use db_common::sqlite::rusqlite::Connection;
 use futures::channel::mpsc::channel;
@@ -45,6 +57,10 @@ use mm2_number::bigdecimal::{BigDecimal, Signed};
 use mm2_test_helpers::for_tests::{mm_ctx_with_custom_db, MORTY_ELECTRUM_ADDRS, RICK_ELECTRUM_ADDRS};
 use mocktopus::mocking::*;
 use rpc::v1::types::H256 as H256Json;
+use script::Opcode;
+#[cfg(not(target_arch = "wasm32"))]
+use script::UnsignedTransactionInput;
+#[cfg(not(target_arch = "wasm32"))] use serialization::serialize;
 use serialization::{deserialize, CoinVariant};
 use spv_validation::conf::{BlockHeaderValidationParams, SPVBlockHeader};
 use spv_validation::storage::BlockHeaderStorageOps;
@@ -53,6 +69,9 @@ use spv_validation::work::DifficultyAlgorithm;
 use std::iter;
 use std::mem::discriminant;
 use std::num::NonZeroUsize;
+use utxo_signer::bip322;
+use utxo_signer::psbt::{multisig_script_pubkey, Psbt, PsbtError};
+use utxo_signer::with_key_pair;
 
 #[cfg(not(target_arch = "wasm32"))]
 const TAKER_PAYMENT_SPEND_SEARCH_INTERVAL: f64 = 1.;
@@ -586,6 +605,10 @@ fn test_withdraw_impl_set_fixed_fee() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoFixed {
             amount: "0.1".parse().unwrap(),
         }),
@@ -595,6 +618,8 @@ fn test_withdraw_impl_set_fixed_fee() {
         UtxoFeeDetails {
             coin: Some(TEST_COIN_NAME.into()),
             amount: "0.1".parse().unwrap(),
+            fee_rate: Some("0.1".parse().unwrap()),
+            fee_rate_source: Some(UtxoFeeRateSource::Manual),
         }
         .into(),
     );
@@ -628,6 +653,10 @@ fn test_withdraw_impl_sat_per_kb_fee() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoPerKbyte {
             amount: "0.1".parse().unwrap(),
         }),
@@ -640,6 +669,8 @@ fn test_withdraw_impl_sat_per_kb_fee() {
         UtxoFeeDetails {
             coin: Some(TEST_COIN_NAME.into()),
             amount: "0.0245".parse().unwrap(),
+            fee_rate: Some("0.1".parse().unwrap()),
+            fee_rate_source: Some(UtxoFeeRateSource::Manual),
         }
         .into(),
     );
@@ -673,6 +704,10 @@ fn test_withdraw_impl_sat_per_kb_fee_amount_equal_to_max() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoPerKbyte {
             amount: "0.1".parse().unwrap(),
         }),
@@ -686,6 +721,8 @@ fn test_withdraw_impl_sat_per_kb_fee_amount_equal_to_max() {
         UtxoFeeDetails {
             coin: Some(TEST_COIN_NAME.into()),
             amount: "0.0211".parse().unwrap(),
+            fee_rate: Some("0.1".parse().unwrap()),
+            fee_rate_source: Some(UtxoFeeRateSource::Manual),
         }
         .into(),
     );
@@ -720,6 +757,10 @@ fn test_withdraw_impl_sat_per_kb_fee_amount_equal_to_max_dust_included_to_fee()
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoPerKbyte {
             amount: "0.09999999".parse().unwrap(),
         }),
@@ -733,6 +774,8 @@ fn test_withdraw_impl_sat_per_kb_fee_amount_equal_to_max_dust_included_to_fee()
         UtxoFeeDetails {
             coin: Some(TEST_COIN_NAME.into()),
             amount: "0.0211".parse().unwrap(),
+            fee_rate: Some("0.09999999".parse().unwrap()),
+            fee_rate_source: Some(UtxoFeeRateSource::Manual),
         }
         .into(),
     );
@@ -767,6 +810,10 @@ fn test_withdraw_impl_sat_per_kb_fee_amount_over_max() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoPerKbyte {
             amount: "0.1".parse().unwrap(),
         }),
@@ -801,6 +848,10 @@ fn test_withdraw_impl_sat_per_kb_fee_max() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: TEST_COIN_NAME.into(),
         max: true,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: Some(WithdrawFee::UtxoPerKbyte {
             amount: "0.1".parse().unwrap(),
         }),
@@ -813,6 +864,8 @@ fn test_withdraw_impl_sat_per_kb_fee_max() {
         UtxoFeeDetails {
             coin: Some(TEST_COIN_NAME.into()),
             amount: "0.0211".parse().unwrap(),
+            fee_rate: Some("0.1".parse().unwrap()),
+            fee_rate_source: Some(UtxoFeeRateSource::Manual),
         }
         .into(),
     );
@@ -820,6 +873,872 @@ fn test_withdraw_impl_sat_per_kb_fee_max() {
     assert_eq!(expected, tx_details.fee_details);
 }
 
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_impl_fee_target() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000000,
+            height: Default::default(),
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+    // 1000 sat per kbyte per every block the confirmation can be delayed.
+    NativeClient::estimate_fee_sat
+        .mock_safe(|_, _, _, _, n_blocks| MockResult::Return(Box::new(futures01::future::ok(n_blocks as u64 * 1000))));
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let mut fields = utxo_coin_fields_for_test(UtxoRpcClientEnum::Native(client), None, false);
+    fields.tx_fee = TxFee::Dynamic(EstimateFeeMethod::SmartFee);
+    fields.conf.estimate_fee_blocks = 3;
+    fields.conf.swap_fee_priority = Some(UtxoFeePriority::Low);
+    let coin = utxo_coin_from_fields(fields);
+
+    let withdraw_fee_details = |fee: Option<Json>| {
+        let withdraw_req: WithdrawRequest = json::from_value(json!({
+            "coin": TEST_COIN_NAME,
+            "to": "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa",
+            "amount": "1",
+            "fee": fee,
+        }))
+        .unwrap();
+        match coin.withdraw(withdraw_req).wait().unwrap().fee_details {
+            Some(TxFeeDetails::Utxo(fee_details)) => fee_details,
+            other => panic!("Unexpected fee details: {:?}", other),
+        }
+    };
+
+    let fee_details = withdraw_fee_details(Some(json!({"type": "UtxoTargetBlocks", "blocks": 10})));
+    assert_eq!(fee_details.fee_rate, Some("0.0001".parse().unwrap()));
+    assert_eq!(
+        fee_details.fee_rate_source,
+        Some(UtxoFeeRateSource::Estimated { target_blocks: 10 })
+    );
+
+    let fee_details = withdraw_fee_details(Some(json!({"type": "UtxoPriority", "priority": "medium"})));
+    assert_eq!(fee_details.fee_rate, Some("0.00006".parse().unwrap()));
+    assert_eq!(
+        fee_details.fee_rate_source,
+        Some(UtxoFeeRateSource::Estimated { target_blocks: 6 })
+    );
+
+    // Withdraws without the fee specified are not affected by `swap_fee_priority`.
+    let fee_details = withdraw_fee_details(None);
+    assert_eq!(fee_details.fee_rate, Some("0.00003".parse().unwrap()));
+    assert_eq!(
+        fee_details.fee_rate_source,
+        Some(UtxoFeeRateSource::Estimated { target_blocks: 3 })
+    );
+
+    let swap_fee = block_on(coin.get_tx_fee()).unwrap();
+    assert_eq!(swap_fee, ActualTxFee::Dynamic(24000));
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_impl_fee_target_fixed_fee_coin() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let withdraw_req: WithdrawRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "to": "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa",
+        "amount": "1",
+        "fee": {"type": "UtxoPriority", "priority": "high"},
+    }))
+    .unwrap();
+    let error = coin.withdraw(withdraw_req).wait().unwrap_err().into_inner();
+    assert!(matches!(error, WithdrawError::InvalidFeePolicy(_)), "{:?}", error);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_impl_batch_outputs_with_max() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000000,
+            height: Default::default(),
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let withdraw_req = WithdrawRequest {
+        amount: 0u64.into(),
+        from: None,
+        to: String::new(),
+        coin: TEST_COIN_NAME.into(),
+        max: false,
+        outputs: vec![
+            WithdrawOutput {
+                to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
+                amount: 1u64.into(),
+                max: false,
+            },
+            WithdrawOutput {
+                to: "R9o9xTocqr6CeEDGDH6mEYpwLoMz6jNjMW".to_string(),
+                amount: 0u64.into(),
+                max: true,
+            },
+        ],
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
+        fee: Some(WithdrawFee::UtxoFixed {
+            amount: "0.1".parse().unwrap(),
+        }),
+        memo: None,
+    };
+    let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
+    assert_eq!(tx_details.to, vec![
+        "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_owned(),
+        "R9o9xTocqr6CeEDGDH6mEYpwLoMz6jNjMW".to_owned()
+    ]);
+
+    // The fee is deducted from the `max` output, and there is no change output.
+    let transaction: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    let values: Vec<_> = transaction.outputs.iter().map(|output| output.value).collect();
+    assert_eq!(values, vec![100000000, 890000000]);
+}
+
+#[test]
+fn test_withdraw_request_invalid_outputs() {
+    let req: WithdrawRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "outputs": [
+            {"to": "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa", "max": true},
+            {"to": "R9o9xTocqr6CeEDGDH6mEYpwLoMz6jNjMW", "max": true},
+        ],
+    }))
+    .unwrap();
+    let error = req.withdraw_outputs().unwrap_err().into_inner();
+    assert!(matches!(error, WithdrawError::InvalidOutputs(_)), "{:?}", error);
+
+    let req: WithdrawRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "to": "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa",
+        "outputs": [{"to": "R9o9xTocqr6CeEDGDH6mEYpwLoMz6jNjMW", "amount": "1"}],
+    }))
+    .unwrap();
+    let error = req.withdraw_outputs().unwrap_err().into_inner();
+    assert!(matches!(error, WithdrawError::InvalidOutputs(_)), "{:?}", error);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_impl_explicit_inputs() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![
+            UnspentInfo {
+                outpoint: OutPoint {
+                    hash: 1.into(),
+                    index: 0,
+                },
+                value: 1000000000,
+                height: Default::default(),
+            },
+            UnspentInfo {
+                outpoint: OutPoint {
+                    hash: 2.into(),
+                    index: 1,
+                },
+                value: 2000000000,
+                height: Default::default(),
+            },
+        ];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let selected = OutPoint {
+        hash: 2.into(),
+        index: 1,
+    };
+    let withdraw_req = WithdrawRequest {
+        amount: 1u64.into(),
+        from: None,
+        to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
+        coin: TEST_COIN_NAME.into(),
+        max: false,
+        outputs: Vec::new(),
+        inputs: vec![WithdrawInput {
+            tx_hash: selected.hash.reversed().into(),
+            vout: selected.index,
+        }],
+        rbf: false,
+        psbt: false,
+        fee: None,
+        memo: None,
+    };
+    // The smallest unspent would be selected automatically, but only the explicit input must be spent.
+    let tx_details = coin.withdraw(withdraw_req.clone()).wait().unwrap();
+    let transaction: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    let spent: Vec<_> = transaction.inputs.iter().map(|input| input.previous_output).collect();
+    assert_eq!(spent, vec![selected]);
+
+    let unknown = WithdrawRequest {
+        inputs: vec![WithdrawInput {
+            tx_hash: H256::from(3).reversed().into(),
+            vout: 0,
+        }],
+        ..withdraw_req
+    };
+    let error = coin.withdraw(unknown).wait().unwrap_err().into_inner();
+    assert!(matches!(error, WithdrawError::InvalidInputs(_)), "{:?}", error);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_impl_signal_rbf() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000000,
+            height: Default::default(),
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let withdraw_req: WithdrawRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "to": "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa",
+        "amount": "1",
+        "rbf": true,
+    }))
+    .unwrap();
+    let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
+    let transaction: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    assert!(transaction
+        .inputs
+        .iter()
+        .all(|input| input.sequence == utxo_common::SEQUENCE_RBF));
+}
+
+/// Mocks the pending `tx` and the `prev_txs` spent by it for the fee bumping tests.
+#[cfg(not(target_arch = "wasm32"))]
+fn mock_pending_tx_for_bump_fee(pending: UtxoTx, prev_txs: Vec<UtxoTx>) {
+    NativeClient::get_verbose_transaction.mock_safe(move |_, txid| {
+        let verbose: RpcTransaction = json::from_value(json!({
+            "hex": hex::encode(serialize(&pending)),
+            "txid": *txid,
+            "version": pending.version,
+            "locktime": pending.lock_time,
+            "vin": [],
+            "vout": [],
+            "confirmations": 0,
+        }))
+        .unwrap();
+        MockResult::Return(Box::new(futures01::future::ok(verbose)))
+    });
+    NativeClient::get_transaction_bytes.mock_safe(move |_, txid| {
+        let prev_tx = prev_txs
+            .iter()
+            .find(|prev_tx| H256Json::from(prev_tx.hash().reversed()) == *txid)
+            .expect("Unexpected prev tx");
+        MockResult::Return(Box::new(futures01::future::ok(serialize(prev_tx).into())))
+    });
+}
+
+/// Returns a tx paying `value` to my address and the pending tx spending it that signals RBF.
+/// The pending tx sends 0.5 to another address and the rest except of `fee` back to my address.
+#[cfg(not(target_arch = "wasm32"))]
+fn pending_tx_for_bump_fee(coin: &UtxoStandardCoin, value: u64, fee: u64) -> (UtxoTx, UtxoTx) {
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err().unwrap();
+    let my_script_pubkey = output_script(my_address, ScriptType::P2PKH).to_bytes();
+    let other_script_pubkey = Builder::build_p2pkh(&AddressHashEnum::default_address_hash()).to_bytes();
+
+    let mut prev_tx = UtxoTx::from(coin.as_ref().transaction_preimage());
+    prev_tx.outputs.push(TransactionOutput {
+        value,
+        script_pubkey: my_script_pubkey.clone(),
+    });
+
+    let mut unsigned = coin.as_ref().transaction_preimage();
+    unsigned.inputs.push(UnsignedTransactionInput {
+        previous_output: OutPoint {
+            hash: prev_tx.hash(),
+            index: 0,
+        },
+        sequence: utxo_common::SEQUENCE_RBF,
+        amount: value,
+        witness: Vec::new(),
+    });
+    unsigned.outputs = vec![
+        TransactionOutput {
+            value: 50000000,
+            script_pubkey: other_script_pubkey,
+        },
+        TransactionOutput {
+            value: value - 50000000 - fee,
+            script_pubkey: my_script_pubkey,
+        },
+    ];
+    let pending = with_key_pair::sign_tx(
+        unsigned,
+        coin.as_ref().priv_key_policy.key_pair_or_err().unwrap(),
+        prev_script_for_signing(my_address),
+        coin.as_ref().conf.signature_version,
+        coin.as_ref().conf.fork_id,
+    )
+    .unwrap();
+    (prev_tx, pending)
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_bump_fee_rbf() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+    let (prev_tx, pending) = pending_tx_for_bump_fee(&coin, 100000000, 10000);
+    let v_size = serialize(&pending).len() as u64;
+    mock_pending_tx_for_bump_fee(pending.clone(), vec![prev_tx]);
+
+    // 45 sat per byte pays a bit more than the replaced fee, but BIP-125 rule 4 requires at least
+    // `old_fee + v_size` at the default incremental relay fee of 1 sat per byte.
+    let insufficient_fee = calc_tx_fee(&ActualTxFee::Dynamic(45000), v_size);
+    assert!(insufficient_fee > 10000 && insufficient_fee < 10000 + v_size);
+    let req: BumpFeeRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "tx_hash": H256Json::from(pending.hash().reversed()),
+        "fee": {"type": "UtxoPerKbyte", "amount": "0.00045"},
+    }))
+    .unwrap();
+    let error = block_on(bump_fee_rbf_impl(&coin, &req)).unwrap_err().into_inner();
+    assert!(
+        matches!(error, BumpFeeError::InsufficientReplacementFee { .. }),
+        "{:?}",
+        error
+    );
+
+    let req: BumpFeeRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "tx_hash": H256Json::from(pending.hash().reversed()),
+        "fee": {"type": "UtxoPerKbyte", "amount": "0.001"},
+    }))
+    .unwrap();
+    let tx_details = block_on(bump_fee_rbf_impl(&coin, &req)).unwrap();
+    let replacement: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    let new_fee = 100000 * v_size / 1000;
+    assert_eq!(replacement.inputs.len(), 1);
+    assert_eq!(replacement.inputs[0].previous_output, pending.inputs[0].previous_output);
+    assert_eq!(replacement.inputs[0].sequence, utxo_common::SEQUENCE_RBF);
+    assert_eq!(replacement.outputs[0], pending.outputs[0]);
+    assert_eq!(
+        replacement.outputs[1].value,
+        pending.outputs[1].value - (new_fee - 10000)
+    );
+    let expected_fee = big_decimal_from_sat_unsigned(new_fee, coin.as_ref().decimals);
+    match tx_details.fee_details {
+        Some(TxFeeDetails::Utxo(fee_details)) => assert_eq!(fee_details.amount, expected_fee),
+        fee_details => panic!("Unexpected fee details: {:?}", fee_details),
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_bump_fee_rbf_htlc_refund() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let mut fields = utxo_coin_fields_for_test(UtxoRpcClientEnum::Native(client), None, false);
+    fields.conf.swap_txs_rbf = true;
+    let coin = utxo_coin_from_fields(fields);
+    let my_address = coin.as_ref().derivation_method.single_addr_or_err().unwrap();
+    let key_pair = coin.as_ref().priv_key_policy.key_pair_or_err().unwrap();
+
+    let time_lock = 1700000000;
+    let other_pub = Public::from_slice(&DEX_FEE_ADDR_RAW_PUBKEY).unwrap();
+    let redeem_script = utxo_common::payment_script(time_lock, &[1; 20], key_pair.public(), &other_pub);
+    let mut payment = UtxoTx::from(coin.as_ref().transaction_preimage());
+    payment.outputs.push(TransactionOutput {
+        value: 100000000,
+        script_pubkey: Builder::build_p2sh(&dhash160(&redeem_script).into()).to_bytes(),
+    });
+
+    let mut unsigned = coin.as_ref().transaction_preimage();
+    unsigned.lock_time = time_lock;
+    unsigned.inputs.push(UnsignedTransactionInput {
+        previous_output: OutPoint {
+            hash: payment.hash(),
+            index: 0,
+        },
+        sequence: utxo_common::SEQUENCE_RBF,
+        amount: 100000000,
+        witness: Vec::new(),
+    });
+    unsigned.outputs.push(TransactionOutput {
+        value: 100000000 - 1000,
+        script_pubkey: output_script(my_address, ScriptType::P2PKH).to_bytes(),
+    });
+    let refund_script_data = Builder::default().push_opcode(Opcode::OP_1).into_script();
+    let signed_input = with_key_pair::p2sh_spend(
+        &unsigned,
+        0,
+        key_pair,
+        refund_script_data.clone(),
+        redeem_script.clone(),
+        coin.as_ref().conf.signature_version,
+        coin.as_ref().conf.fork_id,
+    )
+    .unwrap();
+    let refund = UtxoTx {
+        inputs: vec![signed_input],
+        ..UtxoTx::from(unsigned.clone())
+    };
+    mock_pending_tx_for_bump_fee(refund.clone(), vec![payment]);
+
+    let req: BumpFeeRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "tx_hash": H256Json::from(refund.hash().reversed()),
+        "fee": {"type": "UtxoPerKbyte", "amount": "0.0001"},
+    }))
+    .unwrap();
+    let tx_details = block_on(bump_fee_rbf_impl(&coin, &req)).unwrap();
+    let replacement: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    let new_fee = 10000 * serialize(&refund).len() as u64 / 1000;
+    assert_eq!(replacement.lock_time, time_lock);
+    assert_eq!(replacement.outputs[0].value, 100000000 - new_fee);
+
+    // The replacement unlocks the HTLC the same way, but is signed for the new outputs.
+    unsigned.outputs[0].value = 100000000 - new_fee;
+    let expected_input = with_key_pair::p2sh_spend(
+        &unsigned,
+        0,
+        key_pair,
+        refund_script_data,
+        redeem_script,
+        coin.as_ref().conf.signature_version,
+        coin.as_ref().conf.fork_id,
+    )
+    .unwrap();
+    assert_eq!(replacement.inputs, vec![expected_input]);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_bump_fee_cpfp() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+    let (prev_tx, parent) = pending_tx_for_bump_fee(&coin, 100000000, 1000);
+    let parent_v_size = serialize(&parent).len() as u64;
+    let change = parent.outputs[1].value;
+    mock_pending_tx_for_bump_fee(parent.clone(), vec![prev_tx]);
+
+    let parent_hash = parent.hash();
+    UtxoStandardCoin::get_all_unspent_ordered_list.mock_safe(move |coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: parent_hash,
+                index: 1,
+            },
+            value: change,
+            height: None,
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let req: BumpFeeRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "tx_hash": H256Json::from(parent.hash().reversed()),
+        "fee": {"type": "UtxoPerKbyte", "amount": "0.0001"},
+    }))
+    .unwrap();
+    let tx_details = block_on(bump_fee_cpfp_impl(&coin, &req)).unwrap();
+    let child: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    assert_eq!(child.inputs.len(), 1);
+    assert_eq!(child.inputs[0].previous_output, OutPoint {
+        hash: parent.hash(),
+        index: 1,
+    });
+    assert_eq!(child.outputs.len(), 1);
+
+    // The child pays for both of the transactions at the requested fee rate.
+    let child_v_size = tx_size_in_v_bytes(&UtxoAddressFormat::Standard, &UtxoTx {
+        inputs: vec![TransactionInput {
+            script_sig: Bytes::default(),
+            ..child.inputs[0].clone()
+        }],
+        ..child.clone()
+    }) as u64;
+    let child_fee = change - child.outputs[0].value;
+    assert_eq!(child_fee, 10000 * (parent_v_size + child_v_size) / 1000 - 1000);
+
+    // The parent doesn't have my unspent outputs.
+    UtxoStandardCoin::get_all_unspent_ordered_list.mock_safe(move |coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        MockResult::Return(Box::pin(futures::future::ok((Vec::new(), cache))))
+    });
+    let error = block_on(bump_fee_cpfp_impl(&coin, &req)).unwrap_err().into_inner();
+    assert!(matches!(error, BumpFeeError::NoChangeOutput(_)), "{:?}", error);
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_consolidate_utxos_dry_run() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = [1000000, 2000000, 3000000, 1000000000]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| UnspentInfo {
+                outpoint: OutPoint {
+                    hash: (i as u64 + 1).into(),
+                    index: 0,
+                },
+                value: *value,
+                height: Default::default(),
+            })
+            .collect();
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let req: ConsolidateUtxosRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "merge_below": "0.5",
+        "dry_run": true,
+    }))
+    .unwrap();
+    let consolidation = json::to_value(block_on(consolidate_once(&coin, &req)).unwrap()).unwrap();
+    assert_eq!(consolidation["broadcasted"], json!(false));
+    assert_eq!(consolidation["merged_utxos"], json!(3));
+    assert_eq!(consolidation["utxos_before"], json!(4));
+    assert_eq!(consolidation["utxos_after"], json!(2));
+
+    let tx_hex: BytesJson = json::from_value(consolidation["tx_hex"].clone()).unwrap();
+    let transaction: UtxoTx = deserialize(tx_hex.as_slice()).unwrap();
+    assert_eq!(transaction.inputs.len(), 3);
+    assert_eq!(transaction.outputs.len(), 1);
+
+    // The test coin uses the fixed 1000 sat per kbyte fee.
+    let too_expensive: ConsolidateUtxosRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "max_fee_rate": "0.000001",
+        "dry_run": true,
+    }))
+    .unwrap();
+    let error = block_on(consolidate_once(&coin, &too_expensive))
+        .unwrap_err()
+        .into_inner();
+    assert!(
+        matches!(error, ConsolidateUtxosError::FeeRateTooHigh { .. }),
+        "{:?}",
+        error
+    );
+
+    let nothing: ConsolidateUtxosRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "merge_below": "0.015",
+        "dry_run": true,
+    }))
+    .unwrap();
+    let error = block_on(consolidate_once(&coin, &nothing)).unwrap_err().into_inner();
+    assert!(
+        matches!(error, ConsolidateUtxosError::NothingToConsolidate { matching: 1 }),
+        "{:?}",
+        error
+    );
+
+    // The background consolidation must be limited by the fee rate.
+    let background: ConsolidateUtxosRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "background": {},
+    }))
+    .unwrap();
+    let error = block_on(consolidate_utxos_impl(coin, background))
+        .unwrap_err()
+        .into_inner();
+    assert!(matches!(error, ConsolidateUtxosError::InvalidRequest(_)), "{:?}", error);
+}
+
+#[test]
+fn test_consolidate_utxos_background_check_every() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    for check_every in [json!(0), json!(-600), json!(29.9)] {
+        let req: ConsolidateUtxosRequest = json::from_value(json!({
+            "coin": TEST_COIN_NAME,
+            "max_fee_rate": "0.001",
+            "background": {
+                "check_every": check_every,
+            },
+        }))
+        .unwrap();
+        let error = block_on(consolidate_utxos_impl(coin.clone(), req))
+            .unwrap_err()
+            .into_inner();
+        match error {
+            ConsolidateUtxosError::InvalidRequest(e) => assert!(e.contains("check_every"), "{}", e),
+            e => panic!("Expected 'InvalidRequest', found {:?}", e),
+        }
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_psbt_and_finalize() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000000,
+            height: Default::default(),
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let mut fields = utxo_coin_fields_for_test(UtxoRpcClientEnum::Native(client), None, true);
+    // PSBT can be used with standard Bitcoin transactions only.
+    fields.conf.overwintered = false;
+    fields.conf.zcash = false;
+    fields.conf.tx_version = 2;
+    let coin = utxo_coin_from_fields(fields);
+    let my_address = coin.as_ref().derivation_method.unwrap_single_addr().clone();
+
+    let withdraw_req: WithdrawRequest = json::from_value(json!({
+        "coin": TEST_COIN_NAME,
+        "to": my_address.to_string(),
+        "amount": "1",
+        "psbt": true,
+    }))
+    .unwrap();
+    let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
+    let psbt_bytes = base64::decode(tx_details.psbt.as_ref().unwrap()).unwrap();
+    let mut psbt = Psbt::deserialize(&psbt_bytes).unwrap();
+
+    let unsigned_tx: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    assert_eq!(psbt.unsigned_tx, unsigned_tx);
+    let spent_output = psbt.inputs[0].witness_utxo.clone().unwrap();
+    assert_eq!(spent_output.value, 1000000000);
+
+    // The PSBT isn't signed yet.
+    assert!(psbt.clone().finalize().is_err());
+
+    // Sign the input as an external signer would do.
+    let key_pair = coin.as_ref().priv_key_policy.key_pair_or_err().unwrap();
+    let mut signer = TransactionInputSigner::from(psbt.unsigned_tx.clone());
+    signer.inputs[0].amount = spent_output.value;
+    let expected_input = with_key_pair::p2wpkh_spend(
+        &signer,
+        0,
+        key_pair,
+        Builder::build_p2pkh(&my_address.hash),
+        SignatureVersion::WitnessV0,
+        0,
+    )
+    .unwrap();
+    psbt.inputs[0]
+        .partial_sigs
+        .push((*key_pair.public(), expected_input.script_witness[0].clone()));
+
+    // The PSBT has to be passed through the serialization as it would be with an external signer.
+    let mut psbt = Psbt::deserialize(&psbt.serialize()).unwrap();
+    psbt.finalize().unwrap();
+    let signed = psbt.extract_tx().unwrap();
+    assert_eq!(signed.inputs[0], expected_input);
+    assert_eq!(signed.outputs, unsigned_tx.outputs);
+}
+
+#[test]
+fn test_multisig_psbt_sign_combine_finalize() {
+    let key_pair1 = key_pair_from_seed("multisig co-signer 1").unwrap();
+    let key_pair2 = key_pair_from_seed("multisig co-signer 2").unwrap();
+    let key_pair3 = key_pair_from_seed("multisig co-signer 3").unwrap();
+    let pubkeys = vec![*key_pair1.public(), *key_pair2.public(), *key_pair3.public()];
+    let multisig_script = Builder::build_multisig(2, &pubkeys).unwrap();
+
+    for signature_version in [SignatureVersion::Base, SignatureVersion::WitnessV0] {
+        let script_pubkey = multisig_script_pubkey(&multisig_script, signature_version);
+        let tx = UtxoTx {
+            version: 2,
+            inputs: vec![TransactionInput {
+                previous_output: OutPoint {
+                    hash: 1.into(),
+                    index: 0,
+                },
+                ..TransactionInput::default()
+            }],
+            outputs: vec![TransactionOutput {
+                value: 900000,
+                script_pubkey: Builder::build_p2pkh(&key_pair1.public().address_hash().into()).to_bytes(),
+            }],
+            ..UtxoTx::default()
+        };
+        let mut psbt = Psbt::from_unsigned(&TransactionInputSigner::from(tx)).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TransactionOutput {
+            value: 1000000,
+            script_pubkey: script_pubkey.to_bytes(),
+        });
+        match signature_version {
+            SignatureVersion::WitnessV0 => psbt.inputs[0].witness_script = Some(multisig_script.to_bytes()),
+            _ => psbt.inputs[0].redeem_script = Some(multisig_script.to_bytes()),
+        }
+
+        // The co-signers sign the PSBT independently.
+        let mut psbt1 = psbt.clone();
+        assert_eq!(psbt1.sign_multisig_inputs(&key_pair1, signature_version, 0).unwrap(), 1);
+        let mut psbt3 = psbt.clone();
+        assert_eq!(psbt3.sign_multisig_inputs(&key_pair3, signature_version, 0).unwrap(), 1);
+
+        // One signature isn't enough.
+        let error = psbt1.clone().finalize().unwrap_err().into_inner();
+        assert!(matches!(error, PsbtError::NotEnoughSignatures {
+            index: 0,
+            required: 2,
+            actual: 1
+        }));
+
+        // The key that is not a co-signer doesn't sign anything.
+        let stranger = key_pair_from_seed("not a co-signer").unwrap();
+        assert_eq!(psbt1.sign_multisig_inputs(&stranger, signature_version, 0).unwrap(), 0);
+
+        let psbt3 = Psbt::deserialize(&psbt3.serialize()).unwrap();
+        psbt1.combine(psbt3).unwrap();
+        psbt1.finalize().unwrap();
+        let signed = psbt1.extract_tx().unwrap();
+
+        // The signatures are expected to be in the order of the public keys in the script.
+        let (signatures, script) = match signature_version {
+            SignatureVersion::WitnessV0 => {
+                let witness = &signed.inputs[0].script_witness;
+                assert!(signed.inputs[0].script_sig.is_empty());
+                assert_eq!(witness.len(), 4);
+                assert!(witness[0].is_empty());
+                (witness[1..3].to_vec(), witness[3].clone())
+            },
+            _ => {
+                let script_sig: Script = signed.inputs[0].script_sig.clone().into();
+                let instructions: Vec<_> = script_sig.iter().map(|instruction| instruction.unwrap()).collect();
+                assert_eq!(instructions.len(), 4);
+                assert_eq!(instructions[0].opcode, Opcode::OP_0);
+                let data: Vec<Bytes> = instructions[1..]
+                    .iter()
+                    .map(|instruction| instruction.data.unwrap().to_vec().into())
+                    .collect();
+                (data[0..2].to_vec(), data[2].clone())
+            },
+        };
+        assert_eq!(Script::from(script), multisig_script);
+
+        let mut signer = TransactionInputSigner::from(signed.clone());
+        signer.inputs[0].amount = 1000000;
+        let sighash = signer.signature_hash(0, 1000000, &multisig_script, signature_version, 1);
+        for (signature, pubkey) in signatures.iter().zip([key_pair1.public(), key_pair3.public()]) {
+            let (sighash_type, der) = signature.split_last().unwrap();
+            assert_eq!(*sighash_type, 1);
+            assert!(pubkey.verify(&sighash, &der.to_vec().into()).unwrap());
+        }
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_multisig_balance_native_address_not_imported() {
+    static mut IS_WATCH_ONLY: bool = false;
+    NativeClientImpl::validate_address.mock_safe(|_, _| {
+        let result = ValidateAddressRes {
+            is_valid: true,
+            address: "".to_string(),
+            script_pub_key: Default::default(),
+            seg_id: None,
+            is_mine: Some(false),
+            is_watch_only: Some(unsafe { IS_WATCH_ONLY }),
+            is_script: true,
+            account: None,
+        };
+        MockResult::Return(Box::new(futures01::future::ok(result)))
+    });
+    NativeClient::list_unspent.mock_safe(|_, _, _| {
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000,
+            height: Some(1),
+        }];
+        MockResult::Return(Box::new(futures01::future::ok(unspents)))
+    });
+
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(native_client_for_test()), None, false);
+    let key_pair1 = key_pair_from_seed("multisig co-signer 1").unwrap();
+    let key_pair2 = key_pair_from_seed("multisig co-signer 2").unwrap();
+    let params: MultisigWalletParams = json::from_value(json!({
+        "required": 2,
+        "keys": [key_pair1.public().to_string(), key_pair2.public().to_string()],
+        "script_type": "p2sh",
+    }))
+    .unwrap();
+
+    // The native node doesn't know the address, so it can't tell the balance.
+    let error = block_on(multisig_balance_impl(&coin, &params))
+        .unwrap_err()
+        .into_inner();
+    assert!(matches!(error, MultisigError::AddressNotImported { .. }), "{:?}", error);
+
+    unsafe { IS_WATCH_ONLY = true };
+    let balance = json::to_value(block_on(multisig_balance_impl(&coin, &params)).unwrap()).unwrap();
+    assert_eq!(balance["balance"], json!("0.01"));
+    assert_eq!(balance["unspents_count"], json!(1));
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_exclude_frozen_unspents() {
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, false);
+
+    let unspents: Vec<_> = (1..=3)
+        .map(|i| UnspentInfo {
+            outpoint: OutPoint {
+                hash: i.into(),
+                index: 0,
+            },
+            value: i as u64 * 1000000,
+            height: Default::default(),
+        })
+        .collect();
+    coin.as_ref()
+        .frozen_outpoints
+        .lock()
+        .unwrap()
+        .insert(unspents[1].outpoint);
+
+    let actual = utxo_common::exclude_frozen_unspents(&coin, unspents.clone());
+    assert_eq!(actual, vec![unspents[0].clone(), unspents[2].clone()]);
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn test_withdraw_kmd_rewards_impl(
     tx_hash: &'static str,
@@ -863,12 +1782,18 @@ fn test_withdraw_kmd_rewards_impl(
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: "KMD".to_owned(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some("KMD".into()),
         amount: "0.00001".parse().unwrap(),
+        fee_rate: Some("0.00001".parse().unwrap()),
+        fee_rate_source: Some(UtxoFeeRateSource::CoinConfig),
     });
     let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
     assert_eq!(tx_details.fee_details, Some(expected_fee));
@@ -938,12 +1863,18 @@ fn test_withdraw_rick_rewards_none() {
         to: "RQq6fWoy8aGGMLjvRfMY5mBNVm2RQxJyLa".to_string(),
         coin: "RICK".to_owned(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some(TEST_COIN_NAME.into()),
         amount: "0.00001".parse().unwrap(),
+        fee_rate: Some("0.00001".parse().unwrap()),
+        fee_rate_source: Some(UtxoFeeRateSource::CoinConfig),
     });
     let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
     assert_eq!(tx_details.fee_details, Some(expected_fee));
@@ -1380,7 +2311,7 @@ fn test_cashaddresses_in_tx_details_by_hash() {
          ],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -1419,7 +2350,7 @@ fn test_address_from_str_with_cashaddress_activated() {
          ],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -1457,7 +2388,7 @@ fn test_address_from_str_with_legacy_address_activated() {
          ],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -1519,7 +2450,7 @@ fn test_unavailable_electrum_proto_version() {
          "servers": [{"url":"electrum1.cipig.net:10017"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let priv_key = Secp256k1Secret::from([1; 32]);
     let error = block_on(utxo_standard_coin_with_priv_key(&ctx, "RICK", &conf, &params, priv_key))
@@ -1541,7 +2472,7 @@ fn test_spam_rick() {
     });
 
     let key_pair = key_pair_from_seed("my_seed").unwrap();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(utxo_standard_coin_with_priv_key(
         &ctx,
@@ -1593,7 +2524,7 @@ fn test_one_unavailable_electrum_proto_version() {
          "servers": [{"url":"electrum1.cipig.net:10000"},{"url":"electrum-mona.bitbank.cc:50001"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -1616,7 +2547,7 @@ fn test_qtum_generate_pod() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
 
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(&ctx, "tQTUM", &conf, &params, priv_key)).unwrap();
@@ -1635,7 +2566,7 @@ fn test_qtum_add_delegation() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(
         &ctx,
@@ -1674,7 +2605,7 @@ fn test_qtum_add_delegation_on_already_delegating() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(
         &ctx,
@@ -1704,7 +2635,7 @@ fn test_qtum_get_delegation_infos() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
 
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(
@@ -1735,7 +2666,7 @@ fn test_qtum_remove_delegation() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(
         &ctx,
@@ -1793,7 +2724,7 @@ fn test_qtum_my_balance() {
         "servers": [{"url":"electrum1.cipig.net:10071"}, {"url":"electrum2.cipig.net:10071"}, {"url":"electrum3.cipig.net:10071"}],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
 
     let priv_key = Secp256k1Secret::from([
         184, 199, 116, 240, 113, 222, 8, 199, 253, 143, 98, 185, 127, 26, 87, 38, 246, 206, 159, 27, 207, 20, 27, 112,
@@ -1829,7 +2760,7 @@ fn test_qtum_my_balance_with_check_utxo_maturity_false() {
         "check_utxo_maturity": false,
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
 
     let priv_key = Secp256k1Secret::from([
         184, 199, 116, 240, 113, 222, 8, 199, 253, 143, 98, 185, 127, 26, 87, 38, 246, 206, 159, 27, 207, 20, 27, 112,
@@ -2682,6 +3613,8 @@ fn firo_lelantus_tx_details() {
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some(TEST_COIN_NAME.into()),
         amount: "0.00003793".parse().unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(Some(expected_fee), tx_details.fee_details);
 
@@ -2693,6 +3626,8 @@ fn firo_lelantus_tx_details() {
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some(TEST_COIN_NAME.into()),
         amount: "0.00045778".parse().unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(Some(expected_fee), tx_details.fee_details);
 }
@@ -2722,7 +3657,7 @@ fn test_generate_tx_doge_fee() {
         "coin": "DOGE",
         "servers": [{"url": "electrum1.cipig.net:10060"},{"url": "electrum2.cipig.net:10060"},{"url": "electrum3.cipig.net:10060"}],
     });
-    let ctx = MmCtxBuilder::default().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&request).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -2917,6 +3852,8 @@ fn test_tx_details_kmd_rewards() {
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some("KMD".into()),
         amount: BigDecimal::from_str("0.00001").unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(tx_details.fee_details, Some(expected_fee));
 
@@ -2951,6 +3888,8 @@ fn test_tx_details_kmd_rewards_claimed_by_other() {
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some("KMD".into()),
         amount: BigDecimal::from_str("0.00001").unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(tx_details.fee_details, Some(expected_fee));
 
@@ -2978,6 +3917,8 @@ fn test_tx_details_bch_no_rewards() {
     let expected_fee = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some(TEST_COIN_NAME.into()),
         amount: BigDecimal::from_str("0.00000452").unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(tx_details.fee_details, Some(expected_fee));
     assert_eq!(tx_details.kmd_rewards, None);
@@ -3011,6 +3952,8 @@ fn test_update_kmd_rewards() {
     let expected_fee_details = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some("KMD".into()),
         amount: BigDecimal::from_str("0.00001").unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(tx_details.fee_details, Some(expected_fee_details));
 }
@@ -3043,6 +3986,8 @@ fn test_update_kmd_rewards_claimed_not_by_me() {
     let expected_fee_details = TxFeeDetails::Utxo(UtxoFeeDetails {
         coin: Some("KMD".into()),
         amount: BigDecimal::from_str("0.00001").unwrap(),
+        fee_rate: None,
+        fee_rate_source: None,
     });
     assert_eq!(tx_details.fee_details, Some(expected_fee_details));
 }
@@ -3103,6 +4048,10 @@ fn test_withdraw_to_p2pkh() {
         to: p2pkh_address.to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
@@ -3151,6 +4100,10 @@ fn test_withdraw_to_p2sh() {
         to: p2sh_address.to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
@@ -3199,6 +4152,10 @@ fn test_withdraw_to_p2wpkh() {
         to: p2wpkh_address.to_string(),
         coin: TEST_COIN_NAME.into(),
         max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
         fee: None,
         memo: None,
     };
@@ -3211,6 +4168,66 @@ fn test_withdraw_to_p2wpkh() {
     assert_eq!(output_script, expected_script);
 }
 
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_withdraw_to_p2tr() {
+    UtxoStandardCoin::get_unspent_ordered_list.mock_safe(|coin, _| {
+        let cache = block_on(coin.as_ref().recently_spent_outpoints.lock());
+        let unspents = vec![UnspentInfo {
+            outpoint: OutPoint {
+                hash: 1.into(),
+                index: 0,
+            },
+            value: 1000000000,
+            height: Default::default(),
+        }];
+        MockResult::Return(Box::pin(futures::future::ok((unspents, cache))))
+    });
+
+    let client = NativeClient(Arc::new(NativeClientImpl::default()));
+
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(client), None, true);
+
+    // Create a p2tr address for the test coin
+    let output_key = coin
+        .as_ref()
+        .priv_key_policy
+        .key_pair_or_err()
+        .unwrap()
+        .public()
+        .taproot_output_key()
+        .unwrap();
+    let p2tr_address = Address {
+        prefix: coin.as_ref().conf.pub_addr_prefix,
+        hash: AddressHashEnum::WitnessScriptHash(output_key),
+        t_addr_prefix: coin.as_ref().conf.pub_t_addr_prefix,
+        checksum_type: coin.as_ref().derivation_method.unwrap_single_addr().checksum_type,
+        hrp: coin.as_ref().conf.bech32_hrp.clone(),
+        addr_format: UtxoAddressFormat::Taproot,
+    };
+
+    let withdraw_req = WithdrawRequest {
+        amount: 1.into(),
+        from: None,
+        to: p2tr_address.to_string(),
+        coin: TEST_COIN_NAME.into(),
+        max: false,
+        outputs: Vec::new(),
+        inputs: Vec::new(),
+        rbf: false,
+        psbt: false,
+        fee: None,
+        memo: None,
+    };
+    let tx_details = coin.withdraw(withdraw_req).wait().unwrap();
+    let transaction: UtxoTx = deserialize(tx_details.tx_hex.as_slice()).unwrap();
+    let output_script: Script = transaction.outputs[0].script_pubkey.clone().into();
+
+    let expected_script = Builder::build_p2tr(&p2tr_address.hash);
+
+    assert_eq!(output_script, expected_script);
+}
+
 /// `UtxoStandardCoin` has to check UTXO maturity if `check_utxo_maturity` is `true`.
 /// https://github.com/KomodoPlatform/atomicDEX-API/issues/1181
 #[test]
@@ -3235,7 +4252,7 @@ fn test_utxo_standard_with_check_utxo_maturity_true() {
         "check_utxo_maturity": true,
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -3275,7 +4292,7 @@ fn test_utxo_standard_without_check_utxo_maturity() {
          ]
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -3310,7 +4327,7 @@ fn test_qtum_without_check_utxo_maturity() {
         ],
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -3355,7 +4372,7 @@ fn test_split_qtum() {
             {"url":"electrum3.cipig.net:10071"},
         ],
     });
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(&ctx, "QTUM", &conf, &params, priv_key)).unwrap();
     let p2pkh_address = coin.as_ref().derivation_method.unwrap_single_addr();
@@ -3425,7 +4442,7 @@ fn test_qtum_with_check_utxo_maturity_false() {
         "check_utxo_maturity": false,
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -4276,6 +5293,185 @@ fn test_sign_verify_message_segwit() {
     assert!(is_valid);
 }
 
+/// https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki#test-vectors
+#[test]
+fn test_sign_verify_message_bip322() {
+    assert_eq!(
+        bip322::message_hash(b""),
+        H256::from("c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1")
+    );
+    assert_eq!(
+        bip322::message_hash(b"Hello World"),
+        H256::from("f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a")
+    );
+
+    let key_pair =
+        KeyPair::from_private(Private::from("L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k")).unwrap();
+    let p2wpkh = Builder::build_witness_script(&key_pair.public().address_hash().into());
+    let signature = base64::decode("AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=").unwrap();
+    assert!(bip322::verify_simple(&p2wpkh, b"Hello World", &signature).unwrap());
+    assert!(!bip322::verify_simple(&p2wpkh, b"Hello World!", &signature).unwrap());
+
+    // bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3, the signature is SIGHASH_ALL.
+    let output_key = key_pair.public().taproot_output_key().unwrap();
+    let p2tr = Builder::build_p2tr(&AddressHashEnum::WitnessScriptHash(output_key));
+    let signature =
+        base64::decode("AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==")
+            .unwrap();
+    assert!(bip322::verify_simple(&p2tr, b"Hello World", &signature).unwrap());
+    assert!(!bip322::verify_simple(&p2tr, b"Hello World!", &signature).unwrap());
+
+    for script_pubkey in [p2wpkh, p2tr] {
+        let signature = bip322::sign_simple(&key_pair, &script_pubkey, b"Hello World").unwrap();
+        assert!(bip322::verify_simple(&script_pubkey, b"Hello World", &signature).unwrap());
+        assert!(!bip322::verify_simple(&script_pubkey, b"", &signature).unwrap());
+
+        let stranger = key_pair_from_seed("not the address owner").unwrap();
+        let error = bip322::sign_simple(&stranger, &script_pubkey, b"Hello World").unwrap_err();
+        assert!(matches!(error.into_inner(), bip322::Bip322Error::MismatchScript(_)));
+        let stranger_signature = if script_pubkey.is_pay_to_taproot() {
+            let output_key = stranger.public().taproot_output_key().unwrap();
+            let stranger_script = Builder::build_p2tr(&AddressHashEnum::WitnessScriptHash(output_key));
+            bip322::sign_simple(&stranger, &stranger_script, b"Hello World").unwrap()
+        } else {
+            let stranger_script = Builder::build_witness_script(&stranger.public().address_hash().into());
+            bip322::sign_simple(&stranger, &stranger_script, b"Hello World").unwrap()
+        };
+        assert!(!bip322::verify_simple(&script_pubkey, b"Hello World", &stranger_signature).unwrap());
+    }
+
+    let p2pkh = Builder::build_p2pkh(&key_pair.public().address_hash().into());
+    let error = bip322::sign_simple(&key_pair, &p2pkh, b"Hello World").unwrap_err();
+    assert!(matches!(error.into_inner(), bip322::Bip322Error::UnsupportedScript(_)));
+}
+
+/// https://github.com/bitcoin/bips/blob/master/bip-0084.mediawiki#test-vectors
+#[test]
+fn test_watch_only_hd_wallet_from_account_xpub() {
+    const ACCOUNT_ZPUB: &str = "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs";
+
+    let ctx = mm_ctx_with_custom_db();
+    let conf = json!({
+        "coin": "BTC",
+        "asset": "BTC",
+        "rpcport": 8332,
+        "segwit": true,
+        "bech32_hrp": "bc",
+        "address_format": {"format": "segwit"},
+        "derivation_path": "m/84'/0'",
+    });
+    let req = json!({ "method": "electrum", "servers": [] });
+    let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
+    let priv_key_policy = PrivKeyBuildPolicy::WatchOnly(WatchOnlyParams::AccountXpub(ACCOUNT_ZPUB.to_owned()));
+    let builder = UtxoArcBuilder::new(&ctx, "BTC", &conf, &params, priv_key_policy, UtxoStandardCoin::from);
+    let utxo_conf = UtxoConfBuilder::new(&conf, &params, "BTC").build().unwrap();
+
+    let hd_wallet = block_on(builder.watch_only_hd_wallet(&utxo_conf, ACCOUNT_ZPUB.to_owned())).unwrap();
+    let accounts = block_on(hd_wallet.accounts.lock()).clone();
+    assert_eq!(accounts.len(), 1);
+    let account = &accounts[&0];
+    assert_eq!(account.account_derivation_path.to_string(), "m/84'/0'/0'");
+
+    let first_pubkey = account
+        .extended_pubkey
+        .derive_child(Bip44Chain::External.to_child_number())
+        .and_then(|chain| chain.derive_child(ChildNumber::from(0)))
+        .unwrap();
+    let first_address = utxo_common::address_from_pubkey(
+        &Public::Compressed(H264::from(first_pubkey.public_key().serialize())),
+        utxo_conf.pub_addr_prefix,
+        utxo_conf.pub_t_addr_prefix,
+        utxo_conf.checksum_type,
+        utxo_conf.bech32_hrp.clone(),
+        UtxoAddressFormat::Segwit,
+    );
+    assert_eq!(first_address.to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+
+    // The account is stored once and loaded from the storage on the next activation.
+    let stored = block_on(hd_wallet.hd_wallet_storage.load_all_accounts()).unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].account_id, 0);
+    let hd_wallet = block_on(builder.watch_only_hd_wallet(&utxo_conf, ACCOUNT_ZPUB.to_owned())).unwrap();
+    assert_eq!(block_on(hd_wallet.accounts.lock()).len(), 1);
+    assert_eq!(
+        block_on(hd_wallet.hd_wallet_storage.load_all_accounts()).unwrap(),
+        stored
+    );
+
+    // An extended public key of the chain level can't be used.
+    let chain_xpub = account
+        .extended_pubkey
+        .derive_child(Bip44Chain::External.to_child_number())
+        .unwrap()
+        .to_string(bip32::Prefix::XPUB);
+    let error = block_on(builder.watch_only_hd_wallet(&utxo_conf, chain_xpub)).unwrap_err();
+    assert!(matches!(
+        error.into_inner(),
+        UtxoCoinBuildError::InvalidWatchOnlyXpub(_)
+    ));
+}
+
+#[test]
+fn test_watch_only_address() {
+    let ctx = mm_ctx_with_custom_db();
+    let conf = json!({
+        "coin": "BTC",
+        "asset": "BTC",
+        "rpcport": 8332,
+        "segwit": true,
+        "bech32_hrp": "bc",
+    });
+    let req = json!({ "method": "electrum", "servers": [] });
+    let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
+    let address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".to_owned();
+    let priv_key_policy = PrivKeyBuildPolicy::WatchOnly(WatchOnlyParams::Address(address));
+    let builder = UtxoArcBuilder::new(&ctx, "BTC", &conf, &params, priv_key_policy, UtxoStandardCoin::from);
+    let utxo_conf = UtxoConfBuilder::new(&conf, &params, "BTC").build().unwrap();
+
+    let supported = [
+        ("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", UtxoAddressFormat::Standard),
+        ("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", UtxoAddressFormat::Segwit),
+        (
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+            UtxoAddressFormat::Taproot,
+        ),
+    ];
+    for (address, expected_format) in supported {
+        let my_address = builder.watch_only_address(&utxo_conf, address).unwrap();
+        assert_eq!(my_address.addr_format, expected_format);
+        assert_eq!(my_address.to_string(), address);
+    }
+
+    let unsupported = [
+        // P2SH
+        "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+        // P2WSH
+        "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+        // Testnet P2WPKH
+        "tb1qdkwjk42dw6pryvs9sl0ht3pn3mxghuma64jst5",
+        "not an address",
+    ];
+    for address in unsupported {
+        let error = builder.watch_only_address(&utxo_conf, address).unwrap_err();
+        assert!(matches!(
+            error.into_inner(),
+            UtxoCoinBuildError::InvalidWatchOnlyAddress(_)
+        ));
+    }
+}
+
+#[test]
+#[cfg(not(target_arch = "wasm32"))]
+fn test_watch_only_coin_is_watch_only() {
+    let coin = utxo_coin_for_test(UtxoRpcClientEnum::Native(native_client_for_test()), None, false);
+    assert!(!coin.is_watch_only());
+
+    let mut fields = utxo_coin_fields_for_test(UtxoRpcClientEnum::Native(native_client_for_test()), None, false);
+    fields.priv_key_policy = PrivKeyPolicy::WatchOnly;
+    let coin = utxo_coin_from_fields(fields);
+    assert!(coin.is_watch_only());
+}
+
 #[test]
 fn test_tx_enum_from_bytes() {
     let client = electrum_client_for_test(RICK_ELECTRUM_ADDRS);
@@ -4316,7 +5512,7 @@ fn test_utxo_validate_valid_and_invalid_pubkey() {
         "check_utxo_maturity": true,
     });
 
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
     let priv_key = Secp256k1Secret::from([1; 32]);
@@ -4456,6 +5652,22 @@ fn test_block_header_utxo_loop() {
     };
 }
 
+#[test]
+fn test_taproot_signature_version_is_rejected() {
+    let conf = json!({
+        "coin": "BTC",
+        "asset": "BTC",
+        "rpcport": 8332,
+        "segwit": true,
+        "bech32_hrp": "bc",
+        "signature_version": "taproot",
+    });
+    let req = json!({ "method": "electrum", "servers": [] });
+    let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
+    let err = UtxoConfBuilder::new(&conf, &params, "BTC").build().unwrap_err();
+    assert!(matches!(err.into_inner(), UtxoConfError::InvalidSignatureVersion(_)));
+}
+
 #[test]
 fn test_spv_conf_with_verification() {
     let verification_params = BlockHeaderValidationParams {
@@ -1,11 +1,13 @@
 use crate::rpc_command::init_withdraw::{WithdrawInProgressStatus, WithdrawTaskHandle};
-use crate::utxo::utxo_common::{big_decimal_from_sat, UtxoTxBuilder};
-use crate::utxo::{output_script, sat_from_big_decimal, ActualTxFee, Address, FeePolicy, GetUtxoListOps, PrivKeyPolicy,
-                  UtxoAddressFormat, UtxoCoinFields, UtxoCommonOps, UtxoFeeDetails, UtxoTx, UTXO_LOCK};
-use crate::{CoinWithDerivationMethod, GetWithdrawSenderAddress, MarketCoinOps, TransactionDetails, WithdrawError,
-            WithdrawFee, WithdrawRequest, WithdrawResult};
+use crate::utxo::rpc_clients::UnspentInfo;
+use crate::utxo::utxo_common::{big_decimal_from_sat, get_prev_tx, get_tx_fee_for_target, UtxoTxBuilder};
+use crate::utxo::{output_script, prev_script_for_signing, sat_from_big_decimal, ActualTxFee, Address, FeePolicy,
+                  GetUtxoListOps, PrivKeyPolicy, UtxoAddressFormat, UtxoCoinFields, UtxoCommonOps, UtxoFeeDetails,
+                  UtxoFeeRateSource, UtxoTx, UTXO_LOCK};
+use crate::{CoinWithDerivationMethod, GetWithdrawSenderAddress, MarketCoinOps, PrivKeyPolicyNotAllowed,
+            TransactionDetails, WithdrawError, WithdrawFee, WithdrawInput, WithdrawRequest, WithdrawResult};
 use async_trait::async_trait;
-use chain::TransactionOutput;
+use chain::{OutPoint, TransactionOutput};
 use common::log::info;
 use common::now_sec;
 use crypto::trezor::{TrezorError, TrezorProcessingError};
@@ -13,11 +15,14 @@ use crypto::{from_hw_error, CryptoCtx, CryptoCtxError, DerivationPath, HwError,
 use keys::{Public as PublicKey, Type as ScriptType};
 use mm2_core::mm_ctx::MmArc;
 use mm2_err_handle::prelude::*;
+use primitives::hash::H256;
 use rpc::v1::types::ToTxHash;
 use rpc_task::RpcTaskError;
-use script::{Builder, Script, SignatureVersion, TransactionInputSigner};
+use script::{Script, SignatureVersion, TransactionInputSigner};
 use serialization::{serialize, serialize_with_flags, SERIALIZE_TRANSACTION_WITNESS};
+use std::collections::HashSet;
 use std::iter::once;
+use utxo_signer::psbt::{Psbt, PsbtError};
 use utxo_signer::sign_params::{OutputDestination, SendingOutputInfo, SpendingInputInfo, UtxoSignTxParamsBuilder};
 use utxo_signer::{with_key_pair, UtxoSignTxError};
 use utxo_signer::{SignPolicy, UtxoSignerOps};
@@ -100,11 +105,12 @@ where
     fn signature_version(&self) -> SignatureVersion {
         match self.sender_address().addr_format {
             UtxoAddressFormat::Segwit => SignatureVersion::WitnessV0,
+            UtxoAddressFormat::Taproot => SignatureVersion::Taproot,
             _ => self.coin().as_ref().conf.signature_version,
         }
     }
 
-    fn prev_script(&self) -> Script { Builder::build_p2pkh(&self.sender_address().hash) }
+    fn prev_script(&self) -> Script { prev_script_for_signing(&self.sender_address()) }
 
     #[allow(clippy::result_large_err)]
     fn on_generating_transaction(&self) -> Result<(), MmError<WithdrawError>>;
@@ -118,87 +124,96 @@ where
         let coin = self.coin();
         let ticker = coin.as_ref().conf.ticker.clone();
         let decimals = coin.as_ref().decimals;
-        let conf = &self.coin().as_ref().conf;
         let req = self.request();
 
-        let to = coin.address_from_str(&req.to)?;
-
-        let is_p2pkh = to.prefix == conf.pub_addr_prefix && to.t_addr_prefix == conf.pub_t_addr_prefix;
-        let is_p2sh = to.prefix == conf.p2sh_addr_prefix && to.t_addr_prefix == conf.p2sh_t_addr_prefix;
-
-        let script_type = if is_p2pkh {
-            ScriptType::P2PKH
-        } else if is_p2sh {
-            ScriptType::P2SH
-        } else {
-            return MmError::err(WithdrawError::InvalidAddress("Expected either P2PKH or P2SH".into()));
-        };
+        let withdraw_outputs = req.withdraw_outputs()?;
+        let mut outputs = Vec::with_capacity(withdraw_outputs.len());
+        let mut max_output_index = None;
+        for (index, withdraw_output) in withdraw_outputs.iter().enumerate() {
+            let value = if withdraw_output.max {
+                max_output_index = Some(index);
+                // The value is set below when the unspents are known.
+                0
+            } else {
+                sat_from_big_decimal(&withdraw_output.amount, decimals)?
+            };
+            outputs.push(withdraw_tx_output(coin, &withdraw_output.to, value)?);
+        }
 
         // Generate unsigned transaction.
         self.on_generating_transaction()?;
 
-        let script_pubkey = output_script(&to, script_type).to_bytes();
-
         let _utxo_lock = UTXO_LOCK.lock().await;
         let (unspents, _) = coin.get_unspent_ordered_list(&self.sender_address()).await?;
-        let (value, fee_policy) = if req.max {
-            (
-                unspents.iter().fold(0, |sum, unspent| sum + unspent.value),
-                FeePolicy::DeductFromOutput(0),
-            )
-        } else {
-            let value = sat_from_big_decimal(&req.amount, decimals)?;
-            (value, FeePolicy::SendExact)
+        let unspents = select_withdraw_inputs(unspents, &req.inputs, &self.sender_address_string())?;
+        let fee_policy = match max_output_index {
+            Some(max_output_index) => {
+                // The `max` output absorbs everything left after paying the rest outputs, and the fee is deducted from it.
+                let available = unspents.iter().fold(0, |sum, unspent| sum + unspent.value);
+                let required = outputs.iter().fold(0, |sum, output| sum + output.value);
+                outputs[max_output_index].value =
+                    available
+                        .checked_sub(required)
+                        .or_mm_err(|| WithdrawError::NotSufficientBalance {
+                            coin: ticker.clone(),
+                            available: big_decimal_from_sat(available as i64, decimals),
+                            required: big_decimal_from_sat(required as i64, decimals),
+                        })?;
+                FeePolicy::DeductFromOutput(max_output_index)
+            },
+            None => FeePolicy::SendExact,
         };
-        let outputs = vec![TransactionOutput { value, script_pubkey }];
 
         let mut tx_builder = UtxoTxBuilder::new(coin)
             .with_from_address(self.sender_address())
             .add_available_inputs(unspents)
             .add_outputs(outputs)
             .with_fee_policy(fee_policy);
+        if !req.inputs.is_empty() {
+            tx_builder = tx_builder.spend_all_available_inputs();
+        }
+        if req.rbf {
+            tx_builder = tx_builder.signal_rbf();
+        }
 
-        match req.fee {
-            Some(WithdrawFee::UtxoFixed { ref amount }) => {
-                let fixed = sat_from_big_decimal(amount, decimals)?;
-                tx_builder = tx_builder.with_fee(ActualTxFee::FixedPerKb(fixed));
-            },
-            Some(WithdrawFee::UtxoPerKbyte { ref amount }) => {
-                let dynamic = sat_from_big_decimal(amount, decimals)?;
-                tx_builder = tx_builder.with_fee(ActualTxFee::Dynamic(dynamic));
-            },
-            Some(ref fee_policy) => {
-                let error = format!(
-                    "Expected 'UtxoFixed' or 'UtxoPerKbyte' fee types, found {:?}",
-                    fee_policy
-                );
-                return MmError::err(WithdrawError::InvalidFeePolicy(error));
-            },
-            None => (),
-        };
+        let (fee_rate, fee_rate_source) = withdraw_fee_rate(coin, &req.fee).await?;
         let (unsigned, data) = tx_builder
+            .with_fee(fee_rate)
             .build()
             .await
             .mm_err(|gen_tx_error| WithdrawError::from_generate_tx_error(gen_tx_error, ticker.clone(), decimals))?;
 
-        // Sign the `unsigned` transaction.
-        let signed = self.sign_tx(unsigned).await?;
+        // Either sign the `unsigned` transaction or hand it over to an external signer as PSBT.
+        // Watch-only wallets can't sign, so they always produce a PSBT.
+        let (signed, psbt) = if req.psbt || coin.as_ref().priv_key_policy.is_watch_only() {
+            let psbt =
+                generate_withdraw_psbt(coin, &unsigned, &self.sender_address(), self.signature_version()).await?;
+            (psbt.unsigned_tx.clone(), Some(base64::encode(&psbt.serialize())))
+        } else {
+            (self.sign_tx(unsigned).await?, None)
+        };
 
         // Finish by generating `TransactionDetails` from the signed transaction.
         self.on_finishing()?;
 
         let fee_amount = data.fee_amount + data.unused_change.unwrap_or_default();
+        let fee_rate_sat = match fee_rate {
+            ActualTxFee::Dynamic(sat) | ActualTxFee::FixedPerKb(sat) => sat,
+        };
         let fee_details = UtxoFeeDetails {
             coin: Some(ticker.clone()),
             amount: big_decimal_from_sat(fee_amount as i64, decimals),
+            fee_rate: Some(big_decimal_from_sat(fee_rate_sat as i64, decimals)),
+            fee_rate_source: Some(fee_rate_source),
         };
-        let tx_hex = match coin.addr_format() {
-            UtxoAddressFormat::Segwit => serialize_with_flags(&signed, SERIALIZE_TRANSACTION_WITNESS).into(),
-            _ => serialize(&signed).into(),
+        let tx_hex = if coin.addr_format().is_witness() {
+            serialize_with_flags(&signed, SERIALIZE_TRANSACTION_WITNESS).into()
+        } else {
+            serialize(&signed).into()
         };
         Ok(TransactionDetails {
             from: vec![self.sender_address_string()],
-            to: vec![req.to.clone()],
+            to: withdraw_outputs.into_iter().map(|output| output.to).collect(),
             total_amount: big_decimal_from_sat(data.spent_by_me as i64, decimals),
             spent_by_me: big_decimal_from_sat(data.spent_by_me as i64, decimals),
             received_by_me: big_decimal_from_sat(data.received_by_me as i64, decimals),
@@ -213,10 +228,171 @@ where
             kmd_rewards: data.kmd_rewards,
             transaction_type: Default::default(),
             memo: None,
+            psbt,
+            to_name: None,
         })
     }
 }
 
+/// Resolves the fee rate per kbyte the withdraw is generated with and where it comes from.
+/// If the fee isn't specified, the fee rate is estimated for the coin's `estimate_fee_blocks`.
+async fn withdraw_fee_rate<Coin>(
+    coin: &Coin,
+    fee: &Option<WithdrawFee>,
+) -> Result<(ActualTxFee, UtxoFeeRateSource), MmError<WithdrawError>>
+where
+    Coin: UtxoCommonOps,
+{
+    let fields = coin.as_ref();
+    let target_blocks = match fee {
+        Some(WithdrawFee::UtxoFixed { amount }) => {
+            let fixed = sat_from_big_decimal(amount, fields.decimals)?;
+            return Ok((ActualTxFee::FixedPerKb(fixed), UtxoFeeRateSource::Manual));
+        },
+        Some(WithdrawFee::UtxoPerKbyte { amount }) => {
+            let dynamic = sat_from_big_decimal(amount, fields.decimals)?;
+            return Ok((ActualTxFee::Dynamic(dynamic), UtxoFeeRateSource::Manual));
+        },
+        Some(WithdrawFee::UtxoTargetBlocks { blocks: 0 }) => {
+            let error = "The confirmation target must be at least 1 block".to_owned();
+            return MmError::err(WithdrawError::InvalidFeePolicy(error));
+        },
+        Some(WithdrawFee::UtxoTargetBlocks { blocks }) => *blocks,
+        Some(WithdrawFee::UtxoPriority { priority }) => priority.target_blocks(),
+        Some(fee_policy) => {
+            let error = format!(
+                "Expected 'UtxoFixed', 'UtxoPerKbyte', 'UtxoTargetBlocks' or 'UtxoPriority' fee types, found {:?}",
+                fee_policy
+            );
+            return MmError::err(WithdrawError::InvalidFeePolicy(error));
+        },
+        None => fields.conf.estimate_fee_blocks,
+    };
+
+    match get_tx_fee_for_target(fields, target_blocks).await? {
+        dynamic @ ActualTxFee::Dynamic(_) => Ok((dynamic, UtxoFeeRateSource::Estimated { target_blocks })),
+        ActualTxFee::FixedPerKb(_) if fee.is_some() => {
+            let error = format!(
+                "'{}' coin uses the fixed fee rate, it can't be estimated for a confirmation target",
+                fields.conf.ticker
+            );
+            MmError::err(WithdrawError::InvalidFeePolicy(error))
+        },
+        fixed @ ActualTxFee::FixedPerKb(_) => Ok((fixed, UtxoFeeRateSource::CoinConfig)),
+    }
+}
+
+/// Checks if the `to` address is either P2PKH or P2SH and builds an output paying `value` to it.
+#[allow(clippy::result_large_err)]
+fn withdraw_tx_output<Coin>(coin: &Coin, to: &str, value: u64) -> Result<TransactionOutput, MmError<WithdrawError>>
+where
+    Coin: UtxoCommonOps,
+{
+    let conf = &coin.as_ref().conf;
+    let to = coin.address_from_str(to)?;
+
+    let is_p2pkh = to.prefix == conf.pub_addr_prefix && to.t_addr_prefix == conf.pub_t_addr_prefix;
+    let is_p2sh = to.prefix == conf.p2sh_addr_prefix && to.t_addr_prefix == conf.p2sh_t_addr_prefix;
+
+    let script_type = if is_p2pkh {
+        ScriptType::P2PKH
+    } else if is_p2sh {
+        ScriptType::P2SH
+    } else {
+        return MmError::err(WithdrawError::InvalidAddress("Expected either P2PKH or P2SH".into()));
+    };
+
+    Ok(TransactionOutput {
+        value,
+        script_pubkey: output_script(&to, script_type).to_bytes(),
+    })
+}
+
+/// Generates a PSBT of the `unsigned` transaction spending the outputs of the `from` address.
+/// The spent outputs are attached as BIP-174 requires: the whole previous transactions for non-witness inputs
+/// and the spent outputs only for witness inputs.
+async fn generate_withdraw_psbt<Coin>(
+    coin: &Coin,
+    unsigned: &TransactionInputSigner,
+    from: &Address,
+    signature_version: SignatureVersion,
+) -> Result<Psbt, MmError<WithdrawError>>
+where
+    Coin: UtxoCommonOps,
+{
+    let ticker = coin.as_ref().conf.ticker.clone();
+    let mut psbt = Psbt::from_unsigned(unsigned).mm_err(|e| match e {
+        PsbtError::UnsupportedTxFormat => WithdrawError::CoinDoesntSupportPsbt { coin: ticker },
+        other => WithdrawError::InternalError(other.to_string()),
+    })?;
+
+    // BIP-371 signers need the internal key to find out which key the taproot output key is tweaked from.
+    let tap_internal_key = match (signature_version, &coin.as_ref().priv_key_policy) {
+        (SignatureVersion::Taproot, PrivKeyPolicy::KeyPair(key_pair)) => {
+            let internal_key = key_pair
+                .public()
+                .x_only()
+                .map_to_mm(|e| WithdrawError::InternalError(e.to_string()))?;
+            Some(H256::from(internal_key.serialize()))
+        },
+        _ => None,
+    };
+
+    let prev_script = output_script(from, ScriptType::P2PKH);
+    for (psbt_input, input) in psbt.inputs.iter_mut().zip(unsigned.inputs.iter()) {
+        if matches!(
+            signature_version,
+            SignatureVersion::WitnessV0 | SignatureVersion::Taproot
+        ) {
+            psbt_input.witness_utxo = Some(TransactionOutput {
+                value: input.amount,
+                script_pubkey: prev_script.to_bytes(),
+            });
+            psbt_input.tap_internal_key = tap_internal_key;
+            continue;
+        }
+
+        psbt_input.non_witness_utxo = Some(get_prev_tx(coin, &input.previous_output.hash).await?);
+    }
+    Ok(psbt)
+}
+
+/// Leaves only the `inputs` explicitly selected by the user if there are any.
+/// Otherwise, all of the `unspents` are available to fund the transaction.
+#[allow(clippy::result_large_err)]
+fn select_withdraw_inputs(
+    unspents: Vec<UnspentInfo>,
+    inputs: &[WithdrawInput],
+    from_address: &str,
+) -> Result<Vec<UnspentInfo>, MmError<WithdrawError>> {
+    if inputs.is_empty() {
+        return Ok(unspents);
+    }
+
+    let available: HashSet<OutPoint> = unspents.iter().map(|unspent| unspent.outpoint).collect();
+    let mut selected = HashSet::with_capacity(inputs.len());
+    for input in inputs {
+        let outpoint = OutPoint {
+            hash: input.tx_hash.reversed().into(),
+            index: input.vout,
+        };
+        if !available.contains(&outpoint) {
+            let error = format!(
+                "{}:{} is not a spendable unspent output of {}. It may be spent, frozen or immature",
+                input.tx_hash, input.vout, from_address
+            );
+            return MmError::err(WithdrawError::InvalidInputs(error));
+        }
+        selected.insert(outpoint);
+    }
+
+    // Keep the ascending order of the unspents.
+    Ok(unspents
+        .into_iter()
+        .filter(|unspent| selected.contains(&unspent.outpoint))
+        .collect())
+}
+
 pub struct InitUtxoWithdraw<'a, Coin> {
     ctx: MmArc,
     coin: Coin,
@@ -245,16 +421,25 @@ where
     fn request(&self) -> &WithdrawRequest { &self.req }
 
     fn on_generating_transaction(&self) -> Result<(), MmError<WithdrawError>> {
-        let amount_display = if self.req.max {
-            "MAX".to_owned()
-        } else {
-            self.req.amount.to_string()
-        };
+        let outputs_display = self
+            .req
+            .withdraw_outputs()?
+            .into_iter()
+            .map(|output| {
+                let amount_display = if output.max {
+                    "MAX".to_owned()
+                } else {
+                    output.amount.to_string()
+                };
+                format!("{} {} to {}", amount_display, self.req.coin, output.to)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
 
         // Display the address from which we are trying to withdraw funds.
         info!(
-            "Trying to withdraw {} {} from {} to {}",
-            amount_display, self.req.coin, self.from_address_string, self.req.to,
+            "Trying to withdraw from {}: {}",
+            self.from_address_string, outputs_display
         );
 
         Ok(self
@@ -280,20 +465,22 @@ where
             address_pubkey: self.from_pubkey,
         }));
 
-        sign_params.add_outputs_infos(once(SendingOutputInfo {
-            destination_address: OutputDestination::plain(self.req.to.clone()),
+        let withdraw_outputs = self.req.withdraw_outputs()?;
+        let recipients_count = withdraw_outputs.len();
+        sign_params.add_outputs_infos(withdraw_outputs.into_iter().map(|output| SendingOutputInfo {
+            destination_address: OutputDestination::plain(output.to),
         }));
-        match unsigned_tx.outputs.len() {
+        match unsigned_tx.outputs.len().checked_sub(recipients_count) {
             // There is no change output.
-            1 => (),
+            Some(0) => (),
             // There is a change output.
-            2 => {
+            Some(1) => {
                 sign_params.add_outputs_infos(once(SendingOutputInfo {
                     destination_address: OutputDestination::change(self.from_derivation_path.clone()),
                 }));
             },
-            unexpected => {
-                let error = format!("Unexpected number of outputs: {}", unexpected);
+            _ => {
+                let error = format!("Unexpected number of outputs: {}", unsigned_tx.outputs.len());
                 return MmError::err(WithdrawError::InternalError(error));
             },
         }
@@ -301,7 +488,7 @@ where
         sign_params
             .with_signature_version(self.signature_version())
             .with_unsigned_tx(unsigned_tx)
-            .with_prev_script(Builder::build_p2pkh(&self.from_address.hash));
+            .with_prev_script(self.prev_script());
         let sign_params = sign_params.build()?;
 
         let crypto_ctx = CryptoCtx::from_ctx(&self.ctx)?;
@@ -315,6 +502,11 @@ where
                 let trezor_session = hw_ctx.trezor().await?;
                 SignPolicy::WithTrezor(trezor_session)
             },
+            PrivKeyPolicy::WatchOnly => {
+                return MmError::err(WithdrawError::from(
+                    PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported,
+                ))
+            },
         };
 
         self.task_handle
@@ -0,0 +1,201 @@
+//! A minimal implementation of the [BIP-322](https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki)
+//! generic signed message format.
+//!
+//! Only the "simple" variant is supported, i.e. the signature is the witness stack of the virtual `to_sign` transaction.
+//! That's enough to prove the ownership of P2WPKH and P2TR (key path) addresses.
+
+use bitcrypto::tagged_hash;
+use chain::{OutPoint, Transaction as UtxoTx, TransactionInput, TransactionOutput};
+use derive_more::Display;
+use keys::bytes::Bytes;
+use keys::{verify_schnorr, AddressHashEnum, KeyPair, Public as PublicKey};
+use mm2_err_handle::prelude::*;
+use primitives::hash::H256;
+use script::{Builder, Opcode, Script, SignatureVersion, TransactionInputSigner};
+use serialization::{serialize_list, Reader};
+
+const BIP322_TAG: &[u8] = b"BIP0322-signed-message";
+
+const SIGHASH_DEFAULT: u32 = 0;
+const SIGHASH_ALL: u32 = 1;
+const SCHNORR_SIGNATURE_LEN: usize = 64;
+
+pub type Bip322Result<T> = Result<T, MmError<Bip322Error>>;
+
+#[derive(Debug, Display)]
+pub enum Bip322Error {
+    #[display(
+        fmt = "BIP-322 simple signatures are supported for P2WPKH and P2TR addresses only, got '{}' script",
+        _0
+    )]
+    UnsupportedScript(Script),
+    #[display(fmt = "The key pair doesn't match the '{}' script", _0)]
+    MismatchScript(Script),
+    #[display(fmt = "Invalid signature: {}", _0)]
+    InvalidSignature(String),
+    #[display(fmt = "Error signing the message: {}", _0)]
+    ErrorSigning(keys::Error),
+}
+
+impl From<keys::Error> for Bip322Error {
+    fn from(e: keys::Error) -> Self { Bip322Error::ErrorSigning(e) }
+}
+
+/// Returns the `tagged_hash("BIP0322-signed-message", message)` committed to by the `to_spend` transaction.
+pub fn message_hash(message: &[u8]) -> H256 { tagged_hash(BIP322_TAG, message) }
+
+/// Signs the `message` on behalf of the `script_pubkey` owner and returns the serialized witness stack.
+/// `script_pubkey` is expected to be either a P2WPKH or a P2TR script derived from the `key_pair`.
+pub fn sign_simple(key_pair: &KeyPair, script_pubkey: &Script, message: &[u8]) -> Bip322Result<Bytes> {
+    let signer = to_sign_tx(message, script_pubkey);
+
+    let witness = if script_pubkey.is_pay_to_witness_key_hash() {
+        let public = key_pair.public();
+        if Builder::build_witness_script(&AddressHashEnum::from(public.address_hash())) != *script_pubkey {
+            return MmError::err(Bip322Error::MismatchScript(script_pubkey.clone()));
+        }
+        let sighash = p2wpkh_sighash(&signer, public, SIGHASH_ALL);
+        let mut signature = key_pair.private().sign(&sighash)?.to_vec();
+        signature.push(SIGHASH_ALL as u8);
+        vec![Bytes::from(signature), Bytes::from(public.to_vec())]
+    } else if script_pubkey.is_pay_to_taproot() {
+        let output_key = key_pair.public().taproot_output_key()?;
+        if Builder::build_p2tr(&AddressHashEnum::WitnessScriptHash(output_key)) != *script_pubkey {
+            return MmError::err(Bip322Error::MismatchScript(script_pubkey.clone()));
+        }
+        let sighash = p2tr_sighash(&signer, script_pubkey, SIGHASH_DEFAULT)?;
+        let signature = key_pair.private().sign_taproot_key_path(&sighash)?;
+        vec![Bytes::from(signature.to_vec())]
+    } else {
+        return MmError::err(Bip322Error::UnsupportedScript(script_pubkey.clone()));
+    };
+
+    Ok(serialize_list::<Bytes, Bytes>(&witness))
+}
+
+/// Verifies the serialized witness stack produced by [`sign_simple`] against the `script_pubkey`.
+pub fn verify_simple(script_pubkey: &Script, message: &[u8], signature: &[u8]) -> Bip322Result<bool> {
+    let mut reader = Reader::new(signature);
+    let witness: Vec<Bytes> = reader
+        .read_list()
+        .map_to_mm(|e| Bip322Error::InvalidSignature(format!("Error parsing the witness stack: {:?}", e)))?;
+    if !reader.is_finished() {
+        return MmError::err(Bip322Error::InvalidSignature(
+            "Unexpected bytes after the witness stack".to_owned(),
+        ));
+    }
+
+    let signer = to_sign_tx(message, script_pubkey);
+
+    if script_pubkey.is_pay_to_witness_key_hash() {
+        let (signature, public) = match witness.as_slice() {
+            [signature, public] if !signature.is_empty() => (signature, public),
+            _ => {
+                let error = format!("P2WPKH witness must contain 2 items, found {}", witness.len());
+                return MmError::err(Bip322Error::InvalidSignature(error));
+            },
+        };
+        let public = PublicKey::from_slice(public)
+            .map_to_mm(|e| Bip322Error::InvalidSignature(format!("Invalid public key: {}", e)))?;
+        if !matches!(public, PublicKey::Compressed(_)) || *public.address_hash() != script_pubkey[2..] {
+            return Ok(false);
+        }
+
+        let (sighash_type, der_signature) = signature.split_last().expect("signature is checked to be not empty");
+        let sighash = p2wpkh_sighash(&signer, &public, *sighash_type as u32);
+        Ok(public.verify(&sighash, &der_signature.to_vec().into()).unwrap_or(false))
+    } else if script_pubkey.is_pay_to_taproot() {
+        let signature = match witness.as_slice() {
+            [signature] => signature,
+            _ => {
+                let error = format!("P2TR key path witness must contain 1 item, found {}", witness.len());
+                return MmError::err(Bip322Error::InvalidSignature(error));
+            },
+        };
+        let (schnorr_signature, sighash_type) = match signature.len() {
+            SCHNORR_SIGNATURE_LEN => (signature.as_slice(), SIGHASH_DEFAULT),
+            len if len == SCHNORR_SIGNATURE_LEN + 1 && signature[SCHNORR_SIGNATURE_LEN] != 0 => (
+                &signature[..SCHNORR_SIGNATURE_LEN],
+                signature[SCHNORR_SIGNATURE_LEN] as u32,
+            ),
+            len => {
+                let error = format!("Unexpected Schnorr signature length {}", len);
+                return MmError::err(Bip322Error::InvalidSignature(error));
+            },
+        };
+
+        let output_key = H256::from(&script_pubkey[2..]);
+        let sighash = match p2tr_sighash(&signer, script_pubkey, sighash_type) {
+            Ok(sighash) => sighash,
+            // E.g. an undefined sighash type, the signature is invalid then.
+            Err(_) => return Ok(false),
+        };
+        Ok(verify_schnorr(&output_key, &sighash, schnorr_signature).unwrap_or(false))
+    } else {
+        MmError::err(Bip322Error::UnsupportedScript(script_pubkey.clone()))
+    }
+}
+
+/// The virtual transaction spending nothing and paying 0 to the `script_pubkey`, committed to the `message`.
+fn to_spend_tx(message: &[u8], script_pubkey: &Script) -> UtxoTx {
+    let script_sig = Builder::default()
+        .push_opcode(Opcode::OP_0)
+        .push_data(&*message_hash(message))
+        .into_bytes();
+    UtxoTx {
+        version: 0,
+        inputs: vec![TransactionInput {
+            previous_output: OutPoint {
+                hash: H256::default(),
+                index: u32::MAX,
+            },
+            script_sig,
+            sequence: 0,
+            script_witness: vec![],
+        }],
+        outputs: vec![TransactionOutput {
+            value: 0,
+            script_pubkey: script_pubkey.to_bytes(),
+        }],
+        lock_time: 0,
+        ..UtxoTx::default()
+    }
+}
+
+/// The virtual transaction spending the only output of [`to_spend_tx`] to an `OP_RETURN` output.
+fn to_sign_tx(message: &[u8], script_pubkey: &Script) -> TransactionInputSigner {
+    let to_spend = to_spend_tx(message, script_pubkey);
+    let to_sign = UtxoTx {
+        version: 0,
+        inputs: vec![TransactionInput {
+            previous_output: OutPoint {
+                hash: to_spend.hash(),
+                index: 0,
+            },
+            script_sig: Bytes::default(),
+            sequence: 0,
+            script_witness: vec![],
+        }],
+        outputs: vec![TransactionOutput {
+            value: 0,
+            script_pubkey: Builder::default().push_opcode(Opcode::OP_RETURN).into_bytes(),
+        }],
+        lock_time: 0,
+        ..UtxoTx::default()
+    };
+    // The spent amount is 0, that's what the `From<TransactionInput>` conversion sets.
+    TransactionInputSigner::from(to_sign)
+}
+
+/// BIP-143 signature hash of the `to_sign` input, the script code of a P2WPKH output is the corresponding P2PKH script.
+fn p2wpkh_sighash(signer: &TransactionInputSigner, public: &PublicKey, sighash_type: u32) -> H256 {
+    let script_code = Builder::build_p2pkh(&AddressHashEnum::from(public.address_hash()));
+    signer.signature_hash(0, 0, &script_code, SignatureVersion::WitnessV0, sighash_type)
+}
+
+/// BIP-341 signature hash of the `to_sign` input spending the `script_pubkey` output of [`to_spend_tx`].
+fn p2tr_sighash(signer: &TransactionInputSigner, script_pubkey: &Script, sighash_type: u32) -> Bip322Result<H256> {
+    signer
+        .signature_hash_taproot(0, &[script_pubkey.clone()], sighash_type)
+        .map_to_mm(|e| Bip322Error::InvalidSignature(format!("Error calculating the signature hash: {}", e)))
+}
@@ -8,6 +8,8 @@ use mm2_err_handle::prelude::*;
 use rpc::v1::types::{Transaction as RpcTransaction, H256 as H256Json};
 use script::Script;
 
+pub mod bip322;
+pub mod psbt;
 mod sign_common;
 pub mod sign_params;
 pub mod with_key_pair;
@@ -32,6 +34,8 @@ pub enum UtxoSignTxError {
     CoinNotSupportedWithTrezor { coin: String },
     #[display(fmt = "Trezor doesn't support P2WPKH outputs yet")]
     TrezorDoesntSupportP2WPKH,
+    #[display(fmt = "Trezor doesn't support P2TR outputs yet")]
+    TrezorDoesntSupportP2TR,
     #[display(fmt = "Trezor client error: {}", _0)]
     TrezorError(TrezorError),
     #[display(fmt = "Encountered invalid parameter '{}': {}", param, description)]
@@ -81,7 +85,9 @@ impl From<UtxoSignWithKeyPairError> for UtxoSignTxError {
             // `with_key_pair` contains methods that checks parameters
             // that are expected to be checked by [`sign_common::UtxoSignTxParamsBuilder::build`] already.
             // So if this error happens, it's our internal error.
-            UtxoSignWithKeyPairError::InputIndexOutOfBound { .. } => UtxoSignTxError::Internal(error),
+            UtxoSignWithKeyPairError::InputIndexOutOfBound { .. } | UtxoSignWithKeyPairError::InvalidSighash(_) => {
+                UtxoSignTxError::Internal(error)
+            },
             UtxoSignWithKeyPairError::ErrorSigning(sign) => UtxoSignTxError::ErrorSigning(sign),
         }
     }
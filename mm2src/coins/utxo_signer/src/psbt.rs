@@ -0,0 +1,816 @@
+//! A minimal implementation of [BIP-174](https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki)
+//! Partially Signed Bitcoin Transactions.
+//!
+//! It covers exactly what is needed to hand an unsigned transaction over to an external signer (e.g. an offline one)
+//! or to the co-signers of a multisig wallet, and to finalize the transaction once it's signed there:
+//! the unsigned transaction, the spent outputs, the redeem and witness scripts, the partial signatures
+//! and the finalized scripts.
+//! The rest of the key-value pairs are kept as is, so they are serialized back without changes.
+
+use crate::sign_common::{complete_tx, script_sig};
+use bitcrypto::{dhash160, sha256};
+use chain::{Transaction as UtxoTx, TransactionInput, TransactionOutput};
+use derive_more::Display;
+use keys::bytes::Bytes;
+use keys::{AddressHashEnum, KeyPair, Public as PublicKey};
+use mm2_err_handle::prelude::*;
+use primitives::hash::H256;
+use script::{Builder, Opcode, Script, SignatureVersion, TransactionInputSigner};
+use serialization::{deserialize, serialize, serialize_list, serialize_with_flags, Reader, Stream,
+                    SERIALIZE_TRANSACTION_WITNESS};
+use std::collections::HashSet;
+
+const PSBT_MAGIC: &[u8; 5] = b"psbt\xff";
+
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+
+const PSBT_IN_NON_WITNESS_UTXO: u8 = 0x00;
+const PSBT_IN_WITNESS_UTXO: u8 = 0x01;
+const PSBT_IN_PARTIAL_SIG: u8 = 0x02;
+const PSBT_IN_SIGHASH_TYPE: u8 = 0x03;
+const PSBT_IN_REDEEM_SCRIPT: u8 = 0x04;
+const PSBT_IN_WITNESS_SCRIPT: u8 = 0x05;
+const PSBT_IN_BIP32_DERIVATION: u8 = 0x06;
+const PSBT_IN_FINAL_SCRIPTSIG: u8 = 0x07;
+const PSBT_IN_FINAL_SCRIPTWITNESS: u8 = 0x08;
+// https://github.com/bitcoin/bips/blob/master/bip-0371.mediawiki
+const PSBT_IN_TAP_KEY_SIG: u8 = 0x13;
+const PSBT_IN_TAP_INTERNAL_KEY: u8 = 0x17;
+
+const PSBT_OUT_REDEEM_SCRIPT: u8 = 0x00;
+const PSBT_OUT_WITNESS_SCRIPT: u8 = 0x01;
+const PSBT_OUT_BIP32_DERIVATION: u8 = 0x02;
+
+/// The input key types that don't have the key data.
+const PSBT_IN_KEY_TYPES_WITHOUT_KEY_DATA: &[u8] = &[
+    PSBT_IN_NON_WITNESS_UTXO,
+    PSBT_IN_WITNESS_UTXO,
+    PSBT_IN_SIGHASH_TYPE,
+    PSBT_IN_REDEEM_SCRIPT,
+    PSBT_IN_WITNESS_SCRIPT,
+    PSBT_IN_FINAL_SCRIPTSIG,
+    PSBT_IN_FINAL_SCRIPTWITNESS,
+    PSBT_IN_TAP_KEY_SIG,
+    PSBT_IN_TAP_INTERNAL_KEY,
+];
+
+pub type PsbtResult<T> = Result<T, MmError<PsbtError>>;
+
+#[derive(Debug, Display)]
+pub enum PsbtError {
+    #[display(fmt = "PSBT can be used with standard Bitcoin transactions only")]
+    UnsupportedTxFormat,
+    #[display(fmt = "Invalid PSBT: {}", _0)]
+    InvalidPsbt(String),
+    #[display(fmt = "The spent output of the input {} is unknown", index)]
+    UnknownSpentOutput { index: usize },
+    #[display(fmt = "The input {} spends an unsupported script '{}'", index, script)]
+    UnsupportedScript { index: usize, script: Script },
+    #[display(fmt = "The input {} is not signed", index)]
+    InputNotSigned { index: usize },
+    #[display(
+        fmt = "The input {} requires {} signatures, but only {} are provided",
+        index,
+        required,
+        actual
+    )]
+    NotEnoughSignatures {
+        index: usize,
+        required: usize,
+        actual: usize,
+    },
+    #[display(fmt = "Error signing using a private key: {}", _0)]
+    ErrorSigning(keys::Error),
+    #[display(fmt = "The input {} is not finalized", index)]
+    InputNotFinalized { index: usize },
+}
+
+/// Key-value pairs where every key starts with the key type.
+pub type PsbtKeyValues = Vec<(Bytes, Bytes)>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Psbt {
+    /// The transaction with empty `script_sig` and `script_witness` of every input.
+    pub unsigned_tx: UtxoTx,
+    /// The number of elements is the same as `unsigned_tx.inputs.len()`.
+    pub inputs: Vec<PsbtInput>,
+    /// The number of elements is the same as `unsigned_tx.outputs.len()`.
+    pub outputs: Vec<PsbtOutput>,
+    /// The global key-value pairs except for the unsigned transaction.
+    pub unknown: PsbtKeyValues,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PsbtInput {
+    /// The whole transaction the input spends an output of. Set for the inputs spending non-witness outputs.
+    pub non_witness_utxo: Option<UtxoTx>,
+    /// The output the input spends. Set for the inputs spending witness outputs.
+    pub witness_utxo: Option<TransactionOutput>,
+    /// The signatures (including the sighash type) by the public keys.
+    pub partial_sigs: Vec<(PublicKey, Bytes)>,
+    /// The redeem script of the P2SH output the input spends.
+    pub redeem_script: Option<Bytes>,
+    /// The witness script of the P2WSH output the input spends.
+    pub witness_script: Option<Bytes>,
+    /// The BIP-340 signature (and the sighash type if it's not `SIGHASH_DEFAULT`) of the taproot key path spending.
+    pub tap_key_sig: Option<Bytes>,
+    /// The x-only internal key the taproot output key is tweaked from.
+    pub tap_internal_key: Option<H256>,
+    pub final_script_sig: Option<Bytes>,
+    pub final_script_witness: Option<Vec<Bytes>>,
+    pub unknown: PsbtKeyValues,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PsbtOutput {
+    pub unknown: PsbtKeyValues,
+}
+
+impl Psbt {
+    /// Creates a PSBT with no information about the spent outputs.
+    /// They are expected to be added by [`PsbtInput::non_witness_utxo`] or [`PsbtInput::witness_utxo`].
+    pub fn from_unsigned(unsigned: &TransactionInputSigner) -> PsbtResult<Psbt> {
+        if unsigned.overwintered
+            || unsigned.zcash
+            || unsigned.posv
+            || unsigned.n_time.is_some()
+            || unsigned.str_d_zeel.is_some()
+        {
+            return MmError::err(PsbtError::UnsupportedTxFormat);
+        }
+
+        let inputs = unsigned
+            .inputs
+            .iter()
+            .map(|input| TransactionInput {
+                previous_output: input.previous_output,
+                script_sig: Bytes::default(),
+                sequence: input.sequence,
+                script_witness: vec![],
+            })
+            .collect();
+        let unsigned_tx = complete_tx(unsigned.clone(), inputs);
+        Ok(Psbt {
+            inputs: vec![PsbtInput::default(); unsigned_tx.inputs.len()],
+            outputs: vec![PsbtOutput::default(); unsigned_tx.outputs.len()],
+            unsigned_tx,
+            unknown: Vec::new(),
+        })
+    }
+
+    pub fn serialize(&self) -> Bytes {
+        let mut stream = Stream::new();
+        stream.append_slice(PSBT_MAGIC);
+
+        let mut global = vec![(key(PSBT_GLOBAL_UNSIGNED_TX, &[]), serialize(&self.unsigned_tx))];
+        global.extend(self.unknown.iter().cloned());
+        append_key_values(&mut stream, &global);
+
+        for input in self.inputs.iter() {
+            let mut key_values = Vec::new();
+            if let Some(ref tx) = input.non_witness_utxo {
+                let tx_bytes = serialize_with_flags(tx, SERIALIZE_TRANSACTION_WITNESS);
+                key_values.push((key(PSBT_IN_NON_WITNESS_UTXO, &[]), tx_bytes));
+            }
+            if let Some(ref output) = input.witness_utxo {
+                key_values.push((key(PSBT_IN_WITNESS_UTXO, &[]), serialize(output)));
+            }
+            for (pubkey, signature) in input.partial_sigs.iter() {
+                key_values.push((key(PSBT_IN_PARTIAL_SIG, pubkey), signature.clone()));
+            }
+            if let Some(ref redeem_script) = input.redeem_script {
+                key_values.push((key(PSBT_IN_REDEEM_SCRIPT, &[]), redeem_script.clone()));
+            }
+            if let Some(ref witness_script) = input.witness_script {
+                key_values.push((key(PSBT_IN_WITNESS_SCRIPT, &[]), witness_script.clone()));
+            }
+            if let Some(ref signature) = input.tap_key_sig {
+                key_values.push((key(PSBT_IN_TAP_KEY_SIG, &[]), signature.clone()));
+            }
+            if let Some(ref internal_key) = input.tap_internal_key {
+                key_values.push((key(PSBT_IN_TAP_INTERNAL_KEY, &[]), Bytes::from(internal_key.to_vec())));
+            }
+            if let Some(ref script_sig) = input.final_script_sig {
+                key_values.push((key(PSBT_IN_FINAL_SCRIPTSIG, &[]), script_sig.clone()));
+            }
+            if let Some(ref witness) = input.final_script_witness {
+                let witness_bytes = serialize_list::<Bytes, Bytes>(witness);
+                key_values.push((key(PSBT_IN_FINAL_SCRIPTWITNESS, &[]), witness_bytes));
+            }
+            key_values.extend(input.unknown.iter().cloned());
+            append_key_values(&mut stream, &key_values);
+        }
+
+        for output in self.outputs.iter() {
+            append_key_values(&mut stream, &output.unknown);
+        }
+        stream.out()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> PsbtResult<Psbt> {
+        let mut reader = Reader::new(bytes);
+        let mut magic = [0; 5];
+        reader.read_slice(&mut magic).map_to_mm(invalid_psbt)?;
+        if &magic != PSBT_MAGIC {
+            return MmError::err(PsbtError::InvalidPsbt("Invalid magic bytes".to_owned()));
+        }
+
+        let mut unsigned_tx = None;
+        let mut unknown = Vec::new();
+        for (key, value) in read_key_values(&mut reader)? {
+            match key.as_ref() {
+                [PSBT_GLOBAL_UNSIGNED_TX] => unsigned_tx = Some(deserialize_unsigned_tx(&value)?),
+                [PSBT_GLOBAL_UNSIGNED_TX, ..] => return MmError::err(unexpected_key_data(&key)),
+                _ => unknown.push((key, value)),
+            }
+        }
+        let unsigned_tx =
+            unsigned_tx.or_mm_err(|| PsbtError::InvalidPsbt("The unsigned transaction is missing".to_owned()))?;
+
+        let mut inputs = Vec::with_capacity(unsigned_tx.inputs.len());
+        for _ in 0..unsigned_tx.inputs.len() {
+            inputs.push(read_input(&mut reader)?);
+        }
+        let mut outputs = Vec::with_capacity(unsigned_tx.outputs.len());
+        for _ in 0..unsigned_tx.outputs.len() {
+            outputs.push(read_output(&mut reader)?);
+        }
+        if !reader.is_finished() {
+            return MmError::err(PsbtError::InvalidPsbt("Unexpected data after the outputs".to_owned()));
+        }
+
+        Ok(Psbt {
+            unsigned_tx,
+            inputs,
+            outputs,
+            unknown,
+        })
+    }
+
+    /// Merges the signatures and the other information known by the `other` PSBT of the same transaction,
+    /// e.g. the one signed by another co-signer of a multisig wallet.
+    pub fn combine(&mut self, other: Psbt) -> PsbtResult<()> {
+        if self.unsigned_tx != other.unsigned_tx {
+            let error = "The PSBTs are of different transactions".to_owned();
+            return MmError::err(PsbtError::InvalidPsbt(error));
+        }
+
+        for (input, other) in self.inputs.iter_mut().zip(other.inputs) {
+            for (pubkey, signature) in other.partial_sigs {
+                if !input.partial_sigs.iter().any(|(known, _)| *known == pubkey) {
+                    input.partial_sigs.push((pubkey, signature));
+                }
+            }
+            input.non_witness_utxo = input.non_witness_utxo.take().or(other.non_witness_utxo);
+            input.witness_utxo = input.witness_utxo.take().or(other.witness_utxo);
+            input.redeem_script = input.redeem_script.take().or(other.redeem_script);
+            input.witness_script = input.witness_script.take().or(other.witness_script);
+            input.tap_key_sig = input.tap_key_sig.take().or(other.tap_key_sig);
+            input.tap_internal_key = input.tap_internal_key.take().or(other.tap_internal_key);
+            input.final_script_sig = input.final_script_sig.take().or(other.final_script_sig);
+            input.final_script_witness = input.final_script_witness.take().or(other.final_script_witness);
+            merge_unknown(&mut input.unknown, other.unknown);
+        }
+        for (output, other) in self.outputs.iter_mut().zip(other.outputs) {
+            merge_unknown(&mut output.unknown, other.unknown);
+        }
+        merge_unknown(&mut self.unknown, other.unknown);
+        Ok(())
+    }
+
+    /// Signs every not finalized input spending a P2SH or P2WSH multisig output the `key_pair` is a co-signer of,
+    /// and adds the signature to the partial signatures of the input. Returns the number of the signed inputs.
+    /// `signature_version` is used to sign the P2SH inputs, the P2WSH ones are always signed as witness v0 inputs.
+    pub fn sign_multisig_inputs(
+        &mut self,
+        key_pair: &KeyPair,
+        signature_version: SignatureVersion,
+        fork_id: u32,
+    ) -> PsbtResult<usize> {
+        let signer = TransactionInputSigner::from(self.unsigned_tx.clone());
+        let mut signed = 0;
+        for index in 0..self.inputs.len() {
+            if self.inputs[index].is_finalized() {
+                continue;
+            }
+            let (script_code, signature_version) = match self.multisig_script(index, signature_version)? {
+                Some(multisig) => multisig,
+                None => continue,
+            };
+            let pubkeys = script_code.extract_multisig_pubkeys().map_to_mm(invalid_psbt)?;
+            if !pubkeys.contains(key_pair.public()) {
+                continue;
+            }
+
+            let amount = self.spent_output(index)?.value;
+            let sighash = signer.signature_hash(index, amount, &script_code, signature_version, 1 | fork_id);
+            let signature = key_pair.private().sign(&sighash).map_to_mm(PsbtError::ErrorSigning)?;
+            let signature = script_sig(Bytes::from(signature.to_vec()), fork_id);
+
+            let input = &mut self.inputs[index];
+            input.partial_sigs.retain(|(pubkey, _)| pubkey != key_pair.public());
+            input.partial_sigs.push((*key_pair.public(), signature));
+            signed += 1;
+        }
+        Ok(signed)
+    }
+
+    /// Returns the multisig script the input with the given `index` is signed with and the signature version,
+    /// if the input spends a P2SH or P2WSH multisig output.
+    fn multisig_script(
+        &self,
+        index: usize,
+        signature_version: SignatureVersion,
+    ) -> PsbtResult<Option<(Script, SignatureVersion)>> {
+        let input = &self.inputs[index];
+        let (script, signature_version) = match (&input.redeem_script, &input.witness_script) {
+            (_, Some(witness_script)) => (Script::from(witness_script.clone()), SignatureVersion::WitnessV0),
+            (Some(redeem_script), None) => (Script::from(redeem_script.clone()), signature_version),
+            (None, None) => return Ok(None),
+        };
+        if !script.is_multisig_script() {
+            return Ok(None);
+        }
+
+        let prev_script: Script = self.spent_output(index)?.script_pubkey.into();
+        if prev_script != multisig_script_pubkey(&script, signature_version) {
+            let error = format!(
+                "The multisig script of the input {} doesn't match the spent output",
+                index
+            );
+            return MmError::err(PsbtError::InvalidPsbt(error));
+        }
+        Ok(Some((script, signature_version)))
+    }
+
+    /// Builds the final `script_sig` or `script_witness` of every signed input from its partial signatures.
+    /// Only P2PK, P2PKH, P2WPKH, P2TR (key path), P2SH multisig and P2WSH multisig outputs can be spent for now.
+    pub fn finalize(&mut self) -> PsbtResult<()> {
+        for index in 0..self.inputs.len() {
+            if self.inputs[index].is_finalized() {
+                continue;
+            }
+            let prev_script = self.spent_output(index)?.script_pubkey.into();
+            // The signature version is only used to check the multisig script against the spent output.
+            let multisig = self.multisig_script(index, SignatureVersion::Base)?;
+            let input = &mut self.inputs[index];
+            match multisig {
+                Some((script, signature_version)) => finalize_multisig_input(index, input, script, signature_version)?,
+                None => finalize_input(index, input, prev_script)?,
+            }
+            // BIP-174: everything but the UTXOs, the final scripts and the unknown fields is cleared.
+            input.partial_sigs.clear();
+            input.redeem_script = None;
+            input.witness_script = None;
+            input.tap_key_sig = None;
+            input.tap_internal_key = None;
+        }
+        Ok(())
+    }
+
+    /// Extracts the network transaction from the PSBT. Every input is expected to be finalized.
+    pub fn extract_tx(&self) -> PsbtResult<UtxoTx> {
+        let mut tx = self.unsigned_tx.clone();
+        for (index, (tx_input, input)) in tx.inputs.iter_mut().zip(self.inputs.iter()).enumerate() {
+            if !input.is_finalized() {
+                return MmError::err(PsbtError::InputNotFinalized { index });
+            }
+            tx_input.script_sig = input.final_script_sig.clone().unwrap_or_default();
+            tx_input.script_witness = input.final_script_witness.clone().unwrap_or_default();
+        }
+        Ok(tx)
+    }
+
+    /// Returns the output spent by the input with the given `index`.
+    pub fn spent_output(&self, index: usize) -> PsbtResult<TransactionOutput> {
+        let (tx_input, input) = self
+            .unsigned_tx
+            .inputs
+            .get(index)
+            .zip(self.inputs.get(index))
+            .or_mm_err(|| PsbtError::UnknownSpentOutput { index })?;
+
+        if let Some(ref output) = input.witness_utxo {
+            return Ok(output.clone());
+        }
+        let prev_tx = input
+            .non_witness_utxo
+            .as_ref()
+            .or_mm_err(|| PsbtError::UnknownSpentOutput { index })?;
+        if prev_tx.hash() != tx_input.previous_output.hash {
+            let error = format!(
+                "The non-witness UTXO of the input {} doesn't match the spent transaction",
+                index
+            );
+            return MmError::err(PsbtError::InvalidPsbt(error));
+        }
+        prev_tx
+            .outputs
+            .get(tx_input.previous_output.index as usize)
+            .cloned()
+            .or_mm_err(|| PsbtError::UnknownSpentOutput { index })
+    }
+}
+
+impl PsbtInput {
+    pub fn is_finalized(&self) -> bool { self.final_script_sig.is_some() || self.final_script_witness.is_some() }
+}
+
+fn finalize_input(index: usize, input: &mut PsbtInput, prev_script: Script) -> PsbtResult<()> {
+    let find_signature = |matches_script: &dyn Fn(&PublicKey) -> bool| {
+        input
+            .partial_sigs
+            .iter()
+            .find(|(pubkey, _)| matches_script(pubkey))
+            .cloned()
+            .or_mm_err(|| PsbtError::InputNotSigned { index })
+    };
+
+    if prev_script.is_pay_to_public_key_hash() {
+        let (pubkey, signature) = find_signature(&|pubkey| pubkey.address_hash().as_slice() == &prev_script[3..23])?;
+        let script_sig = Builder::default().push_data(&signature).push_data(&pubkey).into_bytes();
+        input.final_script_sig = Some(script_sig);
+    } else if prev_script.is_pay_to_witness_key_hash() {
+        let (pubkey, signature) = find_signature(&|pubkey| pubkey.address_hash().as_slice() == &prev_script[2..22])?;
+        input.final_script_witness = Some(vec![signature, Bytes::from(pubkey.to_vec())]);
+    } else if prev_script.is_pay_to_public_key() {
+        let (_, signature) = find_signature(&|pubkey| **pubkey == prev_script[1..prev_script.len() - 1])?;
+        let script_sig = Builder::default().push_data(&signature).into_bytes();
+        input.final_script_sig = Some(script_sig);
+    } else if prev_script.is_pay_to_taproot() {
+        let signature = input
+            .tap_key_sig
+            .clone()
+            .or_mm_err(|| PsbtError::InputNotSigned { index })?;
+        input.final_script_witness = Some(vec![signature]);
+    } else {
+        return MmError::err(PsbtError::UnsupportedScript {
+            index,
+            script: prev_script,
+        });
+    }
+    Ok(())
+}
+
+fn finalize_multisig_input(
+    index: usize,
+    input: &mut PsbtInput,
+    script: Script,
+    signature_version: SignatureVersion,
+) -> PsbtResult<()> {
+    let required = script.num_signatures_required() as usize;
+    // The signatures must be in the same order as the public keys are in the script.
+    let signatures: Vec<Bytes> = script
+        .extract_multisig_pubkeys()
+        .map_to_mm(invalid_psbt)?
+        .iter()
+        .filter_map(|pubkey| {
+            input
+                .partial_sigs
+                .iter()
+                .find(|(signed_by, _)| signed_by == pubkey)
+                .map(|(_, signature)| signature.clone())
+        })
+        .take(required)
+        .collect();
+    if signatures.len() < required {
+        return MmError::err(PsbtError::NotEnoughSignatures {
+            index,
+            required,
+            actual: signatures.len(),
+        });
+    }
+
+    match signature_version {
+        SignatureVersion::WitnessV0 => {
+            // The dummy element consumed by `OP_CHECKMULTISIG` due to the off-by-one bug.
+            let mut witness = vec![Bytes::default()];
+            witness.extend(signatures);
+            witness.push(script.to_bytes());
+            input.final_script_witness = Some(witness);
+        },
+        _ => {
+            let builder = signatures
+                .iter()
+                .fold(Builder::default().push_opcode(Opcode::OP_0), |builder, signature| {
+                    builder.push_data(signature)
+                });
+            input.final_script_sig = Some(builder.push_data(&script).into_bytes());
+        },
+    }
+    Ok(())
+}
+
+/// Returns the script pubkey of the P2WSH output if `signature_version` is witness v0, of the P2SH output otherwise.
+pub fn multisig_script_pubkey(script: &Script, signature_version: SignatureVersion) -> Script {
+    match signature_version {
+        SignatureVersion::WitnessV0 => {
+            Builder::build_witness_script(&AddressHashEnum::WitnessScriptHash(sha256(script)))
+        },
+        _ => Builder::build_p2sh(&AddressHashEnum::AddressHash(dhash160(script))),
+    }
+}
+
+fn read_input(reader: &mut Reader<&[u8]>) -> PsbtResult<PsbtInput> {
+    let mut input = PsbtInput::default();
+    for (key, value) in read_key_values(reader)? {
+        match key.as_ref() {
+            [PSBT_IN_NON_WITNESS_UTXO] => {
+                let tx: UtxoTx = deserialize(value.as_slice()).map_to_mm(invalid_psbt)?;
+                input.non_witness_utxo = Some(tx);
+            },
+            [PSBT_IN_WITNESS_UTXO] => {
+                let output: TransactionOutput = deserialize(value.as_slice()).map_to_mm(invalid_psbt)?;
+                input.witness_utxo = Some(output);
+            },
+            [PSBT_IN_PARTIAL_SIG, pubkey @ ..] => {
+                let pubkey = PublicKey::from_slice(pubkey).map_to_mm(invalid_psbt)?;
+                input.partial_sigs.push((pubkey, value));
+            },
+            [PSBT_IN_REDEEM_SCRIPT] => input.redeem_script = Some(value),
+            [PSBT_IN_WITNESS_SCRIPT] => input.witness_script = Some(value),
+            [PSBT_IN_TAP_KEY_SIG] => input.tap_key_sig = Some(value),
+            [PSBT_IN_TAP_INTERNAL_KEY] => {
+                if value.len() != 32 {
+                    return MmError::err(PsbtError::InvalidPsbt("Invalid taproot internal key length".to_owned()));
+                }
+                input.tap_internal_key = Some(H256::from(value.as_slice()));
+            },
+            [PSBT_IN_FINAL_SCRIPTSIG] => input.final_script_sig = Some(value),
+            [PSBT_IN_FINAL_SCRIPTWITNESS] => {
+                let witness = Reader::new(value.as_slice())
+                    .read_list::<Bytes>()
+                    .map_to_mm(invalid_psbt)?;
+                input.final_script_witness = Some(witness);
+            },
+            [PSBT_IN_BIP32_DERIVATION, pubkey @ ..] => {
+                PublicKey::from_slice(pubkey).map_to_mm(invalid_psbt)?;
+                input.unknown.push((key, value));
+            },
+            [key_type, _, ..] if PSBT_IN_KEY_TYPES_WITHOUT_KEY_DATA.contains(key_type) => {
+                return MmError::err(unexpected_key_data(&key))
+            },
+            _ => input.unknown.push((key, value)),
+        }
+    }
+    Ok(input)
+}
+
+/// Reads the output key-value pairs and checks the ones of the known types.
+fn read_output(reader: &mut Reader<&[u8]>) -> PsbtResult<PsbtOutput> {
+    let unknown = read_key_values(reader)?;
+    for (key, _) in unknown.iter() {
+        match key.as_ref() {
+            [PSBT_OUT_BIP32_DERIVATION, pubkey @ ..] => {
+                PublicKey::from_slice(pubkey).map_to_mm(invalid_psbt)?;
+            },
+            [PSBT_OUT_REDEEM_SCRIPT | PSBT_OUT_WITNESS_SCRIPT, _, ..] => return MmError::err(unexpected_key_data(key)),
+            _ => (),
+        }
+    }
+    Ok(PsbtOutput { unknown })
+}
+
+fn deserialize_unsigned_tx(bytes: &[u8]) -> PsbtResult<UtxoTx> {
+    let tx: UtxoTx = deserialize(bytes).map_to_mm(invalid_psbt)?;
+    if tx.n_time.is_some() || tx.overwintered {
+        return MmError::err(PsbtError::UnsupportedTxFormat);
+    }
+    if serialize(&tx).len() != bytes.len() {
+        let error = "The unsigned transaction must be serialized in the non-witness format".to_owned();
+        return MmError::err(PsbtError::InvalidPsbt(error));
+    }
+    let has_scripts = tx
+        .inputs
+        .iter()
+        .any(|input| !input.script_sig.is_empty() || !input.script_witness.is_empty());
+    if has_scripts {
+        let error = "The unsigned transaction must have empty scriptSigs and scriptWitnesses".to_owned();
+        return MmError::err(PsbtError::InvalidPsbt(error));
+    }
+    Ok(tx)
+}
+
+/// Reads key-value pairs until the separator, that is a zero-length key.
+fn read_key_values(reader: &mut Reader<&[u8]>) -> PsbtResult<PsbtKeyValues> {
+    let mut keys = HashSet::new();
+    let mut key_values = Vec::new();
+    loop {
+        let key: Bytes = reader.read().map_to_mm(invalid_psbt)?;
+        if key.is_empty() {
+            return Ok(key_values);
+        }
+        let value: Bytes = reader.read().map_to_mm(invalid_psbt)?;
+        if !keys.insert(key.clone()) {
+            return MmError::err(PsbtError::InvalidPsbt(format!("Duplicate key {:?}", key)));
+        }
+        key_values.push((key, value));
+    }
+}
+
+/// Adds the `other` key-value pairs with the keys that are not in the `key_values` yet.
+fn merge_unknown(key_values: &mut PsbtKeyValues, other: PsbtKeyValues) {
+    for (key, value) in other {
+        if !key_values.iter().any(|(known, _)| *known == key) {
+            key_values.push((key, value));
+        }
+    }
+}
+
+fn append_key_values(stream: &mut Stream, key_values: &[(Bytes, Bytes)]) {
+    for (key, value) in key_values {
+        stream.append(key).append(value);
+    }
+    // The separator.
+    stream.append(&0u8);
+}
+
+fn key(key_type: u8, key_data: &[u8]) -> Bytes {
+    let mut key = Vec::with_capacity(1 + key_data.len());
+    key.push(key_type);
+    key.extend_from_slice(key_data);
+    key.into()
+}
+
+fn invalid_psbt<E: std::fmt::Display>(e: E) -> PsbtError { PsbtError::InvalidPsbt(e.to_string()) }
+
+fn unexpected_key_data(key: &Bytes) -> PsbtError {
+    PsbtError::InvalidPsbt(format!("Unexpected key data of the key type {:?}", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcrypto::ChecksumType;
+    use keys::Private;
+
+    // The BIP-174 test vectors: https://github.com/bitcoin/bips/blob/master/bip-0174.mediawiki#test-vectors
+    const PSBT_P2PKH: &str = "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be20e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab300000000000000";
+    const PSBT_UNKNOWN_INPUT_TYPES: &str = "70736274ff01003f0200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a010000000000000a0f0102030405060708090f0102030405060708090a0b0c0d0e0f0000";
+    const NETWORK_TX: &str = "0200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf6000000006a473044022070b2245123e6bf474d60c5b50c043d4c691a5d2435f09a34a7662a9dc251790a022001329ca9dacf280bdf30740ec0390422422c81cb45839457aeb76fc12edd95b3012102657d118d3357b8e0f4c2cd46db7b39f6d9c38d9a70abcb9b2de5dc8dbfe4ce31feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300";
+    const PSBT_MISSING_OUTPUTS: &str = "70736274ff0100750200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000feffffff02d3dff505000000001976a914d0c59903c5bac2868760e90fd521a4665aa7652088ac00e1f5050000000017a9143545e6e33b832c47050f24d3eeb93c9c03948bc787b32e1300000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be20e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab30000000000";
+    const PSBT_WITHOUT_UNSIGNED_TX: &str = "70736274ff000100fda5010100000000010289a3c71eab4d20e0371bbba4cc698fa295c9463afa2e397f8533ccb62f9567e50100000017160014be18d152a9b012039daf3da7de4f53349eecb985ffffffff86f8aa43a71dff1448893a530a7237ef6b4608bbb2dd2d0171e63aec6a4890b40100000017160014fe3e9ef1a745e974d902c4355943abcb34bd5353ffffffff0200c2eb0b000000001976a91485cff1097fd9e008bb34af709c62197b38978a4888ac72fef84e2c00000017a914339725ba21efd62ac753a9bcd067d6c7a6a39d05870247304402202712be20e0270f394f568311dc7ca9a68970b8025fdd3b240229f07f8a5f3a240220018b38d7dcd314e734c9276bd6fb40f673325bc4baa144c800d2f2f02db2765c012103d2e15674941bad4a996372cb87e1856d3652606d98562fe39c5e9e7e413f210502483045022100d12b852d85dcd961d2f5f4ab660654df6eedcc794c0c33ce5cc309ffb5fce58d022067338a8e0e1725c197fb1a88af59f51e44e4255b20167c8684031c05d1f2592a01210223b72beef0965d10be0778efecd61fcac6f79a4ea169393380734464f84f2ab30000000000";
+
+    /// The global map of the BIP-174 PSBT with the unknown input types: one input and one `OP_RETURN` output.
+    const ONE_INPUT_ONE_OUTPUT_GLOBAL: &str = "70736274ff01003f0200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a01000000000000";
+    const WITNESS_SERIALIZED_TX_GLOBAL: &str = "70736274ff01004202000000000101ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff0000000000ffffffff010000000000000000036a0100000000000000";
+    const SCRIPT_SIG_IN_UNSIGNED_TX_GLOBAL: &str = "70736274ff0100400200000001ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff000000000151ffffffff010000000000000000036a01000000000000";
+
+    // A P2SH 2-of-2 multisig and a P2WSH 2-of-2 multisig outputs spent by one transaction.
+    const MULTISIG_KEY_A: &str = "57f759b6794228bfd1db4a206d043fc47a32f1f26cb85a36237015336a6ff095";
+    const MULTISIG_KEY_B: &str = "b4a43f012f191262f5c24ccd2cd3a74b00aaee8dc75272833e91933ea424c5b6";
+    const MULTISIG_UNSIGNED: &str = "70736274ff01007e020000000249e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0000000000fdffffff49e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0100000000fdffffff01708be60e000000001976a91449498c2f3a35c7e7115bc59e23a043b2c29a6e4188ac000000000001007f02000000011111111111111111111111111111111111111111111111111111111111111111000000000151ffffffff0280d1f0080000000017a9140441e0fb22c3bce8d12c49272b32d095e930d37b8700e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f588000000000104475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0001012b00e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f5880105475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0000";
+    const MULTISIG_SIGNED_A: &str = "70736274ff01007e020000000249e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0000000000fdffffff49e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0100000000fdffffff01708be60e000000001976a91449498c2f3a35c7e7115bc59e23a043b2c29a6e4188ac000000000001007f02000000011111111111111111111111111111111111111111111111111111111111111111000000000151ffffffff0280d1f0080000000017a9140441e0fb22c3bce8d12c49272b32d095e930d37b8700e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f588000000002202039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6347304402201f22aa74b2c29b532dc095f168f5843bd44b1f393d6514ad82ea3829b4563f3902206a413edfa2719f662e6357e0c16baefd4ed65a421d222b89ca01e3dae9de42d5010104475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0001012b00e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f5882202039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd63483045022100aacb5fe8a65f31858cf3aeac119540e34bc9792be61481b4bd90489610b88d1b02202cc332a825319c709d92938fe88464832eb1e5a9a06f8c3165ef6301350bc635010105475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0000";
+    const MULTISIG_COMBINED: &str = "70736274ff01007e020000000249e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0000000000fdffffff49e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0100000000fdffffff01708be60e000000001976a91449498c2f3a35c7e7115bc59e23a043b2c29a6e4188ac000000000001007f02000000011111111111111111111111111111111111111111111111111111111111111111000000000151ffffffff0280d1f0080000000017a9140441e0fb22c3bce8d12c49272b32d095e930d37b8700e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f588000000002202039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6347304402201f22aa74b2c29b532dc095f168f5843bd44b1f393d6514ad82ea3829b4563f3902206a413edfa2719f662e6357e0c16baefd4ed65a421d222b89ca01e3dae9de42d5012202022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e92473044022026a4a4b2a75294eec1c000dc8c6491414f9f5fc30c1268e6e992cf1e12eb6f560220447f4d6486147bb4e485699d294a2edfcc189b15e5f896244302a3c90359de73010104475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0001012b00e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f5882202039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd63483045022100aacb5fe8a65f31858cf3aeac119540e34bc9792be61481b4bd90489610b88d1b02202cc332a825319c709d92938fe88464832eb1e5a9a06f8c3165ef6301350bc635012202022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e92483045022100a448428c8ff4faf5f00fd53dc793605c28a3ae530a8649a8717a63208b307486022012c0b295469f02c0bd70f9e1d59051bdf96b3a5c5f2ef664336d352820189790010105475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0000";
+    const MULTISIG_FINALIZED: &str = "70736274ff01007e020000000249e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0000000000fdffffff49e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0100000000fdffffff01708be60e000000001976a91449498c2f3a35c7e7115bc59e23a043b2c29a6e4188ac000000000001007f02000000011111111111111111111111111111111111111111111111111111111111111111000000000151ffffffff0280d1f0080000000017a9140441e0fb22c3bce8d12c49272b32d095e930d37b8700e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f588000000000107d90047304402201f22aa74b2c29b532dc095f168f5843bd44b1f393d6514ad82ea3829b4563f3902206a413edfa2719f662e6357e0c16baefd4ed65a421d222b89ca01e3dae9de42d501473044022026a4a4b2a75294eec1c000dc8c6491414f9f5fc30c1268e6e992cf1e12eb6f560220447f4d6486147bb4e485699d294a2edfcc189b15e5f896244302a3c90359de7301475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0001012b00e1f50500000000220020c8edd25469fde1b45bda54e7627211e0420b9b0ccb12551423229b1fe064f5880108dc0400483045022100aacb5fe8a65f31858cf3aeac119540e34bc9792be61481b4bd90489610b88d1b02202cc332a825319c709d92938fe88464832eb1e5a9a06f8c3165ef6301350bc63501483045022100a448428c8ff4faf5f00fd53dc793605c28a3ae530a8649a8717a63208b307486022012c0b295469f02c0bd70f9e1d59051bdf96b3a5c5f2ef664336d35282018979001475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae0000";
+    const MULTISIG_FINAL_TX: &str = "0200000000010249e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd00000000d90047304402201f22aa74b2c29b532dc095f168f5843bd44b1f393d6514ad82ea3829b4563f3902206a413edfa2719f662e6357e0c16baefd4ed65a421d222b89ca01e3dae9de42d501473044022026a4a4b2a75294eec1c000dc8c6491414f9f5fc30c1268e6e992cf1e12eb6f560220447f4d6486147bb4e485699d294a2edfcc189b15e5f896244302a3c90359de7301475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252aefdffffff49e5e70d2a9ff316fe67fb3091f5410320a05967974e8dd1cd877d49d51c0bcd0100000000fdffffff01708be60e000000001976a91449498c2f3a35c7e7115bc59e23a043b2c29a6e4188ac000400483045022100aacb5fe8a65f31858cf3aeac119540e34bc9792be61481b4bd90489610b88d1b02202cc332a825319c709d92938fe88464832eb1e5a9a06f8c3165ef6301350bc63501483045022100a448428c8ff4faf5f00fd53dc793605c28a3ae530a8649a8717a63208b307486022012c0b295469f02c0bd70f9e1d59051bdf96b3a5c5f2ef664336d35282018979001475221039e8adda59cf7bb6dc2c15bf143620ca8ff70e2b6a9fb543e223ec7f28bb3bd6321022c119bf63501ebdc2374f91017397afcaf09fdc5be8eca972f24433da6bb4e9252ae00000000";
+
+    fn psbt_bytes(hex_str: &str) -> Vec<u8> { hex::decode(hex_str).unwrap() }
+
+    fn key_pair(secret: &str) -> KeyPair {
+        let private = Private {
+            prefix: 128,
+            secret: H256::from(psbt_bytes(secret).as_slice()),
+            compressed: true,
+            checksum_type: ChecksumType::DSHA256,
+        };
+        KeyPair::from_private(private).unwrap()
+    }
+
+    /// Builds a PSBT of the [`ONE_INPUT_ONE_OUTPUT_GLOBAL`] transaction with the given input and output key-value pairs.
+    fn one_input_one_output_psbt(input: &[(&str, &str)], output: &[(&str, &str)]) -> Vec<u8> {
+        let to_key_values = |key_values: &[(&str, &str)]| -> Vec<(Bytes, Bytes)> {
+            key_values
+                .iter()
+                .map(|(key, value)| (psbt_bytes(key).into(), psbt_bytes(value).into()))
+                .collect()
+        };
+        let mut stream = Stream::new();
+        stream.append_slice(&psbt_bytes(ONE_INPUT_ONE_OUTPUT_GLOBAL));
+        append_key_values(&mut stream, &to_key_values(input));
+        append_key_values(&mut stream, &to_key_values(output));
+        stream.out().take()
+    }
+
+    fn assert_invalid_psbt(bytes: &[u8]) {
+        match Psbt::deserialize(bytes).map_err(|e| e.into_inner()) {
+            Err(PsbtError::InvalidPsbt(_)) => (),
+            other => panic!("Expected 'InvalidPsbt', found {:?}", other.map(|psbt| psbt.serialize())),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_valid_psbts() {
+        let bytes = psbt_bytes(PSBT_P2PKH);
+        let psbt = Psbt::deserialize(&bytes).unwrap();
+        assert_eq!(psbt.inputs.len(), 1);
+        assert_eq!(psbt.outputs.len(), 2);
+        assert!(psbt.inputs[0].non_witness_utxo.is_some());
+        assert_eq!(psbt.spent_output(0).unwrap().value, 200000000);
+        assert_eq!(psbt.serialize().take(), bytes);
+
+        let bytes = psbt_bytes(PSBT_UNKNOWN_INPUT_TYPES);
+        let psbt = Psbt::deserialize(&bytes).unwrap();
+        assert_eq!(psbt.inputs[0].unknown.len(), 1);
+        assert_eq!(psbt.serialize().take(), bytes);
+
+        // Empty input and output maps.
+        let bytes = one_input_one_output_psbt(&[], &[]);
+        let psbt = Psbt::deserialize(&bytes).unwrap();
+        assert_eq!(psbt.serialize().take(), bytes);
+    }
+
+    #[test]
+    fn test_deserialize_invalid_psbts() {
+        assert_invalid_psbt(&psbt_bytes(NETWORK_TX));
+        assert_invalid_psbt(&psbt_bytes(PSBT_MISSING_OUTPUTS));
+        assert_invalid_psbt(&psbt_bytes(PSBT_WITHOUT_UNSIGNED_TX));
+        assert_invalid_psbt(&psbt_bytes(WITNESS_SERIALIZED_TX_GLOBAL));
+        assert_invalid_psbt(&psbt_bytes(SCRIPT_SIG_IN_UNSIGNED_TX_GLOBAL));
+
+        // The unsigned transaction key with the key data.
+        let global_with_key_data = ONE_INPUT_ONE_OUTPUT_GLOBAL.replacen("70736274ff0100", "70736274ff020000", 1);
+        let mut bytes = psbt_bytes(&global_with_key_data);
+        bytes.extend_from_slice(&[0, 0]);
+        assert_invalid_psbt(&bytes);
+
+        // A 32-byte public key.
+        let invalid_pubkey = "0211111111111111111111111111111111111111111111111111111111111111";
+        let invalid_inputs = [
+            // The input key types that must not have the key data.
+            ("0000", "00"),
+            ("0100", "00"),
+            ("0300", "01000000"),
+            ("0400", "51"),
+            ("0500", "51"),
+            ("0700", "00"),
+            ("0800", "00"),
+            ("1300", "00"),
+            ("1700", "00"),
+        ];
+        for (key, value) in invalid_inputs.iter() {
+            assert_invalid_psbt(&one_input_one_output_psbt(&[(key, value)], &[]));
+        }
+        let invalid_pubkey_keys = [format!("02{}", invalid_pubkey), format!("06{}", invalid_pubkey)];
+        for key in invalid_pubkey_keys.iter() {
+            assert_invalid_psbt(&one_input_one_output_psbt(&[(key, "00")], &[]));
+        }
+
+        let invalid_outputs = [
+            ("0000".to_owned(), "51"),
+            ("0100".to_owned(), "51"),
+            (format!("02{}", invalid_pubkey), "00"),
+        ];
+        for (key, value) in invalid_outputs.iter() {
+            assert_invalid_psbt(&one_input_one_output_psbt(&[], &[(key, value)]));
+        }
+
+        // Duplicate keys.
+        assert_invalid_psbt(&one_input_one_output_psbt(&[("04", "51"), ("04", "52")], &[]));
+    }
+
+    #[test]
+    fn test_sign_combine_finalize_multisig() {
+        let key_a = key_pair(MULTISIG_KEY_A);
+        let key_b = key_pair(MULTISIG_KEY_B);
+
+        let mut signed_a = Psbt::deserialize(&psbt_bytes(MULTISIG_UNSIGNED)).unwrap();
+        let mut signed_b = signed_a.clone();
+        assert_eq!(
+            signed_a
+                .sign_multisig_inputs(&key_a, SignatureVersion::Base, 0)
+                .unwrap(),
+            2
+        );
+        assert_eq!(signed_a.serialize().take(), psbt_bytes(MULTISIG_SIGNED_A));
+        assert_eq!(
+            signed_b
+                .sign_multisig_inputs(&key_b, SignatureVersion::Base, 0)
+                .unwrap(),
+            2
+        );
+
+        // One signature isn't enough to finalize the 2-of-2 inputs.
+        let error = signed_a.clone().finalize().unwrap_err().into_inner();
+        assert!(matches!(error, PsbtError::NotEnoughSignatures { .. }), "{:?}", error);
+
+        let mut combined = Psbt::deserialize(&psbt_bytes(MULTISIG_SIGNED_A)).unwrap();
+        combined.combine(signed_b).unwrap();
+        assert_eq!(combined.serialize().take(), psbt_bytes(MULTISIG_COMBINED));
+
+        combined.finalize().unwrap();
+        assert_eq!(combined.serialize().take(), psbt_bytes(MULTISIG_FINALIZED));
+
+        let tx = combined.extract_tx().unwrap();
+        let tx_bytes = serialize_with_flags(&tx, SERIALIZE_TRANSACTION_WITNESS);
+        assert_eq!(tx_bytes.take(), psbt_bytes(MULTISIG_FINAL_TX));
+    }
+
+    #[test]
+    fn test_combine_different_txs() {
+        let mut psbt = Psbt::deserialize(&psbt_bytes(MULTISIG_UNSIGNED)).unwrap();
+        let other = Psbt::deserialize(&psbt_bytes(PSBT_P2PKH)).unwrap();
+        let error = psbt.combine(other).unwrap_err().into_inner();
+        assert!(matches!(error, PsbtError::InvalidPsbt(_)), "{:?}", error);
+    }
+}
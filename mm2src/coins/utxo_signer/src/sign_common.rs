@@ -101,6 +101,19 @@ pub(crate) fn p2wpkh_spend_with_signature(
     }
 }
 
+/// Key path spending uses `SIGHASH_DEFAULT`, so the signature is pushed to the witness without the sighash type.
+pub(crate) fn p2tr_spend_with_signature(
+    unsigned_input: &UnsignedTransactionInput,
+    signature: Signature,
+) -> TransactionInput {
+    TransactionInput {
+        previous_output: unsigned_input.previous_output,
+        script_sig: Bytes::from(Vec::new()),
+        sequence: unsigned_input.sequence,
+        script_witness: vec![signature],
+    }
+}
+
 pub(crate) fn script_sig_with_pub(public_key: &PublicKey, fork_id: u32, signature: Signature) -> Bytes {
     let script_sig = script_sig(signature, fork_id);
     let builder = Builder::default();
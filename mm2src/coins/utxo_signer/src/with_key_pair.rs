@@ -1,10 +1,10 @@
 use crate::sign_common::{complete_tx, p2pk_spend_with_signature, p2pkh_spend_with_signature,
-                         p2sh_spend_with_signature, p2wpkh_spend_with_signature};
+                         p2sh_spend_with_signature, p2tr_spend_with_signature, p2wpkh_spend_with_signature};
 use crate::Signature;
 use chain::{Transaction as UtxoTx, TransactionInput};
 use derive_more::Display;
 use keys::bytes::Bytes;
-use keys::KeyPair;
+use keys::{AddressHashEnum, KeyPair};
 use mm2_err_handle::prelude::*;
 use primitives::hash::H256;
 use script::{Builder, Script, SignatureVersion, TransactionInputSigner, UnsignedTransactionInput};
@@ -28,6 +28,8 @@ pub enum UtxoSignWithKeyPairError {
     InputIndexOutOfBound { len: usize, index: usize },
     #[display(fmt = "Error signing using a private key")]
     ErrorSigning(keys::Error),
+    #[display(fmt = "Error calculating the signature hash: {}", _0)]
+    InvalidSighash(String),
 }
 
 impl From<keys::Error> for UtxoSignWithKeyPairError {
@@ -55,6 +57,11 @@ pub fn sign_tx(
                 )?);
             }
         },
+        SignatureVersion::Taproot => {
+            for (i, _) in unsigned.inputs.iter().enumerate() {
+                signed_inputs.push(p2tr_spend(&unsigned, i, key_pair, prev_script.clone())?);
+            }
+        },
         _ => {
             for (i, _) in unsigned.inputs.iter().enumerate() {
                 signed_inputs.push(p2pkh_spend(
@@ -173,6 +180,40 @@ pub fn p2wpkh_spend(
     ))
 }
 
+/// Creates signed input spending p2tr output using the key path.
+/// `prev_script` is expected to pay to the taproot output key of the `key_pair`.
+pub fn p2tr_spend(
+    signer: &TransactionInputSigner,
+    input_index: usize,
+    key_pair: &KeyPair,
+    prev_script: Script,
+) -> UtxoSignWithKeyPairResult<TransactionInput> {
+    let unsigned_input = get_input(signer, input_index)?;
+
+    let output_key = key_pair.public().taproot_output_key()?;
+    let script = Builder::build_p2tr(&AddressHashEnum::WitnessScriptHash(output_key));
+    if script != prev_script {
+        return MmError::err(UtxoSignWithKeyPairError::MismatchScript {
+            script_type: "P2TR".to_owned(),
+            script,
+            prev_script,
+        });
+    }
+
+    // SIGHASH_DEFAULT
+    let sighash_type = 0;
+    // Every input is expected to spend an output of the same `key_pair`.
+    let spent_scripts = vec![script; signer.inputs.len()];
+    let sighash = signer
+        .signature_hash_taproot(input_index, &spent_scripts, sighash_type)
+        .map_to_mm(UtxoSignWithKeyPairError::InvalidSighash)?;
+    let signature = key_pair.private().sign_taproot_key_path(&sighash)?;
+    Ok(p2tr_spend_with_signature(
+        unsigned_input,
+        Bytes::from(signature.to_vec()),
+    ))
+}
+
 /// Calculates the input script hash and sign it using `key_pair`.
 pub(crate) fn calc_and_sign_sighash(
     signer: &TransactionInputSigner,
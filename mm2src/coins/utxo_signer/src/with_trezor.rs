@@ -23,8 +23,10 @@ pub struct TrezorTxSigner<'a, TxP> {
 
 impl<'a, TxP: TxProvider + Send + Sync> TrezorTxSigner<'a, TxP> {
     pub async fn sign_tx(mut self) -> UtxoSignTxResult<UtxoTx> {
-        if let SignatureVersion::WitnessV0 = self.params.signature_version {
-            return MmError::err(UtxoSignTxError::TrezorDoesntSupportP2WPKH);
+        match self.params.signature_version {
+            SignatureVersion::WitnessV0 => return MmError::err(UtxoSignTxError::TrezorDoesntSupportP2WPKH),
+            SignatureVersion::Taproot => return MmError::err(UtxoSignTxError::TrezorDoesntSupportP2TR),
+            SignatureVersion::Base | SignatureVersion::ForkId => (),
         }
 
         let trezor_unsigned_tx = self.get_trezor_unsigned_tx().await?;
@@ -5,7 +5,7 @@ use crate::utxo::rpc_clients::{ElectrumRpcRequest, UnspentInfo, UtxoRpcClientEnu
                                UtxoRpcResult};
 use crate::utxo::utxo_builder::{UtxoCoinBuildError, UtxoCoinBuilder, UtxoCoinBuilderCommonOps,
                                 UtxoFieldsWithGlobalHDBuilder, UtxoFieldsWithHardwareWalletBuilder,
-                                UtxoFieldsWithIguanaSecretBuilder};
+                                UtxoFieldsWithIguanaSecretBuilder, UtxoFieldsWithWatchOnlyBuilder};
 use crate::utxo::utxo_common::{addresses_from_script, big_decimal_from_sat, big_decimal_from_sat_unsigned,
                                payment_script};
 use crate::utxo::{sat_from_big_decimal, utxo_common, ActualTxFee, AdditionalTxData, AddrFromStrError, Address,
@@ -799,6 +799,10 @@ impl<'a> UtxoFieldsWithGlobalHDBuilder for ZCoinBuilder<'a> {}
 /// `UtxoCoinBuilder` trait requires `UtxoFieldsWithHardwareWalletBuilder` to be implemented.
 impl<'a> UtxoFieldsWithHardwareWalletBuilder for ZCoinBuilder<'a> {}
 
+/// Although, `ZCoin` doesn't support [`PrivKeyBuildPolicy::WatchOnly`] yet,
+/// `UtxoCoinBuilder` trait requires `UtxoFieldsWithWatchOnlyBuilder` to be implemented.
+impl<'a> UtxoFieldsWithWatchOnlyBuilder for ZCoinBuilder<'a> {}
+
 #[async_trait]
 impl<'a> UtxoCoinBuilder for ZCoinBuilder<'a> {
     type ResultCoin = ZCoin;
@@ -1896,12 +1900,16 @@ impl InitWithdrawCoin for ZCoin {
             fee_details: Some(TxFeeDetails::Utxo(UtxoFeeDetails {
                 coin: Some(self.ticker().to_owned()),
                 amount: big_decimal_from_sat_unsigned(data.fee_amount, self.decimals()),
+                fee_rate: None,
+                fee_rate_source: None,
             })),
             coin: self.ticker().to_owned(),
             internal_id: tx_hash.into(),
             kmd_rewards: None,
             transaction_type: Default::default(),
             memo: req.memo,
+            psbt: None,
+            to_name: None,
         })
     }
 }
@@ -1939,6 +1947,12 @@ fn extended_spending_key_from_protocol_info_and_policy(
                 UtxoCoinBuildError::PrivKeyPolicyNotAllowed(priv_key_err),
             ))
         },
+        PrivKeyBuildPolicy::WatchOnly(_) => {
+            let priv_key_err = PrivKeyPolicyNotAllowed::WatchOnlyWalletNotSupported;
+            MmError::err(ZCoinBuildError::UtxoBuilderError(
+                UtxoCoinBuildError::PrivKeyPolicyNotAllowed(priv_key_err),
+            ))
+        },
     }
 }
 
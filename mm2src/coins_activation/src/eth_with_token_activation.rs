@@ -5,12 +5,16 @@ use crate::{platform_coin_with_tokens::{EnablePlatformCoinWithTokensError, GetPl
             prelude::*};
 use async_trait::async_trait;
 use coins::eth::EthPrivKeyBuildPolicy;
-use coins::{eth::v2_activation::EthPrivKeyActivationPolicy, MmCoinEnum};
-use coins::{eth::{v2_activation::{eth_coin_from_conf_and_request_v2, Erc20Protocol, Erc20TokenActivationError,
+use coins::{coin_balance::HDWalletBalanceOps,
+            eth::{eth_hd_wallet::EthHDWallet,
+                  eth_tx_history_v2::eth_history_loop,
+                  v2_activation::{eth_coin_from_conf_and_request_v2, Erc20Protocol, Erc20TokenActivationError,
                                   Erc20TokenActivationRequest, EthActivationV2Error, EthActivationV2Request},
                   Erc20TokenInfo, EthCoin, EthCoinType},
             my_tx_history_v2::TxHistoryStorage,
-            CoinBalance, CoinProtocol, MarketCoinOps, MmCoin};
+            CoinBalance, CoinProtocol, CoinWithDerivationMethod, MarketCoinOps, MmCoin};
+use coins::{eth::v2_activation::EthPrivKeyActivationPolicy, MmCoinEnum};
+use common::executor::{AbortSettings, SpawnAbortable};
 use common::Future01CompatExt;
 use common::{drop_mutability, true_f};
 use mm2_core::mm_ctx::MmArc;
@@ -125,10 +129,13 @@ pub struct EthWithTokensActivationRequest {
     erc20_tokens_requests: Vec<TokenActivationRequest<Erc20TokenActivationRequest>>,
     #[serde(default = "true_f")]
     pub get_balances: bool,
+    /// Whether to fetch the ETH and ERC20 transaction history of my address in the background.
+    #[serde(default)]
+    tx_history: bool,
 }
 
 impl TxHistory for EthWithTokensActivationRequest {
-    fn tx_history(&self) -> bool { false }
+    fn tx_history(&self) -> bool { self.tx_history }
 }
 
 impl TokenOf for EthCoin {
@@ -221,6 +228,10 @@ impl PlatformWithTokensActivationOps for EthCoin {
             .await
             .map_err(EthActivationV2Error::InternalError)?;
 
+        if let Some(hd_wallet) = self.derivation_method().hd_wallet() {
+            return hd_wallet_activation_result(self, hd_wallet, activation_request, current_block).await;
+        }
+
         let my_address = self.my_address()?;
         let pubkey = self.get_public_key()?;
 
@@ -276,12 +287,76 @@ impl PlatformWithTokensActivationOps for EthCoin {
     fn start_history_background_fetching(
         &self,
         _ctx: MmArc,
-        _storage: impl TxHistoryStorage + Send + 'static,
+        storage: impl TxHistoryStorage + Send + 'static,
         _initial_balance: Option<BigDecimal>,
     ) {
+        let fut = eth_history_loop(self.clone(), storage);
+
+        let settings = AbortSettings::info_on_abort(format!("eth_history_loop stopped for {}", self.ticker()));
+        self.spawner().spawn_with_settings(fut, settings);
     }
 }
 
+/// Returns infos of every known HD wallet address.
+/// Balances of the platform coin and tokens are requested for each address if [`EthWithTokensActivationRequest::get_balances`] is set.
+async fn hd_wallet_activation_result(
+    coin: &EthCoin,
+    hd_wallet: &EthHDWallet,
+    activation_request: &EthWithTokensActivationRequest,
+    current_block: u64,
+) -> Result<EthWithTokensActivationResult, MmError<EthActivationV2Error>> {
+    let hd_addresses = coin
+        .enable_hd_wallet_addresses(hd_wallet, &activation_request.platform_request.enable_params)
+        .await?;
+
+    let mut eth_addresses_infos = HashMap::with_capacity(hd_addresses.len());
+    let mut erc20_addresses_infos = HashMap::with_capacity(hd_addresses.len());
+    for hd_address in hd_addresses {
+        let derivation_path = hd_address.derivation_path.to_string();
+        let pubkey = format!("04{}", hex::encode(hd_address.pubkey));
+
+        let mut eth_address_info = CoinAddressInfo {
+            derivation_method: DerivationMethod::HDWallet(derivation_path.clone()),
+            pubkey: pubkey.clone(),
+            balances: None,
+            tickers: None,
+        };
+        let mut erc20_address_info = CoinAddressInfo {
+            derivation_method: DerivationMethod::HDWallet(derivation_path),
+            pubkey,
+            balances: None,
+            tickers: None,
+        };
+
+        if activation_request.get_balances {
+            let eth_balance = coin
+                .known_address_balance(&hd_address.address)
+                .await
+                .map_err(|e| EthActivationV2Error::CouldNotFetchBalance(e.to_string()))?;
+            eth_address_info.balances = Some(eth_balance);
+
+            let token_balances = coin
+                .get_tokens_balance_list_for_address(hd_address.address.0)
+                .await
+                .map_err(|e| EthActivationV2Error::CouldNotFetchBalance(e.to_string()))?;
+            erc20_address_info.balances = Some(token_balances);
+        } else {
+            let tickers: HashSet<_> = coin.get_erc_tokens_infos().into_keys().collect();
+            erc20_address_info.tickers = Some(tickers);
+        }
+
+        let address = hd_address.address.to_string();
+        eth_addresses_infos.insert(address.clone(), eth_address_info);
+        erc20_addresses_infos.insert(address, erc20_address_info);
+    }
+
+    Ok(EthWithTokensActivationResult {
+        current_block,
+        eth_addresses_infos,
+        erc20_addresses_infos,
+    })
+}
+
 fn eth_priv_key_build_policy(
     ctx: &MmArc,
     activation_policy: &EthPrivKeyActivationPolicy,
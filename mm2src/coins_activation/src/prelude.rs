@@ -36,7 +36,6 @@ pub enum DerivationMethod {
     /// Legacy iguana's privkey derivation, used by default
     Iguana,
     /// HD wallet derivation path, String is temporary here
-    #[allow(dead_code)]
     HDWallet(String),
 }
 
@@ -62,7 +62,7 @@ impl InitStandaloneCoinActivationOps for UtxoStandardCoin {
         _protocol_info: Self::StandaloneProtocol,
         task_handle: &UtxoStandardRpcTaskHandle,
     ) -> MmResult<Self, InitUtxoStandardError> {
-        let priv_key_policy = priv_key_build_policy(&ctx, activation_request.priv_key_policy)?;
+        let priv_key_policy = priv_key_build_policy(&ctx, &activation_request.priv_key_policy)?;
 
         let coin = UtxoArcBuilder::new(
             &ctx,
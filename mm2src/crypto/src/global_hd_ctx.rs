@@ -1,9 +1,12 @@
 use crate::privkey::{bip39_seed_from_passphrase, key_pair_from_secret, PrivKeyError};
-use crate::{mm2_internal_der_path, Bip32DerPathOps, Bip32Error, CryptoInitError, CryptoInitResult,
-            StandardHDPathToCoin};
+use crate::{mm2_internal_der_path, Bip32DerPathOps, Bip32Error, CryptoInitError, CryptoInitResult, DerivationPath,
+            StandardHDPath, StandardHDPathToCoin};
 use bip32::{ChildNumber, ExtendedPrivateKey};
+use bitcrypto::dhash160;
+use hw_common::primitives::Secp256k1ExtendedPublicKey;
 use keys::{KeyPair, Secret as Secp256k1Secret};
 use mm2_err_handle::prelude::*;
+use primitives::hash::H160;
 use std::convert::TryInto;
 use std::num::TryFromIntError;
 use std::ops::Deref;
@@ -101,8 +104,46 @@ impl GlobalHDAccountCtx {
         account_der_path.push(ChildNumber::new(CHAIN_ID, NON_HARDENED).unwrap());
         account_der_path.push(self.hd_account);
 
+        self.derive_secp256k1_secret_by_path(account_der_path)
+    }
+
+    /// Derives a `secp256k1::SecretKey` from [`HDAccountCtx::bip39_secp_priv_key`]
+    /// at the given full `m/purpose'/coin_type'/account_id'/chain/address_id` derivation path.
+    /// Unlike [`GlobalHDAccountCtx::derive_secp256k1_secret`], the address is not limited to the activated HD account.
+    pub fn derive_secp256k1_secret_at(
+        &self,
+        derivation_path: &StandardHDPath,
+    ) -> MmResult<Secp256k1Secret, Bip32Error> {
+        self.derive_secp256k1_secret_by_path(derivation_path.to_derivation_path())
+    }
+
+    /// Derives an extended public key from [`HDAccountCtx::bip39_secp_priv_key`] at the given derivation path,
+    /// e.g. at `m/purpose'/coin_type'/account_id'`.
+    pub fn derive_secp256k1_extended_pubkey(
+        &self,
+        derivation_path: DerivationPath,
+    ) -> MmResult<Secp256k1ExtendedPublicKey, Bip32Error> {
         let mut priv_key = self.bip39_secp_priv_key.clone();
-        for child in account_der_path {
+        for child in derivation_path {
+            priv_key = priv_key.derive_child(child)?;
+        }
+        Ok(priv_key.public_key())
+    }
+
+    /// Returns RIPEMD160(SHA256(x)) where x is the root public key.
+    /// Unlike the internal pubkey, it doesn't depend on [`HDAccountCtx::hd_account`],
+    /// so it identifies the HD wallet in the storage.
+    pub fn root_pubkey_rmd160(&self) -> H160 {
+        let root_pubkey = self.bip39_secp_priv_key.public_key();
+        dhash160(&root_pubkey.public_key().serialize())
+    }
+
+    fn derive_secp256k1_secret_by_path(
+        &self,
+        derivation_path: DerivationPath,
+    ) -> MmResult<Secp256k1Secret, Bip32Error> {
+        let mut priv_key = self.bip39_secp_priv_key.clone();
+        for child in derivation_path {
             priv_key = priv_key.derive_child(child)?;
         }
 
@@ -84,6 +84,18 @@ pub fn dhash160(input: &[u8]) -> H160 { ripemd160(&*sha256(input)) }
 #[inline]
 pub fn dhash256(input: &[u8]) -> H256 { sha256(&*sha256(input)) }
 
+/// BIP-340 tagged hash `SHA-256(SHA-256(tag) || SHA-256(tag) || input)`
+/// https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki#design
+#[inline]
+pub fn tagged_hash(tag: &[u8], input: &[u8]) -> H256 {
+    let tag_hash = sha256(tag);
+    let mut hasher = Sha256::new();
+    hasher.update(&*tag_hash);
+    hasher.update(&*tag_hash);
+    hasher.update(input);
+    (*hasher.finalize()).into()
+}
+
 /// SipHash-2-4
 #[inline]
 pub fn siphash24(key0: u64, key1: u64, input: &[u8]) -> u64 {
@@ -110,7 +122,7 @@ pub fn checksum(data: &[u8], sum_type: &ChecksumType) -> H32 {
 
 #[cfg(test)]
 mod tests {
-    use super::{checksum, dhash160, dhash256, ripemd160, sha1, sha256, siphash24};
+    use super::{checksum, dhash160, dhash256, ripemd160, sha1, sha256, siphash24, tagged_hash};
     use primitives::bytes::Bytes;
     use primitives::hash::{H160, H256, H32};
     use ChecksumType;
@@ -156,6 +168,13 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_tagged_hash() {
+        let expected: H256 = "ac12b2c18138a6ea5b05c3d05f88c7cb305fb94fe448aebdbba7b1dee05dd0eb".into();
+        let result = tagged_hash(b"TapSighash", b"hello");
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_siphash24() {
         let expected = 0x74f839c593dc67fd_u64;
@@ -8,6 +8,7 @@
 use base58::{FromBase58, ToBase58};
 use crypto::{checksum, dgroestl512, dhash256, keccak256, ChecksumType};
 use derive_more::Display;
+use segwitaddress::AddressType as SegwitAddressType;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::Deref;
@@ -37,6 +38,11 @@ pub enum Type {
     /// as the scripthash, eg: bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3.
     /// https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
     P2WSH,
+    /// Pay to Taproot
+    /// Segwit v1 P2TR which begins with the human readable part followed by 1p followed by 58 base32 characters
+    /// as the taproot output key, eg: bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    P2TR,
 }
 
 #[derive(Clone, Debug, Display, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -51,6 +57,11 @@ pub enum AddressFormat {
     /// https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
     #[serde(rename = "segwit")]
     Segwit,
+    /// Taproot (segwit v1) Address encoded with Bech32m
+    /// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki
+    /// https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki
+    #[serde(rename = "taproot")]
+    Taproot,
     /// Bitcoin Cash specific address format.
     /// https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/cashaddr.md
     #[serde(rename = "cashaddress")]
@@ -71,6 +82,11 @@ impl Default for AddressFormat {
 impl AddressFormat {
     pub fn is_segwit(&self) -> bool { matches!(*self, AddressFormat::Segwit) }
 
+    pub fn is_taproot(&self) -> bool { matches!(*self, AddressFormat::Taproot) }
+
+    /// Whether the address is encoded as a native witness program (either Segwit v0 or Taproot).
+    pub fn is_witness(&self) -> bool { self.is_segwit() || self.is_taproot() }
+
     pub fn is_cashaddress(&self) -> bool { matches!(*self, AddressFormat::CashAddress { .. }) }
 
     pub fn is_legacy(&self) -> bool { matches!(*self, AddressFormat::Standard) }
@@ -188,6 +204,11 @@ impl fmt::Display for Address {
                     .to_string()
                     .fmt(f)
             },
+            AddressFormat::Taproot => self
+                .to_segwitaddress()
+                .expect("Taproot address should have an hrp")
+                .to_string()
+                .fmt(f),
             AddressFormat::CashAddress {
                 network,
                 pub_addr_prefix,
@@ -227,6 +248,7 @@ impl Address {
                 Some(hrp) => Ok(SegwitAddress::new(&self.hash, hrp.clone()).to_string()),
                 None => Err("Cannot display segwit address for a coin with no bech32_hrp in config".into()),
             },
+            AddressFormat::Taproot => self.to_segwitaddress().map(|address| address.to_string()),
 
             AddressFormat::CashAddress {
                 network,
@@ -310,6 +332,10 @@ impl Address {
         };
         hash.copy_from_slice(address.program.as_slice());
 
+        let addr_format = match address.address_type() {
+            Some(SegwitAddressType::P2tr) => AddressFormat::Taproot,
+            _ => AddressFormat::Segwit,
+        };
         let hrp = Some(address.hrp);
 
         Ok(Address {
@@ -318,14 +344,21 @@ impl Address {
             hash,
             checksum_type,
             hrp,
-            addr_format: AddressFormat::Segwit,
+            addr_format,
         })
     }
 
     pub fn to_segwitaddress(&self) -> Result<SegwitAddress, String> {
-        match &self.hrp {
-            Some(hrp) => Ok(SegwitAddress::new(&self.hash, hrp.to_string())),
-            None => Err("hrp must be provided for segwit address".into()),
+        let hrp = match &self.hrp {
+            Some(hrp) => hrp.to_string(),
+            None => return Err("hrp must be provided for segwit address".into()),
+        };
+        match (&self.addr_format, &self.hash) {
+            (AddressFormat::Taproot, AddressHashEnum::WitnessScriptHash(output_key)) => {
+                Ok(SegwitAddress::new_taproot(output_key, hrp))
+            },
+            (AddressFormat::Taproot, _) => Err("Taproot address must contain a 32 bytes long output key".into()),
+            _ => Ok(SegwitAddress::new(&self.hash, hrp)),
         }
     }
 }
@@ -561,4 +594,24 @@ mod tests {
         let address: Address = "1DmFp16U73RrVZtYUbo2Ectt8mAnYScpqM".into();
         assert_eq!(address.to_cashaddress("prefix", 0, 5).unwrap(), expected_address);
     }
+
+    #[test]
+    fn test_from_to_taproot_address() {
+        let taproot = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr";
+        let address = Address::from_segwitaddress(taproot, ChecksumType::DSHA256, 0, 0).unwrap();
+        assert_eq!(address.addr_format, AddressFormat::Taproot);
+        assert_eq!(
+            address.hash,
+            AddressHashEnum::WitnessScriptHash(
+                "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into()
+            )
+        );
+        assert_eq!(address.to_string(), taproot);
+        assert_eq!(address.display_address().unwrap(), taproot);
+
+        let segwit = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        let address = Address::from_segwitaddress(segwit, ChecksumType::DSHA256, 0, 0).unwrap();
+        assert_eq!(address.addr_format, AddressFormat::Segwit);
+        assert_eq!(address.to_string(), segwit);
+    }
 }
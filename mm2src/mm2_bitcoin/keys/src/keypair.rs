@@ -97,6 +97,8 @@ impl KeyPair {
 mod tests {
     use super::KeyPair;
     use crypto::dhash256;
+    use hash::H256;
+    use Public;
 
     /// Tests from:
     /// https://github.com/bitcoin/bitcoin/blob/a6a860796a44a2805a58391a009ba22752f64e32/src/test/key_tests.cpp
@@ -152,6 +154,26 @@ mod tests {
         assert!(!check_sign(SECRET_2C, b"", SIGN_2));
     }
 
+    #[test]
+    fn test_taproot_output_key() {
+        // https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki#test-vectors
+        let public = Public::Compressed("02cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115".into());
+        let expected: H256 = "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into();
+        assert_eq!(public.taproot_output_key().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sign_verify_taproot_key_path() {
+        let message = dhash256(b"Very deterministic message");
+        let kp = KeyPair::from_private(SECRET_1C.into()).unwrap();
+        let signature = kp.private().sign_taproot_key_path(&message).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(kp.public().verify_taproot_key_path(&message, &signature).unwrap());
+
+        let other = KeyPair::from_private(SECRET_2C.into()).unwrap();
+        assert!(!other.public().verify_taproot_key_path(&message, &signature).unwrap());
+    }
+
     #[test]
     fn test_verify() {
         let message = b"Very deterministic message";
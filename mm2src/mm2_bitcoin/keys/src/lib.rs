@@ -31,7 +31,7 @@ pub use error::Error;
 pub use keypair::KeyPair;
 pub use network::Network;
 pub use private::Private;
-pub use public::Public;
+pub use public::{verify_schnorr, Public};
 pub use segwitaddress::SegwitAddress;
 pub use signature::{CompactSignature, Signature};
 
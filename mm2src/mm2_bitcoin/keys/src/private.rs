@@ -1,11 +1,11 @@
 //! Secret with additional network identifier and format type
 
-use crate::SECP_SIGN;
+use crate::{SECP_SIGN, SECP_VERIFY};
 use address::detect_checksum;
 use base58::{FromBase58, ToBase58};
-use crypto::{checksum, ChecksumType};
+use crypto::{checksum, tagged_hash, ChecksumType};
 use hex::ToHex;
-use secp256k1::{Message as SecpMessage, SecretKey};
+use secp256k1::{schnorrsig, Message as SecpMessage, SecretKey};
 use std::fmt;
 use std::str::FromStr;
 use {DisplayLayout, Error, Message, Secret, Signature};
@@ -44,6 +44,19 @@ impl Private {
         out.insert(0, byte);
         Ok(out.into())
     }
+
+    /// Signs the BIP-341 sighash with the private key tweaked the same way as [`crate::Public::taproot_output_key`].
+    /// Returns the 64 bytes long BIP-340 signature.
+    pub fn sign_taproot_key_path(&self, message: &Message) -> Result<Signature, Error> {
+        let mut key_pair = schnorrsig::KeyPair::from_seckey_slice(&SECP_SIGN, &*self.secret)?;
+        let internal_key = schnorrsig::PublicKey::from_keypair(&SECP_SIGN, &key_pair);
+        let tweak = tagged_hash(b"TapTweak", &internal_key.serialize());
+        key_pair.tweak_add_assign(&SECP_VERIFY, &*tweak)?;
+
+        let message = SecpMessage::from_slice(&**message)?;
+        let signature = SECP_SIGN.schnorrsig_sign_no_aux_rand(&message, &key_pair);
+        Ok(signature[..].to_vec().into())
+    }
 }
 
 impl DisplayLayout for Private {
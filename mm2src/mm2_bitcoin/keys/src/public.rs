@@ -1,9 +1,9 @@
 use crate::SECP_VERIFY;
-use crypto::dhash160;
-use hash::{H160, H264, H520};
+use crypto::{dhash160, tagged_hash};
+use hash::{H160, H256, H264, H520};
 use hex::ToHex;
 use secp256k1::{recovery::{RecoverableSignature, RecoveryId},
-                Message as SecpMessage, PublicKey, Signature as SecpSignature};
+                schnorrsig, Message as SecpMessage, PublicKey, Signature as SecpSignature};
 use std::{fmt, ops};
 use {CompactSignature, Error, Message, Signature};
 
@@ -80,6 +80,39 @@ impl Public {
             Public::Normal(_) => None,
         }
     }
+
+    /// Returns the x-only internal key used by BIP-340 signatures.
+    pub fn x_only(&self) -> Result<schnorrsig::PublicKey, Error> {
+        let public = match self {
+            Public::Compressed(public) => PublicKey::from_slice(&**public)?,
+            Public::Normal(public) => PublicKey::from_slice(&**public)?,
+        };
+        let compressed = public.serialize();
+        Ok(schnorrsig::PublicKey::from_slice(&compressed[1..33])?)
+    }
+
+    /// Returns the x-only taproot output key `Q = P + tagged_hash("TapTweak", P)G` committing to no script tree.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#constructing-and-spending-taproot-outputs
+    pub fn taproot_output_key(&self) -> Result<H256, Error> {
+        let mut output_key = self.x_only()?;
+        let tweak = tagged_hash(b"TapTweak", &output_key.serialize());
+        output_key.tweak_add_assign(&SECP_VERIFY, &*tweak)?;
+        Ok(output_key.serialize().into())
+    }
+
+    /// Verifies the BIP-340 `signature` of the `message` against the taproot output key.
+    pub fn verify_taproot_key_path(&self, message: &Message, signature: &[u8]) -> Result<bool, Error> {
+        verify_schnorr(&self.taproot_output_key()?, message, signature)
+    }
+}
+
+/// Verifies the BIP-340 `signature` of the `message` against the given x-only public key,
+/// e.g. the taproot output key taken from a P2TR script pubkey.
+pub fn verify_schnorr(x_only: &H256, message: &Message, signature: &[u8]) -> Result<bool, Error> {
+    let public = schnorrsig::PublicKey::from_slice(&**x_only)?;
+    let signature = schnorrsig::Signature::from_slice(signature)?;
+    let message = SecpMessage::from_slice(&**message)?;
+    Ok(SECP_VERIFY.schnorrsig_verify(&signature, &message, &public).is_ok())
 }
 
 impl ops::Deref for Public {
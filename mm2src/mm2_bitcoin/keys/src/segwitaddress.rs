@@ -2,6 +2,7 @@ use std::fmt;
 use std::str::FromStr;
 
 use bech32;
+use hash::H256;
 use AddressHashEnum;
 
 /// Address error.
@@ -19,6 +20,8 @@ pub enum Error {
     InvalidWitnessProgramLength(usize),
     /// A v0 witness program must be either of length 20 or 32.
     InvalidSegwitV0ProgramLength(usize),
+    /// A v1 (taproot) witness program must be of length 32.
+    InvalidSegwitV1ProgramLength(usize),
     /// An uncompressed pubkey was used where it is not allowed.
     UncompressedPubkey,
     /// An address variant that is not supported yet was used.
@@ -44,6 +47,9 @@ impl fmt::Display for Error {
                 "a v0 witness program must be either of length 20 or 32 bytes: length={}",
                 l,
             ),
+            Error::InvalidSegwitV1ProgramLength(l) => {
+                write!(f, "a v1 witness program must be of length 32 bytes: length={}", l,)
+            },
             Error::UncompressedPubkey => write!(f, "an uncompressed pubkey was used where it is not allowed",),
             Error::UnsupportedAddressVariant(ref v) => write!(f, "address variant/format {} is not supported yet!", v),
             Error::UnsupportedWitnessVersion(v) => write!(f, "witness script version: {} is not supported yet!", v),
@@ -62,6 +68,8 @@ pub enum AddressType {
     P2wpkh,
     /// pay-to-witness-script-hash
     P2wsh,
+    /// pay-to-taproot
+    P2tr,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -84,16 +92,35 @@ impl SegwitAddress {
         }
     }
 
+    /// Creates a v1 (P2TR) address paying to the given x-only taproot output key.
+    pub fn new_taproot(output_key: &H256, hrp: String) -> SegwitAddress {
+        SegwitAddress {
+            hrp,
+            version: bech32::u5::try_from_u8(1).expect("1<32"),
+            program: output_key.to_vec(),
+        }
+    }
+
+    /// Bech32 is used for v0 witness programs and Bech32m for v1+.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#addresses-for-segregated-witness-outputs
+    fn variant(&self) -> bech32::Variant {
+        match self.version.to_u8() {
+            0 => bech32::Variant::Bech32,
+            _ => bech32::Variant::Bech32m,
+        }
+    }
+
     /// Get the address type of the address.
     /// None if unknown or non-standard.
     pub fn address_type(&self) -> Option<AddressType> {
-        // BIP-141 p2wpkh or p2wsh addresses.
+        // BIP-141 p2wpkh or p2wsh addresses and BIP-341 p2tr addresses.
         match self.version.to_u8() {
             0 => match self.program.len() {
                 20 => Some(AddressType::P2wpkh),
                 32 => Some(AddressType::P2wsh),
                 _ => None,
             },
+            1 if self.program.len() == 32 => Some(AddressType::P2tr),
             _ => None,
         }
     }
@@ -128,7 +155,7 @@ impl fmt::Display for SegwitAddress {
         } else {
             fmt as &mut dyn fmt::Write
         };
-        let mut bech32_writer = bech32::Bech32Writer::new(self.hrp.as_str(), bech32::Variant::Bech32, writer)?;
+        let mut bech32_writer = bech32::Bech32Writer::new(self.hrp.as_str(), self.variant(), writer)?;
         bech32::WriteBase32::write_u5(&mut bech32_writer, self.version)?;
         bech32::ToBase32::write_base32(&self.program, &mut bech32_writer)
     }
@@ -138,18 +165,13 @@ impl FromStr for SegwitAddress {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<SegwitAddress, Error> {
-        // decode as bech32, should use Variant if Bech32m is used alongside Bech32
+        // decode as bech32 or bech32m, the variant must match the witness version
         // The improved Bech32m variant described in [BIP-0350](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki)
         // hrp checks (mixed case not allowed, allowed length and characters) are part of the decode function
         let (hrp, payload, variant) = bech32::decode(s)?;
         if payload.is_empty() {
             return Err(Error::EmptyBech32Payload);
         }
-        match variant {
-            bech32::Variant::Bech32 => (),
-            bech32::Variant::Bech32m => return Err(Error::UnsupportedAddressVariant("Bech32m".into())),
-            // Important: If a new variant is added we should return an error until we support the new variant
-        }
 
         // Get the script version and program (converted from 5-bit to 8-bit)
         let (version, program): (bech32::u5, Vec<u8>) = {
@@ -165,18 +187,26 @@ impl FromStr for SegwitAddress {
             return Err(Error::InvalidWitnessProgramLength(program.len()));
         }
 
-        // Specific segwit v0 check.
-        if version.to_u8() != 0 {
-            return Err(Error::UnsupportedWitnessVersion(version.to_u8()));
-        }
-
-        // Bech32 length check.
-        // Important: we should be careful when using new program lengths since a valid Bech32 string can be modified according to
-        // the below 2 links while still having a valid checksum.
-        // https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#motivation
-        // https://github.com/sipa/bech32/issues/51
-        if program.len() != 20 && program.len() != 32 {
-            return Err(Error::InvalidSegwitV0ProgramLength(program.len()));
+        match (variant, version.to_u8()) {
+            // Bech32 length check.
+            // Important: we should be careful when using new program lengths since a valid Bech32 string can be modified according to
+            // the below 2 links while still having a valid checksum.
+            // https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#motivation
+            // https://github.com/sipa/bech32/issues/51
+            (bech32::Variant::Bech32, 0) => {
+                if program.len() != 20 && program.len() != 32 {
+                    return Err(Error::InvalidSegwitV0ProgramLength(program.len()));
+                }
+            },
+            // Taproot outputs are the only v1 witness programs that are standard.
+            (bech32::Variant::Bech32m, 1) => {
+                if program.len() != 32 {
+                    return Err(Error::InvalidSegwitV1ProgramLength(program.len()));
+                }
+            },
+            (bech32::Variant::Bech32m, 0) => return Err(Error::UnsupportedAddressVariant("Bech32m".into())),
+            // Important: If a new variant is added we should return an error until we support the new variant
+            (_, version) => return Err(Error::UnsupportedWitnessVersion(version)),
         }
 
         Ok(SegwitAddress { hrp, version, program })
@@ -228,6 +258,35 @@ mod tests {
         assert_eq!(addr.address_type(), Some(AddressType::P2wsh));
     }
 
+    #[test]
+    // https://github.com/bitcoin/bips/blob/master/bip-0086.mediawiki#test-vectors
+    fn test_p2tr_address() {
+        let output_key: H256 = "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into();
+        let addr = SegwitAddress::new_taproot(&output_key, "bc".to_string());
+        assert_eq!(
+            &addr.to_string(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(addr.address_type(), Some(AddressType::P2tr));
+
+        let parsed = SegwitAddress::from_str("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr").unwrap();
+        assert_eq!(parsed, addr);
+    }
+
+    #[test]
+    // https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki#test-vectors-for-v0-v16-native-segregated-witness-addresses
+    fn test_invalid_bech32m_addresses() {
+        // Invalid checksum (Bech32 instead of Bech32m)
+        let invalid_address = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd";
+        let err = SegwitAddress::from_str(invalid_address).unwrap_err();
+        assert_eq!(err, Error::UnsupportedWitnessVersion(1));
+
+        // Invalid checksum (Bech32m instead of Bech32)
+        let invalid_address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh";
+        let err = SegwitAddress::from_str(invalid_address).unwrap_err();
+        assert_eq!(err, Error::UnsupportedAddressVariant("Bech32m".into()));
+    }
+
     #[test]
     // https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki#test-vectors
     fn test_valid_segwit() {
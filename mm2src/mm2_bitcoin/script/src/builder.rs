@@ -2,7 +2,7 @@
 
 use bytes::Bytes;
 use keys::{AddressHashEnum, Public};
-use {Num, Opcode, Script};
+use {Error, Num, Opcode, Script};
 
 /// Script builder
 #[derive(Default)]
@@ -47,6 +47,37 @@ impl Builder {
             .into_script()
     }
 
+    /// Builds p2tr script pubkey, the hash is expected to be the x-only taproot output key
+    pub fn build_p2tr(address: &AddressHashEnum) -> Script {
+        Builder::default()
+            .push_opcode(Opcode::OP_1)
+            .push_bytes(&address.to_vec())
+            .into_script()
+    }
+
+    /// Builds m-of-n multisig script, the public keys are pushed in the given order
+    pub fn build_multisig(required: usize, pubkeys: &[Public]) -> Result<Script, Error> {
+        if pubkeys.is_empty() || pubkeys.len() > 16 {
+            return Err(Error::PubkeyCount);
+        }
+        if required == 0 || required > pubkeys.len() {
+            return Err(Error::SigCount);
+        }
+
+        let op_n = |n: usize| {
+            Opcode::from_u8(Opcode::OP_1 as u8 + n as u8 - 1).expect("value is within [OP_1; OP_16] interval; qed")
+        };
+        let builder = pubkeys
+            .iter()
+            .fold(Builder::default().push_opcode(op_n(required)), |builder, pubkey| {
+                builder.push_bytes(pubkey)
+            });
+        Ok(builder
+            .push_opcode(op_n(pubkeys.len()))
+            .push_opcode(Opcode::OP_CHECKMULTISIG)
+            .into_script())
+    }
+
     /// Builds op_return script
     pub fn build_nulldata(bytes: &[u8]) -> Script {
         Builder::default()
@@ -19,6 +19,7 @@ pub enum ScriptType {
     NullData,
     WitnessScript,
     WitnessKey,
+    WitnessTaproot,
     // Qtum specific
     CallSender,
     CreateSender,
@@ -68,6 +69,14 @@ impl ScriptAddress {
             hash,
         }
     }
+
+    /// Creates P2TR-type ScriptAddress
+    pub fn new_p2tr(hash: AddressHashEnum) -> Self {
+        ScriptAddress {
+            kind: keys::Type::P2TR,
+            hash,
+        }
+    }
 }
 
 /// Serialized script, used inside transaction inputs and outputs.
@@ -160,6 +169,11 @@ impl Script {
         self.data.len() == 34 && self.data[0] == Opcode::OP_0 as u8 && self.data[1] == Opcode::OP_PUSHBYTES_32 as u8
     }
 
+    /// Extra-fast test for pay-to-taproot scripts.
+    pub fn is_pay_to_taproot(&self) -> bool {
+        self.data.len() == 34 && self.data[0] == Opcode::OP_1 as u8 && self.data[1] == Opcode::OP_PUSHBYTES_32 as u8
+    }
+
     /// Extra-fast test for multisig scripts.
     pub fn is_multisig_script(&self) -> bool {
         if self.data.len() < 3 {
@@ -348,6 +362,8 @@ impl Script {
             ScriptType::WitnessKey
         } else if self.is_pay_to_witness_script_hash() {
             ScriptType::WitnessScript
+        } else if self.is_pay_to_taproot() {
+            ScriptType::WitnessTaproot
         // TODO add Call
         } else {
             ScriptType::NonStandard
@@ -429,22 +445,11 @@ impl Script {
             ScriptType::ScriptHash => Ok(vec![ScriptAddress::new_p2sh(AddressHashEnum::AddressHash(
                 self.data[2..22].into(),
             ))]),
-            ScriptType::Multisig => {
-                let mut addresses: Vec<ScriptAddress> = Vec::new();
-                let mut pc = 1;
-                while pc < self.len() - 2 {
-                    let instruction = self
-                        .get_instruction_at(pc)
-                        .expect("this method depends on previous check in script_type()");
-                    let data = instruction
-                        .data
-                        .expect("this method depends on previous check in script_type()");
-                    let address = Public::from_slice(data)?.address_hash();
-                    addresses.push(ScriptAddress::new_p2pkh(AddressHashEnum::AddressHash(address)));
-                    pc += instruction.step;
-                }
-                Ok(addresses)
-            },
+            ScriptType::Multisig => Ok(self
+                .extract_multisig_pubkeys()?
+                .into_iter()
+                .map(|public| ScriptAddress::new_p2pkh(AddressHashEnum::AddressHash(public.address_hash())))
+                .collect()),
             ScriptType::NullData => Ok(vec![]),
             ScriptType::WitnessScript => Ok(vec![ScriptAddress::new_p2wsh(AddressHashEnum::WitnessScriptHash(
                 self.data[2..34].into(),
@@ -452,6 +457,9 @@ impl Script {
             ScriptType::WitnessKey => Ok(vec![ScriptAddress::new_p2wpkh(AddressHashEnum::AddressHash(
                 self.data[2..22].into(),
             ))]),
+            ScriptType::WitnessTaproot => Ok(vec![ScriptAddress::new_p2tr(AddressHashEnum::WitnessScriptHash(
+                self.data[2..34].into(),
+            ))]),
             ScriptType::CallSender => {
                 Ok(vec![]) // TODO
             },
@@ -470,6 +478,28 @@ impl Script {
         }
     }
 
+    /// Extracts the public keys of the multisig script in the order they are pushed to the script.
+    /// Returns an empty list if it's not a multisig script.
+    pub fn extract_multisig_pubkeys(&self) -> Result<Vec<Public>, keys::Error> {
+        if !self.is_multisig_script() {
+            return Ok(vec![]);
+        }
+
+        let mut pubkeys = Vec::new();
+        let mut pc = 1;
+        while pc < self.len() - 2 {
+            let instruction = self
+                .get_instruction_at(pc)
+                .expect("this method depends on previous check in is_multisig_script()");
+            let data = instruction
+                .data
+                .expect("this method depends on previous check in is_multisig_script()");
+            pubkeys.push(Public::from_slice(data)?);
+            pc += instruction.step;
+        }
+        Ok(pubkeys)
+    }
+
     pub fn pay_to_script_hash_sigops(&self, prev_out: &Script) -> usize {
         if !prev_out.is_pay_to_script_hash() {
             return 0;
@@ -609,7 +639,7 @@ pub fn is_witness_commitment_script(script: &[u8]) -> bool {
 mod tests {
     use super::{Script, ScriptAddress, ScriptType};
     use crypto::ChecksumType;
-    use keys::{Address, Public};
+    use keys::{Address, AddressHashEnum, Public};
     use {Builder, Error, Opcode};
 
     /// Maximum number of bytes pushable to the stack
@@ -639,6 +669,21 @@ mod tests {
         assert!(!script2.is_pay_to_witness_script_hash());
     }
 
+    #[test]
+    fn test_is_pay_to_taproot() {
+        let script: Script = "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into();
+        let script2: Script = "0020a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into();
+        assert!(script.is_pay_to_taproot());
+        assert!(!script2.is_pay_to_taproot());
+        assert_eq!(script.script_type(), ScriptType::WitnessTaproot);
+        assert_eq!(
+            script.extract_destinations(),
+            Ok(vec![ScriptAddress::new_p2tr(AddressHashEnum::WitnessScriptHash(
+                "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c".into()
+            ))])
+        );
+    }
+
     #[test]
     fn test_script_debug() {
         use std::fmt::Write;
@@ -841,6 +886,26 @@ OP_ADD
         );
     }
 
+    #[test]
+    fn test_build_multisig() {
+        let pubkeys = vec![
+            Public::from_slice(&[2; 33]).unwrap(),
+            Public::from_slice(&[3; 33]).unwrap(),
+            Public::from_slice(&[4; 65]).unwrap(),
+        ];
+        let script = Builder::build_multisig(2, &pubkeys).unwrap();
+        assert_eq!(script.script_type(), ScriptType::Multisig);
+        assert_eq!(script.num_signatures_required(), 2);
+        assert_eq!(script.extract_multisig_pubkeys(), Ok(pubkeys.clone()));
+
+        assert_eq!(Builder::build_multisig(0, &pubkeys), Err(Error::SigCount));
+        assert_eq!(Builder::build_multisig(4, &pubkeys), Err(Error::SigCount));
+        assert_eq!(Builder::build_multisig(1, &[]), Err(Error::PubkeyCount));
+
+        let not_multisig = Builder::build_p2pkh(&pubkeys[0].address_hash().into());
+        assert_eq!(not_multisig.extract_multisig_pubkeys(), Ok(vec![]));
+    }
+
     #[test]
     fn test_extract_destinations_multisig() {
         let pubkey1_bytes = [0; 33];
@@ -4,7 +4,7 @@ use blake2b_simd::Params as Blake2b;
 use bytes::Bytes;
 use chain::{JoinSplit, OutPoint, ShieldedOutput, ShieldedSpend, Transaction, TransactionInput, TransactionOutput,
             TxHashAlgo};
-use crypto::{dhash256, sha256};
+use crypto::{dhash256, sha256, tagged_hash};
 use hash::{H256, H512};
 use keys::KeyPair;
 use ser::Stream;
@@ -27,6 +27,8 @@ pub enum SignatureVersion {
     WitnessV0,
     #[serde(rename = "fork_id")]
     ForkId,
+    #[serde(rename = "taproot")]
+    Taproot,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -246,9 +248,110 @@ impl TransactionInputSigner {
             SignatureVersion::WitnessV0 => {
                 self.signature_hash_witness0(input_index, input_amount, script_pubkey, sighashtype, sighash)
             },
+            // Only one `script_pubkey` is given here, so every input is considered to spend an output locked by it.
+            // Use [`TransactionInputSigner::signature_hash_taproot`] directly if that's not the case
+            // or if the input index and the sighash type aren't checked by the caller.
+            // The coins can't be configured with the taproot `signature_version`, so it's never reached by the swaps.
+            SignatureVersion::Taproot => {
+                let spent_scripts = vec![script_pubkey.clone(); self.inputs.len()];
+                self.signature_hash_taproot(input_index, &spent_scripts, sighashtype)
+                    .unwrap()
+            },
         }
     }
 
+    /// BIP-341 signature hash for the key path spending (no annex).
+    /// `spent_scripts` are the script pubkeys of the outputs spent by every input of the transaction.
+    /// https://github.com/bitcoin/bips/blob/master/bip-0341.mediawiki#common-signature-message
+    ///
+    /// Unlike the legacy signature hash, there is no `1` hash for an invalid input: such a signature is invalid.
+    pub fn signature_hash_taproot(
+        &self,
+        input_index: usize,
+        spent_scripts: &[Script],
+        sighashtype: u32,
+    ) -> Result<H256, String> {
+        if !matches!(sighashtype, 0x00..=0x03 | 0x81..=0x83) {
+            return Err(format!("Invalid taproot sighash type {:#x}", sighashtype));
+        }
+        if input_index >= self.inputs.len() {
+            return Err(format!(
+                "Input index {} is out of bound, there are {} inputs",
+                input_index,
+                self.inputs.len()
+            ));
+        }
+        if spent_scripts.len() != self.inputs.len() {
+            return Err(format!(
+                "Expected {} spent scripts, found {}",
+                self.inputs.len(),
+                spent_scripts.len()
+            ));
+        }
+
+        let sighash = Sighash::from_u32(SignatureVersion::Taproot, sighashtype);
+        if sighash.base == SighashBase::Single && input_index >= self.outputs.len() {
+            return Err(format!(
+                "SIGHASH_SINGLE input {} has no corresponding output, there are {} outputs",
+                input_index,
+                self.outputs.len()
+            ));
+        }
+
+        let mut stream = Stream::default();
+        // sighash epoch
+        stream.append(&0u8);
+        stream.append(&(sighashtype as u8));
+        stream.append(&self.version);
+        stream.append(&self.lock_time);
+
+        if !sighash.anyone_can_pay {
+            let mut prevouts = Stream::default();
+            let mut amounts = Stream::default();
+            let mut scripts = Stream::default();
+            let mut sequences = Stream::default();
+            for (input, script) in self.inputs.iter().zip(spent_scripts) {
+                prevouts.append(&input.previous_output);
+                amounts.append(&input.amount);
+                scripts.append(&script.to_bytes());
+                sequences.append(&input.sequence);
+            }
+            stream.append(&sha256(&prevouts.out()));
+            stream.append(&sha256(&amounts.out()));
+            stream.append(&sha256(&scripts.out()));
+            stream.append(&sha256(&sequences.out()));
+        }
+
+        if sighash.base == SighashBase::All {
+            let mut outputs = Stream::default();
+            for output in self.outputs.iter() {
+                outputs.append(output);
+            }
+            stream.append(&sha256(&outputs.out()));
+        }
+
+        // spend_type: key path spending without annex
+        stream.append(&0u8);
+
+        if sighash.anyone_can_pay {
+            let input = &self.inputs[input_index];
+            stream.append(&input.previous_output);
+            stream.append(&input.amount);
+            stream.append(&spent_scripts[input_index].to_bytes());
+            stream.append(&input.sequence);
+        } else {
+            stream.append(&(input_index as u32));
+        }
+
+        if sighash.base == SighashBase::Single {
+            let mut output = Stream::default();
+            output.append(&self.outputs[input_index]);
+            stream.append(&sha256(&output.out()));
+        }
+
+        Ok(tagged_hash(b"TapSighash", &stream.out()))
+    }
+
     /// input_index - index of input to sign
     /// script_pubkey - script_pubkey of input's previous_output pubkey
     pub fn signed_input(
@@ -607,8 +710,9 @@ mod tests {
                 UnsignedTransactionInput};
     use bytes::Bytes;
     use chain::{OutPoint, Transaction, TransactionOutput};
+    use crypto::ChecksumType;
     use hash::{H160, H256};
-    use keys::{Address, AddressHashEnum, Private};
+    use keys::{Address, AddressHashEnum, KeyPair, Private};
     use script::Script;
     use sign::SignerHashAlgo;
 
@@ -873,4 +977,115 @@ mod tests {
             hash.reversed()
         );
     }
+
+    // https://github.com/bitcoin/bips/blob/master/bip-0341/wallet-test-vectors.json `keyPathSpending`
+    #[test]
+    fn test_signature_hash_taproot() {
+        let tx: Transaction = "02000000097de20cbff686da83a54981d2b9bab3586f4ca7e48f57f5b55963115f3b334e9c010000000000000000d7b7cab57b1393ace2d064f4d4a2cb8af6def61273e127517d44759b6dafdd990000000000fffffffff8e1f583384333689228c5d28eac13366be082dc57441760d957275419a418420000000000fffffffff0689180aa63b30cb162a73c6d2a38b7eeda2a83ece74310fda0843ad604853b0100000000feffffffaa5202bdf6d8ccd2ee0f0202afbbb7461d9264a25e5bfd3c5a52ee1239e0ba6c0000000000feffffff956149bdc66faa968eb2be2d2faa29718acbfe3941215893a2a3446d32acd050000000000000000000e664b9773b88c09c32cb70a2a3e4da0ced63b7ba3b22f848531bbb1d5d5f4c94010000000000000000e9aa6b8e6c9de67619e6a3924ae25696bb7b694bb677a632a74ef7eadfd4eabf0000000000ffffffffa778eb6a263dc090464cd125c466b5a99667720b1c110468831d058aa1b82af10100000000ffffffff0200ca9a3b000000001976a91406afd46bcdfd22ef94ac122aa11f241244a37ecc88ac807840cb0000000020ac9a87f5594be208f8532db38cff670c450ed2fea8fcdefcc9a663f78bab962b0065cd1d".into();
+        let utxos_spent = [
+            (
+                "512053a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+                420000000,
+            ),
+            (
+                "5120147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+                462000000,
+            ),
+            ("76a914751e76e8199196d454941c45d1b3a323f1433bd688ac", 294000000),
+            (
+                "5120e4d810fd50586274face62b8a807eb9719cef49c04177cc6b76a9a4251d5450e",
+                504000000,
+            ),
+            (
+                "512091b64d5324723a985170e4dc5a0f84c041804f2cd12660fa5dec09fc21783605",
+                630000000,
+            ),
+            ("00147dd65592d0ab2fe0d0257d571abf032cd9db93dc", 378000000),
+            (
+                "512075169f4001aa68f15bbed28b218df1d0a62cbbcf1188c6665110c293c907b831",
+                672000000,
+            ),
+            (
+                "5120712447206d7a5238acc7ff53fbe94a3b64539ad291c7cdbc490b7577e4b17df5",
+                546000000,
+            ),
+            (
+                "512077e30a5522dd9f894c3f8b8bd4c4b2cf82ca7da8a3ea6a239655c39c050ab220",
+                588000000,
+            ),
+        ];
+        let mut signer = TransactionInputSigner::from(tx);
+        let mut spent_scripts = Vec::new();
+        for (input, (script, amount)) in signer.inputs.iter_mut().zip(utxos_spent.iter()) {
+            input.amount = *amount;
+            spent_scripts.push(Script::from(*script));
+        }
+
+        // (input index, sighash type, expected sighash)
+        let expected = [
+            (
+                0,
+                0x03,
+                "2514a6272f85cfa0f45eb907fcb0d121b808ed37c6ea160a5a9046ed5526d555",
+            ),
+            (
+                1,
+                0x83,
+                "325a644af47e8a5a2591cda0ab0723978537318f10e6a63d4eed783b96a71a4d",
+            ),
+            (
+                3,
+                0x01,
+                "bf013ea93474aa67815b1b6cc441d23b64fa310911d991e713cd34c7f5d46669",
+            ),
+            (
+                4,
+                0x00,
+                "4f900a0bae3f1446fd48490c2958b5a023228f01661cda3496a11da502a7f7ef",
+            ),
+            (
+                6,
+                0x02,
+                "15f25c298eb5cdc7eb1d638dd2d45c97c4c59dcaec6679cfc16ad84f30876b85",
+            ),
+            (
+                7,
+                0x82,
+                "cd292de50313804dabe4685e83f923d2969577191a3e1d2882220dca88cbeb10",
+            ),
+            (
+                8,
+                0x81,
+                "cccb739eca6c13a8a89e6e5cd317ffe55669bbda23f2fd37b0f18755e008edd2",
+            ),
+        ];
+        for (input_index, sighash_type, expected_sighash) in expected.iter() {
+            let sighash = signer
+                .signature_hash_taproot(*input_index, &spent_scripts, *sighash_type)
+                .unwrap();
+            assert_eq!(sighash, H256::from(*expected_sighash), "input {}", input_index);
+        }
+
+        // The key path spending of the input 0 with the untweaked private key, no script tree.
+        let private = Private {
+            prefix: 128,
+            secret: "6b973d88838f27366ed61c9ad6367663045cb456e28335c109e30717ae0c6baa".into(),
+            compressed: true,
+            checksum_type: ChecksumType::DSHA256,
+        };
+        let key_pair = KeyPair::from_private(private).unwrap();
+        let output_key = key_pair.public().taproot_output_key().unwrap();
+        assert_eq!(&*output_key, &spent_scripts[0][2..]);
+        let sighash = H256::from(expected[0].2);
+        let signature = key_pair.private().sign_taproot_key_path(&sighash).unwrap();
+        assert!(key_pair.public().verify_taproot_key_path(&sighash, &signature).unwrap());
+
+        // There is no legacy `1` sighash for the invalid inputs.
+        assert!(signer.signature_hash_taproot(9, &spent_scripts, 0x00).is_err());
+        assert!(signer.signature_hash_taproot(0, &spent_scripts[1..], 0x00).is_err());
+        // SIGHASH_SINGLE of the input 2 while there are 2 outputs only.
+        assert!(signer.signature_hash_taproot(2, &spent_scripts, 0x03).is_err());
+        assert!(signer.signature_hash_taproot(0, &spent_scripts, 0x04).is_err());
+        assert!(signer.signature_hash_taproot(0, &spent_scripts, 0x80).is_err());
+    }
 }
@@ -77,15 +77,27 @@ impl ObjectType {
     }
 }
 
-/// Add `Int64`, `Uint64`, `Int256`, `Array` types if required.
+/// The types of the EIP712 struct members.
 /// https://github.com/ethereum/EIPs/blob/master/EIPS/eip-712.md#definition-of-typed-structured-data-%F0%9D%95%8A
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PropertyType {
     Bool,
     String,
     Uint256,
     Address,
     Bytes32,
+    /// `uint8` to `uint248`. `uint256` is parsed as [`PropertyType::Uint256`].
+    Uint(usize),
+    /// `int8` to `int256`.
+    Int(usize),
+    /// Dynamic `bytes`.
+    Bytes,
+    /// `bytes1` to `bytes31`. `bytes32` is parsed as [`PropertyType::Bytes32`].
+    FixedBytes(usize),
+    /// `Type[]`.
+    Array(Box<PropertyType>),
+    /// `Type[n]`.
+    FixedArray(Box<PropertyType>, usize),
     Custom(String),
 }
 
@@ -97,6 +109,12 @@ impl fmt::Display for PropertyType {
             PropertyType::Uint256 => write!(f, "uint256"),
             PropertyType::Address => write!(f, "address"),
             PropertyType::Bytes32 => write!(f, "bytes32"),
+            PropertyType::Uint(bits) => write!(f, "uint{bits}"),
+            PropertyType::Int(bits) => write!(f, "int{bits}"),
+            PropertyType::Bytes => write!(f, "bytes"),
+            PropertyType::FixedBytes(len) => write!(f, "bytes{len}"),
+            PropertyType::Array(item) => write!(f, "{item}[]"),
+            PropertyType::FixedArray(item, len) => write!(f, "{item}[{len}]"),
             PropertyType::Custom(custom) => write!(f, "{custom}"),
         }
     }
@@ -106,18 +124,52 @@ impl FromStr for PropertyType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(item) = s.strip_suffix(']') {
+            let index = item.rfind('[').ok_or_else(|| format!("Invalid array type '{s}'"))?;
+            let item_type = Box::new(PropertyType::from_str(&item[..index])?);
+            let len = &item[index + 1..];
+            if len.is_empty() {
+                return Ok(PropertyType::Array(item_type));
+            }
+            let len = len.parse().map_err(|e| format!("Invalid array type '{s}': {e}"))?;
+            return Ok(PropertyType::FixedArray(item_type, len));
+        }
+
         let property_type = match s {
             "bool" => PropertyType::Bool,
             "string" => PropertyType::String,
             "uint256" => PropertyType::Uint256,
             "address" => PropertyType::Address,
             "bytes32" => PropertyType::Bytes32,
-            custom => PropertyType::Custom(custom.to_string()),
+            "bytes" => PropertyType::Bytes,
+            _ => {
+                if let Some(bits) = s.strip_prefix("uint").and_then(parse_int_bits) {
+                    PropertyType::Uint(bits)
+                } else if let Some(bits) = s.strip_prefix("int").and_then(parse_int_bits) {
+                    PropertyType::Int(bits)
+                } else if let Some(len) = s.strip_prefix("bytes").and_then(|len| len.parse().ok()) {
+                    if !(1..=32).contains(&len) {
+                        return Err(format!("Invalid fixed bytes type '{s}'"));
+                    }
+                    PropertyType::FixedBytes(len)
+                } else {
+                    PropertyType::Custom(s.to_string())
+                }
+            },
         };
         Ok(property_type)
     }
 }
 
+/// Parses the size of `uintN`/`intN` that is a multiple of 8 from 8 to 256.
+fn parse_int_bits(bits: &str) -> Option<usize> {
+    let bits = bits.parse().ok()?;
+    if bits == 0 || bits > 256 || bits % 8 != 0 {
+        return None;
+    }
+    Some(bits)
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ObjectProperty {
     pub(crate) name: String,
@@ -125,7 +177,7 @@ pub struct ObjectProperty {
     pub(crate) property_type: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Eip712<Domain, SignData> {
     /// Defines the types of the domain and data you will be signing.
     pub types: CustomTypes,
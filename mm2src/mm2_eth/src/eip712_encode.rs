@@ -80,9 +80,15 @@ fn encode_data(
     match data_type {
         PropertyType::Bool => encode_bool(data, field_name),
         PropertyType::String => encode_string(data, field_name),
-        PropertyType::Uint256 => encode_u256(data, field_name),
+        PropertyType::Uint256 => encode_uint(data, 256, field_name),
         PropertyType::Address => encode_address(data, field_name),
-        PropertyType::Bytes32 => encode_bytes32(data, field_name),
+        PropertyType::Bytes32 => encode_fixed_bytes(data, 32, field_name),
+        PropertyType::Uint(bits) => encode_uint(data, bits, field_name),
+        PropertyType::Int(bits) => encode_int(data, bits, field_name),
+        PropertyType::Bytes => encode_bytes(data, field_name),
+        PropertyType::FixedBytes(len) => encode_fixed_bytes(data, len, field_name),
+        PropertyType::Array(item_type) => encode_array(custom_types, *item_type, data, None, field_name),
+        PropertyType::FixedArray(item_type, len) => encode_array(custom_types, *item_type, data, Some(len), field_name),
         PropertyType::Custom(custom) => encode_custom(custom_types, &custom, data, field_name),
     }
 }
@@ -110,18 +116,58 @@ fn encode_custom(
     Ok(keccak256(&encoded_tokens).as_ref().to_vec())
 }
 
-fn encode_bytes32(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
-    let string = value
-        .as_str()
-        .ok_or_else(|| expected_type_error("bytes32", value, field_name))?;
-    check_hex(string, field_name)?;
+/// Arrays are encoded as the hash of the concatenated encodings of their items.
+fn encode_array(
+    custom_types: &CustomTypes,
+    item_type: PropertyType,
+    value: &Json,
+    expected_len: Option<usize>,
+    field_name: Option<&str>,
+) -> Result<Vec<u8>> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| expected_type_error("array", value, field_name))?;
+    if let Some(expected_len) = expected_len {
+        if items.len() != expected_len {
+            let error = format!("Expected {expected_len} array items, found {}", items.len());
+            return Err(decode_error(error, field_name));
+        }
+    }
+
+    let mut encoded_items = Vec::new();
+    for item in items {
+        let mut encoded = encode_data(custom_types, item_type.clone(), item, field_name)?;
+        encoded_items.append(&mut encoded);
+    }
+    Ok(encode(&[Token::FixedBytes(keccak256(&encoded_items).to_vec())]))
+}
+
+fn encode_fixed_bytes(value: &Json, len: usize, field_name: Option<&str>) -> Result<Vec<u8>> {
+    let bytes = decode_hex_bytes(value, field_name)?;
+    if bytes.len() != len {
+        let error = format!("Expected {len} bytes, found {}", bytes.len());
+        return Err(decode_error(error, field_name));
+    }
+    // `FixedBytes` is right-padded to 32 bytes.
+    Ok(encode(&[Token::FixedBytes(bytes)]))
+}
 
-    let bytes = hex::decode(string).map_err(|e| decode_error(e, field_name))?;
+fn encode_bytes(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
+    let bytes = decode_hex_bytes(value, field_name)?;
     let hash = keccak256(&bytes).to_vec();
 
     Ok(encode(&[Token::FixedBytes(hash)]))
 }
 
+fn decode_hex_bytes(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
+    let string = value
+        .as_str()
+        .ok_or_else(|| expected_type_error("bytes", value, field_name))?;
+    check_hex(string, field_name)?;
+
+    hex::decode(&string[2..]).map_err(|e| decode_error(e, field_name))
+}
+
 fn encode_string(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
     let string = value
         .as_str()
@@ -150,16 +196,55 @@ fn encode_address(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
     Ok(encode(&[Token::Address(address)]))
 }
 
-fn encode_u256(value: &Json, field_name: Option<&str>) -> Result<Vec<u8>> {
-    let string = value
-        .as_str()
-        .ok_or_else(|| expected_type_error("str(u256)", value, field_name))?;
-    check_hex(string, field_name)?;
-
-    let uint = U256::from_str(&string[2..]).map_err(|e| decode_error(e, field_name))?;
+fn encode_uint(value: &Json, bits: usize, field_name: Option<&str>) -> Result<Vec<u8>> {
+    let (is_negative, uint) = parse_integer(value, field_name)?;
+    if is_negative || (bits < 256 && uint.bits() > bits) {
+        return Err(decode_error(
+            format!("{value} is out of 'uint{bits}' range"),
+            field_name,
+        ));
+    }
     Ok(encode(&[Token::Uint(uint)]))
 }
 
+fn encode_int(value: &Json, bits: usize, field_name: Option<&str>) -> Result<Vec<u8>> {
+    let (is_negative, abs) = parse_integer(value, field_name)?;
+    // The range is `[-2^(bits-1), 2^(bits-1) - 1]`.
+    let limit = U256::one() << (bits - 1);
+    let out_of_range = if is_negative { abs > limit } else { abs >= limit };
+    if out_of_range {
+        return Err(decode_error(format!("{value} is out of 'int{bits}' range"), field_name));
+    }
+
+    // Negative integers are encoded in two's complement.
+    let int = if is_negative {
+        (!abs).overflowing_add(U256::one()).0
+    } else {
+        abs
+    };
+    Ok(encode(&[Token::Int(int)]))
+}
+
+/// Parses a JSON number, a decimal or a 0x-prefixed hex string.
+/// Returns whether the value is negative and its absolute value.
+fn parse_integer(value: &Json, field_name: Option<&str>) -> Result<(bool, U256)> {
+    let string = match value {
+        Json::Number(number) => number.to_string(),
+        Json::String(string) => string.clone(),
+        _ => return Err(expected_type_error("integer", value, field_name)),
+    };
+    let (is_negative, abs) = match string.strip_prefix('-') {
+        Some(abs) => (true, abs),
+        None => (false, string.as_str()),
+    };
+
+    let abs = match abs.strip_prefix("0x") {
+        Some(hex) => U256::from_str(hex).map_err(|e| decode_error(e, field_name))?,
+        None => U256::from_dec_str(abs).map_err(|e| decode_error(e, field_name))?,
+    };
+    Ok((is_negative, abs))
+}
+
 fn encode_type(custom_types: &CustomTypes, data_type: &str) -> Result<String> {
     let deps = {
         let mut temp = build_dependencies(data_type, custom_types).ok_or_else(|| {
@@ -214,7 +299,7 @@ fn build_dependencies<'a>(data_type: &'a str, custom_types: &'a CustomTypes) ->
                     &field.property_type
                 };
                 // seen this type before? or not a custom type skip
-                if !deps.contains(field_type) || custom_types.contains_key(field_type) {
+                if !deps.contains(field_type) && custom_types.contains_key(field_type) {
                     types_stack.insert(field_type);
                 }
             }
@@ -391,4 +476,132 @@ mod tests {
             "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2",
         );
     }
+
+    /// https://github.com/MetaMask/eth-sig-util/blob/v7.0.0/src/sign-typed-data.test.ts
+    #[test]
+    fn test_hash_data_with_arrays() {
+        const JSON: &str = r#"{
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": {
+                    "name": "Cow",
+                    "wallets": [
+                        "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                        "0xDeaDbeefdEAdbeefdEadbEEFdeadbeEFdEaDbeeF"
+                    ]
+                },
+                "to": [
+                    {
+                        "name": "Bob",
+                        "wallets": [
+                            "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                            "0xB0BdaBea57B0BDABeA57b0bdABEA57b0BDabEa57",
+                            "0xB0B0b0b0b0b0B000000000000000000000000000"
+                        ]
+                    }
+                ],
+                "contents": "Hello, Bob!"
+            },
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Group": [
+                    { "name": "name", "type": "string" },
+                    { "name": "members", "type": "Person[]" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person[]" },
+                    { "name": "contents", "type": "string" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallets", "type": "address[]" }
+                ]
+            }
+        }"#;
+
+        let typed_data = serde_json::from_str::<Eip712Raw>(JSON).expect("alas error!");
+        let hash = hash_typed_data_raw(typed_data).expect("alas error!");
+        assert_eq!(
+            format!("{:02x}", hash),
+            "a85c2e2b118698e88db68a8105b794a8cc7cec074e89ef991cb4f5f533819cc2",
+        );
+    }
+
+    #[test]
+    fn test_hash_data_with_sized_types() {
+        const JSON: &str = r#"{
+            "primaryType": "Order",
+            "domain": {
+                "name": "Order Book",
+                "chainId": "137"
+            },
+            "message": {
+                "side": 1,
+                "delta": "-42",
+                "selector": "0xa9059cbb",
+                "payload": "0xdeadbeef",
+                "amounts": ["1000000000000000000", "0x10"],
+                "active": true
+            },
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "chainId", "type": "uint256" }
+                ],
+                "Order": [
+                    { "name": "side", "type": "uint8" },
+                    { "name": "delta", "type": "int64" },
+                    { "name": "selector", "type": "bytes4" },
+                    { "name": "payload", "type": "bytes" },
+                    { "name": "amounts", "type": "uint256[2]" },
+                    { "name": "active", "type": "bool" }
+                ]
+            }
+        }"#;
+
+        let typed_data = serde_json::from_str::<Eip712Raw>(JSON).expect("alas error!");
+        let hash = hash_typed_data_raw(typed_data).expect("alas error!");
+        assert_eq!(
+            format!("{:02x}", hash),
+            "64ea70f4a00f76ed959203e7a75d36f743c5674fb263d5d71b69517501af8ef8",
+        );
+    }
+
+    #[test]
+    fn test_encode_out_of_range() {
+        encode_uint(&Json::from(256), 8, None).unwrap_err();
+        encode_uint(&Json::from("-1"), 256, None).unwrap_err();
+        encode_int(&Json::from(-129), 8, None).unwrap_err();
+        encode_int(&Json::from(128), 8, None).unwrap_err();
+        encode_int(&Json::from(-128), 8, None).unwrap();
+        encode_fixed_bytes(&Json::from("0xa9059c"), 4, None).unwrap_err();
+    }
+
+    #[test]
+    fn test_property_type_from_str() {
+        let actual = PropertyType::from_str("Person[][3]").unwrap();
+        let expected = PropertyType::FixedArray(
+            Box::new(PropertyType::Array(Box::new(PropertyType::Custom("Person".into())))),
+            3,
+        );
+        assert_eq!(actual, expected);
+        assert_eq!(actual.to_string(), "Person[][3]");
+
+        assert_eq!(PropertyType::from_str("uint8").unwrap(), PropertyType::Uint(8));
+        assert_eq!(PropertyType::from_str("int256").unwrap(), PropertyType::Int(256));
+        assert_eq!(PropertyType::from_str("bytes4").unwrap(), PropertyType::FixedBytes(4));
+        PropertyType::from_str("bytes33").unwrap_err();
+    }
 }
@@ -12,7 +12,7 @@ use db_common::sqlite::run_optimization_pragmas;
 use db_common::sqlite::rusqlite::{Result as SqlResult, NO_PARAMS};
 use mm2_core::mm_ctx::MmArc;
 
-use my_swaps::fill_my_swaps_from_json_statements;
+use my_swaps::{fill_my_swaps_from_json_statements, update_my_swaps_status_from_json_statements};
 use stats_swaps::create_and_fill_stats_swaps_from_json_statements;
 
 const SELECT_MIGRATION: &str = "SELECT * FROM migration ORDER BY current_migration DESC LIMIT 1;";
@@ -101,6 +101,12 @@ fn migration_8() -> Vec<(&'static str, Vec<String>)> {
     db_common::sqlite::execute_batch(stats_swaps::ADD_MAKER_TAKER_PUBKEYS)
 }
 
+async fn migration_9(ctx: &MmArc) -> Vec<(&'static str, Vec<String>)> {
+    let mut statements = db_common::sqlite::execute_batch(my_swaps::ADD_STATUS_COLUMNS);
+    statements.extend(update_my_swaps_status_from_json_statements(ctx).await);
+    statements
+}
+
 async fn statements_for_migration(ctx: &MmArc, current_migration: i64) -> Option<Vec<(&'static str, Vec<String>)>> {
     match current_migration {
         1 => Some(migration_1(ctx).await),
@@ -111,6 +117,7 @@ async fn statements_for_migration(ctx: &MmArc, current_migration: i64) -> Option
         6 => Some(migration_6()),
         7 => Some(migration_7()),
         8 => Some(migration_8()),
+        9 => Some(migration_9(ctx).await),
         _ => None,
     }
 }
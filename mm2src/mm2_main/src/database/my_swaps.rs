@@ -1,5 +1,5 @@
 /// This module contains code to work with my_swaps table in MM2 SQLite DB
-use crate::mm2::lp_swap::{MyRecentSwapsUuids, MySwapsFilter, SavedSwap, SavedSwapIo};
+use crate::mm2::lp_swap::{MyRecentSwapsUuids, MySwapsFilter, SavedSwap, SavedSwapIo, SavedSwapStatus};
 use common::log::debug;
 use common::PagingOptions;
 use db_common::sqlite::offset_by_uuid;
@@ -27,6 +27,19 @@ macro_rules! CREATE_MY_SWAPS_TABLE {
 }
 const INSERT_MY_SWAP: &str = "INSERT INTO my_swaps (my_coin, other_coin, uuid, started_at) VALUES (?1, ?2, ?3, ?4)";
 
+/// Adds the columns that are updated on every swap event to filter the swaps by their progress.
+/// `other_pubkey` is the persistent pubkey of the counterparty without the prefix byte.
+pub const ADD_STATUS_COLUMNS: &[&str] = &[
+    "ALTER TABLE my_swaps ADD COLUMN status VARCHAR(255) NOT NULL DEFAULT 'in_progress';",
+    "ALTER TABLE my_swaps ADD COLUMN recoverable INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE my_swaps ADD COLUMN other_pubkey VARCHAR(255);",
+    "CREATE INDEX IF NOT EXISTS my_swaps_status_started_at ON my_swaps (status, started_at);",
+    "CREATE INDEX IF NOT EXISTS my_swaps_other_pubkey_started_at ON my_swaps (other_pubkey, started_at);",
+];
+
+const UPDATE_MY_SWAP_STATUS: &str =
+    "UPDATE my_swaps SET status = ?1, recoverable = ?2, other_pubkey = ?3 WHERE uuid = ?4";
+
 pub fn insert_new_swap(ctx: &MmArc, my_coin: &str, other_coin: &str, uuid: &str, started_at: &str) -> SqlResult<()> {
     debug!("Inserting new swap {} to the SQLite database", uuid);
     let conn = ctx.sqlite_connection();
@@ -55,6 +68,34 @@ fn insert_saved_swap_sql(swap: SavedSwap) -> Option<(&'static str, Vec<String>)>
     Some((INSERT_MY_SWAP, params))
 }
 
+pub fn update_swap_status(ctx: &MmArc, swap: &SavedSwap) -> SqlResult<()> {
+    let (statement, params) = match update_swap_status_sql(swap) {
+        Some(statement) => statement,
+        None => return Ok(()),
+    };
+    debug!("Updating the status of swap {} in the SQLite database", swap.uuid());
+    let conn = ctx.sqlite_connection();
+    conn.execute(statement, params).map(|_| ())
+}
+
+/// Returns SQL statements to fill the status columns of my_swaps table using existing DB with JSON files
+pub async fn update_my_swaps_status_from_json_statements(ctx: &MmArc) -> Vec<(&'static str, Vec<String>)> {
+    let swaps = SavedSwap::load_all_my_swaps_from_db(ctx).await.unwrap_or_default();
+    swaps.iter().filter_map(update_swap_status_sql).collect()
+}
+
+fn update_swap_status_sql(swap: &SavedSwap) -> Option<(&'static str, Vec<String>)> {
+    // The swap that did not even start is not indexed.
+    let other_pubkey = swap.other_pubkey()?;
+    let params = vec![
+        swap.status().as_str().to_owned(),
+        (swap.is_recoverable() as u8).to_string(),
+        hex::encode(other_pubkey.0),
+        swap.uuid().to_string(),
+    ];
+    Some((UPDATE_MY_SWAP_STATUS, params))
+}
+
 #[derive(Debug)]
 pub enum SelectRecentSwapsUuidsErr {
     Sql(SqlError),
@@ -94,6 +135,24 @@ fn apply_my_swaps_filter(builder: &mut SqlBuilder, params: &mut Vec<(&str, Strin
         builder.and_where("started_at < :to_timestamp");
         params.push((":to_timestamp", to_timestamp.to_string()));
     }
+
+    if let Some(uuids) = &filter.uuids {
+        builder.and_where_in_quoted("uuid", uuids);
+    }
+
+    if let Some(statuses) = &filter.statuses {
+        let statuses: Vec<_> = statuses.iter().map(SavedSwapStatus::as_str).collect();
+        builder.and_where_in_quoted("status", &statuses);
+    }
+
+    if let Some(other_pubkey) = &filter.other_pubkey {
+        builder.and_where("other_pubkey = :other_pubkey");
+        params.push((":other_pubkey", hex::encode(other_pubkey.0)));
+    }
+
+    if let Some(recoverable) = filter.recoverable {
+        builder.and_where_eq("recoverable", recoverable as u8);
+    }
 }
 
 pub fn select_uuids_by_my_swaps_filter(
@@ -152,3 +211,136 @@ pub fn select_uuids_by_my_swaps_filter(
         skipped,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rpc::v1::types::H256 as H256Json;
+    use uuid::Uuid;
+
+    const STARTED_AT: u64 = 1673517077;
+
+    fn init_my_swaps_table(conn: &Connection) {
+        conn.execute_batch(CREATE_MY_SWAPS_TABLE!()).unwrap();
+        for statement in ADD_STATUS_COLUMNS {
+            conn.execute_batch(statement).unwrap();
+        }
+    }
+
+    fn insert_swap(
+        conn: &Connection,
+        uuid: Uuid,
+        started_at: u64,
+        status: SavedSwapStatus,
+        recoverable: bool,
+        other_pubkey: [u8; 32],
+    ) {
+        let started_at = started_at.to_string();
+        let uuid = uuid.to_string();
+        conn.execute(INSERT_MY_SWAP, &["RICK", "MORTY", uuid.as_str(), started_at.as_str()])
+            .unwrap();
+        let params = vec![
+            status.as_str().to_owned(),
+            (recoverable as u8).to_string(),
+            hex::encode(other_pubkey),
+            uuid,
+        ];
+        conn.execute(UPDATE_MY_SWAP_STATUS, params).unwrap();
+    }
+
+    fn select_uuids(conn: &Connection, filter: &MySwapsFilter) -> Vec<Uuid> {
+        let mut uuids = select_uuids_by_my_swaps_filter(conn, filter, None).unwrap().uuids;
+        uuids.sort();
+        uuids
+    }
+
+    #[test]
+    fn test_migration_9_fills_status_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CREATE_MY_SWAPS_TABLE!()).unwrap();
+
+        let swap: SavedSwap =
+            serde_json::from_str(include_str!("../for_tests/iris_nimda_rick_taker_swap.json")).unwrap();
+        let (statement, params) = insert_saved_swap_sql(swap.clone()).unwrap();
+        conn.execute(statement, params).unwrap();
+
+        for statement in ADD_STATUS_COLUMNS {
+            conn.execute_batch(statement).unwrap();
+        }
+        let select_status = "SELECT status, recoverable, other_pubkey FROM my_swaps WHERE uuid = ?1";
+        let uuid = swap.uuid().to_string();
+        let (status, recoverable, other_pubkey): (String, u8, Option<String>) = conn
+            .query_row(select_status, &[uuid.as_str()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!(status, "in_progress");
+        assert_eq!(recoverable, 0);
+        assert_eq!(other_pubkey, None);
+
+        let (statement, params) = update_swap_status_sql(&swap).unwrap();
+        conn.execute(statement, params).unwrap();
+        let (status, recoverable, other_pubkey): (String, u8, Option<String>) = conn
+            .query_row(select_status, &[uuid.as_str()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!(status, "finished");
+        assert_eq!(recoverable, 0);
+        assert_eq!(
+            other_pubkey.as_deref(),
+            Some("238527725c96affccb7c55f3900b345b80ec5268d483284f162807775d32b32b")
+        );
+    }
+
+    #[test]
+    fn test_select_uuids_by_status_filters() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_my_swaps_table(&conn);
+
+        let uuids: Vec<_> = (1..=4).map(|i| Uuid::from_bytes([i; 16])).collect();
+        insert_swap(&conn, uuids[0], STARTED_AT, SavedSwapStatus::InProgress, false, [1; 32]);
+        insert_swap(&conn, uuids[1], STARTED_AT + 1, SavedSwapStatus::Failed, true, [1; 32]);
+        insert_swap(&conn, uuids[2], STARTED_AT + 2, SavedSwapStatus::Failed, false, [2; 32]);
+        insert_swap(
+            &conn,
+            uuids[3],
+            STARTED_AT + 3,
+            SavedSwapStatus::Finished,
+            false,
+            [2; 32],
+        );
+
+        let filter = MySwapsFilter {
+            statuses: Some(vec![SavedSwapStatus::InProgress, SavedSwapStatus::Finished]),
+            ..MySwapsFilter::default()
+        };
+        assert_eq!(select_uuids(&conn, &filter), vec![uuids[0], uuids[3]]);
+
+        let filter = MySwapsFilter {
+            other_pubkey: Some(H256Json([2; 32])),
+            ..MySwapsFilter::default()
+        };
+        assert_eq!(select_uuids(&conn, &filter), vec![uuids[2], uuids[3]]);
+
+        let filter = MySwapsFilter {
+            recoverable: Some(true),
+            ..MySwapsFilter::default()
+        };
+        assert_eq!(select_uuids(&conn, &filter), vec![uuids[1]]);
+
+        let filter = MySwapsFilter {
+            statuses: Some(vec![SavedSwapStatus::Failed]),
+            recoverable: Some(false),
+            ..MySwapsFilter::default()
+        };
+        assert_eq!(select_uuids(&conn, &filter), vec![uuids[2]]);
+
+        let filter = MySwapsFilter {
+            uuids: Some(vec![uuids[1], uuids[3]]),
+            other_pubkey: Some(H256Json([1; 32])),
+            ..MySwapsFilter::default()
+        };
+        assert_eq!(select_uuids(&conn, &filter), vec![uuids[1]]);
+    }
+}
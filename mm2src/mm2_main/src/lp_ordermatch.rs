@@ -69,7 +69,7 @@ use crate::mm2::lp_swap::{calc_max_maker_vol, check_balance_for_maker_swap, chec
                           is_pubkey_banned, lp_atomic_locktime, p2p_keypair_and_peer_id_to_broadcast,
                           p2p_private_and_peer_id_to_broadcast, run_maker_swap, run_taker_swap, AtomicLocktimeVersion,
                           CheckBalanceError, CheckBalanceResult, CoinVolumeInfo, MakerSwap, RunMakerSwapInput,
-                          RunTakerSwapInput, SwapConfirmationsSettings, TakerSwap};
+                          RunTakerSwapInput, SwapConfirmationsSettings, SwapPolicy, TakerSwap};
 
 pub use best_orders::{best_orders_rpc, best_orders_rpc_v2};
 use my_orders_storage::{delete_my_maker_order, delete_my_taker_order, save_maker_order_on_update,
@@ -2754,6 +2754,8 @@ struct OrdermatchContext {
     /// Pending MakerReserved messages for a specific TakerOrder UUID
     /// Used to select a trade with the best price upon matching
     pending_maker_reserved: AsyncMutex<HashMap<Uuid, Vec<MakerReserved>>>,
+    /// The maker swap policy rules configured by `swap_policy` in MM2.json.
+    swap_policy: SwapPolicy,
     #[cfg(target_arch = "wasm32")]
     ordermatch_db: ConstructibleDb<OrdermatchDb>,
 }
@@ -2783,6 +2785,17 @@ pub fn init_ordermatch_context(ctx: &MmArc) -> OrdermatchInitResult<()> {
         }
     }
 
+    let swap_policy = if ctx.conf["swap_policy"].is_null() {
+        SwapPolicy::default()
+    } else {
+        json::from_value(ctx.conf["swap_policy"].clone()).map_to_mm(|e| {
+            OrdermatchInitError::ErrorDeserializingConfig {
+                field: "swap_policy".to_owned(),
+                error: e.to_string(),
+            }
+        })?
+    };
+
     let ordermatch_context = OrdermatchContext {
         maker_orders_ctx: PaMutex::new(MakerOrdersContext::new(ctx)?),
         my_taker_orders: Default::default(),
@@ -2790,6 +2803,7 @@ pub fn init_ordermatch_context(ctx: &MmArc) -> OrdermatchInitResult<()> {
         pending_maker_reserved: Default::default(),
         orderbook_tickers,
         original_tickers,
+        swap_policy,
         #[cfg(target_arch = "wasm32")]
         ordermatch_db: ConstructibleDb::new(ctx),
     };
@@ -2820,6 +2834,7 @@ impl OrdermatchContext {
                 pending_maker_reserved: Default::default(),
                 orderbook_tickers: Default::default(),
                 original_tickers: Default::default(),
+                swap_policy: Default::default(),
                 #[cfg(target_arch = "wasm32")]
                 ordermatch_db: ConstructibleDb::new(ctx),
             })
@@ -2943,6 +2958,13 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
                 return;
             },
         };
+        if let Some(coin) = [&maker_coin, &taker_coin].iter().find(|coin| coin.is_watch_only()) {
+            error!(
+                "Coin {} is activated in watch-only mode, can't start the swap",
+                coin.ticker()
+            );
+            return;
+        }
         let alice = bits256::from(maker_match.request.sender_pubkey.0);
         let maker_amount = maker_match.reserved.get_base_amount().to_decimal();
         let taker_amount = maker_match.reserved.get_rel_amount().to_decimal();
@@ -2952,8 +2974,17 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
         let raw_priv = crypto_ctx.mm2_internal_privkey_secret();
         let my_persistent_pub = compressed_pub_key_from_priv_raw(raw_priv.as_slice(), ChecksumType::DSHA256).unwrap();
 
+        let ordermatch_ctx =
+            OrdermatchContext::from_ctx(&ctx).expect("'OrdermatchContext' must be initialized already");
+        let maker_conf_settings = maker_order_conf_settings(
+            &ordermatch_ctx.swap_policy,
+            &maker_order,
+            &maker_coin,
+            &taker_coin,
+            maker_match.reserved.get_base_amount(),
+        );
         let my_conf_settings = choose_maker_confs_and_notas(
-            maker_order.conf_settings,
+            Some(maker_conf_settings),
             &maker_match.request,
             &maker_coin,
             &taker_coin,
@@ -2979,6 +3010,12 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
             maker_order.rel_orderbook_ticker(),
             atomic_locktime_v,
         );
+        let policy_lock_duration = ordermatch_ctx.swap_policy.lock_duration(
+            &maker_order.base,
+            &maker_order.rel,
+            maker_match.reserved.get_base_amount(),
+            lock_time,
+        );
         log_tag!(
             ctx,
             "";
@@ -3016,6 +3053,10 @@ fn lp_connect_start_bob(ctx: MmArc, maker_match: MakerMatch, maker_order: MakerO
             maker_order.p2p_privkey.map(SerializableSecp256k1Keypair::into_inner),
             secret,
         );
+        let maker_swap = match policy_lock_duration {
+            Some(lock_duration) => maker_swap.with_policy_lock_duration(lock_duration),
+            None => maker_swap,
+        };
         run_maker_swap(RunMakerSwapInput::StartNew(maker_swap), ctx).await;
     };
 
@@ -3055,6 +3096,13 @@ fn lp_connected_alice(ctx: MmArc, taker_order: TakerOrder, taker_match: TakerMat
                 return;
             },
         };
+        if let Some(coin) = [&maker_coin, &taker_coin].iter().find(|coin| coin.is_watch_only()) {
+            error!(
+                "Coin {} is activated in watch-only mode, can't start the swap",
+                coin.ticker()
+            );
+            return;
+        }
 
         // lp_connected_alice is called only from process_maker_connected, which returns if CryptoCtx is not initialized
         let crypto_ctx = CryptoCtx::from_ctx(&ctx).expect("'CryptoCtx' must be initialized already");
@@ -3563,21 +3611,26 @@ async fn process_taker_request(ctx: MmArc, from_pubkey: H256Json, taker_request:
                 _ => return, // attempt to match with deactivated coin
             };
 
+            let maker_conf_settings =
+                maker_order_conf_settings(&ordermatch_ctx.swap_policy, &order, &base_coin, &rel_coin, &base_amount);
             let my_conf_settings =
-                choose_maker_confs_and_notas(order.conf_settings, &taker_request, &base_coin, &rel_coin);
+                choose_maker_confs_and_notas(Some(maker_conf_settings), &taker_request, &base_coin, &rel_coin);
             let other_conf_settings =
-                choose_taker_confs_and_notas(&taker_request, &order.conf_settings, &base_coin, &rel_coin);
+                choose_taker_confs_and_notas(&taker_request, &Some(maker_conf_settings), &base_coin, &rel_coin);
             let atomic_locktime_v = AtomicLocktimeVersion::V2 {
                 my_conf_settings,
                 other_conf_settings,
             };
-            let maker_lock_duration = (lp_atomic_locktime(
+            let lock_time = lp_atomic_locktime(
                 order.base_orderbook_ticker(),
                 order.rel_orderbook_ticker(),
                 atomic_locktime_v,
-            ) as f64
-                * rel_coin.maker_locktime_multiplier())
-            .ceil() as u64;
+            );
+            let lock_time = ordermatch_ctx
+                .swap_policy
+                .lock_duration(&order.base, &order.rel, &base_amount, lock_time)
+                .unwrap_or(lock_time);
+            let maker_lock_duration = (lock_time as f64 * rel_coin.maker_locktime_multiplier()).ceil() as u64;
 
             if !order.matches.contains_key(&taker_request.uuid)
                 && base_coin.is_coin_protocol_supported(
@@ -3602,14 +3655,7 @@ async fn process_taker_request(ctx: MmArc, from_pubkey: H256Json, taker_request:
                     rel: order.rel_orderbook_ticker().to_owned(),
                     taker_order_uuid: taker_request.uuid,
                     maker_order_uuid: *uuid,
-                    conf_settings: order.conf_settings.or_else(|| {
-                        Some(OrderConfirmationsSettings {
-                            base_confs: base_coin.required_confirmations(),
-                            base_nota: base_coin.requires_notarization(),
-                            rel_confs: rel_coin.required_confirmations(),
-                            rel_nota: rel_coin.requires_notarization(),
-                        })
-                    }),
+                    conf_settings: Some(maker_conf_settings),
                     base_protocol_info: Some(base_coin.coin_protocol_info(None)),
                     rel_protocol_info: Some(rel_coin.coin_protocol_info(Some(rel_amount.clone()))),
                 };
@@ -3750,6 +3796,12 @@ pub async fn buy(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
     if rel_coin.wallet_only(&ctx) {
         return ERR!("Rel coin {} is wallet only", input.rel);
     }
+    if base_coin.is_watch_only() {
+        return ERR!("Base coin {} is activated in watch-only mode", input.base);
+    }
+    if rel_coin.is_watch_only() {
+        return ERR!("Rel coin {} is activated in watch-only mode", input.rel);
+    }
     let my_amount = &input.volume * &input.price;
     try_s!(
         check_balance_for_taker_swap(
@@ -3782,6 +3834,12 @@ pub async fn sell(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
     if rel_coin.wallet_only(&ctx) {
         return ERR!("Rel coin {} is wallet only", input.rel);
     }
+    if base_coin.is_watch_only() {
+        return ERR!("Base coin {} is activated in watch-only mode", input.base);
+    }
+    if rel_coin.is_watch_only() {
+        return ERR!("Rel coin {} is activated in watch-only mode", input.rel);
+    }
     try_s!(
         check_balance_for_taker_swap(
             &ctx,
@@ -4603,6 +4661,12 @@ pub async fn create_maker_order(ctx: &MmArc, req: SetPriceReq) -> Result<MakerOr
     if rel_coin.wallet_only(ctx) {
         return ERR!("Rel coin {} is wallet only", req.rel);
     }
+    if base_coin.is_watch_only() {
+        return ERR!("Base coin {} is activated in watch-only mode", req.base);
+    }
+    if rel_coin.is_watch_only() {
+        return ERR!("Rel coin {} is activated in watch-only mode", req.rel);
+    }
 
     let (volume, balance) = if req.max {
         let CoinVolumeInfo { volume, balance, .. } = try_s!(
@@ -5653,6 +5717,24 @@ pub struct RpcOrderbookEntryV2 {
     conf_settings: Option<OrderConfirmationsSettings>,
 }
 
+/// Returns the maker order confirmations settings (or the coins' defaults if they are not set)
+/// raised up to the requirements of the maker [`SwapPolicy`] for the given base volume.
+fn maker_order_conf_settings(
+    swap_policy: &SwapPolicy,
+    order: &MakerOrder,
+    base_coin: &MmCoinEnum,
+    rel_coin: &MmCoinEnum,
+    base_volume: &MmNumber,
+) -> OrderConfirmationsSettings {
+    let settings = order.conf_settings.unwrap_or(OrderConfirmationsSettings {
+        base_confs: base_coin.required_confirmations(),
+        base_nota: base_coin.requires_notarization(),
+        rel_confs: rel_coin.required_confirmations(),
+        rel_nota: rel_coin.requires_notarization(),
+    });
+    swap_policy.apply_to_conf_settings(&order.base, &order.rel, base_volume, settings)
+}
+
 fn choose_maker_confs_and_notas(
     maker_confs: Option<OrderConfirmationsSettings>,
     taker_req: &TakerRequest,
@@ -5856,5 +5938,7 @@ fn orderbook_address(
         // Todo: a routing node will know about a payment it routed but not the sender or the receiver. This will require using a new keypair for every order/swap
         // Todo: similar to how it's done for zcoin.
         CoinProtocol::ZHTLC { .. } | CoinProtocol::LIGHTNING { .. } => Ok(OrderbookAddress::Shielded),
+        #[cfg(not(target_arch = "wasm32"))]
+        CoinProtocol::SIMULATION => Ok(OrderbookAddress::Transparent(pubkey.to_owned())),
     }
 }
@@ -92,11 +92,15 @@ use std::sync::atomic::{AtomicU64, Ordering};
 #[path = "lp_swap/check_balance.rs"] mod check_balance;
 #[path = "lp_swap/maker_swap.rs"] mod maker_swap;
 #[path = "lp_swap/max_maker_vol_rpc.rs"] mod max_maker_vol_rpc;
+#[path = "lp_swap/my_swap_statuses.rs"] mod my_swap_statuses;
 #[path = "lp_swap/my_swaps_storage.rs"] mod my_swaps_storage;
 #[path = "lp_swap/pubkey_banning.rs"] mod pubkey_banning;
 #[path = "lp_swap/recreate_swap_data.rs"] mod recreate_swap_data;
 #[path = "lp_swap/saved_swap.rs"] mod saved_swap;
+#[path = "lp_swap/swap_ledger.rs"] mod swap_ledger;
 #[path = "lp_swap/swap_lock.rs"] mod swap_lock;
+#[path = "lp_swap/swap_policy.rs"] mod swap_policy;
+#[path = "lp_swap/swap_timeline.rs"] mod swap_timeline;
 #[path = "lp_swap/swap_watcher.rs"] pub(crate) mod swap_watcher;
 #[path = "lp_swap/taker_swap.rs"] mod taker_swap;
 #[path = "lp_swap/trade_preimage.rs"] mod trade_preimage;
@@ -113,11 +117,15 @@ pub use maker_swap::{calc_max_maker_vol, check_balance_for_maker_swap, get_max_m
                      run_maker_swap, CoinVolumeInfo, MakerSavedEvent, MakerSavedSwap, MakerSwap,
                      MakerSwapStatusChanged, MakerTradePreimage, RunMakerSwapInput, MAKER_PAYMENT_SENT_LOG};
 pub use max_maker_vol_rpc::max_maker_vol;
+pub use my_swap_statuses::{my_swap_statuses, MySwapStatusesError, MySwapStatusesRequest, MySwapStatusesResponse};
 use my_swaps_storage::{MySwapsOps, MySwapsStorage};
 use pubkey_banning::BanReason;
 pub use pubkey_banning::{ban_pubkey_rpc, is_pubkey_banned, list_banned_pubkeys_rpc, unban_pubkeys_rpc};
 pub use recreate_swap_data::recreate_swap_data;
-pub use saved_swap::{SavedSwap, SavedSwapError, SavedSwapIo, SavedSwapResult};
+pub use saved_swap::{SavedSwap, SavedSwapError, SavedSwapIo, SavedSwapResult, SavedSwapStatus};
+pub use swap_ledger::{export_swap_ledger, SwapLedgerEntry, SwapLedgerError, SwapLedgerRequest, SwapLedgerResponse};
+pub use swap_policy::{SwapPolicy, SwapPolicyRule};
+pub use swap_timeline::{ConfirmationWait, SwapDeadlines, SwapTimeline, SwapTimelineStep};
 pub use swap_watcher::{process_watcher_msg, watcher_topic, TakerSwapWatcherData, MAKER_PAYMENT_SPEND_FOUND_LOG,
                        MAKER_PAYMENT_SPEND_SENT_LOG, TAKER_PAYMENT_REFUND_SENT_LOG, TAKER_SWAP_ENTRY_TIMEOUT,
                        WATCHER_PREFIX};
@@ -717,6 +725,11 @@ pub struct NegotiationDataV3 {
     taker_coin_swap_contract: Vec<u8>,
     maker_coin_htlc_pub: Vec<u8>,
     taker_coin_htlc_pub: Vec<u8>,
+    /// The lock duration requested by the maker according to its [`SwapPolicy`].
+    /// Is not serialized if the lock duration is derived by [`lp_atomic_locktime`] to keep the compatibility.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lock_duration: Option<u64>,
 }
 
 #[derive(Clone, Debug, Eq, Deserialize, PartialEq, Serialize)]
@@ -744,6 +757,13 @@ impl NegotiationDataMsg {
         }
     }
 
+    pub fn lock_duration(&self) -> Option<u64> {
+        match self {
+            NegotiationDataMsg::V1(_) | NegotiationDataMsg::V2(_) => None,
+            NegotiationDataMsg::V3(v3) => v3.lock_duration,
+        }
+    }
+
     pub fn secret_hash(&self) -> &[u8] {
         match self {
             NegotiationDataMsg::V1(v1) => &v1.secret_hash,
@@ -857,6 +877,14 @@ pub async fn insert_new_swap_to_db(
         .map_err(|e| ERRL!("{}", e))
 }
 
+/// Updates the swap fields that `my_swap_statuses` filters the swaps by.
+pub async fn update_swap_status_in_db(ctx: MmArc, swap: &SavedSwap) -> Result<(), String> {
+    MySwapsStorage::new(ctx)
+        .update_swap_status(swap)
+        .await
+        .map_err(|e| ERRL!("{}", e))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn add_swap_to_db_index(ctx: &MmArc, swap: &SavedSwap) {
     if let Some(conn) = ctx.sqlite_conn_opt() {
@@ -930,6 +958,8 @@ struct MySwapStatusResponse {
     swap: SavedSwap,
     my_info: Option<MySwapInfo>,
     recoverable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeline: Option<SwapTimeline>,
 }
 
 impl From<SavedSwap> for MySwapStatusResponse {
@@ -938,6 +968,7 @@ impl From<SavedSwap> for MySwapStatusResponse {
         MySwapStatusResponse {
             my_info: swap.get_my_info(),
             recoverable: swap.is_recoverable(),
+            timeline: None,
             swap,
         }
     }
@@ -946,13 +977,19 @@ impl From<SavedSwap> for MySwapStatusResponse {
 /// Returns the status of swap performed on `my` node
 pub async fn my_swap_status(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, String> {
     let uuid: Uuid = try_s!(json::from_value(req["params"]["uuid"].clone()));
+    let with_timeline = req["params"]["timeline"].as_bool().unwrap_or(false);
     let status = match SavedSwap::load_my_swap_from_db(&ctx, uuid).await {
         Ok(Some(status)) => status,
         Ok(None) => return Err("swap data is not found".to_owned()),
         Err(e) => return ERR!("{}", e),
     };
 
-    let res_js = json!({ "result": MySwapStatusResponse::from(status) });
+    let timeline = with_timeline.then(|| status.timeline());
+    let response = MySwapStatusResponse {
+        timeline,
+        ..MySwapStatusResponse::from(status)
+    };
+    let res_js = json!({ "result": response });
     let res = try_s!(json::to_vec(&res_js));
     Ok(try_s!(Response::builder().body(res)))
 }
@@ -990,6 +1027,15 @@ struct SwapStatus {
     data: SavedSwap,
 }
 
+/// Aggregates the durations of the finished swap into the metrics histograms.
+async fn record_my_swap_timeline_metrics(ctx: &MmArc, uuid: Uuid) {
+    match SavedSwap::load_my_swap_from_db(ctx, uuid).await {
+        Ok(Some(swap)) => swap.timeline().record_metrics(ctx),
+        Ok(None) => warn!("Swap {} is not found to record the timeline metrics", uuid),
+        Err(e) => warn!("Error {} loading swap {} to record the timeline metrics", e, uuid),
+    }
+}
+
 /// Broadcasts `my` swap status to P2P network
 async fn broadcast_my_swap_status(ctx: &MmArc, uuid: Uuid) -> Result<(), String> {
     let mut status = match try_s!(SavedSwap::load_my_swap_from_db(ctx, uuid).await) {
@@ -1010,12 +1056,34 @@ async fn broadcast_my_swap_status(ctx: &MmArc, uuid: Uuid) -> Result<(), String>
     Ok(())
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct MySwapsFilter {
     pub my_coin: Option<String>,
     pub other_coin: Option<String>,
     pub from_timestamp: Option<u64>,
     pub to_timestamp: Option<u64>,
+    /// Select only the swaps with the given uuids.
+    pub uuids: Option<Vec<Uuid>>,
+    pub statuses: Option<Vec<SavedSwapStatus>>,
+    /// The persistent pubkey of the counterparty.
+    #[serde(default, deserialize_with = "deserialize_other_pubkey")]
+    pub other_pubkey: Option<H256Json>,
+    pub recoverable: Option<bool>,
+}
+
+/// Accepts both the compressed pubkey and the pubkey without the prefix byte that is stored in the swap data.
+fn deserialize_other_pubkey<'de, D>(deserializer: D) -> Result<Option<H256Json>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let pubkey: Option<BytesJson> = serde::Deserialize::deserialize(deserializer)?;
+    pubkey
+        .map(|pubkey| match pubkey.len() {
+            32 => Ok(H256Json::from(pubkey.as_slice())),
+            33 => Ok(H256Json::from(&pubkey[1..])),
+            len => Err(serde::de::Error::invalid_length(len, &"32 or 33 bytes")),
+        })
+        .transpose()
 }
 
 // TODO: Should return the result from SQL like in order history. So it can be clear the exact started_at time
@@ -1080,8 +1148,7 @@ pub async fn latest_swaps_for_pair(
     let filter = MySwapsFilter {
         my_coin: Some(my_coin),
         other_coin: Some(other_coin),
-        from_timestamp: None,
-        to_timestamp: None,
+        ..MySwapsFilter::default()
     };
 
     let paging_options = PagingOptions {
@@ -1222,6 +1289,16 @@ async fn kickstart_thread_handler(ctx: MmArc, swap: SavedSwap, maker_coin_ticker
             },
         };
     };
+    for coin in [&maker_coin, &taker_coin] {
+        if coin.is_watch_only() {
+            error!(
+                "Can't kickstart the swap {} since the coin {} is activated in watch-only mode",
+                swap.uuid(),
+                coin.ticker()
+            );
+            return;
+        }
+    }
     match swap {
         SavedSwap::Maker(saved_swap) => {
             run_maker_swap(
@@ -1294,6 +1371,9 @@ pub async fn import_swaps(ctx: MmArc, req: Json) -> Result<Response<Vec<u8>>, St
                     {
                         error!("Error {} on new swap insertion", e);
                     }
+                    if let Err(e) = update_swap_status_in_db(ctx.clone(), &swap).await {
+                        error!("Error {} on swap status update", e);
+                    }
                 }
                 imported.push(swap.uuid().to_owned());
             },
@@ -1408,6 +1488,34 @@ mod lp_swap_tests {
     use mm2_core::mm_ctx::MmCtxBuilder;
     use mm2_test_helpers::for_tests::{morty_conf, rick_conf, MORTY_ELECTRUM_ADDRS, RICK_ELECTRUM_ADDRS};
 
+    #[test]
+    fn test_deserialize_my_swaps_filter() {
+        let expected_pubkey: H256Json = "15d9c51c657ab1be4ae9d3ab6e76a619d3bccfe830d5363fa168424c0d044732"
+            .parse()
+            .unwrap();
+        let filter: MySwapsFilter = json::from_value(json!({
+            "statuses": ["finished", "in_progress"],
+            "other_pubkey": "0315d9c51c657ab1be4ae9d3ab6e76a619d3bccfe830d5363fa168424c0d044732",
+            "recoverable": false,
+        }))
+        .unwrap();
+        assert_eq!(
+            filter.statuses,
+            Some(vec![SavedSwapStatus::Finished, SavedSwapStatus::InProgress])
+        );
+        assert_eq!(filter.other_pubkey, Some(expected_pubkey));
+        assert_eq!(filter.recoverable, Some(false));
+
+        let filter: MySwapsFilter = json::from_value(json!({
+            "other_pubkey": "15d9c51c657ab1be4ae9d3ab6e76a619d3bccfe830d5363fa168424c0d044732",
+        }))
+        .unwrap();
+        assert_eq!(filter.other_pubkey, Some(expected_pubkey));
+
+        let error = json::from_value::<MySwapsFilter>(json!({ "other_pubkey": "0315d9c5" })).unwrap_err();
+        assert!(error.to_string().contains("invalid length 4"));
+    }
+
     #[test]
     fn test_dex_fee_amount() {
         let dex_fee_threshold = MmNumber::from("0.0001");
@@ -1681,6 +1789,7 @@ mod lp_swap_tests {
             taker_coin_swap_contract: vec![1; 20],
             maker_coin_htlc_pub: vec![1; 33],
             taker_coin_htlc_pub: vec![1; 33],
+            lock_duration: None,
         });
 
         // v3 must be deserialized to v3, backward compatibility is not required
@@ -1689,6 +1798,25 @@ mod lp_swap_tests {
         let deserialized: NegotiationDataMsg = rmp_serde::from_read_ref(serialized.as_slice()).unwrap();
 
         assert_eq!(deserialized, v3);
+
+        // v3 with the lock duration requested by the maker policy
+        let v3 = NegotiationDataMsg::V3(NegotiationDataV3 {
+            started_at: 0,
+            payment_locktime: 0,
+            secret_hash: vec![0; 20],
+            maker_coin_swap_contract: vec![1; 20],
+            taker_coin_swap_contract: vec![1; 20],
+            maker_coin_htlc_pub: vec![1; 33],
+            taker_coin_htlc_pub: vec![1; 33],
+            lock_duration: Some(15600),
+        });
+
+        let serialized = rmp_serde::to_vec(&v3).unwrap();
+
+        let deserialized: NegotiationDataMsg = rmp_serde::from_read_ref(serialized.as_slice()).unwrap();
+
+        assert_eq!(deserialized, v3);
+        assert_eq!(deserialized.lock_duration(), Some(15600));
     }
 
     #[test]
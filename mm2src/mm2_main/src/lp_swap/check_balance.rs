@@ -165,6 +165,16 @@ pub async fn check_base_coin_balance_for_swap(
     }
 }
 
+/// Watch-only coins can't sign the swap transactions, so they must not participate in the swaps.
+pub fn check_coin_is_not_watch_only(coin: &MmCoinEnum) -> CheckBalanceResult<()> {
+    if coin.is_watch_only() {
+        return MmError::err(CheckBalanceError::CoinIsWatchOnly {
+            coin: coin.ticker().to_owned(),
+        });
+    }
+    Ok(())
+}
+
 pub struct TakerFeeAdditionalInfo {
     pub dex_fee: MmNumber,
     pub fee_to_send_dex_fee: TradeFee,
@@ -210,6 +220,8 @@ pub enum CheckBalanceError {
         volume: BigDecimal,
         threshold: BigDecimal,
     },
+    #[display(fmt = "Coin {} is activated in watch-only mode", coin)]
+    CoinIsWatchOnly { coin: String },
     #[display(fmt = "Transport error: {}", _0)]
     Transport(String),
     #[display(fmt = "Internal error: {}", _0)]
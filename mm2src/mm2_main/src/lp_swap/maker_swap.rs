@@ -1,11 +1,12 @@
-use super::check_balance::{check_base_coin_balance_for_swap, check_my_coin_balance_for_swap, CheckBalanceError,
-                           CheckBalanceResult};
+use super::check_balance::{check_base_coin_balance_for_swap, check_coin_is_not_watch_only,
+                           check_my_coin_balance_for_swap, CheckBalanceError, CheckBalanceResult};
 use super::pubkey_banning::ban_pubkey_on_failed_swap;
 use super::swap_lock::{SwapLock, SwapLockOps};
 use super::trade_preimage::{TradePreimageRequest, TradePreimageRpcError, TradePreimageRpcResult};
 use super::{broadcast_my_swap_status, broadcast_p2p_tx_msg, broadcast_swap_message_every,
             check_other_coin_balance_for_swap, detect_secret_hash_algo, dex_fee_amount_from_taker_coin,
-            get_locked_amount, recv_swap_msg, swap_topic, taker_payment_spend_deadline, tx_helper_topic,
+            get_locked_amount, record_my_swap_timeline_metrics, recv_swap_msg, swap_topic,
+            taker_payment_spend_deadline, tx_helper_topic, update_swap_status_in_db,
             wait_for_maker_payment_conf_until, AtomicSwap, LockedAmount, MySwapInfo, NegotiationDataMsg,
             NegotiationDataV2, NegotiationDataV3, RecoveredSwap, RecoveredSwapAction, SavedSwap, SavedSwapIo,
             SavedTradeFee, SecretHashAlgo, SwapConfirmationsSettings, SwapError, SwapMsg, SwapPubkeys, SwapTxDataMsg,
@@ -109,6 +110,9 @@ async fn save_my_maker_swap_event(ctx: &MmArc, swap: &MakerSwap, event: MakerSav
         }
         let new_swap = SavedSwap::Maker(maker_swap);
         try_s!(new_swap.save_to_db(ctx).await);
+        if let Err(e) = update_swap_status_in_db(ctx.clone(), &new_swap).await {
+            error!("Error {} on swap status update", e);
+        }
         Ok(())
     } else {
         ERR!("Expected SavedSwap::Maker, got {:?}", swap)
@@ -222,6 +226,9 @@ pub struct MakerSwap {
     mutable: RwLock<MakerSwapMut>,
     conf_settings: SwapConfirmationsSettings,
     payment_locktime: u64,
+    /// Whether the `payment_locktime` is required by the [`crate::mm2::lp_swap::SwapPolicy`]
+    /// and has to be announced to the taker explicitly.
+    policy_payment_locktime: bool,
     /// Temporary privkey used to sign P2P messages when applicable
     p2p_privkey: Option<KeyPair>,
     secret: H256,
@@ -387,6 +394,7 @@ impl MakerSwap {
             taker_payment_confirmed: AtomicBool::new(false),
             conf_settings,
             payment_locktime,
+            policy_payment_locktime: false,
             p2p_privkey,
             mutable: RwLock::new(MakerSwapMut {
                 data: MakerSwapData::default(),
@@ -409,6 +417,13 @@ impl MakerSwap {
         }
     }
 
+    /// Overrides the payment lock duration by the one required by the maker [`crate::mm2::lp_swap::SwapPolicy`].
+    pub fn with_policy_lock_duration(mut self, lock_duration: u64) -> Self {
+        self.payment_locktime = lock_duration;
+        self.policy_payment_locktime = true;
+        self
+    }
+
     fn get_my_negotiation_data(&self) -> NegotiationDataMsg {
         let r = self.r();
         let secret_hash = self.secret_hash();
@@ -424,7 +439,8 @@ impl MakerSwap {
         let equal = r.data.maker_coin_htlc_pubkey == r.data.taker_coin_htlc_pubkey;
         let same_as_persistent = r.data.maker_coin_htlc_pubkey == Some(r.data.my_persistent_pub);
 
-        if equal && same_as_persistent {
+        // the lock duration required by the policy can be sent in V3 only
+        if equal && same_as_persistent && !self.policy_payment_locktime {
             NegotiationDataMsg::V2(NegotiationDataV2 {
                 started_at: r.data.started_at,
                 payment_locktime: r.data.maker_payment_lock,
@@ -442,6 +458,7 @@ impl MakerSwap {
                 taker_coin_swap_contract,
                 maker_coin_htlc_pub: self.my_maker_coin_htlc_pub().into(),
                 taker_coin_htlc_pub: self.my_taker_coin_htlc_pub().into(),
+                lock_duration: self.policy_payment_locktime.then_some(r.data.lock_duration),
             })
         }
     }
@@ -2127,6 +2144,7 @@ pub async fn run_maker_swap(swap: RunMakerSwapInput, ctx: MmArc) {
                         command = c;
                     },
                     None => {
+                        record_my_swap_timeline_metrics(&ctx, uuid).await;
                         if to_broadcast {
                             if let Err(e) = broadcast_my_swap_status(&ctx, uuid).await {
                                 error!("!broadcast_my_swap_status({}): {}", uuid, e);
@@ -2159,6 +2177,9 @@ pub async fn check_balance_for_maker_swap(
     prepared_params: Option<MakerSwapPreparedParams>,
     stage: FeeApproxStage,
 ) -> CheckBalanceResult<BigDecimal> {
+    check_coin_is_not_watch_only(my_coin)?;
+    check_coin_is_not_watch_only(other_coin)?;
+
     let (maker_payment_trade_fee, taker_payment_spend_trade_fee) = match prepared_params {
         Some(MakerSwapPreparedParams {
             maker_payment_trade_fee,
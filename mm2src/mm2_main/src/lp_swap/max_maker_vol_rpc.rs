@@ -54,6 +54,8 @@ pub enum MaxMakerVolRpcError {
     NoSuchCoin { coin: String },
     #[display(fmt = "Coin {} is wallet only", coin)]
     CoinIsWalletOnly { coin: String },
+    #[display(fmt = "Coin {} is activated in watch-only mode", coin)]
+    CoinIsWatchOnly { coin: String },
     #[display(fmt = "Transport error: {}", _0)]
     Transport(String),
     #[display(fmt = "Internal error: {}", _0)]
@@ -102,6 +104,7 @@ impl From<CheckBalanceError> for MaxMakerVolRpcError {
                 volume,
                 threshold,
             },
+            CheckBalanceError::CoinIsWatchOnly { coin } => MaxMakerVolRpcError::CoinIsWatchOnly { coin },
             CheckBalanceError::Transport(transport) => MaxMakerVolRpcError::Transport(transport),
             CheckBalanceError::InternalError(internal) => MaxMakerVolRpcError::InternalError(internal),
         }
@@ -115,7 +118,8 @@ impl HttpStatusCode for MaxMakerVolRpcError {
             | MaxMakerVolRpcError::NotSufficientBaseCoinBalance { .. }
             | MaxMakerVolRpcError::VolumeTooLow { .. }
             | MaxMakerVolRpcError::NoSuchCoin { .. }
-            | MaxMakerVolRpcError::CoinIsWalletOnly { .. } => StatusCode::BAD_REQUEST,
+            | MaxMakerVolRpcError::CoinIsWalletOnly { .. }
+            | MaxMakerVolRpcError::CoinIsWatchOnly { .. } => StatusCode::BAD_REQUEST,
             MaxMakerVolRpcError::Transport(_) | MaxMakerVolRpcError::InternalError(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             },
@@ -141,6 +145,9 @@ pub async fn max_maker_vol(ctx: MmArc, req: MaxMakerVolRequest) -> MmResult<MaxM
     if coin.wallet_only(&ctx) {
         return MmError::err(MaxMakerVolRpcError::CoinIsWalletOnly { coin: req.coin });
     }
+    if coin.is_watch_only() {
+        return MmError::err(MaxMakerVolRpcError::CoinIsWatchOnly { coin: req.coin });
+    }
     let CoinVolumeInfo {
         volume,
         balance,
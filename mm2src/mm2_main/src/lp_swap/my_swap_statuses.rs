@@ -0,0 +1,102 @@
+use super::my_swaps_storage::{MySwapsError, MySwapsOps, MySwapsStorage};
+use super::{MySwapStatusResponse, MySwapsFilter, SavedSwap, SavedSwapIo};
+use common::log::{error, warn};
+use common::{calc_total_pages, HttpStatusCode, PagingOptions};
+use derive_more::Display;
+use http::StatusCode;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use ser_error_derive::SerializeErrorType;
+use uuid::Uuid;
+
+#[derive(Debug, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum MySwapStatusesError {
+    #[display(fmt = "'from_uuid' not found: {}", _0)]
+    FromUuidNotFound(Uuid),
+    #[display(fmt = "Invalid timestamp range")]
+    InvalidTimestampRange,
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for MySwapStatusesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MySwapStatusesError::FromUuidNotFound(_) | MySwapStatusesError::InvalidTimestampRange => {
+                StatusCode::BAD_REQUEST
+            },
+            MySwapStatusesError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<MySwapsError> for MySwapStatusesError {
+    fn from(e: MySwapsError) -> Self {
+        match e {
+            MySwapsError::FromUuidNotFound(uuid) => MySwapStatusesError::FromUuidNotFound(uuid),
+            MySwapsError::InvalidTimestampRange => MySwapStatusesError::InvalidTimestampRange,
+            other => MySwapStatusesError::InternalError(other.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MySwapStatusesRequest {
+    /// The `from_uuid` returned as `next_from_uuid` in the previous response is used as the page cursor.
+    #[serde(flatten)]
+    paging_options: PagingOptions,
+    #[serde(flatten)]
+    filter: MySwapsFilter,
+    /// Whether to include the timeline of every swap.
+    #[serde(default)]
+    timeline: bool,
+}
+
+#[derive(Serialize)]
+pub struct MySwapStatusesResponse {
+    swaps: Vec<MySwapStatusResponse>,
+    limit: usize,
+    skipped: usize,
+    total: usize,
+    total_pages: usize,
+    /// The cursor of the next page. `None` if this page is the last one.
+    next_from_uuid: Option<Uuid>,
+}
+
+/// Returns the statuses of `my` swaps selected by the list of uuids or by the filter in one call.
+pub async fn my_swap_statuses(
+    ctx: MmArc,
+    req: MySwapStatusesRequest,
+) -> MmResult<MySwapStatusesResponse, MySwapStatusesError> {
+    let db_result = MySwapsStorage::new(ctx.clone())
+        .my_recent_swaps_with_filters(&req.filter, Some(&req.paging_options))
+        .await?;
+
+    let has_next_page = db_result.skipped + db_result.uuids.len() < db_result.total_count;
+    let next_from_uuid = db_result.uuids.last().copied().filter(|_| has_next_page);
+
+    let mut swaps = Vec::with_capacity(db_result.uuids.len());
+    for uuid in db_result.uuids.iter() {
+        match SavedSwap::load_my_swap_from_db(&ctx, *uuid).await {
+            Ok(Some(swap)) => {
+                let timeline = req.timeline.then(|| swap.timeline());
+                swaps.push(MySwapStatusResponse {
+                    timeline,
+                    ..MySwapStatusResponse::from(swap)
+                });
+            },
+            Ok(None) => warn!("No such swap with the uuid '{}'", uuid),
+            Err(e) => error!("Error loading a swap with the uuid '{}': {}", uuid, e),
+        }
+    }
+
+    Ok(MySwapStatusesResponse {
+        swaps,
+        limit: req.paging_options.limit,
+        skipped: db_result.skipped,
+        total: db_result.total_count,
+        total_pages: calc_total_pages(db_result.total_count, req.paging_options.limit),
+        next_from_uuid,
+    })
+}
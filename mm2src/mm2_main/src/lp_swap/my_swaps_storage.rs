@@ -1,4 +1,4 @@
-use super::{MyRecentSwapsUuids, MySwapsFilter};
+use super::{MyRecentSwapsUuids, MySwapsFilter, SavedSwap};
 use async_trait::async_trait;
 use common::PagingOptions;
 use derive_more::Display;
@@ -33,6 +33,9 @@ pub enum MySwapsError {
 pub trait MySwapsOps {
     async fn save_new_swap(&self, my_coin: &str, other_coin: &str, uuid: Uuid, started_at: u64) -> MySwapsResult<()>;
 
+    /// Updates the status, `recoverable` flag and counterparty pubkey the swap can be filtered by.
+    async fn update_swap_status(&self, swap: &SavedSwap) -> MySwapsResult<()>;
+
     async fn my_recent_swaps_with_filters(
         &self,
         filter: &MySwapsFilter,
@@ -51,7 +54,8 @@ impl MySwapsStorage {
 #[cfg(not(target_arch = "wasm32"))]
 mod native_impl {
     use super::*;
-    use crate::mm2::database::my_swaps::{insert_new_swap, select_uuids_by_my_swaps_filter, SelectRecentSwapsUuidsErr};
+    use crate::mm2::database::my_swaps::{insert_new_swap, select_uuids_by_my_swaps_filter, update_swap_status,
+                                         SelectRecentSwapsUuidsErr};
     use db_common::sqlite::rusqlite::Error as SqlError;
 
     impl From<SelectRecentSwapsUuidsErr> for MySwapsError {
@@ -85,6 +89,10 @@ mod native_impl {
             )?)
         }
 
+        async fn update_swap_status(&self, swap: &SavedSwap) -> MySwapsResult<()> {
+            Ok(update_swap_status(&self.ctx, swap)?)
+        }
+
         async fn my_recent_swaps_with_filters(
             &self,
             filter: &MySwapsFilter,
@@ -103,8 +111,9 @@ mod native_impl {
 mod wasm_impl {
     use super::*;
     use crate::mm2::lp_swap::swap_wasm_db::cursor_prelude::*;
-    use crate::mm2::lp_swap::swap_wasm_db::{DbTransactionError, InitDbError, MySwapsFiltersTable};
-    use crate::mm2::lp_swap::SwapsContext;
+    use crate::mm2::lp_swap::swap_wasm_db::{DbTransactionError, InitDbError, ItemId, MySwapsFiltersTable};
+    use crate::mm2::lp_swap::{SavedSwapStatus, SwapsContext};
+    use mm2_db::indexed_db::DbTable;
     use std::collections::BTreeSet;
     use uuid::Uuid;
 
@@ -176,11 +185,30 @@ mod wasm_impl {
                 my_coin: my_coin.to_owned(),
                 other_coin: other_coin.to_owned(),
                 started_at: started_at as u32,
+                status: Some(SavedSwapStatus::InProgress),
+                recoverable: false,
+                other_pubkey: None,
             };
             my_swaps_table.add_item(&item).await?;
             Ok(())
         }
 
+        async fn update_swap_status(&self, swap: &SavedSwap) -> MySwapsResult<()> {
+            let swap_ctx = SwapsContext::from_ctx(&self.ctx).map_to_mm(MySwapsError::InternalError)?;
+            let db = swap_ctx.swap_db().await?;
+            let transaction = db.transaction().await?;
+            let my_swaps_table = transaction.table::<MySwapsFiltersTable>().await?;
+
+            let (item_id, mut item) = match my_swaps_table.get_item_by_unique_index("uuid", *swap.uuid()).await? {
+                Some(item) => item,
+                // The swap that did not even start is not indexed.
+                None => return Ok(()),
+            };
+            item.set_status(swap);
+            my_swaps_table.replace_item(item_id, &item).await?;
+            Ok(())
+        }
+
         async fn my_recent_swaps_with_filters(
             &self,
             filter: &MySwapsFilter,
@@ -197,39 +225,100 @@ mod wasm_impl {
                 return MmError::err(MySwapsError::InvalidTimestampRange);
             }
 
-            let items = match (&filter.my_coin, &filter.other_coin) {
-                (Some(my_coin), Some(other_coin)) => {
-                    my_swaps_table
-                        .cursor_builder()
-                        .only("my_coin", my_coin)?
-                        .only("other_coin", other_coin)?
-                        .bound("started_at", from_timestamp, to_timestamp)
-                        .open_cursor("with_my_other_coins")
-                        .await?
-                        .collect()
-                        .await?
+            let items = match filter.uuids {
+                Some(ref uuids) => {
+                    let mut items = Vec::with_capacity(uuids.len());
+                    for uuid in uuids {
+                        if let Some(item) = my_swaps_table.get_item_by_unique_index("uuid", *uuid).await? {
+                            items.push(item);
+                        }
+                    }
+                    items
+                },
+                None => select_items_by_index(&my_swaps_table, filter, from_timestamp, to_timestamp).await?,
+            };
+
+            let uuids: BTreeSet<OrderedUuid> = items
+                .into_iter()
+                .filter(|(_item_id, item)| is_matched(filter, item, from_timestamp, to_timestamp))
+                .map(|(_item_id, item)| OrderedUuid::from(item))
+                .collect();
+            match paging_options {
+                Some(paging) => take_according_to_paging_opts(uuids, paging),
+                None => {
+                    let total_count = uuids.len();
+                    Ok(MyRecentSwapsUuids {
+                        uuids: uuids.into_iter().map(|ordered| ordered.uuid).collect(),
+                        total_count,
+                        skipped: 0,
+                    })
                 },
-                (Some(my_coin), None) => {
+            }
+        }
+    }
+
+    /// Selects the items by the most suitable index.
+    /// The rest of the filter constraints are checked by [`is_matched`].
+    async fn select_items_by_index(
+        my_swaps_table: &DbTable<'_, MySwapsFiltersTable>,
+        filter: &MySwapsFilter,
+        from_timestamp: u32,
+        to_timestamp: u32,
+    ) -> MySwapsResult<Vec<(ItemId, MySwapsFiltersTable)>> {
+        let items = match (&filter.my_coin, &filter.other_coin) {
+            (Some(my_coin), Some(other_coin)) => {
+                my_swaps_table
+                    .cursor_builder()
+                    .only("my_coin", my_coin)?
+                    .only("other_coin", other_coin)?
+                    .bound("started_at", from_timestamp, to_timestamp)
+                    .open_cursor("with_my_other_coins")
+                    .await?
+                    .collect()
+                    .await?
+            },
+            (Some(my_coin), None) => {
+                my_swaps_table
+                    .cursor_builder()
+                    .only("my_coin", my_coin)?
+                    .bound("started_at", from_timestamp, to_timestamp)
+                    .open_cursor("with_my_coin")
+                    .await?
+                    .collect()
+                    .await?
+            },
+            (None, Some(other_coin)) => {
+                my_swaps_table
+                    .cursor_builder()
+                    .only("other_coin", other_coin)?
+                    .bound("started_at", from_timestamp, to_timestamp)
+                    .open_cursor("with_other_coin")
+                    .await?
+                    .collect()
+                    .await?
+            },
+            (None, None) => match (&filter.other_pubkey, filter.statuses.as_deref()) {
+                (Some(other_pubkey), _) => {
                     my_swaps_table
                         .cursor_builder()
-                        .only("my_coin", my_coin)?
+                        .only("other_pubkey", hex::encode(other_pubkey.0))?
                         .bound("started_at", from_timestamp, to_timestamp)
-                        .open_cursor("with_my_coin")
+                        .open_cursor("with_other_pubkey")
                         .await?
                         .collect()
                         .await?
                 },
-                (None, Some(other_coin)) => {
+                (None, Some([status])) => {
                     my_swaps_table
                         .cursor_builder()
-                        .only("other_coin", other_coin)?
+                        .only("status", status)?
                         .bound("started_at", from_timestamp, to_timestamp)
-                        .open_cursor("with_other_coin")
+                        .open_cursor("with_status")
                         .await?
                         .collect()
                         .await?
                 },
-                (None, None) => {
+                _ => {
                     my_swaps_table
                         .cursor_builder()
                         .bound("started_at", from_timestamp, to_timestamp)
@@ -238,24 +327,38 @@ mod wasm_impl {
                         .collect()
                         .await?
                 },
-            };
+            },
+        };
+        Ok(items)
+    }
 
-            let uuids: BTreeSet<OrderedUuid> = items
-                .into_iter()
-                .map(|(_item_id, item)| OrderedUuid::from(item))
-                .collect();
-            match paging_options {
-                Some(paging) => take_according_to_paging_opts(uuids, paging),
-                None => {
-                    let total_count = uuids.len();
-                    Ok(MyRecentSwapsUuids {
-                        uuids: uuids.into_iter().map(|ordered| ordered.uuid).collect(),
-                        total_count,
-                        skipped: 0,
-                    })
-                },
+    fn is_matched(filter: &MySwapsFilter, item: &MySwapsFiltersTable, from_timestamp: u32, to_timestamp: u32) -> bool {
+        if let Some(ref my_coin) = filter.my_coin {
+            if *my_coin != item.my_coin {
+                return false;
             }
         }
+        if let Some(ref other_coin) = filter.other_coin {
+            if *other_coin != item.other_coin {
+                return false;
+            }
+        }
+        if let Some(ref statuses) = filter.statuses {
+            if !item.status.map_or(false, |status| statuses.contains(&status)) {
+                return false;
+            }
+        }
+        if let Some(ref other_pubkey) = filter.other_pubkey {
+            if item.other_pubkey.as_deref() != Some(hex::encode(other_pubkey.0).as_str()) {
+                return false;
+            }
+        }
+        if let Some(recoverable) = filter.recoverable {
+            if recoverable != item.recoverable {
+                return false;
+            }
+        }
+        from_timestamp <= item.started_at && item.started_at <= to_timestamp
     }
 
     pub(super) fn take_according_to_paging_opts(
@@ -311,6 +414,7 @@ mod wasm_impl {
 mod wasm_tests {
     use super::wasm_impl::*;
     use super::*;
+    use crate::mm2::lp_swap::SavedSwapStatus;
     use common::log::wasm_log::register_wasm_log;
     use common::new_uuid;
     use mm2_core::mm_ctx::MmCtxBuilder;
@@ -466,6 +570,7 @@ mod wasm_tests {
             other_coin: Some("MORTY".to_owned()),
             from_timestamp: Some(2000),
             to_timestamp: Some(3000),
+            ..MySwapsFilter::default()
         };
         test_my_recent_swaps_impl(1000, &COINS, 1000..5000, filters).await;
 
@@ -474,6 +579,7 @@ mod wasm_tests {
             other_coin: None,
             from_timestamp: Some(2000),
             to_timestamp: Some(3000),
+            ..MySwapsFilter::default()
         };
         test_my_recent_swaps_impl(100, &COINS, 1000..5000, filters).await;
 
@@ -482,6 +588,7 @@ mod wasm_tests {
             other_coin: None,
             from_timestamp: Some(2000),
             to_timestamp: None,
+            ..MySwapsFilter::default()
         };
         test_my_recent_swaps_impl(100, &COINS, 1000..5000, filters).await;
 
@@ -490,6 +597,7 @@ mod wasm_tests {
             other_coin: None,
             from_timestamp: None,
             to_timestamp: Some(1000),
+            ..MySwapsFilter::default()
         };
         test_my_recent_swaps_impl(10, &COINS, 1001..3000, filters).await;
 
@@ -498,7 +606,73 @@ mod wasm_tests {
             other_coin: None,
             from_timestamp: Some(3000),
             to_timestamp: None,
+            ..MySwapsFilter::default()
         };
         test_my_recent_swaps_impl(10, &COINS, 1000..2000, filters).await;
     }
+
+    async fn expect_uuids(my_swaps: &MySwapsStorage, filters: MySwapsFilter, expected: Vec<Uuid>) {
+        let actual = my_swaps
+            .my_recent_swaps_with_filters(&filters, None)
+            .await
+            .expect("!MySwapsStorage::my_recent_swaps_with_filters");
+        assert_eq!(actual.uuids, expected);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_my_recent_swaps_by_status() {
+        register_wasm_log();
+
+        let ctx = MmCtxBuilder::new().with_test_db_namespace().into_mm_arc();
+        let my_swaps = MySwapsStorage::new(ctx);
+
+        let finished: SavedSwap =
+            serde_json::from_str(include_str!("../for_tests/iris_nimda_rick_taker_swap.json")).unwrap();
+        let finished_uuid = *finished.uuid();
+        my_swaps
+            .save_new_swap("RICK", "IRIS-NIMDA", finished_uuid, 1673517077)
+            .await
+            .expect("!MySwapsStorage::save_new_swap");
+        my_swaps
+            .update_swap_status(&finished)
+            .await
+            .expect("!MySwapsStorage::update_swap_status");
+
+        let in_progress_uuid = new_uuid();
+        my_swaps
+            .save_new_swap("RICK", "MORTY", in_progress_uuid, 1673517078)
+            .await
+            .expect("!MySwapsStorage::save_new_swap");
+
+        let filters = MySwapsFilter {
+            statuses: Some(vec![SavedSwapStatus::Finished]),
+            ..MySwapsFilter::default()
+        };
+        expect_uuids(&my_swaps, filters, vec![finished_uuid]).await;
+
+        let filters = MySwapsFilter {
+            statuses: Some(vec![SavedSwapStatus::InProgress, SavedSwapStatus::Failed]),
+            ..MySwapsFilter::default()
+        };
+        expect_uuids(&my_swaps, filters, vec![in_progress_uuid]).await;
+
+        let filters = MySwapsFilter {
+            other_pubkey: finished.other_pubkey(),
+            ..MySwapsFilter::default()
+        };
+        expect_uuids(&my_swaps, filters, vec![finished_uuid]).await;
+
+        let filters = MySwapsFilter {
+            recoverable: Some(true),
+            ..MySwapsFilter::default()
+        };
+        expect_uuids(&my_swaps, filters, Vec::new()).await;
+
+        let filters = MySwapsFilter {
+            my_coin: Some("RICK".to_owned()),
+            recoverable: Some(false),
+            ..MySwapsFilter::default()
+        };
+        expect_uuids(&my_swaps, filters, vec![in_progress_uuid, finished_uuid]).await;
+    }
 }
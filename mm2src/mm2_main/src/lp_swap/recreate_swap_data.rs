@@ -335,6 +335,7 @@ async fn recreate_taker_swap(ctx: MmArc, maker_swap: MakerSavedSwap) -> Recreate
         maker_coin_start_block: started_event.maker_coin_start_block,
         taker_coin_start_block: started_event.taker_coin_start_block,
         // Don't set the fee since the value is used when we calculate locked by other swaps amount only.
+        dex_fee: None,
         fee_to_send_taker_fee: None,
         // Don't set the fee since the value is used when we calculate locked by other swaps amount only.
         taker_payment_trade_fee: None,
@@ -363,6 +364,8 @@ async fn recreate_taker_swap(ctx: MmArc, maker_swap: MakerSavedSwap) -> Recreate
         taker_coin_swap_contract_addr: negotiated_event.taker_coin_swap_contract_addr,
         maker_coin_htlc_pubkey: started_event.maker_coin_htlc_pubkey,
         taker_coin_htlc_pubkey: started_event.taker_coin_htlc_pubkey,
+        // the taker lock duration is already taken from the maker `Started` event
+        lock_duration: None,
     });
     taker_swap.events.push(TakerSavedEvent {
         timestamp: negotiated_timestamp,
@@ -1,6 +1,6 @@
 use crate::mm2::lp_swap::maker_swap::{MakerSavedSwap, MakerSwap, MakerSwapEvent};
 use crate::mm2::lp_swap::taker_swap::{TakerSavedSwap, TakerSwap, TakerSwapEvent};
-use crate::mm2::lp_swap::{MySwapInfo, RecoveredSwap};
+use crate::mm2::lp_swap::{MySwapInfo, RecoveredSwap, SwapTimeline};
 use async_trait::async_trait;
 use coins::lp_coinfind;
 use derive_more::Display;
@@ -33,6 +33,27 @@ pub enum SavedSwap {
     Taker(TakerSavedSwap),
 }
 
+/// The progress of the swap, stored in the `my_swaps` index to filter the swaps by.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedSwapStatus {
+    InProgress,
+    /// The swap is finished successfully.
+    Finished,
+    /// The swap is finished with an error.
+    Failed,
+}
+
+impl SavedSwapStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SavedSwapStatus::InProgress => "in_progress",
+            SavedSwapStatus::Finished => "finished",
+            SavedSwapStatus::Failed => "failed",
+        }
+    }
+}
+
 impl From<MakerSavedSwap> for SavedSwap {
     fn from(maker: MakerSavedSwap) -> Self { SavedSwap::Maker(maker) }
 }
@@ -56,6 +77,24 @@ impl SavedSwap {
         }
     }
 
+    pub fn status(&self) -> SavedSwapStatus {
+        if !self.is_finished() {
+            SavedSwapStatus::InProgress
+        } else if self.is_finished_and_success() {
+            SavedSwapStatus::Finished
+        } else {
+            SavedSwapStatus::Failed
+        }
+    }
+
+    /// Returns the persistent pubkey of the counterparty without the prefix byte.
+    pub fn other_pubkey(&self) -> Option<H256Json> {
+        match self {
+            SavedSwap::Maker(swap) => swap.swap_data().ok().map(|data| data.taker),
+            SavedSwap::Taker(swap) => swap.swap_data().ok().map(|data| data.maker),
+        }
+    }
+
     pub fn uuid(&self) -> &Uuid {
         match self {
             SavedSwap::Maker(swap) => &swap.uuid,
@@ -98,6 +137,11 @@ impl SavedSwap {
             Ok(None) => return ERR!("Coin {} is not activated", taker_ticker),
             Err(e) => return ERR!("Error {} on {} coin find attempt", e, taker_ticker),
         };
+        for coin in [&maker_coin, &taker_coin] {
+            if coin.is_watch_only() {
+                return ERR!("Coin {} is activated in watch-only mode", coin.ticker());
+            }
+        }
         match self {
             SavedSwap::Maker(saved) => {
                 let (maker_swap, _) = try_s!(MakerSwap::load_from_saved(ctx, maker_coin, taker_coin, saved));
@@ -117,6 +161,13 @@ impl SavedSwap {
         }
     }
 
+    pub fn timeline(&self) -> SwapTimeline {
+        match self {
+            SavedSwap::Maker(swap) => SwapTimeline::from_maker_swap(swap),
+            SavedSwap::Taker(swap) => SwapTimeline::from_taker_swap(swap),
+        }
+    }
+
     pub fn hide_secrets(&mut self) {
         match self {
             SavedSwap::Maker(swap) => {
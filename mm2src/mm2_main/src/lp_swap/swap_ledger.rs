@@ -0,0 +1,492 @@
+//! Exports the finished swaps as an accounting ledger, e.g. for tax reporting.
+//! The withdrawals and deposits found in the transaction history of the requested coins
+//! can be merged into the same ledger.
+
+use super::maker_swap::{MakerSavedSwap, MakerSwapEvent};
+use super::my_swaps_storage::{MySwapsError, MySwapsOps, MySwapsStorage};
+use super::taker_swap::{TakerSavedSwap, TakerSwapEvent};
+use super::{dex_fee_amount, dex_fee_amount_from_taker_coin, dex_fee_threshold, MySwapsFilter, SavedSwap, SavedSwapIo,
+            SavedSwapStatus, SavedTradeFee, TransactionIdentifier};
+use coins::my_tx_history_v2::{load_all_tx_history_v2, MyTxHistoryErrorV2, MyTxHistoryTarget};
+use coins::{lp_coinfind, lp_coinfind_or_err, CoinFindError, TransactionDetails};
+use common::log::{error, warn};
+use common::HttpStatusCode;
+use derive_more::Display;
+use futures::compat::Future01CompatExt;
+use http::StatusCode;
+use mm2_core::mm_ctx::MmArc;
+use mm2_err_handle::prelude::*;
+use mm2_number::{BigDecimal, MmNumber};
+use ser_error_derive::SerializeErrorType;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const CSV_HEADER: &str = "type,timestamp,uuid,sent_coin,sent_amount,sent_coin_usd_price,received_coin,received_amount,\
+                          received_coin_usd_price,dex_fee_coin,dex_fee_amount,tx_fees,tx_ids";
+
+#[derive(Debug, Display, Serialize, SerializeErrorType)]
+#[serde(tag = "error_type", content = "error_data")]
+pub enum SwapLedgerError {
+    #[display(fmt = "No such coin {}", coin)]
+    NoSuchCoin { coin: String },
+    #[display(fmt = "Invalid timestamp range")]
+    InvalidTimestampRange,
+    #[display(fmt = "Error loading tx history of {}: {}", coin, error)]
+    TxHistoryError { coin: String, error: String },
+    #[display(fmt = "Internal error: {}", _0)]
+    InternalError(String),
+}
+
+impl HttpStatusCode for SwapLedgerError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SwapLedgerError::NoSuchCoin { .. } | SwapLedgerError::InvalidTimestampRange => StatusCode::BAD_REQUEST,
+            SwapLedgerError::TxHistoryError { .. } | SwapLedgerError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        }
+    }
+}
+
+impl From<MySwapsError> for SwapLedgerError {
+    fn from(e: MySwapsError) -> Self {
+        match e {
+            MySwapsError::InvalidTimestampRange => SwapLedgerError::InvalidTimestampRange,
+            other => SwapLedgerError::InternalError(other.to_string()),
+        }
+    }
+}
+
+impl From<CoinFindError> for SwapLedgerError {
+    fn from(e: CoinFindError) -> Self {
+        match e {
+            CoinFindError::NoSuchCoin { coin } => SwapLedgerError::NoSuchCoin { coin },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapLedgerFormat {
+    Json,
+    Csv,
+}
+
+impl Default for SwapLedgerFormat {
+    fn default() -> Self { SwapLedgerFormat::Json }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SwapLedgerRequest {
+    /// The entries timestamped at or after this timestamp (in seconds) are exported.
+    /// The swap entries are timestamped by the swap finish time.
+    from_timestamp: Option<u64>,
+    /// The entries timestamped before this timestamp (in seconds) are exported.
+    to_timestamp: Option<u64>,
+    #[serde(default)]
+    format: SwapLedgerFormat,
+    /// The coins whose withdrawals and deposits are merged into the ledger.
+    /// The coins have to be enabled.
+    #[serde(default)]
+    tx_history_coins: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapLedgerEntryType {
+    Swap,
+    Withdrawal,
+    Deposit,
+}
+
+impl SwapLedgerEntryType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SwapLedgerEntryType::Swap => "swap",
+            SwapLedgerEntryType::Withdrawal => "withdrawal",
+            SwapLedgerEntryType::Deposit => "deposit",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SwapLedgerFee {
+    pub coin: String,
+    pub amount: BigDecimal,
+}
+
+impl From<&SavedTradeFee> for SwapLedgerFee {
+    fn from(fee: &SavedTradeFee) -> Self {
+        SwapLedgerFee {
+            coin: fee.coin.clone(),
+            amount: fee.amount.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SwapLedgerEntry {
+    #[serde(rename = "type")]
+    pub entry_type: SwapLedgerEntryType,
+    /// The time the swap is finished at or the transaction timestamp, in seconds.
+    pub timestamp: u64,
+    pub uuid: Option<Uuid>,
+    pub sent_coin: Option<String>,
+    pub sent_amount: Option<BigDecimal>,
+    /// The USD price of the sent coin captured at the end of the swap.
+    pub sent_coin_usd_price: Option<BigDecimal>,
+    pub received_coin: Option<String>,
+    pub received_amount: Option<BigDecimal>,
+    pub received_coin_usd_price: Option<BigDecimal>,
+    pub dex_fee: Option<SwapLedgerFee>,
+    /// The transaction fees. The swap transaction fees are the values saved at the start of the swap.
+    pub tx_fees: Vec<SwapLedgerFee>,
+    pub tx_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum SwapLedgerResponse {
+    Json { entries: Vec<SwapLedgerEntry> },
+    Csv { csv: String },
+}
+
+fn tx_id(tx: &TransactionIdentifier) -> String { hex::encode(&tx.tx_hash.0) }
+
+fn maker_swap_entry(swap: &MakerSavedSwap) -> Option<SwapLedgerEntry> {
+    let data = swap.swap_data().ok()?;
+    let tx_ids = swap
+        .events
+        .iter()
+        .filter_map(|event| match &event.event {
+            MakerSwapEvent::MakerPaymentSent(tx)
+            | MakerSwapEvent::TakerPaymentReceived(tx)
+            | MakerSwapEvent::TakerPaymentSpent(tx) => Some(tx_id(tx)),
+            _ => None,
+        })
+        .collect();
+    let tx_fees = [&data.maker_payment_trade_fee, &data.taker_payment_spend_trade_fee]
+        .iter()
+        .filter_map(|fee| fee.as_ref().map(SwapLedgerFee::from))
+        .collect();
+
+    Some(SwapLedgerEntry {
+        entry_type: SwapLedgerEntryType::Swap,
+        timestamp: swap.finished_at().unwrap_or(data.started_at),
+        uuid: Some(swap.uuid),
+        sent_coin: Some(data.maker_coin.clone()),
+        sent_amount: Some(data.maker_amount.clone()),
+        sent_coin_usd_price: swap.maker_coin_usd_price.clone(),
+        received_coin: Some(data.taker_coin.clone()),
+        received_amount: Some(data.taker_amount.clone()),
+        received_coin_usd_price: swap.taker_coin_usd_price.clone(),
+        dex_fee: None,
+        tx_fees,
+        tx_ids,
+    })
+}
+
+async fn taker_swap_entry(ctx: &MmArc, swap: &TakerSavedSwap) -> Option<SwapLedgerEntry> {
+    let data = swap.swap_data().ok()?;
+    let tx_ids = swap
+        .events
+        .iter()
+        .filter_map(|event| match &event.event {
+            TakerSwapEvent::TakerFeeSent(tx)
+            | TakerSwapEvent::MakerPaymentReceived(tx)
+            | TakerSwapEvent::TakerPaymentSent(tx)
+            | TakerSwapEvent::MakerPaymentSpent(tx) => Some(tx_id(tx)),
+            TakerSwapEvent::TakerPaymentSpent(spent) => Some(tx_id(&spent.transaction)),
+            _ => None,
+        })
+        .collect();
+    let tx_fees = [
+        &data.fee_to_send_taker_fee,
+        &data.taker_payment_trade_fee,
+        &data.maker_payment_spend_trade_fee,
+    ]
+    .iter()
+    .filter_map(|fee| fee.as_ref().map(SwapLedgerFee::from))
+    .collect();
+
+    // The swaps started before the dex fee was saved in the `Started` event have it recomputed.
+    // The dex fee threshold depends on the minimal transaction amount of the taker coin.
+    // Use the default threshold if the coin is not enabled.
+    let dex_fee = match &data.dex_fee {
+        Some(dex_fee) => dex_fee.clone(),
+        None => {
+            let taker_amount = MmNumber::from(data.taker_amount.clone());
+            let dex_fee = match lp_coinfind(ctx, &data.taker_coin).await {
+                Ok(Some(taker_coin)) => dex_fee_amount_from_taker_coin(&taker_coin, &data.maker_coin, &taker_amount),
+                _ => {
+                    let threshold = dex_fee_threshold(MmNumber::from(0));
+                    dex_fee_amount(&data.taker_coin, &data.maker_coin, &taker_amount, &threshold)
+                },
+            };
+            dex_fee.into()
+        },
+    };
+
+    Some(SwapLedgerEntry {
+        entry_type: SwapLedgerEntryType::Swap,
+        timestamp: swap.finished_at().unwrap_or(data.started_at),
+        uuid: Some(swap.uuid),
+        sent_coin: Some(data.taker_coin.clone()),
+        sent_amount: Some(data.taker_amount.clone()),
+        sent_coin_usd_price: swap.taker_coin_usd_price.clone(),
+        received_coin: Some(data.maker_coin.clone()),
+        received_amount: Some(data.maker_amount.clone()),
+        received_coin_usd_price: swap.maker_coin_usd_price.clone(),
+        dex_fee: Some(SwapLedgerFee {
+            coin: data.taker_coin.clone(),
+            amount: dex_fee,
+        }),
+        tx_fees,
+        tx_ids,
+    })
+}
+
+/// Converts the transaction that changes `my` balance into the withdrawal or deposit entry.
+/// The fee paid with the same coin is excluded from the withdrawn amount.
+fn transfer_entry(tx: &TransactionDetails) -> Option<SwapLedgerEntry> {
+    let zero = BigDecimal::from(0);
+    let balance_change = tx.my_balance_change();
+    if *balance_change == zero {
+        return None;
+    }
+
+    let fee = tx.fee_details().map(|fee_details| {
+        let (fee_coin, amount) = fee_details.total_fee();
+        SwapLedgerFee {
+            coin: fee_coin.unwrap_or_else(|| tx.coin()).to_owned(),
+            amount,
+        }
+    });
+
+    let mut entry = SwapLedgerEntry {
+        entry_type: SwapLedgerEntryType::Deposit,
+        timestamp: tx.timestamp(),
+        uuid: None,
+        sent_coin: None,
+        sent_amount: None,
+        sent_coin_usd_price: None,
+        received_coin: None,
+        received_amount: None,
+        received_coin_usd_price: None,
+        dex_fee: None,
+        tx_fees: fee.clone().into_iter().collect(),
+        tx_ids: vec![tx.tx_hash().to_owned()],
+    };
+
+    if *balance_change > zero {
+        entry.received_coin = Some(tx.coin().to_owned());
+        entry.received_amount = Some(balance_change.clone());
+        return Some(entry);
+    }
+
+    let fee_in_same_coin = fee
+        .filter(|fee| fee.coin == tx.coin())
+        .map(|fee| fee.amount)
+        .unwrap_or_else(|| zero.clone());
+    entry.entry_type = SwapLedgerEntryType::Withdrawal;
+    entry.sent_coin = Some(tx.coin().to_owned());
+    entry.sent_amount = Some(-balance_change.clone() - fee_in_same_coin);
+    Some(entry)
+}
+
+/// Loads the transaction history V2 of the coin if it's supported and initialized,
+/// or the legacy history otherwise.
+async fn load_tx_history(ctx: &MmArc, ticker: &str) -> MmResult<Vec<TransactionDetails>, SwapLedgerError> {
+    let coin = lp_coinfind_or_err(ctx, ticker).await?;
+    let tx_history_error = |error: String| SwapLedgerError::TxHistoryError {
+        coin: ticker.to_owned(),
+        error,
+    };
+
+    match load_all_tx_history_v2(ctx.clone(), ticker, MyTxHistoryTarget::Iguana).await {
+        Ok(history) => return Ok(history),
+        Err(e) => match e.into_inner() {
+            MyTxHistoryErrorV2::NotSupportedFor(_) | MyTxHistoryErrorV2::StorageIsNotInitialized(_) => (),
+            other => return MmError::err(tx_history_error(other.to_string())),
+        },
+    }
+    coin.load_history_from_file(ctx)
+        .compat()
+        .await
+        .map_to_mm(|e| tx_history_error(e.to_string()))
+}
+
+/// Escapes the CSV field according to RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn to_csv(entries: &[SwapLedgerEntry]) -> String {
+    fn opt<T: ToString>(value: &Option<T>) -> String { value.as_ref().map(T::to_string).unwrap_or_default() }
+
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for entry in entries {
+        let tx_fees: Vec<_> = entry
+            .tx_fees
+            .iter()
+            .map(|fee| format!("{} {}", fee.amount, fee.coin))
+            .collect();
+        let fields = [
+            entry.entry_type.as_str().to_owned(),
+            entry.timestamp.to_string(),
+            opt(&entry.uuid),
+            opt(&entry.sent_coin),
+            opt(&entry.sent_amount),
+            opt(&entry.sent_coin_usd_price),
+            opt(&entry.received_coin),
+            opt(&entry.received_amount),
+            opt(&entry.received_coin_usd_price),
+            opt(&entry.dex_fee.as_ref().map(|fee| fee.coin.clone())),
+            opt(&entry.dex_fee.as_ref().map(|fee| fee.amount.clone())),
+            tx_fees.join(";"),
+            entry.tx_ids.join(";"),
+        ];
+        let row: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+pub async fn export_swap_ledger(ctx: MmArc, req: SwapLedgerRequest) -> MmResult<SwapLedgerResponse, SwapLedgerError> {
+    let from_timestamp = req.from_timestamp.unwrap_or_default();
+    let to_timestamp = req.to_timestamp.unwrap_or(u64::MAX);
+    let is_in_range = |timestamp: u64| from_timestamp <= timestamp && timestamp < to_timestamp;
+
+    // The swaps are stored by the start time, but the entries are timestamped by the finish time,
+    // so a swap started before `from_timestamp` can still finish in the range.
+    // A swap can't finish before it's started, so `to_timestamp` is safe to filter by.
+    let filter = MySwapsFilter {
+        to_timestamp: req.to_timestamp,
+        statuses: Some(vec![SavedSwapStatus::Finished]),
+        ..MySwapsFilter::default()
+    };
+    let db_result = MySwapsStorage::new(ctx.clone())
+        .my_recent_swaps_with_filters(&filter, None)
+        .await?;
+
+    let mut entries = Vec::with_capacity(db_result.uuids.len());
+    for uuid in db_result.uuids.iter() {
+        let entry = match SavedSwap::load_my_swap_from_db(&ctx, *uuid).await {
+            Ok(Some(SavedSwap::Maker(swap))) => maker_swap_entry(&swap),
+            Ok(Some(SavedSwap::Taker(swap))) => taker_swap_entry(&ctx, &swap).await,
+            Ok(None) => {
+                warn!("No such swap with the uuid '{}'", uuid);
+                continue;
+            },
+            Err(e) => {
+                error!("Error loading a swap with the uuid '{}': {}", uuid, e);
+                continue;
+            },
+        };
+        entries.extend(entry);
+    }
+
+    // The swap transactions are in the history too, so they are skipped not to be accounted twice.
+    // The swaps finished out of the range are collected too, as their transactions can be in the range.
+    let swap_tx_ids: HashSet<_> = entries.iter().flat_map(|entry| entry.tx_ids.clone()).collect();
+    entries.retain(|entry| is_in_range(entry.timestamp));
+    for ticker in req.tx_history_coins.iter() {
+        let history = load_tx_history(&ctx, ticker).await?;
+        let transfers = history
+            .iter()
+            .filter(|tx| is_in_range(tx.timestamp()))
+            .filter(|tx| !swap_tx_ids.contains(tx.tx_hash().trim_start_matches("0x")))
+            .filter_map(transfer_entry);
+        entries.extend(transfers);
+    }
+    entries.sort_by_key(|entry| entry.timestamp);
+
+    Ok(match req.format {
+        SwapLedgerFormat::Json => SwapLedgerResponse::Json { entries },
+        SwapLedgerFormat::Csv => SwapLedgerResponse::Csv { csv: to_csv(&entries) },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::block_on;
+    use mm2_core::mm_ctx::MmCtxBuilder;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("0.1 ETH;0.0001 KMD"), "0.1 ETH;0.0001 KMD");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_swap_ledger_to_csv() {
+        let entry = SwapLedgerEntry {
+            entry_type: SwapLedgerEntryType::Swap,
+            timestamp: 1000,
+            uuid: Some("5acb0e63-8b26-469e-81df-7dd9e4a9ad15".parse().unwrap()),
+            sent_coin: Some("RICK".to_owned()),
+            sent_amount: Some("1.5".parse().unwrap()),
+            sent_coin_usd_price: None,
+            received_coin: Some("MORTY".to_owned()),
+            received_amount: Some("2".parse().unwrap()),
+            received_coin_usd_price: Some("0.5".parse().unwrap()),
+            dex_fee: Some(SwapLedgerFee {
+                coin: "RICK".to_owned(),
+                amount: "0.0019".parse().unwrap(),
+            }),
+            tx_fees: vec![
+                SwapLedgerFee {
+                    coin: "RICK".to_owned(),
+                    amount: "0.00001".parse().unwrap(),
+                },
+                SwapLedgerFee {
+                    coin: "MORTY".to_owned(),
+                    amount: "0.00002".parse().unwrap(),
+                },
+            ],
+            tx_ids: vec!["aa".to_owned(), "bb".to_owned()],
+        };
+        let csv = to_csv(&[entry]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "swap,1000,5acb0e63-8b26-469e-81df-7dd9e4a9ad15,RICK,1.5,,MORTY,2,0.5,RICK,0.0019,\
+                 0.00001 RICK;0.00002 MORTY,aa;bb"
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_taker_swap_entry_dex_fee_from_started_event() {
+        let ctx = MmCtxBuilder::default().into_mm_arc();
+        let saved_swap: SavedSwap =
+            serde_json::from_str(include_str!("../for_tests/iris_nimda_rick_taker_swap.json")).unwrap();
+        let mut swap = match saved_swap {
+            SavedSwap::Taker(swap) => swap,
+            SavedSwap::Maker(_) => panic!("Expected a taker swap"),
+        };
+        match &mut swap.events[0].event {
+            TakerSwapEvent::Started(data) => data.dex_fee = Some("0.0000777".parse().unwrap()),
+            event => panic!("Unexpected first event {:?}", event),
+        }
+
+        let entry = block_on(taker_swap_entry(&ctx, &swap)).unwrap();
+        let expected = SwapLedgerFee {
+            coin: "RICK".to_owned(),
+            amount: "0.0000777".parse().unwrap(),
+        };
+        assert_eq!(entry.dex_fee, Some(expected));
+        assert_eq!(entry.uuid, Some(swap.uuid));
+    }
+}
@@ -0,0 +1,190 @@
+//! Maker swap policy rules configured by the `swap_policy` array in MM2.json, e.g.
+//!
+//! ```json
+//! "swap_policy": [
+//!     { "base": "BTC", "rel": "*", "min_volume": "0.1", "base_confs": 3, "base_nota": false },
+//!     { "base": "*", "rel": "DOGE", "lock_duration": 15600 }
+//! ]
+//! ```
+//!
+//! Every rule is keyed by the order pair (`*` matches any ticker) and an optional volume tier.
+//! The confirmations and notarization settings of a matching rule are the minimal requirements
+//! the maker asks the taker to respect, and `lock_duration` overrides the locktime
+//! that would be derived by [`super::lp_atomic_locktime`] if it is longer.
+
+use crate::mm2::lp_ordermatch::OrderConfirmationsSettings;
+use mm2_number::MmNumber;
+
+/// The ticker wildcard that matches any coin.
+const ANY_TICKER: &str = "*";
+
+/// The longest lock duration, as a multiple of the lock duration derived by the taker,
+/// that the taker accepts when it's requested by the maker during the negotiation.
+pub const MAX_POLICY_LOCK_DURATION_FACTOR: u64 = 4;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct SwapPolicyRule {
+    /// The base coin ticker of the order or `*`.
+    pub base: String,
+    /// The rel coin ticker of the order or `*`.
+    pub rel: String,
+    /// The rule is applied only if the base volume of the swap is greater than or equal to this value.
+    #[serde(default)]
+    pub min_volume: Option<MmNumber>,
+    #[serde(default)]
+    pub base_confs: Option<u64>,
+    #[serde(default)]
+    pub base_nota: Option<bool>,
+    #[serde(default)]
+    pub rel_confs: Option<u64>,
+    #[serde(default)]
+    pub rel_nota: Option<bool>,
+    /// The swap payment lock duration in seconds.
+    #[serde(default)]
+    pub lock_duration: Option<u64>,
+}
+
+impl SwapPolicyRule {
+    fn matches(&self, base: &str, rel: &str, volume: &MmNumber) -> bool {
+        let ticker_matches = |pattern: &str, ticker: &str| pattern == ANY_TICKER || pattern == ticker;
+        ticker_matches(&self.base, base)
+            && ticker_matches(&self.rel, rel)
+            && self.min_volume.as_ref().map_or(true, |min_volume| volume >= min_volume)
+    }
+
+    /// The rule with a pair specified explicitly takes priority over the rule with wildcards.
+    fn specificity(&self) -> u8 { (self.base != ANY_TICKER) as u8 + (self.rel != ANY_TICKER) as u8 }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SwapPolicy {
+    rules: Vec<SwapPolicyRule>,
+}
+
+impl SwapPolicy {
+    /// Finds the most specific rule for the given pair and base volume.
+    /// If there are several rules of the same specificity, the one with the highest volume tier is chosen.
+    pub fn find_rule(&self, base: &str, rel: &str, volume: &MmNumber) -> Option<&SwapPolicyRule> {
+        let zero = MmNumber::from(0);
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(base, rel, volume))
+            .max_by(|a, b| {
+                let a_volume = a.min_volume.as_ref().unwrap_or(&zero);
+                let b_volume = b.min_volume.as_ref().unwrap_or(&zero);
+                a.specificity()
+                    .cmp(&b.specificity())
+                    .then_with(|| a_volume.cmp(b_volume))
+            })
+    }
+
+    /// Raises the given confirmations settings up to the requirements of the matching rule.
+    pub fn apply_to_conf_settings(
+        &self,
+        base: &str,
+        rel: &str,
+        volume: &MmNumber,
+        settings: OrderConfirmationsSettings,
+    ) -> OrderConfirmationsSettings {
+        let rule = match self.find_rule(base, rel, volume) {
+            Some(rule) => rule,
+            None => return settings,
+        };
+        OrderConfirmationsSettings {
+            base_confs: settings.base_confs.max(rule.base_confs.unwrap_or_default()),
+            base_nota: settings.base_nota || rule.base_nota.unwrap_or_default(),
+            rel_confs: settings.rel_confs.max(rule.rel_confs.unwrap_or_default()),
+            rel_nota: settings.rel_nota || rule.rel_nota.unwrap_or_default(),
+        }
+    }
+
+    /// Returns the lock duration required by the matching rule if it's longer than the `default_lock_duration`.
+    pub fn lock_duration(&self, base: &str, rel: &str, volume: &MmNumber, default_lock_duration: u64) -> Option<u64> {
+        self.find_rule(base, rel, volume)
+            .and_then(|rule| rule.lock_duration)
+            .filter(|lock_duration| *lock_duration > default_lock_duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json as json;
+
+    fn policy() -> SwapPolicy {
+        json::from_value(json!([
+            { "base": "BTC", "rel": "*", "base_confs": 2 },
+            { "base": "BTC", "rel": "*", "min_volume": "0.1", "base_confs": 3, "base_nota": true },
+            { "base": "*", "rel": "DOGE", "rel_confs": 5, "lock_duration": 15600 },
+            { "base": "BTC", "rel": "DOGE", "rel_confs": 10 },
+        ]))
+        .unwrap()
+    }
+
+    fn conf_settings() -> OrderConfirmationsSettings {
+        OrderConfirmationsSettings {
+            base_confs: 1,
+            base_nota: false,
+            rel_confs: 1,
+            rel_nota: false,
+        }
+    }
+
+    #[test]
+    fn test_swap_policy_volume_tiers() {
+        let policy = policy();
+
+        let actual = policy.apply_to_conf_settings("BTC", "KMD", &MmNumber::from("0.01"), conf_settings());
+        let expected = OrderConfirmationsSettings {
+            base_confs: 2,
+            ..conf_settings()
+        };
+        assert_eq!(actual, expected);
+
+        let actual = policy.apply_to_conf_settings("BTC", "KMD", &MmNumber::from("0.1"), conf_settings());
+        let expected = OrderConfirmationsSettings {
+            base_confs: 3,
+            base_nota: true,
+            ..conf_settings()
+        };
+        assert_eq!(actual, expected);
+
+        let actual = policy.apply_to_conf_settings("KMD", "BTC", &MmNumber::from(1), conf_settings());
+        assert_eq!(actual, conf_settings());
+    }
+
+    #[test]
+    fn test_swap_policy_specific_pair_priority() {
+        let policy = policy();
+
+        let actual = policy.apply_to_conf_settings("BTC", "DOGE", &MmNumber::from(1), conf_settings());
+        let expected = OrderConfirmationsSettings {
+            rel_confs: 10,
+            ..conf_settings()
+        };
+        assert_eq!(actual, expected);
+
+        // the explicit BTC/DOGE rule doesn't set the lock duration
+        assert_eq!(policy.lock_duration("BTC", "DOGE", &MmNumber::from(1), 7800), None);
+        assert_eq!(
+            policy.lock_duration("KMD", "DOGE", &MmNumber::from(1), 7800),
+            Some(15600)
+        );
+        // the policy never shortens the locktime
+        assert_eq!(policy.lock_duration("KMD", "DOGE", &MmNumber::from(1), 31200), None);
+    }
+
+    #[test]
+    fn test_swap_policy_stricter_order_settings_kept() {
+        let policy = policy();
+        let settings = OrderConfirmationsSettings {
+            base_confs: 6,
+            base_nota: true,
+            rel_confs: 1,
+            rel_nota: false,
+        };
+        let actual = policy.apply_to_conf_settings("BTC", "KMD", &MmNumber::from(1), settings);
+        assert_eq!(actual, settings);
+    }
+}
@@ -0,0 +1,267 @@
+//! The timeline of a saved swap computed from the timestamps of its events.
+//! Allows to find out which phase of the swap took the most time: a slow chain, a slow counterparty, etc.
+
+use super::maker_swap::{MakerSavedSwap, MakerSwapEvent};
+use super::taker_swap::{TakerSavedSwap, TakerSwapEvent};
+use super::{taker_payment_spend_deadline, wait_for_maker_payment_conf_until};
+use mm2_core::mm_ctx::MmArc;
+use mm2_metrics::{mm_label, mm_timing};
+use serde::Serialize;
+use serde_json as json;
+
+const MAKER_ROLE: &str = "maker";
+const TAKER_ROLE: &str = "taker";
+
+/// The swap state transition.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SwapTimelineStep {
+    /// The type of the event that finished the step.
+    pub event: String,
+    /// The time the event was saved at, in milliseconds.
+    pub timestamp: u64,
+    /// The time elapsed since the previous event, in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// The time spent waiting for the swap transaction to be confirmed.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConfirmationWait {
+    pub coin: String,
+    /// The event after which the confirmations are awaited.
+    pub started_by: String,
+    pub started_at: u64,
+    /// `None` if the confirmations are still awaited.
+    pub finished_at: Option<u64>,
+    pub duration_ms: Option<u64>,
+}
+
+/// The swap deadlines, in seconds since UNIX epoch.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SwapDeadlines {
+    pub wait_for_maker_payment_conf_until: u64,
+    pub taker_payment_spend_deadline: u64,
+    pub maker_payment_lock: u64,
+    pub taker_payment_lock: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SwapTimeline {
+    pub role: &'static str,
+    pub steps: Vec<SwapTimelineStep>,
+    pub confirmation_waits: Vec<ConfirmationWait>,
+    pub deadlines: Option<SwapDeadlines>,
+    /// The time elapsed between the first and the last events, in milliseconds.
+    pub total_duration_ms: u64,
+}
+
+/// Describes the events between which the swap waits for the transaction confirmations of the `coin`.
+struct ConfirmationWaitRule<'a> {
+    coin: &'a str,
+    start: &'static str,
+    finish: &'static [&'static str],
+}
+
+/// Returns the `type` tag the event is serialized with.
+fn event_type<T: Serialize>(event: &T) -> String {
+    json::to_value(event)
+        .ok()
+        .and_then(|event| event["type"].as_str().map(ToOwned::to_owned))
+        .unwrap_or_default()
+}
+
+impl SwapTimeline {
+    fn new(role: &'static str, events: Vec<(u64, String)>, rules: &[ConfirmationWaitRule<'_>]) -> SwapTimeline {
+        let mut steps = Vec::with_capacity(events.len());
+        let mut prev_timestamp = events.first().map(|(timestamp, _)| *timestamp).unwrap_or_default();
+        for (timestamp, event) in events.iter() {
+            steps.push(SwapTimelineStep {
+                event: event.clone(),
+                timestamp: *timestamp,
+                duration_ms: timestamp.saturating_sub(prev_timestamp),
+            });
+            prev_timestamp = *timestamp;
+        }
+
+        let mut confirmation_waits = Vec::new();
+        for rule in rules {
+            for (i, (started_at, _)) in events.iter().enumerate().filter(|(_, (_, event))| event == rule.start) {
+                let finished_at = events[i + 1..]
+                    .iter()
+                    .find(|(_, event)| rule.finish.contains(&event.as_str()))
+                    .map(|(timestamp, _)| *timestamp);
+                confirmation_waits.push(ConfirmationWait {
+                    coin: rule.coin.to_owned(),
+                    started_by: rule.start.to_owned(),
+                    started_at: *started_at,
+                    finished_at,
+                    duration_ms: finished_at.map(|finished_at| finished_at.saturating_sub(*started_at)),
+                });
+            }
+        }
+
+        let total_duration_ms = match (events.first(), events.last()) {
+            (Some((first, _)), Some((last, _))) => last.saturating_sub(*first),
+            _ => 0,
+        };
+        SwapTimeline {
+            role,
+            steps,
+            confirmation_waits,
+            deadlines: None,
+            total_duration_ms,
+        }
+    }
+
+    pub fn from_maker_swap(swap: &MakerSavedSwap) -> SwapTimeline {
+        let events = swap
+            .events
+            .iter()
+            .map(|event| (event.timestamp, event_type(&event.event)))
+            .collect();
+        let taker_coin = swap.taker_coin().unwrap_or_default();
+        let rules = [
+            ConfirmationWaitRule {
+                coin: &taker_coin,
+                start: "TakerPaymentWaitConfirmStarted",
+                finish: &[
+                    "TakerPaymentValidatedAndConfirmed",
+                    "TakerPaymentValidateFailed",
+                    "TakerPaymentWaitConfirmFailed",
+                ],
+            },
+            ConfirmationWaitRule {
+                coin: &taker_coin,
+                start: "TakerPaymentSpendConfirmStarted",
+                finish: &["TakerPaymentSpendConfirmed", "TakerPaymentSpendConfirmFailed"],
+            },
+        ];
+        let mut timeline = SwapTimeline::new(MAKER_ROLE, events, &rules);
+
+        timeline.deadlines = swap.swap_data().ok().map(|data| SwapDeadlines {
+            wait_for_maker_payment_conf_until: wait_for_maker_payment_conf_until(data.started_at, data.lock_duration),
+            taker_payment_spend_deadline: taker_payment_spend_deadline(data.started_at, data.lock_duration),
+            maker_payment_lock: data.maker_payment_lock,
+            taker_payment_lock: swap.events.iter().find_map(|event| match &event.event {
+                MakerSwapEvent::Negotiated(negotiated) => Some(negotiated.taker_payment_locktime),
+                _ => None,
+            }),
+        });
+        timeline
+    }
+
+    pub fn from_taker_swap(swap: &TakerSavedSwap) -> SwapTimeline {
+        let events = swap
+            .events
+            .iter()
+            .map(|event| (event.timestamp, event_type(&event.event)))
+            .collect();
+        let maker_coin = swap.maker_coin().unwrap_or_default();
+        let rules = [ConfirmationWaitRule {
+            coin: &maker_coin,
+            start: "MakerPaymentWaitConfirmStarted",
+            finish: &[
+                "MakerPaymentValidatedAndConfirmed",
+                "MakerPaymentValidateFailed",
+                "MakerPaymentWaitConfirmFailed",
+            ],
+        }];
+        let mut timeline = SwapTimeline::new(TAKER_ROLE, events, &rules);
+
+        let data = match swap.events.first().map(|event| &event.event) {
+            Some(TakerSwapEvent::Started(data)) => data,
+            _ => return timeline,
+        };
+        let negotiated = swap.events.iter().find_map(|event| match &event.event {
+            TakerSwapEvent::Negotiated(negotiated) => Some(negotiated),
+            _ => None,
+        });
+        // The lock duration might be extended by the maker swap policy during the negotiation.
+        let lock_duration = negotiated
+            .and_then(|negotiated| negotiated.lock_duration)
+            .unwrap_or(data.lock_duration);
+        timeline.deadlines = negotiated.map(|negotiated| SwapDeadlines {
+            wait_for_maker_payment_conf_until: wait_for_maker_payment_conf_until(data.started_at, lock_duration),
+            taker_payment_spend_deadline: taker_payment_spend_deadline(data.started_at, lock_duration),
+            maker_payment_lock: negotiated.maker_payment_locktime,
+            taker_payment_lock: Some(data.started_at + lock_duration),
+        });
+        timeline
+    }
+
+    /// Aggregates the step and confirmation durations into the histograms.
+    pub fn record_metrics(&self, ctx: &MmArc) {
+        for step in self.steps.iter() {
+            mm_timing!(ctx.metrics, "swap.step.duration", step.duration_ms as f64 / 1000., "role" => self.role, "event" => step.event);
+        }
+        for wait in self.confirmation_waits.iter() {
+            if let Some(duration_ms) = wait.duration_ms {
+                mm_timing!(ctx.metrics, "swap.confirmation_wait.duration", duration_ms as f64 / 1000., "coin" => wait.coin);
+            }
+        }
+        mm_timing!(ctx.metrics, "swap.total.duration", self.total_duration_ms as f64 / 1000., "role" => self.role);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_timeline_steps_and_confirmation_waits() {
+        let events = vec![
+            (1000, "Started".to_owned()),
+            (3000, "Negotiated".to_owned()),
+            (4000, "TakerFeeValidated".to_owned()),
+            (5000, "MakerPaymentSent".to_owned()),
+            (9000, "TakerPaymentReceived".to_owned()),
+            (9500, "TakerPaymentWaitConfirmStarted".to_owned()),
+            (19500, "TakerPaymentValidatedAndConfirmed".to_owned()),
+            (20000, "TakerPaymentSpent".to_owned()),
+            (20100, "TakerPaymentSpendConfirmStarted".to_owned()),
+        ];
+        let rules = [
+            ConfirmationWaitRule {
+                coin: "MORTY",
+                start: "TakerPaymentWaitConfirmStarted",
+                finish: &["TakerPaymentValidatedAndConfirmed"],
+            },
+            ConfirmationWaitRule {
+                coin: "MORTY",
+                start: "TakerPaymentSpendConfirmStarted",
+                finish: &["TakerPaymentSpendConfirmed"],
+            },
+        ];
+        let timeline = SwapTimeline::new(MAKER_ROLE, events, &rules);
+
+        let durations: Vec<_> = timeline.steps.iter().map(|step| step.duration_ms).collect();
+        assert_eq!(durations, vec![0, 2000, 1000, 1000, 4000, 500, 10000, 500, 100]);
+        assert_eq!(timeline.total_duration_ms, 19100);
+
+        let expected = vec![
+            ConfirmationWait {
+                coin: "MORTY".to_owned(),
+                started_by: "TakerPaymentWaitConfirmStarted".to_owned(),
+                started_at: 9500,
+                finished_at: Some(19500),
+                duration_ms: Some(10000),
+            },
+            ConfirmationWait {
+                coin: "MORTY".to_owned(),
+                started_by: "TakerPaymentSpendConfirmStarted".to_owned(),
+                started_at: 20100,
+                finished_at: None,
+                duration_ms: None,
+            },
+        ];
+        assert_eq!(timeline.confirmation_waits, expected);
+    }
+
+    #[test]
+    fn test_event_type() {
+        assert_eq!(
+            event_type(&MakerSwapEvent::TakerPaymentWaitConfirmStarted),
+            "TakerPaymentWaitConfirmStarted"
+        );
+        assert_eq!(event_type(&TakerSwapEvent::Finished), "Finished");
+    }
+}
@@ -1,4 +1,6 @@
+use super::{SavedSwap, SavedSwapStatus};
 use async_trait::async_trait;
+use common::log::{error, warn};
 use mm2_db::indexed_db::{DbIdentifier, DbInstance, DbUpgrader, IndexedDb, IndexedDbBuilder, OnUpgradeResult,
                          TableSignature};
 use std::ops::Deref;
@@ -6,10 +8,12 @@ use uuid::Uuid;
 
 pub use mm2_db::indexed_db::{cursor_prelude, DbTransactionError, DbTransactionResult, InitDbError, InitDbResult,
                              ItemId};
-pub use tables::{MySwapsFiltersTable, SavedSwapTable, SwapLockTable};
+pub use tables::{MySwapsFiltersTable, SavedSwapTable, SwapDbMigrationTable, SwapLockTable};
 
 const DB_NAME: &str = "swap";
-const DB_VERSION: u32 = 1;
+const DB_VERSION: u32 = 2;
+/// The migration that fills the status fields of the `my_swaps` items added before the `DB_VERSION` 2.
+const LEGACY_SWAP_STATUSES_MIGRATION: u32 = 2;
 
 pub struct SwapDb {
     inner: IndexedDb,
@@ -25,9 +29,59 @@ impl DbInstance for SwapDb {
             .with_table::<SwapLockTable>()
             .with_table::<SavedSwapTable>()
             .with_table::<MySwapsFiltersTable>()
+            .with_table::<SwapDbMigrationTable>()
             .build()
             .await?;
-        Ok(SwapDb { inner })
+        let db = SwapDb { inner };
+        if let Err(e) = db.index_legacy_swap_statuses().await {
+            error!("Error indexing the statuses of the swaps: {}", e);
+        }
+        Ok(db)
+    }
+}
+
+impl SwapDb {
+    /// Fills the status fields of the `my_swaps` items that were added before the `DB_VERSION` 2.
+    /// Runs once: the migration is recorded in [`SwapDbMigrationTable`] when it completes.
+    async fn index_legacy_swap_statuses(&self) -> DbTransactionResult<()> {
+        let transaction = self.inner.transaction().await?;
+        let migration_table = transaction.table::<SwapDbMigrationTable>().await?;
+        if migration_table
+            .get_item_by_unique_index("migration", LEGACY_SWAP_STATUSES_MIGRATION)
+            .await?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let my_swaps_table = transaction.table::<MySwapsFiltersTable>().await?;
+        let saved_swap_table = transaction.table::<SavedSwapTable>().await?;
+
+        for (item_id, mut item) in my_swaps_table.get_all_items().await? {
+            if item.status.is_some() {
+                continue;
+            }
+            let saved_swap = match saved_swap_table.get_item_by_unique_index("uuid", item.uuid).await? {
+                Some((_item_id, SavedSwapTable { saved_swap, .. })) => saved_swap,
+                None => continue,
+            };
+            match serde_json::from_value::<SavedSwap>(saved_swap) {
+                Ok(swap) => item.set_status(&swap),
+                Err(e) => {
+                    warn!("Error deserializing swap {}: {}", item.uuid, e);
+                    continue;
+                },
+            }
+            my_swaps_table.replace_item(item_id, &item).await?;
+        }
+
+        let migration = SwapDbMigrationTable {
+            migration: LEGACY_SWAP_STATUSES_MIGRATION,
+        };
+        migration_table
+            .add_item_or_ignore_by_unique_index("migration", LEGACY_SWAP_STATUSES_MIGRATION, &migration)
+            .await?;
+        Ok(())
     }
 }
 
@@ -78,19 +132,62 @@ pub mod tables {
         pub my_coin: String,
         pub other_coin: String,
         pub started_at: u32,
+        /// `None` if the item was added before the swap statuses were indexed.
+        #[serde(default)]
+        pub status: Option<SavedSwapStatus>,
+        /// Booleans can't be used as IndexedDB keys, so this field is not indexed.
+        #[serde(default)]
+        pub recoverable: bool,
+        /// The persistent pubkey of the counterparty without the prefix byte.
+        #[serde(default)]
+        pub other_pubkey: Option<String>,
+    }
+
+    impl MySwapsFiltersTable {
+        pub fn set_status(&mut self, swap: &SavedSwap) {
+            self.status = Some(swap.status());
+            self.recoverable = swap.is_recoverable();
+            self.other_pubkey = swap.other_pubkey().map(|pubkey| hex::encode(pubkey.0));
+        }
     }
 
     impl TableSignature for MySwapsFiltersTable {
         fn table_name() -> &'static str { "my_swaps" }
 
         fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
-            if let (0, 1) = (old_version, new_version) {
+            let table = match old_version {
+                0 => {
+                    let table = upgrader.create_table(Self::table_name())?;
+                    table.create_index("uuid", true)?;
+                    table.create_index("started_at", false)?;
+                    table.create_multi_index("with_my_coin", &["my_coin", "started_at"], false)?;
+                    table.create_multi_index("with_other_coin", &["other_coin", "started_at"], false)?;
+                    table.create_multi_index("with_my_other_coins", &["my_coin", "other_coin", "started_at"], false)?;
+                    table
+                },
+                _ => upgrader.open_table(Self::table_name())?,
+            };
+            if old_version < 2 && new_version >= 2 {
+                table.create_multi_index("with_status", &["status", "started_at"], false)?;
+                table.create_multi_index("with_other_pubkey", &["other_pubkey", "started_at"], false)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// This table records the data migrations that have already been applied to the database.
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    pub struct SwapDbMigrationTable {
+        pub migration: u32,
+    }
+
+    impl TableSignature for SwapDbMigrationTable {
+        fn table_name() -> &'static str { "swap_db_migration" }
+
+        fn on_upgrade_needed(upgrader: &DbUpgrader, old_version: u32, new_version: u32) -> OnUpgradeResult<()> {
+            if old_version < 2 && new_version >= 2 {
                 let table = upgrader.create_table(Self::table_name())?;
-                table.create_index("uuid", true)?;
-                table.create_index("started_at", false)?;
-                table.create_multi_index("with_my_coin", &["my_coin", "started_at"], false)?;
-                table.create_multi_index("with_other_coin", &["other_coin", "started_at"], false)?;
-                table.create_multi_index("with_my_other_coins", &["my_coin", "other_coin", "started_at"], false)?;
+                table.create_index("migration", true)?;
             }
             Ok(())
         }
@@ -100,10 +197,10 @@ pub mod tables {
     fn on_upgrade_swap_table_by_uuid_v1(
         upgrader: &DbUpgrader,
         old_version: u32,
-        new_version: u32,
+        _new_version: u32,
         table_name: &'static str,
     ) -> OnUpgradeResult<()> {
-        if let (0, 1) = (old_version, new_version) {
+        if old_version == 0 {
             let table = upgrader.create_table(table_name)?;
             table.create_index("uuid", true)?;
         }
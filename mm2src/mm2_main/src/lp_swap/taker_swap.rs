@@ -1,15 +1,17 @@
-use super::check_balance::{check_my_coin_balance_for_swap, CheckBalanceError, CheckBalanceResult,
-                           TakerFeeAdditionalInfo};
+use super::check_balance::{check_coin_is_not_watch_only, check_my_coin_balance_for_swap, CheckBalanceError,
+                           CheckBalanceResult, TakerFeeAdditionalInfo};
 use super::pubkey_banning::ban_pubkey_on_failed_swap;
 use super::swap_lock::{SwapLock, SwapLockOps};
+use super::swap_policy::MAX_POLICY_LOCK_DURATION_FACTOR;
 use super::swap_watcher::{watcher_topic, SwapWatcherMsg};
 use super::trade_preimage::{TradePreimageRequest, TradePreimageRpcError, TradePreimageRpcResult};
 use super::{broadcast_my_swap_status, broadcast_swap_message, broadcast_swap_message_every,
             check_other_coin_balance_for_swap, dex_fee_amount_from_taker_coin, dex_fee_rate, dex_fee_threshold,
-            get_locked_amount, recv_swap_msg, swap_topic, wait_for_maker_payment_conf_until, AtomicSwap, LockedAmount,
-            MySwapInfo, NegotiationDataMsg, NegotiationDataV2, NegotiationDataV3, RecoveredSwap, RecoveredSwapAction,
-            SavedSwap, SavedSwapIo, SavedTradeFee, SwapConfirmationsSettings, SwapError, SwapMsg, SwapPubkeys,
-            SwapTxDataMsg, SwapsContext, TransactionIdentifier, WAIT_CONFIRM_INTERVAL};
+            get_locked_amount, record_my_swap_timeline_metrics, recv_swap_msg, swap_topic, update_swap_status_in_db,
+            wait_for_maker_payment_conf_until, AtomicSwap, LockedAmount, MySwapInfo, NegotiationDataMsg,
+            NegotiationDataV2, NegotiationDataV3, RecoveredSwap, RecoveredSwapAction, SavedSwap, SavedSwapIo,
+            SavedTradeFee, SwapConfirmationsSettings, SwapError, SwapMsg, SwapPubkeys, SwapTxDataMsg, SwapsContext,
+            TransactionIdentifier, WAIT_CONFIRM_INTERVAL};
 use crate::mm2::lp_network::subscribe_to_topic;
 use crate::mm2::lp_ordermatch::{MatchBy, OrderConfirmationsSettings, TakerAction, TakerOrderBuilder};
 use crate::mm2::lp_swap::{broadcast_p2p_tx_msg, tx_helper_topic, wait_for_maker_payment_conf_duration,
@@ -132,6 +134,9 @@ async fn save_my_taker_swap_event(ctx: &MmArc, swap: &TakerSwap, event: TakerSav
         }
         let new_swap = SavedSwap::Taker(taker_swap);
         try_s!(new_swap.save_to_db(ctx).await);
+        if let Err(e) = update_swap_status_in_db(ctx.clone(), &new_swap).await {
+            error!("Error {} on swap status update", e);
+        }
         Ok(())
     } else {
         ERR!("Expected SavedSwap::Taker, got {:?}", swap)
@@ -465,6 +470,7 @@ pub async fn run_taker_swap(swap: RunTakerSwapInput, ctx: MmArc) {
                         command = c;
                     },
                     None => {
+                        record_my_swap_timeline_metrics(&ctx, running_swap.uuid).await;
                         if to_broadcast {
                             if let Err(e) = broadcast_my_swap_status(&ctx, running_swap.uuid).await {
                                 error!("!broadcast_my_swap_status({}): {}", uuid, e);
@@ -503,6 +509,10 @@ pub struct TakerSwapData {
     pub maker_payment_wait: u64,
     pub maker_coin_start_block: u64,
     pub taker_coin_start_block: u64,
+    /// The dex fee amount paid in the taker coin.
+    /// `None` if the swap was started before the dex fee was saved.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dex_fee: Option<BigDecimal>,
     /// A transaction fee that should be paid to send a `TakerFee`.
     /// Note this value is used to calculate locked amount only.
     pub fee_to_send_taker_fee: Option<SavedTradeFee>,
@@ -600,6 +610,10 @@ pub struct MakerNegotiationData {
     pub taker_coin_swap_contract_addr: Option<BytesJson>,
     pub maker_coin_htlc_pubkey: Option<H264Json>,
     pub taker_coin_htlc_pubkey: Option<H264Json>,
+    /// The lock duration requested by the maker swap policy if it differs from the one derived by the taker.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_duration: Option<u64>,
 }
 
 impl MakerNegotiationData {
@@ -765,6 +779,12 @@ impl TakerSwap {
             TakerSwapEvent::Negotiated(data) => {
                 self.maker_payment_lock
                     .store(data.maker_payment_locktime, Ordering::Relaxed);
+                if let Some(lock_duration) = data.lock_duration {
+                    let started_at = self.r().data.started_at;
+                    self.w().data.lock_duration = lock_duration;
+                    self.w().data.taker_payment_lock = started_at + lock_duration;
+                    self.w().data.maker_payment_wait = wait_for_maker_payment_conf_until(started_at, lock_duration);
+                }
                 self.w().other_maker_coin_htlc_pub = data.other_maker_coin_htlc_pub();
                 self.w().other_taker_coin_htlc_pub = data.other_taker_coin_htlc_pub();
                 self.w().secret_hash = data.secret_hash;
@@ -894,6 +914,7 @@ impl TakerSwap {
         secret_hash: Vec<u8>,
         maker_coin_swap_contract: Vec<u8>,
         taker_coin_swap_contract: Vec<u8>,
+        taker_payment_lock: u64,
     ) -> NegotiationDataMsg {
         let r = self.r();
 
@@ -904,7 +925,7 @@ impl TakerSwap {
             NegotiationDataMsg::V2(NegotiationDataV2 {
                 started_at: r.data.started_at,
                 secret_hash,
-                payment_locktime: r.data.taker_payment_lock,
+                payment_locktime: taker_payment_lock,
                 persistent_pubkey: self.my_persistent_pub.to_vec(),
                 maker_coin_swap_contract,
                 taker_coin_swap_contract,
@@ -912,12 +933,13 @@ impl TakerSwap {
         } else {
             NegotiationDataMsg::V3(NegotiationDataV3 {
                 started_at: r.data.started_at,
-                payment_locktime: r.data.taker_payment_lock,
+                payment_locktime: taker_payment_lock,
                 secret_hash,
                 maker_coin_swap_contract,
                 taker_coin_swap_contract,
                 maker_coin_htlc_pub: self.my_maker_coin_htlc_pub().into(),
                 taker_coin_htlc_pub: self.my_taker_coin_htlc_pub().into(),
+                lock_duration: None,
             })
         }
     }
@@ -1059,6 +1081,7 @@ impl TakerSwap {
             maker_payment_wait: wait_for_maker_payment_conf_until(started_at, self.payment_locktime),
             maker_coin_start_block,
             taker_coin_start_block,
+            dex_fee: Some(dex_fee.to_decimal()),
             fee_to_send_taker_fee: Some(SavedTradeFee::from(fee_to_send_dex_fee)),
             taker_payment_trade_fee: Some(SavedTradeFee::from(taker_payment_trade_fee)),
             maker_payment_spend_trade_fee: Some(SavedTradeFee::from(maker_payment_spend_trade_fee)),
@@ -1101,8 +1124,30 @@ impl TakerSwap {
             )]));
         }
 
-        let customized_lock_duration =
-            (self.r().data.lock_duration as f64 * self.taker_coin.maker_locktime_multiplier()).ceil() as u64;
+        let my_lock_duration = self.r().data.lock_duration;
+        // the maker may request a longer lock duration according to its swap policy
+        let lock_duration = match maker_data.lock_duration() {
+            Some(requested) if requested != my_lock_duration => {
+                let max_lock_duration = my_lock_duration * MAX_POLICY_LOCK_DURATION_FACTOR;
+                if requested < my_lock_duration || requested > max_lock_duration {
+                    return Ok((Some(TakerSwapCommand::Finish), vec![TakerSwapEvent::NegotiateFailed(
+                        ERRL!(
+                            "maker_data.lock_duration {} is out of the acceptable range [{}, {}]",
+                            requested,
+                            my_lock_duration,
+                            max_lock_duration
+                        )
+                        .into(),
+                    )]));
+                }
+                Some(requested)
+            },
+            _ => None,
+        };
+
+        let customized_lock_duration = (lock_duration.unwrap_or(my_lock_duration) as f64
+            * self.taker_coin.maker_locktime_multiplier())
+        .ceil() as u64;
         let expected_lock_time = maker_data.started_at().checked_add(customized_lock_duration);
         if Some(maker_data.payment_locktime()) != expected_lock_time {
             return Ok((Some(TakerSwapCommand::Finish), vec![TakerSwapEvent::NegotiateFailed(
@@ -1174,10 +1219,15 @@ impl TakerSwap {
             .clone()
             .map_or_else(Vec::new, |bytes| bytes.0);
 
+        let taker_payment_lock = match lock_duration {
+            Some(lock_duration) => self.r().data.started_at + lock_duration,
+            None => self.r().data.taker_payment_lock,
+        };
         let my_negotiation_data = self.get_my_negotiation_data(
             maker_data.secret_hash().to_vec(),
             maker_coin_swap_contract_bytes,
             taker_coin_swap_contract_bytes,
+            taker_payment_lock,
         );
 
         let taker_data = SwapMsg::NegotiationReply(my_negotiation_data);
@@ -1222,6 +1272,7 @@ impl TakerSwap {
                 taker_coin_swap_contract_addr,
                 maker_coin_htlc_pubkey: Some(maker_data.maker_coin_htlc_pub().into()),
                 taker_coin_htlc_pubkey: Some(maker_data.taker_coin_htlc_pub().into()),
+                lock_duration,
             },
         )]))
     }
@@ -2275,6 +2326,9 @@ pub async fn check_balance_for_taker_swap(
     prepared_params: Option<TakerSwapPreparedParams>,
     stage: FeeApproxStage,
 ) -> CheckBalanceResult<()> {
+    check_coin_is_not_watch_only(my_coin)?;
+    check_coin_is_not_watch_only(other_coin)?;
+
     let params = match prepared_params {
         Some(params) => params,
         None => {
@@ -224,6 +224,8 @@ pub enum TradePreimageRpcError {
     NoSuchCoin { coin: String },
     #[display(fmt = "Coin {} is wallet only", coin)]
     CoinIsWalletOnly { coin: String },
+    #[display(fmt = "Coin {} is activated in watch-only mode", coin)]
+    CoinIsWatchOnly { coin: String },
     #[display(fmt = "Rel coin can not be same as base")]
     BaseEqualRel,
     #[display(fmt = "Incorrect use of the '{}' parameter: {}", param, reason)]
@@ -244,6 +246,7 @@ impl HttpStatusCode for TradePreimageRpcError {
             | TradePreimageRpcError::VolumeTooLow { .. }
             | TradePreimageRpcError::NoSuchCoin { .. }
             | TradePreimageRpcError::CoinIsWalletOnly { .. }
+            | TradePreimageRpcError::CoinIsWatchOnly { .. }
             | TradePreimageRpcError::BaseEqualRel
             | TradePreimageRpcError::InvalidParam { .. }
             | TradePreimageRpcError::PriceTooLow { .. } => StatusCode::BAD_REQUEST,
@@ -301,6 +304,7 @@ impl From<CheckBalanceError> for TradePreimageRpcError {
                 volume,
                 threshold,
             },
+            CheckBalanceError::CoinIsWatchOnly { coin } => TradePreimageRpcError::CoinIsWatchOnly { coin },
             CheckBalanceError::Transport(transport) => TradePreimageRpcError::Transport(transport),
             CheckBalanceError::InternalError(internal) => TradePreimageRpcError::InternalError(internal),
         }
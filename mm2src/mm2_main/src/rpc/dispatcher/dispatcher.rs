@@ -7,13 +7,23 @@ use crate::mm2::lp_ordermatch::{best_orders_rpc_v2, orderbook_rpc_v2, start_simp
 use crate::mm2::rpc::rate_limiter::{process_rate_limit, RateLimitContext};
 use crate::{mm2::lp_stats::{add_node_to_version_stat, remove_node_from_version_stat, start_version_stat_collection,
                             stop_version_stat_collection, update_version_stat_collection},
-            mm2::lp_swap::{get_locked_amount_rpc, max_maker_vol, recreate_swap_data, trade_preimage_rpc},
+            mm2::lp_swap::{export_swap_ledger, get_locked_amount_rpc, max_maker_vol, my_swap_statuses,
+                           recreate_swap_data, trade_preimage_rpc},
             mm2::rpc::lp_commands::{get_public_key, get_public_key_hash, get_shared_db_id, trezor_connection_status}};
 use coins::eth::EthCoin;
 use coins::my_tx_history_v2::my_tx_history_v2_rpc;
 use coins::nft;
 use coins::rpc_command::tendermint::{ibc_chains, ibc_transfer_channels, ibc_withdraw};
 use coins::rpc_command::{account_balance::account_balance,
+                         bip322::{sign_message_bip322, verify_message_bip322},
+                         bump_fee::{bump_fee, bump_fee_cpfp},
+                         coin_control::{freeze_unspents, list_unspents, unfreeze_unspents},
+                         consolidate_utxos::{consolidate_utxos, stop_utxo_consolidation},
+                         erc20_allowance::{approve_erc20, get_erc20_allowance, revoke_erc20_approval},
+                         eth_contract_call::{call_eth_contract, sign_eth_contract_call},
+                         eth_nodes_status::get_eth_nodes_status,
+                         eth_pending_tx::{cancel_eth_transaction, get_eth_pending_nonces, speed_up_eth_transaction},
+                         eth_typed_data::{sign_typed_data, verify_typed_data},
                          get_current_mtp::get_current_mtp_rpc,
                          get_enabled_coins::get_enabled_coins,
                          get_new_address::{cancel_get_new_address, get_new_address, init_get_new_address,
@@ -24,7 +34,10 @@ use coins::rpc_command::{account_balance::account_balance,
                                                init_create_new_account_status, init_create_new_account_user_action},
                          init_scan_for_new_addresses::{cancel_scan_for_new_addresses, init_scan_for_new_addresses,
                                                        init_scan_for_new_addresses_status},
-                         init_withdraw::{cancel_withdraw, init_withdraw, withdraw_status, withdraw_user_action}};
+                         init_withdraw::{cancel_withdraw, init_withdraw, withdraw_status, withdraw_user_action},
+                         multisig::{combine_psbt, multisig_address, multisig_balance, multisig_history,
+                                    multisig_withdraw, sign_multisig_psbt},
+                         psbt::broadcast_psbt};
 use coins::tendermint::{TendermintCoin, TendermintToken};
 use coins::utxo::bch::BchCoin;
 use coins::utxo::qtum::QtumCoin;
@@ -155,7 +168,15 @@ async fn dispatcher_v2(request: MmRpcRequest, ctx: MmArc) -> DispatcherResult<Re
         "account_balance" => handle_mmrpc(ctx, request, account_balance).await,
         "add_delegation" => handle_mmrpc(ctx, request, add_delegation).await,
         "add_node_to_version_stat" => handle_mmrpc(ctx, request, add_node_to_version_stat).await,
+        "approve_erc20" => handle_mmrpc(ctx, request, approve_erc20).await,
         "best_orders" => handle_mmrpc(ctx, request, best_orders_rpc_v2).await,
+        "broadcast_psbt" => handle_mmrpc(ctx, request, broadcast_psbt).await,
+        "bump_fee" => handle_mmrpc(ctx, request, bump_fee).await,
+        "bump_fee_cpfp" => handle_mmrpc(ctx, request, bump_fee_cpfp).await,
+        "call_eth_contract" => handle_mmrpc(ctx, request, call_eth_contract).await,
+        "cancel_eth_transaction" => handle_mmrpc(ctx, request, cancel_eth_transaction).await,
+        "combine_psbt" => handle_mmrpc(ctx, request, combine_psbt).await,
+        "consolidate_utxos" => handle_mmrpc(ctx, request, consolidate_utxos).await,
         "enable_bch_with_tokens" => handle_mmrpc(ctx, request, enable_platform_coin_with_tokens::<BchCoin>).await,
         "enable_slp" => handle_mmrpc(ctx, request, enable_token::<SlpToken>).await,
         "enable_eth_with_tokens" => handle_mmrpc(ctx, request, enable_platform_coin_with_tokens::<EthCoin>).await,
@@ -164,8 +185,13 @@ async fn dispatcher_v2(request: MmRpcRequest, ctx: MmArc) -> DispatcherResult<Re
             handle_mmrpc(ctx, request, enable_platform_coin_with_tokens::<TendermintCoin>).await
         },
         "enable_tendermint_token" => handle_mmrpc(ctx, request, enable_token::<TendermintToken>).await,
+        "export_swap_ledger" => handle_mmrpc(ctx, request, export_swap_ledger).await,
+        "freeze_unspents" => handle_mmrpc(ctx, request, freeze_unspents).await,
         "get_current_mtp" => handle_mmrpc(ctx, request, get_current_mtp_rpc).await,
         "get_enabled_coins" => handle_mmrpc(ctx, request, get_enabled_coins).await,
+        "get_erc20_allowance" => handle_mmrpc(ctx, request, get_erc20_allowance).await,
+        "get_eth_nodes_status" => handle_mmrpc(ctx, request, get_eth_nodes_status).await,
+        "get_eth_pending_nonces" => handle_mmrpc(ctx, request, get_eth_pending_nonces).await,
         "get_locked_amount" => handle_mmrpc(ctx, request, get_locked_amount_rpc).await,
         "get_my_address" => handle_mmrpc(ctx, request, get_my_address).await,
         "get_new_address" => handle_mmrpc(ctx, request, get_new_address).await,
@@ -177,21 +203,37 @@ async fn dispatcher_v2(request: MmRpcRequest, ctx: MmArc) -> DispatcherResult<Re
         "get_raw_transaction" => handle_mmrpc(ctx, request, get_raw_transaction).await,
         "get_shared_db_id" => handle_mmrpc(ctx, request, get_shared_db_id).await,
         "get_staking_infos" => handle_mmrpc(ctx, request, get_staking_infos).await,
+        "list_unspents" => handle_mmrpc(ctx, request, list_unspents).await,
         "max_maker_vol" => handle_mmrpc(ctx, request, max_maker_vol).await,
+        "multisig_address" => handle_mmrpc(ctx, request, multisig_address).await,
+        "multisig_balance" => handle_mmrpc(ctx, request, multisig_balance).await,
+        "multisig_history" => handle_mmrpc(ctx, request, multisig_history).await,
+        "multisig_withdraw" => handle_mmrpc(ctx, request, multisig_withdraw).await,
+        "my_swap_statuses" => handle_mmrpc(ctx, request, my_swap_statuses).await,
         "my_tx_history" => handle_mmrpc(ctx, request, my_tx_history_v2_rpc).await,
         "orderbook" => handle_mmrpc(ctx, request, orderbook_rpc_v2).await,
         "recreate_swap_data" => handle_mmrpc(ctx, request, recreate_swap_data).await,
         "remove_delegation" => handle_mmrpc(ctx, request, remove_delegation).await,
         "remove_node_from_version_stat" => handle_mmrpc(ctx, request, remove_node_from_version_stat).await,
+        "revoke_erc20_approval" => handle_mmrpc(ctx, request, revoke_erc20_approval).await,
+        "sign_eth_contract_call" => handle_mmrpc(ctx, request, sign_eth_contract_call).await,
         "sign_message" => handle_mmrpc(ctx, request, sign_message).await,
+        "sign_message_bip322" => handle_mmrpc(ctx, request, sign_message_bip322).await,
+        "sign_multisig_psbt" => handle_mmrpc(ctx, request, sign_multisig_psbt).await,
+        "sign_typed_data" => handle_mmrpc(ctx, request, sign_typed_data).await,
+        "speed_up_eth_transaction" => handle_mmrpc(ctx, request, speed_up_eth_transaction).await,
         "start_simple_market_maker_bot" => handle_mmrpc(ctx, request, start_simple_market_maker_bot).await,
         "start_version_stat_collection" => handle_mmrpc(ctx, request, start_version_stat_collection).await,
         "stop_simple_market_maker_bot" => handle_mmrpc(ctx, request, stop_simple_market_maker_bot).await,
+        "stop_utxo_consolidation" => handle_mmrpc(ctx, request, stop_utxo_consolidation).await,
         "stop_version_stat_collection" => handle_mmrpc(ctx, request, stop_version_stat_collection).await,
         "trade_preimage" => handle_mmrpc(ctx, request, trade_preimage_rpc).await,
         "trezor_connection_status" => handle_mmrpc(ctx, request, trezor_connection_status).await,
+        "unfreeze_unspents" => handle_mmrpc(ctx, request, unfreeze_unspents).await,
         "update_version_stat_collection" => handle_mmrpc(ctx, request, update_version_stat_collection).await,
         "verify_message" => handle_mmrpc(ctx, request, verify_message).await,
+        "verify_message_bip322" => handle_mmrpc(ctx, request, verify_message_bip322).await,
+        "verify_typed_data" => handle_mmrpc(ctx, request, verify_typed_data).await,
         "withdraw" => handle_mmrpc(ctx, request, withdraw).await,
         "ibc_withdraw" => handle_mmrpc(ctx, request, ibc_withdraw).await,
         "ibc_chains" => handle_mmrpc(ctx, request, ibc_chains).await,
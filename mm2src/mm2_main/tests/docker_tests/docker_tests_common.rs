@@ -31,7 +31,8 @@ use http::StatusCode;
 use keys::{Address, AddressHashEnum, KeyPair, NetworkPrefix as CashAddrPrefix};
 use mm2_core::mm_ctx::{MmArc, MmCtxBuilder};
 use mm2_number::BigDecimal;
-use mm2_test_helpers::for_tests::{enable_native, eth_testnet_conf, ETH_DEV_NODES, ETH_DEV_SWAP_CONTRACT};
+use mm2_test_helpers::for_tests::{enable_native, eth_testnet_conf, mm_ctx_with_custom_db, ETH_DEV_NODES,
+                                  ETH_DEV_SWAP_CONTRACT};
 use mm2_test_helpers::structs::{MyBalanceResponse, TransactionDetails};
 use primitives::hash::{H160, H256};
 use script::Builder;
@@ -118,7 +119,7 @@ impl UtxoAssetDockerOps {
         let conf = json!({"asset": ticker, "txfee": 1000, "network": "regtest"});
         let req = json!({"method":"enable"});
         let priv_key = Secp256k1Secret::from("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f");
-        let ctx = MmCtxBuilder::new().into_mm_arc();
+        let ctx = mm_ctx_with_custom_db();
         let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
 
         let coin = block_on(utxo_standard_coin_with_priv_key(&ctx, ticker, &conf, &params, priv_key)).unwrap();
@@ -214,7 +215,7 @@ impl BchDockerOps {
         let conf = json!({"asset": ticker,"txfee":1000,"network": "regtest","txversion":4,"overwintered":1});
         let req = json!({"method":"enable", "bchd_urls": [], "allow_slp_unsafe_conf": true});
         let priv_key = Secp256k1Secret::from("809465b17d0a4ddb3e4c69e8f23c2cabad868f51f8bed5c765ad1d6516c3306f");
-        let ctx = MmCtxBuilder::new().into_mm_arc();
+        let ctx = mm_ctx_with_custom_db();
         let params = BchActivationRequest::from_legacy_req(&req).unwrap();
 
         let coin = block_on(bch_coin_with_priv_key(
@@ -408,7 +409,7 @@ pub fn qrc20_coin_from_privkey(ticker: &str, priv_key: Secp256k1Secret) -> (MmAr
         )
     };
     let platform = "QTUM";
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let confpath = unsafe { QTUM_CONF_PATH.as_ref().expect("Qtum config is not set yet") };
     let conf = json!({
         "coin":ticker,
@@ -469,7 +470,7 @@ fn qrc20_coin_conf_item(ticker: &str) -> Json {
 
 /// Build asset `UtxoStandardCoin` from ticker and privkey without filling the balance.
 pub fn utxo_coin_from_privkey(ticker: &str, priv_key: Secp256k1Secret) -> (MmArc, UtxoStandardCoin) {
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let conf = json!({"asset":ticker,"txversion":4,"overwintered":1,"txfee":1000,"network":"regtest"});
     let req = json!({"method":"enable"});
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
@@ -598,7 +599,7 @@ pub fn generate_qtum_coin_with_random_privkey(
     });
     let req = json!({"method": "enable"});
     let priv_key = random_secp256k1_secret();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(&ctx, "QTUM", &conf, &params, priv_key)).unwrap();
 
@@ -636,7 +637,7 @@ pub fn generate_segwit_qtum_coin_with_random_privkey(
     });
     let req = json!({"method": "enable"});
     let priv_key = random_secp256k1_secret();
-    let ctx = MmCtxBuilder::new().into_mm_arc();
+    let ctx = mm_ctx_with_custom_db();
     let params = UtxoActivationParams::from_legacy_req(&req).unwrap();
     let coin = block_on(qtum_coin_with_priv_key(&ctx, "QTUM", &conf, &params, priv_key)).unwrap();
 
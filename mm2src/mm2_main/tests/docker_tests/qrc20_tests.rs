@@ -16,9 +16,10 @@ use crypto::Secp256k1Secret;
 use ethereum_types::H160;
 use futures01::Future;
 use http::StatusCode;
-use mm2_core::mm_ctx::{MmArc, MmCtxBuilder};
+use mm2_core::mm_ctx::MmArc;
 use mm2_main::mm2::lp_swap::{dex_fee_amount, max_taker_vol_from_available};
 use mm2_number::BigDecimal;
+use mm2_test_helpers::for_tests::mm_ctx_with_custom_db;
 use mm2_test_helpers::structs::{trade_preimage_error, EnableElectrumResponse, OrderbookResponse, RpcErrorResponse,
                                 RpcSuccessResponse, TransactionDetails};
 use rand6::Rng;
@@ -49,7 +50,7 @@ impl CoinDockerOps for QtumDockerOps {
 
 impl QtumDockerOps {
     pub fn new() -> QtumDockerOps {
-        let ctx = MmCtxBuilder::new().into_mm_arc();
+        let ctx = mm_ctx_with_custom_db();
         let confpath = unsafe { QTUM_CONF_PATH.as_ref().expect("Qtum config is not set yet") };
         let conf = json!({"decimals":8,"network":"regtest","confpath":confpath});
         let req = json!({
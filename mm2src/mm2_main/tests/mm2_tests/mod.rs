@@ -6,6 +6,7 @@ mod lightning_tests;
 mod lp_bot_tests;
 mod mm2_tests_inner;
 mod orderbook_sync_tests;
+mod simulated_swap_tests;
 mod tendermint_ibc_asset_tests;
 mod tendermint_tests;
 mod z_coin_tests;
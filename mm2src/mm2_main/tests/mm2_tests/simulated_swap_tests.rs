@@ -0,0 +1,59 @@
+use common::block_on;
+use common::executor::Timer;
+use mm2_test_helpers::for_tests::{enable_native, my_balance, simulated_coin_conf, start_swaps,
+                                  wait_for_swaps_finish_and_check_status, MarketMakerIt, Mm2TestConf};
+use serde_json::json;
+
+#[test]
+fn test_simulated_swap_dry_run() {
+    let ledger_path = std::env::temp_dir().join(format!("mm2_simulation_swap_{}", common::now_ms()));
+    let ledger_path = ledger_path.display().to_string();
+    let coins = json!([
+        simulated_coin_conf("SIMA", &ledger_path),
+        simulated_coin_conf("SIMB", &ledger_path),
+    ]);
+
+    let bob_conf = Mm2TestConf::seednode("simulated swap bob seed", &coins);
+    let mut mm_bob = MarketMakerIt::start(bob_conf.conf, bob_conf.rpc_password, None).unwrap();
+    let (_bob_dump_log, _bob_dump_dashboard) = mm_bob.mm_dump();
+
+    let alice_conf = Mm2TestConf::light_node("simulated swap alice seed", &coins, &[&mm_bob.ip.to_string()]);
+    let mut mm_alice = MarketMakerIt::start(alice_conf.conf, alice_conf.rpc_password, None).unwrap();
+    let (_alice_dump_log, _alice_dump_dashboard) = mm_alice.mm_dump();
+
+    for mm in [&mm_bob, &mm_alice] {
+        for coin in ["SIMA", "SIMB"] {
+            let enable = block_on(enable_native(mm, coin, &[]));
+            assert_eq!(enable["result"], "success", "!enable {}: {}", coin, enable);
+        }
+    }
+
+    let pairs = &[("SIMA", "SIMB")];
+    let uuids = block_on(start_swaps(&mut mm_bob, &mut mm_alice, pairs, 1., 1., 1.));
+    block_on(wait_for_swaps_finish_and_check_status(
+        &mut mm_bob,
+        &mut mm_alice,
+        &uuids,
+        1.,
+        1.,
+    ));
+    block_on(Timer::sleep(1.));
+
+    // Alice (taker) sent 1 SIMB to Bob and received 1 SIMA, the dex fee and tx fees are paid from the initial balance.
+    let alice_sima = block_on(my_balance(&mm_alice, "SIMA"));
+    assert!(
+        alice_sima.balance > 1000.into(),
+        "Unexpected Alice SIMA balance {:?}",
+        alice_sima
+    );
+    let bob_simb = block_on(my_balance(&mm_bob, "SIMB"));
+    assert!(
+        bob_simb.balance > 1000.into(),
+        "Unexpected Bob SIMB balance {:?}",
+        bob_simb
+    );
+
+    block_on(mm_bob.stop()).unwrap();
+    block_on(mm_alice.stop()).unwrap();
+    std::fs::remove_dir_all(ledger_path).unwrap();
+}
@@ -419,6 +419,21 @@ pub fn morty_conf() -> Json {
     })
 }
 
+/// The stand-in coin config. The nodes sharing the same `ledger_path` see the transactions of each other.
+pub fn simulated_coin_conf(ticker: &str, ledger_path: &str) -> Json {
+    json!({
+        "coin": ticker,
+        "mm2": 1,
+        "required_confirmations": 1,
+        "simulated_balance": "1000",
+        "simulated_tx_fee": "0.00001",
+        "ledger_path": ledger_path,
+        "protocol": {
+            "type": "SIMULATION"
+        }
+    })
+}
+
 pub fn kmd_conf(tx_fee: u64) -> Json {
     json!({
         "coin":"KMD",
@@ -641,6 +641,10 @@ pub struct TransactionDetails {
     pub internal_id: String,
     pub transaction_type: TransactionType,
     pub memo: Option<String>,
+    #[serde(default)]
+    pub psbt: Option<String>,
+    #[serde(default)]
+    pub to_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -994,6 +998,8 @@ pub struct UtxoFeeDetails {
     pub r#type: String,
     pub coin: Option<String>,
     pub amount: BigDecimal,
+    pub fee_rate: Option<BigDecimal>,
+    pub fee_rate_source: Option<Json>,
 }
 
 #[derive(Debug, Deserialize)]